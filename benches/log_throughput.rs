@@ -0,0 +1,31 @@
+// 日誌管道吞吐量：模擬一個高輸出量的進程，測量每秒能處理的行數。
+// 終端輸出會很多，建議重定向：cargo bench --bench log_throughput > /dev/null
+use std::time::Instant;
+
+use janus::logging::handler::LogHandler;
+use janus::logging::LogType;
+
+const LINES: usize = 200_000;
+
+fn run(label: &str, handler: &LogHandler) {
+    let line = "GET /api/v1/items?page=3 200 12ms user=4821 request_id=8f14e45fceea167a5a36dedd4bea2543";
+    let started = Instant::now();
+    for _ in 0..LINES {
+        handler.log("bench", LogType::Stdout, line);
+    }
+    handler.flush();
+    let elapsed = started.elapsed();
+    eprintln!(
+        "{:<16} {:>8} lines in {:>8.3?} ({:>10.0} lines/s)",
+        label,
+        LINES,
+        elapsed,
+        LINES as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    run("console", &LogHandler::new("info"));
+    run("console + file", &LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf())));
+}
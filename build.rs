@@ -0,0 +1,8 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 使用內置的 protoc，構建時不需要在系統中安裝 protobuf 編譯器
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    tonic_prost_build::compile_protos("proto/janus.proto")?;
+    Ok(())
+}
@@ -1,35 +1,124 @@
-use clap::{Command, Arg, ArgMatches};
+use chrono::Local;
+use clap::parser::ValueSource;
+use clap::{Command, Arg, ArgAction, ArgMatches};
+use std::fs;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+use crate::config::{in_project, ProcessConfig, SighupAction};
+use crate::config::lint::{self, Diagnostic, Severity};
+use crate::config::manager::{ConfigManager, DEFAULT_CONFIG_FILE};
+use crate::config::secret::{self, DataKey};
+use crate::control::ControlListener;
+use crate::grpc::proto::{ProcessInfo, StreamLogsRequest};
+use crate::grpc::client::{log_entry, remote_error};
+use crate::grpc::{GrpcServer, RemoteClient};
 use crate::error::{JanusError, Result};
+use crate::logging::cast::CastRecorder;
+use crate::logging::handler::LogHandler;
+use crate::logging::{list_log_files, log_file_stem};
+use crate::logging::retention::{self, RetentionPolicy};
+use crate::logging::search;
+use crate::logging::tail::{LogFilter, LogTailer};
+use crate::msg;
+use crate::process::{clock, handover, kubernetes, notify, registry};
+use crate::process::kubernetes::PodReporter;
 use crate::process::manager::ProcessManager;
 use crate::process::ProcessStatus;
 
+use super::console::{self, LineEditor};
+use super::graph::DependencyGraph;
+use super::progress::{Action, Progress};
 use super::status_reporter::StatusReporter;
+use super::systemd::{ReloadMethod, SystemdUnit};
+
+// How often the foreground supervisor checks on managed processes
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(1);
+
+// How often the supervisor applies the log retention policy
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60);
+
+// How often 'janus ready' polls the supervisor
+const READY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// State selectors accepted by start/stop/restart: flag, matched state, help
+const STATE_SELECTORS: [(&str, ProcessStatus, &str); 3] = [
+    ("failed", ProcessStatus::Failed, "Only processes that exited with an error"),
+    ("stopped", ProcessStatus::Stopped, "Only processes that are stopped"),
+    ("unhealthy", ProcessStatus::GaveUp, "Only processes that exhausted their restart budget"),
+];
 
 pub struct CommandParser {
     manager: Arc<Mutex<ProcessManager>>,
+    // Set by --project: process names on the command line are relative to it
+    project: Option<String>,
 }
 
 impl CommandParser {
     // Constructor
     pub fn new(manager: Arc<Mutex<ProcessManager>>) -> Self {
-        Self { manager }
+        Self { manager, project: None }
     }
     
     // Main command execution
     pub async fn parse_and_execute(&self, args: Vec<String>) -> Result<()> {
         let matches = self.build_cli().get_matches_from(args);
+        let parser = Self {
+            manager: self.manager.clone(),
+            project: matches.get_one::<String>("project").cloned(),
+        };
+        parser.execute(&matches).await
+    }
+    
+    async fn execute(&self, matches: &ArgMatches) -> Result<()> {
+        // These act on the running supervisor; ready connects by itself so it
+        // can keep retrying while the supervisor comes up
+        match matches.subcommand() {
+            Some(("ready", sub_m)) => return self.cmd_ready(matches, sub_m).await,
+            Some(("upgrade", sub_m)) => return self.cmd_upgrade(matches, sub_m).await,
+            Some(("console", _)) => return self.cmd_console(matches).await,
+            Some(("maintenance" | "record" | "timeline" | "events" | "kill" | "reload", _)) => {
+                let (mut client, _) = self.connect_supervisor(matches).await?;
+                return self.execute_remote(&mut client, matches).await;
+            }
+            // Only the running supervisor can start the process again later
+            Some(("stop-one", sub_m)) if sub_m.contains_id("for") && !matches.contains_id("host") => {
+                let (mut client, _) = self.connect_supervisor(matches).await?;
+                return self.remote_stop_one(&mut client, sub_m).await;
+            }
+            _ => {}
+        }
+        
+        // With --host every command runs against a remote janus instead of the local config
+        if let Some(host) = matches.get_one::<String>("host") {
+            let token = matches.get_one::<String>("token").map(String::as_str);
+            let mut client = RemoteClient::connect(host, token).await?;
+            return self.execute_remote(&mut client, matches).await;
+        }
         
         match matches.subcommand() {
-            Some(("start", _)) => self.cmd_start_all().await,
-            Some(("stop", _)) => self.cmd_stop_all().await,
-            Some(("restart", _)) => self.cmd_restart_all().await,
-            Some(("status", _)) => self.cmd_status().await,
+            Some(("start", sub_m)) => self.cmd_start_all(sub_m).await,
+            Some(("stop", sub_m)) => self.cmd_stop_all(sub_m).await,
+            Some(("restart", sub_m)) => self.cmd_restart_all(sub_m).await,
+            Some(("status", sub_m)) => self.cmd_status(sub_m).await,
+            Some(("ps", _)) => self.cmd_ps().await,
             Some(("start-one", sub_m)) => self.cmd_start_one(sub_m).await,
             Some(("stop-one", sub_m)) => self.cmd_stop_one(sub_m).await,
             Some(("restart-one", sub_m)) => self.cmd_restart_one(sub_m).await,
+            Some(("schema", _)) => self.cmd_schema(),
+            Some(("secret", sub_m)) => self.cmd_secret(matches.get_one::<String>("config"), sub_m),
+            Some(("tail", sub_m)) => self.cmd_tail(sub_m).await,
+            Some(("grep", sub_m)) => self.cmd_grep(sub_m).await,
+            Some(("logs", sub_m)) => self.cmd_logs(sub_m).await,
+            Some(("generate", sub_m)) => self.cmd_generate(sub_m).await,
+            Some(("enqueue", sub_m)) => self.cmd_enqueue(sub_m).await,
+            Some(("env", sub_m)) => self.cmd_env(sub_m).await,
+            Some(("validate", sub_m)) => self.cmd_validate(matches.get_one::<String>("config"), sub_m),
+            Some(("graph", sub_m)) => self.cmd_graph(matches.get_one::<String>("config"), sub_m),
             _ => Err(JanusError::Command("Unknown command".to_string())),
         }
     }
@@ -41,13 +130,15 @@ impl CommandParser {
             .author("Janus Team")
             .about("A lightweight process manager for container environments")
             .long_about(self.get_long_about())
-            .arg(self.create_config_arg());
+            .arg(self.create_config_arg())
+            .args(self.create_remote_args())
+            .arg(self.create_project_arg());
             
         self.add_subcommands(app)
             .after_help(self.get_config_file_help())
     }
     
-    fn get_long_about(&self) -> &str {
+    fn get_long_about(&self) -> &'static str {
         "Janus is a lightweight process manager designed specifically for container environments. \
         It provides simple yet powerful commands to manage multiple processes, \
         with features like auto-restart, status monitoring, and structured logging."
@@ -66,14 +157,66 @@ impl CommandParser {
             )
     }
     
+    fn create_project_arg(&self) -> Arg {
+        Arg::new("project")
+            .long("project")
+            .value_name("NAME")
+            .env("JANUS_PROJECT")
+            .help("Only act on the processes of this project")
+            .long_help(
+                "Limit the command to the processes of one [[project]]. \
+                Process names given to the command are relative to the project, \
+                so 'janus --project api restart-one web' restarts api/web."
+            )
+    }
+    
+    fn create_remote_args(&self) -> [Arg; 2] {
+        [
+            Arg::new("host")
+                .long("host")
+                .value_name("URL")
+                .env("JANUS_HOST")
+                .help("Run the command against a remote janus, e.g. tcp://10.0.0.5:7777")
+                .long_help(
+                    "Send the command to the gRPC API of a janus started with grpc_listen on another host.                     status, ps, start, stop, restart, start-one, stop-one, restart-one, kill, tail, ready, maintenance and reload are supported."
+                ),
+            Arg::new("token")
+                .long("token")
+                .value_name("TOKEN")
+                .env("JANUS_TOKEN")
+                .hide_env_values(true)
+                .help("Token for the remote janus (its grpc_token)"),
+        ]
+    }
+    
     fn add_subcommands(&self, app: Command) -> Command {
         app.subcommand(self.create_start_subcommand())
            .subcommand(self.create_stop_subcommand())
            .subcommand(self.create_restart_subcommand())
            .subcommand(self.create_status_subcommand())
+           .subcommand(self.create_ps_subcommand())
            .subcommand(self.create_start_one_subcommand())
            .subcommand(self.create_stop_one_subcommand())
            .subcommand(self.create_restart_one_subcommand())
+           .subcommand(self.create_schema_subcommand())
+           .subcommand(self.create_tail_subcommand())
+           .subcommand(self.create_grep_subcommand())
+           .subcommand(self.create_logs_subcommand())
+           .subcommand(self.create_generate_subcommand())
+           .subcommand(self.create_enqueue_subcommand())
+           .subcommand(self.create_validate_subcommand())
+           .subcommand(self.create_ready_subcommand())
+           .subcommand(self.create_maintenance_subcommand())
+           .subcommand(self.create_graph_subcommand())
+           .subcommand(self.create_upgrade_subcommand())
+           .subcommand(self.create_record_subcommand())
+           .subcommand(self.create_timeline_subcommand())
+           .subcommand(self.create_events_subcommand())
+           .subcommand(self.create_console_subcommand())
+           .subcommand(self.create_secret_subcommand())
+           .subcommand(self.create_kill_subcommand())
+           .subcommand(self.create_env_subcommand())
+           .subcommand(self.create_reload_subcommand())
     }
     
     // Subcommand definitions
@@ -83,8 +226,10 @@ impl CommandParser {
             .long_about(
                 "Start all processes defined in the configuration file. \
                 Processes that are already running will be skipped. \
-                Any startup errors will be reported, but won't prevent other processes from starting."
+                Any startup errors will be reported, but won't prevent other processes from starting. \
+                Janus then stays in the foreground supervising the processes until it receives a termination signal."
             )
+            .args(self.create_state_selector_args())
             .display_order(1)
     }
     
@@ -96,6 +241,7 @@ impl CommandParser {
                 This sends a termination signal to each process and waits for them to exit gracefully. \
                 For containers, this is often the command to use before shutting down."
             )
+            .args(self.create_state_selector_args())
             .display_order(2)
     }
     
@@ -106,7 +252,25 @@ impl CommandParser {
                 "Restart all processes by stopping them if they're running, then starting them again. \
                 This is useful when you need to reload all processes, such as after a configuration change."
             )
+            .args(self.create_state_selector_args())
             .display_order(3)
+            .after_help("Example: janus restart --failed")
+    }
+    
+    fn create_state_selector_args(&self) -> Vec<Arg> {
+        STATE_SELECTORS
+            .iter()
+            .map(|(flag, _, help)| {
+                Arg::new(*flag)
+                    .long(*flag)
+                    .help(*help)
+                    .long_help(format!(
+                        "{}. Selectors can be combined; without any selector all processes are affected.",
+                        help
+                    ))
+                    .action(ArgAction::SetTrue)
+            })
+            .collect()
     }
     
     fn create_status_subcommand(&self) -> Command {
@@ -117,7 +281,29 @@ impl CommandParser {
                 uptime (for running processes), command, arguments, environment variables, \
                 and restart configuration."
             )
+            .arg(
+                Arg::new("tree")
+                    .long("tree")
+                    .help("Show the process tree of each process with per-process memory usage")
+                    .long_help(
+                        "Show every process together with all the children and grandchildren it has forked, \
+                        read from /proc, with the resident memory of each one. \
+                        Useful to spot leaked grandchildren and runaway forks. Only available on Linux."
+                    )
+                    .action(ArgAction::SetTrue)
+            )
             .display_order(4)
+            .after_help("Example: janus status --tree")
+    }
+    
+    fn create_ps_subcommand(&self) -> Command {
+        Command::new("ps")
+            .about("List processes in a compact format")
+            .long_about(
+                "Print one line per process with its name, PID, state, last exit code and command. \
+                Long commands are truncated so the listing fits narrow terminals and is easy to grep."
+            )
+            .display_order(5)
     }
     
     fn create_process_name_arg(&self) -> Arg {
@@ -139,7 +325,7 @@ impl CommandParser {
                 This command is useful when you want to start processes selectively."
             )
             .arg(self.create_process_name_arg())
-            .display_order(5)
+            .display_order(6)
             .after_help("Example: janus start-one web-server")
     }
     
@@ -148,11 +334,27 @@ impl CommandParser {
             .about("Stop a specific process")
             .long_about(
                 "Stop a single process by name. If the process is not running, a message will be displayed. \
-                This command sends a termination signal and waits for the process to exit gracefully."
+                This command sends a termination signal and waits for the process to exit gracefully. \
+                The supervisor does not restart a process stopped this way, nor run it on its schedule, \
+                until it is started again explicitly. With --for the supervisor starts it again by itself \
+                once the duration has passed; this connects like 'janus ready'. \
+                To terminate a hung process without waiting for it, use 'janus kill'."
             )
             .arg(self.create_process_name_arg())
-            .display_order(6)
-            .after_help("Example: janus stop-one database")
+            .arg(
+                Arg::new("cascade")
+                    .long("cascade")
+                    .help("Also stop the processes that require it, dependents first")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("for")
+                    .long("for")
+                    .value_name("DURATION")
+                    .help("Start the process again after this long, e.g. 10m")
+            )
+            .display_order(7)
+            .after_help("Example: janus stop-one worker --for 10m")
     }
     
     fn create_restart_one_subcommand(&self) -> Command {
@@ -163,11 +365,524 @@ impl CommandParser {
                 This is useful for reloading a specific process after configuration changes."
             )
             .arg(self.create_process_name_arg())
-            .display_order(7)
+            .display_order(8)
             .after_help("Example: janus restart-one api-service")
     }
     
-    fn get_config_file_help(&self) -> &str {
+    fn create_schema_subcommand(&self) -> Command {
+        Command::new("schema")
+            .about("Print the JSON Schema of the configuration file")
+            .long_about(
+                "Print a JSON Schema describing the configuration file format. \
+                Point your editor or CI validator at it to get completion and validation for janus.toml. \
+                This command does not need a configuration file."
+            )
+            .display_order(9)
+            .after_help("Example: janus schema > janus.schema.json")
+    }
+    
+    fn create_tail_subcommand(&self) -> Command {
+        Command::new("tail")
+            .about("Follow the logs of one or more processes")
+            .long_about(
+                "Print the last lines of each selected process's log file and keep following new output, \
+                merged in timestamp order. Requires global.log_dir so the supervisor writes the log files. \
+                Name patterns are shell-style globs; --match filters lines by a regular expression."
+            )
+            .arg(
+                Arg::new("names")
+                    .value_name("NAME")
+                    .help("Names of the processes to follow")
+                    .num_args(1..)
+                    .index(1)
+            )
+            .arg(
+                Arg::new("all")
+                    .long("all")
+                    .help("Follow all processes")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("include")
+                    .long("include")
+                    .value_name("PATTERN")
+                    .help("Only follow processes whose name matches the pattern (repeatable)")
+                    .action(ArgAction::Append)
+            )
+            .arg(
+                Arg::new("exclude")
+                    .long("exclude")
+                    .value_name("PATTERN")
+                    .help("Skip processes whose name matches the pattern (repeatable)")
+                    .action(ArgAction::Append)
+            )
+            .arg(
+                Arg::new("match")
+                    .long("match")
+                    .value_name("REGEX")
+                    .help("Only show lines matching the regular expression")
+            )
+            .arg(
+                Arg::new("lines")
+                    .short('n')
+                    .long("lines")
+                    .value_name("N")
+                    .help("Number of existing lines to show before following")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("10")
+            )
+            .display_order(10)
+            .after_help("Example: janus tail --all --exclude 'cron-*' --match 'ERROR|WARN'")
+    }
+    
+    fn create_grep_subcommand(&self) -> Command {
+        Command::new("grep")
+            .about("Search the stored process logs")
+            .long_about(
+                "Search the log files written to global.log_dir for lines matching a regular expression \
+                and print them in timestamp order with the process name. \
+                Use --since to limit the search to recent output."
+            )
+            .arg(
+                Arg::new("pattern")
+                    .value_name("PATTERN")
+                    .help("Regular expression to search for")
+                    .required(true)
+                    .index(1)
+            )
+            .arg(
+                Arg::new("name")
+                    .value_name("NAME")
+                    .help("Only search the logs of this process")
+                    .index(2)
+            )
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION")
+                    .help("Only search lines newer than this, e.g. 30s, 15m, 1h, 2d")
+            )
+            .display_order(11)
+            .after_help("Example: janus grep 'timeout|refused' api --since 1h")
+    }
+    
+    fn create_logs_subcommand(&self) -> Command {
+        Command::new("logs")
+            .about("List or purge the stored process logs")
+            .long_about(
+                "List the log files written to global.log_dir with their sizes. \
+                With --purge, empty the log files of all processes or of the named one. \
+                Files are truncated rather than deleted so a running supervisor keeps writing to them."
+            )
+            .arg(
+                Arg::new("name")
+                    .value_name("NAME")
+                    .help("Only act on the logs of this process")
+                    .index(1)
+            )
+            .arg(
+                Arg::new("purge")
+                    .long("purge")
+                    .help("Empty the selected log files")
+                    .action(ArgAction::SetTrue)
+            )
+            .display_order(12)
+            .after_help("Example: janus logs --purge worker")
+    }
+    
+    fn create_generate_subcommand(&self) -> Command {
+        Command::new("generate")
+            .about("Generate integration files")
+            .long_about(
+                "Generate files for running Janus outside of containers. \
+                The output is printed to stdout so it can be reviewed before installing."
+            )
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("systemd-unit")
+                    .about("Print a systemd service unit for this configuration")
+                    .long_about(
+                        "Print a systemd service unit that runs 'janus start' with this configuration file. \
+                        With global.sd_notify the unit is Type=notify, so systemd waits until the processes are started. \
+                        Reloads run 'janus reload' when global.grpc_listen is set and send SIGHUP otherwise; \
+                        with sighup_action = \"forward\" and no grpc_listen no unit is printed, because SIGHUP \
+                        would reach the processes instead of reloading janus. KillMode=mixed lets Janus stop \
+                        its processes before systemd cleans up the rest of the service."
+                    )
+                    .arg(
+                        Arg::new("exec")
+                            .long("exec")
+                            .value_name("PATH")
+                            .help("Path of the janus binary (default: the running binary)")
+                    )
+                    .arg(
+                        Arg::new("user")
+                            .long("user")
+                            .value_name("USER")
+                            .help("Run the service as this user")
+                    )
+                    .after_help("Example: janus generate systemd-unit > /etc/systemd/system/janus.service")
+            )
+            .display_order(13)
+    }
+    
+    fn create_enqueue_subcommand(&self) -> Command {
+        Command::new("enqueue")
+            .about("Queue a one-off task for the running supervisor")
+            .long_about(
+                "Append a configured task to the queue in global.queue_file. \
+                The foreground supervisor started with 'janus start' runs queued tasks in order, \
+                global.queue_concurrency at a time. Pending tasks are kept in the file across restarts."
+            )
+            .arg(
+                Arg::new("name")
+                    .value_name("NAME")
+                    .help("Name of the task to queue")
+                    .required_unless_present("list")
+                    .index(1)
+            )
+            .arg(
+                Arg::new("list")
+                    .long("list")
+                    .help("List the pending tasks instead")
+                    .action(ArgAction::SetTrue)
+            )
+            .display_order(14)
+            .after_help("Example: janus enqueue db-vacuum")
+    }
+    
+    fn create_validate_subcommand(&self) -> Command {
+        Command::new("validate")
+            .about("Check the configuration file")
+            .long_about(
+                "Load the configuration file and report problems by level. \
+                Errors such as duplicate process names prevent janus from running. \
+                Warnings flag risky settings such as auto_restart without restart_limit, \
+                and info notes describe how the configuration will behave. \
+                Exits with an error when there are errors, or warnings with --strict."
+            )
+            .arg(
+                Arg::new("strict")
+                    .long("strict")
+                    .help("Treat warnings as failures, for CI")
+                    .action(ArgAction::SetTrue)
+            )
+            .display_order(15)
+            .after_help("Example: janus --config janus.toml validate --strict")
+    }
+    
+    fn create_ready_subcommand(&self) -> Command {
+        Command::new("ready")
+            .about("Wait until all critical processes are ready")
+            .long_about(
+                "Poll the running supervisor over its gRPC API until every critical process is ready, \
+                for Kubernetes postStart hooks and startup scripts. A process is ready when it is running \
+                and, with notify, has reported READY=1, or when it has completed. \
+                Processes with critical = false, and every/schedule processes by default, are not waited for. \
+                Connects to --host, or else to global.grpc_listen of the local config, \
+                and keeps retrying while the supervisor starts. \
+                Exits 0 once everything is ready and 1 on timeout or when a process gives up."
+            )
+            .arg(
+                Arg::new("names")
+                    .value_name("NAME")
+                    .help("Only wait for these processes")
+                    .num_args(0..)
+                    .index(1)
+            )
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .value_name("DURATION")
+                    .help("How long to wait, e.g. 120s or 5m")
+                    .default_value("60s")
+            )
+            .display_order(16)
+            .after_help("Example: janus ready --timeout 120s")
+    }
+    
+    fn create_maintenance_subcommand(&self) -> Command {
+        Command::new("maintenance")
+            .about("Suspend auto-restarts and health checks while debugging")
+            .long_about(
+                "Put processes of the running supervisor into maintenance mode, or take them out of it. \
+                In maintenance mode processes that exit are not restarted, restarts already scheduled wait, \
+                and watchdog, memory_limit and restart_on_binary_change do not act. \
+                Maintenance ends by itself after --for. Connects like 'janus ready'."
+            )
+            .arg(
+                Arg::new("state")
+                    .value_name("on|off")
+                    .help("Turn maintenance mode on or off")
+                    .value_parser(["on", "off"])
+                    .required(true)
+                    .index(1)
+            )
+            .arg(
+                Arg::new("names")
+                    .value_name("NAME")
+                    .help("Processes to select; all processes when neither names nor --group are given")
+                    .num_args(0..)
+                    .index(2)
+            )
+            .arg(
+                Arg::new("group")
+                    .long("group")
+                    .value_name("GROUP")
+                    .help("Select the processes with this group")
+                    .conflicts_with("names")
+            )
+            .arg(
+                Arg::new("for")
+                    .long("for")
+                    .value_name("DURATION")
+                    .help("How long maintenance mode lasts, e.g. 30m")
+                    .default_value("1h")
+            )
+            .display_order(17)
+            .after_help("Example: janus maintenance on --group backend --for 30m")
+    }
+    
+    fn create_graph_subcommand(&self) -> Command {
+        Command::new("graph")
+            .about("Show the dependency graph and start order")
+            .long_about(
+                "Print how processes depend on each other through requires, with their wait_for \
+                and wait_for_path dependencies, followed by the order 'janus start' uses. \
+                Circular requires, unknown processes and the processes they keep from starting are reported, \
+                and the command fails when there are any. --format dot prints Graphviz input instead of a tree."
+            )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .help("Output format")
+                    .value_parser(["tree", "dot"])
+                    .default_value("tree")
+            )
+            .display_order(18)
+            .after_help("Example: janus graph --format dot | dot -Tsvg > graph.svg")
+    }
+    
+    fn create_upgrade_subcommand(&self) -> Command {
+        Command::new("upgrade")
+            .about("Replace the running janus binary without restarting processes")
+            .long_about(
+                "Make the running supervisor exec a new janus binary in place. Running processes are handed over \
+                with their pids and output pipes and keep running; the new binary takes them over instead of \
+                starting them again, and processes stopped by a user stay stopped. Without a path the supervisor \
+                execs the file its own binary was started from, so replace that file first; any other path must be \
+                listed in global.upgrade_executables. The supervisor must have global.grpc_token set. \
+                The gRPC and control listeners are unavailable for a moment while the new binary starts. \
+                Linux only. Connects like 'janus ready'."
+            )
+            .arg(
+                Arg::new("exec")
+                    .long("exec")
+                    .value_name("PATH")
+                    .help("Exec the new binary in place, from PATH or the current binary's path")
+                    .num_args(0..=1)
+                    .required(true)
+            )
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .value_name("DURATION")
+                    .help("How long to wait for the new binary to take over")
+                    .default_value("30s")
+            )
+            .display_order(19)
+            .after_help("Example: janus upgrade --exec /usr/local/bin/janus-1.4")
+    }
+    
+    fn create_record_subcommand(&self) -> Command {
+        Command::new("record")
+            .about("Record a process's output with timing for later replay")
+            .long_about(
+                "Record the output of a process of the running supervisor to an asciicast v2 file, \
+                which 'asciinema play' replays with the original timing. Output is recorded line by line; \
+                janus messages such as starts and exits are included in a dim color. \
+                With --restart the process is restarted once recording has begun, to capture its startup. \
+                Recording stops after --for or on Ctrl+C, and the file stays valid when interrupted. \
+                Connects like 'janus ready'."
+            )
+            .arg(self.create_process_name_arg())
+            .arg(
+                Arg::new("to")
+                    .long("to")
+                    .value_name("FILE")
+                    .help("File to write the recording to")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("restart")
+                    .long("restart")
+                    .help("Restart the process after recording has started")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("for")
+                    .long("for")
+                    .value_name("DURATION")
+                    .help("Stop recording after this long, e.g. 5m")
+            )
+            .display_order(20)
+            .after_help("Example: janus record api --to startup.cast --restart --for 2m")
+    }
+    
+    fn create_timeline_subcommand(&self) -> Command {
+        Command::new("timeline")
+            .about("Show when a process started, exited and restarted")
+            .long_about(
+                "Print the recent lifecycle events of a process of the running supervisor, oldest first: \
+                starts, exits with their exit codes, stops, given-up restarts and OOM kills, \
+                to see when and how often it flapped. The supervisor keeps the last 500 events per process, \
+                and with global.timeline_file also across its own restarts. Connects like 'janus ready'."
+            )
+            .arg(self.create_process_name_arg())
+            .arg(
+                Arg::new("since")
+                    .long("since")
+                    .value_name("DURATION")
+                    .help("Only show events from the last DURATION, e.g. 6h")
+            )
+            .display_order(21)
+            .after_help("Example: janus timeline api --since 6h")
+    }
+    
+    fn create_events_subcommand(&self) -> Command {
+        Command::new("events")
+            .about("Follow lifecycle events as they happen")
+            .long_about(
+                "Print the lifecycle events of the running supervisor as they happen: starts, exits, stops, \
+                given-up restarts, OOM kills and paused restarts. Runs until interrupted. Connects like 'janus ready'."
+            )
+            .arg(
+                Arg::new("names")
+                    .value_name("NAME")
+                    .help("Only show events of these processes")
+                    .num_args(0..)
+                    .index(1)
+            )
+            .display_order(22)
+            .after_help("Example: janus events api worker")
+    }
+    
+    fn create_console_subcommand(&self) -> Command {
+        Command::new("console")
+            .about("Drive the running supervisor from an interactive prompt")
+            .long_about(
+                "Open an interactive prompt connected to the running supervisor, for operators in a shell \
+                inside the container. It accepts the janus commands without the binary path and flags, \
+                plus the shorthands start NAME, stop NAME, restart NAME and logs [NAME...]. \
+                Tab completes commands and process names and the arrow keys recall earlier commands. \
+                Ctrl+C stops a command that follows logs or events, Ctrl+D or exit leaves the console. \
+                When stdin is not a terminal, commands are read one per line. Connects like 'janus ready'."
+            )
+            .display_order(23)
+            .after_help("Example: janus console")
+    }
+    
+    fn create_secret_subcommand(&self) -> Command {
+        Command::new("secret")
+            .about("Create sops metadata and encrypted values for the configuration file")
+            .long_about(
+                "Encrypt credentials so the configuration file can be committed safely. \
+                Values use the sops format ENC[AES256_GCM,...] and can replace any string in janus.toml. \
+                The data key is stored in the [sops] table of the file, encrypted with age for each recipient, \
+                so files encrypted by sops (as JSON or YAML, then converted to TOML) load as well. \
+                Each value is bound to the key path it was encrypted for and cannot be moved to another key. \
+                Janus decrypts values when loading the configuration with the age identity in SOPS_AGE_KEY, \
+                in the file named by SOPS_AGE_KEY_FILE, or in sops' default keys.txt."
+            )
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("init")
+                    .about("Print a [sops] table with a new data key encrypted for age recipients")
+                    .arg(
+                        Arg::new("age")
+                            .long("age")
+                            .value_name("RECIPIENT")
+                            .help("age recipient (age1...) that may decrypt the configuration, repeatable")
+                            .action(ArgAction::Append)
+                            .required(true)
+                    )
+                    .after_help("Example: janus secret init --age age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p >> janus.toml")
+            )
+            .subcommand(
+                Command::new("encrypt")
+                    .about("Encrypt a value with the data key of the configuration file")
+                    .arg(
+                        Arg::new("value")
+                            .value_name("VALUE")
+                            .help("Value to encrypt (default: read from stdin)")
+                            .index(1)
+                    )
+                    .arg(
+                        Arg::new("path")
+                            .long("path")
+                            .value_name("KEY_PATH")
+                            .help("Dotted key path of the value in janus.toml, without array indices, e.g. process.env.DB_PASSWORD")
+                            .required(true)
+                    )
+                    .after_help("Example: SOPS_AGE_KEY_FILE=keys.txt janus secret encrypt --path process.env.DB_PASSWORD 'hunter2'")
+            )
+            .display_order(24)
+    }
+    
+    fn create_kill_subcommand(&self) -> Command {
+        Command::new("kill")
+            .about("Terminate a process immediately, without stopping it gracefully")
+            .long_about(
+                "Escape hatch for a process that hangs or ignores stop: the running supervisor sends it \
+                the signal right away, SIGKILL by default, so the process cannot clean up. \
+                Like stop-one, the supervisor does not restart it until it is started again explicitly. \
+                The supervisor logs 'Killed by user with SIGKILL' for the process, separately from a regular stop. \
+                With a signal other than KILL the command returns once the signal is sent. \
+                Connects like 'janus ready'."
+            )
+            .arg(self.create_process_name_arg())
+            .arg(
+                Arg::new("signal")
+                    .long("signal")
+                    .short('s')
+                    .value_name("SIGNAL")
+                    .default_value("KILL")
+                    .help("Signal to send, by name or number, e.g. KILL, TERM or 9; Windows only supports KILL")
+            )
+            .display_order(25)
+            .after_help("Example: janus kill api --signal KILL")
+    }
+    
+    fn create_env_subcommand(&self) -> Command {
+        Command::new("env")
+            .about("Show the environment variables a process gets from the config")
+            .long_about(
+                "Print the environment variables of a process as KEY=value lines sorted by name, with its env_presets expanded. \
+                Later sources override earlier ones: global env, then each preset in the order env_presets lists them, \
+                then the process's own env. Vault references are shown as written; variables inherited from janus, \
+                read from env_dirs at start or passed in port_env are not included."
+            )
+            .arg(self.create_process_name_arg())
+            .display_order(26)
+            .after_help("Example: janus env api")
+    }
+    
+    fn create_reload_subcommand(&self) -> Command {
+        Command::new("reload")
+            .about("Reload the configuration of the running supervisor")
+            .long_about(
+                "Make the running supervisor reload its configuration file, as SIGHUP does with sighup_action = \"reload\", \
+                also when sighup_action is \"forward\". Added processes are started, removed ones are stopped and \
+                changed ones are restarted with the new configuration. When the new file is invalid the supervisor \
+                keeps the current configuration and the command fails with the error. \
+                'generate systemd-unit' uses it as ExecReload. Connects like 'janus ready'."
+            )
+            .display_order(27)
+            .after_help("Example: janus reload")
+    }
+
+    fn get_config_file_help(&self) -> &'static str {
         "CONFIGURATION FILE FORMAT:\n\
         The configuration file uses TOML format with the following structure:\n\n\
         [global]\n\
@@ -194,145 +909,1414 @@ impl CommandParser {
         let manager = self.manager.lock().await;
         manager.get_all_processes()
             .keys()
+            .filter(|name| self.in_scope(name))
             .cloned()
             .collect::<Vec<_>>()
     }
     
+    // Resolve a process name given on the command line against --project
+    fn qualify(&self, name: &str) -> String {
+        match &self.project {
+            Some(project) if !name.contains('/') => format!("{}/{}", project, name),
+            _ => name.to_string(),
+        }
+    }
+    
+    // The process name relative to --project, as it is given on the command line
+    fn unqualify(&self, name: &str) -> String {
+        match &self.project {
+            Some(project) => name.strip_prefix(&format!("{}/", project)).unwrap_or(name).to_string(),
+            None => name.to_string(),
+        }
+    }
+    
+    fn in_scope(&self, name: &str) -> bool {
+        self.project.as_ref().is_none_or(|project| in_project(project, name))
+    }
+    
+    // The named process, or every process of --project; empty means all processes
+    async fn scoped_names(&self, name: Option<&String>) -> Vec<String> {
+        match name {
+            Some(name) => vec![self.qualify(name)],
+            None if self.project.is_some() => self.get_all_process_names().await,
+            None => Vec::new(),
+        }
+    }
+    
+    // Filter the process table by the state selectors given on the command line
+    async fn select_process_names(&self, matches: &ArgMatches) -> Vec<String> {
+        let states: Vec<&ProcessStatus> = STATE_SELECTORS
+            .iter()
+            .filter(|(flag, _, _)| matches.get_flag(flag))
+            .map(|(_, status, _)| status)
+            .collect();
+        
+        if states.is_empty() {
+            return self.get_all_process_names().await;
+        }
+        
+        let manager = self.manager.lock().await;
+        manager.get_all_processes()
+            .values()
+            .filter(|p| states.contains(&&p.status) && self.in_scope(&p.name))
+            .map(|p| p.name.clone())
+            .collect()
+    }
+    
+    fn selection_label(matches: &ArgMatches) -> String {
+        if STATE_SELECTORS.iter().any(|(flag, _, _)| matches.get_flag(flag)) {
+            msg!("selection.selected")
+        } else {
+            msg!("selection.all")
+        }
+    }
+    
     async fn is_process_running(&self, name: &str) -> bool {
         let manager = self.manager.lock().await;
         manager.get_all_processes()
             .get(name)
-            .map(|p| p.status == ProcessStatus::Running && p.process.is_some())
+            .map(|p| p.status == ProcessStatus::Running && p.pid().is_some())
             .unwrap_or(false)
     }
     
+    async fn running_dependents(&self, name: &str) -> Vec<String> {
+        let manager = self.manager.lock().await;
+        manager
+            .dependents(name)
+            .into_iter()
+            .filter(|dependent| {
+                manager
+                    .get_process(dependent)
+                    .is_some_and(|process| process.status == ProcessStatus::Running)
+            })
+            .collect()
+    }
+    
     async fn start_single_process(&self, name: &str) -> Result<()> {
-        let mut manager = self.manager.lock().await;
-        manager.start_process(name).await
+        ProcessManager::start_when_ready(&self.manager, name).await
     }
     
     async fn stop_single_process(&self, name: &str) -> Result<()> {
         let mut manager = self.manager.lock().await;
-        if let Some(process) = manager.get_process_mut(name) {
-            if let Some(child) = &mut process.process {
-                match child.kill().await {
-                    Ok(_) => {
-                        process.status = ProcessStatus::Stopped;
-                        process.process = None;
-                        Ok(())
-                    }
-                    Err(e) => Err(JanusError::Process(format!("Failed to kill process: {}", e)))
+        manager.stop_process_manually(name, None).await
+    }
+    
+    // Stop one process at a time, releasing the lock in between so signals are still handled
+    async fn stop_processes(&self, process_names: &[String]) -> Result<()> {
+        let running: Vec<&String> = {
+            let manager = self.manager.lock().await;
+            process_names
+                .iter()
+                .filter(|name| manager.get_process(name).is_some_and(|process| process.status == ProcessStatus::Running))
+                .collect()
+        };
+        
+        let mut progress = Progress::new(Action::Stop, running.len());
+        for name in running {
+            let stop = async { self.manager.lock().await.stop_if_running(name).await.unwrap_or(Ok(())) };
+            progress.track(name, stop).await;
+        }
+        progress.finish();
+        
+        Ok(())
+    }
+    
+    async fn start_processes(&self, process_names: &[String]) -> Result<()> {
+        let process_names = self.manager.lock().await.start_order(process_names);
+        
+        let mut progress = Progress::new(Action::Start, process_names.len());
+        for name in &process_names {
+            progress.track(name, self.start_single_process(name)).await;
+        }
+        progress.finish();
+        
+        Ok(())
+    }
+    
+    // Timers with run_on_start = false wait for their first interval instead
+    async fn supervisor_start_names(&self, process_names: Vec<String>) -> Vec<String> {
+        let manager = self.manager.lock().await;
+        process_names
+            .into_iter()
+            .filter(|name| {
+                manager
+                    .get_process(name)
+                    .is_none_or(|process| process.starts_with_supervisor())
+            })
+            .collect()
+    }
+    
+    // Processes outside their not_before/not_after window are started by the supervisor once it opens
+    async fn defer_outside_window(&self, process_names: Vec<String>) -> Vec<String> {
+        let mut manager = self.manager.lock().await;
+        process_names
+            .into_iter()
+            .filter(|name| {
+                if !manager.defer_start(name) {
+                    return true;
                 }
-            } else {
-                Ok(()) // Process is not running
+                if let Some(window) = manager.get_process(name).and_then(|process| process.start_window.as_ref()) {
+                    println!("{}", msg!("process.start_deferred", name = name, window = window));
+                }
+                false
+            })
+            .collect()
+    }
+    
+    // Command implementation methods
+    async fn cmd_start_all(&self, matches: &ArgMatches) -> Result<()> {
+        let label = Self::selection_label(matches);
+        println!("{}", msg!("processes.starting", selection = label));
+        
+        let process_names = self.select_process_names(matches).await;
+        let process_names = self.supervisor_start_names(process_names).await;
+        // After 'janus upgrade --exec' the handed over processes are still running
+        let resumed = self.resume_handover().await;
+        let process_names: Vec<String> = process_names.into_iter().filter(|name| !resumed.contains(name)).collect();
+        let process_names = self.defer_outside_window(process_names).await;
+        self.start_processes(&process_names).await?;
+        
+        println!("{}", capitalize(&msg!("processes.started", selection = label)));
+        self.check_local_timezone().await;
+        self.start_registry().await;
+        self.start_kubernetes_status().await;
+        let control = self.start_control_listener().await?;
+        let grpc = self.start_grpc_server().await?;
+        self.print_banner(process_names.len() + resumed.len(), control, grpc).await;
+        self.notify_systemd_ready().await;
+        self.supervise().await
+    }
+    
+    // Take over the processes handed over by the janus binary this one replaced, if any
+    async fn resume_handover(&self) -> Vec<String> {
+        match handover::take() {
+            Some(Ok(state)) => {
+                let resumed = self.manager.lock().await.resume_handover(state);
+                println!("{}", msg!("upgrade.resumed", count = resumed.len()));
+                resumed
             }
-        } else {
-            Err(JanusError::Process(format!("Process not found: {}", name)))
+            Some(Err(e)) => {
+                eprintln!("{}", msg!("upgrade.handover_failed", error = e));
+                Vec::new()
+            }
+            None => Vec::new(),
         }
     }
     
-    async fn stop_all_processes(&self) -> Result<()> {
-        let mut manager = self.manager.lock().await;
-        manager.stop_all().await
+    // Warn when local time silently falls back to UTC because the time zone data is missing
+    async fn check_local_timezone(&self) {
+        let manager = self.manager.lock().await;
+        let uses_local_time = manager.config_snapshot().is_some_and(|config| {
+            config.get_process_configs().iter().any(|process| {
+                process.timezone.is_none()
+                    && (process.schedule.is_some()
+                        || process.scheduled_restart.is_some()
+                        || process.not_before.is_some()
+                        || process.not_after.is_some())
+            })
+        });
+        if let Some(problem) = clock::local_timezone_problem(uses_local_time) {
+            eprintln!("{}", msg!("clock.timezone", problem = problem));
+        }
+    }
+    
+    // Keep global.registry_file in step with the published status
+    async fn start_registry(&self) {
+        let manager = self.manager.lock().await;
+        let path = manager
+            .config_snapshot()
+            .and_then(|config| config.get_global_config().registry_file.clone());
+        if let Some(path) = path {
+            tokio::spawn(registry::watch(
+                PathBuf::from(path),
+                manager.subscribe_status(),
+                manager.get_log_handler().clone(),
+            ));
+        }
     }
     
-    async fn start_all_processes(&self) -> Result<()> {
-        let process_names = self.get_all_process_names().await;
+    // Keep the pod condition or annotation of global.kubernetes_status in step with the process health
+    async fn start_kubernetes_status(&self) {
+        let manager = self.manager.lock().await;
+        let config = manager
+            .config_snapshot()
+            .and_then(|config| config.get_global_config().kubernetes_status.clone());
+        let Some(config) = config else {
+            return;
+        };
         
-        for name in process_names {
-            let result = self.start_single_process(&name).await;
+        match PodReporter::from_env(&config) {
+            Ok(reporter) => {
+                println!("{}", msg!("kubernetes.reporting", pod = reporter.pod()));
+                tokio::spawn(kubernetes::watch(
+                    reporter,
+                    manager.subscribe_status(),
+                    manager.get_log_handler().clone(),
+                ));
+            }
+            Err(e) => eprintln!("{}", msg!("kubernetes.unavailable", error = e)),
+        }
+    }
+    
+    // Serve remote status and restart requests while the supervisor runs
+    async fn start_control_listener(&self) -> Result<Option<SocketAddr>> {
+        let config = self
+            .manager
+            .lock()
+            .await
+            .config_snapshot()
+            .and_then(|config| config.get_global_config().control.clone());
+        let Some(config) = config else {
+            return Ok(None);
+        };
+        
+        let listener = ControlListener::bind(&config).await?;
+        let address = listener.local_addr()?;
+        println!("{}", msg!("control.listening", address = address));
+        tokio::spawn(listener.run(self.manager.clone()));
+        Ok(Some(address))
+    }
+    
+    // Serve the gRPC management API, and its read-only counterpart for monitoring sidecars, while the supervisor runs.
+    // Returns the bound management and read-only addresses
+    async fn start_grpc_server(&self) -> Result<(Option<SocketAddr>, Option<SocketAddr>)> {
+        let Some(config) = self.manager.lock().await.config_snapshot() else {
+            return Ok((None, None));
+        };
+        let global = config.get_global_config();
+        let mut readonly = None;
+        if let Some(address) = &global.grpc_readonly_listen {
+            let server = GrpcServer::bind(address)
+                .await?
+                .with_token(global.grpc_readonly_token.clone())
+                .read_only();
+            let address = server.local_addr()?;
+            println!("{}", msg!("grpc.readonly_listening", address = address));
+            tokio::spawn(server.run(self.manager.clone()));
+            readonly = Some(address);
+        }
+        let Some(address) = &global.grpc_listen else {
+            return Ok((None, readonly));
+        };
+        
+        let server = GrpcServer::bind(address).await?.with_token(global.grpc_token.clone());
+        let address = server.local_addr()?;
+        println!("{}", msg!("grpc.listening", address = address));
+        tokio::spawn(server.run(self.manager.clone()));
+        Ok((Some(address), readonly))
+    }
+    
+    // With global.startup_banner, summarize which configuration this janus runs, for container logs
+    async fn print_banner(&self, started: usize, control: Option<SocketAddr>, grpc: (Option<SocketAddr>, Option<SocketAddr>)) {
+        let manager = self.manager.lock().await;
+        let Some(config) = manager.config_snapshot() else {
+            return;
+        };
+        let global = config.get_global_config();
+        if global.startup_banner != Some(true) {
+            return;
+        }
+        
+        let path = fs::canonicalize(config.get_config_path())
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| config.get_config_path().to_string());
+        println!("{}", msg!("banner.title", version = env!("CARGO_PKG_VERSION"), pid = std::process::id()));
+        println!("{}", msg!("banner.config", path = path));
+        println!("{}", msg!("banner.hash", hash = config.get_config_hash()));
+        println!(
+            "{}",
+            msg!("banner.processes", count = config.get_process_configs().len(), started = started)
+        );
+        let projects: Vec<&str> = config.get_projects().iter().map(|project| project.name.as_str()).collect();
+        if !projects.is_empty() {
+            println!("{}", msg!("banner.projects", projects = projects.join(", ")));
+        }
+        if let Some(address) = control {
+            println!("{}", msg!("banner.control", address = address));
+        }
+        let (grpc, grpc_readonly) = grpc;
+        if let Some(address) = grpc {
+            println!("{}", msg!("banner.grpc", address = address));
+        }
+        if let Some(address) = grpc_readonly {
+            println!("{}", msg!("banner.grpc_readonly", address = address));
+        }
+        if let Some(path) = &global.registry_file {
+            println!("{}", msg!("banner.registry", path = path));
+        }
+    }
+    
+    // With global.sd_notify the unit is Type=notify: systemd considers janus started
+    // once the processes are started and the listeners are up
+    async fn notify_systemd_ready(&self) {
+        let enabled = self
+            .manager
+            .lock()
+            .await
+            .config_snapshot()
+            .is_some_and(|config| config.get_global_config().sd_notify == Some(true));
+        if enabled {
+            let message = format!("READY=1\nMAINPID={}", std::process::id());
+            if let Err(e) = notify::notify_systemd(&message) {
+                eprintln!("{}", msg!("systemd.notify_failed", error = e));
+            }
+        }
+    }
+    
+    // Keep running in the foreground, reaping exits and applying restart policies
+    async fn supervise(&self) -> Result<()> {
+        let mut interval = tokio::time::interval(SUPERVISE_INTERVAL);
+        let mut last_retention: Option<Instant> = None;
+        
+        loop {
+            interval.tick().await;
             
-            if let Err(e) = result {
-                eprintln!("Failed to restart {}: {}", name, e);
+            let mut manager = self.manager.lock().await;
+            manager.check_processes().await?;
+            
+            if let Some(executable) = manager.take_upgrade() {
+                Self::exec_upgrade(&manager, &executable);
+            }
+            
+            if last_retention.is_none_or(|at| at.elapsed() >= RETENTION_INTERVAL) {
+                last_retention = Some(Instant::now());
+                Self::apply_log_retention(&manager);
             }
         }
+    }
+    
+    // Hand the running processes over to a new janus binary; returns only when exec failed
+    fn exec_upgrade(manager: &ProcessManager, executable: &Path) {
+        let state = manager.handover();
+        let running = state.processes.iter().filter(|process| process.pid.is_some()).count();
+        println!("{}", msg!("upgrade.exec", executable = executable.display(), count = running));
+        manager.get_log_handler().flush();
         
+        let error = handover::exec(executable, state);
+        eprintln!("{}", msg!("upgrade.failed", error = error));
+    }
+    
+    fn apply_log_retention(manager: &ProcessManager) {
+        let Some(config_manager) = manager.get_config_manager() else {
+            return;
+        };
+        let policy = RetentionPolicy::from_global(config_manager.get_global_config());
+        
+        if let Err(e) = manager.get_log_handler().apply_retention(&policy) {
+            eprintln!("{}", msg!("logs.retention_failed", error = e));
+        }
+    }
+    
+    async fn cmd_stop_all(&self, matches: &ArgMatches) -> Result<()> {
+        let label = Self::selection_label(matches);
+        println!("{}", msg!("processes.stopping", selection = label));
+        
+        let process_names = self.select_process_names(matches).await;
+        self.stop_processes(&process_names).await?;
+        
+        println!("{}", capitalize(&msg!("processes.stopped", selection = label)));
         Ok(())
     }
     
-    // Command implementation methods
-    async fn cmd_start_all(&self) -> Result<()> {
-        println!("Starting all processes...");
+    async fn cmd_restart_all(&self, matches: &ArgMatches) -> Result<()> {
+        let label = Self::selection_label(matches);
+        println!("{}", msg!("processes.restarting", selection = label));
         
-        let process_names = self.get_all_process_names().await;
+        // Resolve the selection once, before stopping changes the states
+        let process_names = self.select_process_names(matches).await;
+        self.stop_processes(&process_names).await?;
+        self.start_processes(&process_names).await?;
         
-        for name in process_names {
-            let result = self.start_single_process(&name).await;
-            
-            if let Err(e) = result {
-                eprintln!("Failed to start {}: {}", name, e);
+        println!("{}", capitalize(&msg!("processes.restarted", selection = label)));
+        Ok(())
+    }
+    
+    async fn cmd_status(&self, matches: &ArgMatches) -> Result<()> {
+        let manager = self.manager.lock().await;
+        let reporter = StatusReporter::new(&manager).with_project(self.project.as_deref());
+        if matches.get_flag("tree") {
+            reporter.report_tree()?;
+        } else {
+            reporter.report_all()?;
+        }
+        Ok(())
+    }
+    
+    async fn cmd_ps(&self) -> Result<()> {
+        let manager = self.manager.lock().await;
+        let reporter = StatusReporter::new(&manager).with_project(self.project.as_deref());
+        reporter.report_compact()?;
+        Ok(())
+    }
+    
+    fn cmd_schema(&self) -> Result<()> {
+        println!("{}", ConfigManager::json_schema()?);
+        Ok(())
+    }
+    
+    fn cmd_secret(&self, config_path: Option<&String>, matches: &ArgMatches) -> Result<()> {
+        match matches.subcommand() {
+            Some(("init", sub_m)) => {
+                let recipients: Vec<String> = sub_m.get_many::<String>("age").into_iter().flatten().cloned().collect();
+                let metadata = DataKey::generate()?.to_metadata(&recipients)?;
+                let mut table = toml::Table::new();
+                table.insert(secret::METADATA_KEY.to_string(), metadata);
+                let text = toml::to_string(&table).map_err(|e| JanusError::Command(e.to_string()))?;
+                print!("{}", text);
+            }
+            Some(("encrypt", sub_m)) => {
+                let config_path = config_path.map(String::as_str).unwrap_or(DEFAULT_CONFIG_FILE);
+                let content = fs::read_to_string(config_path)
+                    .map_err(|e| JanusError::Command(format!("Failed to read {}: {}", config_path, e)))?;
+                let config: toml::Value = toml::from_str(&content)
+                    .map_err(|e| JanusError::Command(format!("Failed to parse {}: {}", config_path, e)))?;
+                let metadata = config.get(secret::METADATA_KEY).ok_or_else(|| {
+                    JanusError::Command(format!("{} has no [sops] table, add one with 'janus secret init'", config_path))
+                })?;
+                let identities = secret::load_identities()?;
+                if identities.is_empty() {
+                    return Err(JanusError::Command(format!(
+                        "Set {} or {} to an age identity to encrypt values",
+                        secret::AGE_KEY_ENV,
+                        secret::AGE_KEY_FILE_ENV
+                    )));
+                }
+                let key = DataKey::from_metadata(metadata, &identities)?;
+                let value = match sub_m.get_one::<String>("value") {
+                    Some(value) => value.clone(),
+                    None => {
+                        let mut value = String::new();
+                        std::io::stdin().read_to_string(&mut value)?;
+                        value.trim_end_matches(['\r', '\n']).to_string()
+                    }
+                };
+                let path = sub_m.get_one::<String>("path").unwrap();
+                println!("{}", key.encrypt(&value, &secret::sops_path(path))?);
+            }
+            _ => return Err(JanusError::Command("Unknown secret command".to_string())),
+        }
+        Ok(())
+    }
+    
+    async fn tail_filter(&self, matches: &ArgMatches) -> Result<LogFilter> {
+        let mut names = self.tail_names(matches)?;
+        if names.is_empty() {
+            names = self.scoped_names(None).await;
+        }
+        
+        let filter = LogFilter::new(
+            names,
+            &tail_patterns(matches, "include"),
+            &tail_patterns(matches, "exclude"),
+            matches.get_one::<String>("match").map(String::as_str),
+        )?;
+        Ok(filter)
+    }
+    
+    // The names given to tail; empty with --all
+    fn tail_names(&self, matches: &ArgMatches) -> Result<Vec<String>> {
+        let names: Vec<String> = matches
+            .get_many::<String>("names")
+            .map(|values| values.map(|name| self.qualify(name)).collect())
+            .unwrap_or_default();
+        if names.is_empty() && !matches.get_flag("all") {
+            return Err(JanusError::Command("Specify process names or --all".to_string()));
+        }
+        Ok(names)
+    }
+    
+    async fn cmd_tail(&self, matches: &ArgMatches) -> Result<()> {
+        let filter = self.tail_filter(matches).await?;
+        let (log_dir, log_handler) = self.log_dir_and_handler("tail").await?;
+        
+        LogTailer::new(log_dir, filter, log_handler)
+            .follow(*matches.get_one::<usize>("lines").unwrap())
+            .await
+    }
+    
+    async fn cmd_grep(&self, matches: &ArgMatches) -> Result<()> {
+        let pattern = matches.get_one::<String>("pattern").unwrap();
+        let names = self.scoped_names(matches.get_one::<String>("name")).await;
+        let filter = LogFilter::new(names, &[], &[], Some(pattern))?;
+        
+        let since = match matches.get_one::<String>("since") {
+            Some(value) => {
+                let duration = chrono::Duration::from_std(search::parse_duration(value)?)
+                    .map_err(|_| JanusError::Command(format!("Invalid duration '{}'", value)))?;
+                Some(Local::now() - duration)
             }
+            None => None,
+        };
+        
+        let (log_dir, log_handler) = self.log_dir_and_handler("grep").await?;
+        for entry in search::search(&log_dir, &filter, since)? {
+            print!("{}", log_handler.format_log_entry(&entry));
         }
         
-        println!("All processes started");
         Ok(())
     }
     
-    async fn cmd_stop_all(&self) -> Result<()> {
-        println!("Stopping all processes...");
+    async fn cmd_logs(&self, matches: &ArgMatches) -> Result<()> {
+        let name = matches.get_one::<String>("name").map(|name| self.qualify(name));
+        let names: Vec<String> = self
+            .scoped_names(matches.get_one::<String>("name"))
+            .await
+            .iter()
+            .map(|name| log_file_stem(name))
+            .collect();
+        let (log_dir, _) = self.log_dir_and_handler("logs").await?;
         
-        {
-            let mut manager = self.manager.lock().await;
-            manager.stop_all().await?;
+        if matches.get_flag("purge") {
+            let purged = if self.project.is_some() && name.is_none() {
+                let mut purged = Vec::new();
+                for name in &names {
+                    purged.extend(retention::purge(&log_dir, Some(name))?);
+                }
+                purged
+            } else {
+                retention::purge(&log_dir, name.as_deref())?
+            };
+            match name {
+                Some(name) if purged.is_empty() => println!("{}", msg!("logs.not_found", name = name)),
+                _ => println!("{}", msg!("logs.purged", count = purged.len())),
+            }
+            return Ok(());
+        }
+        
+        for (file_name, path) in list_log_files(&log_dir) {
+            if !names.is_empty() && !names.contains(&file_name) {
+                continue;
+            }
+            let size = fs::metadata(&path)?.len();
+            println!("{:<24} {:>10}  {}", file_name, format_size(size), path.display());
         }
         
-        println!("All processes stopped");
         Ok(())
     }
     
-    async fn cmd_restart_all(&self) -> Result<()> {
-        println!("Restarting all processes...");
+    async fn cmd_generate(&self, matches: &ArgMatches) -> Result<()> {
+        let Some(("systemd-unit", sub_m)) = matches.subcommand() else {
+            return Err(JanusError::Command("Unknown generate target".to_string()));
+        };
         
-        self.stop_all_processes().await?;
-        self.start_all_processes().await?;
+        let exec_path = match sub_m.get_one::<String>("exec") {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_exe()?,
+        };
+        let config = self
+            .manager
+            .lock()
+            .await
+            .config_snapshot()
+            .ok_or_else(|| JanusError::Config("No configuration loaded".to_string()))?;
+        let config_path = fs::canonicalize(config.get_config_path())?;
+        let global = config.get_global_config();
+        
+        // With sighup_action = "forward" SIGHUP goes to the processes, so only 'janus reload' reloads janus
+        let reload = if global.grpc_listen.is_some() {
+            ReloadMethod::Command
+        } else if global.sighup_action.unwrap_or_default() == SighupAction::Reload {
+            ReloadMethod::Sighup
+        } else {
+            return Err(JanusError::Config(
+                "With sighup_action = \"forward\" 'systemctl reload' would send SIGHUP to the processes instead of \
+                reloading janus; set global.grpc_listen so the unit reloads with 'janus reload', \
+                or use sighup_action = \"reload\"".to_string(),
+            ));
+        };
+        let unit = SystemdUnit {
+            exec_path: &exec_path,
+            config_path: &config_path,
+            user: sub_m.get_one::<String>("user").map(String::as_str),
+            notify: global.sd_notify == Some(true),
+            reload,
+        };
+        print!("{}", unit.render());
         
-        println!("All processes restarted");
         Ok(())
     }
     
-    async fn cmd_status(&self) -> Result<()> {
+    fn cmd_validate(&self, config_path: Option<&String>, matches: &ArgMatches) -> Result<()> {
+        let config_path = config_path.map(String::as_str).unwrap_or(DEFAULT_CONFIG_FILE);
+        
+        // Hard errors stop loading at the first problem, so there is at most one
+        let diagnostics = match ConfigManager::new(config_path) {
+            Ok(config_manager) => lint::lint(&config_manager),
+            Err(e) => vec![Diagnostic {
+                severity: Severity::Error,
+                process: None,
+                message: e.to_string(),
+            }],
+        };
+        
+        let mut sorted = diagnostics.clone();
+        sorted.sort_by_key(|diagnostic| diagnostic.severity);
+        for diagnostic in &sorted {
+            match &diagnostic.process {
+                Some(name) => println!(
+                    "{}",
+                    msg!("validate.process_diagnostic", severity = diagnostic.severity, name = name, message = diagnostic.message)
+                ),
+                None => println!(
+                    "{}",
+                    msg!("validate.diagnostic", severity = diagnostic.severity, message = diagnostic.message)
+                ),
+            }
+        }
+        
+        let count = |severity| diagnostics.iter().filter(|diagnostic| diagnostic.severity == severity).count();
+        let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+        println!(
+            "{}",
+            msg!("validate.summary", errors = errors, warnings = warnings, infos = count(Severity::Info))
+        );
+        
+        if errors > 0 || (warnings > 0 && matches.get_flag("strict")) {
+            return Err(JanusError::Config(format!("{} failed validation", config_path)));
+        }
+        Ok(())
+    }
+    
+    fn cmd_graph(&self, config_path: Option<&String>, matches: &ArgMatches) -> Result<()> {
+        let config_path = config_path.map(String::as_str).unwrap_or(DEFAULT_CONFIG_FILE);
+        let config_manager = ConfigManager::load(config_path)?;
+        let processes: Vec<ProcessConfig> = config_manager
+            .get_process_configs()
+            .iter()
+            .filter(|process| self.in_scope(&process.name))
+            .cloned()
+            .collect();
+        let graph = DependencyGraph::new(&processes);
+        
+        if matches.get_one::<String>("format").map(String::as_str) == Some("dot") {
+            print!("{}", graph.render_dot());
+        } else {
+            print!("{}", graph.render_tree());
+        }
+        
+        let problems = graph.problems();
+        for problem in &problems {
+            eprintln!("{}", msg!("validate.diagnostic", severity = Severity::Error, message = problem));
+        }
+        if !problems.is_empty() {
+            return Err(JanusError::Config(format!("{} has dependency problems", config_path)));
+        }
+        Ok(())
+    }
+    
+    async fn cmd_env(&self, matches: &ArgMatches) -> Result<()> {
         let manager = self.manager.lock().await;
-        let reporter = StatusReporter::new(&manager);
-        reporter.report_all()?;
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        let process = manager
+            .get_process(name)
+            .ok_or_else(|| JanusError::Process(format!("Process not found: {}", name)))?;
+        
+        let mut env: Vec<_> = process.env.iter().collect();
+        env.sort();
+        for (key, value) in env {
+            println!("{}={}", key, value);
+        }
         Ok(())
     }
     
+    async fn cmd_enqueue(&self, matches: &ArgMatches) -> Result<()> {
+        let manager = self.manager.lock().await;
+        let queue = manager.task_queue().ok_or_else(|| {
+            JanusError::Config("enqueue requires global.queue_file to be set".to_string())
+        })?;
+        
+        if matches.get_flag("list") {
+            for (index, name) in queue.pending().await?.iter().enumerate() {
+                println!("{:>3}  {}", index + 1, name);
+            }
+            return Ok(());
+        }
+        
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        let process = manager
+            .get_process(name)
+            .ok_or_else(|| JanusError::Process(format!("Process not found: {}", name)))?;
+        if process.auto_restart {
+            return Err(JanusError::Command(format!(
+                "{} has auto_restart enabled and cannot be queued as a one-off task",
+                name
+            )));
+        }
+        
+        let position = queue.push(name).await?;
+        println!("{}", msg!("queue.enqueued", name = name, position = position));
+        Ok(())
+    }
+    
+    // Logs are only searchable when the supervisor writes them to global.log_dir
+    async fn log_dir_and_handler(&self, command: &str) -> Result<(PathBuf, LogHandler)> {
+        let (config, log_handler) = {
+            let manager = self.manager.lock().await;
+            (manager.config_snapshot(), manager.get_log_handler().clone())
+        };
+        let log_dir = config
+            .and_then(|config_manager| config_manager.get_global_config().log_dir.clone())
+            .ok_or_else(|| {
+                JanusError::Config(format!("{} requires global.log_dir to be set", command))
+            })?;
+        
+        Ok((PathBuf::from(log_dir), log_handler))
+    }
+    
     async fn cmd_start_one(&self, matches: &ArgMatches) -> Result<()> {
-        let name = matches.get_one::<String>("name").unwrap();
-        println!("Starting process: {}", name);
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        println!("{}", msg!("process.starting", name = name));
         
         self.start_single_process(name).await?;
         
-        println!("Process started: {}", name);
+        println!("{}", msg!("process.started", name = name));
         Ok(())
     }
     
     async fn cmd_stop_one(&self, matches: &ArgMatches) -> Result<()> {
-        let name = matches.get_one::<String>("name").unwrap();
-        println!("Stopping process: {}", name);
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        
+        // Stopping a process silently breaks the running processes that require it
+        let dependents = self.running_dependents(name).await;
+        if !dependents.is_empty() {
+            if matches.get_flag("cascade") {
+                for dependent in &dependents {
+                    println!("{}", msg!("process.stopping", name = dependent));
+                    self.stop_single_process(dependent).await?;
+                    println!("{}", msg!("process.stopped", name = dependent));
+                }
+            } else {
+                eprintln!(
+                    "{}",
+                    msg!("process.dependents_running", name = name, dependents = dependents.join(", "))
+                );
+            }
+        }
+        
+        println!("{}", msg!("process.stopping", name = name));
         
         let process_exists_and_running = self.is_process_running(name).await;
         
         if process_exists_and_running {
             self.stop_single_process(name).await?;
-            println!("Process stopped: {}", name);
+            println!("{}", msg!("process.stopped", name = name));
         } else {
-            println!("Process is not running: {}", name);
+            println!("{}", msg!("process.not_running", name = name));
         }
         
         Ok(())
     }
     
     async fn cmd_restart_one(&self, matches: &ArgMatches) -> Result<()> {
-        let name = matches.get_one::<String>("name").unwrap();
-        println!("Restarting process: {}", name);
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        println!("{}", msg!("process.restarting", name = name));
         
-        {
-            let mut manager = self.manager.lock().await;
-            manager.restart_process(name).await?;
+        ProcessManager::restart_when_ready(&self.manager, name).await?;
+        
+        println!("{}", msg!("process.restarted", name = name));
+        Ok(())
+    }
+    
+    // Connect to the running supervisor, explaining the likely cause when nothing answers
+    async fn connect_supervisor(&self, matches: &ArgMatches) -> Result<(RemoteClient, String)> {
+        let (host, token) = self.supervisor_target(matches).await?;
+        match RemoteClient::connect(&host, token.as_deref()).await {
+            Ok(client) => Ok((client, host)),
+            Err(JanusError::Command(error)) if error.starts_with("Failed to connect") => Err(JanusError::Command(format!(
+                "{}\n{}",
+                error,
+                msg!("supervisor.unreachable", host = host)
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+    
+    // Address and token of the running supervisor: --host, or grpc_listen of the local config
+    async fn supervisor_target(&self, matches: &ArgMatches) -> Result<(String, Option<String>)> {
+        let token = matches.get_one::<String>("token").cloned();
+        if let Some(host) = matches.get_one::<String>("host") {
+            return Ok((host.clone(), token));
+        }
+        
+        let config = self.manager.lock().await.config_snapshot();
+        let global = config.as_ref().map(|config| config.get_global_config());
+        let Some(listen) = global.and_then(|global| global.grpc_listen.as_deref()) else {
+            return Err(JanusError::Command(
+                "This command needs global.grpc_listen in the config or --host: it talks to the supervisor started by janus start through its gRPC API".to_string(),
+            ));
+        };
+        // A supervisor listening on all interfaces is reached through loopback
+        let host = match listen.parse::<std::net::SocketAddr>() {
+            Ok(mut address) if address.ip().is_unspecified() => {
+                address.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+                address.to_string()
+            }
+            _ => listen.to_string(),
+        };
+        Ok((host, token.or_else(|| global.and_then(|global| global.grpc_token.clone()))))
+    }
+    
+    // The processes 'janus ready' waits for: the named ones, or every critical one in scope.
+    // Completed one-shot processes are done and only count when named explicitly
+    fn ready_selection(&self, processes: Vec<ProcessInfo>, names: &[String]) -> Result<Vec<ProcessInfo>> {
+        let completed = format!("{:?}", ProcessStatus::Completed);
+        let processes: Vec<ProcessInfo> = processes
+            .into_iter()
+            .filter(|process| self.in_scope(&process.name))
+            .filter(|process| {
+                if names.is_empty() {
+                    process.critical && process.status != completed
+                } else {
+                    names.contains(&process.name)
+                }
+            })
+            .collect();
+        
+        if let Some(missing) = names.iter().find(|name| !processes.iter().any(|process| &process.name == *name)) {
+            return Err(JanusError::Command(format!("Process not found: {}", missing)));
+        }
+        // A process that exhausted its restarts will not come back by waiting longer
+        let gave_up = format!("{:?}", ProcessStatus::GaveUp);
+        if let Some(process) = processes.iter().find(|process| process.status == gave_up) {
+            return Err(JanusError::Process(format!("{} gave up and will not become ready", process.name)));
         }
+        Ok(processes)
+    }
+    
+    async fn cmd_ready(&self, global: &ArgMatches, matches: &ArgMatches) -> Result<()> {
+        let timeout = search::parse_duration(matches.get_one::<String>("timeout").unwrap())?;
+        let names: Vec<String> = matches
+            .get_many::<String>("names")
+            .map(|names| names.map(|name| self.qualify(name)).collect())
+            .unwrap_or_default();
+        let (host, token) = self.supervisor_target(global).await?;
+        let deadline = Instant::now() + timeout;
         
-        println!("Process restarted: {}", name);
+        let mut client = None;
+        let mut last_error = None;
+        let mut reported: Vec<String> = Vec::new();
+        let mut announced = false;
+        loop {
+            if client.is_none() {
+                match RemoteClient::connect(&host, token.as_deref()).await {
+                    Ok(connected) => client = Some(connected),
+                    Err(e) => last_error = Some(e.to_string()),
+                }
+            }
+            
+            let mut pending = None;
+            if let Some(connected) = client.as_mut() {
+                match connected.list().await {
+                    Ok(processes) => {
+                        let processes = self.ready_selection(processes, &names)?;
+                        if !announced {
+                            println!("{}", msg!("ready.waiting", count = processes.len(), timeout = matches.get_one::<String>("timeout").unwrap()));
+                            announced = true;
+                        }
+                        for process in processes.iter().filter(|process| is_ready(process)) {
+                            if !reported.contains(&process.name) {
+                                println!("{}", msg!("ready.process_ready", name = process.name));
+                                reported.push(process.name.clone());
+                            }
+                        }
+                        
+                        let not_ready: Vec<String> = processes
+                            .iter()
+                            .filter(|process| !is_ready(process))
+                            .map(|process| format!("{} ({})", process.name, readiness_label(process)))
+                            .collect();
+                        if not_ready.is_empty() {
+                            println!("{}", msg!("ready.all_ready", count = processes.len()));
+                            return Ok(());
+                        }
+                        pending = Some(not_ready);
+                    }
+                    // The connection broke, reconnect on the next poll
+                    Err(e) => {
+                        last_error = Some(e.to_string());
+                        client = None;
+                    }
+                }
+            }
+            
+            if Instant::now() >= deadline {
+                let reason = match pending {
+                    Some(not_ready) => format!("not ready: {}", not_ready.join(", ")),
+                    None => last_error.unwrap_or_else(|| format!("could not reach {}", host)),
+                };
+                return Err(JanusError::Process(format!(
+                    "Timed out after {}s waiting for critical processes, {}",
+                    timeout.as_secs(),
+                    reason
+                )));
+            }
+            tokio::time::sleep(READY_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+        }
+    }
+    
+    async fn remote_maintenance(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let duration = match matches.get_one::<String>("state").map(String::as_str) {
+            Some("on") => {
+                let duration = search::parse_duration(matches.get_one::<String>("for").unwrap())?;
+                if duration.is_zero() {
+                    return Err(JanusError::Command("--for must be longer than zero".to_string()));
+                }
+                Some(duration)
+            }
+            _ => None,
+        };
+        let mut names: Vec<String> = matches
+            .get_many::<String>("names")
+            .map(|names| names.map(|name| self.qualify(name)).collect())
+            .unwrap_or_default();
+        let group = matches.get_one::<String>("group").cloned();
+        
+        // With --project and no selection only that project's processes are affected
+        if names.is_empty() && group.is_none() && self.project.is_some() {
+            names = client
+                .list()
+                .await?
+                .into_iter()
+                .filter(|process| self.in_scope(&process.name))
+                .map(|process| process.name)
+                .collect();
+            if names.is_empty() {
+                return Ok(());
+            }
+        }
+        
+        for process in client.set_maintenance(names, group, duration).await? {
+            if self.in_scope(&process.name) {
+                match process.maintenance_secs {
+                    Some(secs) => println!("{}", msg!("maintenance.on", name = process.name, seconds = secs)),
+                    None => println!("{}", msg!("maintenance.off", name = process.name)),
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    async fn cmd_upgrade(&self, global: &ArgMatches, matches: &ArgMatches) -> Result<()> {
+        let timeout = search::parse_duration(matches.get_one::<String>("timeout").unwrap())?;
+        let (host, token) = self.supervisor_target(global).await?;
+        let mut client = RemoteClient::connect(&host, token.as_deref()).await?;
+        let upgrade = client.upgrade(matches.get_one::<String>("exec").cloned()).await?;
+        println!("{}", msg!("upgrade.requested", executable = upgrade.executable));
+        
+        // The supervisor execs on its next check; the listener is gone until the new binary binds it again
+        let deadline = Instant::now() + timeout;
+        let mut client = None;
+        loop {
+            tokio::time::sleep(READY_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+            if client.is_none() {
+                client = RemoteClient::connect(&host, token.as_deref()).await.ok();
+            }
+            if let Some(connected) = client.as_mut() {
+                match connected.generation().await {
+                    Ok(generation) if generation >= upgrade.generation => {
+                        let running = connected.list().await?.iter().filter(|process| process.pid.is_some()).count();
+                        println!("{}", msg!("upgrade.done", count = running));
+                        return Ok(());
+                    }
+                    Ok(_) => {}
+                    Err(_) => client = None,
+                }
+            }
+            
+            if Instant::now() >= deadline {
+                return Err(JanusError::Process(format!(
+                    "The supervisor did not come back upgraded within {}s, check its output",
+                    timeout.as_secs()
+                )));
+            }
+        }
+    }
+    
+    async fn remote_record(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let name = self.qualify(matches.get_one::<String>("name").unwrap());
+        let path = PathBuf::from(matches.get_one::<String>("to").unwrap());
+        let deadline = match matches.get_one::<String>("for") {
+            Some(duration) => Some(Instant::now() + search::parse_duration(duration)?),
+            None => None,
+        };
+        self.remote_process(client, &name).await?;
+        
+        // Subscribe before restarting so the first lines of the new run are not missed
+        let request = StreamLogsRequest { names: vec![name.clone()], lines: Some(0), ..Default::default() };
+        let mut lines = client.stream_logs(request).await?;
+        let mut recorder = CastRecorder::create(&path, &format!("janus record {}", name))?;
+        println!("{}", msg!("record.started", name = name, path = path.display()));
+        if matches.get_flag("restart") {
+            client.stop(&name).await?;
+            client.start(&name).await?;
+        }
+        
+        loop {
+            let line = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline.into(), lines.message()).await {
+                    Ok(line) => line,
+                    Err(_) => break,
+                },
+                None => lines.message().await,
+            };
+            // The stream ends when the supervisor shuts down
+            let Some(line) = line.map_err(remote_error)? else {
+                break;
+            };
+            recorder.record(&log_entry(line))?;
+        }
+        
+        println!("{}", msg!("record.saved", count = recorder.events(), path = path.display()));
+        Ok(())
+    }
+    
+    async fn cmd_console(&self, global: &ArgMatches) -> Result<()> {
+        let (mut client, host) = self.connect_supervisor(global).await?;
+        let mut editor = LineEditor::new();
+        if editor.is_interactive() {
+            println!("{}", msg!("console.welcome", host = host));
+        }
+        
+        loop {
+            // Refresh the names for completion before each prompt; processes come and go with reloads
+            let names: Vec<String> = match client.list().await {
+                Ok(processes) => processes
+                    .into_iter()
+                    .filter(|process| self.in_scope(&process.name))
+                    .map(|process| self.unqualify(&process.name))
+                    .collect(),
+                Err(_) => Vec::new(),
+            };
+            let Some(line) = editor.read_line("janus> ", &names)? else {
+                break;
+            };
+            let words = console::split_words(&line);
+            match words.first().map(String::as_str) {
+                None => continue,
+                Some("exit" | "quit") => break,
+                Some("help") => {
+                    println!("{}", msg!("console.help"));
+                    continue;
+                }
+                _ => {}
+            }
+            
+            let args = std::iter::once("janus".to_string()).chain(console::command_args(&words));
+            let matches = match self.build_cli().try_get_matches_from(args) {
+                Ok(matches) => matches,
+                Err(e) => {
+                    let _ = e.print();
+                    continue;
+                }
+            };
+            // Commands that need the local machine, such as 'status --tree', report that they are unavailable
+            let result = match matches.subcommand_name() {
+                Some("console") => Err(JanusError::Command("Already in the console".to_string())),
+                _ => editor.until_interrupted(self.execute_remote(&mut client, &matches)).await?.unwrap_or(Ok(())),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+            }
+        }
+        Ok(())
+    }
+    
+    // Remote counterparts of the commands above, backed by the gRPC API
+    async fn execute_remote(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        match matches.subcommand() {
+            Some(("start", sub_m)) => self.remote_start_all(client, sub_m).await,
+            Some(("stop", sub_m)) => self.remote_stop_all(client, sub_m).await,
+            Some(("restart", sub_m)) => {
+                self.remote_stop_all(client, sub_m).await?;
+                self.remote_start_all(client, sub_m).await
+            }
+            // The tree is read from /proc of the machine janus runs on, which the API does not expose
+            Some(("status", sub_m)) if sub_m.get_flag("tree") => {
+                Err(JanusError::Command("status --tree is not available with --host".to_string()))
+            }
+            Some(("status", _)) => StatusReporter::report_remote(&client.list().await?),
+            Some(("ps", _)) => StatusReporter::report_remote_compact(&client.list().await?),
+            Some(("start-one", sub_m)) => self.remote_start_one(client, sub_m).await,
+            Some(("stop-one", sub_m)) => self.remote_stop_one(client, sub_m).await,
+            Some(("restart-one", sub_m)) => self.remote_restart_one(client, sub_m).await,
+            Some(("tail", sub_m)) => self.remote_tail(client, sub_m).await,
+            Some(("maintenance", sub_m)) => self.remote_maintenance(client, sub_m).await,
+            Some(("record", sub_m)) => self.remote_record(client, sub_m).await,
+            Some(("timeline", sub_m)) => self.remote_timeline(client, sub_m).await,
+            Some(("events", sub_m)) => self.remote_events(client, sub_m).await,
+            Some(("kill", sub_m)) => self.remote_kill(client, sub_m).await,
+            Some(("reload", _)) => {
+                let version = client.reload().await?;
+                println!("{}", msg!("reload.done", version = version));
+                Ok(())
+            }
+            Some(("schema", _)) => self.cmd_schema(),
+            Some(("secret", sub_m)) => self.cmd_secret(matches.get_one::<String>("config"), sub_m),
+            Some((name, _)) => Err(JanusError::Command(format!("{} is not available with --host", name))),
+            None => Err(JanusError::Command("Unknown command".to_string())),
+        }
+    }
+    
+    async fn remote_select(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<Vec<String>> {
+        let states: Vec<String> = STATE_SELECTORS
+            .iter()
+            .filter(|(flag, _, _)| matches.get_flag(flag))
+            .map(|(_, status, _)| format!("{:?}", status))
+            .collect();
+        
+        Ok(client
+            .list()
+            .await?
+            .into_iter()
+            .filter(|process| states.is_empty() || states.contains(&process.status))
+            .filter(|process| self.in_scope(&process.name))
+            .map(|process| process.name)
+            .collect())
+    }
+    
+    async fn remote_process(&self, client: &mut RemoteClient, name: &str) -> Result<ProcessInfo> {
+        client
+            .list()
+            .await?
+            .into_iter()
+            .find(|process| process.name == name)
+            .ok_or_else(|| JanusError::Command(format!("Process not found: {}", name)))
+    }
+    
+    async fn remote_start_all(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let label = Self::selection_label(matches);
+        println!("{}", msg!("processes.starting", selection = label));
+        
+        let names = self.remote_select(client, matches).await?;
+        let mut progress = Progress::new(Action::Start, names.len());
+        for name in &names {
+            progress.track(name, async { client.start(name).await.map(|_| ()) }).await;
+        }
+        progress.finish();
+        
+        println!("{}", capitalize(&msg!("processes.started", selection = label)));
+        Ok(())
+    }
+    
+    async fn remote_stop_all(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let label = Self::selection_label(matches);
+        println!("{}", msg!("processes.stopping", selection = label));
+        
+        let names = self.remote_select(client, matches).await?;
+        let mut progress = Progress::new(Action::Stop, names.len());
+        let mut failed = 0;
+        for name in &names {
+            if !progress.track(name, async { client.stop(name).await.map(|_| ()) }).await {
+                failed += 1;
+            }
+        }
+        progress.finish();
+        if failed > 0 {
+            return Err(JanusError::Command(format!("{} process(es) could not be stopped", failed)));
+        }
+        
+        println!("{}", capitalize(&msg!("processes.stopped", selection = label)));
+        Ok(())
+    }
+    
+    async fn remote_start_one(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        println!("{}", msg!("process.starting", name = name));
+        client.start(name).await?;
+        println!("{}", msg!("process.started", name = name));
+        Ok(())
+    }
+    
+    async fn remote_stop_one(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        if matches.get_flag("cascade") {
+            return Err(JanusError::Command("--cascade is not available with --host".to_string()));
+        }
+        let resume_after = matches
+            .get_one::<String>("for")
+            .map(|duration| search::parse_duration(duration))
+            .transpose()?;
+        println!("{}", msg!("process.stopping", name = name));
+        
+        if self.remote_process(client, name).await?.status == format!("{:?}", ProcessStatus::Running) {
+            client.stop_for(name, resume_after).await?;
+            match matches.get_one::<String>("for") {
+                Some(duration) => println!("{}", msg!("process.stopped_for", name = name, duration = duration)),
+                None => println!("{}", msg!("process.stopped", name = name)),
+            }
+        } else {
+            println!("{}", msg!("process.not_running", name = name));
+        }
         Ok(())
     }
+    
+    async fn remote_kill(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        let signal = matches.get_one::<String>("signal").unwrap();
+        println!("{}", msg!("process.killing", name = name, signal = signal));
+        client.kill(name, signal).await?;
+        println!("{}", msg!("process.killed", name = name));
+        Ok(())
+    }
+    
+    async fn remote_restart_one(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let name = &self.qualify(matches.get_one::<String>("name").unwrap());
+        println!("{}", msg!("process.restarting", name = name));
+        client.stop(name).await?;
+        client.start(name).await?;
+        println!("{}", msg!("process.restarted", name = name));
+        Ok(())
+    }
+    
+    async fn remote_timeline(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let name = self.qualify(matches.get_one::<String>("name").unwrap());
+        let since = matches.get_one::<String>("since").map(|since| search::parse_duration(since)).transpose()?;
+        let entries = client.timeline(&name, since).await?;
+        if entries.is_empty() {
+            println!("{}", msg!("timeline.empty", name = name));
+            return Ok(());
+        }
+        
+        println!("{:<19}  {:<14}  {:>7}  {:>4}  DETAIL", "TIME", "EVENT", "PID", "EXIT");
+        for entry in &entries {
+            let time = chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+                .map(|time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|_| entry.timestamp.clone());
+            println!(
+                "{:<19}  {:<14}  {:>7}  {:>4}  {}",
+                time,
+                entry.event,
+                entry.pid.map_or("-".to_string(), |pid| pid.to_string()),
+                entry.exit_code.map_or("-".to_string(), |code| code.to_string()),
+                entry.detail,
+            );
+        }
+        
+        // Exits that were not clean, including deaths by signal, which carry no exit code
+        let starts = entries.iter().filter(|entry| entry.event == "started").count();
+        let failures = entries
+            .iter()
+            .filter(|entry| entry.event == "start_failed" || (entry.event == "exited" && entry.exit_code != Some(0)))
+            .count();
+        println!();
+        println!("{}", msg!("timeline.summary", events = entries.len(), starts = starts, failures = failures));
+        Ok(())
+    }
+    
+    async fn remote_events(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let names: Vec<String> = matches
+            .get_many::<String>("names")
+            .map(|names| names.map(|name| self.qualify(name)).collect())
+            .unwrap_or_default();
+        let mut events = client.stream_events(names).await?;
+        
+        while let Some(event) = events.message().await.map_err(remote_error)? {
+            if !self.in_scope(&event.process) {
+                continue;
+            }
+            let time = chrono::DateTime::parse_from_rfc3339(&event.timestamp)
+                .map(|time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|_| event.timestamp.clone());
+            let kind = event.kind().as_str_name().trim_start_matches("EVENT_KIND_").to_ascii_lowercase();
+            let mut line = format!("[{}] {} {}", time, event.process, kind);
+            if let Some(pid) = event.pid {
+                line.push_str(&format!(" pid={}", pid));
+            }
+            if let Some(code) = event.exit_code {
+                line.push_str(&format!(" exit_code={}", code));
+            }
+            if !event.detail.is_empty() {
+                line.push_str(&format!(": {}", event.detail));
+            }
+            println!("{}", line);
+        }
+        Ok(())
+    }
+    
+    // The supervisor applies the selection and filters, so only matching lines cross the network.
+    // Without an explicit --lines it sends the default backlog only when it writes log files.
+    async fn remote_tail(&self, client: &mut RemoteClient, matches: &ArgMatches) -> Result<()> {
+        let mut names = self.tail_names(matches)?;
+        if names.is_empty() && self.project.is_some() {
+            names = client
+                .list()
+                .await?
+                .into_iter()
+                .map(|process| process.name)
+                .filter(|name| self.in_scope(name))
+                .collect();
+        }
+        let lines = match matches.value_source("lines") {
+            Some(ValueSource::CommandLine) => Some(*matches.get_one::<usize>("lines").unwrap() as u32),
+            _ => None,
+        };
+        let request = StreamLogsRequest {
+            names,
+            include: tail_patterns(matches, "include"),
+            exclude: tail_patterns(matches, "exclude"),
+            r#match: matches.get_one::<String>("match").cloned().unwrap_or_default(),
+            lines,
+        };
+        
+        let log_handler = LogHandler::new("info");
+        let mut lines = client.stream_logs(request).await?;
+        while let Some(line) = lines.message().await.map_err(remote_error)? {
+            print!("{}", log_handler.format_log_entry(&log_entry(line)));
+        }
+        Ok(())
+    }
+}
+
+// Running processes are ready unless they use notify and have not sent READY=1 yet
+fn is_ready(process: &ProcessInfo) -> bool {
+    if process.status == format!("{:?}", ProcessStatus::Running) {
+        process.ready != Some(false)
+    } else {
+        process.status == format!("{:?}", ProcessStatus::Completed)
+    }
+}
+
+fn readiness_label(process: &ProcessInfo) -> String {
+    if process.status == format!("{:?}", ProcessStatus::Running) {
+        match process.starting_secs {
+            Some(secs) => format!(
+                "waiting for READY=1, {}",
+                StatusReporter::starting_label(secs, process.expected_start_secs)
+            ),
+            None => "waiting for READY=1".to_string(),
+        }
+    } else {
+        process.status.to_lowercase()
+    }
+}
+
+fn tail_patterns(matches: &ArgMatches, id: &str) -> Vec<String> {
+    matches
+        .get_many::<String>(id)
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
 }
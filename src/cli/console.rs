@@ -0,0 +1,365 @@
+// janus console 的行編輯：在終端中支持光標移動、歷史記錄和 Tab 補全命令與進程名，
+// 並讓 Ctrl+C 只中斷正在跟隨日誌或事件的命令而不退出控制台。標準輸入不是終端時 (例如管道)
+// 按行讀取命令，不顯示提示符
+use std::future::Future;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+// 控制台的命令，用於補全第一個詞
+pub const COMMANDS: &[&str] = &[
+    "status", "ps", "start", "stop", "restart", "kill", "logs", "events", "timeline", "maintenance", "help", "exit", "quit",
+];
+
+pub struct LineEditor {
+    history: Vec<String>,
+    interactive: bool,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            interactive: cfg!(unix) && io::stdin().is_terminal() && io::stdout().is_terminal(),
+        }
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    // 讀取一行命令，輸入結束 (空行上按 Ctrl+D) 時返回 None；names 為可補全的進程名
+    pub fn read_line(&mut self, prompt: &str, names: &[String]) -> io::Result<Option<String>> {
+        if !self.interactive {
+            let mut line = String::new();
+            if io::stdin().lock().read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            return Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()));
+        }
+
+        #[cfg(unix)]
+        {
+            let line = self.edit(prompt, names)?;
+            if let Some(line) = line.as_ref().filter(|line| !line.trim().is_empty()) {
+                if self.history.last() != Some(line) {
+                    self.history.push(line.clone());
+                }
+            }
+            Ok(line)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (prompt, names);
+            unreachable!("the line editor is only interactive on unix")
+        }
+    }
+
+    // 運行 task 直到完成，或在終端中按下 Ctrl+C；被中斷時返回 None
+    pub async fn until_interrupted<F: Future>(&self, task: F) -> io::Result<Option<F::Output>> {
+        if !self.interactive {
+            return Ok(Some(task.await));
+        }
+
+        #[cfg(unix)]
+        {
+            unix::until_interrupted(task).await
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(Some(task.await))
+        }
+    }
+
+    #[cfg(unix)]
+    fn edit(&mut self, prompt: &str, names: &[String]) -> io::Result<Option<String>> {
+        let _raw = unix::RawMode::enable()?;
+        let mut stdout = io::stdout();
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // 瀏覽歷史時的位置，等於 history.len() 表示正在編輯的新行
+        let mut position = self.history.len();
+        redraw(&mut stdout, prompt, &line, cursor)?;
+
+        loop {
+            let Some(byte) = unix::read_byte()? else {
+                return Ok(None);
+            };
+            match byte {
+                b'\r' | b'\n' => {
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    return Ok(Some(line.into_iter().collect()));
+                }
+                // Ctrl+C 放棄當前行
+                0x03 => {
+                    write!(stdout, "^C\r\n")?;
+                    line.clear();
+                    cursor = 0;
+                    position = self.history.len();
+                }
+                // Ctrl+D 在空行上結束輸入
+                0x04 if line.is_empty() => {
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    return Ok(None);
+                }
+                0x7f | 0x08 if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                b'\t' => {
+                    let before: String = line[..cursor].iter().collect();
+                    let (completion, choices) = complete(&before, names);
+                    for c in completion.chars() {
+                        line.insert(cursor, c);
+                        cursor += 1;
+                    }
+                    if completion.is_empty() && choices.len() > 1 {
+                        write!(stdout, "\r\n{}\r\n", choices.join("  "))?;
+                    }
+                }
+                // Ctrl+A、Ctrl+E 移到行首、行尾，Ctrl+U 清空整行
+                0x01 => cursor = 0,
+                0x05 => cursor = line.len(),
+                0x15 => {
+                    line.clear();
+                    cursor = 0;
+                }
+                0x1b => match (unix::read_byte()?, unix::read_byte()?) {
+                    (Some(b'['), Some(b'A')) if position > 0 => {
+                        position -= 1;
+                        line = self.history[position].chars().collect();
+                        cursor = line.len();
+                    }
+                    (Some(b'['), Some(b'B')) if position < self.history.len() => {
+                        position += 1;
+                        line = self.history.get(position).map(|entry| entry.chars().collect()).unwrap_or_default();
+                        cursor = line.len();
+                    }
+                    (Some(b'['), Some(b'C')) if cursor < line.len() => cursor += 1,
+                    (Some(b'['), Some(b'D')) if cursor > 0 => cursor -= 1,
+                    _ => {}
+                },
+                byte if byte >= 0x20 => {
+                    if let Some(c) = unix::read_char(byte)? {
+                        line.insert(cursor, c);
+                        cursor += 1;
+                    }
+                }
+                _ => {}
+            }
+            redraw(&mut stdout, prompt, &line, cursor)?;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn redraw(stdout: &mut io::Stdout, prompt: &str, line: &[char], cursor: usize) -> io::Result<()> {
+    let text: String = line.iter().collect();
+    write!(stdout, "\r\x1b[2K{}{}", prompt, text)?;
+    if cursor < line.len() {
+        write!(stdout, "\x1b[{}D", line.len() - cursor)?;
+    }
+    stdout.flush()
+}
+
+// 補全光標前的最後一個詞：第一個詞補全命令，之後的詞補全進程名。
+// 返回要插入的文本和所有候選項；只有一個候選項時補全整個詞並加上空格，否則補全到共同前綴
+pub fn complete(before: &str, names: &[String]) -> (String, Vec<String>) {
+    let start = before.rfind(' ').map_or(0, |index| index + 1);
+    let word = &before[start..];
+    let mut choices: Vec<String> = if start == 0 {
+        COMMANDS.iter().map(|command| command.to_string()).collect()
+    } else {
+        names.to_vec()
+    };
+    choices.retain(|choice| choice.starts_with(word));
+    choices.sort();
+    choices.dedup();
+
+    let completion = match choices.as_slice() {
+        [] => String::new(),
+        [choice] => format!("{} ", &choice[word.len()..]),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.chars().count(), |common, choice| {
+                first.chars().zip(choice.chars()).take(common).take_while(|(a, b)| a == b).count()
+            });
+            first.chars().take(common).skip(word.chars().count()).collect()
+        }
+    };
+    (completion, choices)
+}
+
+// 按空白分詞，單引號或雙引號中的空白保留在同一個詞中
+pub fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+// 將控制台的簡寫轉換為 janus 的子命令：start、stop、restart 後跟一個進程名時作用於單個進程，
+// logs 跟隨進程輸出 (不帶進程名時跟隨所有進程)；其他命令按 janus 的命令行原樣解析
+pub fn command_args(words: &[String]) -> Vec<String> {
+    let Some((command, rest)) = words.split_first() else {
+        return Vec::new();
+    };
+    let mut args = match (command.as_str(), rest) {
+        ("start" | "stop" | "restart", [name]) if !name.starts_with('-') => vec![format!("{}-one", command)],
+        ("logs", []) => vec!["tail".to_string(), "--all".to_string()],
+        ("logs", _) => vec!["tail".to_string()],
+        _ => vec![command.clone()],
+    };
+    args.extend(rest.iter().cloned());
+    args
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::future::Future;
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    // 檢查停止標誌的間隔
+    const POLL_INTERVAL_MS: libc::c_int = 100;
+
+    // 關閉回顯和行緩衝，Ctrl+C 作為普通字節讀取；保留輸出處理，\n 仍按 \r\n 輸出。離開作用域時恢復
+    pub struct RawMode {
+        original: libc::termios,
+    }
+
+    impl RawMode {
+        pub fn enable() -> io::Result<Self> {
+            // SAFETY: termios 是普通的 C 結構體，由 tcgetattr 填充
+            let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+            // SAFETY: 傳入有效的文件描述符和指向 termios 的指針
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let original = termios;
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            termios.c_iflag &= !(libc::IXON | libc::ICRNL);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            // SAFETY: 同上
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { original })
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // SAFETY: 恢復 enable 時讀取的設置
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+        }
+    }
+
+    // 直接從文件描述符讀取，不經過 Stdin 的緩衝；輸入結束時返回 None
+    pub fn read_byte() -> io::Result<Option<u8>> {
+        let mut byte = 0u8;
+        loop {
+            // SAFETY: 向一個字節的緩衝區讀取最多一個字節
+            let read = unsafe { libc::read(libc::STDIN_FILENO, (&mut byte as *mut u8).cast(), 1) };
+            match read {
+                1 => return Ok(Some(byte)),
+                0 => return Ok(None),
+                _ => {
+                    let error = io::Error::last_os_error();
+                    if error.kind() != io::ErrorKind::Interrupted {
+                        return Err(error);
+                    }
+                }
+            }
+        }
+    }
+
+    // 讀取以 first 開頭的 UTF-8 字符的其餘字節
+    pub fn read_char(first: u8) -> io::Result<Option<char>> {
+        let length = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Ok(None),
+        };
+        let mut bytes = vec![first];
+        for _ in 1..length {
+            match read_byte()? {
+                Some(byte) => bytes.push(byte),
+                None => return Ok(None),
+            }
+        }
+        Ok(std::str::from_utf8(&bytes).ok().and_then(|text| text.chars().next()))
+    }
+
+    // 在原始模式下運行 task，同時在阻塞線程中等待 Ctrl+C；task 完成後停止等待並恢復終端
+    pub async fn until_interrupted<F: Future>(task: F) -> io::Result<Option<F::Output>> {
+        let _raw = RawMode::enable()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut watcher = tokio::task::spawn_blocking({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let mut pollfd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+                    // SAFETY: 傳入一個有效的 pollfd
+                    if unsafe { libc::poll(&mut pollfd, 1, POLL_INTERVAL_MS) } > 0 {
+                        match read_byte() {
+                            Ok(Some(0x03)) => return true,
+                            Ok(Some(_)) => {}
+                            Ok(None) | Err(_) => return false,
+                        }
+                    }
+                }
+                false
+            }
+        });
+
+        tokio::pin!(task);
+        let output = tokio::select! {
+            output = &mut task => Some(output),
+            interrupted = &mut watcher => match interrupted {
+                Ok(true) => None,
+                // 標準輸入已關閉，繼續等待 task
+                _ => Some(task.await),
+            },
+        };
+        stop.store(true, Ordering::Relaxed);
+        // 等待線程退出，避免它讀走下一條命令的輸入
+        if !watcher.is_finished() {
+            let _ = watcher.await;
+        }
+        Ok(output)
+    }
+}
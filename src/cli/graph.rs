@@ -0,0 +1,241 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::config::ProcessConfig;
+use crate::process::dependency;
+
+// 進程之間的 requires 依賴圖，以及 wait_for 和 wait_for_path 這些 janus 之外的依賴
+pub struct DependencyGraph {
+    requires: BTreeMap<String, Vec<String>>,
+    external: BTreeMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    pub fn new(processes: &[ProcessConfig]) -> Self {
+        let requires = processes
+            .iter()
+            .map(|process| (process.name.clone(), process.requires.clone().unwrap_or_default()))
+            .collect();
+        let external = processes
+            .iter()
+            .map(|process| {
+                let endpoints = process.wait_for.iter().flatten().cloned();
+                let paths = process.wait_for_path.iter().flatten().map(|path| format!("path {}", path));
+                (process.name.clone(), endpoints.chain(paths).collect())
+            })
+            .collect();
+        Self { requires, external }
+    }
+
+    // name 依賴 required 這條邊是否在環上
+    fn in_cycle(&self, name: &str, required: &str) -> bool {
+        self.reachable(required).contains(name)
+    }
+
+    // 從 name 出發沿 requires 能到達的所有已知進程，不包括 name 本身（除非存在環）
+    fn reachable(&self, name: &str) -> BTreeSet<String> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<&String> = self.requires.get(name).into_iter().flatten().collect();
+        while let Some(next) = stack.pop() {
+            if !self.requires.contains_key(next) || !seen.insert(next.clone()) {
+                continue;
+            }
+            stack.extend(self.requires.get(next).into_iter().flatten());
+        }
+        seen
+    }
+
+    // 每個環只報告一次，從名稱最小的進程開始
+    fn cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = Vec::new();
+        let mut reported = BTreeSet::new();
+        for name in self.requires.keys() {
+            if reported.contains(name) || !self.reachable(name).contains(name) {
+                continue;
+            }
+            let mut cycle = vec![name.clone()];
+            let mut current = name.clone();
+            loop {
+                let next = self.requires[&current]
+                    .iter()
+                    .find(|required| self.requires.contains_key(*required) && self.reachable(required).contains(name))
+                    .cloned();
+                let Some(next) = next else {
+                    break;
+                };
+                cycle.push(next.clone());
+                if &next == name {
+                    break;
+                }
+                current = next;
+            }
+            reported.extend(cycle.iter().cloned());
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
+    fn unknown(&self) -> Vec<(String, String)> {
+        self.requires
+            .iter()
+            .flat_map(|(name, requires)| {
+                requires
+                    .iter()
+                    .filter(|required| !self.requires.contains_key(*required))
+                    .map(move |required| (name.clone(), required.clone()))
+            })
+            .collect()
+    }
+
+    // 依賴環或未知進程使這些進程無法按 requires 啟動
+    fn blocked(&self) -> Vec<String> {
+        let broken: BTreeSet<String> = self
+            .unknown()
+            .into_iter()
+            .map(|(name, _)| name)
+            .chain(self.cycles().into_iter().flatten())
+            .collect();
+        self.requires
+            .keys()
+            .filter(|name| broken.contains(*name) || self.reachable(name).iter().any(|next| broken.contains(next)))
+            .cloned()
+            .collect()
+    }
+
+    // 依賴圖中的問題，每條一行；為空時表示所有進程都能按順序啟動
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self
+            .cycles()
+            .iter()
+            .map(|cycle| format!("circular requires: {}", cycle.join(" -> ")))
+            .collect();
+        problems.extend(
+            self.unknown()
+                .into_iter()
+                .map(|(name, required)| format!("{} requires unknown process {}", name, required)),
+        );
+        let blocked = self.blocked();
+        if !blocked.is_empty() {
+            problems.push(format!("cannot be started: {}", blocked.join(", ")));
+        }
+        problems
+    }
+
+    // 從沒有被其他進程依賴的進程開始，逐層列出它們依賴的進程
+    pub fn render_tree(&self) -> String {
+        let required: BTreeSet<&String> = self.requires.values().flatten().collect();
+        let roots = self.requires.keys().filter(|name| !required.contains(name));
+
+        let mut output = String::new();
+        let mut printed = BTreeSet::new();
+        for root in roots {
+            self.render_node(root, "", None, &mut Vec::new(), &mut printed, &mut output);
+        }
+        // 只出現在環中的進程沒有根，從名稱最小的未列出進程開始
+        for name in self.requires.keys() {
+            if !printed.contains(name) {
+                self.render_node(name, "", None, &mut Vec::new(), &mut printed, &mut output);
+            }
+        }
+
+        if self.requires.is_empty() {
+            return output;
+        }
+        let requires: HashMap<String, Vec<String>> = self.requires.clone().into_iter().collect();
+        match dependency::start_order(&requires) {
+            Ok(order) => output.push_str(&format!("\nStart order: {}\n", order.join(", "))),
+            Err(_) => output.push_str("\nStart order: undetermined\n"),
+        }
+        output
+    }
+
+    fn render_node(
+        &self,
+        name: &str,
+        prefix: &str,
+        last: Option<bool>,
+        path: &mut Vec<String>,
+        printed: &mut BTreeSet<String>,
+        output: &mut String,
+    ) {
+        let (branch, child_prefix) = match last {
+            None => ("", String::new()),
+            Some(true) => ("└── ", format!("{}    ", prefix)),
+            Some(false) => ("├── ", format!("{}│   ", prefix)),
+        };
+
+        let note = if path.iter().any(|ancestor| ancestor == name) {
+            " (cycle)"
+        } else if !self.requires.contains_key(name) {
+            " (unknown process)"
+        } else if printed.contains(name) && self.has_children(name) {
+            " (*)"
+        } else {
+            ""
+        };
+        output.push_str(&format!("{}{}{}{}\n", prefix, branch, name, note));
+        if !note.is_empty() {
+            return;
+        }
+        printed.insert(name.to_string());
+
+        let requires = self.requires.get(name).into_iter().flatten();
+        let external = self.external.get(name).into_iter().flatten();
+        let children: Vec<(&String, bool)> = requires
+            .map(|required| (required, true))
+            .chain(external.map(|dependency| (dependency, false)))
+            .collect();
+        path.push(name.to_string());
+        for (index, (child, process)) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+            if *process {
+                self.render_node(child, &child_prefix, Some(last), path, printed, output);
+            } else {
+                let branch = if last { "└── " } else { "├── " };
+                output.push_str(&format!("{}{}{} (external)\n", child_prefix, branch, child));
+            }
+        }
+        path.pop();
+    }
+
+    fn has_children(&self, name: &str) -> bool {
+        self.requires.get(name).is_some_and(|requires| !requires.is_empty())
+            || self.external.get(name).is_some_and(|external| !external.is_empty())
+    }
+
+    // Graphviz 格式：箭頭從依賴者指向被依賴的進程，環和未知進程標紅
+    pub fn render_dot(&self) -> String {
+        let mut dot = String::from("digraph janus {\n");
+        for name in self.requires.keys() {
+            dot.push_str(&format!("  {};\n", quote(name)));
+        }
+        let unknown: BTreeSet<String> = self.unknown().into_iter().map(|(_, required)| required).collect();
+        for name in &unknown {
+            let label = format!("{} (unknown)", name);
+            dot.push_str(&format!("  {} [color=red, style=dashed, label={}];\n", quote(name), quote(&label)));
+        }
+        let external: BTreeSet<&String> = self.external.values().flatten().collect();
+        for dependency in external {
+            dot.push_str(&format!("  {} [shape=box, style=dashed];\n", quote(dependency)));
+        }
+        for (name, external) in &self.external {
+            for dependency in external {
+                dot.push_str(&format!("  {} -> {} [style=dashed];\n", quote(name), quote(dependency)));
+            }
+        }
+        for (name, requires) in &self.requires {
+            for required in requires {
+                if self.requires.contains_key(required) && self.in_cycle(name, required) {
+                    dot.push_str(&format!("  {} -> {} [color=red];\n", quote(name), quote(required)));
+                } else {
+                    dot.push_str(&format!("  {} -> {};\n", quote(name), quote(required)));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
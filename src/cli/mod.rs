@@ -1,2 +1,6 @@
 pub mod command_parser;
+pub mod console;
+pub mod graph;
+pub mod progress;
 pub mod status_reporter;
+pub mod systemd;
@@ -0,0 +1,147 @@
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::msg;
+
+// 旋轉指示的刷新間隔
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Start,
+    Stop,
+}
+
+struct Outcome {
+    name: String,
+    elapsed: Duration,
+    error: Option<String>,
+}
+
+// 批量啟動或停止進程時的進度：每個進程完成時打印一行結果，結束後打印成功和失敗的彙總表；
+// 輸出到終端時，等待中的進程顯示旋轉指示
+pub struct Progress {
+    action: Action,
+    total: usize,
+    outcomes: Vec<Outcome>,
+    interactive: bool,
+}
+
+impl Progress {
+    pub fn new(action: Action, total: usize) -> Self {
+        Self {
+            action,
+            total,
+            outcomes: Vec::new(),
+            interactive: std::io::stdout().is_terminal(),
+        }
+    }
+
+    // 等待 task 完成並打印它的結果，返回是否成功
+    pub async fn track<F>(&mut self, name: &str, task: F) -> bool
+    where
+        F: Future<Output = Result<()>>,
+    {
+        let index = self.outcomes.len() + 1;
+        let started = Instant::now();
+        let result = if self.interactive {
+            self.spin(name, index, task).await
+        } else {
+            task.await
+        };
+        let elapsed = started.elapsed();
+        let seconds = format!("{:.2}", elapsed.as_secs_f64());
+
+        let error = result.err().map(|e| e.to_string());
+        match (&error, self.action) {
+            (None, Action::Start) => {
+                println!("{}", msg!("progress.started", index = index, total = self.total, name = name, seconds = seconds))
+            }
+            (None, Action::Stop) => {
+                println!("{}", msg!("progress.stopped", index = index, total = self.total, name = name, seconds = seconds))
+            }
+            (Some(error), Action::Start) => {
+                eprintln!("{}", msg!("progress.start_failed", index = index, total = self.total, name = name, error = error))
+            }
+            (Some(error), Action::Stop) => {
+                eprintln!("{}", msg!("progress.stop_failed", index = index, total = self.total, name = name, error = error))
+            }
+        }
+
+        let succeeded = error.is_none();
+        self.outcomes.push(Outcome {
+            name: name.to_string(),
+            elapsed,
+            error,
+        });
+        succeeded
+    }
+
+    async fn spin<F>(&self, name: &str, index: usize, task: F) -> Result<()>
+    where
+        F: Future<Output = Result<()>>,
+    {
+        let mut stdout = std::io::stdout();
+        let mut ticker = tokio::time::interval(SPINNER_INTERVAL);
+        tokio::pin!(task);
+        let mut frames = SPINNER_FRAMES.iter().cycle();
+        let result = loop {
+            tokio::select! {
+                result = &mut task => break result,
+                _ = ticker.tick() => {
+                    let spinner = frames.next().unwrap();
+                    let line = match self.action {
+                        Action::Start => msg!("progress.starting", spinner = spinner, index = index, total = self.total, name = name),
+                        Action::Stop => msg!("progress.stopping", spinner = spinner, index = index, total = self.total, name = name),
+                    };
+                    let _ = write!(stdout, "\r\x1b[2K{}", line);
+                    let _ = stdout.flush();
+                }
+            }
+        };
+        // 清除旋轉指示所在的行，結果另起一行打印
+        let _ = write!(stdout, "\r\x1b[2K");
+        let _ = stdout.flush();
+        result
+    }
+
+    // 處理了多個進程時打印每個進程的結果和總數
+    pub fn finish(&self) {
+        if self.outcomes.len() < 2 {
+            return;
+        }
+
+        let name_width = self
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("NAME".len());
+        let done = match self.action {
+            Action::Start => "started",
+            Action::Stop => "stopped",
+        };
+
+        println!();
+        println!("{:<name_width$}  {:<7}  {:>8}  ERROR", "NAME", "RESULT", "TIME");
+        for outcome in &self.outcomes {
+            println!(
+                "{:<name_width$}  {:<7}  {:>8}  {}",
+                outcome.name,
+                if outcome.error.is_some() { "failed" } else { done },
+                format!("{:.2}s", outcome.elapsed.as_secs_f64()),
+                outcome.error.as_deref().unwrap_or("-"),
+            );
+        }
+
+        let failed = self.outcomes.iter().filter(|outcome| outcome.error.is_some()).count();
+        println!(
+            "{}",
+            msg!("progress.summary", succeeded = self.outcomes.len() - failed, done = done, failed = failed)
+        );
+    }
+}
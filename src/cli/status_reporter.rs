@@ -1,27 +1,70 @@
 use std::time::Duration;
 
-use crate::process::{ProcessStatus, manager::ProcessManager};
+use crate::grpc::proto::ProcessInfo;
+use crate::msg;
+use crate::config::in_project;
+use crate::process::snapshot::StatusSnapshot;
+use crate::process::tree::{ProcessNode, ProcessTable};
+use crate::process::{ManagedProcess, ProcessStatus, Termination, manager::ProcessManager};
+use super::command_parser::format_size;
+
+// ps 輸出中命令列的最大寬度
+const COMMAND_WIDTH: usize = 40;
+
+// 進程樹中命令的最大寬度，不含縮進
+const TREE_COMMAND_WIDTH: usize = 60;
 
 pub struct StatusReporter<'a> {
     process_manager: &'a ProcessManager,
+    // 只報告該項目的進程
+    project: Option<&'a str>,
 }
 
 impl<'a> StatusReporter<'a> {
     pub fn new(process_manager: &'a ProcessManager) -> Self {
-        Self { process_manager }
+        Self { process_manager, project: None }
+    }
+    
+    pub fn with_project(mut self, project: Option<&'a str>) -> Self {
+        self.project = project;
+        self
+    }
+    
+    fn processes(&self) -> Vec<(&'a String, &'a ManagedProcess)> {
+        self.process_manager
+            .get_all_processes()
+            .iter()
+            .filter(|(name, _)| self.project.is_none_or(|project| in_project(project, name)))
+            .collect()
     }
     
     pub fn report_all(&self) -> crate::error::Result<()> {
-        let processes = self.process_manager.get_all_processes();
+        let processes = self.processes();
         
         if processes.is_empty() {
-            println!("No processes configured");
+            Self::print_no_processes();
             return Ok(());
         }
         
-        println!("Process Status Report:");
+        println!("{}", msg!("status.title"));
         println!("=====================");
         
+        if self.process_manager.is_config_reload_pending() {
+            println!("{}", msg!("status.reload_pending"));
+            println!("---------------------");
+        }
+        
+        let log_output = self.process_manager.get_log_handler().write_stats();
+        if log_output.write_errors > 0 {
+            let failover = if log_output.failed_over { msg!("status.log_failover") } else { String::new() };
+            println!(
+                "{}{}",
+                msg!("status.log_errors", errors = log_output.write_errors, dropped = format_size(log_output.dropped_bytes)),
+                failover
+            );
+            println!("---------------------");
+        }
+        
         for (name, process) in processes {
             self.report_status(name, process);
             println!("---------------------");
@@ -30,6 +73,415 @@ impl<'a> StatusReporter<'a> {
         Ok(())
     }
     
+    // 精簡輸出：每個進程一行，適合窄終端和 grep
+    pub fn report_compact(&self) -> crate::error::Result<()> {
+        let mut processes: Vec<_> = self.processes().into_iter().map(|(_, process)| process).collect();
+        processes.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        let name_width = processes
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("NAME".len());
+        let states: Vec<String> = processes.iter().map(|process| Self::state_label(process)).collect();
+        let state_width = states.iter().map(String::len).max().unwrap_or(0).max(9);
+        
+        println!(
+            "{:<name_width$}  {:>7}  {:<state_width$}  {:>4}  COMMAND",
+            "NAME", "PID", "STATE", "EXIT",
+        );
+        
+        for (process, state) in processes.into_iter().zip(states) {
+            let pid = process
+                .pid()
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let exit_code = process
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let command = std::iter::once(process.command.as_str())
+                .chain(process.args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ");
+            
+            println!(
+                "{:<name_width$}  {:>7}  {:<state_width$}  {:>4}  {}",
+                process.name,
+                pid,
+                state,
+                exit_code,
+                Self::truncate(&command, COMMAND_WIDTH),
+            );
+        }
+        
+        Ok(())
+    }
+    
+    // 每個受管進程及其派生的全部子孫進程，便於發現遺留的孫進程和失控的 fork
+    pub fn report_tree(&self) -> crate::error::Result<()> {
+        let mut processes: Vec<_> = self.processes().into_iter().map(|(_, process)| process).collect();
+        processes.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        if processes.is_empty() {
+            Self::print_no_processes();
+            return Ok(());
+        }
+        
+        let Some(table) = ProcessTable::scan() else {
+            println!("{}", msg!("status.tree_unavailable"));
+            return Ok(());
+        };
+        
+        for process in processes {
+            let status = format!("{:?}", process.status);
+            match process.pid().and_then(|pid| table.tree(pid)) {
+                Some(root) => {
+                    println!(
+                        "{}",
+                        msg!(
+                            "status.tree_process",
+                            name = process.name,
+                            status = status,
+                            count = root.count(),
+                            rss = format_size(root.total_rss())
+                        )
+                    );
+                    Self::print_node(&root, "", true);
+                }
+                None => println!("{}", msg!("status.tree_not_running", name = process.name, status = status)),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn print_node(node: &ProcessNode, prefix: &str, last: bool) {
+        let branch = if last { "└─" } else { "├─" };
+        println!(
+            "{}{} {:>7}  {:>9}  {}",
+            prefix,
+            branch,
+            node.pid,
+            format_size(node.rss),
+            Self::truncate(&node.command, TREE_COMMAND_WIDTH),
+        );
+        
+        let child_prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+        for (index, child) in node.children.iter().enumerate() {
+            Self::print_node(child, &child_prefix, index + 1 == node.children.len());
+        }
+    }
+    
+    // 附上如何添加進程的提示，空的配置不會被誤認為 janus 出錯
+    fn print_no_processes() {
+        println!("{}", msg!("status.no_processes"));
+        println!("{}", msg!("status.no_processes_hint"));
+    }
+    
+    // 遠程 janus 通過 gRPC 返回的進程狀態，只包含接口提供的字段
+    pub fn report_remote(processes: &[ProcessInfo]) -> crate::error::Result<()> {
+        if processes.is_empty() {
+            Self::print_no_processes();
+            return Ok(());
+        }
+        
+        println!("{}", msg!("status.title"));
+        println!("=====================");
+        
+        for process in processes {
+            println!("{}", msg!("status.process", name = process.name));
+            println!("{}", msg!("status.status", status = Self::remote_state_label(process)));
+            if let (Some(secs), Some(timeout)) = (process.stopping_secs, process.stop_timeout_secs) {
+                println!(
+                    "{}",
+                    msg!(
+                        "status.stopping",
+                        elapsed = Self::format_duration(Duration::from_secs(secs)),
+                        timeout = Self::format_duration(Duration::from_secs(timeout))
+                    )
+                );
+            }
+            if let Some(window) = &process.waiting_for_window {
+                println!("{}", msg!("status.start_window", window = window));
+            }
+            if let Some(secs) = process.maintenance_secs {
+                println!("{}", msg!("status.maintenance", remaining = Self::format_duration(Duration::from_secs(secs))));
+            }
+            if let Some(secs) = process.resume_secs {
+                println!("{}", msg!("status.resume", remaining = Self::format_duration(Duration::from_secs(secs))));
+            } else if process.manual_stop {
+                println!("{}", msg!("status.manual_stop"));
+            }
+            if let Some(uptime) = process.uptime_secs {
+                println!("{}", msg!("status.uptime", uptime = Self::format_duration(Duration::from_secs(uptime))));
+            }
+            if let Some(port) = process.port {
+                println!("{}", msg!("status.port", port = port));
+            }
+            if let Some(code) = process.exit_code {
+                println!("{}", msg!("status.exit_code", code = code));
+            }
+            if let Some(image) = &process.image {
+                println!("{}", msg!("status.image", image = image));
+            }
+            println!("{}", msg!("status.command", command = process.command));
+            if !process.args.is_empty() {
+                println!("{}", msg!("status.args", args = format!("{:?}", process.args)));
+            }
+            println!("{}", msg!("status.restart_count", count = process.restart_count));
+            println!("---------------------");
+        }
+        
+        Ok(())
+    }
+    
+    // 監控循環發布的狀態快照，信號處理程序不必等待進程管理器的鎖；其中的狀態最多落後一個檢查週期
+    pub fn report_snapshot(snapshot: &StatusSnapshot) -> crate::error::Result<()> {
+        if snapshot.processes.is_empty() {
+            Self::print_no_processes();
+            return Ok(());
+        }
+        
+        println!("{}", msg!("status.title"));
+        println!("=====================");
+        
+        if snapshot.config_reload_pending {
+            println!("{}", msg!("status.reload_pending"));
+            println!("---------------------");
+        }
+        
+        let log_output = &snapshot.log_output;
+        if log_output.write_errors > 0 {
+            let failover = if log_output.failed_over { msg!("status.log_failover") } else { String::new() };
+            println!(
+                "{}{}",
+                msg!("status.log_errors", errors = log_output.write_errors, dropped = format_size(log_output.dropped_bytes)),
+                failover
+            );
+            println!("---------------------");
+        }
+        
+        for process in &snapshot.processes {
+            println!("{}", msg!("status.process", name = process.name));
+            if let Some(label) = &process.display_label {
+                println!("{}", msg!("status.display_name", name = label));
+            }
+            let state = match process.starting_secs {
+                Some(secs) => Self::starting_label(secs, process.expected_start_secs),
+                None => process.status.clone(),
+            };
+            println!("{}", msg!("status.status", status = state));
+            if let (Some(secs), Some(timeout)) = (process.stopping_secs, process.stop_timeout_secs) {
+                println!(
+                    "{}",
+                    msg!(
+                        "status.stopping",
+                        elapsed = Self::format_duration(Duration::from_secs(secs)),
+                        timeout = Self::format_duration(Duration::from_secs(timeout))
+                    )
+                );
+            }
+            if let Some(window) = &process.waiting_for_window {
+                println!("{}", msg!("status.start_window", window = window));
+            }
+            if let Some(secs) = process.maintenance_secs {
+                println!("{}", msg!("status.maintenance", remaining = Self::format_duration(Duration::from_secs(secs))));
+            }
+            if let Some(secs) = process.resume_secs {
+                println!("{}", msg!("status.resume", remaining = Self::format_duration(Duration::from_secs(secs))));
+            } else if process.manual_stop {
+                println!("{}", msg!("status.manual_stop"));
+            }
+            if let Some(uptime) = process.uptime_secs {
+                println!("{}", msg!("status.uptime", uptime = Self::format_duration(Duration::from_secs(uptime))));
+            }
+            if let Some(port) = process.port {
+                println!("{}", msg!("status.port", port = port));
+            }
+            if let Some(ms) = process.last_start_duration_ms {
+                println!("{}", msg!("status.last_start", seconds = format!("{:.3}", ms as f64 / 1000.0)));
+            }
+            if let Some(ms) = process.last_stop_duration_ms {
+                println!("{}", msg!("status.last_stop", seconds = format!("{:.3}", ms as f64 / 1000.0)));
+            }
+            if let Some(code) = process.exit_code {
+                println!("{}", msg!("status.exit_code", code = code));
+            }
+            if let Some(signal) = process.termination.as_deref().and_then(|termination| termination.strip_prefix("killed by ")) {
+                println!("{}", msg!("status.killed_by", signal = signal));
+            }
+            if let Some(ready) = process.ready.filter(|_| process.status == "Running") {
+                println!("{}", msg!("status.ready", ready = ready));
+                if let Some(status) = &process.notify_status {
+                    println!("{}", msg!("status.notify_status", status = status));
+                }
+            }
+            if let Some(image) = &process.image {
+                println!("{}", msg!("status.image", image = image));
+            }
+            println!("{}", msg!("status.command", command = process.command));
+            if !process.args.is_empty() {
+                println!("{}", msg!("status.args", args = format!("{:?}", process.args)));
+            }
+            if let Some(dir) = &process.working_dir {
+                println!("{}", msg!("status.working_dir", dir = dir));
+            }
+            if process.env_count > 0 {
+                println!("{}", msg!("status.env", count = process.env_count));
+            }
+            if process.env_cache_hits + process.env_cache_misses > 0 {
+                println!("{}", msg!("status.env_cache", hits = process.env_cache_hits, misses = process.env_cache_misses));
+            }
+            println!("{}", msg!("status.auto_restart", enabled = process.auto_restart));
+            println!("{}", msg!("status.restart_count", count = process.restart_count));
+            match process.restart_limit {
+                Some(limit) => println!("{}", msg!("status.restart_limit", limit = limit)),
+                None => println!("{}", msg!("status.restart_limit_unlimited")),
+            }
+            println!("{}", msg!("status.restart_delay", seconds = process.restart_delay));
+            if let Some(budget) = process.restart_budget {
+                println!(
+                    "{}",
+                    msg!("status.restart_budget", budget = budget, seconds = process.restart_budget_window)
+                );
+            }
+            if let Some(every) = process.every_secs {
+                println!("{}", msg!("status.every", interval = Self::format_duration(Duration::from_secs(every))));
+            }
+            if let Some(schedule) = &process.schedule {
+                println!("{}", msg!("status.schedule", schedule = schedule));
+            }
+            
+            if !process.recent_runs.is_empty() {
+                println!("{}", msg!("status.recent_runs"));
+                for run in process.recent_runs.iter().rev() {
+                    let finished = chrono::DateTime::parse_from_rfc3339(&run.finished_at)
+                        .map(|finished| finished.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_else(|_| run.finished_at.clone());
+                    let exit_code = match (run.exit_code, run.termination.as_deref()) {
+                        (Some(code), _) => code.to_string(),
+                        (None, Some(termination)) => termination.strip_prefix("killed by ").unwrap_or(termination).to_string(),
+                        (None, None) => "-".to_string(),
+                    };
+                    let seconds = format!("{:.3}", run.duration_ms as f64 / 1000.0);
+                    match &run.output_path {
+                        Some(path) => println!(
+                            "{}",
+                            msg!("status.run_with_output", finished = finished, code = exit_code, seconds = seconds, path = path)
+                        ),
+                        None => println!("{}", msg!("status.run", finished = finished, code = exit_code, seconds = seconds)),
+                    }
+                    if let Some(usage) = &run.usage {
+                        println!(
+                            "{}",
+                            msg!(
+                                "status.run_usage",
+                                user = format!("{:.2}", usage.user_cpu_ms as f64 / 1000.0),
+                                system = format!("{:.2}", usage.system_cpu_ms as f64 / 1000.0),
+                                read = format_size(usage.read_bytes),
+                                written = format_size(usage.write_bytes),
+                                voluntary = usage.voluntary_switches,
+                                involuntary = usage.involuntary_switches
+                            )
+                        );
+                    }
+                    if let Some(path) = &run.core_path {
+                        println!("{}", msg!("status.run_core", path = path));
+                    }
+                    if run.required_kill {
+                        println!("{}", msg!("status.run_required_kill"));
+                    }
+                }
+                
+                // 只展開最近一次異常退出前的輸出
+                if let Some(run) = process.recent_runs.last().filter(|run| !run.crash_context.is_empty()) {
+                    println!("{}", msg!("status.crash_context", count = run.crash_context.len()));
+                    for line in &run.crash_context {
+                        println!("    | {}", line);
+                    }
+                }
+            }
+            println!("---------------------");
+        }
+        
+        Ok(())
+    }
+    
+    pub fn report_remote_compact(processes: &[ProcessInfo]) -> crate::error::Result<()> {
+        let name_width = processes
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("NAME".len());
+        let states: Vec<String> = processes.iter().map(Self::remote_state_label).collect();
+        let state_width = states.iter().map(String::len).max().unwrap_or(0).max(9);
+        
+        println!(
+            "{:<name_width$}  {:>7}  {:<state_width$}  {:>4}  COMMAND",
+            "NAME", "PID", "STATE", "EXIT",
+        );
+        
+        for (process, state) in processes.iter().zip(states) {
+            let pid = process.pid.map(|id| id.to_string()).unwrap_or_else(|| "-".to_string());
+            let exit_code = process
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let command = std::iter::once(process.command.as_str())
+                .chain(process.args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ");
+            
+            println!(
+                "{:<name_width$}  {:>7}  {:<state_width$}  {:>4}  {}",
+                process.name,
+                pid,
+                state,
+                exit_code,
+                Self::truncate(&command, COMMAND_WIDTH),
+            );
+        }
+        
+        Ok(())
+    }
+    
+    // 仍在啟動的進程顯示啟動進度，例如 "starting (45s / expected 90s)"，慢啟動不會被誤認為卡住
+    pub fn starting_label(elapsed_secs: u64, expected_secs: Option<u64>) -> String {
+        match expected_secs {
+            Some(expected) => msg!("status.starting_expected", elapsed = elapsed_secs, expected = expected),
+            None => msg!("status.starting", elapsed = elapsed_secs),
+        }
+    }
+    
+    fn state_label(process: &ManagedProcess) -> String {
+        match process.starting_for() {
+            Some(uptime) => Self::starting_label(
+                uptime.as_secs(),
+                process.expected_start_duration.map(|expected| expected.as_secs()),
+            ),
+            None => format!("{:?}", process.status),
+        }
+    }
+    
+    fn remote_state_label(process: &ProcessInfo) -> String {
+        match process.starting_secs {
+            Some(secs) => Self::starting_label(secs, process.expected_start_secs),
+            None => process.status.clone(),
+        }
+    }
+    
+    fn truncate(text: &str, width: usize) -> String {
+        if text.chars().count() <= width {
+            return text.to_string();
+        }
+        
+        let truncated: String = text.chars().take(width.saturating_sub(3)).collect();
+        format!("{}...", truncated)
+    }
+    
     fn format_duration(duration: Duration) -> String {
         let total_secs = duration.as_secs();
         let days = total_secs / (24 * 60 * 60);
@@ -49,43 +501,168 @@ impl<'a> StatusReporter<'a> {
     }
     
     fn report_status(&self, name: &str, process: &crate::process::ManagedProcess) {
-        println!("Process: {}", name);
-        println!("Status: {:?}", process.status);
+        println!("{}", msg!("status.process", name = name));
+        if process.display_label() != name {
+            println!("{}", msg!("status.display_name", name = process.display_label()));
+        }
+        println!("{}", msg!("status.status", status = Self::state_label(process)));
+        if let (Some(since), Some(timeout)) = (process.stopping_since, process.stop_timeout) {
+            println!(
+                "{}",
+                msg!("status.stopping", elapsed = Self::format_duration(since.elapsed()), timeout = Self::format_duration(timeout))
+            );
+        }
+        if let Some(window) = process.start_window.as_ref().filter(|_| process.waiting_for_window) {
+            println!("{}", msg!("status.start_window", window = window));
+        }
+        if let Some(until) = process.maintenance_until.filter(|_| process.in_maintenance(std::time::Instant::now())) {
+            let remaining = until.saturating_duration_since(std::time::Instant::now());
+            println!("{}", msg!("status.maintenance", remaining = Self::format_duration(remaining)));
+        }
+        if let Some(resume_at) = process.resume_at {
+            let remaining = resume_at.saturating_duration_since(std::time::Instant::now());
+            println!("{}", msg!("status.resume", remaining = Self::format_duration(remaining)));
+        } else if process.manual_stop {
+            println!("{}", msg!("status.manual_stop"));
+        }
         
         // 顯示運行時間（如果進程正在運行）
         if process.status == ProcessStatus::Running {
             if let Some(start_time) = process.start_time {
                 let uptime = start_time.elapsed();
-                println!("Uptime: {}", Self::format_duration(uptime));
+                println!("{}", msg!("status.uptime", uptime = Self::format_duration(uptime)));
+            }
+        }
+        
+        if let Some(port) = process.port {
+            println!("{}", msg!("status.port", port = port));
+        }
+        
+        // 顯示最近一次啟動和停止的耗時
+        if let Some(duration) = process.last_start_duration {
+            println!("{}", msg!("status.last_start", seconds = format!("{:.3}", duration.as_secs_f64())));
+        }
+        if let Some(duration) = process.last_stop_duration {
+            println!("{}", msg!("status.last_stop", seconds = format!("{:.3}", duration.as_secs_f64())));
+        }
+        
+        // 顯示最近一次的退出碼（如果進程已退出）
+        if let Some(code) = process.exit_code {
+            println!("{}", msg!("status.exit_code", code = code));
+        }
+        if let Some(termination @ Termination::Signaled { .. }) = process.termination {
+            println!("{}", msg!("status.killed_by", signal = termination.short_label()));
+        }
+        
+        // 顯示進程通過 NOTIFY_SOCKET 報告的就緒狀態和狀態文本
+        if process.notify && process.status == ProcessStatus::Running {
+            let notify_status = process.notify_state.get();
+            println!("{}", msg!("status.ready", ready = notify_status.ready));
+            if let Some(status) = &notify_status.status {
+                println!("{}", msg!("status.notify_status", status = status));
             }
         }
         
         // 顯示命令和參數
-        println!("Command: {}", process.command);
+        if let Some(image) = &process.image {
+            println!("{}", msg!("status.image", image = image));
+        }
+        println!("{}", msg!("status.command", command = process.command));
         if !process.args.is_empty() {
-            println!("Args: {:?}", process.args);
+            println!("{}", msg!("status.args", args = format!("{:?}", process.args)));
         }
         
         // 顯示工作目錄
         if let Some(dir) = &process.working_dir {
-            println!("Working directory: {}", dir);
+            println!("{}", msg!("status.working_dir", dir = dir));
         }
         
         // 顯示環境變量
         if !process.env.is_empty() {
-            println!("Environment variables: {} defined", process.env.len());
+            println!("{}", msg!("status.env", count = process.env.len()));
+        }
+        
+        // env_dirs 的緩存，只在用到過緩存後顯示
+        let cache = &process.env_cache;
+        if cache.hits + cache.misses > 0 {
+            println!("{}", msg!("status.env_cache", hits = cache.hits, misses = cache.misses));
         }
         
         // 顯示重啟配置
-        println!("Auto-restart: {}", process.auto_restart);
-        println!("Restart count: {}", process.restart_count);
+        println!("{}", msg!("status.auto_restart", enabled = process.auto_restart));
+        println!("{}", msg!("status.restart_count", count = process.restart_count));
         
         if let Some(limit) = process.restart_limit {
-            println!("Restart limit: {}", limit);
+            println!("{}", msg!("status.restart_limit", limit = limit));
         } else {
-            println!("Restart limit: unlimited");
+            println!("{}", msg!("status.restart_limit_unlimited"));
         }
         
-        println!("Restart delay: {} seconds", process.restart_delay);
+        println!("{}", msg!("status.restart_delay", seconds = process.restart_delay));
+        
+        if let Some(budget) = process.restart_budget {
+            println!(
+                "{}",
+                msg!("status.restart_budget", budget = budget, seconds = process.restart_budget_window)
+            );
+        }
+        
+        if let Some(every) = process.every {
+            println!("{}", msg!("status.every", interval = Self::format_duration(every)));
+        }
+        if let Some(schedule) = &process.schedule {
+            println!("{}", msg!("status.schedule", schedule = schedule));
+        }
+        
+        // 顯示最近的運行記錄（退出碼及輸出文件）
+        if !process.run_history.is_empty() {
+            println!("{}", msg!("status.recent_runs"));
+            for run in process.run_history.iter().rev() {
+                let exit_code = run
+                    .termination
+                    .map(|termination| termination.short_label())
+                    .unwrap_or_else(|| "-".to_string());
+                let finished = run.finished_at.format("%Y-%m-%d %H:%M:%S");
+                let seconds = format!("{:.3}", run.duration.as_secs_f64());
+                match &run.output_path {
+                    Some(path) => println!(
+                        "{}",
+                        msg!("status.run_with_output", finished = finished, code = exit_code, seconds = seconds, path = path.display())
+                    ),
+                    None => println!(
+                        "{}",
+                        msg!("status.run", finished = finished, code = exit_code, seconds = seconds)
+                    ),
+                }
+                if let Some(usage) = &run.usage {
+                    println!(
+                        "{}",
+                        msg!(
+                            "status.run_usage",
+                            user = format!("{:.2}", usage.user_time.as_secs_f64()),
+                            system = format!("{:.2}", usage.system_time.as_secs_f64()),
+                            read = format_size(usage.read_bytes),
+                            written = format_size(usage.write_bytes),
+                            voluntary = usage.voluntary_switches,
+                            involuntary = usage.involuntary_switches
+                        )
+                    );
+                }
+                if let Some(path) = &run.core_path {
+                    println!("{}", msg!("status.run_core", path = path.display()));
+                }
+                if run.required_kill {
+                    println!("{}", msg!("status.run_required_kill"));
+                }
+            }
+            
+            // 只展開最近一次異常退出前的輸出
+            if let Some(run) = process.run_history.back().filter(|run| !run.crash_context.is_empty()) {
+                println!("{}", msg!("status.crash_context", count = run.crash_context.len()));
+                for line in &run.crash_context {
+                    println!("    | {}", line);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+// 生成在普通虛擬機上運行 janus 的 systemd 服務單元
+pub struct SystemdUnit<'a> {
+    pub exec_path: &'a Path,
+    pub config_path: &'a Path,
+    pub user: Option<&'a str>,
+    // global.sd_notify：janus 啟動完所有進程後才報告 READY=1
+    pub notify: bool,
+    pub reload: ReloadMethod,
+}
+
+// systemctl reload 讓 janus 重新加載配置的方式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReloadMethod {
+    // 通過 gRPC 調用 janus reload，配置無效時 systemctl reload 失敗
+    Command,
+    // 發送 SIGHUP，只在 sighup_action = "reload" 時重新加載配置
+    Sighup,
+}
+
+impl SystemdUnit<'_> {
+    pub fn render(&self) -> String {
+        let working_dir = self.config_path.parent().unwrap_or(Path::new("/"));
+        let config_name = self
+            .config_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut unit = String::new();
+        unit.push_str("[Unit]\n");
+        unit.push_str(&format!("Description=Janus process manager ({})\n", config_name));
+        unit.push_str("After=network-online.target\n");
+        unit.push_str("Wants=network-online.target\n\n");
+
+        unit.push_str("[Service]\n");
+        // janus 在前台運行 start 並監控子進程
+        if self.notify {
+            unit.push_str("Type=notify\n");
+        } else {
+            unit.push_str("Type=simple\n");
+        }
+        let janus = format!(
+            "{} --config {}",
+            quote(&self.exec_path.to_string_lossy()),
+            quote(&self.config_path.to_string_lossy())
+        );
+        unit.push_str(&format!("ExecStart={} start\n", janus));
+        match self.reload {
+            ReloadMethod::Command => unit.push_str(&format!("ExecReload={} reload\n", janus)),
+            ReloadMethod::Sighup => unit.push_str("ExecReload=/bin/kill -HUP $MAINPID\n"),
+        }
+        unit.push_str(&format!("WorkingDirectory={}\n", quote(&working_dir.to_string_lossy())));
+        if let Some(user) = self.user {
+            unit.push_str(&format!("User={}\n", user));
+        }
+        // SIGTERM 只發給 janus，由它停止子進程；超時後 systemd 再清理整個 cgroup
+        unit.push_str("KillMode=mixed\n");
+        unit.push_str("TimeoutStopSec=30\n");
+        unit.push_str("Restart=on-failure\n\n");
+
+        unit.push_str("[Install]\n");
+        unit.push_str("WantedBy=multi-user.target\n");
+
+        unit
+    }
+}
+
+// systemd 命令行中包含空白的參數需要加引號
+fn quote(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,163 @@
+// 通過環境變量定義進程，用於不方便寫配置文件的極簡鏡像：JANUS_PROCESS_<NAME>_<FIELD> 設置進程 <name>
+// (小寫) 的一個配置項，例如 JANUS_PROCESS_WEB_COMMAND=node、JANUS_PROCESS_WEB_ARGS=server.js，
+// JANUS_PROCESS_WEB_ENV_<KEY> 設置進程的環境變量。與配置文件中同名的進程合併，環境變量中的配置項優先
+use std::collections::BTreeMap;
+
+use crate::config::ProcessConfig;
+use crate::error::{JanusError, Result};
+
+pub const PREFIX: &str = "JANUS_PROCESS_";
+
+// 配置項的值按 schema 中的類型解析
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    // 原樣使用
+    String,
+    // 按空白分割，或者以 [ 開頭時按 TOML 數組解析，例如 ["-c", "echo hello"]；
+    // 元素不是字符串時每個元素按 TOML 解析，例如 cpu_affinity 的 "0 1"
+    Array { strings: bool },
+    // 布爾值、數字和內聯表按 TOML 解析，無法解析時作為字符串
+    Other,
+}
+
+// 是否有進程通過環境變量定義；此時配置文件可以不存在
+pub fn defines_processes() -> bool {
+    std::env::vars().any(|(key, _)| key.starts_with(PREFIX))
+}
+
+// 把 vars 中以 PREFIX 開頭的變量合併到 processes
+pub fn merge_processes(processes: &mut Vec<ProcessConfig>, vars: impl IntoIterator<Item = (String, String)>) -> Result<()> {
+    let fields = fields();
+    let mut defined: BTreeMap<String, toml::Table> = BTreeMap::new();
+    for (key, value) in vars {
+        let Some(rest) = key.strip_prefix(PREFIX) else {
+            continue;
+        };
+        let (name, field) = split(rest, &fields).ok_or_else(|| {
+            JanusError::Config(format!(
+                "Unrecognized variable {}: expected {}<NAME>_<FIELD> or {}<NAME>_ENV_<KEY>, such as {}WEB_COMMAND",
+                key, PREFIX, PREFIX, PREFIX
+            ))
+        })?;
+        let table = defined.entry(name).or_default();
+        match field {
+            Field::Env(env_key) => {
+                let env = table
+                    .entry("env")
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+                if let toml::Value::Table(env) = env {
+                    env.insert(env_key, toml::Value::String(value));
+                }
+            }
+            Field::Option(field, kind) => {
+                let value = parse_value(&value, kind).map_err(|e| {
+                    JanusError::Config(format!("Invalid value of {}: {}", key, e))
+                })?;
+                table.insert(field, value);
+            }
+        }
+    }
+
+    for (name, table) in defined {
+        let invalid = |e: &dyn std::fmt::Display| {
+            JanusError::Config(format!("Process {} defined by {}{}_* variables: {}", name, PREFIX, name.to_uppercase(), e))
+        };
+        match processes.iter_mut().find(|process| process.name == name) {
+            Some(process) => {
+                let mut merged = toml::Table::try_from(&*process).map_err(|e| invalid(&e))?;
+                for (field, value) in table {
+                    match (merged.get_mut(&field), value) {
+                        (Some(toml::Value::Table(env)), toml::Value::Table(overrides)) if field == "env" => {
+                            env.extend(overrides);
+                        }
+                        (_, value) => {
+                            merged.insert(field, value);
+                        }
+                    }
+                }
+                *process = merged.try_into().map_err(|e| invalid(&e))?;
+            }
+            None => {
+                let mut table = table;
+                table.insert("name".to_string(), toml::Value::String(name.clone()));
+                processes.push(table.try_into().map_err(|e| invalid(&e))?);
+            }
+        }
+    }
+    Ok(())
+}
+
+enum Field {
+    Option(String, Kind),
+    Env(String),
+}
+
+// 進程名可以包含下劃線，取能匹配到配置項的最短名稱，例如 MY_API_COMMAND 為 my_api 的 command
+fn split(rest: &str, fields: &BTreeMap<String, Kind>) -> Option<(String, Field)> {
+    rest.match_indices('_').find_map(|(index, _)| {
+        let name = rest[..index].to_lowercase();
+        let field = &rest[index + 1..];
+        if name.is_empty() {
+            return None;
+        }
+        if let Some(env_key) = field.strip_prefix("ENV_").filter(|key| !key.is_empty()) {
+            return Some((name, Field::Env(env_key.to_string())));
+        }
+        let field = field.to_lowercase();
+        fields.get(&field).map(|kind| (name, Field::Option(field.clone(), *kind)))
+    })
+}
+
+// 進程配置項的名稱和類型，取自配置的 JSON Schema；name 來自變量名，env 單獨處理
+fn fields() -> BTreeMap<String, Kind> {
+    let schema = schemars::schema_for!(ProcessConfig);
+    let Some(properties) = schema.get("properties").and_then(|properties| properties.as_object()) else {
+        return BTreeMap::new();
+    };
+    properties
+        .iter()
+        .filter(|(field, _)| !matches!(field.as_str(), "name" | "env"))
+        .map(|(field, property)| {
+            let types: Vec<&str> = match &property["type"] {
+                serde_json::Value::String(kind) => vec![kind.as_str()],
+                serde_json::Value::Array(kinds) => kinds.iter().filter_map(|kind| kind.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            let kind = if types.contains(&"array") {
+                Kind::Array {
+                    strings: property["items"]["type"] == "string",
+                }
+            } else if types.contains(&"string") {
+                Kind::String
+            } else {
+                Kind::Other
+            };
+            (field.clone(), kind)
+        })
+        .collect()
+}
+
+fn parse_value(value: &str, kind: Kind) -> std::result::Result<toml::Value, String> {
+    let parse = |value: &str| {
+        toml::from_str::<toml::Table>(&format!("value = {}", value))
+            .map_err(|e| e.message().to_string())
+            .and_then(|mut table| table.remove("value").ok_or_else(|| "missing value".to_string()))
+    };
+    match kind {
+        Kind::String => Ok(toml::Value::String(value.to_string())),
+        Kind::Array { .. } if value.trim_start().starts_with('[') => parse(value),
+        Kind::Array { strings } => Ok(toml::Value::Array(
+            value
+                .split_whitespace()
+                .map(|item| {
+                    if strings {
+                        toml::Value::String(item.to_string())
+                    } else {
+                        parse(item).unwrap_or_else(|_| toml::Value::String(item.to_string()))
+                    }
+                })
+                .collect(),
+        )),
+        Kind::Other => Ok(parse(value).unwrap_or_else(|_| toml::Value::String(value.to_string()))),
+    }
+}
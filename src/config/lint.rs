@@ -0,0 +1,119 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::config::manager::ConfigManager;
+use crate::config::ProcessConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub process: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn warning(process: &ProcessConfig, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            process: Some(process.name.clone()),
+            message,
+        }
+    }
+
+    fn info(process: Option<&ProcessConfig>, message: String) -> Self {
+        Self {
+            severity: Severity::Info,
+            process: process.map(|process| process.name.clone()),
+            message,
+        }
+    }
+}
+
+// 對已通過加載校驗（硬錯誤）的配置做進一步檢查，返回不阻止運行但可能有問題的設置
+pub fn lint(config_manager: &ConfigManager) -> Vec<Diagnostic> {
+    let global = config_manager.get_global_config();
+    let mut diagnostics = Vec::new();
+
+    if global.log_dir.is_none() {
+        diagnostics.push(Diagnostic::info(
+            None,
+            "log_dir is not set; tail, grep and logs have nothing to read".to_string(),
+        ));
+    }
+
+    // 進程環境變量可以引用 janus 自身的環境、全局 env 和同一進程的 env
+    let mut defined: HashSet<String> = std::env::vars().map(|(key, _)| key).collect();
+    defined.extend(global.env.iter().flatten().map(|(key, _)| key.clone()));
+    let reference = Regex::new(r"\$\{?([A-Za-z_][A-Za-z0-9_]*)\}?").unwrap();
+
+    for process in config_manager.get_process_configs() {
+        let auto_restart = process.auto_restart.unwrap_or(false);
+
+        if auto_restart && process.restart_delay == Some(0) {
+            diagnostics.push(Diagnostic::warning(
+                process,
+                "restart_delay is 0, a crashing process will be restarted in a tight loop".to_string(),
+            ));
+        }
+        if auto_restart && process.restart_limit.is_none() && process.restart_budget.is_none() {
+            diagnostics.push(Diagnostic::warning(
+                process,
+                "auto_restart without restart_limit or restart_budget restarts forever".to_string(),
+            ));
+        }
+
+        if let Some(dir) = &process.working_dir {
+            if !Path::new(dir).is_dir() {
+                diagnostics.push(Diagnostic::warning(
+                    process,
+                    format!("working_dir {} does not exist", dir),
+                ));
+            }
+        }
+
+        let mut process_defined = defined.clone();
+        process_defined.extend(process.env.iter().flatten().map(|(key, _)| key.clone()));
+        let mut env: Vec<_> = process.env.iter().flatten().collect();
+        env.sort();
+        for (key, value) in env {
+            for captures in reference.captures_iter(value) {
+                let variable = &captures[1];
+                if !process_defined.contains(variable) {
+                    diagnostics.push(Diagnostic::warning(
+                        process,
+                        format!("env {} references undefined variable {}", key, variable),
+                    ));
+                }
+            }
+        }
+
+        let scheduled = process.every.is_some() || process.schedule.is_some();
+        if !auto_restart && !scheduled {
+            diagnostics.push(Diagnostic::info(
+                Some(process),
+                "runs once; it is not restarted when it exits".to_string(),
+            ));
+        }
+    }
+
+    diagnostics
+}
@@ -1,28 +1,99 @@
+use std::collections::HashMap;
 use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 
-use crate::config::{Config, GlobalConfig, ProcessConfig};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use crate::config::env;
+use crate::config::secret;
+use crate::config::{Config, GlobalConfig, LogMode, PortConfig, ProcessConfig, ProjectConfig, SignalAction};
 use crate::error::{JanusError, Result};
+use crate::logging::sampling::{normalize_level, LEVELS};
+use crate::logging::search::parse_duration;
+use crate::process::container;
+use crate::process::dependency::{self, Endpoint};
+use crate::process::http;
+use crate::process::memory;
+use crate::process::ports;
+use crate::process::render;
+use crate::process::restart_script::RestartScript;
+use crate::process::schedule::CronSchedule;
+use crate::process::secrets::SecretRef;
+use crate::process::scheduling;
+use crate::process::start_window::StartWindow;
+
+// 未指定 --config 時使用的配置文件
+pub const DEFAULT_CONFIG_FILE: &str = "janus.toml";
 
 #[derive(Debug)]
 pub struct ConfigManager {
     config: Config,
+    config_path: String,
+    config_hash: String,
+    // 各項目配置文件的路徑，與主配置文件一起計算哈希
+    project_paths: Vec<PathBuf>,
+    // 配置文件不存在，進程全部由 JANUS_PROCESS_* 環境變量定義
+    file_missing: bool,
 }
 
 impl ConfigManager {
     pub fn new(config_path: &str) -> Result<Self> {
-        let config = Self::load_config(config_path)?;
-        
-        let manager = Self {
-            config,
+        let manager = Self::load(config_path)?;
+        manager.validate()?;
+        Ok(manager)
+    }
+    
+    // 讀取並合併配置但不驗證，graph 據此顯示有問題的依賴關係
+    pub fn load(config_path: &str) -> Result<Self> {
+        let file_missing = !Path::new(config_path).exists() && env::defines_processes();
+        let config_content = if file_missing {
+            String::new()
+        } else {
+            Self::read_config(config_path)?
         };
+        let mut config = Self::parse_config(&config_content)?;
+        env::merge_processes(&mut config.process, std::env::vars())?;
+        Self::expand_env(&mut config.process, &config.global, config.env_presets.as_ref())?;
         
-        manager.validate()?;
+        // 項目的進程合併到同一個進程表中，之後與主配置中的進程一起驗證
+        let projects = config.project.clone().unwrap_or_default();
+        let project_paths = Self::project_paths(config_path, &projects);
+        let mut contents = vec![config_content];
+        for (project, path) in projects.iter().zip(&project_paths) {
+            let content = fs::read_to_string(path).map_err(|e| {
+                JanusError::Config(format!("Failed to read config file of project {}: {}", project.name, e))
+            })?;
+            let project_config = Self::parse_config(&content).map_err(|e| {
+                JanusError::Config(format!("Project {}: {}", project.name, e))
+            })?;
+            Self::add_project(&mut config.process, project, project_config)?;
+            contents.push(content);
+        }
         
-        Ok(manager)
+        Ok(Self {
+            config,
+            config_path: config_path.to_string(),
+            config_hash: Self::hash_content(&contents.join("\0")),
+            project_paths,
+            file_missing,
+        })
     }
     
     pub fn validate(&self) -> Result<()> {
+        let mut projects = std::collections::HashSet::new();
+        for project in self.config.project.iter().flatten() {
+            if project.name.is_empty() || project.name.contains('/') {
+                return Err(JanusError::Config(format!("Invalid project name: '{}'", project.name)));
+            }
+            if !projects.insert(&project.name) {
+                return Err(JanusError::Config(format!("Duplicate project name: {}", project.name)));
+            }
+        }
+        
         let mut names = std::collections::HashSet::new();
+        let mut fixed_ports = std::collections::HashMap::new();
         
         for process in &self.config.process {
             if !names.insert(&process.name) {
@@ -32,12 +103,519 @@ impl ConfigManager {
                 )));
             }
             
-            if process.command.trim().is_empty() {
+            if process.command.trim().is_empty() && process.image.is_none() {
                 return Err(JanusError::Config(format!(
                     "Empty command for process: {}",
                     process.name
                 )));
             }
+            
+            for endpoint in process.wait_for.iter().flatten() {
+                Endpoint::parse(endpoint).map_err(|e| {
+                    JanusError::Config(format!("Process {}: {}", process.name, e))
+                })?;
+            }
+            
+            if process.wait_for_path.iter().flatten().any(|path| path.trim().is_empty()) {
+                return Err(JanusError::Config(format!("wait_for_path for process {} cannot contain empty paths", process.name)));
+            }
+            
+            if process.cleanup_paths.iter().flatten().any(|path| path.trim().is_empty()) {
+                return Err(JanusError::Config(format!("cleanup_paths for process {} cannot contain empty paths", process.name)));
+            }
+            
+            if let Some(cpu) = process.cpu_affinity.iter().flatten().find(|cpu| **cpu >= scheduling::MAX_CPUS) {
+                return Err(JanusError::Config(format!(
+                    "Invalid cpu_affinity for process {}: CPU {} is out of range",
+                    process.name, cpu
+                )));
+            }
+            
+            if let Err(e) = scheduling::validate_policy(process.sched, process.sched_priority) {
+                return Err(JanusError::Config(format!(
+                    "Invalid scheduling for process {}: {}",
+                    process.name, e
+                )));
+            }
+            
+            if let Some(source) = &process.restart_script {
+                RestartScript::compile(source).map_err(|e| {
+                    JanusError::Config(format!("Invalid restart_script for process {}: {}", process.name, e))
+                })?;
+            }
+            
+            if let Some(limit) = process.memory_limit.as_deref().filter(|limit| memory::parse_size(limit).is_none()) {
+                return Err(JanusError::Config(format!(
+                    "Invalid memory_limit '{}' for process {}, expected a size such as 512M or 2G",
+                    limit, process.name
+                )));
+            }
+            
+            if process.max_open_fds == Some(0) {
+                return Err(JanusError::Config(format!(
+                    "Invalid max_open_fds for process {}, must be greater than 0",
+                    process.name
+                )));
+            }
+            
+            let has_heartbeat = process.watchdog_file.is_some()
+                || process.watchdog_socket.unwrap_or(false)
+                || process.watchdog_pattern.is_some();
+            if process.stop_timeout == Some(0) {
+                return Err(JanusError::Config(format!(
+                    "Invalid stop_timeout for process {}, must be greater than 0",
+                    process.name
+                )));
+            }
+            
+            match process.watchdog_timeout {
+                Some(0) => {
+                    return Err(JanusError::Config(format!(
+                        "Invalid watchdog_timeout for process {}, must be greater than 0",
+                        process.name
+                    )));
+                }
+                // notify 也可以用 WATCHDOG=1 發送心跳，但單獨使用時只報告就緒
+                Some(_) if !has_heartbeat && !process.notify.unwrap_or(false) => {
+                    return Err(JanusError::Config(format!(
+                        "watchdog_timeout for process {} needs watchdog_file, watchdog_socket, watchdog_pattern or notify",
+                        process.name
+                    )));
+                }
+                None if has_heartbeat => {
+                    return Err(JanusError::Config(format!(
+                        "Process {} configures a watchdog heartbeat without watchdog_timeout",
+                        process.name
+                    )));
+                }
+                _ => {}
+            }
+            
+            if let Some(pattern) = &process.watchdog_pattern {
+                Regex::new(pattern).map_err(|e| {
+                    JanusError::Config(format!("Invalid watchdog_pattern for process {}: {}", process.name, e))
+                })?;
+            }
+            
+            if let Some(every) = &process.every {
+                match parse_duration(every) {
+                    Ok(interval) if !interval.is_zero() => {}
+                    _ => {
+                        return Err(JanusError::Config(format!(
+                            "Invalid every '{}' for process {}, expected a non-zero duration such as 30s or 5m",
+                            every, process.name
+                        )));
+                    }
+                }
+                if process.auto_restart.unwrap_or(false) {
+                    return Err(JanusError::Config(format!(
+                        "Process {} cannot combine every with auto_restart",
+                        process.name
+                    )));
+                }
+            }
+            
+            if let Some(stable_after) = &process.stable_after {
+                if !parse_duration(stable_after).is_ok_and(|duration| !duration.is_zero()) {
+                    return Err(JanusError::Config(format!(
+                        "Invalid stable_after '{}' for process {}, expected a non-zero duration such as 10m",
+                        stable_after, process.name
+                    )));
+                }
+            }
+            
+            if let Some(schedule) = &process.schedule {
+                if let Err(e) = CronSchedule::parse(schedule, process.timezone.as_deref()) {
+                    return Err(JanusError::Config(format!(
+                        "Invalid schedule for process {}: {}",
+                        process.name, e
+                    )));
+                }
+                if process.auto_restart.unwrap_or(false) || process.every.is_some() {
+                    return Err(JanusError::Config(format!(
+                        "Process {} cannot combine schedule with auto_restart or every",
+                        process.name
+                    )));
+                }
+            } else if process.missed.is_some() {
+                return Err(JanusError::Config(format!("Process {}: missed requires schedule", process.name)));
+            } else if process.timezone.is_some()
+                && process.scheduled_restart.is_none()
+                && process.not_before.is_none()
+                && process.not_after.is_none()
+            {
+                return Err(JanusError::Config(format!(
+                    "Process {}: timezone requires schedule, scheduled_restart, not_before or not_after",
+                    process.name
+                )));
+            }
+            
+            if let Err(e) = StartWindow::parse(
+                process.not_before.as_deref(),
+                process.not_after.as_deref(),
+                process.timezone.as_deref(),
+            ) {
+                return Err(JanusError::Config(format!("Invalid start window for process {}: {}", process.name, e)));
+            }
+            
+            if let Some(schedule) = &process.scheduled_restart {
+                if let Err(e) = CronSchedule::parse(schedule, process.timezone.as_deref()) {
+                    return Err(JanusError::Config(format!(
+                        "Invalid scheduled_restart for process {}: {}",
+                        process.name, e
+                    )));
+                }
+                if process.schedule.is_some() || process.every.is_some() {
+                    return Err(JanusError::Config(format!(
+                        "Process {} cannot combine scheduled_restart with schedule or every",
+                        process.name
+                    )));
+                }
+            }
+            
+            if let Some(expected) = &process.expected_start_duration {
+                if parse_duration(expected).is_err() {
+                    return Err(JanusError::Config(format!(
+                        "Invalid expected_start_duration '{}' for process {}, expected a duration such as 90s",
+                        expected, process.name
+                    )));
+                }
+            }
+            
+            if let Some(jitter) = &process.jitter {
+                if parse_duration(jitter).is_err() {
+                    return Err(JanusError::Config(format!(
+                        "Invalid jitter '{}' for process {}, expected a duration such as 30s",
+                        jitter, process.name
+                    )));
+                }
+            }
+            
+            let scheduled = process.every.is_some() || process.schedule.is_some();
+            if !scheduled && (process.overlap.is_some() || process.jitter.is_some()) {
+                return Err(JanusError::Config(format!(
+                    "Process {}: overlap and jitter require every or schedule",
+                    process.name
+                )));
+            }
+            
+            if let Some(digest) = &process.expected_sha256 {
+                if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(JanusError::Config(format!(
+                        "expected_sha256 for process {} must be 64 hex characters",
+                        process.name
+                    )));
+                }
+            } else if process.checksum_mismatch.is_some() {
+                return Err(JanusError::Config(format!("Process {}: checksum_mismatch requires expected_sha256", process.name)));
+            }
+            
+            if process.exec_prefix.as_ref().is_some_and(|prefix| prefix.first().is_none_or(|wrapper| wrapper.trim().is_empty())) {
+                return Err(JanusError::Config(format!("exec_prefix for process {} must start with a command", process.name)));
+            }
+            
+            // 通過 shell 運行時 command 是命令行而不是可執行文件，無法校驗或監視
+            if process.shell.is_some() && (process.expected_sha256.is_some() || process.restart_on_binary_change == Some(true)) {
+                return Err(JanusError::Config(format!(
+                    "Process {}: expected_sha256 and restart_on_binary_change cannot be used with shell",
+                    process.name
+                )));
+            }
+            
+            // 容器中的進程由容器運行時啟動，可執行文件和進程屬性都在容器中
+            if let Some(image) = &process.image {
+                if image.trim().is_empty() {
+                    return Err(JanusError::Config(format!("image for process {} must not be empty", process.name)));
+                }
+                let unsupported = [
+                    ("shell", process.shell.is_some()),
+                    ("exec_prefix", process.exec_prefix.is_some()),
+                    ("expected_sha256", process.expected_sha256.is_some()),
+                    ("restart_on_binary_change", process.restart_on_binary_change == Some(true)),
+                    ("env_dirs", process.env_dirs.is_some()),
+                    ("notify", process.notify == Some(true)),
+                    ("memory_limit", process.memory_limit.is_some()),
+                    ("max_open_fds", process.max_open_fds.is_some()),
+                ];
+                if let Some((option, _)) = unsupported.iter().find(|(_, set)| *set) {
+                    return Err(JanusError::Config(format!(
+                        "Process {}: {} cannot be used with image; set the equivalent fields in container_host_config",
+                        process.name, option
+                    )));
+                }
+                if let Some(mapping) = process.container_ports.iter().flatten().find(|mapping| container::parse_port(mapping).is_none()) {
+                    return Err(JanusError::Config(format!(
+                        "Process {}: invalid container port {}, expected [ip:]host:container[/protocol]",
+                        process.name, mapping
+                    )));
+                }
+            } else if let Some((option, _)) = [
+                ("container_ports", process.container_ports.is_some()),
+                ("container_volumes", process.container_volumes.is_some()),
+                ("container_host_config", process.container_host_config.is_some()),
+            ]
+            .iter()
+            .find(|(_, set)| *set)
+            {
+                return Err(JanusError::Config(format!("Process {}: {} requires image", process.name, option)));
+            }
+            
+            if process.group.as_ref().is_some_and(|group| group.trim().is_empty()) {
+                return Err(JanusError::Config(format!("group for process {} must not be empty", process.name)));
+            }
+            
+            if process.pipe_size == Some(0) || process.read_buffer_size == Some(0) {
+                return Err(JanusError::Config(format!(
+                    "pipe_size and read_buffer_size for process {} must be greater than 0",
+                    process.name
+                )));
+            }
+            
+            // 原始模式不逐行讀取輸出，依賴逐行處理的選項無法生效
+            if process.log_mode == Some(LogMode::Raw) {
+                let line_options = [
+                    ("watchdog_pattern", process.watchdog_pattern.is_some()),
+                    ("merge_output", process.merge_output.unwrap_or(false)),
+                    ("output_encoding", process.output_encoding.is_some()),
+                    ("pipe_size", process.pipe_size.is_some()),
+                    ("read_buffer_size", process.read_buffer_size.is_some()),
+                    ("logging", process.logging.is_some()),
+                ];
+                if let Some((option, _)) = line_options.iter().find(|(_, set)| *set) {
+                    return Err(JanusError::Config(format!(
+                        "Process {}: {} cannot be used with log_mode = \"raw\"",
+                        process.name, option
+                    )));
+                }
+            }
+            
+            if let Some(logging) = &process.logging {
+                let mut rates = [logging.stdout_sample, logging.stderr_sample]
+                    .into_iter()
+                    .flatten()
+                    .chain(logging.level_sample.iter().flatten().map(|(_, rate)| *rate));
+                if rates.any(|rate| rate == 0) {
+                    return Err(JanusError::Config(format!(
+                        "Sample rates for process {} must be greater than 0",
+                        process.name
+                    )));
+                }
+                let mut levels = logging.level_sample.iter().flatten().map(|(level, _)| level);
+                if let Some(level) = levels.find(|level| normalize_level(level).is_none()) {
+                    return Err(JanusError::Config(format!(
+                        "Unknown level '{}' in level_sample for process {}, expected one of {}",
+                        level,
+                        process.name,
+                        LEVELS.join(", ")
+                    )));
+                }
+            }
+            
+            // 輸出按行讀取，編碼必須與 ASCII 兼容才能按換行符分割
+            if let Some(label) = &process.output_encoding {
+                match encoding_rs::Encoding::for_label(label.as_bytes()) {
+                    Some(encoding) if encoding.is_ascii_compatible() => {}
+                    _ => {
+                        return Err(JanusError::Config(format!(
+                            "Unsupported output_encoding '{}' for process {}",
+                            label, process.name
+                        )));
+                    }
+                }
+            }
+            
+            if let Some(PortConfig::Fixed(port)) = process.port {
+                if let Some(other) = fixed_ports.insert(port, &process.name) {
+                    return Err(JanusError::Config(format!(
+                        "Processes {} and {} both use port {}",
+                        other, process.name, port
+                    )));
+                }
+            }
+            if process.port.is_some() {
+                let port_env = process.port_env.as_deref().unwrap_or(ports::DEFAULT_ENV);
+                if port_env.is_empty() || process.env.as_ref().is_some_and(|env| env.contains_key(port_env)) {
+                    return Err(JanusError::Config(format!(
+                        "Process {}: port_env must be a variable that is not also set in env",
+                        process.name
+                    )));
+                }
+            }
+            
+            for render in process.render.iter().flatten() {
+                if render.template.trim().is_empty() || render.target.trim().is_empty() {
+                    return Err(JanusError::Config(format!("render for process {} needs a template and a target", process.name)));
+                }
+                if let Some(mode) = render.mode.as_deref().filter(|mode| render::parse_mode(mode).is_none()) {
+                    return Err(JanusError::Config(format!(
+                        "Invalid render mode '{}' for process {}, expected octal permissions such as 0644",
+                        mode, process.name
+                    )));
+                }
+            }
+            
+            for (key, value) in process.env.iter().flatten() {
+                if let Some(Err(e)) = SecretRef::parse(value) {
+                    return Err(JanusError::Config(format!("Process {}: env {}: {}", process.name, key, e)));
+                }
+            }
+            
+            if process.env_dirs.iter().flatten().any(|dir| dir.trim().is_empty()) {
+                return Err(JanusError::Config(format!("env_dirs for process {} cannot contain empty paths", process.name)));
+            }
+            
+            let env_patterns = process.env_allow.iter().chain(&process.env_deny).flatten();
+            for pattern in env_patterns {
+                if let Err(e) = glob::Pattern::new(pattern) {
+                    return Err(JanusError::Config(format!(
+                        "Invalid env pattern '{}' for process {}: {}",
+                        pattern, process.name, e
+                    )));
+                }
+            }
+        }
+        
+        // 邊車只能掛在普通進程上，不能形成邊車的鏈
+        for process in &self.config.process {
+            let Some(main) = &process.sidecar_of else {
+                continue;
+            };
+            let main_config = self.config.process.iter().find(|other| &other.name == main);
+            match main_config {
+                None => {
+                    return Err(JanusError::Config(format!("Process {}: sidecar_of names unknown process {}", process.name, main)));
+                }
+                Some(_) if main == &process.name => {
+                    return Err(JanusError::Config(format!("Process {} cannot be a sidecar of itself", process.name)));
+                }
+                Some(main_config) if main_config.sidecar_of.is_some() => {
+                    return Err(JanusError::Config(format!(
+                        "Process {}: {} is itself a sidecar and cannot have sidecars",
+                        process.name, main
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+        
+        // 邊車在主進程之後啟動，與 requires 一起排序
+        let requires = self
+            .config
+            .process
+            .iter()
+            .map(|process| {
+                let mut requires = process.requires.clone().unwrap_or_default();
+                requires.extend(process.sidecar_of.clone());
+                (process.name.clone(), requires)
+            })
+            .collect();
+        dependency::start_order(&requires).map_err(JanusError::Config)?;
+        
+        let global = &self.config.global;
+        for (option, action) in [
+            ("sigusr1_action", &global.sigusr1_action),
+            ("sigusr2_action", &global.sigusr2_action),
+        ] {
+            if let Some(SignalAction::Restart(targets)) = action {
+                if let Some(unknown) = targets.iter().find(|target| !names.contains(target)) {
+                    return Err(JanusError::Config(format!(
+                        "Unknown process in {}: {}",
+                        option, unknown
+                    )));
+                }
+            }
+        }
+        
+        if global.shutdown_timeout == Some(0) {
+            return Err(JanusError::Config("shutdown_timeout must be at least 1 second".to_string()));
+        }
+        
+        if global.restart_rate_limit == Some(0) || global.restart_rate_window == Some(0) {
+            return Err(JanusError::Config("restart_rate_limit and restart_rate_window must be positive".to_string()));
+        }
+        
+        if let Some(control) = &global.control {
+            if control.listen.parse::<SocketAddr>().is_err() {
+                return Err(JanusError::Config(format!(
+                    "Invalid control listen address: {}",
+                    control.listen
+                )));
+            }
+        }
+        
+        if let Some(kubernetes) = &global.kubernetes_status {
+            if kubernetes.condition.is_none() && kubernetes.annotation.is_none() {
+                return Err(JanusError::Config(
+                    "kubernetes_status needs a condition, an annotation or both".to_string(),
+                ));
+            }
+            if let Some(url) = kubernetes.api_server.as_deref().filter(|url| http::Endpoint::parse(url).is_none()) {
+                return Err(JanusError::Config(format!(
+                    "Invalid kubernetes_status api_server '{}', expected an http:// or https:// URL",
+                    url
+                )));
+            }
+        }
+        
+        if let Some(range) = global.port_range.as_deref().filter(|range| ports::parse_range(range).is_none()) {
+            return Err(JanusError::Config(format!(
+                "Invalid port_range '{}', expected a range such as 20000-29999",
+                range
+            )));
+        }
+        
+        if let Some(address) = &global.grpc_listen {
+            let Ok(socket_address) = address.parse::<SocketAddr>() else {
+                return Err(JanusError::Config(format!("Invalid grpc_listen address: {}", address)));
+            };
+            // 管理接口可以停止進程和重新加載配置，監聽本機以外的地址時必須認證
+            if !socket_address.ip().is_loopback() && global.grpc_token.is_none() {
+                return Err(JanusError::Config(format!(
+                    "grpc_listen {} is not a loopback address, set grpc_token to authenticate clients",
+                    address
+                )));
+            }
+        }
+        
+        if let Some(path) = global.upgrade_executables.iter().flatten().find(|path| !Path::new(path).is_absolute()) {
+            return Err(JanusError::Config(format!("upgrade_executables must be absolute paths: {}", path)));
+        }
+        
+        match &global.grpc_readonly_listen {
+            Some(address) if address.parse::<SocketAddr>().is_err() => {
+                return Err(JanusError::Config(format!("Invalid grpc_readonly_listen address: {}", address)));
+            }
+            Some(address) if global.grpc_listen.as_ref() == Some(address) => {
+                return Err(JanusError::Config(format!(
+                    "grpc_readonly_listen must differ from grpc_listen ({})",
+                    address
+                )));
+            }
+            None if global.grpc_readonly_token.is_some() => {
+                return Err(JanusError::Config("grpc_readonly_token requires grpc_readonly_listen".to_string()));
+            }
+            _ => {}
+        }
+        
+        for plugin in global.log_plugins.iter().flatten() {
+            if plugin.fuel == Some(0) {
+                return Err(JanusError::Config(format!("Log plugin {}: fuel must be positive", plugin.path)));
+            }
+            if let Some(size) = plugin.max_memory.as_deref().filter(|size| memory::parse_size(size).is_none()) {
+                return Err(JanusError::Config(format!(
+                    "Log plugin {}: invalid max_memory '{}', expected a size such as 16M",
+                    plugin.path, size
+                )));
+            }
+        }
+        
+        for plugin in global.plugins.iter().flatten() {
+            if plugin.command.trim().is_empty() {
+                return Err(JanusError::Config("Plugin command cannot be empty".to_string()));
+            }
+            if plugin.timeout == Some(0) {
+                return Err(JanusError::Config(format!("Plugin {}: timeout must be at least 1 second", plugin.command)));
+            }
         }
         
         Ok(())
@@ -51,20 +629,127 @@ impl ConfigManager {
         &self.config.global
     }
     
-    fn load_config(config_path: &str) -> Result<Config> {
-        let config_content = match fs::read_to_string(config_path) {
-            Ok(content) => content,
-            Err(e) => {
-                return Err(JanusError::Config(format!(
-                    "Failed to read config file: {}",
-                    e
-                )))
-            }
+    pub fn get_projects(&self) -> &[ProjectConfig] {
+        self.config.project.as_deref().unwrap_or_default()
+    }
+    
+    pub fn get_config_path(&self) -> &str {
+        &self.config_path
+    }
+    
+    // 加載時配置文件內容的 SHA-256，用於識別正在使用的配置版本
+    pub fn get_config_hash(&self) -> &str {
+        &self.config_hash
+    }
+    
+    // 磁盤上的配置文件已被修改（或刪除）但尚未重新加載
+    pub fn is_changed_on_disk(&self) -> bool {
+        let main = match fs::read_to_string(&self.config_path) {
+            Err(e) if self.file_missing && e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            main => main,
         };
+        let contents: std::io::Result<Vec<String>> = std::iter::once(main)
+            .chain(self.project_paths.iter().map(fs::read_to_string))
+            .collect();
+        match contents {
+            Ok(contents) => Self::hash_content(&contents.join("\0")) != self.config_hash,
+            Err(_) => true,
+        }
+    }
+    
+    pub fn hash_content(content: &str) -> String {
+        Sha256::digest(content.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+    
+    // 從配置結構生成 JSON Schema，供編輯器和 CI 驗證使用
+    pub fn json_schema() -> Result<String> {
+        let schema = schemars::schema_for!(Config);
+        serde_json::to_string_pretty(&schema)
+            .map_err(|e| JanusError::Config(format!("Failed to generate config schema: {}", e)))
+    }
+    
+    fn project_paths(config_path: &str, projects: &[ProjectConfig]) -> Vec<PathBuf> {
+        let base = Path::new(config_path).parent().unwrap_or(Path::new(""));
+        projects.iter().map(|project| base.join(&project.config)).collect()
+    }
+    
+    // 進程名、requires 和 sidecar_of 加上項目前綴；項目的全局工作目錄和環境變量作為其進程的默認值
+    fn add_project(processes: &mut Vec<ProcessConfig>, project: &ProjectConfig, mut config: Config) -> Result<()> {
+        if config.project.is_some() {
+            return Err(JanusError::Config(format!(
+                "Project {} cannot define its own projects",
+                project.name
+            )));
+        }
         
-        match toml::from_str::<Config>(&config_content) {
+        Self::expand_env(&mut config.process, &config.global, config.env_presets.as_ref())
+            .map_err(|e| JanusError::Config(format!("Project {}: {}", project.name, e)))?;
+        let qualify = |name: &str| format!("{}/{}", project.name, name);
+        for mut process in config.process {
+            process.name = qualify(&process.name);
+            process.requires = process
+                .requires
+                .map(|names| names.iter().map(|name| qualify(name)).collect());
+            process.sidecar_of = process.sidecar_of.map(|name| qualify(&name));
+            if process.working_dir.is_none() {
+                process.working_dir = config.global.working_dir.clone();
+            }
+            processes.push(process);
+        }
+        
+        Ok(())
+    }
+    
+    // 合併每個進程的環境變量，後者覆蓋前者：global.env、env_presets 中列出的預設 (按列出的順序)、進程自身的 env
+    fn expand_env(
+        processes: &mut [ProcessConfig],
+        global: &GlobalConfig,
+        presets: Option<&HashMap<String, HashMap<String, String>>>,
+    ) -> Result<()> {
+        for process in processes {
+            let mut env = global.env.clone().unwrap_or_default();
+            for name in process.env_presets.iter().flatten() {
+                let preset = presets.and_then(|presets| presets.get(name)).ok_or_else(|| {
+                    JanusError::Config(format!("Process {}: unknown env preset '{}'", process.name, name))
+                })?;
+                env.extend(preset.clone());
+            }
+            if env.is_empty() {
+                continue;
+            }
+            env.extend(process.env.take().unwrap_or_default());
+            process.env = Some(env);
+        }
+        Ok(())
+    }
+    
+    fn read_config(config_path: &str) -> Result<String> {
+        fs::read_to_string(config_path).map_err(|e| {
+            JanusError::Config(format!("Failed to read config file: {}", e))
+        })
+    }
+    
+    fn parse_config(config_content: &str) -> Result<Config> {
+        // sops 加密的值和 [sops] 元數據
+        if config_content.contains("ENC[") || config_content.contains("[sops") {
+            return Self::parse_encrypted_config(config_content);
+        }
+        match toml::from_str::<Config>(config_content) {
             Ok(config) => Ok(config),
             Err(e) => Err(JanusError::Config(format!("Failed to parse config file: {}", e))),
         }
     }
+    
+    // 含有加密值的配置先解析為 TOML 值並解密，再轉換為配置結構
+    fn parse_encrypted_config(config_content: &str) -> Result<Config> {
+        let mut value: toml::Value = toml::from_str(config_content)
+            .map_err(|e| JanusError::Config(format!("Failed to parse config file: {}", e)))?;
+        secret::decrypt_config(&mut value)?;
+        value
+            .try_into()
+            .map_err(|e| JanusError::Config(format!("Failed to parse config file: {}", e)))
+    }
 }
@@ -1,13 +1,248 @@
+pub mod env;
+pub mod lint;
 pub mod manager;
+pub mod secret;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+// 收到 SIGHUP 時的行為
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SighupAction {
+    /// Reload the configuration file and reconcile processes
+    #[default]
+    Reload,
+    /// Forward SIGHUP to all running processes
+    Forward,
+}
+
+// 收到 SIGUSR1/SIGUSR2 時執行的動作
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignalAction {
+    /// Print the status report to stdout
+    DumpStatus,
+    /// Write a JSON diagnostic snapshot to dump_dir
+    DumpFile,
+    /// Restart the named processes
+    Restart(Vec<String>),
+}
+
+// 子進程的 Linux 調度策略
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedPolicy {
+    /// Default time-sharing policy (SCHED_OTHER)
+    Other,
+    /// CPU-bound batch work that should not preempt interactive tasks (SCHED_BATCH)
+    Batch,
+    /// Only run when nothing else wants the CPU (SCHED_IDLE)
+    Idle,
+    /// Real-time first-in first-out, requires sched_priority (SCHED_FIFO)
+    Fifo,
+    /// Real-time round-robin, requires sched_priority (SCHED_RR)
+    Rr,
+}
+
+// 錯過排程時間（主機休眠、janus 未運行）後的處理方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissedRunPolicy {
+    /// Drop missed runs and wait for the next scheduled time
+    Skip,
+    /// Run once as soon as possible, however many runs were missed
+    RunOnce,
+}
+
+// janus start 啟動進程的順序，依賴的進程總是先啟動
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartOrder {
+    /// Alphabetical by name
+    Name,
+    /// Processes with the longest expected_start_duration first, so slow boots overlap with the rest
+    SlowestFirst,
+}
+
+// 定時或 cron 運行到期時上一次運行仍未結束的處理方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlapPolicy {
+    /// Skip the new run
+    Skip,
+    /// Start the new run once the previous one exits; at most one run is queued
+    Queue,
+    /// Stop the previous run and start the new one
+    KillPrevious,
+}
+
+// 運行 command 的 shell
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellKind {
+    /// sh -c
+    Sh,
+    /// cmd /D /S /C
+    Cmd,
+    /// Windows PowerShell: powershell -NoProfile -NonInteractive -Command
+    Powershell,
+    /// PowerShell 7: pwsh -NoProfile -NonInteractive -Command
+    Pwsh,
+}
+
+// 進程輸出的處理方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogMode {
+    /// Read output line by line, add timestamps and prefixes, and write it to the logs
+    #[default]
+    Line,
+    /// Hand janus's own stdout/stderr (or the capture_to file) to the process without any per-line processing
+    Raw,
+}
+
+// 可執行文件的 SHA-256 與 expected_sha256 不符時的處理方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumMismatch {
+    /// Refuse to start the process
+    #[default]
+    Refuse,
+    /// Log a warning and start the process anyway
+    Warn,
+}
+
+// 進程監聽的端口：固定端口，或 "auto" 由 janus 從 global.port_range 中分配
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum PortConfig {
+    /// A fixed port, checked for collisions with other processes
+    Fixed(u16),
+    /// "auto" to get a free port from global.port_range
+    Auto(AutoPort),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoPort {
+    Auto,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct PluginConfig {
+    /// Executable to run for every process event
+    pub command: String,
+    /// Arguments passed to the executable
+    pub args: Option<Vec<String>>,
+    /// Seconds to wait for the plugin to answer before it is killed (default 5)
+    pub timeout: Option<u64>,
+}
+
+// 遠程控制監聽器：TCP + 雙向 TLS，只接受由 client_ca 簽發的客戶端證書
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ControlConfig {
+    /// Address to listen on, e.g. "0.0.0.0:7700"
+    pub listen: String,
+    /// PEM certificate chain presented to clients
+    pub cert: String,
+    /// PEM private key for cert
+    pub key: String,
+    /// PEM CA bundle that client certificates must chain to
+    pub client_ca: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct LogPluginConfig {
+    /// Path to the .wasm module
+    pub path: String,
+    /// Fuel (roughly instructions) the module may use per log entry (default 1000000)
+    pub fuel: Option<u64>,
+    /// Largest linear memory the module may grow to, such as "16M" (default 16M)
+    pub max_memory: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct KubernetesStatusConfig {
+    /// Pod condition type kept True while every process is healthy, e.g. "janus.io/healthy"; list it in the pod's readinessGates to gate traffic on it (needs patch on pods/status)
+    pub condition: Option<String>,
+    /// Pod annotation kept set to a health summary such as "healthy" or "unhealthy: web (Failed)" (needs patch on pods)
+    pub annotation: Option<String>,
+    /// API server URL (default: https://$KUBERNETES_SERVICE_HOST:$KUBERNETES_SERVICE_PORT)
+    pub api_server: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct GlobalConfig {
+    /// Default working directory for all processes
     pub working_dir: Option<String>,
+    /// Log level (debug/info/warn/error)
     pub log_level: Option<String>,
+    /// Environment variables shared by all processes
     pub env: Option<HashMap<String, String>>,
+    /// What to do when janus receives SIGHUP (default reload)
+    pub sighup_action: Option<SighupAction>,
+    /// Action to run when janus receives SIGUSR1
+    pub sigusr1_action: Option<SignalAction>,
+    /// Action to run when janus receives SIGUSR2
+    pub sigusr2_action: Option<SignalAction>,
+    /// Seconds janus gives all processes to stop, in reverse dependency order and each within its own stop_timeout, when it shuts down; processes still running afterwards are killed (default 10)
+    pub shutdown_grace: Option<u64>,
+    /// Overall deadline in seconds for shutting janus down; janus exits even if processes or log output are still pending (default 30)
+    pub shutdown_timeout: Option<u64>,
+    /// Directory for diagnostic snapshots (default: system temp directory)
+    pub dump_dir: Option<String>,
+    /// Directory where each process's output is also written as <name>.log
+    pub log_dir: Option<String>,
+    /// File that receives janus's console output once its stdout or stderr pipe is closed, e.g. when the log driver restarts (default: the output is dropped and counted)
+    pub log_failover_file: Option<String>,
+    /// Drop log lines older than this many seconds
+    pub log_max_age: Option<u64>,
+    /// Keep the log files under this total size in megabytes, dropping the oldest lines first
+    pub log_max_total_size: Option<u64>,
+    /// File that persists tasks queued with janus enqueue
+    pub queue_file: Option<String>,
+    /// How many queued tasks may run at the same time (default 1)
+    pub queue_concurrency: Option<usize>,
+    /// File that records the last handled cron run of each process, so missed runs are detected across restarts
+    pub schedule_state_file: Option<String>,
+    /// Remote control listener using mutual TLS, started by janus start
+    pub control: Option<ControlConfig>,
+    /// Address for the gRPC management API served by janus start, e.g. "127.0.0.1:7777"
+    pub grpc_listen: Option<String>,
+    /// Token that gRPC clients must send as "authorization: Bearer <token>"
+    pub grpc_token: Option<String>,
+    /// Absolute paths of other janus binaries that upgrade --exec PATH may switch to; without it only the running binary's own path is accepted
+    pub upgrade_executables: Option<Vec<String>>,
+    /// Address for a second, read-only gRPC API (status, logs, events and timeline) for monitoring sidecars, e.g. "0.0.0.0:7778"; start, stop, kill, maintenance and upgrade are refused there
+    pub grpc_readonly_listen: Option<String>,
+    /// Token that clients of grpc_readonly_listen must send as "authorization: Bearer <token>" (default none)
+    pub grpc_readonly_token: Option<String>,
+    /// Executables that receive process events as JSON on stdin and may answer with an action
+    pub plugins: Option<Vec<PluginConfig>>,
+    /// WASM modules that transform, filter or route every log entry, applied in order
+    pub log_plugins: Option<Vec<LogPluginConfig>>,
+    /// Ports handed out to processes with port = "auto", such as "20000-29999" (the default)
+    pub port_range: Option<String>,
+    /// JSON file kept up to date with the status, pid and port of every process, for other programs in the container
+    pub registry_file: Option<String>,
+    /// Print the config path, config hash, process count and listeners when janus start begins supervising
+    pub startup_banner: Option<bool>,
+    /// Report to systemd through $NOTIFY_SOCKET: READY=1 once janus start has started the processes and STOPPING=1 at shutdown, for units with Type=notify (Unix only, default false)
+    pub sd_notify: Option<bool>,
+    /// Maximum crash restarts across all processes within restart_rate_window; beyond it restarts of non-critical processes are paused (default unlimited)
+    pub restart_rate_limit: Option<u32>,
+    /// Window in seconds for restart_rate_limit (default 60)
+    pub restart_rate_window: Option<u64>,
+    /// JSON Lines file that keeps the recent lifecycle events shown by janus timeline across janus restarts
+    pub timeline_file: Option<String>,
+    /// Order in which janus start starts processes after their dependencies: name or slowest-first (default name)
+    pub start_order: Option<StartOrder>,
+    /// Docker/Podman API socket for processes with image, as a path or unix:// URL (default: $CONTAINER_HOST or $DOCKER_HOST, else the first of /run/podman/podman.sock, $XDG_RUNTIME_DIR/podman/podman.sock and /var/run/docker.sock that exists)
+    pub container_socket: Option<String>,
+    /// Report the aggregate health of the processes on the pod janus runs in, using its service account
+    pub kubernetes_status: Option<KubernetesStatusConfig>,
 }
 
 impl Default for GlobalConfig {
@@ -16,25 +251,244 @@ impl Default for GlobalConfig {
             working_dir: None,
             log_level: Some("info".to_string()),
             env: Some(HashMap::new()),
+            sighup_action: None,
+            sigusr1_action: None,
+            sigusr2_action: None,
+            shutdown_grace: None,
+            shutdown_timeout: None,
+            dump_dir: None,
+            log_dir: None,
+            log_failover_file: None,
+            log_max_age: None,
+            log_max_total_size: None,
+            queue_file: None,
+            queue_concurrency: None,
+            schedule_state_file: None,
+            control: None,
+            grpc_listen: None,
+            grpc_token: None,
+            upgrade_executables: None,
+            grpc_readonly_listen: None,
+            grpc_readonly_token: None,
+            plugins: None,
+            log_plugins: None,
+            port_range: None,
+            registry_file: None,
+            startup_banner: None,
+            sd_notify: None,
+            restart_rate_limit: None,
+            restart_rate_window: None,
+            timeline_file: None,
+            start_order: None,
+            container_socket: None,
+            kubernetes_status: None,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
 pub struct ProcessConfig {
+    /// Unique process name
     pub name: String,
+    /// Human-friendly name shown in log prefixes and status instead of the process name
+    pub display_name: Option<String>,
+    /// Short label such as an emoji, shown before the name in log prefixes and status
+    pub label: Option<String>,
+    /// Group used to select several processes at once, e.g. janus maintenance on --group backend
+    pub group: Option<String>,
+    /// Command to execute; with image, the command run in the container (default: the image's own)
+    #[serde(default)]
     pub command: String,
+    /// Command arguments
     pub args: Option<Vec<String>>,
+    /// Wrapper command and arguments prepended to every spawn, e.g. ["tini", "--"] or ["numactl", "-N0"]
+    pub exec_prefix: Option<Vec<String>>,
+    /// Run command as a command line of this shell (sh, cmd, powershell or pwsh); args are appended quoted for that shell
+    pub shell: Option<ShellKind>,
+    /// Experimental: run the process as a container of this image through the Docker/Podman API, e.g. "redis:7"; containers are named janus-<instance>-<name> and labeled io.janus.instance=<instance>, where the instance is derived from the config file path
+    pub image: Option<String>,
+    /// Published container ports as [ip:]host:container[/protocol], e.g. ["6379:6379"]
+    pub container_ports: Option<Vec<String>>,
+    /// Bind mounts as host:container[:options], e.g. ["/data/redis:/data"]
+    pub container_volumes: Option<Vec<String>>,
+    /// Extra HostConfig fields of the Docker Engine API create request, e.g. { Memory = 536870912, NetworkMode = "host" }
+    pub container_host_config: Option<HashMap<String, serde_json::Value>>,
+    /// Working directory, overrides the global one
     pub working_dir: Option<String>,
+    /// Environment variables, merged with the global ones; a value such as "vault:secret/data/app#password" is read from Vault on every start
     pub env: Option<HashMap<String, String>>,
+    /// Names of env_presets merged into the environment in order; later presets override earlier ones, and env overrides them all
+    pub env_presets: Option<Vec<String>>,
+    /// Directories whose files become environment variables (file name = key, contents = value), such as mounted Kubernetes secrets; read on every start and overridden by env
+    pub env_dirs: Option<Vec<String>>,
+    /// Glob patterns of janus environment variables the process may inherit (default all)
+    pub env_allow: Option<Vec<String>>,
+    /// Glob patterns of janus environment variables the process must not inherit
+    pub env_deny: Option<Vec<String>>,
+    /// Restart the process when it exits (default false)
     pub auto_restart: Option<bool>,
+    /// Maximum number of restarts (default unlimited)
     pub restart_limit: Option<u32>,
+    /// Seconds to wait before restarting (default 1)
     pub restart_delay: Option<u64>,
+    /// Maximum restarts within restart_budget_window before giving up (default unlimited)
+    pub restart_budget: Option<u32>,
+    /// Length of the restart budget window in seconds (default 60)
+    pub restart_budget_window: Option<u64>,
+    /// Reset restart_count and the restart budget once the process has run this long without exiting, such as "10m", so crashes long ago no longer count towards restart_limit
+    pub stable_after: Option<String>,
+    /// Rhai script run before each restart; return false to skip it, a number of seconds to delay it, or true for the usual restart_delay
+    pub restart_script: Option<String>,
+    /// Restart the process when its executable is replaced (default false)
+    pub restart_on_binary_change: Option<bool>,
+    /// Hex SHA-256 digest the executable must match before every start
+    pub expected_sha256: Option<String>,
+    /// What to do when the executable does not match expected_sha256 (default refuse)
+    pub checksum_mismatch: Option<ChecksumMismatch>,
+    /// Mark a process that exits 0 as Completed instead of restarting it (default false)
+    pub oneshot_keep_status: Option<bool>,
+    /// Exit codes of an intentional shutdown, such as 143 after SIGTERM; logged as clean exits and not counted against restart limits
+    pub ignore_exit_codes: Option<Vec<i32>>,
+    /// External endpoints (tcp://host:port or http://host:port/path) that must be reachable before starting
+    pub wait_for: Option<Vec<String>>,
+    /// Files or directories that must exist before starting, such as mounted volumes or secrets; relative paths are resolved against working_dir
+    pub wait_for_path: Option<Vec<String>>,
+    /// Stale files such as sockets and pid files that janus removes before each start and after the process exits; relative paths are resolved against working_dir
+    pub cleanup_paths: Option<Vec<String>>,
+    /// Seconds to wait for all wait_for endpoints, wait_for_path entries and required notify processes to be ready (default 60, or twice the longest expected_start_duration of the required processes)
+    pub wait_timeout: Option<u64>,
+    /// Other processes this process needs; they are started first and stop-one warns before stopping them
+    pub requires: Option<Vec<String>>,
+    /// Whether janus ready waits for this process (default true, false for every and schedule processes)
+    pub critical: Option<bool>,
+    /// Log a warning when starting takes at least this many seconds
+    pub slow_start_threshold: Option<u64>,
+    /// How long the process usually takes to become ready, such as "90s"; status shows the start as in progress until then
+    pub expected_start_duration: Option<String>,
+    /// Log a warning when stopping takes at least this many seconds
+    pub slow_stop_threshold: Option<u64>,
+    /// Seconds to wait after SIGTERM before a stop falls back to SIGKILL; unset stops with SIGKILL right away
+    pub stop_timeout: Option<u64>,
+    /// Capture stdout and stderr through one pipe so lines keep the order the process wrote them (logged as stdout)
+    pub merge_output: Option<bool>,
+    /// Character encoding of the process output, e.g. utf-8, latin1 or shift_jis (default utf-8)
+    pub output_encoding: Option<String>,
+    /// How output is handled: line (default) or raw passthrough without timestamps, prefixes or log files
+    pub log_mode: Option<LogMode>,
+    /// Capacity in bytes of the stdout/stderr pipes, raised so bursty output does not block the process (Linux only, default 64 KiB)
+    pub pipe_size: Option<usize>,
+    /// Size in bytes of the buffer used to read the process output (default 8 KiB)
+    pub read_buffer_size: Option<usize>,
+    /// CPUs the process may run on, e.g. [0, 1] (Linux only)
+    pub cpu_affinity: Option<Vec<usize>>,
+    /// Linux scheduling policy: other, batch, idle, fifo or rr
+    pub sched: Option<SchedPolicy>,
+    /// Real-time priority (1-99) for the fifo and rr policies
+    pub sched_priority: Option<u8>,
+    /// Write each run's full output to this file; {name} and {timestamp} are replaced
+    pub capture_to: Option<String>,
+    /// Only write output to capture_to instead of also logging it (default false)
+    pub capture_only: Option<bool>,
+    /// Directory to move core files into when the process dumps core; also lifts the core size limit for the process
+    pub core_dump_dir: Option<String>,
+    /// PID file of an instance started outside janus; while that process is alive janus supervises it instead of starting a new one
+    pub adopt_pid_file: Option<String>,
+    /// Memory limit for the process and everything it forks, such as "512M" or "2G"; the whole group is killed and restarted when it is exceeded
+    pub memory_limit: Option<String>,
+    /// Warn (log line and fds_high event) when the process has this many open file descriptors (default 90% of its RLIMIT_NOFILE soft limit; Linux only)
+    pub max_open_fds: Option<u64>,
+    /// Restart the process when no heartbeat arrives for this many seconds; needs watchdog_file, watchdog_socket or watchdog_pattern
+    pub watchdog_timeout: Option<u64>,
+    /// File the process touches as its heartbeat
+    pub watchdog_file: Option<String>,
+    /// Provide a datagram socket whose path is passed in JANUS_WATCHDOG_SOCKET; every datagram counts as a heartbeat (Unix only)
+    pub watchdog_socket: Option<bool>,
+    /// Regular expression; output lines that match it count as a heartbeat
+    pub watchdog_pattern: Option<String>,
+    /// Pass a systemd-compatible NOTIFY_SOCKET; READY=1 marks the process ready for the processes that require it, STATUS= is shown in status and WATCHDOG=1 counts as a heartbeat (Unix only, default false)
+    pub notify: Option<bool>,
+    /// How many of the last output lines to keep and record when the process exits unexpectedly (default 20, 0 disables)
+    pub crash_context_lines: Option<usize>,
+    /// Run the process on a fixed interval such as "30s" or "5m"; an interval is skipped while the previous run is still going
+    pub every: Option<String>,
+    /// With every, also run once when janus starts instead of waiting for the first interval (default true)
+    pub run_on_start: Option<bool>,
+    /// Cron expression such as "0 3 * * *" for when to run the process
+    pub schedule: Option<String>,
+    /// IANA time zone for schedule, scheduled_restart, not_before and not_after, such as "Europe/Berlin" (default local time)
+    pub timezone: Option<String>,
+    /// What to do when a scheduled run was missed: skip or run-once (default skip)
+    pub missed: Option<MissedRunPolicy>,
+    /// Cron expression such as "0 3 * * *" for gracefully restarting the running process, e.g. to recycle leaking services off-peak; skipped in maintenance mode
+    pub scheduled_restart: Option<String>,
+    /// What to do when an every or schedule run comes due while the previous run is still going: skip, queue or kill-previous (default skip)
+    pub overlap: Option<OverlapPolicy>,
+    /// Delay each every or schedule run by a random duration up to this, such as "30s"
+    pub jitter: Option<String>,
+    /// Do not start the process automatically before this time of day such as "18:00", or date such as "2026-11-01 06:00"; start-one is not affected
+    pub not_before: Option<String>,
+    /// Do not start the process automatically from this time of day or date on; with both times of day, "18:00" to "08:00" wraps past midnight
+    pub not_after: Option<String>,
+    /// Sampling of the output written to the log, to cut down very chatty processes
+    pub logging: Option<LoggingConfig>,
+    /// Template files rendered to their target before every start
+    pub render: Option<Vec<RenderConfig>>,
+    /// Port the process listens on: a number, or "auto" to get a free one; passed in port_env
+    pub port: Option<PortConfig>,
+    /// Environment variable that receives the port (default PORT)
+    pub port_env: Option<String>,
+    /// Main process this one is a sidecar of: it starts after the main process, stops with it and is restarted whenever the main process starts again
+    pub sidecar_of: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+// 啟動前渲染的模板文件，變量為進程的環境變量加上 vars
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct RenderConfig {
+    /// Template file with {{ NAME }} or {{ NAME:-default }} placeholders
+    pub template: String,
+    /// File the rendered template is written to
+    pub target: String,
+    /// Extra variables, overriding the environment of the process
+    pub vars: Option<HashMap<String, String>>,
+    /// Permissions of the target file in octal, such as "0600" (Unix only)
+    pub mode: Option<String>,
+}
+
+// 進程輸出的抽樣：每 N 行只記錄一行，級別抽樣優先於按流抽樣
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+pub struct LoggingConfig {
+    /// Log one in this many stdout lines, such as 100 (default 1, every line)
+    pub stdout_sample: Option<u32>,
+    /// Log one in this many stderr lines (default 1, every line)
+    pub stderr_sample: Option<u32>,
+    /// Log one in this many lines of a level, such as { debug = 100 }; the level is read from the start of each line and overrides the stream rate
+    pub level_sample: Option<HashMap<String, u32>>,
+}
+
+// 項目：從單獨的配置文件加載的一組進程，進程名加上 "項目名/" 前綴以避免衝突
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct ProjectConfig {
+    /// Project name, used as the prefix of its process names ("name/process")
+    pub name: String,
+    /// Config file of the project, relative to this file
+    pub config: String,
+}
+
+// 進程是否屬於名為 project 的項目
+pub fn in_project(project: &str, process_name: &str) -> bool {
+    process_name
+        .strip_prefix(project)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Config {
     #[serde(default)]
     pub global: GlobalConfig,
+    #[serde(default)]
     pub process: Vec<ProcessConfig>,
+    /// Other config files supervised as separate projects
+    pub project: Option<Vec<ProjectConfig>>,
+    /// Named sets of environment variables that processes include with env_presets
+    pub env_presets: Option<HashMap<String, HashMap<String, String>>>,
 }
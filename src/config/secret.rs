@@ -0,0 +1,264 @@
+// 兼容 sops 的加密配置值：值的形式為 ENC[AES256_GCM,data:...,iv:...,tag:...,type:...]，用配置中 [sops] 表記錄的數據密鑰解密。
+// 數據密鑰用 age 加密給 [[sops.age]] 中的接收者，janus 與 sops 一樣從 SOPS_AGE_KEY 或 SOPS_AGE_KEY_FILE 讀取 age 身份。
+// 值所在的鍵路徑 (如 process:env:DB_PASSWORD:，不含數組下標) 作為附加數據參與認證，密文不能被挪到其他鍵下使用。
+// sops 的 MAC 按原文件的鍵順序計算，轉換為 TOML 後無法重現，因此不檢查
+use aes_gcm::aead::consts::U32;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::aes::Aes256;
+use aes_gcm::{AesGcm, Nonce};
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::error::{JanusError, Result};
+
+// age 身份的內容，每行一個 AGE-SECRET-KEY-...
+pub const AGE_KEY_ENV: &str = "SOPS_AGE_KEY";
+// age 身份文件的路徑，例如掛載到容器中的 secret；兩者都沒有設置時使用 sops 默認的 $XDG_CONFIG_HOME/sops/age/keys.txt
+pub const AGE_KEY_FILE_ENV: &str = "SOPS_AGE_KEY_FILE";
+
+// 配置中保存 sops 元數據的表
+pub const METADATA_KEY: &str = "sops";
+
+const PREFIX: &str = "ENC[AES256_GCM,";
+const KEY_LEN: usize = 32;
+// sops 使用 32 字節的 GCM 初始向量
+const IV_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+
+type SopsCipher = AesGcm<Aes256, U32>;
+
+// 加密配置值的數據密鑰
+pub struct DataKey([u8; KEY_LEN]);
+
+impl DataKey {
+    pub fn generate() -> Result<Self> {
+        let mut key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| JanusError::Config("Failed to generate a data key".to_string()))?;
+        Ok(Self(key))
+    }
+
+    // 用 age 身份解密 [sops] 表中任一接收者的數據密鑰
+    pub fn from_metadata(metadata: &toml::Value, identities: &[Box<dyn age::Identity>]) -> Result<Self> {
+        let entries: Vec<&str> = metadata
+            .get("age")
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.get("enc")?.as_str())
+            .collect();
+        if entries.is_empty() {
+            return Err(JanusError::Config(
+                "The sops metadata has no age recipients; janus only decrypts data keys encrypted with age".to_string(),
+            ));
+        }
+        for enc in entries {
+            let Ok(decryptor) = age::Decryptor::new(ArmoredReader::new(enc.trim().as_bytes())) else {
+                continue;
+            };
+            let Ok(mut reader) = decryptor.decrypt(identities.iter().map(|identity| identity.as_ref())) else {
+                continue;
+            };
+            let mut key = Vec::new();
+            if reader.read_to_end(&mut key).is_ok() {
+                if let Ok(key) = key.try_into() {
+                    return Ok(Self(key));
+                }
+            }
+        }
+        Err(JanusError::Config(
+            "None of the age identities can decrypt the sops data key".to_string(),
+        ))
+    }
+
+    // 把數據密鑰加密給 age 接收者 (age1...)，返回可以寫入配置的 [sops] 表
+    pub fn to_metadata(&self, recipients: &[String]) -> Result<toml::Value> {
+        let mut entries = Vec::new();
+        for recipient in recipients {
+            let parsed = age::x25519::Recipient::from_str(recipient)
+                .map_err(|e| JanusError::Config(format!("Invalid age recipient {}: {}", recipient, e)))?;
+            let encryptor = age::Encryptor::with_recipients(std::iter::once(&parsed as &dyn age::Recipient))
+                .map_err(|e| JanusError::Config(format!("Failed to encrypt the data key: {}", e)))?;
+            let armored = ArmoredWriter::wrap_output(Vec::new(), Format::AsciiArmor)?;
+            let mut writer = encryptor.wrap_output(armored)?;
+            writer.write_all(&self.0)?;
+            let enc = String::from_utf8(writer.finish()?.finish()?).expect("armored output is ASCII");
+
+            let mut entry = toml::Table::new();
+            entry.insert("recipient".to_string(), toml::Value::String(recipient.clone()));
+            entry.insert("enc".to_string(), toml::Value::String(enc));
+            entries.push(toml::Value::Table(entry));
+        }
+        let mut metadata = toml::Table::new();
+        metadata.insert("age".to_string(), toml::Value::Array(entries));
+        Ok(toml::Value::Table(metadata))
+    }
+
+    // path 是值在配置中的 sops 鍵路徑，解密時必須相同；加密後的類型為 str
+    pub fn encrypt(&self, plaintext: &str, path: &str) -> Result<String> {
+        let mut iv = [0u8; IV_LEN];
+        SystemRandom::new()
+            .fill(&mut iv)
+            .map_err(|_| JanusError::Config("Failed to generate an IV".to_string()))?;
+        let payload = Payload { msg: plaintext.as_bytes(), aad: path.as_bytes() };
+        let mut data = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&iv), payload)
+            .map_err(|_| JanusError::Config("Failed to encrypt value".to_string()))?;
+        let tag = data.split_off(data.len() - TAG_LEN);
+        Ok(format!(
+            "{}data:{},iv:{},tag:{},type:str]",
+            PREFIX,
+            STANDARD.encode(&data),
+            STANDARD.encode(iv),
+            STANDARD.encode(tag)
+        ))
+    }
+
+    // 按 type 字段還原為字符串、整數、浮點數或布爾值
+    pub fn decrypt(&self, value: &str, path: &str) -> Result<toml::Value> {
+        let invalid = || JanusError::Config("Malformed encrypted value".to_string());
+        let fields = value.strip_prefix(PREFIX).and_then(|rest| rest.strip_suffix(']')).ok_or_else(invalid)?;
+        let field = |name: &str| {
+            fields
+                .split(',')
+                .find_map(|field| field.strip_prefix(name)?.strip_prefix(':'))
+                .ok_or_else(invalid)
+        };
+        let decode = |name: &str| STANDARD.decode(field(name)?).map_err(|_| invalid());
+        let iv = decode("iv")?;
+        if iv.len() != IV_LEN {
+            return Err(invalid());
+        }
+        let mut data = decode("data")?;
+        data.extend(decode("tag")?);
+        let payload = Payload { msg: &data, aad: path.as_bytes() };
+        let plaintext = self.cipher().decrypt(Nonce::from_slice(&iv), payload).map_err(|_| {
+            JanusError::Config("Failed to decrypt value: wrong data key, corrupted data or encrypted for another path".to_string())
+        })?;
+        let text = String::from_utf8(plaintext).map_err(|_| invalid())?;
+        let typed = match field("type")? {
+            "str" => Some(toml::Value::String(text)),
+            "int" => text.parse().ok().map(toml::Value::Integer),
+            "float" => text.parse().ok().map(toml::Value::Float),
+            "bool" => parse_bool(&text).map(toml::Value::Boolean),
+            other => {
+                return Err(JanusError::Config(format!("Unsupported encrypted value type: {}", other)));
+            }
+        };
+        typed.ok_or_else(invalid)
+    }
+
+    fn cipher(&self) -> SopsCipher {
+        SopsCipher::new_from_slice(&self.0).expect("key has the right length")
+    }
+}
+
+// 與 Go 的 strconv.ParseBool 相同，sops 把布爾值寫為 True/False
+fn parse_bool(text: &str) -> Option<bool> {
+    match text {
+        "1" | "t" | "T" | "true" | "TRUE" | "True" => Some(true),
+        "0" | "f" | "F" | "false" | "FALSE" | "False" => Some(false),
+        _ => None,
+    }
+}
+
+// 以 . 分隔的鍵路徑 (如 process.env.DB_PASSWORD) 轉換為 sops 的形式 process:env:DB_PASSWORD:
+pub fn sops_path(dotted: &str) -> String {
+    dotted.split('.').map(|key| format!("{}:", key)).collect()
+}
+
+// 從環境變量或 sops 默認的位置讀取 age 身份，都沒有時返回空列表
+pub fn load_identities() -> Result<Vec<Box<dyn age::Identity>>> {
+    let content = if let Ok(content) = std::env::var(AGE_KEY_ENV) {
+        content
+    } else if let Ok(path) = std::env::var(AGE_KEY_FILE_ENV) {
+        fs::read_to_string(&path)
+            .map_err(|e| JanusError::Config(format!("Failed to read age identity file {}: {}", path, e)))?
+    } else {
+        match default_identity_file().and_then(|path| fs::read_to_string(path).ok()) {
+            Some(content) => content,
+            None => return Ok(Vec::new()),
+        }
+    };
+    age::IdentityFile::from_buffer(content.as_bytes())
+        .map_err(|e| JanusError::Config(format!("Invalid age identity: {}", e)))?
+        .into_identities()
+        .map_err(|e| JanusError::Config(format!("Invalid age identity: {}", e)))
+}
+
+fn default_identity_file() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("sops/age/keys.txt"))
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+// 配置中有加密值時取出 [sops] 表並解密所有值；沒有加密值時只移除 [sops] 表
+pub fn decrypt_config(value: &mut toml::Value) -> Result<()> {
+    let metadata = value.as_table_mut().and_then(|table| table.remove(METADATA_KEY));
+    if !contains_encrypted(value) {
+        return Ok(());
+    }
+    let Some(metadata) = metadata else {
+        return Err(JanusError::Config(
+            "Config file contains encrypted values but no [sops] metadata with the data key".to_string(),
+        ));
+    };
+    let identities = load_identities()?;
+    if identities.is_empty() {
+        return Err(JanusError::Config(format!(
+            "Config file contains encrypted values; set {} or {} to an age identity that can decrypt them",
+            AGE_KEY_ENV, AGE_KEY_FILE_ENV
+        )));
+    }
+    let key = DataKey::from_metadata(&metadata, &identities)?;
+    decrypt_all(value, &key)
+}
+
+fn contains_encrypted(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::String(text) => is_encrypted(text),
+        toml::Value::Array(items) => items.iter().any(contains_encrypted),
+        toml::Value::Table(table) => table.values().any(contains_encrypted),
+        _ => false,
+    }
+}
+
+// 遞歸解密配置中的所有加密值
+pub fn decrypt_all(value: &mut toml::Value, key: &DataKey) -> Result<()> {
+    decrypt_value(value, key, "", "")
+}
+
+// location 用於在錯誤消息中指出是哪個值 (含數組下標)，path 是綁定到密文的 sops 鍵路徑
+fn decrypt_value(value: &mut toml::Value, key: &DataKey, location: &str, path: &str) -> Result<()> {
+    match value {
+        toml::Value::String(text) if is_encrypted(text) => {
+            *value = key.decrypt(text, path).map_err(|e| JanusError::Config(format!("{}: {}", location, e)))?;
+        }
+        toml::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                decrypt_value(item, key, &format!("{}[{}]", location, index), path)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (name, item) in table.iter_mut() {
+                let location = if location.is_empty() { name.to_string() } else { format!("{}.{}", location, name) };
+                decrypt_value(item, key, &location, &format!("{}{}:", path, name))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
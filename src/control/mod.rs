@@ -0,0 +1,173 @@
+pub mod tls;
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::{watch, Mutex};
+use tokio_rustls::TlsAcceptor;
+
+use crate::config::ControlConfig;
+use crate::error::Result;
+use crate::msg;
+use crate::process::manager::ProcessManager;
+use crate::process::registry::Registry;
+use crate::process::snapshot::StatusSnapshot;
+
+// 單個請求的最大長度，超出後關閉連接
+pub const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+// 客戶端必須在此時間內完成 TLS 握手
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 控制協議：每行一個 JSON 請求，每個請求對應一行 JSON 響應
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Status,
+    // 與 global.registry_file 內容相同的進程註冊表
+    Registry,
+    Restart { name: String },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    fn ok(result: Option<serde_json::Value>) -> Self {
+        Self { ok: true, result, error: None }
+    }
+
+    fn error(error: impl ToString) -> Self {
+        Self { ok: false, result: None, error: Some(error.to_string()) }
+    }
+}
+
+pub struct ControlListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+}
+
+impl ControlListener {
+    // 在進入監控循環前綁定端口並加載證書，配置錯誤時 start 直接失敗
+    pub async fn bind(config: &ControlConfig) -> Result<Self> {
+        let acceptor = tls::acceptor(config)?;
+        let listener = TcpListener::bind(&config.listen).await?;
+        Ok(Self { listener, acceptor })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub async fn run(self, manager: Arc<Mutex<ProcessManager>>) {
+        let status = manager.lock().await.subscribe_status();
+        loop {
+            let (stream, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("{}", msg!("control.accept_failed", error = e));
+                    continue;
+                }
+            };
+
+            let acceptor = self.acceptor.clone();
+            let manager = manager.clone();
+            let status = status.clone();
+            tokio::spawn(async move {
+                let result = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => handle_connection(stream, manager, status).await,
+                    Ok(Err(e)) => Err(e),
+                    Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "TLS handshake timed out")),
+                };
+                if let Err(e) = result {
+                    eprintln!("{}", msg!("control.connection_failed", peer = peer, error = e));
+                }
+            });
+        }
+    }
+}
+
+// 與傳輸層無關，任何雙向字節流都可以複用同一套協議
+pub async fn handle_connection<S>(
+    stream: S,
+    manager: Arc<Mutex<ProcessManager>>,
+    status: watch::Receiver<Arc<StatusSnapshot>>,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        let read = (&mut reader).take(MAX_REQUEST_BYTES).read_line(&mut line).await?;
+        if read == 0 {
+            return Ok(());
+        }
+
+        let too_long = !line.ends_with('\n') && read as u64 == MAX_REQUEST_BYTES;
+        let request = line.trim();
+        if request.is_empty() && !too_long {
+            continue;
+        }
+
+        let response = if too_long {
+            Response::error(format!("Request exceeds {} bytes", MAX_REQUEST_BYTES))
+        } else {
+            match serde_json::from_str::<Request>(request) {
+                Ok(request) => dispatch(request, &manager, &status).await,
+                Err(e) => Response::error(format!("Invalid request: {}", e)),
+            }
+        };
+
+        let mut output = serde_json::to_string(&response)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        output.push('\n');
+        writer.write_all(output.as_bytes()).await?;
+        writer.flush().await?;
+
+        if too_long {
+            return Ok(());
+        }
+    }
+}
+
+async fn dispatch(
+    request: Request,
+    manager: &Mutex<ProcessManager>,
+    status: &watch::Receiver<Arc<StatusSnapshot>>,
+) -> Response {
+    match request {
+        // 讀取最近發布的快照，其他請求正在停止或重啟進程時也能立即返回
+        Request::Status => {
+            let snapshot = status.borrow().clone();
+            match serde_json::to_value(&*snapshot) {
+                Ok(snapshot) => Response::ok(Some(snapshot)),
+                Err(e) => Response::error(e),
+            }
+        }
+        Request::Registry => {
+            let registry = Registry::from_snapshot(&status.borrow());
+            match serde_json::to_value(&registry) {
+                Ok(registry) => Response::ok(Some(registry)),
+                Err(e) => Response::error(e),
+            }
+        }
+        Request::Restart { name } => match ProcessManager::restart_when_ready(manager, &name).await {
+            Ok(()) => Response::ok(None),
+            Err(e) => Response::error(e),
+        },
+    }
+}
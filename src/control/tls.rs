@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+use crate::config::ControlConfig;
+use crate::error::{JanusError, Result};
+
+// 構建要求客戶端證書的 TLS 服務端：沒有由 client_ca 簽發的證書，握手直接失敗
+pub fn acceptor(config: &ControlConfig) -> Result<TlsAcceptor> {
+    let certs = read_certs(&config.cert)?;
+    let key = PrivateKeyDer::from_pem_file(&config.key)
+        .map_err(|e| JanusError::Config(format!("Failed to read control key {}: {}", config.key, e)))?;
+
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(&config.client_ca)? {
+        roots.add(cert).map_err(|e| {
+            JanusError::Config(format!("Invalid client CA in {}: {}", config.client_ca, e))
+        })?;
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map_err(|e| JanusError::Config(format!("Invalid client CA in {}: {}", config.client_ca, e)))?;
+
+    let server_config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| JanusError::Config(format!("Failed to configure control TLS: {}", e)))?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)
+        .map_err(|e| JanusError::Config(format!("Invalid control certificate {}: {}", config.cert, e)))?;
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+fn read_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+        .map_err(|e| JanusError::Config(format!("Failed to read certificates {}: {}", path, e)))?;
+
+    if certs.is_empty() {
+        return Err(JanusError::Config(format!("No certificates found in {}", path)));
+    }
+    Ok(certs)
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Local};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::Channel;
+use tonic::{Request, Status, Streaming};
+
+use crate::error::{JanusError, Result};
+use crate::logging::{LogEntry, LogType};
+
+use super::proto::{
+    KillRequest, ListProcessesRequest, LogLine, LogStream, MaintenanceRequest, ProcessEvent, ProcessInfo, ProcessRequest,
+    ReloadRequest, StreamEventsRequest, StreamLogsRequest, TimelineEntry, TimelineRequest, UpgradeRequest, UpgradeResponse,
+};
+use super::JanusClient;
+
+// 遠程 janus 的客戶端，--host 指定時所有子命令通過它執行
+pub struct RemoteClient {
+    client: JanusClient<InterceptedService<Channel, TokenInterceptor>>,
+}
+
+impl RemoteClient {
+    // host 形如 tcp://10.0.0.5:7777，也接受 http://
+    pub async fn connect(host: &str, token: Option<&str>) -> Result<Self> {
+        let address = match host.split_once("://") {
+            Some(("tcp" | "http", address)) => format!("http://{}", address),
+            Some((scheme, _)) => {
+                return Err(JanusError::Command(format!("Unsupported host scheme: {}", scheme)))
+            }
+            None => format!("http://{}", host),
+        };
+
+        let token = token
+            .map(|token| {
+                format!("Bearer {}", token)
+                    .parse::<MetadataValue<Ascii>>()
+                    .map_err(|_| JanusError::Command("Invalid token".to_string()))
+            })
+            .transpose()?;
+
+        let channel = Channel::from_shared(address)
+            .map_err(|e| JanusError::Command(format!("Invalid host {}: {}", host, e)))?
+            .connect()
+            .await
+            .map_err(|e| JanusError::Command(format!("Failed to connect to {}: {}", host, e)))?;
+
+        Ok(Self {
+            client: JanusClient::with_interceptor(channel, TokenInterceptor { token }),
+        })
+    }
+
+    pub async fn list(&mut self) -> Result<Vec<ProcessInfo>> {
+        let response = self.client.list_processes(ListProcessesRequest {}).await.map_err(remote_error)?;
+        Ok(response.into_inner().processes)
+    }
+
+    // 升級次數，upgrade --exec 之後新的 janus 接管時增加
+    pub async fn generation(&mut self) -> Result<u64> {
+        let response = self.client.list_processes(ListProcessesRequest {}).await.map_err(remote_error)?;
+        Ok(response.into_inner().generation)
+    }
+
+    pub async fn start(&mut self, name: &str) -> Result<Option<ProcessInfo>> {
+        let request = ProcessRequest { name: name.to_string(), resume_after_secs: None };
+        let response = self.client.start(request).await.map_err(remote_error)?;
+        Ok(response.into_inner().process)
+    }
+
+    pub async fn stop(&mut self, name: &str) -> Result<Option<ProcessInfo>> {
+        self.stop_for(name, None).await
+    }
+
+    // 停止進程，指定 resume_after 時到期後由遠程 janus 重新啟動
+    pub async fn stop_for(&mut self, name: &str, resume_after: Option<std::time::Duration>) -> Result<Option<ProcessInfo>> {
+        let request = ProcessRequest {
+            name: name.to_string(),
+            resume_after_secs: resume_after.map(|resume_after| resume_after.as_secs()),
+        };
+        let response = self.client.stop(request).await.map_err(remote_error)?;
+        Ok(response.into_inner().process)
+    }
+
+    // 立即以 signal 終止進程，signal 在遠程 janus 所在的機器上解析
+    pub async fn kill(&mut self, name: &str, signal: &str) -> Result<Option<ProcessInfo>> {
+        let request = KillRequest { name: name.to_string(), signal: signal.to_string() };
+        let response = self.client.kill(request).await.map_err(remote_error)?;
+        Ok(response.into_inner().process)
+    }
+
+    // duration 為 None 時關閉維護模式；group 和 names 都為空時作用於所有進程
+    pub async fn set_maintenance(
+        &mut self,
+        names: Vec<String>,
+        group: Option<String>,
+        duration: Option<std::time::Duration>,
+    ) -> Result<Vec<ProcessInfo>> {
+        let request = MaintenanceRequest {
+            names,
+            group: group.unwrap_or_default(),
+            enabled: duration.is_some(),
+            duration_secs: duration.map(|duration| duration.as_secs()).unwrap_or_default(),
+        };
+        let response = self.client.set_maintenance(request).await.map_err(remote_error)?;
+        Ok(response.into_inner().processes)
+    }
+
+    // executable 為 None 時使用遠程 janus 當前的可執行文件路徑
+    pub async fn upgrade(&mut self, executable: Option<String>) -> Result<UpgradeResponse> {
+        let request = UpgradeRequest {
+            executable: executable.unwrap_or_default(),
+        };
+        let response = self.client.upgrade(request).await.map_err(remote_error)?;
+        Ok(response.into_inner())
+    }
+
+    // 返回重新加載後的配置版本
+    pub async fn reload(&mut self) -> Result<u64> {
+        let response = self.client.reload(ReloadRequest {}).await.map_err(remote_error)?;
+        Ok(response.into_inner().config_version)
+    }
+
+    // since 為 None 時返回遠程 janus 保留的全部事件
+    pub async fn timeline(&mut self, name: &str, since: Option<std::time::Duration>) -> Result<Vec<TimelineEntry>> {
+        let request = TimelineRequest {
+            name: name.to_string(),
+            since_secs: since.map(|since| since.as_secs()),
+        };
+        let response = self.client.timeline(request).await.map_err(remote_error)?;
+        Ok(response.into_inner().entries)
+    }
+
+    // 過濾在遠程 janus 中完成，只有選中的行經過網絡
+    pub async fn stream_logs(&mut self, request: StreamLogsRequest) -> Result<Streaming<LogLine>> {
+        let response = self
+            .client
+            .stream_logs(request)
+            .await
+            .map_err(remote_error)?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn stream_events(&mut self, names: Vec<String>) -> Result<Streaming<ProcessEvent>> {
+        let response = self
+            .client
+            .stream_events(StreamEventsRequest { names })
+            .await
+            .map_err(remote_error)?;
+        Ok(response.into_inner())
+    }
+}
+
+// 將遠程日誌行轉換為本地日誌條目，以便用相同的格式輸出
+pub fn log_entry(line: LogLine) -> LogEntry {
+    let log_type = match line.stream() {
+        LogStream::Stderr => LogType::Stderr,
+        LogStream::System => LogType::System,
+        LogStream::Stdout | LogStream::Unspecified => LogType::Stdout,
+    };
+    let timestamp = DateTime::parse_from_rfc3339(&line.timestamp)
+        .map(|timestamp| timestamp.with_timezone(&Local))
+        .unwrap_or_else(|_| Local::now());
+
+    LogEntry {
+        timestamp,
+        process_name: line.process,
+        log_type,
+        content: line.content,
+    }
+}
+
+pub fn remote_error(status: Status) -> JanusError {
+    JanusError::Command(status.message().to_string())
+}
+
+// 為每個請求附加 authorization: Bearer <token>
+#[derive(Clone)]
+pub struct TokenInterceptor {
+    token: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        if let Some(token) = &self.token {
+            request.metadata_mut().insert("authorization", token.clone());
+        }
+        Ok(request)
+    }
+}
@@ -0,0 +1,11 @@
+pub mod client;
+pub mod server;
+
+// 由 proto/janus.proto 生成的消息類型、服務端 trait 和客戶端
+pub mod proto {
+    tonic::include_proto!("janus.v1");
+}
+
+pub use client::RemoteClient;
+pub use proto::janus_client::JanusClient;
+pub use server::GrpcServer;
@@ -0,0 +1,519 @@
+use chrono::{Local, Utc};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{watch, Mutex};
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::codegen::{http, BoxFuture, Context, Poll, Service};
+use tonic::server::NamedService;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Response, Status};
+
+use crate::error::Result;
+use crate::logging::tail::{self, LogFilter, DEFAULT_BACKLOG_LINES};
+use crate::logging::{log_file_stem, LogEntry, LogType};
+use crate::msg;
+use crate::process::events::{self, ProcessEvent};
+use crate::process::{executable, handover};
+use crate::process::manager::ProcessManager;
+use crate::process::snapshot::{ProcessSnapshot, StatusSnapshot};
+use crate::process::timeline::{Timeline, TimelineEntry};
+use crate::signal::names;
+
+use super::proto::janus_server::{Janus, JanusServer};
+use super::proto::{
+    EventKind, KillRequest, ListProcessesRequest, ListProcessesResponse, LogLine, LogStream, MaintenanceRequest,
+    ProcessInfo, ProcessRequest, ProcessResponse, ReloadRequest, ReloadResponse, StreamEventsRequest, StreamLogsRequest, TimelineRequest, TimelineResponse,
+    UpgradeRequest, UpgradeResponse,
+};
+
+// 等待進程停止時檢查的間隔
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = std::result::Result<T, Status>> + Send>>;
+
+pub struct GrpcServer {
+    listener: TcpListener,
+    // 設置後，請求必須攜帶 authorization: Bearer <token>
+    token: Option<String>,
+    // 只提供查詢狀態、日誌、事件和時間線的接口，供不應該能停止進程的監控邊車使用
+    read_only: bool,
+}
+
+impl GrpcServer {
+    // 在進入監控循環前綁定端口，地址被佔用時 start 直接失敗
+    pub async fn bind(address: &str) -> Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        Ok(Self { listener, token: None, read_only: false })
+    }
+
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub async fn run(self, manager: Arc<Mutex<ProcessManager>>) {
+        let (status, timeline) = {
+            let manager = manager.lock().await;
+            (manager.subscribe_status(), manager.timeline())
+        };
+        let authenticated = self.token.is_some();
+        let expected = self.token.map(|token| format!("Bearer {}", token));
+        let authenticate = move |request: Request<()>| {
+            let Some(expected) = &expected else {
+                return Ok(request);
+            };
+            let provided = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok());
+            // 常數時間比較，不讓響應時間洩露令牌的前綴；ring 0.17 把該函數標記為棄用但仍然提供
+            #[allow(deprecated)]
+            let matches = provided.is_some_and(|provided| {
+                ring::constant_time::verify_slices_are_equal(provided.as_bytes(), expected.as_bytes()).is_ok()
+            });
+            if matches {
+                Ok(request)
+            } else {
+                Err(Status::unauthenticated("Invalid or missing token"))
+            }
+        };
+
+        let service = JanusServer::new(JanusService { manager, status, timeline, authenticated });
+        let mut builder = tonic::transport::Server::builder();
+        let router = if self.read_only {
+            builder.add_service(InterceptedService::new(ReadOnly(service), authenticate))
+        } else {
+            builder.add_service(InterceptedService::new(service, authenticate))
+        };
+        let result = router.serve_with_incoming(TcpListenerStream::new(self.listener)).await;
+        if let Err(e) = result {
+            eprintln!("{}", msg!("grpc.failed", error = e));
+        }
+    }
+}
+
+// 只讀端點允許調用的方法；之後新增的方法在只讀端點上默認不可用
+const READ_ONLY_METHODS: &[&str] = &["ListProcesses", "StreamLogs", "StreamEvents", "Timeline"];
+
+// 按請求路徑放行 READ_ONLY_METHODS，其他方法直接返回 PERMISSION_DENIED，不會到達 JanusService
+#[derive(Clone)]
+struct ReadOnly<S>(S);
+
+impl<S: NamedService> NamedService for ReadOnly<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S, B> Service<http::Request<B>> for ReadOnly<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<tonic::body::Body>, Error = Infallible> + NamedService,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let allowed = request
+            .uri()
+            .path()
+            .strip_prefix(&format!("/{}/", S::NAME))
+            .is_some_and(|method| READ_ONLY_METHODS.contains(&method));
+        if allowed {
+            return Box::pin(self.0.call(request));
+        }
+        let status = Status::permission_denied(
+            "This endpoint is read-only (global.grpc_readonly_listen), use global.grpc_listen to change processes",
+        );
+        Box::pin(std::future::ready(Ok(status.into_http())))
+    }
+}
+
+struct JanusService {
+    manager: Arc<Mutex<ProcessManager>>,
+    // 列出進程時讀取最近發布的快照，不等待正在進行的啟動或停止操作
+    status: watch::Receiver<Arc<StatusSnapshot>>,
+    timeline: Arc<std::sync::Mutex<Timeline>>,
+    // 是否配置了令牌；升級只接受經過認證的請求
+    authenticated: bool,
+}
+
+impl JanusService {
+    async fn process_response(&self, request: ProcessRequest, start: bool) -> std::result::Result<Response<ProcessResponse>, Status> {
+        let name = &request.name;
+        if self.manager.lock().await.get_process(name).is_none() {
+            return Err(Status::not_found(format!("Process not found: {}", name)));
+        }
+
+        // 通過接口停止的進程視為用戶停止，不會被自動重啟
+        let result = if start {
+            ProcessManager::start_when_ready(&self.manager, name).await
+        } else {
+            let resume_after = request.resume_after_secs.map(Duration::from_secs);
+            self.stop_manually(name, resume_after).await
+        };
+        result.map_err(|e| Status::failed_precondition(e.to_string()))?;
+
+        let manager = self.manager.lock().await;
+        let process = manager.get_process(name).map(ProcessSnapshot::from_process).map(process_info);
+        Ok(Response::new(ProcessResponse { process }))
+    }
+
+    // 等待設置了 stop_timeout 的進程退出時不持有鎖，期間 status 能看到 Stopping 狀態，kill 也能立即生效
+    async fn stop_manually(&self, name: &str, resume_after: Option<Duration>) -> Result<()> {
+        {
+            let mut manager = self.manager.lock().await;
+            manager.mark_stopped_manually(name, resume_after)?;
+            if !manager.begin_stop(name) {
+                return manager.stop_process(name).await;
+            }
+        }
+        loop {
+            tokio::time::sleep(STOP_POLL_INTERVAL).await;
+            if let Some(result) = self.manager.lock().await.poll_stop(name).await {
+                return result;
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Janus for JanusService {
+    async fn list_processes(
+        &self,
+        _request: Request<ListProcessesRequest>,
+    ) -> std::result::Result<Response<ListProcessesResponse>, Status> {
+        let snapshot = self.status.borrow().clone();
+        Ok(Response::new(ListProcessesResponse {
+            processes: snapshot.processes.iter().cloned().map(process_info).collect(),
+            config_version: snapshot.config_version,
+            generation: snapshot.generation,
+            config_reload_pending: snapshot.config_reload_pending,
+        }))
+    }
+
+    async fn start(&self, request: Request<ProcessRequest>) -> std::result::Result<Response<ProcessResponse>, Status> {
+        self.process_response(request.into_inner(), true).await
+    }
+
+    async fn stop(&self, request: Request<ProcessRequest>) -> std::result::Result<Response<ProcessResponse>, Status> {
+        self.process_response(request.into_inner(), false).await
+    }
+
+    async fn kill(&self, request: Request<KillRequest>) -> std::result::Result<Response<ProcessResponse>, Status> {
+        let request = request.into_inner();
+        let name = &request.name;
+        // 信號名稱在運行進程的機器上解析，兩端的信號編號可能不同
+        let signal = if request.signal.is_empty() { "KILL" } else { request.signal.as_str() };
+        let signal = names::signal_number(signal)
+            .ok_or_else(|| Status::invalid_argument(format!("Unknown signal: {}", request.signal)))?;
+
+        let mut manager = self.manager.lock().await;
+        if manager.get_process(name).is_none() {
+            return Err(Status::not_found(format!("Process not found: {}", name)));
+        }
+        manager
+            .kill_process_manually(name, signal)
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+
+        let process = manager.get_process(name).map(ProcessSnapshot::from_process).map(process_info);
+        Ok(Response::new(ProcessResponse { process }))
+    }
+
+    async fn set_maintenance(
+        &self,
+        request: Request<MaintenanceRequest>,
+    ) -> std::result::Result<Response<ListProcessesResponse>, Status> {
+        let request = request.into_inner();
+        let mut manager = self.manager.lock().await;
+        let names = if !request.group.is_empty() {
+            let names = manager.processes_in_group(&request.group);
+            if names.is_empty() {
+                return Err(Status::not_found(format!("No processes in group: {}", request.group)));
+            }
+            names
+        } else if !request.names.is_empty() {
+            request.names
+        } else {
+            manager.get_all_processes().keys().cloned().collect()
+        };
+
+        if let Some(missing) = names.iter().find(|name| manager.get_process(name).is_none()) {
+            return Err(Status::not_found(format!("Process not found: {}", missing)));
+        }
+
+        let duration = request.enabled.then(|| Duration::from_secs(request.duration_secs));
+        let mut processes = Vec::new();
+        for name in &names {
+            manager
+                .set_maintenance(name, duration)
+                .map_err(|e| Status::not_found(e.to_string()))?;
+            processes.extend(manager.get_process(name).map(ProcessSnapshot::from_process).map(process_info));
+        }
+        let snapshot = self.status.borrow().clone();
+        Ok(Response::new(ListProcessesResponse {
+            processes,
+            config_version: snapshot.config_version,
+            generation: snapshot.generation,
+            config_reload_pending: snapshot.config_reload_pending,
+        }))
+    }
+
+    async fn upgrade(&self, request: Request<UpgradeRequest>) -> std::result::Result<Response<UpgradeResponse>, Status> {
+        // janus 常以 root 或 PID 1 運行，升級等於讓它 exec 一個文件：必須配置令牌，
+        // 路徑只能是當前二進制文件的路徑或 global.upgrade_executables 中列出的文件
+        if !self.authenticated {
+            return Err(Status::permission_denied("Upgrade requires global.grpc_token"));
+        }
+        let request = request.into_inner();
+        let current = handover::current_executable().map_err(|e| Status::internal(e.to_string()))?;
+        let executable = if request.executable.is_empty() {
+            current
+        } else {
+            let requested = PathBuf::from(&request.executable);
+            let allowed = self
+                .manager
+                .lock()
+                .await
+                .config_snapshot()
+                .and_then(|config| config.get_global_config().upgrade_executables.clone())
+                .unwrap_or_default();
+            if requested != current && !allowed.iter().any(|path| Path::new(path) == requested) {
+                return Err(Status::permission_denied(format!(
+                    "{} is neither the running janus binary nor listed in global.upgrade_executables",
+                    requested.display()
+                )));
+            }
+            requested
+        };
+        // exec 失敗時舊的 janus 繼續運行，但客戶端已經收到了響應，因此先檢查明顯的錯誤
+        if !executable::is_executable(&executable) {
+            return Err(Status::failed_precondition(format!("Not an executable file: {}", executable.display())));
+        }
+
+        let mut manager = self.manager.lock().await;
+        manager.request_upgrade(executable.clone());
+        Ok(Response::new(UpgradeResponse {
+            executable: executable.display().to_string(),
+            generation: manager.generation() + 1,
+        }))
+    }
+
+    async fn reload(&self, _request: Request<ReloadRequest>) -> std::result::Result<Response<ReloadResponse>, Status> {
+        // 新配置無效時保留當前配置，錯誤返回給客戶端
+        let mut manager = self.manager.lock().await;
+        println!("{}", msg!("reload.requested"));
+        manager
+            .reload()
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        Ok(Response::new(ReloadResponse {
+            config_version: manager.config_version(),
+        }))
+    }
+
+    type StreamLogsStream = ResponseStream<LogLine>;
+
+    async fn stream_logs(
+        &self,
+        request: Request<StreamLogsRequest>,
+    ) -> std::result::Result<Response<Self::StreamLogsStream>, Status> {
+        let request = request.into_inner();
+        let content = Some(request.r#match.as_str()).filter(|pattern| !pattern.is_empty());
+        let filter = LogFilter::new(request.names, &request.include, &request.exclude, content)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let filter = Arc::new(filter);
+        let log_handler = self.manager.lock().await.get_log_handler().clone();
+
+        // 先訂閱再讀取文件中已有的行，兩者之間寫入的行不會丟失
+        let subscribed_at = Local::now();
+        let receiver = log_handler.subscribe();
+        let lines = match (request.lines, log_handler.log_dir()) {
+            (Some(0), _) | (None, None) => 0,
+            (Some(_), None) => {
+                return Err(Status::failed_precondition(
+                    "Showing existing lines requires global.log_dir on the supervisor, use --lines 0 to follow new lines only",
+                ))
+            }
+            (Some(lines), Some(_)) => lines as usize,
+            (None, Some(_)) => DEFAULT_BACKLOG_LINES,
+        };
+        let backlog = match log_handler.log_dir().map(Path::to_path_buf).filter(|_| lines > 0) {
+            Some(log_dir) => {
+                let filter = filter.clone();
+                tokio::task::spawn_blocking(move || {
+                    log_handler.flush();
+                    tail::read_backlog(&log_dir, &filter, lines).map(|(entries, _)| entries)
+                })
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| Status::internal(e.to_string()))?
+            }
+            None => Vec::new(),
+        };
+
+        // 訂閱之後寫入文件的行也會從廣播中收到，跳過已經作為歷史發送過的；文件中的時間戳精確到毫秒
+        let subscribed_at = subscribed_at - chrono::Duration::milliseconds(1);
+        let mut sent: HashSet<(String, String)> = backlog
+            .iter()
+            .filter(|entry| entry.timestamp >= subscribed_at)
+            .map(backlog_key)
+            .collect();
+
+        // 訂閱者落後太多時跳過丟失的行，繼續推送新日誌
+        let live = BroadcastStream::new(receiver).filter_map(move |entry| {
+            entry
+                .ok()
+                .filter(|entry| filter.matches_process(&entry.process_name) && filter.matches_content(&entry.content))
+                .filter(|entry| sent.is_empty() || !sent.remove(&backlog_key(entry)))
+                .map(|entry| Ok(log_line(entry)))
+        });
+        let stream = tokio_stream::iter(backlog.into_iter().map(|entry| Ok(log_line(entry)))).chain(live);
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    type StreamEventsStream = ResponseStream<super::proto::ProcessEvent>;
+
+    async fn stream_events(
+        &self,
+        request: Request<StreamEventsRequest>,
+    ) -> std::result::Result<Response<Self::StreamEventsStream>, Status> {
+        let names = request.into_inner().names;
+        let receiver = self.manager.lock().await.subscribe_events();
+
+        let stream = BroadcastStream::new(receiver).filter_map(move |event| {
+            event
+                .ok()
+                .filter(|event| selected(&names, &event.process))
+                .map(|event| Ok(process_event(event)))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn timeline(&self, request: Request<TimelineRequest>) -> std::result::Result<Response<TimelineResponse>, Status> {
+        let request = request.into_inner();
+        let entries = self.timeline.lock().unwrap().entries(&request.name);
+        // 已從配置中移除的進程仍可查看它的歷史
+        let known = self.status.borrow().processes.iter().any(|process| process.name == request.name);
+        if !known && entries.is_empty() {
+            return Err(Status::not_found(format!("Process not found: {}", request.name)));
+        }
+
+        let since = request
+            .since_secs
+            .map(|secs| Utc::now() - chrono::Duration::seconds(secs.min(i64::MAX as u64) as i64));
+        let entries = entries
+            .into_iter()
+            .filter(|entry| since.is_none_or(|since| entry.time().is_some_and(|time| time >= since)))
+            .map(timeline_entry)
+            .collect();
+        Ok(Response::new(TimelineResponse { entries }))
+    }
+}
+
+// 日誌文件中的一行，按文件名和行內容比較
+fn backlog_key(entry: &LogEntry) -> (String, String) {
+    (log_file_stem(&entry.process_name), entry.to_file_line())
+}
+
+// 未指定進程名時選中所有進程
+fn selected(names: &[String], process: &str) -> bool {
+    names.is_empty() || names.iter().any(|name| name == process)
+}
+
+fn process_info(snapshot: ProcessSnapshot) -> ProcessInfo {
+    ProcessInfo {
+        name: snapshot.name,
+        status: snapshot.status,
+        pid: snapshot.pid,
+        uptime_secs: snapshot.uptime_secs,
+        exit_code: snapshot.exit_code,
+        termination: snapshot.termination,
+        restart_count: snapshot.restart_count,
+        command: snapshot.command,
+        args: snapshot.args,
+        critical: snapshot.critical,
+        ready: snapshot.ready,
+        group: snapshot.group,
+        maintenance_secs: snapshot.maintenance_secs,
+        manual_stop: snapshot.manual_stop,
+        resume_secs: snapshot.resume_secs,
+        port: snapshot.port.map(u32::from),
+        stopping_secs: snapshot.stopping_secs,
+        stop_timeout_secs: snapshot.stop_timeout_secs,
+        waiting_for_window: snapshot.waiting_for_window,
+        starting_secs: snapshot.starting_secs,
+        expected_start_secs: snapshot.expected_start_secs,
+        image: snapshot.image,
+    }
+}
+
+fn log_line(entry: LogEntry) -> LogLine {
+    let stream = match entry.log_type {
+        LogType::Stdout => LogStream::Stdout,
+        LogType::Stderr => LogStream::Stderr,
+        LogType::System => LogStream::System,
+    };
+
+    LogLine {
+        timestamp: entry.timestamp.to_rfc3339(),
+        process: entry.process_name,
+        stream: stream.into(),
+        content: entry.content,
+    }
+}
+
+fn process_event(event: ProcessEvent) -> super::proto::ProcessEvent {
+    let kind = match event.kind {
+        events::EventKind::Started => EventKind::Started,
+        events::EventKind::StartFailed => EventKind::StartFailed,
+        events::EventKind::Exited => EventKind::Exited,
+        events::EventKind::Stopped => EventKind::Stopped,
+        events::EventKind::GaveUp => EventKind::GaveUp,
+        events::EventKind::OomKilled => EventKind::OomKilled,
+        events::EventKind::RestartPaused => EventKind::RestartPaused,
+        events::EventKind::FdsHigh => EventKind::FdsHigh,
+    };
+
+    super::proto::ProcessEvent {
+        timestamp: event.timestamp.to_rfc3339(),
+        process: event.process,
+        kind: kind.into(),
+        pid: event.pid,
+        exit_code: event.exit_code,
+        detail: event.detail,
+        memory_limit: event.memory_limit,
+        memory_peak: event.memory_peak,
+    }
+}
+
+fn timeline_entry(entry: TimelineEntry) -> super::proto::TimelineEntry {
+    super::proto::TimelineEntry {
+        timestamp: entry.timestamp,
+        event: entry.event,
+        pid: entry.pid,
+        exit_code: entry.exit_code,
+        detail: entry.detail,
+    }
+}
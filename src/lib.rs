@@ -0,0 +1,11 @@
+pub mod cli;
+pub mod config;
+pub mod control;
+pub mod error;
+pub mod grpc;
+pub mod logging;
+pub mod messages;
+pub mod process;
+pub mod signal;
+#[cfg(feature = "test-util")]
+pub mod test_util;
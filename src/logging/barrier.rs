@@ -0,0 +1,50 @@
+// 追蹤仍在讀取進程輸出的任務：每個讀取任務持有一個 ReaderGuard，讀到 EOF 或任務結束時釋放。
+// 關閉前等待所有任務讀完，退出時進程最後寫入的輸出就不會丟失
+use tokio::sync::watch;
+
+#[derive(Clone, Debug)]
+pub struct ReaderBarrier {
+    active: watch::Sender<usize>,
+}
+
+impl ReaderBarrier {
+    pub fn new() -> Self {
+        Self {
+            active: watch::channel(0).0,
+        }
+    }
+
+    // 在啟動讀取任務之前調用，這樣任務還沒開始運行時也會被等待
+    pub fn enter(&self) -> ReaderGuard {
+        self.active.send_modify(|active| *active += 1);
+        ReaderGuard {
+            active: self.active.clone(),
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        *self.active.borrow() == 0
+    }
+
+    // 等待所有讀取任務結束
+    pub async fn wait(&self) {
+        let mut active = self.active.subscribe();
+        let _ = active.wait_for(|active| *active == 0).await;
+    }
+}
+
+impl Default for ReaderBarrier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct ReaderGuard {
+    active: watch::Sender<usize>,
+}
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        self.active.send_modify(|active| *active -= 1);
+    }
+}
@@ -0,0 +1,55 @@
+// 把進程輸出錄製為 asciicast v2 文件，可以用 asciinema play 按原來的節奏回放：
+// 第一行是頭部，之後每行一個 [秒數, "o", 內容] 事件。janus 按行讀取輸出，因此時間精確到每一行
+use chrono::{DateTime, Local};
+use serde_json::json;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{JanusError, Result};
+
+use super::{LogEntry, LogType};
+
+// 回放終端的大小，與 asciinema 的默認值相同
+const WIDTH: u16 = 80;
+const HEIGHT: u16 = 24;
+
+pub struct CastRecorder {
+    file: File,
+    started: DateTime<Local>,
+    events: usize,
+}
+
+impl CastRecorder {
+    pub fn create(path: &Path, title: &str) -> Result<Self> {
+        let mut file = File::create(path).map_err(|e| {
+            JanusError::Command(format!("Failed to create recording {}: {}", path.display(), e))
+        })?;
+        let started = Local::now();
+        let header = json!({
+            "version": 2,
+            "width": WIDTH,
+            "height": HEIGHT,
+            "timestamp": started.timestamp(),
+            "title": title,
+        });
+        writeln!(file, "{}", header)?;
+        Ok(Self { file, started, events: 0 })
+    }
+
+    // 文件不經過緩衝，錄製被中斷時已寫入的事件仍然可以回放；janus 自身的消息以暗色顯示
+    pub fn record(&mut self, entry: &LogEntry) -> Result<()> {
+        let elapsed = (entry.timestamp - self.started).num_microseconds().unwrap_or_default().max(0);
+        let text = match entry.log_type {
+            LogType::System => format!("\x1b[2m[janus] {}\x1b[0m\r\n", entry.content),
+            LogType::Stdout | LogType::Stderr => format!("{}\r\n", entry.content),
+        };
+        writeln!(self.file, "{}", json!([elapsed as f64 / 1_000_000.0, "o", text]))?;
+        self.events += 1;
+        Ok(())
+    }
+
+    pub fn events(&self) -> usize {
+        self.events
+    }
+}
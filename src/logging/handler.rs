@@ -1,42 +1,180 @@
 use chrono::Local;
 use colored::*;
-use std::io::Write;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
 
-use crate::logging::{LogEntry, LogType};
+use crate::error::Result;
+use crate::logging::barrier::{ReaderBarrier, ReaderGuard};
+use crate::logging::retention::{self, RetentionPolicy};
+use crate::logging::wasm::{WasmLogPlugin, ROUTE_CONSOLE, ROUTE_FILE};
+use crate::logging::writer::{LogFiles, LogWriter, WriteStats};
+use crate::logging::{LogEntry, LogType, TIMESTAMP_FORMAT};
+use crate::msg;
 
 #[derive(Clone)]
-pub struct LogHandler {}
+pub struct LogHandler {
+    log_dir: Option<Arc<Path>>,
+    // 每個進程的日誌文件句柄，在所有克隆之間共享
+    files: LogFiles,
+    // 成批寫入終端和日誌文件的線程
+    writer: LogWriter,
+    // 進程名到日誌前綴中顯示名稱的映射
+    display_names: Arc<Mutex<HashMap<String, String>>>,
+    // 實時日誌的廣播通道，供遠程訂閱者讀取
+    live: broadcast::Sender<LogEntry>,
+    // 按順序處理每條日誌的 WASM 插件
+    plugins: Arc<Mutex<Vec<WasmLogPlugin>>>,
+    // 所有進程仍在運行的輸出讀取任務
+    readers: ReaderBarrier,
+}
+
+// 訂閱者處理過慢時最多緩存的日誌行數
+const LIVE_BUFFER: usize = 1024;
 
 impl LogHandler {
     pub fn new(_log_level: &str) -> Self {
         // 保留參數以保持 API 兼容性，但不存儲它
-        Self {}
+        let files: LogFiles = Arc::new(Mutex::new(HashMap::new()));
+        Self {
+            log_dir: None,
+            writer: LogWriter::spawn(files.clone()),
+            files,
+            display_names: Arc::new(Mutex::new(HashMap::new())),
+            live: broadcast::channel(LIVE_BUFFER).0,
+            plugins: Arc::new(Mutex::new(Vec::new())),
+            readers: ReaderBarrier::new(),
+        }
+    }
+    
+    pub fn with_plugins(mut self, plugins: Vec<WasmLogPlugin>) -> Self {
+        self.plugins = Arc::new(Mutex::new(plugins));
+        self
+    }
+    
+    // 設置後，每個進程的日誌還會追加寫入 log_dir/<name>.log
+    pub fn with_log_dir(mut self, log_dir: Option<PathBuf>) -> Self {
+        self.log_dir = log_dir.map(Arc::from);
+        self
+    }
+    
+    pub fn log_dir(&self) -> Option<&Path> {
+        self.log_dir.as_deref()
+    }
+    
+    // janus 的 stdout/stderr 管道關閉後，終端輸出改為追加寫入這個文件
+    pub fn with_failover_file(self, path: Option<PathBuf>) -> Self {
+        self.writer.set_failover_file(path);
+        self
+    }
+    
+    // 終端和日誌文件的寫入錯誤統計
+    pub fn write_stats(&self) -> WriteStats {
+        self.writer.stats()
+    }
+    
+    // 設置進程在日誌前綴中的顯示名稱，與進程名相同時不需要設置
+    pub fn set_display_name(&self, process_name: &str, display_name: Option<String>) {
+        let Ok(mut display_names) = self.display_names.lock() else {
+            return;
+        };
+        
+        match display_name {
+            Some(display_name) => display_names.insert(process_name.to_string(), display_name),
+            None => display_names.remove(process_name),
+        };
     }
     
     pub fn log(&self, process_name: &str, log_type: LogType, content: &str) {
-        let entry = LogEntry {
+        let mut entry = LogEntry {
             timestamp: Local::now(),
             process_name: process_name.to_string(),
             log_type: log_type.clone(),
             content: content.to_string(),
         };
         
-        let formatted = self.format_log_entry(&entry);
-        match log_type {
-            LogType::Stderr => {
-                let _ = std::io::stderr().write_all(formatted.as_bytes());
+        let Some(route) = self.apply_plugins(&mut entry) else {
+            return;
+        };
+        
+        // 寫入由寫入線程完成，這裡只格式化
+        if route & ROUTE_CONSOLE != 0 {
+            let formatted = self.format_log_entry(&entry);
+            match log_type {
+                LogType::Stderr => self.writer.stderr(&formatted),
+                _ => self.writer.stdout(&formatted),
             }
-            _ => {
-                let _ = std::io::stdout().write_all(formatted.as_bytes());
+        }
+        
+        if let Some(log_dir) = self.log_dir.as_ref().filter(|_| route & ROUTE_FILE != 0) {
+            self.writer.file(log_dir, &entry.process_name, &entry.to_file_line());
+        }
+        let _ = self.live.send(entry);
+    }
+    
+    // 依次交給每個插件處理，返回 None 表示日誌被丟棄；插件出錯時跳過它，日誌保持原樣
+    fn apply_plugins(&self, entry: &mut LogEntry) -> Option<i32> {
+        let Ok(mut plugins) = self.plugins.lock() else {
+            return Some(ROUTE_CONSOLE | ROUTE_FILE);
+        };
+        
+        let mut route = ROUTE_CONSOLE | ROUTE_FILE;
+        for plugin in plugins.iter_mut() {
+            match plugin.apply(entry) {
+                Ok(Some(target)) => route &= target,
+                Ok(None) => return None,
+                // 錯誤不能再寫入日誌，否則會再次經過插件
+                Err(e) => eprintln!("{}", msg!("log_plugin.failed", path = plugin.path(), error = e)),
             }
         }
+        Some(route)
+    }
+    
+    // 訂閱之後寫入的日誌
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.live.subscribe()
+    }
+    
+    // 每個讀取進程輸出的任務在讀到 EOF 之前持有
+    pub fn reader_guard(&self) -> ReaderGuard {
+        self.readers.enter()
+    }
+    
+    // 關閉屏障：等待所有讀取任務讀到 EOF，再刷新全部輸出
+    pub async fn drain(&self) {
+        self.readers.wait().await;
+        self.flush();
+    }
+    
+    // 等待寫入線程寫完已記錄的日誌，並把日誌文件寫入磁盤；在進程內讀取日誌文件或退出前調用
+    pub fn flush(&self) {
+        self.writer.flush();
+    }
+    
+    // 持有文件鎖裁剪日誌文件，避免與寫入交錯
+    pub fn apply_retention(&self, policy: &RetentionPolicy) -> Result<u64> {
+        let Some(log_dir) = &self.log_dir else {
+            return Ok(0);
+        };
+        let Ok(_files) = self.files.lock() else {
+            return Ok(0);
+        };
+        
+        retention::apply(log_dir, policy)
     }
     
     pub fn format_log_entry(&self, entry: &LogEntry) -> String {
-        let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        let timestamp = entry.timestamp.format(TIMESTAMP_FORMAT).to_string();
+        let name = self
+            .display_names
+            .lock()
+            .ok()
+            .and_then(|display_names| display_names.get(&entry.process_name).cloned())
+            .unwrap_or_else(|| entry.process_name.clone());
         let prefix = match entry.log_type {
-            LogType::Stdout => format!("[{}] [{}]", timestamp.blue(), entry.process_name.green()),
-            LogType::Stderr => format!("[{}] [{}]", timestamp.blue(), entry.process_name.red()),
+            LogType::Stdout => format!("[{}] [{}]", timestamp.blue(), name.green()),
+            LogType::Stderr => format!("[{}] [{}]", timestamp.blue(), name.red()),
             LogType::System => format!("[{}] [{}]", timestamp.blue(), "SYSTEM".yellow()),
         };
         
@@ -1,4 +1,19 @@
+pub mod barrier;
+pub mod cast;
 pub mod handler;
+pub mod retention;
+pub mod sampling;
+pub mod search;
+pub mod tail;
+pub mod wasm;
+pub mod writer;
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 日誌時間戳格式，終端輸出和日誌文件共用
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LogType {
@@ -7,10 +22,88 @@ pub enum LogType {
     System,
 }
 
-#[derive(Debug)]
+impl LogType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogType::Stdout => "stdout",
+            LogType::Stderr => "stderr",
+            LogType::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "stdout" => Some(LogType::Stdout),
+            "stderr" => Some(LogType::Stderr),
+            "system" => Some(LogType::System),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct LogEntry {
     pub timestamp: chrono::DateTime<chrono::Local>,
     pub process_name: String,
     pub log_type: LogType,
     pub content: String,
 }
+
+impl LogEntry {
+    // 日誌文件中的一行：時間戳、流類型、內容（進程名由文件名表示）
+    pub fn to_file_line(&self) -> String {
+        format!(
+            "{} {} {}\n",
+            self.timestamp.format(TIMESTAMP_FORMAT),
+            self.log_type.as_str(),
+            self.content
+        )
+    }
+
+    pub fn from_file_line(process_name: &str, line: &str) -> Option<Self> {
+        let mut parts = line.splitn(4, ' ');
+        let date = parts.next()?;
+        let time = parts.next()?;
+        let log_type = LogType::parse(parts.next()?)?;
+        let content = parts.next().unwrap_or_default();
+
+        let naive = NaiveDateTime::parse_from_str(&format!("{} {}", date, time), TIMESTAMP_FORMAT).ok()?;
+        let timestamp = Local.from_local_datetime(&naive).earliest()?;
+
+        Some(Self {
+            timestamp,
+            process_name: process_name.to_string(),
+            log_type,
+            content: content.to_string(),
+        })
+    }
+}
+
+// 日誌文件名（不含擴展名）；項目中的進程名含有 "/"，不能直接用作文件名
+pub fn log_file_stem(process_name: &str) -> String {
+    process_name.replace(['/', '\\'], "_")
+}
+
+pub fn log_file_path(log_dir: &Path, process_name: &str) -> PathBuf {
+    log_dir.join(format!("{}.log", log_file_stem(process_name)))
+}
+
+// 列出日誌目錄中的所有進程日誌文件及對應的進程名
+pub fn list_log_files(log_dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(log_dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some((name, path))
+        })
+        .collect();
+    files.sort();
+
+    files
+}
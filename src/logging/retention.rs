@@ -0,0 +1,152 @@
+use chrono::{DateTime, Local};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::GlobalConfig;
+use crate::error::Result;
+use crate::logging::{list_log_files, log_file_stem, LogEntry};
+
+// 日誌文件的保留策略，超出的最舊行會從文件開頭刪除
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    pub max_age: Option<Duration>,
+    pub max_total_size: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn from_global(global: &GlobalConfig) -> Self {
+        Self {
+            max_age: global.log_max_age.map(Duration::from_secs),
+            max_total_size: global.log_max_total_size.map(|mb| mb * 1024 * 1024),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_age.is_none() && self.max_total_size.is_none()
+    }
+}
+
+// 每個可解析行的時間戳和起始偏移量
+struct FileIndex {
+    path: PathBuf,
+    lines: Vec<(DateTime<Local>, u64)>,
+    len: u64,
+}
+
+// 按策略裁剪 log_dir 中的日誌文件，返回刪除的字節數
+pub fn apply(log_dir: &Path, policy: &RetentionPolicy) -> Result<u64> {
+    if policy.is_empty() {
+        return Ok(0);
+    }
+
+    let mut indexes = Vec::new();
+    for (_, path) in list_log_files(log_dir) {
+        indexes.push(index_file(path)?);
+    }
+
+    let mut cutoff = policy.max_age.and_then(|max_age| {
+        chrono::Duration::from_std(max_age).ok().map(|max_age| Local::now() - max_age)
+    });
+    if let Some(size_cutoff) = policy.max_total_size.and_then(|max| size_cutoff(&indexes, max)) {
+        cutoff = Some(cutoff.map_or(size_cutoff, |cutoff| cutoff.max(size_cutoff)));
+    }
+    let Some(cutoff) = cutoff else {
+        return Ok(0);
+    };
+
+    let mut removed = 0;
+    for index in &indexes {
+        // 文件按時間順序追加，保留第一條不早於 cutoff 的行及其後的內容
+        let offset = index
+            .lines
+            .iter()
+            .find(|(timestamp, _)| *timestamp >= cutoff)
+            .map_or(index.len, |(_, offset)| *offset);
+
+        if offset > 0 {
+            remove_prefix(&index.path, offset)?;
+            removed += offset;
+        }
+    }
+
+    Ok(removed)
+}
+
+// 清空日誌文件（而非刪除），使正在寫入的 janus 進程可以繼續追加
+pub fn purge(log_dir: &Path, name: Option<&str>) -> Result<Vec<String>> {
+    let mut purged = Vec::new();
+
+    for (file_name, path) in list_log_files(log_dir) {
+        if name.is_some_and(|name| log_file_stem(name) != file_name) {
+            continue;
+        }
+
+        OpenOptions::new().write(true).open(&path)?.set_len(0)?;
+        purged.push(file_name);
+    }
+
+    Ok(purged)
+}
+
+fn index_file(path: PathBuf) -> Result<FileIndex> {
+    let mut reader = BufReader::new(File::open(&path)?);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut offset = 0;
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 {
+            break;
+        }
+
+        if let Some(entry) = LogEntry::from_file_line("", line.trim_end_matches('\n')) {
+            lines.push((entry.timestamp, offset));
+        }
+        offset += read as u64;
+    }
+
+    Ok(FileIndex { path, lines, len: offset })
+}
+
+// 計算保留最新行且總大小不超過 max_total_size 時的時間界限
+fn size_cutoff(indexes: &[FileIndex], max_total_size: u64) -> Option<DateTime<Local>> {
+    let mut lines: Vec<(DateTime<Local>, u64)> = indexes
+        .iter()
+        .flat_map(|index| {
+            index.lines.iter().enumerate().map(|(i, (timestamp, offset))| {
+                let end = index.lines.get(i + 1).map_or(index.len, |(_, next)| *next);
+                (*timestamp, end - offset)
+            })
+        })
+        .collect();
+    lines.sort_by_key(|(timestamp, _)| std::cmp::Reverse(*timestamp));
+
+    let mut total = 0;
+    for (timestamp, len) in lines {
+        total += len;
+        if total > max_total_size {
+            // 時間戳精確到毫秒，同一毫秒內的行一起刪除
+            return Some(timestamp + chrono::Duration::milliseconds(1));
+        }
+    }
+
+    None
+}
+
+fn remove_prefix(path: &Path, offset: u64) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut rest = Vec::new();
+    file.read_to_end(&mut rest)?;
+
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&rest)?;
+    file.set_len(rest.len() as u64)?;
+
+    Ok(())
+}
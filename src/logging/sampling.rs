@@ -0,0 +1,70 @@
+// 按輸出流和日誌級別抽樣記錄進程輸出：每 N 行只記錄一行，減少非常囉嗦的調試輸出。
+// 只影響寫入日誌的行，capture_to、崩潰上下文和 watchdog_pattern 仍然看到每一行
+use std::collections::HashMap;
+
+use crate::config::LoggingConfig;
+
+use super::LogType;
+
+// 可以識別的級別名稱，warning、fatal、critical 分別視為 warn、error、error
+pub const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+// 只在每行開頭的這麼多個單詞中查找級別，避免消息正文中的單詞被誤認為級別
+const LEVEL_WORDS: usize = 12;
+
+pub struct LogSampler {
+    stream_rate: u64,
+    level_rates: HashMap<&'static str, u64>,
+    // 每個級別（None 表示按流抽樣）已經看到的行數
+    seen: HashMap<Option<&'static str>, u64>,
+}
+
+impl LogSampler {
+    // 沒有為這個流配置任何抽樣時返回 None
+    pub fn new(config: &LoggingConfig, stream: &LogType) -> Option<Self> {
+        let stream_rate = match stream {
+            LogType::Stderr => config.stderr_sample,
+            _ => config.stdout_sample,
+        }
+        .unwrap_or(1) as u64;
+        let level_rates: HashMap<&'static str, u64> = config
+            .level_sample
+            .iter()
+            .flatten()
+            .filter_map(|(level, rate)| Some((normalize_level(level)?, *rate as u64)))
+            .collect();
+        if stream_rate <= 1 && level_rates.values().all(|rate| *rate <= 1) {
+            return None;
+        }
+        Some(Self { stream_rate, level_rates, seen: HashMap::new() })
+    }
+
+    // 是否記錄這一行：有配置級別抽樣的級別按級別計數，其他行按流計數，每組的第一行總是保留
+    pub fn keep(&mut self, line: &str) -> bool {
+        let level = detect_level(line).filter(|level| self.level_rates.contains_key(level));
+        let rate = level.map_or(self.stream_rate, |level| self.level_rates[level]);
+        let seen = self.seen.entry(level).or_default();
+        *seen += 1;
+        (*seen - 1).is_multiple_of(rate.max(1))
+    }
+}
+
+// 配置或輸出中的級別名稱，不能識別時返回 None
+pub fn normalize_level(word: &str) -> Option<&'static str> {
+    match word.to_ascii_lowercase().as_str() {
+        "trace" => Some("trace"),
+        "debug" => Some("debug"),
+        "info" => Some("info"),
+        "warn" | "warning" => Some("warn"),
+        "error" | "fatal" | "critical" => Some("error"),
+        _ => None,
+    }
+}
+
+// 從 "DEBUG msg"、"[warn] msg"、"level=info" 或 {"level":"error"} 這類行首取出級別
+pub fn detect_level(line: &str) -> Option<&'static str> {
+    line.split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(LEVEL_WORDS)
+        .find_map(normalize_level)
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Local};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::{JanusError, Result};
+use crate::logging::tail::LogFilter;
+use crate::logging::{list_log_files, LogEntry};
+
+// 解析 "30s"、"15m"、"1h"、"2d" 形式的時長，不帶單位時按秒計算
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: u64 = number
+        .parse()
+        .map_err(|_| JanusError::Command(format!("Invalid duration '{}'", value)))?;
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(JanusError::Command(format!(
+                "Invalid duration unit in '{}', expected s, m, h or d",
+                value
+            )))
+        }
+    };
+
+    number
+        .checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| JanusError::Config(format!("Duration '{}' is too large", value)))
+}
+
+// 在 log_dir 的日誌文件中搜索符合過濾條件的行，按時間戳排序返回
+pub fn search(log_dir: &Path, filter: &LogFilter, since: Option<DateTime<Local>>) -> Result<Vec<LogEntry>> {
+    let mut matches = Vec::new();
+
+    for (name, path) in list_log_files(log_dir) {
+        if !filter.matches_process(&name) {
+            continue;
+        }
+
+        let reader = BufReader::new(File::open(&path)?);
+        for line in reader.lines() {
+            let line = line?;
+            let Some(entry) = LogEntry::from_file_line(&name, &line) else {
+                continue;
+            };
+
+            if since.is_some_and(|since| entry.timestamp < since) {
+                continue;
+            }
+            if filter.matches_content(&entry.content) {
+                matches.push(entry);
+            }
+        }
+    }
+
+    matches.sort_by_key(|entry| entry.timestamp);
+    Ok(matches)
+}
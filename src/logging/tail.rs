@@ -0,0 +1,198 @@
+use glob::Pattern;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::{JanusError, Result};
+use crate::logging::handler::LogHandler;
+use crate::logging::{list_log_files, log_file_stem, LogEntry};
+
+// 輪詢日誌文件的間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+// 讀取歷史日誌時，每個文件最多從末尾讀取的字節數
+const BACKLOG_BYTES: u64 = 256 * 1024;
+// 未指定時顯示的歷史行數，與 tail --lines 的默認值相同
+pub const DEFAULT_BACKLOG_LINES: usize = 10;
+
+// 按進程名和內容過濾日誌
+pub struct LogFilter {
+    names: Vec<String>,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    content: Option<Regex>,
+}
+
+impl LogFilter {
+    pub fn new(
+        names: Vec<String>,
+        include: &[String],
+        exclude: &[String],
+        content: Option<&str>,
+    ) -> Result<Self> {
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>> {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Pattern::new(&log_file_stem(pattern)).map_err(|e| {
+                        JanusError::Command(format!("Invalid name pattern '{}': {}", pattern, e))
+                    })
+                })
+                .collect()
+        };
+
+        let content = content
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    JanusError::Command(format!("Invalid match pattern '{}': {}", pattern, e))
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            names: names.iter().map(|name| log_file_stem(name)).collect(),
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+            content,
+        })
+    }
+
+    // 名稱按日誌文件名比較，日誌文件名和進程名都可以傳入
+    pub fn matches_process(&self, name: &str) -> bool {
+        let name = log_file_stem(name);
+        (self.names.is_empty() || self.names.contains(&name))
+            && (self.include.is_empty() || self.include.iter().any(|p| p.matches(&name)))
+            && !self.exclude.iter().any(|p| p.matches(&name))
+    }
+
+    pub fn matches_content(&self, content: &str) -> bool {
+        self.content.as_ref().is_none_or(|regex| regex.is_match(content))
+    }
+}
+
+// 跟隨 log_dir 中的進程日誌文件，合併輸出符合過濾條件的新行
+pub struct LogTailer {
+    log_dir: PathBuf,
+    filter: LogFilter,
+    log_handler: LogHandler,
+    offsets: HashMap<PathBuf, u64>,
+}
+
+impl LogTailer {
+    pub fn new(log_dir: PathBuf, filter: LogFilter, log_handler: LogHandler) -> Self {
+        Self {
+            log_dir,
+            filter,
+            log_handler,
+            offsets: HashMap::new(),
+        }
+    }
+
+    pub async fn follow(&mut self, backlog_lines: usize) -> Result<()> {
+        self.print_backlog(backlog_lines)?;
+
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.print_new_lines()?;
+        }
+    }
+
+    fn print_backlog(&mut self, backlog_lines: usize) -> Result<()> {
+        let (entries, offsets) = read_backlog(&self.log_dir, &self.filter, backlog_lines)?;
+        self.offsets.extend(offsets);
+        self.print(&entries);
+
+        Ok(())
+    }
+
+    fn print_new_lines(&mut self) -> Result<()> {
+        let mut entries = Vec::new();
+
+        for (name, path) in selected_files(&self.log_dir, &self.filter) {
+            let len = path.metadata()?.len();
+            let mut offset = self.offsets.get(&path).copied().unwrap_or(0);
+
+            // 文件被截斷或清理後從頭讀取
+            if len < offset {
+                offset = 0;
+            }
+            if len == offset {
+                self.offsets.insert(path, offset);
+                continue;
+            }
+
+            let (lines, consumed) = read_lines(&path, offset, len)?;
+            entries.extend(lines.iter().filter_map(|line| parse(&self.filter, &name, line)));
+            self.offsets.insert(path, offset + consumed);
+        }
+
+        entries.sort_by_key(|entry| entry.timestamp);
+        self.print(&entries);
+
+        Ok(())
+    }
+
+    fn print(&self, entries: &[LogEntry]) {
+        for entry in entries {
+            print!("{}", self.log_handler.format_log_entry(entry));
+        }
+    }
+}
+
+// 選中的日誌文件末尾已有的行，按時間排序後保留最後 lines 行；同時返回讀取時各文件的長度，之後從這裡跟隨新行
+pub fn read_backlog(log_dir: &Path, filter: &LogFilter, lines: usize) -> Result<(Vec<LogEntry>, HashMap<PathBuf, u64>)> {
+    let mut entries = Vec::new();
+    let mut offsets = HashMap::new();
+
+    for (name, path) in selected_files(log_dir, filter) {
+        let len = path.metadata()?.len();
+        let start = len.saturating_sub(BACKLOG_BYTES);
+        let (file_lines, _) = read_lines(&path, start, len)?;
+
+        // 從文件中間開始讀取時，第一行可能不完整
+        let skip = usize::from(start > 0);
+        entries.extend(file_lines.iter().skip(skip).filter_map(|line| parse(filter, &name, line)));
+        offsets.insert(path, len);
+    }
+
+    entries.sort_by_key(|entry| entry.timestamp);
+    let skip = entries.len().saturating_sub(lines);
+    entries.drain(..skip);
+
+    Ok((entries, offsets))
+}
+
+fn selected_files(log_dir: &Path, filter: &LogFilter) -> Vec<(String, PathBuf)> {
+    list_log_files(log_dir)
+        .into_iter()
+        .filter(|(name, _)| filter.matches_process(name))
+        .collect()
+}
+
+fn parse(filter: &LogFilter, name: &str, line: &str) -> Option<LogEntry> {
+    LogEntry::from_file_line(name, line).filter(|entry| filter.matches_content(&entry.content))
+}
+
+// 讀取 [start, end) 範圍內的完整行，返回行及實際消費的字節數（不含末尾未完成的行）
+fn read_lines(path: &Path, start: u64, end: u64) -> Result<(Vec<String>, u64)> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buffer = Vec::new();
+    file.take(end - start).read_to_end(&mut buffer)?;
+
+    let consumed = buffer
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let lines = String::from_utf8_lossy(&buffer[..consumed])
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    Ok((lines, consumed as u64))
+}
@@ -0,0 +1,155 @@
+// WASM 日誌插件：在日誌輸出之前轉換、過濾或分流每一條日誌，例如脫敏、補充字段或只寫入文件
+//
+// ABI 版本 1，模塊需要導出：
+//   memory
+//   janus_abi_version() -> i32     返回 1
+//   janus_alloc(len: i32) -> i32   在模塊內存中分配 len 字節，返回地址
+//   janus_process(stream: i32, name_ptr: i32, name_len: i32, content_ptr: i32, content_len: i32) -> i64
+//     stream 為 0 (stdout)、1 (stderr) 或 2 (system)；進程名和內容由 janus 寫入 janus_alloc 分配的內存
+//     返回負數表示丟棄這條日誌，0 表示保持不變，否則為 (ptr << 32) | len，指向替換後的 UTF-8 內容
+// 可以導入 janus.route(target: i32) 決定日誌的去向：1 只輸出到終端，2 只寫入日誌文件，3 兩者 (默認)
+use wasmi::{Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+
+use crate::config::LogPluginConfig;
+use crate::error::{JanusError, Result};
+use crate::process::memory::parse_size;
+
+use super::{LogEntry, LogType};
+
+pub const ABI_VERSION: i32 = 1;
+
+// 處理每條日誌可以消耗的燃料 (約等於執行的指令數)
+pub const DEFAULT_FUEL: u64 = 1_000_000;
+
+// 模塊線性內存的默認上限
+pub const DEFAULT_MAX_MEMORY: usize = 16 << 20;
+
+pub const ROUTE_CONSOLE: i32 = 1;
+pub const ROUTE_FILE: i32 = 2;
+pub const ROUTE_ALL: i32 = ROUTE_CONSOLE | ROUTE_FILE;
+
+struct PluginState {
+    limits: StoreLimits,
+    route: i32,
+}
+
+pub struct WasmLogPlugin {
+    path: String,
+    fuel: u64,
+    store: Store<PluginState>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    process: TypedFunc<(i32, i32, i32, i32, i32), i64>,
+}
+
+impl WasmLogPlugin {
+    pub fn load(config: &LogPluginConfig) -> Result<Self> {
+        let bytes = std::fs::read(&config.path)
+            .map_err(|e| JanusError::Config(format!("Failed to read log plugin {}: {}", config.path, e)))?;
+        Self::from_bytes(config, &bytes)
+    }
+
+    pub fn from_bytes(config: &LogPluginConfig, bytes: &[u8]) -> Result<Self> {
+        let invalid = |e: &dyn std::fmt::Display| JanusError::Config(format!("Invalid log plugin {}: {}", config.path, e));
+
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, bytes).map_err(|e| invalid(&e))?;
+
+        let max_memory = config
+            .max_memory
+            .as_deref()
+            .and_then(parse_size)
+            .map_or(DEFAULT_MAX_MEMORY, |size| size as usize);
+        let state = PluginState {
+            limits: StoreLimitsBuilder::new().memory_size(max_memory).build(),
+            route: ROUTE_ALL,
+        };
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap("janus", "route", |mut caller: Caller<'_, PluginState>, target: i32| {
+                caller.data_mut().route = target & ROUTE_ALL;
+            })
+            .map_err(|e| invalid(&e))?;
+
+        // 實例化本身 (包括 start 函數) 也受燃料限制
+        let fuel = config.fuel.unwrap_or(DEFAULT_FUEL);
+        store.set_fuel(fuel).map_err(|e| invalid(&e))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| invalid(&e))?;
+
+        let version = instance
+            .get_typed_func::<(), i32>(&store, "janus_abi_version")
+            .and_then(|version| version.call(&mut store, ()))
+            .map_err(|e| invalid(&e))?;
+        if version != ABI_VERSION {
+            return Err(invalid(&format!("unsupported ABI version {} (expected {})", version, ABI_VERSION)));
+        }
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| invalid(&"missing exported memory"))?;
+        let alloc = instance.get_typed_func(&store, "janus_alloc").map_err(|e| invalid(&e))?;
+        let process = instance.get_typed_func(&store, "janus_process").map_err(|e| invalid(&e))?;
+
+        Ok(Self {
+            path: config.path.clone(),
+            fuel,
+            store,
+            memory,
+            alloc,
+            process,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // 返回 None 表示丟棄這條日誌，否則返回插件選擇的去向；內容可能已被替換
+    pub fn apply(&mut self, entry: &mut LogEntry) -> std::result::Result<Option<i32>, String> {
+        self.store.set_fuel(self.fuel).map_err(|e| e.to_string())?;
+        self.store.data_mut().route = ROUTE_ALL;
+
+        let stream = match entry.log_type {
+            LogType::Stdout => 0,
+            LogType::Stderr => 1,
+            LogType::System => 2,
+        };
+        let (name_ptr, name_len) = self.write(entry.process_name.as_bytes())?;
+        let (content_ptr, content_len) = self.write(entry.content.as_bytes())?;
+
+        let result = self
+            .process
+            .call(&mut self.store, (stream, name_ptr, name_len, content_ptr, content_len))
+            .map_err(|e| e.to_string())?;
+        if result < 0 {
+            return Ok(None);
+        }
+        if result > 0 {
+            let ptr = (result >> 32) as u32 as usize;
+            let len = result as u32 as usize;
+            let mut buffer = vec![0; len];
+            self.memory
+                .read(&self.store, ptr, &mut buffer)
+                .map_err(|e| format!("invalid result ({}): {}", result, e))?;
+            entry.content = String::from_utf8_lossy(&buffer).into_owned();
+        }
+        Ok(Some(self.store.data().route))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> std::result::Result<(i32, i32), String> {
+        let len = i32::try_from(bytes.len()).map_err(|_| "log entry too large".to_string())?;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(|e| e.to_string())?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|e| format!("janus_alloc returned an invalid address {}: {}", ptr, e))?;
+        Ok((ptr, len))
+    }
+}
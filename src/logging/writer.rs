@@ -0,0 +1,459 @@
+// 日誌寫入線程：log() 只把格式化好的行追加到緩衝區，由一個線程成批寫入終端和日誌文件。
+// 每批中每個輸出只需一次 write 系統調用，只有緩衝區從空變為非空時才喚醒線程，
+// 輸出量很大的進程不會讓 janus 成為 CPU 瓶頸。
+// 寫入失敗的內容保留在有上限的積壓中，之後重試；janus 的 stdout/stderr 管道被關閉時 (例如日誌驅動重啟)
+// 改為寫入 global.log_failover_file，沒有配置時丟棄並計數。失敗的次數和丟棄的字節數見 WriteStats
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde::Serialize;
+
+// 緩衝區超過這個大小時 log() 等待寫入線程，輸出過快的進程因此受到背壓而不會無限佔用內存
+const MAX_PENDING_BYTES: usize = 8 * 1024 * 1024;
+
+// 被喚醒後稍等片刻再寫出，讓連續輸出的行合併成一批；flush 和退出時不等待
+const LINGER: Duration = Duration::from_millis(2);
+
+// 每個輸出寫入失敗後保留的最大字節數，超出時丟棄最舊的行
+const MAX_BACKLOG_BYTES: usize = 1024 * 1024;
+
+// 終端寫入遇到暫時性錯誤時的重試次數和間隔；積壓的內容每隔 RETRY_INTERVAL 重試一次
+const CONSOLE_RETRIES: u32 = 3;
+const CONSOLE_RETRY_DELAY: Duration = Duration::from_millis(10);
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+pub type LogFiles = Arc<Mutex<HashMap<String, File>>>;
+
+// 日誌輸出的寫入錯誤統計
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct WriteStats {
+    pub write_errors: u64,
+    // 積壓超出上限或終端不可用且沒有 failover 文件時丟棄的字節數
+    pub dropped_bytes: u64,
+    // 終端輸出已改為寫入 failover 文件
+    pub failed_over: bool,
+}
+
+#[derive(Default)]
+struct Counters {
+    write_errors: AtomicU64,
+    dropped_bytes: AtomicU64,
+    failed_over: AtomicBool,
+}
+
+impl Counters {
+    fn error(&self) {
+        self.write_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn dropped(&self, bytes: usize) {
+        self.dropped_bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+// 等待寫入的內容；文件內容按進程名分開，保持每個文件內的寫入順序
+#[derive(Default)]
+struct Pending {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    files: HashMap<String, (Arc<Path>, Vec<u8>)>,
+    bytes: usize,
+    // 已追加和已寫出的批次序號，flush 據此等待
+    queued: u64,
+    written: u64,
+    sync: bool,
+    closed: bool,
+}
+
+struct Shared {
+    pending: Mutex<Pending>,
+    counters: Counters,
+    // 終端管道關閉後改寫的文件
+    failover_file: Mutex<Option<PathBuf>>,
+    // 喚醒寫入線程
+    wake: Condvar,
+    // 每寫完一批通知等待空間或等待 flush 的調用者
+    done: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> Option<MutexGuard<'_, Pending>> {
+        self.pending.lock().ok()
+    }
+}
+
+// 最後一個 LogWriter 被釋放時通知寫入線程退出
+struct Owner(Arc<Shared>);
+
+impl Drop for Owner {
+    fn drop(&mut self) {
+        if let Some(mut pending) = self.0.lock() {
+            pending.closed = true;
+        }
+        self.0.wake.notify_one();
+    }
+}
+
+#[derive(Clone)]
+pub struct LogWriter {
+    owner: Arc<Owner>,
+}
+
+impl LogWriter {
+    // 啟動寫入線程；所有 LogWriter 克隆都被釋放後線程寫完剩餘內容並退出
+    pub fn spawn(files: LogFiles) -> Self {
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(Pending::default()),
+            counters: Counters::default(),
+            failover_file: Mutex::new(None),
+            wake: Condvar::new(),
+            done: Condvar::new(),
+        });
+        let thread_shared = shared.clone();
+        std::thread::Builder::new()
+            .name("janus-log-writer".to_string())
+            .spawn(move || run(&thread_shared, &files))
+            .expect("failed to spawn log writer thread");
+        Self {
+            owner: Arc::new(Owner(shared)),
+        }
+    }
+
+    pub fn set_failover_file(&self, path: Option<PathBuf>) {
+        if let Ok(mut failover_file) = self.owner.0.failover_file.lock() {
+            *failover_file = path;
+        }
+    }
+
+    pub fn stats(&self) -> WriteStats {
+        let counters = &self.owner.0.counters;
+        WriteStats {
+            write_errors: counters.write_errors.load(Ordering::Relaxed),
+            dropped_bytes: counters.dropped_bytes.load(Ordering::Relaxed),
+            failed_over: counters.failed_over.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn stdout(&self, line: &str) {
+        self.append(line.len(), |pending| pending.stdout.extend_from_slice(line.as_bytes()));
+    }
+
+    pub fn stderr(&self, line: &str) {
+        self.append(line.len(), |pending| pending.stderr.extend_from_slice(line.as_bytes()));
+    }
+
+    pub fn file(&self, log_dir: &Arc<Path>, process_name: &str, line: &str) {
+        self.append(line.len(), |pending| match pending.files.get_mut(process_name) {
+            Some((_, buffer)) => buffer.extend_from_slice(line.as_bytes()),
+            None => {
+                pending
+                    .files
+                    .insert(process_name.to_string(), (log_dir.clone(), line.as_bytes().to_vec()));
+            }
+        });
+    }
+
+    fn append(&self, len: usize, push: impl FnOnce(&mut Pending)) {
+        let shared = &self.owner.0;
+        let Some(mut pending) = shared.lock() else {
+            return;
+        };
+        while pending.bytes >= MAX_PENDING_BYTES {
+            pending = match shared.done.wait(pending) {
+                Ok(pending) => pending,
+                Err(_) => return,
+            };
+        }
+
+        let was_empty = pending.bytes == 0;
+        push(&mut pending);
+        pending.bytes += len;
+        if was_empty {
+            pending.queued += 1;
+            shared.wake.notify_one();
+        }
+    }
+
+    // 等待已追加的日誌全部寫出，並把日誌文件寫入磁盤
+    pub fn flush(&self) {
+        let shared = &self.owner.0;
+        let Some(mut pending) = shared.lock() else {
+            return;
+        };
+        // 即使沒有待寫內容也需要一批來完成 sync
+        pending.sync = true;
+        pending.queued += 1;
+        let target = pending.queued;
+        shared.wake.notify_one();
+        while pending.written < target {
+            pending = match shared.done.wait(pending) {
+                Ok(pending) => pending,
+                Err(_) => return,
+            };
+        }
+    }
+}
+
+fn run(shared: &Shared, files: &LogFiles) {
+    let mut sinks = Sinks::default();
+    loop {
+        let Some(mut pending) = shared.lock() else {
+            return;
+        };
+        while pending.bytes == 0 && !pending.sync && !pending.closed {
+            // 有積壓時定期醒來重試
+            if sinks.is_empty() {
+                pending = match shared.wake.wait(pending) {
+                    Ok(pending) => pending,
+                    Err(_) => return,
+                };
+                continue;
+            }
+            let (guard, timeout) = match shared.wake.wait_timeout(pending, RETRY_INTERVAL) {
+                Ok(result) => result,
+                Err(_) => return,
+            };
+            pending = guard;
+            if timeout.timed_out() {
+                break;
+            }
+        }
+        if pending.bytes == 0 && !pending.sync && pending.closed {
+            drop(pending);
+            sinks.write(shared, files, Batch::default());
+            return;
+        }
+        if pending.bytes != 0 && !pending.sync && !pending.closed {
+            drop(pending);
+            std::thread::sleep(LINGER);
+            pending = match shared.lock() {
+                Some(pending) => pending,
+                None => return,
+            };
+        }
+
+        let batch = pending.queued;
+        let lines = Batch {
+            sync: std::mem::take(&mut pending.sync),
+            stdout: std::mem::take(&mut pending.stdout),
+            stderr: std::mem::take(&mut pending.stderr),
+            files: std::mem::take(&mut pending.files),
+        };
+        pending.bytes = 0;
+        drop(pending);
+
+        sinks.write(shared, files, lines);
+
+        if let Some(mut pending) = shared.lock() {
+            pending.written = batch;
+        }
+        shared.done.notify_all();
+    }
+}
+
+#[derive(Default)]
+struct Batch {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    files: HashMap<String, (Arc<Path>, Vec<u8>)>,
+    sync: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    // 返回出錯前已經寫出的字節數，見 write_counted
+    fn write(self, data: &[u8]) -> (usize, io::Result<()>) {
+        match self {
+            Stream::Stdout => {
+                let mut out = io::stdout().lock();
+                let (written, result) = write_counted(&mut out, data);
+                (written, result.and_then(|()| out.flush()))
+            }
+            Stream::Stderr => write_counted(&mut io::stderr().lock(), data),
+        }
+    }
+}
+
+// 與 write_all 相同，但同時返回出錯前已經寫出的字節數，重試時只寫剩下的部分，已寫出的內容不會重複
+fn write_counted(out: &mut impl Write, data: &[u8]) -> (usize, io::Result<()>) {
+    let mut written = 0;
+    while written < data.len() {
+        match out.write(&data[written..]) {
+            Ok(0) => return (written, Err(io::ErrorKind::WriteZero.into())),
+            Ok(n) => written += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return (written, Err(error)),
+        }
+    }
+    (written, Ok(()))
+}
+
+// 寫入線程持有的積壓：寫入失敗、等待重試的內容
+#[derive(Default)]
+struct Sinks {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    files: HashMap<String, (Arc<Path>, Vec<u8>)>,
+    // 終端管道已關閉且沒有 failover 文件，之後的終端輸出直接丟棄
+    stdout_closed: bool,
+    stderr_closed: bool,
+}
+
+impl Sinks {
+    fn is_empty(&self) -> bool {
+        self.stdout.is_empty() && self.stderr.is_empty() && self.files.is_empty()
+    }
+
+    fn write(&mut self, shared: &Shared, files: &LogFiles, batch: Batch) {
+        self.stdout.extend_from_slice(&batch.stdout);
+        self.stderr.extend_from_slice(&batch.stderr);
+        for (process_name, (log_dir, lines)) in batch.files {
+            self.files.entry(process_name).or_insert_with(|| (log_dir, Vec::new())).1.extend_from_slice(&lines);
+        }
+
+        self.write_console(shared, Stream::Stdout);
+        self.write_console(shared, Stream::Stderr);
+
+        // 持有文件鎖寫入，避免與日誌保留裁剪交錯
+        let Ok(mut files) = files.lock() else {
+            return;
+        };
+        let counters = &shared.counters;
+        self.files.retain(|process_name, (log_dir, lines)| {
+            if !files.contains_key(process_name) {
+                let path = crate::logging::log_file_path(log_dir, process_name);
+                match OpenOptions::new().create(true).append(true).open(path) {
+                    Ok(file) => {
+                        files.insert(process_name.clone(), file);
+                    }
+                    Err(_) => {
+                        counters.error();
+                        trim_backlog(lines, counters);
+                        return true;
+                    }
+                }
+            }
+            let Some(file) = files.get_mut(process_name) else {
+                return true;
+            };
+            let (written, result) = write_counted(file, lines);
+            match result {
+                Ok(()) => false,
+                // 例如磁盤已滿；下次重新打開文件，文件被刪除或輪換時也能恢復
+                Err(_) => {
+                    lines.drain(..written);
+                    counters.error();
+                    files.remove(process_name);
+                    trim_backlog(lines, counters);
+                    true
+                }
+            }
+        });
+
+        if batch.sync {
+            for file in files.values() {
+                let _ = file.sync_data();
+            }
+        }
+    }
+
+    fn write_console(&mut self, shared: &Shared, stream: Stream) {
+        let (backlog, closed) = match stream {
+            Stream::Stdout => (&mut self.stdout, &mut self.stdout_closed),
+            Stream::Stderr => (&mut self.stderr, &mut self.stderr_closed),
+        };
+        if backlog.is_empty() {
+            return;
+        }
+        if *closed {
+            shared.counters.dropped(backlog.len());
+            backlog.clear();
+            return;
+        }
+
+        let mut attempts = 0;
+        loop {
+            let (written, result) = stream.write(backlog);
+            backlog.drain(..written);
+            let Err(error) = result else {
+                return;
+            };
+            shared.counters.error();
+            if error.kind() == io::ErrorKind::BrokenPipe {
+                // 讀取端已經關閉，不會再恢復
+                match redirect(stream, shared) {
+                    Some(true) => continue,
+                    _ => {
+                        *closed = true;
+                        shared.counters.dropped(backlog.len());
+                        backlog.clear();
+                        return;
+                    }
+                }
+            }
+            attempts += 1;
+            if attempts > CONSOLE_RETRIES {
+                trim_backlog(backlog, &shared.counters);
+                return;
+            }
+            std::thread::sleep(CONSOLE_RETRY_DELAY);
+        }
+    }
+}
+
+// 把關閉的終端 fd 換成 failover 文件，沒有配置或無法打開時換成 /dev/null，之後直接寫入 stdout 的
+// println! 等不會因 EPIPE 而 panic，以後啟動的 log_mode = "raw" 進程也繼承新的 fd。
+// 返回 Some(true) 表示之後的輸出寫入 failover 文件
+#[cfg(unix)]
+fn redirect(stream: Stream, shared: &Shared) -> Option<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let failover_file = shared.failover_file.lock().ok().and_then(|path| path.clone());
+    let failover = failover_file.and_then(|path| OpenOptions::new().create(true).append(true).open(path).ok());
+    let failed_over = failover.is_some();
+    let target = match failover {
+        Some(file) => file,
+        None => OpenOptions::new().write(true).open("/dev/null").ok()?,
+    };
+    let fd = match stream {
+        Stream::Stdout => libc::STDOUT_FILENO,
+        Stream::Stderr => libc::STDERR_FILENO,
+    };
+    // SAFETY: 兩個 fd 都有效，dup2 原子地替換 fd
+    if unsafe { libc::dup2(target.as_raw_fd(), fd) } < 0 {
+        return None;
+    }
+    if failed_over {
+        shared.counters.failed_over.store(true, Ordering::Relaxed);
+    }
+    Some(failed_over)
+}
+
+#[cfg(not(unix))]
+fn redirect(_stream: Stream, _shared: &Shared) -> Option<bool> {
+    None
+}
+
+// 積壓超過上限時從最舊的整行開始丟棄
+fn trim_backlog(backlog: &mut Vec<u8>, counters: &Counters) {
+    if backlog.len() <= MAX_BACKLOG_BYTES {
+        return;
+    }
+    let excess = backlog.len() - MAX_BACKLOG_BYTES;
+    let cut = backlog[excess..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(backlog.len(), |index| excess + index + 1);
+    counters.dropped(cut);
+    backlog.drain(..cut);
+}
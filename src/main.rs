@@ -1,23 +1,29 @@
-mod cli;
-mod config;
-mod error;
-mod logging;
-mod process;
-mod signal;
-
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use cli::command_parser::CommandParser;
-use config::manager::ConfigManager;
-use error::Result;
-use logging::handler::LogHandler;
-use process::manager::ProcessManager;
-use signal::handler::SignalHandler;
+use janus::cli::command_parser::CommandParser;
+use janus::config::env as env_processes;
+use janus::config::manager::{ConfigManager, DEFAULT_CONFIG_FILE};
+use janus::error::{JanusError, Result};
+use janus::logging::handler::LogHandler;
+use janus::logging::wasm::WasmLogPlugin;
+use janus::msg;
+use janus::process::manager::ProcessManager;
+use janus::signal::handler::SignalHandler;
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() -> Result<()> {
+async fn main() {
+    // 錯誤以可讀的文本打印，而不是 Debug 格式
+    if let Err(e) = run().await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> Result<()> {
     // 獲取命令行參數
     let args: Vec<String> = env::args().collect();
     
@@ -39,16 +45,42 @@ async fn main() -> Result<()> {
         return Ok(());
     }
     
-    // 默認配置文件路徑
-    let default_config = "janus.toml";
+    // schema 和 secret 命令不需要加載配置文件；secret encrypt 自行讀取 --config 指定文件中的 [sops] 表
+    let config_arg = args.get(1).is_some_and(|arg| arg == "--config") && args.len() > 3;
+    let command = if config_arg { args.get(3) } else { args.get(1) };
+    if command.is_some_and(|command| command == "schema" || command == "secret") {
+        let empty_manager = Arc::new(Mutex::new(ProcessManager::new_empty()));
+        return CommandParser::new(empty_manager).parse_and_execute(args).await;
+    }
+    
+    // 指定了遠程 janus 時命令通過 gRPC 執行，不需要本地配置文件
+    let remote = args.iter().any(|arg| arg == "--host" || arg.starts_with("--host="))
+        || env::var_os("JANUS_HOST").is_some();
+    if remote {
+        let empty_manager = Arc::new(Mutex::new(ProcessManager::new_empty()));
+        return CommandParser::new(empty_manager).parse_and_execute(args).await;
+    }
     
     // 解析配置文件路徑
-    let config_path = if args.len() > 2 && args[1] == "--config" {
-        &args[2]
+    let (config_path, command_index) = if args.len() > 2 && args[1] == "--config" {
+        (args[2].as_str(), 3)
     } else {
-        default_config
+        (DEFAULT_CONFIG_FILE, 1)
     };
     
+    // validate 和 graph 命令自行加載配置，以便報告配置錯誤而不是直接退出；子命令的幫助不需要配置
+    let wants_help = args.iter().skip(command_index).any(|arg| arg == "--help" || arg == "-h")
+        || args.get(command_index).is_some_and(|command| command == "help");
+    if wants_help || matches!(args.get(command_index).map(String::as_str), Some("validate" | "graph")) {
+        let empty_manager = Arc::new(Mutex::new(ProcessManager::new_empty()));
+        return CommandParser::new(empty_manager).parse_and_execute(args).await;
+    }
+    
+    // 沒有配置文件時說明如何開始，而不是只報告讀取失敗
+    if !Path::new(config_path).exists() && !env_processes::defines_processes() {
+        return Err(JanusError::Config(msg!("config.missing", path = config_path)));
+    }
+    
     // 初始化配置管理器
     let config_manager = ConfigManager::new(config_path)?;
     
@@ -59,11 +91,26 @@ async fn main() -> Result<()> {
         .as_deref()
         .unwrap_or("info");
     
-    // 初始化日誌處理器
-    let log_handler = LogHandler::new(log_level);
+    // 初始化日誌處理器，配置了 log_dir 時同時寫入日誌文件
+    let log_dir = config_manager.get_global_config().log_dir.as_ref().map(PathBuf::from);
+    if let Some(log_dir) = &log_dir {
+        fs::create_dir_all(log_dir)?;
+    }
+    let log_plugins = config_manager
+        .get_global_config()
+        .log_plugins
+        .iter()
+        .flatten()
+        .map(WasmLogPlugin::load)
+        .collect::<Result<Vec<_>>>()?;
+    let log_failover_file = config_manager.get_global_config().log_failover_file.as_ref().map(PathBuf::from);
+    let log_handler = LogHandler::new(log_level)
+        .with_log_dir(log_dir)
+        .with_failover_file(log_failover_file)
+        .with_plugins(log_plugins);
     
     // 初始化進程管理器
-    let process_manager = ProcessManager::new(config_manager, log_handler);
+    let process_manager = ProcessManager::new(config_manager, log_handler.clone());
     
     // 使用 Arc<Mutex<>> 包裝進程管理器以便在多個線程間共享
     let manager = Arc::new(Mutex::new(process_manager));
@@ -75,8 +122,8 @@ async fn main() -> Result<()> {
     // 初始化命令解析器
     let command_parser = CommandParser::new(manager);
     
-    // 解析並執行命令
-    command_parser.parse_and_execute(args).await?;
-    
-    Ok(())
+    // 解析並執行命令，返回前寫出隊列中剩餘的日誌
+    let result = command_parser.parse_and_execute(args).await;
+    log_handler.flush();
+    result
 }
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::error::{JanusError, Result};
+
+// 所有面向用戶的 CLI 消息及其英文默認文本，{name} 形式的佔位符在輸出時替換
+pub const DEFAULT_MESSAGES: &[(&str, &str)] = &[
+    // 批量命令
+    ("selection.all", "all"),
+    ("selection.selected", "selected"),
+    ("processes.starting", "Starting {selection} processes..."),
+    ("processes.started", "{selection} processes started"),
+    ("processes.stopping", "Stopping {selection} processes..."),
+    ("processes.stopped", "{selection} processes stopped"),
+    ("processes.restarting", "Restarting {selection} processes..."),
+    ("processes.restarted", "{selection} processes restarted"),
+    ("progress.starting", "{spinner} [{index}/{total}] Starting {name}..."),
+    ("progress.stopping", "{spinner} [{index}/{total}] Stopping {name}..."),
+    ("progress.started", "[{index}/{total}] Started {name} ({seconds}s)"),
+    ("progress.stopped", "[{index}/{total}] Stopped {name} ({seconds}s)"),
+    ("progress.start_failed", "[{index}/{total}] Failed to start {name}: {error}"),
+    ("progress.stop_failed", "[{index}/{total}] Failed to stop {name}: {error}"),
+    ("progress.summary", "{succeeded} {done}, {failed} failed"),
+    // 單個進程命令
+    ("process.starting", "Starting process: {name}"),
+    ("process.started", "Process started: {name}"),
+    ("process.stopping", "Stopping process: {name}"),
+    ("process.stopped", "Process stopped: {name}"),
+    ("process.stopped_for", "Process stopped: {name} (resumes in {duration})"),
+    ("process.not_running", "Process is not running: {name}"),
+    ("process.start_deferred", "Not starting {name} outside its start window {window}"),
+    ("process.killing", "Killing process with {signal}: {name}"),
+    ("process.killed", "Process killed: {name}"),
+    ("process.dependents_running", "Warning: {dependents} require {name} and will keep running without it (use --cascade to stop them too)"),
+    ("process.restarting", "Restarting process: {name}"),
+    ("process.restarted", "Process restarted: {name}"),
+    // 日誌命令
+    ("logs.retention_failed", "Failed to apply log retention: {error}"),
+    ("logs.not_found", "No logs found for: {name}"),
+    ("logs.purged", "Purged logs: {count}"),
+    // 任務隊列
+    ("queue.enqueued", "Queued {name} (position {position})"),
+    // 配置檢查
+    ("config.missing", "No config file at {path}. Create it with [[process]] sections, pass --config PATH, or define processes with JANUS_PROCESS_<NAME>_COMMAND; run janus --help for the commands"),
+    ("validate.diagnostic", "{severity}: {message}"),
+    ("validate.process_diagnostic", "{severity}: [{name}] {message}"),
+    ("validate.summary", "{errors} error(s), {warnings} warning(s), {infos} info"),
+    // 就緒檢查
+    ("ready.waiting", "Waiting for {count} critical process(es) to become ready (timeout {timeout})..."),
+    ("ready.process_ready", "Ready: {name}"),
+    ("ready.all_ready", "All {count} critical process(es) are ready"),
+    // 維護模式
+    ("maintenance.on", "Maintenance mode on for {seconds}s: {name}"),
+    ("maintenance.off", "Maintenance mode off: {name}"),
+    // 原地升級
+    ("upgrade.requested", "Upgrading janus to {executable}..."),
+    ("upgrade.done", "Upgraded janus, {count} process(es) kept running"),
+    ("upgrade.exec", "Upgrading to {executable}, handing over {count} running process(es)"),
+    ("upgrade.failed", "Upgrade failed, continuing with the current binary: {error}"),
+    ("upgrade.resumed", "Took over {count} process(es) from the previous janus"),
+    ("upgrade.handover_failed", "Failed to read the state handed over by the previous janus: {error}"),
+    // systemd 通知
+    ("systemd.notify_failed", "Failed to notify systemd: {error}"),
+    // 重新加載配置
+    ("reload.requested", "Reload requested over gRPC, reloading configuration..."),
+    ("reload.done", "Reloaded the configuration (version {version})"),
+    // 錄製輸出
+    ("record.started", "Recording {name} to {path}, press Ctrl+C to stop..."),
+    ("record.saved", "Saved {count} line(s) to {path}, replay with: asciinema play {path}"),
+    // 狀態時間線
+    ("timeline.empty", "No events recorded for {name}"),
+    ("timeline.summary", "{events} event(s): {starts} start(s), {failures} failed start(s) or unclean exit(s)"),
+    // 交互式控制台
+    ("console.welcome", "Connected to janus at {host}. Type help for commands, exit or Ctrl+D to leave."),
+    ("console.help", "Commands: status, ps, start [NAME], stop [NAME], restart [NAME], kill NAME, logs [NAME...], events [NAME...],\ntimeline NAME, maintenance on|off [NAME...], help, exit. Other janus commands work as on the command line.\nTab completes commands and process names; Ctrl+C stops logs and events."),
+    // 啟動摘要
+    ("banner.title", "janus {version} (pid {pid})"),
+    ("banner.config", "  Config:     {path}"),
+    ("banner.hash", "  SHA-256:    {hash}"),
+    ("banner.processes", "  Processes:  {count} configured, {started} started"),
+    ("banner.projects", "  Projects:   {projects}"),
+    ("banner.control", "  Control:    {address}"),
+    ("banner.grpc", "  gRPC:       {address}"),
+    ("banner.grpc_readonly", "  gRPC (ro):  {address}"),
+    ("banner.registry", "  Registry:   {path}"),
+    // 遠程控制
+    ("control.listening", "Control listener on {address}"),
+    ("log_plugin.failed", "Log plugin {path} failed: {error}"),
+    ("control.accept_failed", "Control listener failed to accept a connection: {error}"),
+    ("control.connection_failed", "Control connection from {peer} failed: {error}"),
+    // gRPC 管理接口
+    ("grpc.listening", "gRPC API on {address}"),
+    ("grpc.readonly_listening", "Read-only gRPC API on {address}"),
+    ("grpc.failed", "gRPC API stopped: {error}"),
+    ("supervisor.unreachable", "Is janus start running and serving its gRPC API (global.grpc_listen) on {host}?"),
+    // 時區檢查
+    ("clock.timezone", "Warning: {problem}"),
+    // Kubernetes pod 狀態
+    ("kubernetes.reporting", "Reporting process health on pod {pod}"),
+    ("kubernetes.unavailable", "Not reporting process health to Kubernetes: {error}"),
+    // 狀態報告
+    ("status.no_processes", "No processes configured"),
+    ("status.no_processes_hint", "Add [[process]] sections to the config file, or define processes with JANUS_PROCESS_<NAME>_COMMAND"),
+    ("status.title", "Process Status Report:"),
+    ("status.log_errors", "Log output: {errors} write error(s), {dropped} dropped"),
+    ("status.log_failover", ", console output redirected to log_failover_file"),
+    ("status.reload_pending", "Config changed on disk — reload pending (send SIGHUP to apply)"),
+    ("status.process", "Process: {name}"),
+    ("status.display_name", "Display name: {name}"),
+    ("status.status", "Status: {status}"),
+    ("status.uptime", "Uptime: {uptime}"),
+    ("status.port", "Port: {port}"),
+    ("status.last_start", "Last start took: {seconds}s"),
+    ("status.last_stop", "Last stop took: {seconds}s"),
+    ("status.exit_code", "Exit code: {code}"),
+    ("status.killed_by", "Killed by: {signal}"),
+    ("status.ready", "Ready: {ready}"),
+    ("status.maintenance", "Maintenance mode: {remaining} left"),
+    ("status.stopping", "Stopping for {elapsed}, SIGKILL after {timeout}"),
+    ("status.starting", "starting ({elapsed}s)"),
+    ("status.starting_expected", "starting ({elapsed}s / expected {expected}s)"),
+    ("status.start_window", "Waiting for start window: {window}"),
+    ("status.manual_stop", "Stopped manually: not started again until an explicit start"),
+    ("status.resume", "Stopped manually: resumes in {remaining}"),
+    ("status.notify_status", "Reported status: {status}"),
+    ("status.image", "Image: {image} (container)"),
+    ("status.command", "Command: {command}"),
+    ("status.args", "Args: {args}"),
+    ("status.working_dir", "Working directory: {dir}"),
+    ("status.env", "Environment variables: {count} defined"),
+    ("status.env_cache", "Environment cache: {hits} hit(s), {misses} miss(es)"),
+    ("status.auto_restart", "Auto-restart: {enabled}"),
+    ("status.restart_count", "Restart count: {count}"),
+    ("status.restart_limit", "Restart limit: {limit}"),
+    ("status.restart_limit_unlimited", "Restart limit: unlimited"),
+    ("status.restart_delay", "Restart delay: {seconds} seconds"),
+    ("status.restart_budget", "Restart budget: {budget} per {seconds} seconds"),
+    ("status.every", "Runs every: {interval}"),
+    ("status.schedule", "Schedule: {schedule}"),
+    ("status.recent_runs", "Recent runs:"),
+    ("status.run", "  {finished}  exit {code}  {seconds}s"),
+    ("status.run_with_output", "  {finished}  exit {code}  {seconds}s  output: {path}"),
+    ("status.run_usage", "    cpu: {user}s user, {system}s system; disk: {read} read, {written} written; context switches: {voluntary} voluntary, {involuntary} involuntary"),
+    ("status.run_core", "    core: {path}"),
+    ("status.run_required_kill", "    did not stop within stop_timeout, required SIGKILL"),
+    ("status.crash_context", "  Last {count} output lines before the latest failure:"),
+    ("status.tree_unavailable", "Process trees are only available on Linux"),
+    ("status.tree_process", "{name} ({status}, {count} processes, {rss})"),
+    ("status.tree_not_running", "{name} ({status})"),
+    // 信號處理
+    ("signal.shutting_down", "Received {signal}, shutting down..."),
+    ("signal.shutdown_failed", "Error during shutdown: {error}"),
+    ("signal.shutdown_timed_out", "Shutdown did not finish within {seconds} seconds, exiting anyway"),
+    ("signal.ctrl_c_registered", "Ctrl+C handler registered"),
+    ("signal.reloading", "Received SIGHUP, reloading configuration..."),
+    ("signal.reload_failed", "Failed to reload configuration: {error}"),
+    ("signal.forwarding", "Received SIGHUP, forwarding to processes..."),
+    ("signal.dumping_status", "Received {signal}, dumping status..."),
+    ("signal.dump_failed", "Failed to dump status: {error}"),
+    ("signal.snapshot_written", "Received {signal}, status written to {path}"),
+    ("signal.snapshot_failed", "Failed to write status snapshot: {error}"),
+    ("signal.restarting", "Received {signal}, restarting {names}..."),
+    ("signal.restart_failed", "Failed to restart {name}: {error}"),
+    ("signal.no_action", "Received {signal}, no action configured"),
+];
+
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn english() -> Self {
+        Self {
+            messages: DEFAULT_MESSAGES
+                .iter()
+                .map(|(id, text)| (id.to_string(), text.to_string()))
+                .collect(),
+        }
+    }
+
+    // 從 TOML 語言文件（id = "文本"）加載翻譯，未翻譯的消息保留英文
+    pub fn with_locale_file(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            JanusError::Config(format!("Failed to read message file {}: {}", path, e))
+        })?;
+        let translations: HashMap<String, String> = toml::from_str(&content).map_err(|e| {
+            JanusError::Config(format!("Failed to parse message file {}: {}", path, e))
+        })?;
+
+        let mut catalog = Self::english();
+        for (id, text) in translations {
+            if !catalog.messages.contains_key(&id) {
+                return Err(JanusError::Config(format!(
+                    "Unknown message id in {}: {}",
+                    path, id
+                )));
+            }
+            catalog.messages.insert(id, text);
+        }
+
+        Ok(catalog)
+    }
+
+    pub fn format(&self, id: &str, args: &[(&str, String)]) -> String {
+        let mut text = self
+            .messages
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.to_string());
+
+        for (key, value) in args {
+            text = text.replace(&format!("{{{}}}", key), value);
+        }
+
+        text
+    }
+}
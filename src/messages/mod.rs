@@ -0,0 +1,36 @@
+pub mod catalog;
+
+use std::env;
+use std::sync::OnceLock;
+
+use catalog::Catalog;
+
+// 指向語言文件的環境變量，供發行版打包者本地化 CLI 輸出
+pub const MESSAGES_ENV: &str = "JANUS_MESSAGES";
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| match env::var(MESSAGES_ENV) {
+        Ok(path) => Catalog::with_locale_file(&path).unwrap_or_else(|e| {
+            eprintln!("{}, using English messages", e);
+            Catalog::english()
+        }),
+        Err(_) => Catalog::english(),
+    })
+}
+
+pub fn message(id: &str, args: &[(&str, String)]) -> String {
+    catalog().format(id, args)
+}
+
+// 按 ID 查找消息並替換佔位符，例如 msg!("process.started", name = name)
+#[macro_export]
+macro_rules! msg {
+    ($id:expr) => {
+        $crate::messages::message($id, &[])
+    };
+    ($id:expr, $($key:ident = $value:expr),+ $(,)?) => {
+        $crate::messages::message($id, &[$((stringify!($key), $value.to_string())),+])
+    };
+}
@@ -0,0 +1,104 @@
+// 進程一次運行累計的 CPU 時間、磁盤讀寫量和上下文切換次數，用於容量規劃。
+// 在進程退出之後、回收之前從 /proc 讀取：此時它是僵屍進程，數據不再變化，並且包含它回收過的子進程。僅支持 Linux
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResourceUsage {
+    pub user_time: Duration,
+    pub system_time: Duration,
+    // 實際讀寫存儲設備的字節數，不包括頁緩存命中
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub voluntary_switches: u64,
+    pub involuntary_switches: u64,
+}
+
+impl ResourceUsage {
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+}
+
+// 子進程已經退出但尚未被回收；WNOWAIT 只查詢，不回收。不是 janus 的子進程時返回 false
+#[cfg(target_os = "linux")]
+pub fn exited(pid: u32) -> bool {
+    // SAFETY: siginfo_t 是普通的 C 結構，全零是有效的初始值
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    // SAFETY: WNOHANG 不會阻塞，info 指向有效的結構
+    let result = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            pid as libc::id_t,
+            &mut info,
+            libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+        )
+    };
+    // SAFETY: waitid 成功後 si_pid 已由內核填充，沒有已退出的子進程時為 0
+    result == 0 && unsafe { info.si_pid() } != 0
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn exited(_pid: u32) -> bool {
+    false
+}
+
+// 等待已發送 SIGKILL 的子進程退出，返回它回收前的資源用量
+#[cfg(target_os = "linux")]
+pub async fn wait_killed(pid: u32) -> Option<ResourceUsage> {
+    let deadline = std::time::Instant::now() + Duration::from_secs(1);
+    while !exited(pid) {
+        if std::time::Instant::now() >= deadline {
+            return None;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    read(pid)
+}
+
+// 其他平台不能在回收前查詢子進程是否已退出，也沒有資源用量可讀，不必等待
+#[cfg(not(target_os = "linux"))]
+pub async fn wait_killed(_pid: u32) -> Option<ResourceUsage> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+pub fn read(pid: u32) -> Option<ResourceUsage> {
+    use std::fs;
+
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // comm 中可能包含空格和括號，從最後一個 ')' 之後開始按字段分割；utime 是第 14 個字段
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let ticks = |index: usize| fields.get(index - 3).and_then(|value| value.parse::<u64>().ok());
+    // SAFETY: sysconf 只讀取系統配置
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+    let seconds = |ticks: u64| Duration::from_secs_f64(ticks as f64 / ticks_per_second);
+    // utime、stime 加上已回收子進程的 cutime、cstime
+    let user_time = seconds(ticks(14)? + ticks(16)?);
+    let system_time = seconds(ticks(15)? + ticks(17)?);
+
+    // /proc/<pid>/io 需要與 ptrace 相同的權限，讀取失敗時只記錄 CPU 時間
+    let io = fs::read_to_string(format!("/proc/{}/io", pid)).unwrap_or_default();
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+    Some(ResourceUsage {
+        user_time,
+        system_time,
+        read_bytes: field(&io, "read_bytes:"),
+        write_bytes: field(&io, "write_bytes:"),
+        voluntary_switches: field(&status, "voluntary_ctxt_switches:"),
+        involuntary_switches: field(&status, "nonvoluntary_ctxt_switches:"),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_pid: u32) -> Option<ResourceUsage> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn field(content: &str, name: &str) -> u64 {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(name))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or_default()
+}
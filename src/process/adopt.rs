@@ -0,0 +1,81 @@
+// 接管不是由 janus 啟動的進程：從 PID 文件讀取 pid，之後通過 pidfd (macOS 上為 kqueue) 或輪詢判斷它是否退出
+use std::fs;
+use std::path::Path;
+use std::process::ExitStatus;
+
+use super::pidfd::PidFd;
+
+// PID 文件中記錄的進程仍在運行時返回其 pid
+pub fn running_pid(pid_file: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(pid_file).ok()?.trim().parse().ok()?;
+    (pid > 0 && matches!(poll(pid, None), AdoptedState::Running)).then_some(pid)
+}
+
+pub enum AdoptedState {
+    Running,
+    // 進程是 janus 的子進程（例如 janus 作為 PID 1 收養了它）時可以取得退出狀態
+    Exited(Option<ExitStatus>),
+}
+
+#[cfg(unix)]
+pub fn poll(pid: u32, pidfd: Option<&PidFd>) -> AdoptedState {
+    use std::os::unix::process::ExitStatusExt;
+
+    // pidfd 只指向接管時的那個進程，不會因 pid 被重用而誤判或誤回收
+    if let Some(pidfd) = pidfd {
+        return if pidfd.exited() {
+            AdoptedState::Exited(pidfd.reap())
+        } else {
+            AdoptedState::Running
+        };
+    }
+
+    let pid = pid as libc::pid_t;
+    let mut status = 0;
+    // SAFETY: WNOHANG 不會阻塞，status 指向有效的整數
+    match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+        0 => return AdoptedState::Running,
+        reaped if reaped == pid => return AdoptedState::Exited(Some(ExitStatus::from_raw(status))),
+        _ => {}
+    }
+
+    // 不是子進程時只能判斷它是否存在；EPERM 表示進程存在但屬於其他用戶
+    // SAFETY: 信號 0 只做存在和權限檢查，不會發送信號
+    if unsafe { libc::kill(pid, 0) } == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    {
+        AdoptedState::Running
+    } else {
+        AdoptedState::Exited(None)
+    }
+}
+
+#[cfg(not(unix))]
+pub fn poll(_pid: u32, _pidfd: Option<&PidFd>) -> AdoptedState {
+    AdoptedState::Exited(None)
+}
+
+// 進程已經不存在時視為成功
+#[cfg(unix)]
+pub fn kill(pid: u32, pidfd: Option<&PidFd>) -> std::io::Result<()> {
+    let result = match pidfd {
+        Some(pidfd) => pidfd.send_signal(libc::SIGKILL),
+        // SAFETY: kill 只接受整數參數
+        None if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 => {
+            Err(std::io::Error::last_os_error())
+        }
+        None => Ok(()),
+    };
+    match result {
+        Err(error) if error.raw_os_error() != Some(libc::ESRCH) => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn kill(_pid: u32, _pidfd: Option<&PidFd>) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "adopted processes are only supported on unix",
+    ))
+}
@@ -0,0 +1,69 @@
+use chrono::Local;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{JanusError, Result};
+
+// 將一次運行的完整輸出寫入文件，stdout 和 stderr 共用同一個文件
+#[derive(Clone)]
+pub struct OutputCapture {
+    file: Arc<Mutex<File>>,
+    pub path: PathBuf,
+    // 只寫入文件，不再輸出到日誌
+    pub exclusive: bool,
+}
+
+impl OutputCapture {
+    // 路徑模板支持 {name} 和 {timestamp}，以便每次運行寫入單獨的文件
+    pub fn create(template: &str, process_name: &str, exclusive: bool) -> Result<Self> {
+        let path = PathBuf::from(
+            template
+                .replace("{name}", process_name)
+                .replace("{timestamp}", &Local::now().format("%Y%m%dT%H%M%S").to_string()),
+        );
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| {
+                JanusError::Process(format!("Failed to create capture directory {}: {}", parent.display(), e))
+            })?;
+        }
+        let file = File::create(&path).map_err(|e| {
+            JanusError::Process(format!("Failed to create capture file {}: {}", path.display(), e))
+        })?;
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            path,
+            exclusive,
+        })
+    }
+
+    // 升級後繼續寫入交接前的文件
+    pub fn reopen(path: PathBuf, exclusive: bool) -> Result<Self> {
+        let file = fs::OpenOptions::new().append(true).open(&path).map_err(|e| {
+            JanusError::Process(format!("Failed to open capture file {}: {}", path.display(), e))
+        })?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            path,
+            exclusive,
+        })
+    }
+
+    // 原始模式下子進程 (或容器的日誌) 寫入的 stdout 和 stderr，兩者與 capture 共用同一個文件
+    pub fn files(&self) -> Result<(File, File)> {
+        let file = self
+            .file
+            .lock()
+            .map_err(|_| JanusError::Process(format!("Capture file {} is unavailable", self.path.display())))?;
+        Ok((file.try_clone()?, file.try_clone()?))
+    }
+
+    pub fn write(&self, line: &str) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
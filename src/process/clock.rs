@@ -0,0 +1,87 @@
+// 監督邏輯讀取時間的來源：重啟延遲、重啟預算、全局重啟速率、stop_timeout、維護模式、定時任務和啟動時間窗口的判斷
+// 都通過 ProcessManager 的時鐘取得當前時間。默認使用系統時鐘；啟用 test-util 特性時
+// janus::test_util::MockClock 讓測試手動推進時間，不必等待真實的延遲。status 顯示的時長仍按系統時鐘計算
+use chrono::{DateTime, TimeDelta, Utc};
+use std::fmt::Debug;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub trait Clock: Debug + Send + Sync {
+    // 單調時間，用於延遲和超時
+    fn now(&self) -> Instant;
+    // 牆上時間，用於 cron 排程和啟動時間窗口
+    fn utc(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// 牆上時間相對單調時間多走或少走超過這個時長時，視為系統時間被調整，或主機休眠後恢復 (休眠期間單調時鐘不前進)；
+// 監控循環每秒檢查一次，正常的調度延遲和 NTP 的逐步校正遠小於此值
+pub const CLOCK_JUMP_THRESHOLD: Duration = Duration::from_secs(30);
+
+// 比較相鄰兩次檢查之間兩個時鐘前進的時長，發現牆上時間的跳變
+#[derive(Debug, Default, Clone)]
+pub struct JumpDetector {
+    last: Option<(Instant, DateTime<Utc>)>,
+}
+
+impl JumpDetector {
+    // 返回牆上時間比單調時間多走 (向前跳變) 或少走 (向後跳變，負值) 的時長；第一次檢查時只記錄時間
+    pub fn check(&mut self, clock: &dyn Clock) -> Option<TimeDelta> {
+        let now = (clock.now(), clock.utc());
+        let (last_instant, last_utc) = self.last.replace(now)?;
+        let monotonic = TimeDelta::from_std(now.0.saturating_duration_since(last_instant)).ok()?;
+        let jump = (now.1 - last_utc) - monotonic;
+        let threshold = TimeDelta::from_std(CLOCK_JUMP_THRESHOLD).ok()?;
+        (jump.abs() > threshold).then_some(jump)
+    }
+}
+
+// 沒有指定 timezone 的 cron 排程、計劃重啟和啟動時間窗口按本地時間計算，本地時區來自 TZ 或 /etc/localtime。
+// 精簡鏡像中常常缺少時區數據，此時本地時間悄悄變成 UTC，任務在錯誤的時間運行。返回發現的問題；
+// 沒有設置 TZ 且不存在 /etc/localtime 時本地時間本來就是 UTC，只在 uses_local_time 時才算問題
+pub fn local_timezone_problem(uses_local_time: bool) -> Option<String> {
+    let tz = std::env::var("TZ").ok();
+    timezone_problem(tz.as_deref(), Path::new("/etc/localtime"), uses_local_time)
+}
+
+fn timezone_problem(tz: Option<&str>, localtime: &Path, uses_local_time: bool) -> Option<String> {
+    let Some(tz) = tz else {
+        return (uses_local_time && !localtime.exists()).then(|| {
+            format!(
+                "TZ is not set and {} does not exist, so local time is UTC; set TZ or timezone if schedules are meant for another zone",
+                localtime.display()
+            )
+        });
+    };
+    let name = tz.strip_prefix(':').unwrap_or(tz);
+    // 空值、UTC 和 POSIX 規則 (例如 CET-1CEST,M3.5.0,M10.5.0/3) 不需要時區數據
+    if name.is_empty() || matches!(name, "UTC" | "GMT") || (!name.contains('/') && name.contains(|c: char| c.is_ascii_digit())) {
+        return None;
+    }
+    if name.starts_with('/') {
+        return (!Path::new(name).exists()).then(|| format!("TZ={} does not exist, so local time falls back to UTC", tz));
+    }
+    if TIMEZONE_DIRS.iter().any(|dir| Path::new(dir).join(name).is_file()) {
+        return None;
+    }
+    Some(format!(
+        "TZ={} is not in the time zone database ({}), so local time falls back to UTC; install tzdata",
+        tz,
+        TIMEZONE_DIRS.join(", ")
+    ))
+}
+
+// 查找時區數據的目錄，與 chrono 讀取本地時區時相同 (不讀取 TZDIR)
+const TIMEZONE_DIRS: &[&str] = &["/usr/share/zoneinfo", "/share/zoneinfo", "/etc/zoneinfo", "/usr/share/lib/zoneinfo"];
@@ -0,0 +1,523 @@
+// 以容器運行的進程 (實驗性)：janus 通過 Docker Engine API 的 Unix socket 創建、啟動、停止和刪除容器，Podman 提供兼容的 API。
+// 請求與 http 模塊一樣使用 HTTP/1.0，每個請求一個連接，響應不使用分塊編碼，讀到連接關閉即為完整的響應；
+// 跟隨日誌和等待退出的請求在容器運行期間一直保持連接。容器名為 janus-<instance>-<name>，帶本實例的標籤，
+// 啟動前刪除本實例上次遺留的同名容器，退出後刪除容器 (如同 run --rm)。
+// 容器的 stdout/stderr 從日誌流中拆分出來寫入管道，因此狀態、重啟和日誌與普通進程相同，pid 是容器中主進程在主機上的 pid
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
+use tokio::process::{ChildStderr, ChildStdout};
+use tokio::sync::watch;
+
+use super::spawner::{ProcessHandle, WaitFuture};
+
+// 標記容器由哪個 janus 實例創建，只有帶本實例標籤的容器會被刪除
+pub const INSTANCE_LABEL: &str = "io.janus.instance";
+
+// 未配置 global.container_socket 時，依次檢查這兩個環境變量 (unix:// 地址) 和默認的 socket 路徑
+pub const HOST_ENVS: &[&str] = &["CONTAINER_HOST", "DOCKER_HOST"];
+pub const DEFAULT_SOCKETS: &[&str] = &["/run/podman/podman.sock", "/var/run/docker.sock"];
+
+// 非跟隨請求的響應的最大字節數，容器的元數據很小
+const MAX_RESPONSE: u64 = 1024 * 1024;
+
+// 容器退出後等待日誌流結束的時間，之後刪除容器
+const LOG_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 實例標識取自配置文件的絕對路徑，同一主機上使用不同配置的 janus 不會操作彼此的容器
+pub fn instance_id(config_path: &str) -> String {
+    let path = fs::canonicalize(config_path).unwrap_or_else(|_| PathBuf::from(config_path));
+    Sha256::digest(path.to_string_lossy().as_bytes())[..6]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContainerName {
+    pub instance: String,
+    pub name: String,
+}
+
+impl ContainerName {
+    // 項目中的進程名包含 '/'，容器名中不允許
+    pub fn new(instance: &str, process: &str) -> Self {
+        Self {
+            instance: instance.to_string(),
+            name: format!("janus-{}-{}", instance, process.replace('/', "_")),
+        }
+    }
+}
+
+// 端口映射 [ip:]host:container[/protocol] 或 container[/protocol] (主機端口由運行時分配)，返回 (容器端口/協議, 主機 IP, 主機端口)
+pub fn parse_port(mapping: &str) -> Option<(String, String, String)> {
+    let (ports, protocol) = mapping.split_once('/').unwrap_or((mapping, "tcp"));
+    if !matches!(protocol, "tcp" | "udp" | "sctp") {
+        return None;
+    }
+    let parts: Vec<&str> = ports.split(':').collect();
+    let (ip, host, container) = match parts.as_slice() {
+        [container] => ("", "", *container),
+        [host, container] => ("", *host, *container),
+        [ip, host, container] => (*ip, *host, *container),
+        _ => return None,
+    };
+    let valid = |port: &str| port.parse::<u16>().is_ok_and(|port| port > 0);
+    if !valid(container) || !(host.is_empty() || valid(host)) {
+        return None;
+    }
+    Some((format!("{}/{}", container, protocol), ip.to_string(), host.to_string()))
+}
+
+// 創建容器所需的信息
+#[derive(Debug, Clone)]
+pub struct ContainerSpec {
+    pub name: ContainerName,
+    pub image: String,
+    // 為空時運行鏡像自身的命令
+    pub cmd: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub ports: Vec<String>,
+    pub volumes: Vec<String>,
+    // 原樣合併到 HostConfig 中的字段，例如 Memory 或 NetworkMode
+    pub host_config: HashMap<String, Value>,
+}
+
+impl ContainerSpec {
+    // Docker Engine API 的 ContainerCreate 請求正文
+    pub fn create_body(&self) -> Value {
+        let mut exposed = Map::new();
+        let mut bindings = Map::new();
+        for (container_port, ip, host) in self.ports.iter().filter_map(|mapping| parse_port(mapping)) {
+            exposed.insert(container_port.clone(), json!({}));
+            bindings.insert(container_port, json!([{ "HostIp": ip, "HostPort": host }]));
+        }
+        let mut host_config = Map::new();
+        host_config.insert("Binds".to_string(), json!(self.volumes));
+        host_config.insert("PortBindings".to_string(), Value::Object(bindings));
+        host_config.extend(self.host_config.iter().map(|(key, value)| (key.clone(), value.clone())));
+
+        let mut body = json!({
+            "Image": self.image,
+            "Env": self.env.iter().map(|(key, value)| format!("{}={}", key, value)).collect::<Vec<_>>(),
+            "Labels": { INSTANCE_LABEL: self.name.instance },
+            "ExposedPorts": exposed,
+            "HostConfig": host_config,
+        });
+        if !self.cmd.is_empty() {
+            body["Cmd"] = json!(self.cmd);
+        }
+        body
+    }
+}
+
+// Docker/Podman API 的 Unix socket
+#[derive(Debug, Clone, PartialEq)]
+pub struct Api {
+    socket: PathBuf,
+}
+
+impl Api {
+    // 配置的 socket 接受路徑或 unix:// 地址，原樣使用；未配置時返回第一個存在的 socket
+    pub fn locate(configured: Option<&str>) -> Option<Self> {
+        if let Some(socket) = configured {
+            return Some(Self::new(socket.strip_prefix("unix://").unwrap_or(socket)));
+        }
+        let from_env = HOST_ENVS
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .filter_map(|host| host.strip_prefix("unix://").map(PathBuf::from));
+        let rootless = std::env::var_os("XDG_RUNTIME_DIR").map(|dir| Path::new(&dir).join("podman/podman.sock"));
+        let defaults = DEFAULT_SOCKETS.iter().map(PathBuf::from);
+        from_env
+            .chain(rootless)
+            .chain(defaults)
+            .find(|socket| socket.exists())
+            .map(|socket| Self { socket })
+    }
+
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        Self { socket: socket.into() }
+    }
+}
+
+// Docker 的錯誤響應為 {"message": "..."}
+fn error_message(status: u16, body: &str) -> String {
+    let message = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("message")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string());
+    format!("container API returned {}: {}", status, message)
+}
+
+// 路徑和查詢參數中除非保留字符以外的字節都需要編碼，例如鏡像名中的 '/' 和 ':'
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+// 鏡像沒有標籤或摘要時，拉取時必須指定 latest，否則 Docker 會拉取所有標籤
+fn pull_query(image: &str) -> String {
+    let last = image.rsplit('/').next().unwrap_or(image);
+    if image.contains('@') || last.contains(':') {
+        format!("fromImage={}", encode(image))
+    } else {
+        format!("fromImage={}&tag=latest", encode(image))
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::io::Write;
+    use std::os::fd::{AsFd, OwnedFd};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    // 響應頭之後的正文，跟隨請求時一直讀到容器停止
+    pub(super) struct Response {
+        pub status: u16,
+        pub body: BufReader<UnixStream>,
+    }
+
+    impl Response {
+        pub async fn text(mut self) -> io::Result<String> {
+            let mut body = Vec::new();
+            (&mut self.body).take(MAX_RESPONSE + 1).read_to_end(&mut body).await?;
+            if body.len() as u64 > MAX_RESPONSE {
+                return Err(io::Error::other(format!("Response is larger than {} bytes", MAX_RESPONSE)));
+            }
+            Ok(String::from_utf8_lossy(&body).into_owned())
+        }
+
+        // 2xx 時返回正文，否則以 API 的錯誤消息作為錯誤
+        pub async fn ok(self) -> io::Result<String> {
+            let status = self.status;
+            let body = self.text().await?;
+            if !(200..300).contains(&status) {
+                return Err(io::Error::other(error_message(status, &body)));
+            }
+            Ok(body)
+        }
+    }
+
+    impl Api {
+        pub(super) async fn request(&self, method: &str, path: &str, body: Option<&Value>) -> io::Result<Response> {
+            let mut stream = UnixStream::connect(&self.socket)
+                .await
+                .map_err(|e| io::Error::new(e.kind(), format!("Failed to connect to {}: {}", self.socket.display(), e)))?;
+            let body = body.map(Value::to_string).unwrap_or_default();
+            let request = format!(
+                "{} {} HTTP/1.0\r\nHost: docker\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                method,
+                path,
+                body.len(),
+                body
+            );
+            stream.write_all(request.as_bytes()).await?;
+
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            reader.read_line(&mut line).await?;
+            let status = line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|code| code.parse::<u16>().ok())
+                .ok_or_else(|| io::Error::other(format!("Invalid response from {}", self.socket.display())))?;
+            // 跳過響應頭
+            loop {
+                line.clear();
+                if reader.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            Ok(Response { status, body: reader })
+        }
+
+        // 本地沒有鏡像時按 run 的行為拉取；進度逐行返回，錯誤也寫在其中
+        async fn pull(&self, image: &str) -> io::Result<()> {
+            let response = self.request("POST", &format!("/images/create?{}", pull_query(image)), None).await?;
+            if response.status != 200 {
+                return response.ok().await.map(|_| ());
+            }
+            let mut lines = response.body.lines();
+            while let Some(line) = lines.next_line().await? {
+                let error = serde_json::from_str::<Value>(&line)
+                    .ok()
+                    .and_then(|value| value.get("error")?.as_str().map(str::to_string));
+                if let Some(error) = error {
+                    return Err(io::Error::other(format!("Failed to pull {}: {}", image, error)));
+                }
+            }
+            Ok(())
+        }
+
+        async fn create(&self, spec: &ContainerSpec) -> io::Result<String> {
+            let path = format!("/containers/create?name={}", encode(&spec.name.name));
+            let body = spec.create_body();
+            let mut response = self.request("POST", &path, Some(&body)).await?;
+            if response.status == 404 {
+                self.pull(&spec.image).await?;
+                response = self.request("POST", &path, Some(&body)).await?;
+            }
+            let body = response.ok().await?;
+            serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|value| value.get("Id")?.as_str().map(str::to_string))
+                .ok_or_else(|| io::Error::other("container API returned no container id"))
+        }
+
+        async fn inspect(&self, container: &str) -> io::Result<Option<Value>> {
+            let response = self.request("GET", &format!("/containers/{}/json", encode(container)), None).await?;
+            if response.status == 404 {
+                return Ok(None);
+            }
+            let body = response.ok().await?;
+            serde_json::from_str(&body).map(Some).map_err(io::Error::other)
+        }
+
+        async fn remove(&self, container: &str) -> io::Result<()> {
+            let response = self
+                .request("DELETE", &format!("/containers/{}?force=1&v=1", encode(container)), None)
+                .await?;
+            match response.status {
+                404 => Ok(()),
+                _ => response.ok().await.map(|_| ()),
+            }
+        }
+
+        // 容器已經停止時 API 返回 409，忽略即可
+        pub(super) async fn kill(&self, container: &str, signal: i32) -> io::Result<()> {
+            let response = self
+                .request("POST", &format!("/containers/{}/kill?signal={}", encode(container), signal), None)
+                .await?;
+            match response.status {
+                404 | 409 => Ok(()),
+                _ => response.ok().await.map(|_| ()),
+            }
+        }
+
+        // 刪除本實例上次遺留的同名容器，例如 janus 上次被殺死時。
+        // 沒有本實例標籤的同名容器不是這個 janus 創建的，保留不動，之後的創建因名稱衝突失敗並記錄錯誤
+        pub async fn remove_stale(&self, container: &ContainerName) {
+            let Ok(Some(info)) = self.inspect(&container.name).await else {
+                return;
+            };
+            let instance = info.pointer("/Config/Labels").and_then(|labels| labels.get(INSTANCE_LABEL)?.as_str());
+            if instance == Some(container.instance.as_str()) {
+                let _ = self.remove(&container.name).await;
+            }
+        }
+
+        // 創建並啟動容器；outputs 是寫入 stdout 和 stderr 的文件，未提供時創建管道，由 take_stdout/take_stderr 讀取
+        pub async fn run(&self, spec: &ContainerSpec, outputs: Option<(File, File)>) -> io::Result<ContainerHandle> {
+            let id = self.create(spec).await?;
+            match self.start(&id, outputs).await {
+                Ok(handle) => Ok(handle),
+                Err(e) => {
+                    let _ = self.remove(&id).await;
+                    Err(e)
+                }
+            }
+        }
+
+        async fn start(&self, id: &str, outputs: Option<(File, File)>) -> io::Result<ContainerHandle> {
+            self.request("POST", &format!("/containers/{}/start", id), None).await?.ok().await?;
+            let pid = self
+                .inspect(id)
+                .await?
+                .and_then(|info| info.pointer("/State/Pid")?.as_u64())
+                .filter(|pid| *pid > 0)
+                .map(|pid| pid as u32);
+
+            let (stdout, stderr, files) = match outputs {
+                Some(files) => (None, None, files),
+                None => {
+                    let (stdout_reader, stdout_writer) = io::pipe()?;
+                    let (stderr_reader, stderr_writer) = io::pipe()?;
+                    let stdout = ChildStdout::from_std(std::process::ChildStdout::from(OwnedFd::from(stdout_reader)))?;
+                    let stderr = ChildStderr::from_std(std::process::ChildStderr::from(OwnedFd::from(stderr_reader)))?;
+                    let files = (File::from(OwnedFd::from(stdout_writer)), File::from(OwnedFd::from(stderr_writer)));
+                    (Some(stdout), Some(stderr), files)
+                }
+            };
+            let logs = self
+                .request("GET", &format!("/containers/{}/logs?follow=1&stdout=1&stderr=1", id), None)
+                .await?;
+            if logs.status != 200 {
+                return Err(io::Error::other(error_message(logs.status, &logs.text().await?)));
+            }
+            let logs = tokio::spawn(copy_logs(logs.body, files));
+
+            let (exit_tx, exit_rx) = watch::channel(None);
+            let api = self.clone();
+            let container = id.to_string();
+            tokio::spawn(async move {
+                let code = api.wait(&container).await.unwrap_or(255);
+                let _ = tokio::time::timeout(LOG_DRAIN_TIMEOUT, logs).await;
+                let _ = api.remove(&container).await;
+                let _ = exit_tx.send(Some(code));
+            });
+
+            Ok(ContainerHandle {
+                api: self.clone(),
+                id: id.to_string(),
+                pid,
+                exit: exit_rx,
+                stdout,
+                stderr,
+            })
+        }
+
+        // 連接中斷或響應無效時無法得知退出碼，按 255 處理
+        async fn wait(&self, container: &str) -> Option<i64> {
+            let response = self.request("POST", &format!("/containers/{}/wait", container), None).await.ok()?;
+            let body = response.ok().await.ok()?;
+            serde_json::from_str::<Value>(&body).ok()?.get("StatusCode")?.as_i64()
+        }
+    }
+
+    // 日誌流由幀組成：8 字節的頭 (流類型、3 字節填充、4 字節大端長度) 和內容，流類型 1 為 stdout、2 為 stderr
+    // 寫入可能因管道已滿而阻塞，在阻塞線程中進行，讀取管道的任務才能繼續運行
+    async fn copy_logs(mut body: BufReader<UnixStream>, outputs: (File, File)) {
+        let mut outputs = Some(outputs);
+        let mut header = [0u8; 8];
+        while body.read_exact(&mut header).await.is_ok() {
+            let len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+            let mut frame = vec![0; len];
+            if body.read_exact(&mut frame).await.is_err() {
+                break;
+            }
+            let stream = header[0];
+            let Some((mut stdout, mut stderr)) = outputs.take() else {
+                break;
+            };
+            let written = tokio::task::spawn_blocking(move || {
+                // 讀取端已經關閉時丟棄輸出，繼續讀取直到容器停止
+                let _ = match stream {
+                    1 => stdout.write_all(&frame),
+                    2 => stderr.write_all(&frame),
+                    _ => Ok(()),
+                };
+                (stdout, stderr)
+            })
+            .await;
+            outputs = written.ok();
+        }
+    }
+
+    // 原始模式下寫入 janus 自己的 stdout 和 stderr
+    pub fn inherited_outputs() -> io::Result<(File, File)> {
+        Ok((
+            File::from(io::stdout().as_fd().try_clone_to_owned()?),
+            File::from(io::stderr().as_fd().try_clone_to_owned()?),
+        ))
+    }
+}
+
+#[cfg(unix)]
+pub use unix::inherited_outputs;
+
+#[cfg(not(unix))]
+impl Api {
+    pub async fn remove_stale(&self, _container: &ContainerName) {}
+
+    pub async fn run(&self, _spec: &ContainerSpec, _outputs: Option<(File, File)>) -> io::Result<ContainerHandle> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "image is only supported on Unix"))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn inherited_outputs() -> io::Result<(File, File)> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "image is only supported on Unix"))
+}
+
+// 運行中的容器；退出碼由後台任務等待，容器刪除後才報告退出，此時日誌已經讀完
+#[derive(Debug)]
+pub struct ContainerHandle {
+    api: Api,
+    id: String,
+    pid: Option<u32>,
+    exit: watch::Receiver<Option<i64>>,
+    stdout: Option<ChildStdout>,
+    stderr: Option<ChildStderr>,
+}
+
+impl ContainerHandle {
+    fn exited(&self) -> Option<ExitStatus> {
+        self.exit.borrow().map(exit_status)
+    }
+
+    // 信號經由 API 發送，不等待請求完成
+    fn send_signal(&self, signal: i32) {
+        #[cfg(unix)]
+        {
+            let api = self.api.clone();
+            let id = self.id.clone();
+            tokio::spawn(async move {
+                let _ = api.kill(&id, signal).await;
+            });
+        }
+        #[cfg(not(unix))]
+        let _ = signal;
+    }
+}
+
+// 容器的退出碼；被信號殺死的容器報告 128 + 信號值
+#[cfg(unix)]
+fn exit_status(code: i64) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(((code & 0xff) as i32) << 8)
+}
+
+#[cfg(not(unix))]
+fn exit_status(code: i64) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+impl ProcessHandle for ContainerHandle {
+    fn id(&self) -> Option<u32> {
+        self.pid.filter(|_| self.exit.borrow().is_none())
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        Ok(self.exited())
+    }
+
+    fn wait(&mut self) -> WaitFuture<'_> {
+        Box::pin(async move {
+            let _ = self.exit.wait_for(Option::is_some).await;
+            self.exited().ok_or_else(|| io::Error::other("container wait task ended"))
+        })
+    }
+
+    fn start_kill(&mut self) -> io::Result<()> {
+        self.send_signal(9);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> io::Result<()> {
+        self.send_signal(signal);
+        Ok(())
+    }
+
+    fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.stdout.take()
+    }
+
+    fn take_stderr(&mut self) -> Option<ChildStderr> {
+        self.stderr.take()
+    }
+}
@@ -0,0 +1,120 @@
+// 協助保存崩潰進程的 core 文件：解析內核 core_pattern 找到文件，移動到 core_dump_dir
+use chrono::Local;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 內核寫入 core 文件的位置
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoreLocation {
+    // core_pattern 以 | 開頭時 core 交給處理程序（如 systemd-coredump），不寫入文件
+    Piped(String),
+    // 可能的 core 文件路徑的 glob 模式
+    File(String),
+}
+
+#[cfg(target_os = "linux")]
+pub fn locate(pid: u32, working_dir: &Path) -> Option<CoreLocation> {
+    let pattern = fs::read_to_string("/proc/sys/kernel/core_pattern").ok()?;
+    let uses_pid = fs::read_to_string("/proc/sys/kernel/core_uses_pid")
+        .is_ok_and(|value| value.trim() == "1");
+    Some(resolve_pattern(pattern.trim(), uses_pid, pid, working_dir))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn locate(_pid: u32, _working_dir: &Path) -> Option<CoreLocation> {
+    None
+}
+
+// 展開 core_pattern：%p 替換為 pid，其他說明符無法還原，用通配符代替
+pub fn resolve_pattern(pattern: &str, uses_pid: bool, pid: u32, working_dir: &Path) -> CoreLocation {
+    if let Some(handler) = pattern.strip_prefix('|') {
+        return CoreLocation::Piped(handler.to_string());
+    }
+
+    let mut glob = String::new();
+    let mut has_pid = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            glob.push_str(&glob::Pattern::escape(&c.to_string()));
+            continue;
+        }
+        match chars.next() {
+            Some('%') => glob.push('%'),
+            Some('p') | Some('P') => {
+                has_pid = true;
+                glob.push_str(&pid.to_string());
+            }
+            Some(_) => glob.push('*'),
+            None => {}
+        }
+    }
+    // core_uses_pid 為 1 且模式中沒有 %p 時，內核會在文件名後追加 .pid
+    if uses_pid && !has_pid {
+        glob.push_str(&format!(".{}", pid));
+    }
+
+    // 相對路徑相對於崩潰進程的工作目錄
+    if !pattern.starts_with('/') {
+        glob = format!("{}/{}", glob::Pattern::escape(&working_dir.display().to_string()), glob);
+    }
+    CoreLocation::File(glob)
+}
+
+// 找到最新的匹配文件並移動到 dest_dir，文件名包含進程名和時間戳
+pub fn preserve(location: &CoreLocation, dest_dir: &Path, process_name: &str) -> io::Result<Option<PathBuf>> {
+    let CoreLocation::File(pattern) = location else {
+        return Ok(None);
+    };
+    let newest = glob::glob(pattern)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        .filter_map(|path| path.ok())
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+            Some((modified, path))
+        })
+        .max_by_key(|(modified, _)| *modified);
+    let Some((_, source)) = newest else {
+        return Ok(None);
+    };
+
+    fs::create_dir_all(dest_dir)?;
+    let target = dest_dir.join(format!(
+        "{}-{}.core",
+        process_name,
+        Local::now().format("%Y%m%dT%H%M%S")
+    ));
+    // 跨文件系統時不能重命名，改為複製後刪除
+    if fs::rename(&source, &target).is_err() {
+        fs::copy(&source, &target)?;
+        fs::remove_file(&source)?;
+    }
+    Ok(Some(target))
+}
+
+// 在子進程中取消 core 文件大小限制，使崩潰時能夠寫入 core
+#[cfg(unix)]
+pub fn enable_core_dumps() -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: libc::RLIM_INFINITY,
+        rlim_max: libc::RLIM_INFINITY,
+    };
+    // SAFETY: limit 指向有效的 rlimit；硬限制不允許提高時退回只提高軟限制
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } == 0 {
+        return Ok(());
+    }
+
+    let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: current 指向有效的 rlimit
+    unsafe {
+        if libc::getrlimit(libc::RLIMIT_CORE, &mut current) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        current.rlim_cur = current.rlim_max;
+        if libc::setrlimit(libc::RLIMIT_CORE, &current) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Instant};
+
+use crate::error::{JanusError, Result};
+use crate::logging::handler::LogHandler;
+use crate::logging::LogType;
+
+use super::http;
+use super::notify::NotifyState;
+
+// 單次探測的超時時間
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(2);
+// 重試間隔從 INITIAL_BACKOFF 開始倍增，直到 MAX_BACKOFF
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+// 檢查 READY=1 的間隔
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// 檢查 wait_for_path 的間隔
+const PATH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// janus 之外的啟動依賴，例如其他容器中的數據庫或 HTTP 服務
+#[derive(Debug, Clone, PartialEq)]
+pub enum Endpoint {
+    Tcp { address: String },
+    Http { endpoint: http::Endpoint, path: String },
+}
+
+impl Endpoint {
+    pub fn parse(url: &str) -> Result<Self> {
+        if let Some(address) = url.strip_prefix("tcp://") {
+            return Ok(Endpoint::Tcp { address: address.to_string() });
+        }
+
+        if let Some(rest) = url.strip_prefix("http://") {
+            let path = rest.find('/').map_or("/", |index| &rest[index..]);
+            if let Some(endpoint) = http::Endpoint::parse(url) {
+                return Ok(Endpoint::Http {
+                    endpoint,
+                    path: path.to_string(),
+                });
+            }
+        }
+
+        Err(JanusError::Config(format!(
+            "Unsupported wait_for endpoint '{}', expected tcp:// or http://",
+            url
+        )))
+    }
+
+    async fn check(&self) -> bool {
+        match timeout(ATTEMPT_TIMEOUT, self.probe()).await {
+            Ok(Ok(ready)) => ready,
+            _ => false,
+        }
+    }
+
+    async fn probe(&self) -> http::Result<bool> {
+        match self {
+            Endpoint::Tcp { address } => {
+                TcpStream::connect(address).await.map_err(|e| e.to_string())?;
+                Ok(true)
+            }
+            Endpoint::Http { endpoint, path } => {
+                let request = format!(
+                    "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                    path, endpoint.authority
+                );
+                let response = endpoint.send(&request, None).await?;
+                let (status, _) = http::split_response(&response);
+                Ok(matches!(status, Some(200..=399)))
+            }
+        }
+    }
+}
+
+// 依次等待所有端點可用，超過 wait_timeout 秒則返回錯誤
+pub async fn wait_for_endpoints(
+    name: &str,
+    endpoints: &[String],
+    wait_timeout: Duration,
+    log_handler: &LogHandler,
+) -> Result<()> {
+    let deadline = Instant::now() + wait_timeout;
+
+    for url in endpoints {
+        let endpoint = Endpoint::parse(url)?;
+        let mut backoff = INITIAL_BACKOFF;
+
+        log_handler.log(name, LogType::System, &format!("Waiting for {}", url));
+
+        while !endpoint.check().await {
+            if Instant::now() + backoff > deadline {
+                return Err(JanusError::Process(format!(
+                    "Timed out waiting for {} after {} seconds",
+                    url,
+                    wait_timeout.as_secs()
+                )));
+            }
+
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+
+        log_handler.log(name, LogType::System, &format!("{} is ready", url));
+    }
+
+    Ok(())
+}
+
+// 相對路徑按進程的工作目錄解析
+pub fn resolve_path(path: &Path, working_dir: Option<&str>) -> PathBuf {
+    match working_dir {
+        Some(dir) if path.is_relative() => Path::new(dir).join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+// 依次等待所有路徑出現（例如掛載的卷或密鑰文件），超過 wait_timeout 秒則返回錯誤
+pub async fn wait_for_paths(
+    name: &str,
+    paths: &[PathBuf],
+    wait_timeout: Duration,
+    log_handler: &LogHandler,
+) -> Result<()> {
+    let deadline = Instant::now() + wait_timeout;
+
+    for path in paths {
+        if path.exists() {
+            continue;
+        }
+        log_handler.log(name, LogType::System, &format!("Waiting for path {}", path.display()));
+
+        while !path.exists() {
+            if Instant::now() + PATH_POLL_INTERVAL > deadline {
+                return Err(JanusError::Process(format!(
+                    "Timed out waiting for path {} after {} seconds",
+                    path.display(),
+                    wait_timeout.as_secs()
+                )));
+            }
+
+            sleep(PATH_POLL_INTERVAL).await;
+        }
+
+        log_handler.log(name, LogType::System, &format!("{} is available", path.display()));
+    }
+
+    Ok(())
+}
+
+// 等待 requires 中通過 NOTIFY_SOCKET 報告就緒的進程發送 READY=1，超過 wait_timeout 秒則返回錯誤
+pub async fn wait_for_ready(
+    name: &str,
+    required: &[(String, NotifyState)],
+    wait_timeout: Duration,
+    log_handler: &LogHandler,
+) -> Result<()> {
+    let deadline = Instant::now() + wait_timeout;
+
+    for (required_name, state) in required {
+        log_handler.log(name, LogType::System, &format!("Waiting for {} to be ready", required_name));
+
+        while !state.is_ready() {
+            if Instant::now() + READY_POLL_INTERVAL > deadline {
+                return Err(JanusError::Process(format!(
+                    "Timed out waiting for {} to be ready after {} seconds",
+                    required_name,
+                    wait_timeout.as_secs()
+                )));
+            }
+
+            sleep(READY_POLL_INTERVAL).await;
+        }
+    }
+
+    Ok(())
+}
+
+// 啟動一個進程之前需要等待的依賴：wait_for_path、wait_for 端點和尚未就緒的 requires 進程。
+// 由進程管理器收集，調用者在不持有管理器鎖的情況下等待，之後再啟動進程
+pub struct StartWait {
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+    pub endpoints: Vec<String>,
+    pub required: Vec<(String, NotifyState)>,
+    pub timeout: Duration,
+    pub log_handler: LogHandler,
+}
+
+impl StartWait {
+    pub async fn wait(&self) -> Result<()> {
+        wait_for_paths(&self.name, &self.paths, self.timeout, &self.log_handler).await?;
+        wait_for_endpoints(&self.name, &self.endpoints, self.timeout, &self.log_handler).await?;
+        wait_for_ready(&self.name, &self.required, self.timeout, &self.log_handler).await
+    }
+}
+
+// 按 requires 排序：被依賴的進程排在前面；存在未知進程或循環依賴時返回錯誤
+pub fn start_order(requires: &HashMap<String, Vec<String>>) -> std::result::Result<Vec<String>, String> {
+    start_order_by(requires, |_| Duration::ZERO)
+}
+
+// 同 start_order，但 expected 較長的進程 (連同它依賴的進程) 排在前面，相同時按名稱
+pub fn start_order_by(
+    requires: &HashMap<String, Vec<String>>,
+    expected: impl Fn(&str) -> Duration,
+) -> std::result::Result<Vec<String>, String> {
+    fn visit(
+        name: &str,
+        requires: &HashMap<String, Vec<String>>,
+        visiting: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> std::result::Result<(), String> {
+        if order.iter().any(|done| done == name) {
+            return Ok(());
+        }
+        if let Some(index) = visiting.iter().position(|pending| pending == name) {
+            let mut cycle = visiting[index..].to_vec();
+            cycle.push(name.to_string());
+            return Err(format!("circular requires: {}", cycle.join(" -> ")));
+        }
+
+        visiting.push(name.to_string());
+        for required in requires.get(name).into_iter().flatten() {
+            if !requires.contains_key(required) {
+                return Err(format!("{} requires unknown process {}", name, required));
+            }
+            visit(required, requires, visiting, order)?;
+        }
+        visiting.pop();
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut names: Vec<&String> = requires.keys().collect();
+    names.sort_by(|a, b| expected(b).cmp(&expected(a)).then(a.cmp(b)));
+
+    let mut order = Vec::new();
+    for name in names {
+        visit(name, requires, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+// 直接或間接依賴 name 的所有進程，按停止順序排列（依賴者在被依賴者之前）
+pub fn dependents(requires: &HashMap<String, Vec<String>>, name: &str) -> Vec<String> {
+    fn visit(
+        name: &str,
+        requires: &HashMap<String, Vec<String>>,
+        seen: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) {
+        let mut direct: Vec<&String> = requires
+            .iter()
+            .filter(|(_, required)| required.iter().any(|required| required == name))
+            .map(|(dependent, _)| dependent)
+            .collect();
+        direct.sort();
+
+        for dependent in direct {
+            if seen.insert(dependent.clone()) {
+                visit(dependent, requires, seen, order);
+                order.push(dependent.clone());
+            }
+        }
+    }
+
+    let mut order = Vec::new();
+    visit(name, requires, &mut HashSet::from([name.to_string()]), &mut order);
+    order
+}
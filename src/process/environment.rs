@@ -0,0 +1,130 @@
+use glob::Pattern;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// 按照 env_allow / env_deny 過濾從 janus 自身繼承的環境變量
+// 未設置 env_allow 時默認全部允許，env_deny 在 env_allow 之後生效
+pub fn filter_inherited<I>(vars: I, allow: Option<&[String]>, deny: Option<&[String]>) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let allow = allow.map(compile_patterns);
+    let deny = deny.map(compile_patterns).unwrap_or_default();
+
+    vars.into_iter()
+        .filter(|(key, _)| {
+            allow
+                .as_ref()
+                .is_none_or(|patterns| patterns.iter().any(|pattern| pattern.matches(key)))
+        })
+        .filter(|(key, _)| !deny.iter().any(|pattern| pattern.matches(key)))
+        .collect()
+}
+
+// 無效的模式在加載配置時已被拒絕，這裡直接跳過
+fn compile_patterns(patterns: &[String]) -> Vec<Pattern> {
+    patterns
+        .iter()
+        .filter_map(|pattern| Pattern::new(pattern).ok())
+        .collect()
+}
+
+// 讀取像 Kubernetes 掛載的 Secret、ConfigMap 或 downward API 卷那樣的目錄：每個文件是一個變量，
+// 文件名為變量名，內容去掉末尾的一個換行符後為變量值。隱藏條目 (如 ..data) 和子目錄被跳過，符號鏈接會被跟隨
+pub fn read_env_dir(dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(key) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if key.starts_with('.') || key.contains('=') || !entry.path().is_file() {
+            continue;
+        }
+
+        let content = fs::read(entry.path())?;
+        let value = String::from_utf8(content)
+            .ok()
+            .filter(|value| !value.contains('\0'))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not valid text", key)))?;
+        let value = value.strip_suffix('\n').unwrap_or(&value);
+        let value = value.strip_suffix('\r').unwrap_or(value).to_string();
+        vars.push((key, value));
+    }
+    vars.sort();
+    Ok(vars)
+}
+
+// 一個變量文件的名稱、修改時間、大小、inode 和 ctime；符號鏈接按目標計算，Kubernetes 切換 ..data 後也會變化。
+// 修改時間可以被改回原值 (例如 touch -r 或保留時間的複製)，替換文件後 inode 和 ctime 仍會變化
+type FileStamp = (String, Option<SystemTime>, u64, FileIdentity);
+
+// inode 和精確到納秒的 ctime
+type FileIdentity = (u64, i64, i64);
+
+#[cfg(unix)]
+fn file_identity(metadata: &fs::Metadata) -> FileIdentity {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.ino(), metadata.ctime(), metadata.ctime_nsec())
+}
+
+// 其他平台上只按修改時間和大小判斷
+#[cfg(not(unix))]
+fn file_identity(_metadata: &fs::Metadata) -> FileIdentity {
+    (0, 0, 0)
+}
+
+fn stamp_env_dir(dir: &Path) -> io::Result<Vec<FileStamp>> {
+    let mut stamps = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(key) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if key.starts_with('.') || key.contains('=') {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(entry.path()) else {
+            continue;
+        };
+        if metadata.is_file() {
+            stamps.push((key, metadata.modified().ok(), metadata.len(), file_identity(&metadata)));
+        }
+    }
+    stamps.sort();
+    Ok(stamps)
+}
+
+// 一個目錄上次讀取時的文件和讀到的變量
+#[derive(Debug, Clone)]
+struct CachedDir {
+    stamps: Vec<FileStamp>,
+    vars: Vec<(String, String)>,
+}
+
+// 一個進程啟動時讀取的 env_dirs，崩潰循環中的重啟不必每次重新讀取。
+// 目錄中的文件按修改時間、大小、inode 和 ctime 判斷是否變化；配置變更的進程按新配置重建，緩存隨之清空。
+#[derive(Debug, Clone, Default)]
+pub struct EnvCache {
+    dirs: HashMap<PathBuf, CachedDir>,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl EnvCache {
+    // 目錄中的文件沒有變化時返回上次讀到的變量
+    pub fn read_env_dir(&mut self, dir: &Path) -> io::Result<Vec<(String, String)>> {
+        let stamps = stamp_env_dir(dir)?;
+        if let Some(cached) = self.dirs.get(dir).filter(|cached| cached.stamps == stamps) {
+            self.hits += 1;
+            return Ok(cached.vars.clone());
+        }
+        self.misses += 1;
+        let vars = read_env_dir(dir)?;
+        self.dirs.insert(dir.to_path_buf(), CachedDir { stamps, vars: vars.clone() });
+        Ok(vars)
+    }
+}
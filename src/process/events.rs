@@ -0,0 +1,112 @@
+use chrono::{DateTime, Local};
+use tokio::sync::broadcast;
+
+// 訂閱者處理過慢時最多緩存的事件數，超出後丟棄最舊的事件
+const EVENT_BUFFER: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Started,
+    StartFailed,
+    Exited,
+    Stopped,
+    GaveUp,
+    // 內核 OOM killer 殺死了進程或其子孫進程
+    OomKilled,
+    // 超過全局的 restart_rate_limit，重啟被暫緩
+    RestartPaused,
+    // 打開的文件描述符接近上限
+    FdsHigh,
+}
+
+impl EventKind {
+    // 插件、時間線和 janus timeline 使用的名稱
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::Started => "started",
+            EventKind::StartFailed => "start_failed",
+            EventKind::Exited => "exited",
+            EventKind::Stopped => "stopped",
+            EventKind::GaveUp => "gave_up",
+            EventKind::OomKilled => "oom_killed",
+            EventKind::RestartPaused => "restart_paused",
+            EventKind::FdsHigh => "fds_high",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessEvent {
+    pub timestamp: DateTime<Local>,
+    pub process: String,
+    pub kind: EventKind,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub detail: String,
+    // 只對 OomKilled 事件有值，單位為字節
+    pub memory_limit: Option<u64>,
+    pub memory_peak: Option<u64>,
+}
+
+impl ProcessEvent {
+    pub fn new(process: &str, kind: EventKind) -> Self {
+        Self {
+            timestamp: Local::now(),
+            process: process.to_string(),
+            kind,
+            pid: None,
+            exit_code: None,
+            detail: String::new(),
+            memory_limit: None,
+            memory_peak: None,
+        }
+    }
+
+    pub fn with_pid(mut self, pid: Option<u32>) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn with_memory(mut self, limit: Option<u64>, peak: Option<u64>) -> Self {
+        self.memory_limit = limit;
+        self.memory_peak = peak;
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+}
+
+// 進程生命週期事件的廣播通道，沒有訂閱者時事件直接丟棄
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ProcessEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    pub fn emit(&self, event: ProcessEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProcessEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
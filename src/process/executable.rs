@@ -0,0 +1,81 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+// 可執行文件的指紋，用於偵測文件被替換（例如新版本被複製到掛載卷中）
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryFingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+    inode: u64,
+}
+
+// 按照與啟動進程相同的規則解析命令對應的可執行文件路徑
+pub fn resolve(
+    command: &str,
+    env: &HashMap<String, String>,
+    working_dir: Option<&str>,
+) -> Option<PathBuf> {
+    let path = Path::new(command);
+
+    // 包含路徑分隔符的命令直接按路徑處理
+    if path.components().count() > 1 {
+        return Some(match working_dir {
+            Some(dir) if path.is_relative() => Path::new(dir).join(path),
+            _ => path.to_path_buf(),
+        });
+    }
+
+    // 否則在 PATH 中搜索（進程自身的 PATH 優先）
+    let search_path = env
+        .get("PATH")
+        .cloned()
+        .or_else(|| std::env::var("PATH").ok())?;
+
+    std::env::split_paths(&search_path)
+        .map(|dir| dir.join(command))
+        .find(|candidate| candidate.is_file())
+}
+
+pub fn fingerprint(path: &Path) -> Option<BinaryFingerprint> {
+    let metadata = fs::metadata(path).ok()?;
+
+    #[cfg(unix)]
+    let inode = metadata.ino();
+    #[cfg(not(unix))]
+    let inode = 0;
+
+    Some(BinaryFingerprint {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+        inode,
+    })
+}
+
+// 計算文件內容的 SHA-256，返回小寫十六進制字符串
+pub fn sha256(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// 普通文件且至少有一個執行權限位
+pub fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+
+    #[cfg(unix)]
+    let executable = metadata.mode() & 0o111 != 0;
+    #[cfg(not(unix))]
+    let executable = true;
+
+    metadata.is_file() && executable
+}
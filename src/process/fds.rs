@@ -0,0 +1,58 @@
+// 打開的文件描述符接近上限時提前警告：洩漏描述符的進程最終在 accept 或 open 時因 EMFILE 失敗，
+// 那時往往已經無法正常服務。監控循環定期讀取 /proc/<pid>/fd 的數量和 /proc/<pid>/limits 中的軟限制
+// (進程可能自己調整過 RLIMIT_NOFILE，不能只看啟動時的值)，只支持 Linux
+use std::time::{Duration, Instant};
+
+// 兩次採樣的間隔；描述符很多時讀取 /proc/<pid>/fd 並不便宜
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+// 沒有配置 max_open_fds 時，打開的描述符達到軟限制的這個百分比即警告
+pub const DEFAULT_THRESHOLD_PERCENT: u64 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FdUsage {
+    pub open: u64,
+    // RLIMIT_NOFILE 的軟限制，unlimited 時為 None
+    pub limit: Option<u64>,
+}
+
+impl FdUsage {
+    #[cfg(target_os = "linux")]
+    pub fn read(pid: u32) -> Option<Self> {
+        let open = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()?.count() as u64;
+        let limit = std::fs::read_to_string(format!("/proc/{}/limits", pid))
+            .ok()
+            .and_then(|limits| parse_soft_limit(&limits));
+        Some(Self { open, limit })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn read(_pid: u32) -> Option<Self> {
+        None
+    }
+
+    // 配置的 max_open_fds，否則為軟限制的 DEFAULT_THRESHOLD_PERCENT；兩者都沒有時不警告
+    pub fn threshold(&self, configured: Option<u64>) -> Option<u64> {
+        configured.or_else(|| self.limit.map(|limit| limit * DEFAULT_THRESHOLD_PERCENT / 100))
+    }
+}
+
+// 形如 "Max open files            1024                 524288               files"，第一列為軟限制
+fn parse_soft_limit(limits: &str) -> Option<u64> {
+    limits
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+// 一個進程的採樣狀態，每次啟動時重置
+#[derive(Debug, Clone, Default)]
+pub struct FdMonitor {
+    pub next_sample_at: Option<Instant>,
+    // 最近一次採樣的結果
+    pub usage: Option<FdUsage>,
+    // 已經警告過，回落到閾值以下之前不再警告
+    pub warned: bool,
+}
@@ -0,0 +1,162 @@
+// 升級時把運行中的進程交給 exec 之後的新 janus：狀態寫入一個 memfd，輸出管道的讀取端去掉 CLOEXEC，
+// 兩者都跨越 exec 保留下來。exec 不改變 pid，子進程仍是新 janus 的子進程，可以照常回收。僅支持 Linux
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::logging::LogType;
+
+// 新 janus 從這個環境變量得知狀態所在的 fd
+pub const FD_ENV: &str = "JANUS_HANDOVER_FD";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Handover {
+    // 已完成的升級次數，客戶端據此判斷新 janus 是否已經接管
+    pub generation: u64,
+    pub processes: Vec<HandedProcess>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HandedProcess {
+    pub name: String,
+    // 沒有在運行的進程為 None
+    pub pid: Option<u32>,
+    pub restart_count: u32,
+    pub uptime_secs: u64,
+    pub manual_stop: bool,
+    // 輸出管道讀取端的 fd，exec 之前為 janus 中的原始 fd，寫入狀態時換成去掉 CLOEXEC 的副本
+    pub pipes: Vec<(i32, HandedStream)>,
+    pub capture_path: Option<PathBuf>,
+    // 從端口池分配的端口，新 janus 繼續保留給這個進程
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum HandedStream {
+    Stdout,
+    Stderr,
+}
+
+impl HandedStream {
+    pub fn from_log_type(log_type: &LogType) -> Self {
+        match log_type {
+            LogType::Stderr => Self::Stderr,
+            _ => Self::Stdout,
+        }
+    }
+
+    pub fn log_type(self) -> LogType {
+        match self {
+            Self::Stdout => LogType::Stdout,
+            Self::Stderr => LogType::Stderr,
+        }
+    }
+}
+
+// 不再讀取的管道；關閉後進程再寫入輸出時會收到 SIGPIPE
+#[cfg(unix)]
+pub fn close_pipes(pipes: &[(i32, HandedStream)]) {
+    for (fd, _) in pipes {
+        // SAFETY: fd 由上一個 janus 交接，沒有其他持有者
+        unsafe { libc::close(*fd) };
+    }
+}
+
+#[cfg(not(unix))]
+pub fn close_pipes(_pipes: &[(i32, HandedStream)]) {}
+
+// 當前可執行文件的路徑；文件已被新版本替換時 /proc/self/exe 帶有 " (deleted)" 後綴，此時指向新文件
+pub fn current_executable() -> io::Result<PathBuf> {
+    let path = std::env::current_exe()?;
+    match path.to_str().and_then(|path| path.strip_suffix(" (deleted)")) {
+        Some(replaced) => Ok(PathBuf::from(replaced)),
+        None => Ok(path),
+    }
+}
+
+// 以相同的命令行參數 exec 新的 janus，成功時不會返回
+#[cfg(target_os = "linux")]
+pub fn exec(executable: &Path, mut handover: Handover) -> io::Error {
+    use std::ffi::CString;
+    use std::fs::File;
+    use std::io::Write;
+    use std::os::fd::{FromRawFd, OwnedFd};
+    use std::os::unix::process::CommandExt;
+
+    // 副本在 exec 失敗時隨 inherited 一起關閉
+    let mut inherited = Vec::new();
+    for process in &mut handover.processes {
+        for (fd, _) in &mut process.pipes {
+            // SAFETY: F_DUPFD 複製一個有效的 fd，新 fd 不帶 CLOEXEC
+            let copy = unsafe { libc::fcntl(*fd, libc::F_DUPFD, 3) };
+            if copy < 0 {
+                return io::Error::last_os_error();
+            }
+            // SAFETY: copy 是剛複製出來、只由這裡持有的 fd
+            inherited.push(unsafe { OwnedFd::from_raw_fd(copy) });
+            *fd = copy;
+        }
+    }
+
+    let state = match serde_json::to_vec(&handover) {
+        Ok(state) => state,
+        Err(e) => return io::Error::other(e),
+    };
+    let name = CString::new("janus-handover").expect("name has no NUL");
+    // SAFETY: name 是有效的 C 字符串；不傳 MFD_CLOEXEC，fd 在 exec 後保留
+    let memfd = unsafe { libc::memfd_create(name.as_ptr(), 0) };
+    if memfd < 0 {
+        return io::Error::last_os_error();
+    }
+    // SAFETY: memfd 是剛創建、只由這裡持有的 fd
+    let mut file = unsafe { File::from_raw_fd(memfd) };
+    if let Err(e) = file.write_all(&state) {
+        return e;
+    }
+
+    let error = std::process::Command::new(executable)
+        .args(std::env::args_os().skip(1))
+        .env(FD_ENV, memfd.to_string())
+        .exec();
+    drop(inherited);
+    error
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn exec(_executable: &Path, _handover: Handover) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "upgrade --exec is only supported on Linux")
+}
+
+// 讀取上一個 janus 交接的狀態，並刪除環境變量，之後啟動的進程不會看到它；不是通過升級啟動時返回 None
+#[cfg(target_os = "linux")]
+pub fn take() -> Option<io::Result<Handover>> {
+    let fd: i32 = std::env::var(FD_ENV).ok()?.parse().ok()?;
+    std::env::remove_var(FD_ENV);
+    Some(read(fd))
+}
+
+#[cfg(target_os = "linux")]
+fn read(fd: i32) -> io::Result<Handover> {
+    use std::fs::File;
+    use std::io::{Read, Seek};
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: fd 由上一個 janus 在 exec 前創建並通過環境變量傳入，這裡取得其所有權
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.rewind()?;
+    let mut state = String::new();
+    file.read_to_string(&mut state)?;
+    let handover: Handover = serde_json::from_str(&state).map_err(io::Error::other)?;
+    // 交接的管道重新設置 CLOEXEC，之後啟動的進程不會繼承
+    for (fd, _) in handover.processes.iter().flat_map(|process| &process.pipes) {
+        // SAFETY: 只修改 fd 標誌
+        unsafe { libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+    }
+    Ok(handover)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn take() -> Option<io::Result<Handover>> {
+    None
+}
@@ -0,0 +1,101 @@
+// 讀取 Vault 密鑰、更新 Kubernetes pod 狀態和探測 wait_for 端點共用的最小 HTTP 客戶端：每個請求一個連接，
+// 使用 HTTP/1.0 讓響應不使用分塊編碼，讀到連接關閉即為完整的響應；https 時用 rustls 驗證服務器證書
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+// 錯誤直接寫入日誌
+pub type Result<T> = std::result::Result<T, String>;
+
+// 響應的最大字節數；Vault、Kubernetes 和健康檢查的響應都很小，超出時視為錯誤，不會無限佔用內存
+const MAX_RESPONSE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub tls: bool,
+    pub host: String,
+    // 請求的 Host 頭，省略默認端口時不含端口
+    pub authority: String,
+    address: String,
+}
+
+impl Endpoint {
+    // 只支持 http:// 和 https://，忽略地址中的路徑
+    pub fn parse(url: &str) -> Option<Self> {
+        let (tls, rest) = match url.split_once("://")? {
+            ("https", rest) => (true, rest),
+            ("http", rest) => (false, rest),
+            _ => return None,
+        };
+        let authority = rest.split('/').next().unwrap_or(rest);
+        if authority.is_empty() {
+            return None;
+        }
+        // IPv6 地址寫在方括號中，例如 https://[fd00::1]:6443
+        let (host, address) = match authority.rsplit_once(':').filter(|_| !authority.ends_with(']')) {
+            Some((host, _)) => (host, authority.to_string()),
+            None => (authority, format!("{}:{}", authority, if tls { 443 } else { 80 })),
+        };
+        Some(Self {
+            tls,
+            host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            authority: authority.to_string(),
+            address,
+        })
+    }
+
+    // request 是完整的請求，包括請求行、頭部和正文；返回完整的響應
+    pub async fn send(&self, request: &str, ca_cert: Option<&str>) -> Result<String> {
+        let stream = TcpStream::connect(&self.address).await.map_err(|e| e.to_string())?;
+        if self.tls {
+            let connector = tls_connector(ca_cert)?;
+            let server_name = tokio_rustls::rustls::pki_types::ServerName::try_from(self.host.clone())
+                .map_err(|e| format!("Invalid host {}: {}", self.host, e))?;
+            let stream = connector.connect(server_name, stream).await.map_err(|e| e.to_string())?;
+            exchange(stream, request).await.map_err(|e| e.to_string())
+        } else {
+            exchange(stream, request).await.map_err(|e| e.to_string())
+        }
+    }
+}
+
+// 響應的狀態碼和正文；響應無效時狀態碼為 None
+pub fn split_response(response: &str) -> (Option<u16>, &str) {
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((response, ""));
+    let status = head.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok());
+    (status, body)
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, request: &str) -> std::io::Result<String> {
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = Vec::new();
+    (&mut stream).take(MAX_RESPONSE + 1).read_to_end(&mut response).await?;
+    if response.len() as u64 > MAX_RESPONSE {
+        return Err(std::io::Error::other(format!("Response is larger than {} bytes", MAX_RESPONSE)));
+    }
+    Ok(String::from_utf8_lossy(&response).into_owned())
+}
+
+// 沒有指定 CA 證書時使用 SSL_CERT_FILE 或系統的 CA 證書
+fn tls_connector(ca_cert: Option<&str>) -> Result<tokio_rustls::TlsConnector> {
+    use tokio_rustls::rustls::crypto::ring;
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::CertificateDer;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+    let path = ca_cert
+        .map(str::to_string)
+        .or_else(|| std::env::var("SSL_CERT_FILE").ok())
+        .unwrap_or_else(|| "/etc/ssl/certs/ca-certificates.crt".to_string());
+    let mut roots = RootCertStore::empty();
+    let certs = CertificateDer::pem_file_iter(&path)
+        .map_err(|e| format!("Failed to read CA certificates {}: {}", path, e))?;
+    roots.add_parsable_certificates(certs.flatten());
+
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to configure TLS: {}", e))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(tokio_rustls::TlsConnector::from(Arc::new(config)))
+}
@@ -0,0 +1,228 @@
+// 在 Kubernetes 中運行時把進程的整體健康狀況寫到 janus 所在的 pod 上：global.kubernetes_status 的 condition
+// 是 pod 的一個狀態條件 (可列入 readinessGates)，annotation 是一個注解，集群中的工具不必讀取日誌就能看到
+// 每個 pod 中進程的狀態。使用 pod 的服務賬戶訪問 API，只在健康狀況變化時更新，失敗時定期重試
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::timeout;
+
+use crate::config::KubernetesStatusConfig;
+use crate::logging::handler::LogHandler;
+use crate::logging::LogType;
+
+use super::http::{self, Endpoint, Result};
+use super::snapshot::{ProcessSnapshot, StatusSnapshot};
+use super::ProcessStatus;
+
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+// 一次更新的最長時間，以及更新失敗後多久重試
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+// 失敗、放棄重啟或尚未發送 READY=1 的進程使整體不健康；停止、已完成和停止中的進程不影響
+#[derive(Debug, Clone, PartialEq)]
+pub struct Health {
+    // 不健康的進程及原因，按名稱排序
+    pub unhealthy: Vec<(String, String)>,
+}
+
+impl Health {
+    pub fn from_snapshot(snapshot: &StatusSnapshot) -> Self {
+        let mut unhealthy: Vec<(String, String)> = snapshot
+            .processes
+            .iter()
+            .filter_map(|process| Self::problem(process).map(|reason| (process.name.clone(), reason)))
+            .collect();
+        unhealthy.sort();
+        Self { unhealthy }
+    }
+
+    fn problem(process: &ProcessSnapshot) -> Option<String> {
+        let failed = [ProcessStatus::Failed, ProcessStatus::GaveUp].map(|status| format!("{:?}", status));
+        if process.status == format!("{:?}", ProcessStatus::Completed) {
+            None
+        } else if failed.contains(&process.status) {
+            Some(process.status.clone())
+        } else if process.status == format!("{:?}", ProcessStatus::Running) && process.ready == Some(false) {
+            Some("not ready".to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn healthy(&self) -> bool {
+        self.unhealthy.is_empty()
+    }
+
+    // 例如 "healthy" 或 "unhealthy: web (Failed), worker (not ready)"
+    pub fn summary(&self) -> String {
+        if self.healthy() {
+            return "healthy".to_string();
+        }
+        let processes: Vec<String> = self
+            .unhealthy
+            .iter()
+            .map(|(name, reason)| format!("{} ({})", name, reason))
+            .collect();
+        format!("unhealthy: {}", processes.join(", "))
+    }
+}
+
+// janus 所在的 pod 和訪問 API 所需的憑據
+#[derive(Debug, Clone)]
+pub struct PodReporter {
+    endpoint: Endpoint,
+    namespace: String,
+    pod: String,
+    // 每次請求時重新讀取，投射的服務賬戶令牌會定期輪換
+    token_file: PathBuf,
+    ca_cert: Option<String>,
+    condition: Option<String>,
+    annotation: Option<String>,
+}
+
+impl PodReporter {
+    // pod 名稱來自 POD_NAME (downward API) 或 HOSTNAME，命名空間來自 POD_NAMESPACE 或服務賬戶；
+    // 不在 Kubernetes 中運行時返回錯誤
+    pub fn from_env(config: &KubernetesStatusConfig) -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let dir = Path::new(SERVICE_ACCOUNT_DIR);
+
+        let url = match &config.api_server {
+            Some(url) => url.clone(),
+            None => {
+                let host = var("KUBERNETES_SERVICE_HOST")
+                    .ok_or_else(|| "KUBERNETES_SERVICE_HOST is not set, janus does not seem to run in Kubernetes".to_string())?;
+                let port = var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|| "443".to_string());
+                // IPv6 的服務地址需要方括號
+                let host = if host.contains(':') { format!("[{}]", host) } else { host };
+                format!("https://{}:{}", host, port)
+            }
+        };
+        let endpoint = Endpoint::parse(&url).ok_or_else(|| format!("Unsupported Kubernetes API server {}", url))?;
+        let pod = var("POD_NAME")
+            .or_else(|| var("HOSTNAME"))
+            .ok_or_else(|| "Neither POD_NAME nor HOSTNAME is set".to_string())?;
+        let namespace = var("POD_NAMESPACE")
+            .or_else(|| fs::read_to_string(dir.join("namespace")).ok().map(|namespace| namespace.trim().to_string()))
+            .ok_or_else(|| format!("Neither POD_NAMESPACE nor {}/namespace is available", SERVICE_ACCOUNT_DIR))?;
+        let ca_cert = dir.join("ca.crt");
+
+        Ok(Self {
+            endpoint,
+            namespace,
+            pod,
+            token_file: dir.join("token"),
+            ca_cert: ca_cert.exists().then(|| ca_cert.display().to_string()),
+            condition: config.condition.clone(),
+            annotation: config.annotation.clone(),
+        })
+    }
+
+    // 例如 "default/web-7d9c"
+    pub fn pod(&self) -> String {
+        format!("{}/{}", self.namespace, self.pod)
+    }
+
+    // transitioned 表示健康與否發生了變化，此時更新條件的 lastTransitionTime
+    pub async fn report(&self, health: &Health, transitioned: bool) -> Result<()> {
+        let path = format!("/api/v1/namespaces/{}/pods/{}", self.namespace, self.pod);
+        if let Some(condition) = &self.condition {
+            let (status, reason) = if health.healthy() {
+                ("True", "ProcessesHealthy")
+            } else {
+                ("False", "ProcessesUnhealthy")
+            };
+            let mut entry = json!({
+                "type": condition,
+                "status": status,
+                "reason": reason,
+                "message": health.summary(),
+            });
+            if transitioned {
+                entry["lastTransitionTime"] = json!(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+            }
+            // strategic merge patch 按 type 合併條件，不影響 kubelet 維護的其他條件
+            let patch = json!({ "status": { "conditions": [entry] } });
+            self.patch(&format!("{}/status", path), "application/strategic-merge-patch+json", &patch)
+                .await?;
+        }
+        if let Some(annotation) = &self.annotation {
+            let patch = json!({ "metadata": { "annotations": { annotation.as_str(): health.summary() } } });
+            self.patch(&path, "application/merge-patch+json", &patch).await?;
+        }
+        Ok(())
+    }
+
+    async fn patch(&self, path: &str, content_type: &str, patch: &Value) -> Result<()> {
+        let body = patch.to_string();
+        let mut request = format!(
+            "PATCH {} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\nContent-Type: {}\r\nContent-Length: {}\r\n",
+            path,
+            self.endpoint.authority,
+            content_type,
+            body.len()
+        );
+        if let Ok(token) = fs::read_to_string(&self.token_file) {
+            request.push_str(&format!("Authorization: Bearer {}\r\n", token.trim()));
+        }
+        request.push_str("\r\n");
+        request.push_str(&body);
+
+        let response = self.endpoint.send(&request, self.ca_cert.as_deref()).await?;
+        let (status, body) = http::split_response(&response);
+        if status == Some(200) {
+            return Ok(());
+        }
+        let details = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|json| json["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| "no details".to_string());
+        let status = status.map_or_else(|| "invalid response".to_string(), |code| format!("HTTP {}", code));
+        Err(format!("Kubernetes API returned {}: {}", status, details))
+    }
+}
+
+// 跟隨發布的狀態快照更新 pod，直到 janus 退出；失敗時只記錄一次，直到錯誤改變或恢復
+pub async fn watch(reporter: PodReporter, mut status: watch::Receiver<Arc<StatusSnapshot>>, log_handler: LogHandler) {
+    let mut reported: Option<Health> = None;
+    let mut last_error: Option<String> = None;
+    loop {
+        let health = Health::from_snapshot(&status.borrow_and_update());
+        let mut failed = false;
+        if reported.as_ref() != Some(&health) {
+            let transitioned = reported.as_ref().map(Health::healthy) != Some(health.healthy());
+            let result = timeout(REQUEST_TIMEOUT, reporter.report(&health, transitioned))
+                .await
+                .unwrap_or_else(|_| Err("Kubernetes API did not answer in time".to_string()));
+            match result {
+                Ok(()) => {
+                    if last_error.take().is_some() {
+                        log_handler.log("janus", LogType::System, &format!("Pod {} status updated again", reporter.pod()));
+                    }
+                    reported = Some(health);
+                }
+                Err(e) => {
+                    let error = format!("Failed to update pod {} status: {}", reporter.pod(), e);
+                    if last_error.as_ref() != Some(&error) {
+                        log_handler.log("janus", LogType::System, &error);
+                        last_error = Some(error);
+                    }
+                    failed = true;
+                }
+            }
+        }
+
+        if failed {
+            if let Ok(Err(_)) = timeout(RETRY_DELAY, status.changed()).await {
+                return;
+            }
+        } else if status.changed().await.is_err() {
+            return;
+        }
+    }
+}
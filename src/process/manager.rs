@@ -1,27 +1,140 @@
-use std::collections::HashMap;
-use std::time::Instant;
+use chrono::Local;
+use encoding_rs::Encoding;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
 use tokio::process::Command;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc, watch};
 
 use crate::config::manager::ConfigManager;
+use crate::config::{ChecksumMismatch, LogMode, LoggingConfig, MissedRunPolicy, OverlapPolicy, PortConfig, ProcessConfig, StartOrder};
 use crate::error::{JanusError, Result};
+use crate::logging::barrier::ReaderBarrier;
 use crate::logging::handler::LogHandler;
+use crate::logging::sampling::LogSampler;
 use crate::logging::LogType;
+use crate::signal::handler::DEFAULT_SHUTDOWN_GRACE;
+use crate::signal::names;
 
-use super::{ManagedProcess, ProcessStatus};
+use super::adopt::{self, AdoptedState};
+use super::handover::{self, HandedProcess, HandedStream, Handover};
+use super::memory::{cgroup, MemoryGroup};
+use super::notify::{self, NotifySocket};
+use super::pidfd::PidFd;
+use super::pipe::PipeMonitor;
+use super::spawner::ProcessHandle;
+use super::ports::{self, PortPool};
+use super::plugin::{PluginAction, PluginEvent, PluginJob, PluginRunner};
+use super::capture::OutputCapture;
+use super::clock::{Clock, JumpDetector, SystemClock};
+use super::events::{EventBus, EventKind, ProcessEvent};
+use super::fds::{self, FdMonitor, FdUsage};
+use super::queue::TaskQueue;
+use super::recent_output::RecentOutput;
+use super::restart_script::RestartDecision;
+use super::schedule::{ScheduleState, MISSED_RUN_GRACE};
+use super::secrets::SecretResolver;
+use super::shell;
+use super::snapshot::{ProcessSnapshot, StatusSnapshot};
+use super::spawner::{ProcessSpawner, TokioSpawner};
+use super::timeline::{Timeline, TimelineEntry};
+use super::tree::ProcessTable;
+use super::watchdog::{self, LineHeartbeat, WatchdogSocket};
+use super::container::{self, ContainerName, ContainerSpec};
+use super::coredump::{self, CoreLocation};
+use super::dependency::StartWait;
+use super::{accounting, dependency, environment, executable, render, schedule, scheduling, ManagedProcess, ProcessStatus, Termination};
+
+// 關閉時檢查進程是否已經退出的間隔
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// 等待依賴就緒的默認時長
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+// 等待依賴的任務發回的結果：進程名、等待編號和等待結果
+type WaitedStart = (String, u64, Result<()>);
+
+// 進程退出後最多等待讀取任務處理剩餘輸出的時間；孫進程繼承了管道時讀取任務不會很快結束
+const OUTPUT_SETTLE_TIMEOUT: Duration = Duration::from_millis(500);
 
 pub struct ProcessManager {
     processes: HashMap<String, ManagedProcess>,
     log_handler: LogHandler,
+    // 當前生效的配置；重新加載時整體替換，持有舊快照的操作看到的配置保持一致
+    config_manager: Option<Arc<ConfigManager>>,
+    // 每次成功重新加載後遞增
+    config_version: u64,
+    // 從任務隊列啟動、尚未結束的進程
+    queued_running: HashSet<String>,
+    events: EventBus,
+    // 等待交給插件的事件，監控循環每輪處理一次
+    plugin_events: broadcast::Receiver<ProcessEvent>,
+    // 運行插件的任務，第一次有事件交給插件時啟動
+    plugin_runner: Option<PluginRunner>,
+    // 插件還沒有響應的 exited 事件數；期間暫緩這個進程已安排的重啟，插件仍可以取消它
+    plugin_holds: HashMap<String, usize>,
+    // 最新的狀態快照，供 status 請求在不獲取鎖的情況下讀取
+    status: watch::Sender<Arc<StatusSnapshot>>,
+    // 已完成的 upgrade --exec 次數
+    generation: u64,
+    // 等待監控循環執行的升級，值為新 janus 的可執行文件
+    upgrade_to: Option<PathBuf>,
+    // 解析 env 中的密鑰引用，例如 vault:secret/data/app#password
+    secrets: SecretResolver,
+    // port = "auto" 的進程的端口
+    ports: PortPool,
+    // 所有進程最近因異常退出而重啟的時間，用於 restart_rate_limit
+    recent_restarts: VecDeque<Instant>,
+    // 超過 restart_rate_limit 而暫緩重啟的進程
+    paused_restarts: HashSet<String>,
+    // 每個進程最近的生命週期事件，gRPC 接口不獲取管理器的鎖即可讀取
+    timeline: Arc<Mutex<Timeline>>,
+    // 監督邏輯使用的時鐘，測試中可以替換為手動推進的時鐘
+    clock: Arc<dyn Clock>,
+    // 發現牆上時間的跳變，之後重新安排定時任務
+    clock_jumps: JumpDetector,
+    // 啟動子進程的後端
+    spawner: Arc<dyn ProcessSpawner>,
+    // 正在等待依賴的啟動，值為本次等待的編號；編號不符的等待結果已經過時
+    dependency_waits: HashMap<String, u64>,
+    next_dependency_wait: u64,
+    // 等待依賴的任務結束後發回結果，監控循環每輪處理一次
+    waited_starts: (mpsc::UnboundedSender<WaitedStart>, mpsc::UnboundedReceiver<WaitedStart>),
 }
 
 impl ProcessManager {
     pub fn new_empty() -> Self {
-        Self {
+        let events = EventBus::new();
+        let manager = Self {
             processes: HashMap::new(),
             log_handler: LogHandler::new("info"),
-        }
+            config_manager: None,
+            config_version: 0,
+            queued_running: HashSet::new(),
+            plugin_events: events.subscribe(),
+            plugin_runner: None,
+            plugin_holds: HashMap::new(),
+            events,
+            status: watch::channel(Arc::default()).0,
+            generation: 0,
+            upgrade_to: None,
+            secrets: SecretResolver::default(),
+            ports: PortPool::default(),
+            recent_restarts: VecDeque::new(),
+            paused_restarts: HashSet::new(),
+            timeline: Arc::default(),
+            clock: Arc::new(SystemClock),
+            clock_jumps: JumpDetector::default(),
+            spawner: Arc::new(TokioSpawner),
+            dependency_waits: HashMap::new(),
+            next_dependency_wait: 0,
+            waited_starts: mpsc::unbounded_channel(),
+        };
+        manager.publish_status();
+        manager
     }
 
     pub fn new(config_manager: ConfigManager, log_handler: LogHandler) -> Self {
@@ -29,42 +142,1950 @@ impl ProcessManager {
         let processes = config_manager
             .get_process_configs()
             .iter()
-            .map(|config| {
-                let process = ManagedProcess {
-                    name: config.name.clone(),
-                    command: config.command.clone(),
-                    args: config.args.clone().unwrap_or_default(),
-                    env: config.env.clone().unwrap_or_default(),
-                    working_dir: config.working_dir.clone(),
-                    auto_restart: config.auto_restart.unwrap_or(false),
-                    restart_count: 0,
-                    restart_limit: config.restart_limit,
-                    restart_delay: config.restart_delay.unwrap_or(1),
-                    status: ProcessStatus::Stopped,
-                    process: None,
-                    start_time: None,
-                };
-                (config.name.clone(), process)
-            })
-            .collect();
+            .map(|config| (config.name.clone(), ManagedProcess::from_config(config)))
+            .collect::<HashMap<_, _>>();
+        for process in processes.values() {
+            Self::register_display_name(&log_handler, process);
+        }
+        let events = EventBus::new();
+        let ports = PortPool::new(Self::port_range(&config_manager));
+        let timeline = Self::load_timeline(&config_manager, &log_handler);
 
-        Self {
+        let manager = Self {
             processes,
             log_handler,
+            config_manager: Some(Arc::new(config_manager)),
+            config_version: 1,
+            queued_running: HashSet::new(),
+            plugin_events: events.subscribe(),
+            plugin_runner: None,
+            plugin_holds: HashMap::new(),
+            events,
+            status: watch::channel(Arc::default()).0,
+            generation: 0,
+            upgrade_to: None,
+            secrets: SecretResolver::default(),
+            ports,
+            recent_restarts: VecDeque::new(),
+            paused_restarts: HashSet::new(),
+            timeline: Arc::new(Mutex::new(timeline)),
+            clock: Arc::new(SystemClock),
+            clock_jumps: JumpDetector::default(),
+            spawner: Arc::new(TokioSpawner),
+            dependency_waits: HashMap::new(),
+            next_dependency_wait: 0,
+            waited_starts: mpsc::unbounded_channel(),
+        };
+        manager.publish_status();
+        manager
+    }
+
+    // 從 global.timeline_file 恢復時間線；讀取失敗時從空的時間線開始，之後照常寫入該文件
+    fn load_timeline(config_manager: &ConfigManager, log_handler: &LogHandler) -> Timeline {
+        let Some(path) = config_manager.get_global_config().timeline_file.as_deref().map(PathBuf::from) else {
+            return Timeline::default();
+        };
+        Timeline::load(&path).unwrap_or_else(|e| {
+            log_handler.log("janus", LogType::System, &format!("Failed to read timeline {}: {}", path.display(), e));
+            let mut timeline = Timeline::default();
+            timeline.set_file(Some(path));
+            timeline
+        })
+    }
+
+    fn port_range(config_manager: &ConfigManager) -> std::ops::RangeInclusive<u16> {
+        config_manager
+            .get_global_config()
+            .port_range
+            .as_deref()
+            .and_then(ports::parse_range)
+            .unwrap_or(ports::DEFAULT_RANGE)
+    }
+
+    // 替換默認從環境變量配置的密鑰提供者
+    pub fn with_secret_resolver(mut self, secrets: SecretResolver) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    // 替換默認的系統時鐘
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self.clock_jumps = JumpDetector::default();
+        self
+    }
+
+    // 替換默認直接運行命令的進程啟動方式
+    pub fn with_spawner(mut self, spawner: Arc<dyn ProcessSpawner>) -> Self {
+        self.spawner = spawner;
+        self
+    }
+
+    pub fn get_log_handler(&self) -> &LogHandler {
+        &self.log_handler
+    }
+    
+    // 訂閱之後發生的進程生命週期事件
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ProcessEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn get_config_manager(&self) -> Option<&ConfigManager> {
+        self.config_manager.as_deref()
+    }
+    
+    // 當前配置的快照，釋放進程管理器的鎖之後仍可使用，不受之後的重新加載影響
+    pub fn config_snapshot(&self) -> Option<Arc<ConfigManager>> {
+        self.config_manager.clone()
+    }
+    
+    pub fn config_version(&self) -> u64 {
+        self.config_version
+    }
+
+    // 最近發布的狀態快照，讀取時不需要獲取進程管理器的鎖
+    pub fn subscribe_status(&self) -> watch::Receiver<Arc<StatusSnapshot>> {
+        self.status.subscribe()
+    }
+
+    pub fn timeline(&self) -> Arc<Mutex<Timeline>> {
+        self.timeline.clone()
+    }
+    
+    // 監控循環每輪以及啟動、停止進程和重新加載之後發布新的快照；
+    // 耗時的操作持有鎖時，讀者看到的是操作開始前的狀態，而不是一直等待
+    pub fn publish_status(&self) {
+        self.status.send_replace(Arc::new(self.snapshot()));
+    }
+    
+    pub fn snapshot(&self) -> StatusSnapshot {
+        let mut processes: Vec<ProcessSnapshot> = self
+            .processes
+            .values()
+            .map(ProcessSnapshot::from_process)
+            .collect();
+        processes.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        StatusSnapshot {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            config_path: self.config_manager.as_ref().map(|c| c.get_config_path().to_string()),
+            config_sha256: self.config_manager.as_ref().map(|c| c.get_config_hash().to_string()),
+            config_version: self.config_version,
+            generation: self.generation,
+            config_reload_pending: self.is_config_reload_pending(),
+            log_output: self.log_handler.write_stats(),
+            processes,
+            config: self.config_manager.clone(),
         }
     }
 
+    // 配置文件在加載後被修改，需要 SIGHUP 重新加載才會生效
+    pub fn is_config_reload_pending(&self) -> bool {
+        self.config_manager
+            .as_ref()
+            .is_some_and(|config_manager| config_manager.is_changed_on_disk())
+    }
+
     pub fn get_all_processes(&self) -> &HashMap<String, ManagedProcess> {
         &self.processes
     }
 
-    // get_process 方法雖然當前未被使用，但保留它以便將來擴展
     pub fn get_process(&self, name: &str) -> Option<&ManagedProcess> {
         self.processes.get(name)
     }
 
-    pub fn get_process_mut(&mut self, name: &str) -> Option<&mut ManagedProcess> {
-        self.processes.get_mut(name)
+    // 直接或間接 requires name 的進程，按停止順序排列
+    pub fn dependents(&self, name: &str) -> Vec<String> {
+        dependency::dependents(&self.requires_graph(), name)
+    }
+    
+    // 將選中的進程按 requires 排序，被依賴的進程先啟動
+    pub fn start_order(&self, names: &[String]) -> Vec<String> {
+        let slowest_first = self
+            .config_manager
+            .as_ref()
+            .is_some_and(|config_manager| config_manager.get_global_config().start_order == Some(StartOrder::SlowestFirst));
+        let expected = |name: &str| {
+            self.processes
+                .get(name)
+                .and_then(|process| process.expected_start_duration)
+                .filter(|_| slowest_first)
+                .unwrap_or_default()
+        };
+        match dependency::start_order_by(&self.requires_graph(), expected) {
+            Ok(order) => order.into_iter().filter(|name| names.contains(name)).collect(),
+            Err(_) => names.to_vec(),
+        }
+    }
+    
+    // 未配置 wait_timeout 時默認 DEFAULT_WAIT_TIMEOUT；依賴的進程預計啟動較慢時延長到它 expected_start_duration 的兩倍
+    pub fn wait_timeout(&self, name: &str) -> Duration {
+        let Some(process) = self.processes.get(name) else {
+            return DEFAULT_WAIT_TIMEOUT;
+        };
+        if let Some(wait_timeout) = process.wait_timeout {
+            return Duration::from_secs(wait_timeout);
+        }
+        process
+            .requires
+            .iter()
+            .filter_map(|required| self.processes.get(required)?.expected_start_duration)
+            .map(|expected| expected * 2)
+            .fold(DEFAULT_WAIT_TIMEOUT, Duration::max)
+    }
+    
+    // 邊車視為依賴其主進程
+    fn requires_graph(&self) -> HashMap<String, Vec<String>> {
+        self.processes
+            .iter()
+            .map(|(name, process)| {
+                let mut requires = process.requires.clone();
+                requires.extend(process.sidecar_of.clone());
+                (name.clone(), requires)
+            })
+            .collect()
+    }
+    
+    pub fn get_process_mut(&mut self, name: &str) -> Option<&mut ManagedProcess> {
+        self.processes.get_mut(name)
+    }
+
+    pub async fn stop_process(&mut self, name: &str) -> Result<()> {
+        if self.begin_stop(name) {
+            loop {
+                tokio::time::sleep(SHUTDOWN_POLL_INTERVAL).await;
+                if let Some(result) = self.poll_stop(name).await {
+                    return result;
+                }
+            }
+        }
+        
+        let result = self.kill_process(name, "").await;
+        self.stop_sidecars(name).await;
+        self.publish_status();
+        result
+    }
+    
+    // 設置了 stop_timeout 的運行中進程先收到 SIGTERM 並進入 Stopping 狀態；返回是否需要用 poll_stop 等待它退出
+    pub fn begin_stop(&mut self, name: &str) -> bool {
+        let Some(process) = self.processes.get_mut(name) else {
+            return false;
+        };
+        if process.status == ProcessStatus::Stopping {
+            return true;
+        }
+        let Some(timeout) = process.stop_timeout.filter(|_| process.pid().is_some()) else {
+            return false;
+        };
+        
+        process.status = ProcessStatus::Stopping;
+        process.stopping_since = Some(self.clock.now());
+        self.log_handler.log(
+            name,
+            LogType::System,
+            &format!("Stopping, waiting up to {}s for the process to exit", timeout.as_secs()),
+        );
+        self.terminate_process(name);
+        self.publish_status();
+        true
+    }
+    
+    // 檢查 Stopping 狀態的進程：已退出時完成停止，超過 stop_timeout 仍在運行時改用 SIGKILL，
+    // 並在日誌、stopped 事件和運行記錄中註明；仍在等待時返回 None。調用之間不必持有鎖，期間仍可 kill
+    pub async fn poll_stop(&mut self, name: &str) -> Option<Result<()>> {
+        let Some(process) = self.processes.get(name) else {
+            return Some(Err(JanusError::Process(format!("Process not found: {}", name))));
+        };
+        if process.status != ProcessStatus::Stopping {
+            return Some(Ok(()));
+        }
+        let elapsed = process.stopping_since.map(|since| self.clock.now().saturating_duration_since(since)).unwrap_or_default();
+        let timeout = process.stop_timeout.unwrap_or_default();
+        
+        self.settle_output(name).await;
+        let result = if self.reap_for_shutdown(name) {
+            Ok(())
+        } else if elapsed >= timeout {
+            let detail = format!("Did not stop within {}s, required SIGKILL", timeout.as_secs());
+            self.log_handler.log(name, LogType::System, &detail);
+            let result = self.kill_process(name, &detail).await;
+            if let Some(run) = self.processes.get_mut(name).and_then(|process| process.run_history.back_mut()) {
+                run.required_kill = true;
+            }
+            result
+        } else {
+            // 每次檢查都發布一次，status 中的停止計時保持更新
+            self.publish_status();
+            return None;
+        };
+        
+        self.record_stop_duration(name, elapsed);
+        self.stop_sidecars(name).await;
+        self.publish_status();
+        Some(result)
+    }
+    
+    // 主進程不再運行時停止它的邊車，邊車在主進程再次啟動時一起啟動
+    async fn stop_sidecars(&mut self, main: &str) {
+        let sidecars: Vec<String> = self
+            .processes
+            .values()
+            .filter(|process| process.sidecar_of.as_deref() == Some(main) && process.pid().is_some())
+            .map(|process| process.name.clone())
+            .collect();
+        for name in sidecars {
+            if let Some(process) = self.processes.get_mut(&name) {
+                process.waiting_for_main = true;
+                process.restart_at = None;
+            }
+            self.log_handler.log(&name, LogType::System, &format!("Stopping with main process {}", main));
+            if let Err(e) = self.kill_process(&name, "").await {
+                self.log_handler.log(&name, LogType::System, &e.to_string());
+            }
+        }
+    }
+    
+    // 主進程啟動後啟動等待它的邊車；被用戶停止的邊車保持停止
+    async fn start_sidecars(&mut self, main: &str) {
+        let sidecars: Vec<String> = self
+            .processes
+            .values()
+            .filter(|process| {
+                process.sidecar_of.as_deref() == Some(main)
+                    && process.waiting_for_main
+                    && !process.manual_stop
+                    && process.pid().is_none()
+            })
+            .map(|process| process.name.clone())
+            .collect();
+        for name in sidecars {
+            if let Some(process) = self.processes.get_mut(&name) {
+                process.waiting_for_main = false;
+            }
+            self.log_handler.log(&name, LogType::System, &format!("Starting with main process {}", main));
+            // 啟動失敗已在 launch_process 中記錄
+            let _ = self.launch_process(&name).await;
+        }
+    }
+    
+    // 用戶停止的進程不會被自動重啟或定時觸發，直到顯式啟動；指定 resume_after 時到期自動恢復
+    pub async fn stop_process_manually(&mut self, name: &str, resume_after: Option<Duration>) -> Result<()> {
+        self.mark_stopped_manually(name, resume_after)?;
+        self.stop_process(name).await
+    }
+    
+    // 只記錄用戶停止，不停止進程；調用者隨後用 stop_process 或 begin_stop 和 poll_stop 停止它
+    pub fn mark_stopped_manually(&mut self, name: &str, resume_after: Option<Duration>) -> Result<()> {
+        let process = self.processes.get_mut(name).ok_or_else(|| {
+            JanusError::Process(format!("Process not found: {}", name))
+        })?;
+        process.manual_stop = true;
+        self.dependency_waits.remove(name);
+        process.resume_at = resume_after.map(|resume_after| self.clock.now() + resume_after);
+        process.restart_at = None;
+        process.scheduled_run_at = None;
+        process.overlap_queued = false;
+        if let Some(resume_after) = resume_after {
+            self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Stopped manually, resuming in {}s", resume_after.as_secs()),
+            );
+        }
+        Ok(())
+    }
+    
+    // 用戶要求立即終止進程：與 stop 一樣不會被自動重啟，但在日誌和 stopped 事件中單獨記錄；
+    // 發送 SIGKILL 以外的信號時不等待進程退出，由監控循環回收
+    pub async fn kill_process_manually(&mut self, name: &str, signal: i32) -> Result<()> {
+        let process = self.processes.get_mut(name).ok_or_else(|| {
+            JanusError::Process(format!("Process not found: {}", name))
+        })?;
+        if process.pid().is_none() {
+            return Err(JanusError::Process(format!("Process is not running: {}", name)));
+        }
+        process.manual_stop = true;
+        process.resume_at = None;
+        process.restart_at = None;
+        process.scheduled_run_at = None;
+        process.overlap_queued = false;
+        let detail = format!("Killed by user with {}", names::signal_name(signal));
+        self.log_handler.log(name, LogType::System, &detail);
+        
+        #[cfg(unix)]
+        if signal != libc::SIGKILL {
+            self.signal_process(name, signal);
+            self.publish_status();
+            return Ok(());
+        }
+        
+        let result = self.kill_process(name, &detail).await;
+        self.stop_sidecars(name).await;
+        self.publish_status();
+        result
+    }
+    
+    async fn kill_process(&mut self, name: &str, detail: &str) -> Result<()> {
+        let process = self.processes.get_mut(name).ok_or_else(|| {
+            JanusError::Process(format!("Process not found: {}", name))
+        })?;
+        
+        if let Some(child) = &mut process.process {
+            let stop_started = self.clock.now();
+            let pid = child.id();
+            // 先發送 SIGKILL，在回收之前讀取本次運行的資源用量
+            child.start_kill().map_err(|e| {
+                JanusError::Process(format!("Failed to kill process: {}", e))
+            })?;
+            if let Some(pid) = pid {
+                process.exit_usage = accounting::wait_killed(pid).await.or(process.exit_usage);
+            }
+            child.wait().await.map_err(|e| {
+                JanusError::Process(format!("Failed to kill process: {}", e))
+            })?;
+            process.status = ProcessStatus::Stopped;
+            process.stopping_since = None;
+            process.process = None;
+            process.pidfd = None;
+            process.record_run();
+            Self::remove_cleanup_paths(process, &self.log_handler);
+            self.events.emit(ProcessEvent::new(name, EventKind::Stopped).with_pid(pid).with_detail(detail));
+            self.record_stop_duration(name, self.clock.now().saturating_duration_since(stop_started));
+        } else if let Some(pid) = process.adopted_pid {
+            adopt::kill(pid, process.pidfd.as_ref()).map_err(|e| {
+                JanusError::Process(format!("Failed to kill process: {}", e))
+            })?;
+            process.adopted_pid = None;
+            process.pidfd = None;
+            process.status = ProcessStatus::Stopped;
+            process.stopping_since = None;
+            process.record_run();
+            Self::remove_cleanup_paths(process, &self.log_handler);
+            self.events.emit(ProcessEvent::new(name, EventKind::Stopped).with_pid(Some(pid)).with_detail(detail));
+        }
+        
+        Ok(())
+    }
+    
+    fn record_stop_duration(&mut self, name: &str, stop_duration: Duration) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        process.last_stop_duration = Some(stop_duration);
+        if process.slow_stop_threshold.is_some_and(|threshold| stop_duration.as_secs() >= threshold) {
+            self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Slow stop: process took {:.3}s to exit", stop_duration.as_secs_f64()),
+            );
+        }
+    }
+
+    fn register_display_name(log_handler: &LogHandler, process: &ManagedProcess) {
+        let display_label = process.display_label();
+        let display_name = (display_label != process.name).then_some(display_label);
+        log_handler.set_display_name(&process.name, display_name);
+    }
+
+    // 重新讀取配置文件，並按差異新增、移除或重啟進程；未變更的進程保持運行
+    pub async fn reload(&mut self) -> Result<()> {
+        let config_path = self
+            .config_manager
+            .as_ref()
+            .map(|config_manager| config_manager.get_config_path().to_string())
+            .ok_or_else(|| JanusError::Config("No configuration loaded".to_string()))?;
+        
+        // 新配置完整加載並校驗通過後一次性替換，失敗時保留當前配置
+        let new_config_manager = Arc::new(ConfigManager::new(&config_path)?);
+        let old_config_manager = self.config_manager.replace(new_config_manager.clone());
+        self.config_version += 1;
+        self.ports.set_range(Self::port_range(&new_config_manager));
+        let timeline_file = new_config_manager.get_global_config().timeline_file.as_deref().map(PathBuf::from);
+        self.timeline.lock().unwrap().set_file(timeline_file);
+        
+        let old_configs: HashMap<String, ProcessConfig> = old_config_manager
+            .as_ref()
+            .map(|config_manager| {
+                config_manager
+                    .get_process_configs()
+                    .iter()
+                    .map(|config| (config.name.clone(), config.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let new_configs: Vec<ProcessConfig> = new_config_manager.get_process_configs().to_vec();
+        
+        let (mut added, mut removed, mut changed) = (0, 0, 0);
+        
+        // 移除配置中已不存在的進程
+        for name in old_configs.keys() {
+            if new_configs.iter().any(|config| &config.name == name) {
+                continue;
+            }
+            
+            if let Err(e) = self.stop_process(name).await {
+                self.log_handler.log(name, LogType::System, &format!("Failed to stop process: {}", e));
+            }
+            self.processes.remove(name);
+            self.dependency_waits.remove(name);
+            self.ports.release(name);
+            self.log_handler.log(name, LogType::System, "Process removed from configuration");
+            self.log_handler.set_display_name(name, None);
+            removed += 1;
+        }
+        
+        for config in &new_configs {
+            // 新增的進程為 None
+            let was_running = match old_configs.get(&config.name) {
+                Some(old_config) if old_config == config => continue,
+                Some(_) => {
+                    let was_running = self
+                        .processes
+                        .get(&config.name)
+                        .is_some_and(|process| process.status == ProcessStatus::Running);
+                    if let Err(e) = self.stop_process(&config.name).await {
+                        self.log_handler.log(&config.name, LogType::System, &format!("Failed to stop process: {}", e));
+                    }
+                    self.log_handler.log(&config.name, LogType::System, "Process configuration changed");
+                    changed += 1;
+                    Some(was_running)
+                }
+                None => {
+                    self.log_handler.log(&config.name, LogType::System, "Process added to configuration");
+                    added += 1;
+                    None
+                }
+            };
+            
+            // 未變更的進程在上面已跳過，保留原來的運行狀態；變更的進程按新配置重建，
+            // 但保留運行歷史和維護模式，重啟計數隨新配置重新開始
+            let mut process = ManagedProcess::from_config(config);
+            if let Some(old) = self.processes.get_mut(&config.name) {
+                process.run_history = std::mem::take(&mut old.run_history);
+                process.maintenance_until = old.maintenance_until;
+            }
+            // 原本在運行的已變更進程需要重新啟動；新增的進程和 start 一樣，定時任務和只按排程運行的進程
+            // 等待第一次觸發
+            let start = was_running.unwrap_or_else(|| process.starts_with_supervisor());
+            Self::register_display_name(&self.log_handler, &process);
+            self.processes.insert(config.name.clone(), process);
+            
+            // 不在 start_window 內時推遲到窗口打開；失敗已在 start_process 中記錄
+            if start && !self.defer_start(&config.name) {
+                let _ = self.start_process(&config.name).await;
+            }
+        }
+        
+        self.log_handler.log(
+            "janus",
+            LogType::System,
+            &format!(
+                "Configuration reloaded (version {}): {} added, {} removed, {} changed",
+                self.config_version, added, removed, changed
+            ),
+        );
+        
+        self.publish_status();
+        Ok(())
+    }
+
+    // 向所有運行中的進程發送信號
+    #[cfg(unix)]
+    pub fn signal_all(&self, signal: libc::c_int) {
+        for name in self.processes.keys() {
+            self.signal_process(name, signal);
+        }
+    }
+    
+    // 請求進程自行退出：Unix 上發送 SIGTERM；Windows 沒有 SIGTERM，改為向進程所在的進程組發送 CTRL_BREAK
+    pub fn terminate_process(&self, name: &str) {
+        #[cfg(unix)]
+        self.signal_process(name, libc::SIGTERM);
+        
+        #[cfg(windows)]
+        if let Some(pid) = self.processes.get(name).and_then(ManagedProcess::pid) {
+            use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+            // SAFETY: 只傳入整數參數；進程以 CREATE_NEW_PROCESS_GROUP 啟動，進程組 id 即其 pid
+            if unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) } == 0 {
+                let error = std::io::Error::last_os_error();
+                self.log_handler.log(name, LogType::System, &format!("Failed to send CTRL_BREAK: {}", error));
+            }
+        }
+    }
+    
+    #[cfg(unix)]
+    pub fn signal_process(&self, name: &str, signal: libc::c_int) {
+        let Some(process) = self.processes.get(name) else {
+            return;
+        };
+        let Some(pid) = process.pid() else {
+            return;
+        };
+        
+        // 有 pidfd 時信號只會送達這個進程本身，否則交給進程句柄或回退到 kill
+        let result = match (&process.pidfd, &process.process) {
+            (Some(pidfd), _) => pidfd.send_signal(signal),
+            (None, Some(child)) => child.signal(signal),
+            // SAFETY: kill 只接受整數參數，pid 來自接管的進程
+            (None, None) if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 => {
+                Err(std::io::Error::last_os_error())
+            }
+            (None, None) => Ok(()),
+        };
+        if let Err(e) = result {
+            self.log_handler.log(name, LogType::System, &format!("Failed to send signal: {}", e));
+        }
+    }
+
+    // 定期由監控循環調用：回收已退出的進程，處理自動重啟與可執行文件更新
+    pub async fn check_processes(&mut self) -> Result<()> {
+        self.check_clock_jump();
+        self.start_waited().await;
+        let process_names: Vec<String> = self.processes.keys().cloned().collect();
+        
+        // 同一輪檢查中按進程樹計量內存的進程共用一次 /proc 掃描
+        let mut table = None;
+        for name in process_names {
+            // 停止中的進程只等待它退出，不回收為崩潰也不做健康檢查
+            if self.processes.get(&name).is_some_and(|process| process.status == ProcessStatus::Stopping) {
+                if let Some(Err(e)) = self.poll_stop(&name).await {
+                    self.log_handler.log(&name, LogType::System, &format!("Failed to stop process: {}", e));
+                }
+                continue;
+            }
+            self.check_maintenance(&name);
+            self.check_resume(&name).await;
+            self.settle_output(&name).await;
+            self.reap_process(&name);
+            self.check_sidecars(&name).await;
+            self.check_oom(&name);
+            self.check_memory(&name, &mut table).await;
+            self.check_open_fds(&name);
+            self.check_watchdog(&name).await;
+            self.check_started(&name);
+            self.check_output_pipes(&name);
+            self.check_binary_change(&name).await;
+            self.check_stable(&name);
+            self.check_pending_restart(&name).await;
+            self.check_start_window(&name).await;
+            self.check_timer(&name).await;
+            self.check_schedule(&name).await;
+            self.check_scheduled_run(&name).await;
+            self.check_scheduled_restart(&name);
+        }
+        
+        self.run_plugins();
+        self.run_queue().await;
+        self.publish_status();
+        
+        Ok(())
+    }
+    
+    // 牆上時間跳變 (系統時間被調整，或主機休眠後恢復) 後重新安排定時任務。every 按單調時間計時，
+    // 休眠期間不前進，向前跳變時提前相應的時長，到期的任務在本輪運行一次；cron 排程和計劃重啟按牆上時間計算，
+    // 向前跳過的排程由 check_schedule 按 missed 策略處理，向後跳變時從新的時間重新計算，不必等待被撥回的時長。
+    // 重啟延遲和重啟預算只依賴單調時間，不受影響
+    fn check_clock_jump(&mut self) {
+        let Some(jump) = self.clock_jumps.check(self.clock.as_ref()) else {
+            return;
+        };
+        let forward = jump > chrono::TimeDelta::zero();
+        self.log_handler.log(
+            "janus",
+            LogType::System,
+            &format!(
+                "Wall clock jumped {} by {}s (clock change or resume from suspend), re-arming timers",
+                if forward { "forward" } else { "backward" },
+                jump.num_seconds().abs()
+            ),
+        );
+        
+        let now = self.clock.now();
+        let now_utc = self.clock.utc();
+        for process in self.processes.values_mut() {
+            if forward {
+                let skipped = jump.to_std().unwrap_or_default();
+                process.next_run_at = process
+                    .next_run_at
+                    .map(|at| at.checked_sub(skipped).map_or(now, |at| at.max(now)));
+            } else {
+                if let Some(schedule) = &process.schedule {
+                    process.next_scheduled_at = schedule.next_after(now_utc);
+                }
+                if let Some(schedule) = &process.scheduled_restart {
+                    process.next_restart_at = schedule.next_after(now_utc);
+                }
+            }
+        }
+    }
+    
+    // 主進程退出後停止它的邊車
+    async fn check_sidecars(&mut self, name: &str) {
+        if self.processes.get(name).is_some_and(|process| process.pid().is_none()) {
+            self.stop_sidecars(name).await;
+        }
+    }
+    
+    // 開啟或關閉 (duration 為 None) 維護模式
+    pub fn set_maintenance(&mut self, name: &str, duration: Option<Duration>) -> Result<()> {
+        let process = self.processes.get_mut(name).ok_or_else(|| {
+            JanusError::Process(format!("Process not found: {}", name))
+        })?;
+        
+        process.maintenance_until = duration.map(|duration| self.clock.now() + duration);
+        let message = match duration {
+            Some(duration) => format!(
+                "Maintenance mode on for {}s: auto-restarts and health checks are suspended",
+                duration.as_secs()
+            ),
+            None => "Maintenance mode off".to_string(),
+        };
+        self.log_handler.log(name, LogType::System, &message);
+        self.publish_status();
+        Ok(())
+    }
+    
+    pub fn processes_in_group(&self, group: &str) -> Vec<String> {
+        self.processes
+            .values()
+            .filter(|process| process.group.as_deref() == Some(group))
+            .map(|process| process.name.clone())
+            .collect()
+    }
+    
+    // 維護模式到期後自動恢復
+    fn check_maintenance(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if process.maintenance_until.is_none() || process.in_maintenance(self.clock.now()) {
+            return;
+        }
+        
+        process.maintenance_until = None;
+        self.log_handler.log(name, LogType::System, "Maintenance mode expired, auto-restarts resume");
+    }
+    
+    // stop-one --for 到期後恢復：常駐進程重新啟動，定時任務恢復按排程運行
+    async fn check_resume(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if !process.manual_stop || process.resume_at.is_none_or(|resume_at| resume_at > self.clock.now()) {
+            return;
+        }
+        
+        process.manual_stop = false;
+        process.resume_at = None;
+        self.log_handler.log(name, LogType::System, "Manual stop expired, resuming");
+        if process.starts_with_supervisor() {
+            // 啟動失敗已在 start_process 中記錄
+            let _ = self.start_process(name).await;
+        }
+    }
+    
+    // 定時任務按固定間隔觸發；錯過的週期不補跑
+    async fn check_timer(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let Some(every) = process.every else {
+            return;
+        };
+        
+        let now = self.clock.now();
+        let next_run_at = *process.next_run_at.get_or_insert(now + every);
+        if next_run_at > now {
+            return;
+        }
+        
+        let mut following = next_run_at + every;
+        while following <= now {
+            following += every;
+        }
+        process.next_run_at = Some(following);
+        
+        self.trigger_scheduled_run(name);
+    }
+    
+    // cron 任務在排程時間觸發；處理時已超過 MISSED_RUN_GRACE 的排程（主機休眠或 janus 未運行）
+    // 按 missed 策略跳過或補跑一次，錯過多次也只補跑一次
+    async fn check_schedule(&mut self, name: &str) {
+        let state = self.schedule_state();
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let Some(schedule) = &process.schedule else {
+            return;
+        };
+        
+        let now = self.clock.utc();
+        let scheduled_at = match process.next_scheduled_at {
+            Some(scheduled_at) => scheduled_at,
+            None => {
+                // 首次檢查時從上次處理的時間開始計算，以發現 janus 停止期間錯過的運行；
+                // 沒有記錄時記下當前時間，之後重啟也能發現錯過的運行
+                let last_run = state.as_ref().and_then(|state| state.last_run(name));
+                if let (Some(state), None) = (&state, last_run) {
+                    if let Err(e) = state.record(name, now) {
+                        self.log_handler.log(name, LogType::System, &format!("Failed to record schedule state: {}", e));
+                    }
+                }
+                let Some(scheduled_at) = schedule.next_after(last_run.unwrap_or(now)) else {
+                    return;
+                };
+                scheduled_at
+            }
+        };
+        if scheduled_at > now {
+            process.next_scheduled_at = Some(scheduled_at);
+            return;
+        }
+        
+        process.next_scheduled_at = schedule.next_after(now);
+        let missed = (now - scheduled_at).to_std().unwrap_or_default() > MISSED_RUN_GRACE;
+        let skip_missed = missed && process.missed == MissedRunPolicy::Skip;
+        
+        if let Some(state) = state {
+            if let Err(e) = state.record(name, now) {
+                self.log_handler.log(name, LogType::System, &format!("Failed to record schedule state: {}", e));
+            }
+        }
+        
+        if missed {
+            let scheduled_label = scheduled_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S");
+            let message = if skip_missed {
+                format!("Missed scheduled run at {}, skipping", scheduled_label)
+            } else {
+                format!("Missed scheduled run at {}, running it now", scheduled_label)
+            };
+            self.log_handler.log(name, LogType::System, &message);
+        }
+        if !skip_missed {
+            self.trigger_scheduled_run(name);
+        }
+    }
+    
+    // scheduled_restart 到期時優雅地重啟運行中的進程：先發送 SIGTERM，退出後由 reap_process 立即重新啟動，
+    // 超過 shutdown_grace 仍未退出則殺死；維護模式中或進程沒有運行時跳過這一次
+    fn check_scheduled_restart(&mut self, name: &str) {
+        let grace = self
+            .config_manager
+            .as_ref()
+            .and_then(|config_manager| config_manager.get_global_config().shutdown_grace)
+            .unwrap_or(DEFAULT_SHUTDOWN_GRACE);
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        
+        if process.recycling {
+            if process.recycle_kill_at.is_some_and(|at| at <= self.clock.now()) && process.pid().is_some() {
+                process.recycle_kill_at = None;
+                self.log_handler.log(
+                    name,
+                    LogType::System,
+                    &format!("Did not stop within {}s for the scheduled restart, killing", grace),
+                );
+                #[cfg(not(unix))]
+                if let Some(child) = &mut process.process {
+                    let _ = child.start_kill();
+                }
+                #[cfg(unix)]
+                self.signal_process(name, libc::SIGKILL);
+            }
+            return;
+        }
+        
+        let Some(schedule) = &process.scheduled_restart else {
+            return;
+        };
+        let now = self.clock.utc();
+        match process.next_restart_at {
+            Some(restart_at) if restart_at <= now => {}
+            Some(_) => return,
+            None => {
+                process.next_restart_at = schedule.next_after(now);
+                return;
+            }
+        }
+        process.next_restart_at = schedule.next_after(now);
+        
+        if process.pid().is_none() {
+            return;
+        }
+        if process.in_maintenance(self.clock.now()) {
+            self.log_handler.log(name, LogType::System, "In maintenance mode, skipping the scheduled restart");
+            return;
+        }
+        process.recycling = true;
+        process.recycle_kill_at = Some(self.clock.now() + Duration::from_secs(grace));
+        self.log_handler.log(name, LogType::System, "Scheduled restart, stopping gracefully");
+        self.terminate_process(name);
+    }
+    
+    // 到期的定時或 cron 運行在 jitter 內隨機延遲後才真正啟動，延遲期間再到期的運行被合併
+    fn trigger_scheduled_run(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if process.scheduled_run_at.is_some() {
+            return;
+        }
+        
+        let delay = process.jitter.map(schedule::random_delay).unwrap_or_default();
+        process.scheduled_run_at = Some(self.clock.now() + delay);
+        if !delay.is_zero() {
+            self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Delaying scheduled run by {}s (jitter)", delay.as_secs()),
+            );
+        }
+    }
+    
+    // 啟動已到時間的運行；上一次運行未結束時按 overlap 策略跳過、排隊或終止上一次運行
+    async fn check_scheduled_run(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let running = process.status == ProcessStatus::Running;
+        
+        let due = process
+            .scheduled_run_at
+            .is_some_and(|scheduled_run_at| scheduled_run_at <= self.clock.now());
+        if !due {
+            // 排隊的運行在上一次運行結束後啟動
+            if process.overlap_queued && !running {
+                process.overlap_queued = false;
+                self.start_scheduled_run(name, "Starting queued scheduled run").await;
+            }
+            return;
+        }
+        process.scheduled_run_at = None;
+        
+        if !running {
+            self.start_scheduled_run(name, "Starting scheduled run").await;
+            return;
+        }
+        
+        match process.overlap {
+            OverlapPolicy::Skip => {
+                self.log_handler.log(name, LogType::System, "Previous run still in progress, skipping scheduled run");
+            }
+            OverlapPolicy::Queue if process.overlap_queued => {
+                self.log_handler.log(name, LogType::System, "A scheduled run is already queued, skipping this one");
+            }
+            OverlapPolicy::Queue => {
+                process.overlap_queued = true;
+                self.log_handler.log(name, LogType::System, "Previous run still in progress, queueing scheduled run");
+            }
+            OverlapPolicy::KillPrevious => {
+                self.log_handler.log(name, LogType::System, "Previous run still in progress, stopping it");
+                if let Err(e) = self.stop_process(name).await {
+                    self.log_handler.log(
+                        name,
+                        LogType::System,
+                        &format!("Failed to stop previous run: {}", e),
+                    );
+                    return;
+                }
+                self.start_scheduled_run(name, "Starting scheduled run").await;
+            }
+        }
+    }
+    
+    async fn start_scheduled_run(&mut self, name: &str, message: &str) {
+        if self.processes.get(name).is_some_and(|process| process.manual_stop) {
+            self.log_handler.log(name, LogType::System, "Skipping scheduled run, process was stopped manually");
+            return;
+        }
+        self.log_handler.log(name, LogType::System, message);
+        if let Err(e) = self.start_process(name).await {
+            self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Failed to start scheduled run: {}", e),
+            );
+        }
+    }
+    
+    pub fn schedule_state(&self) -> Option<ScheduleState> {
+        self.config_manager
+            .as_ref()
+            .and_then(|config_manager| config_manager.get_global_config().schedule_state_file.as_ref())
+            .map(ScheduleState::new)
+    }
+    
+    pub fn task_queue(&self) -> Option<TaskQueue> {
+        self.config_manager
+            .as_ref()
+            .and_then(|config_manager| config_manager.get_global_config().queue_file.as_ref())
+            .map(TaskQueue::new)
+    }
+    
+    // 在並發上限內按順序啟動隊列中的任務
+    async fn run_queue(&mut self) {
+        let Some(queue) = self.task_queue() else {
+            return;
+        };
+        let concurrency = self
+            .config_manager
+            .as_ref()
+            .and_then(|config_manager| config_manager.get_global_config().queue_concurrency)
+            .unwrap_or(1);
+        
+        // 等待依賴的任務也佔用一個名額
+        let processes = &self.processes;
+        let dependency_waits = &self.dependency_waits;
+        self.queued_running.retain(|name| {
+            dependency_waits.contains_key(name)
+                || processes.get(name).is_some_and(|process| process.status == ProcessStatus::Running)
+        });
+        let slots = concurrency.saturating_sub(self.queued_running.len());
+        if slots == 0 {
+            return;
+        }
+        
+        // 同一任務正在運行時等待它結束，未知的任務名直接取出並記錄
+        let taken = match queue.take(slots, |name| {
+            processes.get(name).is_none_or(|process| process.status != ProcessStatus::Running)
+        }).await {
+            Ok(taken) => taken,
+            Err(e) => {
+                self.log_handler.log("janus", LogType::System, &format!("Failed to read task queue: {}", e));
+                return;
+            }
+        };
+        
+        for name in taken {
+            if !self.processes.contains_key(&name) {
+                self.log_handler.log(&name, LogType::System, "Queued task is not configured, skipping");
+                continue;
+            }
+            
+            self.log_handler.log(&name, LogType::System, "Starting queued task");
+            if self.start_process(&name).await.is_ok() {
+                self.queued_running.insert(name);
+            }
+        }
+    }
+
+    // 進程退出後讀取任務可能還沒處理完管道中剩餘的輸出；回收之前稍等它們讀到 EOF，
+    // 這樣最後幾行輸出排在退出日誌之前，也會出現在崩潰上下文中
+    async fn settle_output(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if process.output_readers.is_idle() {
+            return;
+        }
+        process.capture_usage();
+        // tokio 會保存 try_wait 得到的退出狀態，之後 reap_process 再次調用時仍然返回它
+        let exited = process
+            .process
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(Some(_))));
+        if exited {
+            let readers = process.output_readers.clone();
+            let _ = tokio::time::timeout(OUTPUT_SETTLE_TIMEOUT, readers.wait()).await;
+        }
+    }
+    
+    fn reap_process(&mut self, name: &str) {
+        let log_handler = self.log_handler.clone();
+        let events = self.events.clone();
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        
+        // 回收之後 child.id() 不再可用，先記下 pid 用於查找 core 文件
+        process.capture_usage();
+        let (pid, exit_status) = match (&mut process.process, process.adopted_pid) {
+            (Some(child), _) => {
+                let pid = child.id();
+                match child.try_wait() {
+                    Ok(Some(status)) => (pid, Some(status)),
+                    Ok(None) => return,
+                    Err(e) => {
+                        log_handler.log(
+                            name,
+                            LogType::System,
+                            &format!("Error waiting for process: {}", e),
+                        );
+                        return;
+                    }
+                }
+            }
+            // 接管的進程通常不是子進程，無法得知退出狀態
+            (None, Some(pid)) => match adopt::poll(pid, process.pidfd.as_ref()) {
+                AdoptedState::Running => return,
+                AdoptedState::Exited(status) => (Some(pid), status),
+            },
+            (None, None) => return,
+        };
+        
+        process.process = None;
+        process.adopted_pid = None;
+        process.pidfd = None;
+        let termination = exit_status.as_ref().map(Termination::from_status);
+        process.exit_code = exit_status.and_then(|status| status.code());
+        process.termination = termination;
+        process.record_run();
+        Self::remove_cleanup_paths(process, &log_handler);
+        
+        // ignore_exit_codes 中的退出碼表示有意的關閉，按正常退出處理；被信號終止時按 128 + 信號編號匹配
+        // 計劃重啟發送的 SIGTERM 同樣是預期的
+        let expected = process.recycling
+            || termination.is_some_and(|termination| process.ignore_exit_codes.contains(&termination.shell_code()));
+        let success = exit_status.is_some_and(|status| status.success());
+        process.status = if !success && !expected {
+            ProcessStatus::Failed
+        } else if process.oneshot_keep_status {
+            ProcessStatus::Completed
+        } else {
+            ProcessStatus::Stopped
+        };
+        let message = match termination {
+            Some(termination) if expected => format!("Process {} (expected, treated as a clean exit)", termination),
+            Some(termination) => format!("Process {}", termination),
+            None => "Adopted process exited (exit status unknown)".to_string(),
+        };
+        log_handler.log(name, LogType::System, &message);
+        events.emit(
+            ProcessEvent::new(name, EventKind::Exited)
+                .with_pid(pid)
+                .with_exit_code(process.exit_code)
+                .with_detail(message),
+        );
+        
+        // 異常退出時記錄最後幾行輸出，便於直接看到崩潰原因
+        if process.status == ProcessStatus::Failed {
+            let context = process.recent_output.lines();
+            if !context.is_empty() {
+                log_handler.log(name, LogType::System, &format!("Last {} output lines before exit:", context.len()));
+                for line in &context {
+                    log_handler.log(name, LogType::System, &format!("  | {}", line));
+                }
+            }
+            if let Some(run) = process.run_history.back_mut() {
+                run.crash_context = context;
+            }
+        }
+        
+        if let (Some(core_dump_dir), Some(pid), Some(termination)) = (process.core_dump_dir.clone(), pid, termination) {
+            let core_path = Self::preserve_core_dump(process, termination, pid, &core_dump_dir, &log_handler);
+            if let Some(run) = process.run_history.back_mut() {
+                run.core_path = core_path;
+            }
+        }
+        
+        // 計劃重啟不受 auto_restart 影響，立即重新啟動且不計入重啟次數
+        if process.recycling {
+            process.recycling = false;
+            process.recycle_kill_at = None;
+            process.restart_counted = false;
+            process.restart_at = Some(self.clock.now());
+            log_handler.log(name, LogType::System, "Restarting as scheduled");
+            return;
+        }
+        
+        // 一次性進程正常結束屬於預期行為，不需要重啟
+        if !process.auto_restart || process.status == ProcessStatus::Completed {
+            return;
+        }
+        
+        if process.manual_stop {
+            return;
+        }
+        
+        // 維護期間退出的進程保持停止，不計入重啟次數，由操作人員決定何時再啟動
+        if process.in_maintenance(self.clock.now()) {
+            log_handler.log(name, LogType::System, "In maintenance mode, not restarting");
+            return;
+        }
+        
+        // 預期的退出不計入重啟次數和重啟預算
+        if expected {
+            let Some(delay) = Self::restart_delay(process, &log_handler) else {
+                return;
+            };
+            process.restart_counted = false;
+            process.restart_at = Some(self.clock.now() + Duration::from_secs(delay));
+            log_handler.log(
+                name,
+                LogType::System,
+                &format!("Restarting in {} seconds", delay),
+            );
+            return;
+        }
+        
+        // 檢查重啟次數限制，並按照延遲安排重啟
+        Self::schedule_restart(name, process, self.clock.now(), &log_handler, &events);
+    }
+    
+    // 崩潰和健康檢查觸發的重啟：檢查 restart_limit 和重啟預算，並按照延遲安排重啟；
+    // 全局的 restart_rate_limit 在重啟到期時由 check_pending_restart 檢查
+    fn schedule_restart(name: &str, process: &mut ManagedProcess, now: Instant, log_handler: &LogHandler, events: &EventBus) {
+        if process.restart_limit.is_some_and(|limit| process.restart_count >= limit) {
+            log_handler.log(
+                name,
+                LogType::System,
+                &format!("Restart limit reached ({}), not restarting", process.restart_count),
+            );
+        } else if process.restart_budget_exhausted(now) {
+            process.status = ProcessStatus::GaveUp;
+            let message = format!(
+                "Restart budget exhausted ({} restarts within {} seconds), giving up",
+                process.recent_restarts.len(),
+                process.restart_budget_window
+            );
+            log_handler.log(name, LogType::System, &message);
+            events.emit(ProcessEvent::new(name, EventKind::GaveUp).with_detail(message));
+        } else if let Some(delay) = Self::restart_delay(process, log_handler) {
+            process.recent_restarts.push_back(now);
+            process.restart_counted = true;
+            process.restart_at = Some(now + Duration::from_secs(delay));
+            log_handler.log(
+                name,
+                LogType::System,
+                &format!("Restarting in {} seconds", delay),
+            );
+        }
+    }
+    
+    // 由 restart_script 決定這次是否重啟以及延遲；腳本出錯時按 restart_delay 照常重啟
+    fn restart_delay(process: &ManagedProcess, log_handler: &LogHandler) -> Option<u64> {
+        let Some(script) = &process.restart_script else {
+            return Some(process.restart_delay);
+        };
+        
+        match script.decide(process) {
+            Ok(RestartDecision::Default) => Some(process.restart_delay),
+            Ok(RestartDecision::Delay(delay)) => Some(delay),
+            Ok(RestartDecision::Skip) => {
+                log_handler.log(&process.name, LogType::System, "Restart skipped by restart_script");
+                None
+            }
+            Err(e) => {
+                log_handler.log(&process.name, LogType::System, &format!("restart_script failed: {}", e));
+                Some(process.restart_delay)
+            }
+        }
+    }
+
+    // 刪除 cleanup_paths 中遺留的文件，例如崩潰後沒有刪除的 Unix 套接字，避免下次啟動時報告地址已被使用
+    fn remove_cleanup_paths(process: &ManagedProcess, log_handler: &LogHandler) {
+        for path in &process.cleanup_paths {
+            let path = dependency::resolve_path(path, process.working_dir.as_deref());
+            match std::fs::remove_file(&path) {
+                Ok(()) => log_handler.log(&process.name, LogType::System, &format!("Removed stale {}", path.display())),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log_handler.log(
+                    &process.name,
+                    LogType::System,
+                    &format!("Failed to remove {}: {}", path.display(), e),
+                ),
+            }
+        }
+    }
+    
+    // 進程崩潰並寫入 core 時，按 core_pattern 找到文件並移動到 core_dump_dir
+    fn preserve_core_dump(
+        process: &ManagedProcess,
+        termination: Termination,
+        pid: u32,
+        core_dump_dir: &str,
+        log_handler: &LogHandler,
+    ) -> Option<PathBuf> {
+        let name = &process.name;
+        let Termination::Signaled { core_dumped, .. } = termination else {
+            return None;
+        };
+        if !core_dumped {
+            log_handler.log(name, LogType::System, "No core file was written (check the core size limit and core_pattern)");
+            return None;
+        }
+        
+        let working_dir = process
+            .working_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        let Some(location) = coredump::locate(pid, &working_dir) else {
+            log_handler.log(name, LogType::System, "Core file location is unknown on this platform");
+            return None;
+        };
+        
+        match coredump::preserve(&location, Path::new(core_dump_dir), name) {
+            Ok(Some(path)) => {
+                log_handler.log(name, LogType::System, &format!("Core file saved to {}", path.display()));
+                Some(path)
+            }
+            Ok(None) => {
+                let message = match &location {
+                    CoreLocation::Piped(handler) => format!("Core was passed to the core_pattern handler: {}", handler),
+                    CoreLocation::File(pattern) => format!("No core file found matching {}", pattern),
+                };
+                log_handler.log(name, LogType::System, &message);
+                None
+            }
+            Err(e) => {
+                log_handler.log(name, LogType::System, &format!("Failed to save core file: {}", e));
+                None
+            }
+        }
+    }
+    
+    // 連續運行 stable_after 後清零重啟次數和重啟預算：很久以前的一串崩潰不再把偶爾崩潰一次的進程推到 restart_limit，
+    // restart_script 看到的 restart_count 也從頭計算
+    fn check_stable(&mut self, name: &str) {
+        let now = self.clock.now();
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let (Some(stable_after), Some(start_time)) = (process.stable_after, process.start_time) else {
+            return;
+        };
+        if process.status != ProcessStatus::Running || (process.restart_count == 0 && process.recent_restarts.is_empty()) {
+            return;
+        }
+        if now.saturating_duration_since(start_time) < stable_after {
+            return;
+        }
+        
+        let restart_count = process.restart_count;
+        process.restart_count = 0;
+        process.recent_restarts.clear();
+        self.log_handler.log(
+            name,
+            LogType::System,
+            &format!(
+                "Running stably for {}s, resetting restart count ({}) and restart budget",
+                stable_after.as_secs(),
+                restart_count
+            ),
+        );
+    }
+    
+    async fn check_pending_restart(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        
+        // 進入維護模式前已安排的重啟推遲到維護結束
+        if process.in_maintenance(self.clock.now()) {
+            return;
+        }
+        
+        match process.restart_at {
+            Some(restart_at) if restart_at <= self.clock.now() => {}
+            _ => return,
+        }
+        // 插件響應之前不重啟，它們可能取消重啟或修改重啟延遲
+        if self.plugin_holds.contains_key(name) {
+            return;
+        }
+        if self.defer_start(name) {
+            return;
+        }
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        
+        // 異常退出後的重啟計入全局的重啟速率；超過上限時暫緩非關鍵進程的重啟，直到窗口內的重啟次數回落
+        if process.restart_counted {
+            let critical = process.critical;
+            if self.restart_rate_exceeded() && !critical {
+                self.pause_restart(name);
+                return;
+            }
+            self.recent_restarts.push_back(self.clock.now());
+        }
+        if self.paused_restarts.remove(name) {
+            self.log_handler.log(name, LogType::System, "Restart rate is below the limit again, resuming restart");
+        }
+        
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        process.restart_at = None;
+        if process.restart_counted {
+            process.restart_count += 1;
+        }
+        
+        // 啟動失敗已在 start_process 中記錄
+        let _ = self.start_process(name).await;
+    }
+    
+    // 不在 start_window 內時推遲啟動，窗口打開時由監控循環啟動；返回是否推遲
+    pub fn defer_start(&mut self, name: &str) -> bool {
+        let now = self.clock.utc();
+        let Some(process) = self.processes.get_mut(name) else {
+            return false;
+        };
+        let Some(window) = process.start_window.as_ref().filter(|window| !window.allows(now)) else {
+            return false;
+        };
+        
+        let message = if window.has_ended(now) {
+            format!("Start window {} has ended, not starting automatically", window)
+        } else {
+            format!("Outside start window {}, starting once it opens", window)
+        };
+        process.waiting_for_window = true;
+        process.restart_at = None;
+        self.log_handler.log(name, LogType::System, &message);
+        self.publish_status();
+        true
+    }
+    
+    // 推遲的啟動在窗口打開時進行；期間被用戶停止或已經運行的進程不再啟動
+    async fn check_start_window(&mut self, name: &str) {
+        let Some(process) = self.processes.get(name) else {
+            return;
+        };
+        if !process.waiting_for_window || process.manual_stop || process.pid().is_some() {
+            return;
+        }
+        if process.start_window.as_ref().is_some_and(|window| !window.allows(self.clock.utc())) {
+            return;
+        }
+        
+        self.log_handler.log(name, LogType::System, "Start window opened, starting");
+        // 啟動失敗已在 start_process 中記錄
+        let _ = self.start_process(name).await;
+    }
+    
+    // 配置的 restart_rate_limit 和窗口
+    fn restart_rate(&self) -> Option<(u32, Duration)> {
+        let global = self.config_manager.as_ref()?.get_global_config();
+        let limit = global.restart_rate_limit?;
+        Some((limit, Duration::from_secs(global.restart_rate_window.unwrap_or(60))))
+    }
+    
+    // 清理窗口之前的重啟記錄，返回是否已達到 restart_rate_limit
+    fn restart_rate_exceeded(&mut self) -> bool {
+        let Some((limit, window)) = self.restart_rate() else {
+            return false;
+        };
+        
+        let now = self.clock.now();
+        while self
+            .recent_restarts
+            .front()
+            .is_some_and(|restarted_at| now.saturating_duration_since(*restarted_at) > window)
+        {
+            self.recent_restarts.pop_front();
+        }
+        
+        self.recent_restarts.len() >= limit as usize
+    }
+    
+    // 第一次暫緩某個進程的重啟時記錄並發出 RestartPaused 事件，交給插件發出告警
+    fn pause_restart(&mut self, name: &str) {
+        if !self.paused_restarts.insert(name.to_string()) {
+            return;
+        }
+        
+        let window = self.restart_rate().map_or(0, |(_, window)| window.as_secs());
+        let message = format!(
+            "Restart rate limit reached ({} restarts across all processes within {} seconds), restart paused",
+            self.recent_restarts.len(),
+            window
+        );
+        self.log_handler.log(name, LogType::System, &message);
+        self.events.emit(ProcessEvent::new(name, EventKind::RestartPaused).with_detail(message));
+    }
+
+    // 每輪一次：把新的事件記錄到時間線並提交給插件，然後應用已經返回的插件動作；
+    // 插件在獨立的任務中運行，等待插件響應時不持有管理器的鎖
+    fn run_plugins(&mut self) {
+        let plugins = self
+            .config_manager
+            .as_ref()
+            .and_then(|config_manager| config_manager.get_global_config().plugins.clone())
+            .unwrap_or_default();
+        
+        loop {
+            // 事件積壓超過緩衝區時跳過丟失的事件，繼續處理之後的事件
+            let event = match self.plugin_events.try_recv() {
+                Ok(event) => event,
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            };
+            self.record_timeline(&event);
+            if plugins.is_empty() {
+                continue;
+            }
+            let Some(process) = self.processes.get(&event.process) else {
+                continue;
+            };
+            
+            let restart_in = process
+                .restart_at
+                .map(|restart_at| restart_at.saturating_duration_since(self.clock.now()).as_secs());
+            let plugin_event = PluginEvent::new(
+                &event,
+                process.status == ProcessStatus::Failed,
+                process.restart_count,
+                restart_in,
+            );
+            if event.kind == EventKind::Exited {
+                *self.plugin_holds.entry(event.process.clone()).or_default() += 1;
+            }
+            let job = PluginJob { plugins: plugins.clone(), event, plugin_event };
+            self.plugin_runner.get_or_insert_with(PluginRunner::start).submit(job);
+        }
+        
+        while let Some(reply) = self.plugin_runner.as_mut().and_then(PluginRunner::try_recv) {
+            let event = reply.event;
+            if event.kind == EventKind::Exited {
+                if let Some(holds) = self.plugin_holds.get_mut(&event.process) {
+                    *holds -= 1;
+                    if *holds == 0 {
+                        self.plugin_holds.remove(&event.process);
+                    }
+                }
+            }
+            for (plugin, result) in reply.actions {
+                match result {
+                    Ok(action) => self.apply_plugin_action(&event, &plugin, action),
+                    Err(e) => self.log_handler.log(&event.process, LogType::System, &e.to_string()),
+                }
+            }
+        }
+    }
+    
+    fn record_timeline(&self, event: &ProcessEvent) {
+        let result = self.timeline.lock().unwrap().record(TimelineEntry::from_event(event));
+        if let Err(e) = result {
+            self.log_handler.log("janus", LogType::System, &format!("Failed to write timeline: {}", e));
+        }
+    }
+    
+    fn apply_plugin_action(&mut self, event: &ProcessEvent, plugin: &str, action: PluginAction) {
+        if event.kind != EventKind::Exited || action == PluginAction::default() {
+            return;
+        }
+        let Some(process) = self.processes.get_mut(&event.process) else {
+            return;
+        };
+        if process.restart_at.is_none() {
+            return;
+        }
+        
+        if action.suppress_restart {
+            process.restart_at = None;
+            // 取消的重啟不佔用重啟預算
+            if process.restart_counted {
+                process.recent_restarts.pop_back();
+            }
+            self.log_handler.log(&event.process, LogType::System, &format!("Restart suppressed by plugin {}", plugin));
+        } else if let Some(delay) = action.restart_delay {
+            process.restart_at = Some(self.clock.now() + Duration::from_secs(delay));
+            self.log_handler.log(
+                &event.process,
+                LogType::System,
+                &format!("Plugin {} changed the restart delay to {} seconds", plugin, delay),
+            );
+        }
+    }
+    
+    // 心跳停止超過 watchdog_timeout 時認為進程卡死並重啟；心跳文件和其他來源任一更新都算
+    async fn check_watchdog(&mut self, name: &str) {
+        let Some(process) = self.processes.get(name) else {
+            return;
+        };
+        let Some(timeout) = process.watchdog_timeout else {
+            return;
+        };
+        if process.status != ProcessStatus::Running || process.in_maintenance(self.clock.now()) {
+            return;
+        }
+        
+        let mut idle = process.heartbeat.elapsed();
+        if let Some(age) = process.watchdog_file.as_deref().and_then(watchdog::file_age) {
+            idle = idle.min(age);
+        }
+        if idle <= Duration::from_secs(timeout) {
+            return;
+        }
+        
+        self.log_handler.log(
+            name,
+            LogType::System,
+            &format!("Watchdog: no heartbeat for {}s, restarting process", idle.as_secs()),
+        );
+        self.restart_unhealthy(name).await;
+    }
+    
+    // 健康檢查 (watchdog、memory_limit) 判定進程卡死或失控時停止它，之後和崩潰一樣經過 restart_limit、
+    // 重啟預算和 restart_rate_limit 安排重啟，不會無限地重啟一個一直不健康的進程；不要求 auto_restart
+    async fn restart_unhealthy(&mut self, name: &str) {
+        if let Err(e) = self.stop_process(name).await {
+            self.log_handler.log(name, LogType::System, &format!("Failed to stop process: {}", e));
+            return;
+        }
+        let now = self.clock.now();
+        if let Some(process) = self.processes.get_mut(name) {
+            Self::schedule_restart(name, process, now, &self.log_handler, &self.events);
+        }
+        self.publish_status();
+    }
+    
+    // notify 進程發送 READY=1、或運行滿 expected_start_duration 後記錄啟動耗時；就緒前退出的進程不記錄
+    fn check_started(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        if process.awaiting_ready.is_some() && process.status != ProcessStatus::Running {
+            process.awaiting_ready = None;
+            return;
+        }
+        Self::note_started(name, process, self.clock.now(), &self.log_handler);
+    }
+    
+    // 進程已經就緒時記錄啟動耗時，達到 slow_start_threshold 時記錄警告
+    fn note_started(name: &str, process: &mut ManagedProcess, now: Instant, log_handler: &LogHandler) {
+        let Some((requested_at, spawned_at)) = process.awaiting_ready else {
+            return;
+        };
+        let Some(ready_at) = process.ready_at(spawned_at).filter(|ready_at| *ready_at <= now) else {
+            return;
+        };
+        process.awaiting_ready = None;
+        let start_duration = ready_at.saturating_duration_since(requested_at);
+        process.last_start_duration = Some(start_duration);
+        if process.slow_start_threshold.is_some_and(|threshold| start_duration.as_secs() >= threshold) {
+            log_handler.log(
+                name,
+                LogType::System,
+                &format!("Slow start: process took {:.3}s to start", start_duration.as_secs_f64()),
+            );
+        }
+    }
+    
+    // 內核 OOM killer 殺死進程時發出 OomKilled 事件並附上內存上限和峰值，交給插件通知，
+    // 否則進程只是被 SIGKILL 終止後重啟，看不出原因
+    fn check_oom(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let Some(seen) = process.oom_kills else {
+            return;
+        };
+        // 自己的 cgroup 在運行期間也檢查，能發現被殺死的子孫進程；共用容器的 cgroup 時
+        // 計數可能來自其他進程，只在進程被 SIGKILL (9) 終止後歸因於它
+        let running = process.pid().is_some();
+        let own_cgroup = process.memory_group.as_ref().and_then(MemoryGroup::cgroup).is_some();
+        if running && !own_cgroup {
+            return;
+        }
+        if !running {
+            // 進程退出後只檢查一次，之後的計數變化與它無關
+            process.oom_kills = None;
+            if !own_cgroup && !matches!(process.termination, Some(Termination::Signaled { signal: 9, .. })) {
+                return;
+            }
+        }
+        let Some(cgroup) = process.oom_cgroup() else {
+            return;
+        };
+        
+        let kills = cgroup::oom_kills(&cgroup).unwrap_or(seen);
+        if running {
+            process.oom_kills = Some(kills);
+        }
+        if kills <= seen {
+            return;
+        }
+        
+        let limit = cgroup::max(&cgroup).or(process.memory_limit);
+        let peak = cgroup::peak(&cgroup);
+        let mut usage = Vec::new();
+        if let Some(limit) = limit {
+            usage.push(format!("limit {} MiB", limit >> 20));
+        }
+        if let Some(peak) = peak {
+            usage.push(format!("peak {} MiB", peak >> 20));
+        }
+        let subject = if running { "a process in the group" } else { "the process" };
+        let mut message = format!("OOM killer killed {}", subject);
+        if !usage.is_empty() {
+            message.push_str(&format!(" ({})", usage.join(", ")));
+        }
+        
+        self.log_handler.log(name, LogType::System, &message);
+        self.events.emit(
+            ProcessEvent::new(name, EventKind::OomKilled)
+                .with_memory(limit, peak)
+                .with_detail(message),
+        );
+    }
+    
+    // 整組內存超出 memory_limit 時殺死整組進程並重啟，而不只是直接子進程
+    async fn check_memory(&mut self, name: &str, table: &mut Option<ProcessTable>) {
+        let Some(process) = self.processes.get(name) else {
+            return;
+        };
+        let (Some(limit), Some(group), Some(pid)) = (process.memory_limit, &process.memory_group, process.pid()) else {
+            return;
+        };
+        if process.status != ProcessStatus::Running || process.in_maintenance(self.clock.now()) {
+            return;
+        }
+        
+        let Some(usage) = group.usage(pid, table) else {
+            return;
+        };
+        if usage <= limit {
+            return;
+        }
+        
+        self.log_handler.log(
+            name,
+            LogType::System,
+            &format!(
+                "Memory limit exceeded: process group uses {} MiB of {} MiB, killing and restarting it",
+                usage >> 20,
+                limit >> 20
+            ),
+        );
+        group.kill(pid);
+        self.restart_unhealthy(name).await;
+    }
+    
+    // 每隔 fds::SAMPLE_INTERVAL 採樣打開的文件描述符，達到閾值時記錄一次警告並發出 FdsHigh 事件，
+    // 回落到閾值以下時再記錄一次；容器進程的 pid 是運行時的客戶端，不採樣
+    fn check_open_fds(&mut self, name: &str) {
+        let now = self.clock.now();
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        let Some(pid) = process.pid().filter(|_| process.image.is_none()) else {
+            return;
+        };
+        if process.fd_monitor.next_sample_at.is_some_and(|at| at > now) {
+            return;
+        }
+        
+        process.fd_monitor.next_sample_at = Some(now + fds::SAMPLE_INTERVAL);
+        process.fd_monitor.usage = FdUsage::read(pid);
+        let Some(usage) = process.fd_monitor.usage else {
+            return;
+        };
+        let Some(threshold) = usage.threshold(process.max_open_fds) else {
+            return;
+        };
+        let limit = usage.limit.map_or_else(|| "unlimited".to_string(), |limit| limit.to_string());
+        
+        if usage.open >= threshold && !process.fd_monitor.warned {
+            process.fd_monitor.warned = true;
+            let message = format!(
+                "Open file descriptors high: {} open, warning at {}, limit {}; the process may be leaking descriptors",
+                usage.open, threshold, limit
+            );
+            self.log_handler.log(name, LogType::System, &message);
+            self.events.emit(
+                ProcessEvent::new(name, EventKind::FdsHigh)
+                    .with_pid(Some(pid))
+                    .with_detail(message),
+            );
+        } else if usage.open < threshold && process.fd_monitor.warned {
+            process.fd_monitor.warned = false;
+            self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Open file descriptors back to {} (limit {})", usage.open, limit),
+            );
+        }
+    }
+    
+    // 管道寫滿說明進程正阻塞在寫輸出上，記錄一次警告，恢復時再記錄一次
+    fn check_output_pipes(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        
+        // 進程已退出時釋放管道
+        if process.process.is_none() {
+            process.pipe_monitors.clear();
+            return;
+        }
+        
+        for monitor in &mut process.pipe_monitors {
+            let message = match monitor.check() {
+                Some(true) => format!(
+                    "Output pipe full: process is blocked writing {} until janus catches up",
+                    monitor.stream.as_str()
+                ),
+                Some(false) => format!("Output pipe drained, {} is flowing again", monitor.stream.as_str()),
+                None => continue,
+            };
+            self.log_handler.log(name, LogType::System, &message);
+        }
+    }
+    
+    async fn check_binary_change(&mut self, name: &str) {
+        let Some(process) = self.processes.get_mut(name) else {
+            return;
+        };
+        
+        if !process.restart_on_binary_change || process.status != ProcessStatus::Running || process.in_maintenance(self.clock.now()) {
+            return;
+        }
+        
+        let current = executable::resolve(&process.command, &process.env, process.working_dir.as_deref())
+            .and_then(|path| executable::fingerprint(&path));
+        
+        // 文件暫時不存在（例如正在替換）時不做判斷
+        if current.is_none() || current == process.binary_fingerprint {
+            process.pending_binary_fingerprint = None;
+            return;
+        }
+        
+        // 等待文件在一個檢查週期內保持不變，避免在複製過程中重啟
+        if current != process.pending_binary_fingerprint {
+            process.pending_binary_fingerprint = current;
+            return;
+        }
+        
+        self.log_handler.log(
+            name,
+            LogType::System,
+            "Executable changed, restarting process",
+        );
+        
+        if let Err(e) = self.restart_process(name).await {
+            self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Failed to restart process: {}", e),
+            );
+        }
+    }
+
+    // 記錄 upgrade --exec 請求，監控循環在下一輪交接進程並 exec 新的 janus
+    pub fn request_upgrade(&mut self, executable: PathBuf) {
+        self.upgrade_to = Some(executable);
+    }
+
+    pub fn take_upgrade(&mut self) -> Option<PathBuf> {
+        self.upgrade_to.take()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // 交給新 janus 的狀態：每個進程的 pid、重啟次數和輸出管道；被用戶停止的進程在升級後保持停止
+    pub fn handover(&self) -> Handover {
+        let processes = self
+            .processes
+            .iter()
+            .map(|(name, process)| HandedProcess {
+                name: name.clone(),
+                pid: process.pid(),
+                restart_count: process.restart_count,
+                uptime_secs: process.start_time.map(|start| self.clock.now().saturating_duration_since(start).as_secs()).unwrap_or_default(),
+                manual_stop: process.manual_stop,
+                pipes: process.pid().map(|_| handed_pipes(process)).unwrap_or_default(),
+                capture_path: process.current_output.clone(),
+                port: self.ports.assigned(name),
+            })
+            .collect();
+        Handover {
+            generation: self.generation + 1,
+            processes,
+        }
+    }
+
+    // 接管上一個 janus 在升級前交接的進程，返回不需要再啟動的進程名；
+    // 配置中已經不存在的進程保持運行但不再受管理
+    pub fn resume_handover(&mut self, handover: Handover) -> Vec<String> {
+        self.generation = handover.generation;
+        let log_handler = self.log_handler.clone();
+        let mut resumed = Vec::new();
+        
+        for handed in handover.processes {
+            let Some(process) = self.processes.get_mut(&handed.name) else {
+                if let Some(pid) = handed.pid {
+                    log_handler.log(
+                        &handed.name,
+                        LogType::System,
+                        &format!("No longer in the config after upgrade, leaving pid {} unmanaged", pid),
+                    );
+                }
+                handover::close_pipes(&handed.pipes);
+                continue;
+            };
+            process.restart_count = handed.restart_count;
+            process.manual_stop = handed.manual_stop;
+            if let Some(port) = handed.port {
+                process.port = Some(port);
+                self.ports.claim(&handed.name, port);
+            }
+            let Some(pid) = handed.pid else {
+                if handed.manual_stop {
+                    resumed.push(handed.name);
+                }
+                continue;
+            };
+            
+            // exec 不改變 pid，進程仍是 janus 的子進程，按接管的進程回收
+            process.adopted_pid = Some(pid);
+            process.pidfd = PidFd::open(pid);
+            process.memory_group = process.memory_limit.map(|_| MemoryGroup::for_process(&handed.name));
+            process.oom_kills = process.oom_cgroup().and_then(|cgroup| cgroup::oom_kills(&cgroup));
+            process.fd_monitor = FdMonitor::default();
+            process.heartbeat.beat();
+            process.notify_state.set_ready(self.clock.now());
+            process.status = ProcessStatus::Running;
+            process.start_time = self.clock.now().checked_sub(Duration::from_secs(handed.uptime_secs)).or(Some(self.clock.now()));
+            process.exit_code = None;
+            process.termination = None;
+            process.restart_at = None;
+            
+            // socket 路徑包含 janus 的 pid，exec 後重新綁定到同一路徑，進程無需知道 janus 已經升級
+            if process.watchdog_socket {
+                match WatchdogSocket::bind(&handed.name, process.heartbeat.clone()) {
+                    Ok(listener) => process.watchdog_listener = Some(listener),
+                    Err(e) => log_handler.log(&handed.name, LogType::System, &format!("Failed to create watchdog socket: {}", e)),
+                }
+            }
+            if process.notify {
+                match NotifySocket::bind(
+                    &handed.name,
+                    process.notify_state.clone(),
+                    process.heartbeat.clone(),
+                    self.clock.clone(),
+                    log_handler.clone(),
+                ) {
+                    Ok(listener) => process.notify_listener = Some(listener),
+                    Err(e) => log_handler.log(&handed.name, LogType::System, &format!("Failed to create notify socket: {}", e)),
+                }
+            }
+            
+            let capture = match handed.capture_path {
+                Some(path) => match OutputCapture::reopen(path, process.capture_only) {
+                    Ok(capture) => Some(capture),
+                    Err(e) => {
+                        log_handler.log(&handed.name, LogType::System, &e.to_string());
+                        None
+                    }
+                },
+                None => None,
+            };
+            let sinks = OutputSinks::new(&handed.name, process, &log_handler, capture);
+            process.pipe_monitors = resume_pipes(handed.pipes, &sinks);
+            process.current_output = sinks.capture.map(|capture| capture.path);
+            
+            log_handler.log(&handed.name, LogType::System, &format!("Resumed after supervisor upgrade (pid {})", pid));
+            resumed.push(handed.name);
+        }
+        self.publish_status();
+        resumed
     }
 
     // start_all 方法雖然當前未被使用，但保留它以便將來擴展
@@ -86,83 +2107,366 @@ impl ProcessManager {
     }
 
     pub async fn stop_all(&mut self) -> Result<()> {
-        for (name, process) in &mut self.processes {
-            if process.status == ProcessStatus::Running {
-                if let Some(child) = &mut process.process {
-                    let log_handler = self.log_handler.clone();
-                    let name = name.clone();
-                    
-                    match child.kill().await {
-                        Ok(_) => {
-                            log_handler.log(
-                                &name,
-                                LogType::System,
-                                "Process stopped",
-                            );
-                            process.status = ProcessStatus::Stopped;
-                            process.process = None;
-                        }
-                        Err(e) => {
-                            log_handler.log(
-                                &name,
-                                LogType::System,
-                                &format!("Failed to stop process: {}", e),
-                            );
-                        }
+        let process_names: Vec<String> = self.processes.keys().cloned().collect();
+        self.stop_processes(&process_names).await?;
+        
+        // 退出前刪除心跳和通知 socket 文件
+        for process in self.processes.values_mut() {
+            process.watchdog_listener = None;
+            process.notify_listener = None;
+        }
+        Ok(())
+    }
+
+    // janus 退出前的關閉流程：按啟動順序的逆序停止進程，依賴它的進程和邊車都退出後才請求它退出 (SIGTERM 或 CTRL_BREAK)。
+    // 設置了 stop_timeout 的進程經由 begin_stop 和 poll_stop 停止，超過自己的 stop_timeout 被殺死；其他進程等待自行退出。
+    // grace 是整個流程的上限，超過後殺死剩餘的進程，再通過日誌的關閉屏障等待讀取任務處理完剩餘的輸出並刷新日誌；
+    // 調用者在整個過程中持有鎖，期間不再處理其他命令
+    pub async fn shutdown(&mut self, grace: Duration) -> Result<()> {
+        // 循環依賴時無法排序，所有進程同時停止
+        let graph = self.requires_graph();
+        let (order, dependents): (Vec<String>, HashMap<String, Vec<String>>) = match dependency::start_order(&graph) {
+            Ok(order) => {
+                let dependents = order.iter().map(|name| (name.clone(), dependency::dependents(&graph, name))).collect();
+                (order.into_iter().rev().collect(), dependents)
+            }
+            Err(_) => {
+                let mut names: Vec<String> = self.processes.keys().cloned().collect();
+                names.sort();
+                let dependents = names.iter().map(|name| (name.clone(), Vec::new())).collect();
+                (names, dependents)
+            }
+        };
+        
+        let deadline = self.clock.now() + grace;
+        let mut pending = order.clone();
+        let mut stopping = Vec::new();
+        let mut terminated = Vec::new();
+        loop {
+            let running: HashSet<String> = self
+                .processes
+                .values()
+                .filter(|process| process.pid().is_some())
+                .map(|process| process.name.clone())
+                .collect();
+            for name in std::mem::take(&mut pending) {
+                if !running.contains(&name) {
+                    continue;
+                }
+                if dependents[&name].iter().any(|dependent| running.contains(dependent)) {
+                    pending.push(name);
+                } else if self.begin_stop(&name) {
+                    stopping.push(name);
+                } else {
+                    self.terminate_process(&name);
+                    terminated.push(name);
+                }
+            }
+            
+            for name in std::mem::take(&mut stopping) {
+                match self.poll_stop(&name).await {
+                    Some(Ok(())) => {}
+                    Some(Err(e)) => self.log_handler.log(&name, LogType::System, &format!("Failed to stop process: {}", e)),
+                    None => stopping.push(name),
+                }
+            }
+            for name in std::mem::take(&mut terminated) {
+                self.settle_output(&name).await;
+                if !self.reap_for_shutdown(&name) {
+                    terminated.push(name);
+                }
+            }
+            
+            if pending.is_empty() && stopping.is_empty() && terminated.is_empty() {
+                break;
+            }
+            if self.clock.now() >= deadline {
+                // 超過總期限時不再等待各自的 stop_timeout，按停止順序殺死剩餘的進程
+                let detail = format!("Still running after shutdown_grace of {}s, killed", grace.as_secs());
+                for name in &order {
+                    if self.processes.get(name).and_then(ManagedProcess::pid).is_none() {
+                        continue;
                     }
+                    self.log_handler.log(name, LogType::System, &detail);
+                    if let Err(e) = self.kill_process(name, &detail).await {
+                        self.log_handler.log(name, LogType::System, &format!("Failed to stop process: {}", e));
+                    }
+                }
+                break;
+            }
+            tokio::time::sleep(SHUTDOWN_POLL_INTERVAL).await;
+        }
+        
+        let result = self.stop_all().await;
+        
+        // 進程退出後管道關閉，讀取任務隨之結束；遺留的孫進程仍持有管道時由調用者的總期限兜底
+        self.log_handler.drain().await;
+        result
+    }
+    
+    // 關閉期間回收已退出的進程，只記錄退出而不安排重啟；返回進程是否已不在運行
+    fn reap_for_shutdown(&mut self, name: &str) -> bool {
+        let Some(process) = self.processes.get_mut(name) else {
+            return true;
+        };
+        
+        process.capture_usage();
+        let (pid, exit_status) = match (&mut process.process, process.adopted_pid) {
+            (Some(child), _) => {
+                let pid = child.id();
+                match child.try_wait() {
+                    Ok(Some(status)) => (pid, Some(status)),
+                    // 出錯時留給 stop_all 殺死
+                    _ => return false,
                 }
             }
+            (None, Some(pid)) => match adopt::poll(pid, process.pidfd.as_ref()) {
+                AdoptedState::Running => return false,
+                AdoptedState::Exited(status) => (Some(pid), status),
+            },
+            (None, None) => return true,
+        };
+        
+        process.process = None;
+        process.adopted_pid = None;
+        process.pidfd = None;
+        let termination = exit_status.as_ref().map(Termination::from_status);
+        process.exit_code = exit_status.and_then(|status| status.code());
+        process.termination = termination;
+        process.status = ProcessStatus::Stopped;
+        process.stopping_since = None;
+        process.record_run();
+        Self::remove_cleanup_paths(process, &self.log_handler);
+        
+        let message = match termination {
+            Some(termination) => format!("Process {}", termination),
+            None => "Adopted process exited (exit status unknown)".to_string(),
+        };
+        self.log_handler.log(name, LogType::System, &message);
+        self.events.emit(ProcessEvent::new(name, EventKind::Stopped).with_pid(pid).with_detail(message));
+        true
+    }
+    
+    // 停止指定進程中正在運行的那些，單個失敗只記錄日誌不中斷
+    pub async fn stop_processes(&mut self, names: &[String]) -> Result<()> {
+        for name in names {
+            let _ = self.stop_if_running(name).await;
         }
         
         Ok(())
     }
+    
+    // 進程沒有在運行時返回 None；結果同時寫入進程的日誌
+    pub async fn stop_if_running(&mut self, name: &str) -> Option<Result<()>> {
+        let running = self
+            .processes
+            .get(name)
+            .is_some_and(|process| matches!(process.status, ProcessStatus::Running | ProcessStatus::Stopping));
+        if !running {
+            return None;
+        }
+        
+        let result = self.stop_process(name).await;
+        match &result {
+            Ok(_) => self.log_handler.log(name, LogType::System, "Process stopped"),
+            Err(e) => self.log_handler.log(
+                name,
+                LogType::System,
+                &format!("Failed to stop process: {}", e),
+            ),
+        }
+        Some(result)
+    }
 
     pub async fn restart_process(&mut self, name: &str) -> Result<()> {
-        // 首先檢查進程是否存在
-        if !self.processes.contains_key(name) {
-            return Err(JanusError::Process(format!("Process not found: {}", name)));
+        self.stop_for_restart(name).await?;
+        
+        // 然後重新啟動
+        self.start_process(name).await
+    }
+    
+    // 依賴就緒之前舊進程保持運行，之後停止並重新啟動
+    pub async fn restart_when_ready(manager: &tokio::sync::Mutex<Self>, name: &str) -> Result<()> {
+        Self::wait_for_dependencies(manager, name).await?;
+        let mut manager = manager.lock().await;
+        manager.stop_for_restart(name).await?;
+        manager.start_process_now(name).await
+    }
+    
+    async fn stop_for_restart(&mut self, name: &str) -> Result<()> {
+        let process = self.processes.get(name).ok_or_else(|| {
+            JanusError::Process(format!("Process not found: {}", name))
+        })?;
+        
+        // 如果進程在運行，則先停止它
+        if process.status == ProcessStatus::Running {
+            self.stop_process(name).await?;
+            self.log_handler.log(name, LogType::System, "Process stopped for restart");
+        }
+        Ok(())
+    }
+
+    // 監控循環、重新加載和信號處理持有鎖時的啟動：需要等待依賴時在獨立任務中等待，
+    // 依賴就緒後由監控循環啟動進程，等待失敗記錄在進程日誌中
+    pub async fn start_process(&mut self, name: &str) -> Result<()> {
+        if self.dependency_waits.contains_key(name) {
+            return Ok(());
+        }
+        if self.processes.get(name).is_some_and(|process| process.status == ProcessStatus::Running) {
+            return self.start_process_now(name).await;
+        }
+        let Some(wait) = self.start_wait(name) else {
+            return self.start_process_now(name).await;
+        };
+        if let Some(process) = self.processes.get_mut(name) {
+            process.manual_stop = false;
+            process.resume_at = None;
+            process.waiting_for_window = false;
         }
         
-        // 獲取日誌處理器和進程名稱的克隆
-        let log_handler = self.log_handler.clone();
-        let process_name = name.to_string();
+        self.next_dependency_wait += 1;
+        let id = self.next_dependency_wait;
+        self.dependency_waits.insert(name.to_string(), id);
+        let sender = self.waited_starts.0.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let result = wait.wait().await;
+            let _ = sender.send((name, id, result));
+        });
+        Ok(())
+    }
+    
+    // CLI、gRPC 和控制接口的啟動：等待依賴時不持有鎖，等待超時的錯誤返回給調用者
+    pub async fn start_when_ready(manager: &tokio::sync::Mutex<Self>, name: &str) -> Result<()> {
+        Self::wait_for_dependencies(manager, name).await?;
+        manager.lock().await.start_process_now(name).await
+    }
+    
+    async fn wait_for_dependencies(manager: &tokio::sync::Mutex<Self>, name: &str) -> Result<()> {
+        let wait = manager.lock().await.start_wait(name);
+        match wait {
+            Some(wait) => wait.wait().await,
+            None => Ok(()),
+        }
+    }
+    
+    // 收集啟動前需要等待的依賴，沒有需要等待的依賴時返回 None；需要等待時啟動耗時從這裡開始計算
+    pub fn start_wait(&mut self, name: &str) -> Option<StartWait> {
+        let process = self.processes.get(name)?;
+        // 只有運行中的 notify 進程之後還可能就緒
+        let required: Vec<_> = process
+            .requires
+            .iter()
+            .filter_map(|required| Some((required, self.processes.get(required)?)))
+            .filter(|(_, required)| {
+                required.notify
+                    && required.status == ProcessStatus::Running
+                    && !required.notify_state.is_ready()
+            })
+            .map(|(name, required)| (name.clone(), required.notify_state.clone()))
+            .collect();
+        if process.wait_for.is_empty() && process.wait_for_path.is_empty() && required.is_empty() {
+            return None;
+        }
         
-        // 獲取並處理進程
-        let process_running;
-        {
-            let process = self.get_process_mut(&process_name).ok_or_else(|| {
-                JanusError::Process(format!("Process not found: {}", name))
-            })?;
-            
-            process_running = process.status == ProcessStatus::Running;
-            
-            // 如果進程在運行，則先停止它
-            if process_running {
-                if let Some(child) = &mut process.process {
-                    // 先停止進程
-                    match child.kill().await {
-                        Ok(_) => {
-                            log_handler.log(
-                                &process_name,
-                                LogType::System,
-                                "Process stopped for restart",
-                            );
-                            process.status = ProcessStatus::Stopped;
-                            process.process = None;
-                        }
-                        Err(e) => {
-                            return Err(JanusError::Process(format!("Failed to stop process: {}", e)));
-                        }
-                    }
+        let wait = StartWait {
+            name: name.to_string(),
+            paths: process
+                .wait_for_path
+                .iter()
+                .map(|path| dependency::resolve_path(path, process.working_dir.as_deref()))
+                .collect(),
+            endpoints: process.wait_for.clone(),
+            required,
+            timeout: self.wait_timeout(name),
+            log_handler: self.log_handler.clone(),
+        };
+        if let Some(process) = self.processes.get_mut(name) {
+            process.start_requested_at = Some(self.clock.now());
+        }
+        Some(wait)
+    }
+    
+    // 等待依賴結束的啟動；期間被用戶停止、被移除、已經運行或重新請求了啟動的進程不再處理
+    async fn start_waited(&mut self) {
+        while let Ok((name, id, result)) = self.waited_starts.1.try_recv() {
+            if self.dependency_waits.get(&name) != Some(&id) {
+                continue;
+            }
+            self.dependency_waits.remove(&name);
+            if self.processes.get(&name).is_none_or(|process| process.manual_stop || process.pid().is_some()) {
+                continue;
+            }
+            match result {
+                // 啟動失敗已在 launch_process 中記錄
+                Ok(()) => {
+                    let _ = self.start_process_now(&name).await;
+                }
+                Err(e) => {
+                    self.log_handler.log(&name, LogType::System, &format!("Failed to start process: {}", e));
                 }
             }
         }
+    }
+
+    // 依賴已經就緒或調用者已經等待過依賴時直接啟動
+    pub async fn start_process_now(&mut self, name: &str) -> Result<()> {
+        self.dependency_waits.remove(name);
+        if let Some(process) = self.processes.get_mut(name) {
+            process.manual_stop = false;
+            process.resume_at = None;
+            process.waiting_for_main = false;
+            process.waiting_for_window = false;
+            process.recycling = false;
+            process.recycle_kill_at = None;
+        }
         
-        // 然後重新啟動
-        self.start_process(&process_name).await
+        // 邊車等到主進程運行後才啟動
+        let stopped_main = self
+            .processes
+            .get(name)
+            .and_then(|process| process.sidecar_of.clone())
+            .filter(|main| self.processes.get(main).is_none_or(|main| main.pid().is_none()));
+        if let Some(main) = stopped_main {
+            if let Some(process) = self.processes.get_mut(name) {
+                process.waiting_for_main = true;
+            }
+            self.log_handler.log(name, LogType::System, &format!("Waiting for main process {} to start", main));
+            self.publish_status();
+            return Ok(());
+        }
+        
+        let result = self.launch_process(name).await;
+        if result.is_ok() {
+            self.start_sidecars(name).await;
+        }
+        self.publish_status();
+        result
+    }
+    
+    // 固定端口直接使用；port = "auto" 時從端口池分配，避開其他進程的固定端口
+    fn assign_port(&mut self, name: &str) -> std::result::Result<Option<u16>, String> {
+        match self.processes.get(name).and_then(|process| process.port_setting) {
+            None => Ok(None),
+            Some(PortConfig::Fixed(port)) => Ok(Some(port)),
+            Some(PortConfig::Auto(_)) => {
+                let reserved = self
+                    .processes
+                    .values()
+                    .filter_map(|process| match process.port_setting {
+                        Some(PortConfig::Fixed(port)) => Some(port),
+                        _ => None,
+                    })
+                    .collect();
+                self.ports
+                    .allocate(name, &reserved)
+                    .map(Some)
+                    .ok_or_else(|| "No free port left in port_range".to_string())
+            }
+        }
     }
 
-    pub async fn start_process(&mut self, name: &str) -> Result<()> {
+    async fn launch_process(&mut self, name: &str) -> Result<()> {
         // 檢查進程是否存在
         if !self.processes.contains_key(name) {
             return Err(JanusError::Process(format!("Process not found: {}", name)));
@@ -170,11 +2474,16 @@ impl ProcessManager {
         
         // 獲取日誌處理器的克隆
         let log_handler = self.log_handler.clone();
+        let secrets = self.secrets.clone();
+        let clock = self.clock.clone();
         let process_name = name.to_string();
         
         // 獲取並處理進程
         let process = self.get_process_mut(name).unwrap();
         
+        // 啟動耗時從啟動請求開始計算（包括等待 wait_for 依賴），到進程就緒為止
+        let start_requested_at = process.start_requested_at.take().unwrap_or_else(|| clock.now());
+        
         // 如果進程已在運行，則直接返回
         if process.status == ProcessStatus::Running {
             log_handler.log(
@@ -185,119 +2494,442 @@ impl ProcessManager {
             return Ok(());
         }
         
+        // 接管仍在運行的外部實例，而不是再啟動一個
+        if let Some(pid) = process.adopt_pid_file.as_deref().and_then(adopt::running_pid) {
+            process.adopted_pid = Some(pid);
+            process.pidfd = PidFd::open(pid);
+            // 接管的進程已經在其他 cgroup 中運行，只能按進程樹計量
+            process.memory_group = process.memory_limit.map(|_| MemoryGroup::Tree);
+            process.fd_monitor = FdMonitor::default();
+            process.heartbeat.beat();
+            // 接管的進程無法再報告 READY=1，視為已經就緒
+            process.notify_state.set_ready(clock.now());
+            process.status = ProcessStatus::Running;
+            process.start_time = Some(clock.now());
+            process.exit_code = None;
+            process.termination = None;
+            process.restart_at = None;
+            log_handler.log(name, LogType::System, &format!("Adopted running process (pid {})", pid));
+            self.events.emit(
+                ProcessEvent::new(name, EventKind::Started)
+                    .with_pid(Some(pid))
+                    .with_detail("adopted"),
+            );
+            return Ok(());
+        }
+        
+        // 分配端口，之後重新取得進程
+        let port = self.assign_port(name);
+        let spawner = self.spawner.clone();
+        let container_socket = self
+            .config_manager
+            .as_ref()
+            .and_then(|config_manager| config_manager.get_global_config().container_socket.clone());
+        let container_instance = self
+            .config_manager
+            .as_ref()
+            .map(|config_manager| container::instance_id(config_manager.get_config_path()))
+            .unwrap_or_default();
+        let clock = self.clock.clone();
+        let process = self.get_process_mut(name).unwrap();
+        let port = match port {
+            Ok(port) => port,
+            Err(error_msg) => {
+                log_handler.log(name, LogType::System, &error_msg);
+                process.status = ProcessStatus::Failed;
+                self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                return Err(JanusError::Process(error_msg));
+            }
+        };
+        process.port = port;
+        
         // 複製所需信息以避免借用問題
         let command_str = process.command.clone();
         let args = process.args.clone();
-        let env = process.env.clone();
         let working_dir = process.working_dir.clone();
         
-        // 創建命令（避免借用衝突）
-        let mut command = Command::new(&command_str);
-        command.args(&args)
-               .stdin(Stdio::null())
+        // env 中的密鑰引用每次啟動時重新讀取，輪換後的密鑰在重啟後生效
+        let env = match secrets.resolve(name, &process.env, &log_handler).await {
+            Ok(env) => env,
+            Err(e) => {
+                let error_msg = format!("Failed to resolve secrets: {}", e);
+                log_handler.log(name, LogType::System, &error_msg);
+                process.status = ProcessStatus::Failed;
+                self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                return Err(JanusError::Process(error_msg));
+            }
+        };
+        
+        // 校驗可執行文件的內容，防止掛載卷中的文件被篡改；每次啟動都重新計算
+        if let Some(expected) = &process.expected_sha256 {
+            let actual = executable::resolve(&command_str, &env, working_dir.as_deref())
+                .ok_or_else(|| "executable not found".to_string())
+                .and_then(|path| executable::sha256(&path).map_err(|e| e.to_string()));
+            let problem = match actual {
+                Ok(actual) if actual == *expected => None,
+                Ok(actual) => Some(format!("Checksum mismatch: expected sha256 {}, found {}", expected, actual)),
+                Err(e) => Some(format!("Cannot verify checksum: {}", e)),
+            };
+            if let Some(problem) = problem {
+                if process.checksum_mismatch == ChecksumMismatch::Warn {
+                    log_handler.log(name, LogType::System, &format!("{}, starting anyway", problem));
+                } else {
+                    let error_msg = format!("{}, refusing to start", problem);
+                    log_handler.log(name, LogType::System, &error_msg);
+                    process.status = ProcessStatus::Failed;
+                    self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                    return Err(JanusError::Process(error_msg));
+                }
+            }
+        }
+        
+        // 上次運行崩潰時可能沒有刪除自己的套接字和 pid 文件
+        Self::remove_cleanup_paths(process, &log_handler);
+        
+        // 設置了 image 時通過 Docker/Podman API 運行容器，不啟動本地命令
+        let container = match &process.image {
+            Some(image) => {
+                let Some(api) = container::Api::locate(container_socket.as_deref()) else {
+                    let error_msg = format!(
+                        "No container API socket found for image {} (looked at ${}, {}), set global.container_socket",
+                        image,
+                        container::HOST_ENVS.join(", $"),
+                        container::DEFAULT_SOCKETS.join(", ")
+                    );
+                    log_handler.log(name, LogType::System, &error_msg);
+                    process.status = ProcessStatus::Failed;
+                    self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                    return Err(JanusError::Process(error_msg));
+                };
+                let container_name = ContainerName::new(&container_instance, name);
+                api.remove_stale(&container_name).await;
+                Some((api, container_name, image.clone()))
+            }
+            None => None,
+        };
+        
+        // 創建命令（避免借用衝突），設置了 exec_prefix 時由包裝程序啟動實際的命令；設置了 shell 時運行的是 shell
+        let program = match process.shell {
+            Some(kind) => shell::program(kind),
+            None => command_str.as_str(),
+        };
+        let mut command = match process.exec_prefix.split_first() {
+            Some((wrapper, wrapper_args)) => {
+                let mut command = Command::new(wrapper);
+                command.args(wrapper_args).arg(program);
+                command
+            }
+            None => Command::new(program),
+        };
+        match process.shell {
+            Some(kind) => shell::add_args(&mut command, kind, &shell::command_line(kind, &command_str, &args)),
+            None => {
+                command.args(&args);
+            }
+        }
+        command.stdin(Stdio::null())
                .stdout(Stdio::piped())
                .stderr(Stdio::piped());
         
+        // 在新的進程組中啟動，janus 停止進程時可以向它單獨發送 CTRL_BREAK
+        #[cfg(windows)]
+        command.creation_flags(windows_sys::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP);
+        
+        // 將子進程綁定到指定的 CPU
+        #[cfg(target_os = "linux")]
+        if let Some(cpus) = &process.cpu_affinity {
+            let cpu_set = scheduling::cpu_set(cpus);
+            // SAFETY: 閉包只調用 sched_setaffinity，不分配內存也不獲取鎖
+            unsafe {
+                command.pre_exec(move || scheduling::set_cpu_affinity(&cpu_set));
+            }
+        }
+        
+        // 設置調度策略，例如讓備份任務使用 SCHED_IDLE
+        #[cfg(target_os = "linux")]
+        if let Some(policy) = process.sched {
+            let priority = process.sched_priority;
+            // SAFETY: 閉包只調用 sched_setscheduler，不分配內存也不獲取鎖
+            unsafe {
+                command.pre_exec(move || scheduling::set_policy(policy, priority));
+            }
+        }
+        
+        // 保存 core 文件時取消子進程的 core 大小限制
+        #[cfg(unix)]
+        if process.core_dump_dir.is_some() {
+            // SAFETY: 閉包只調用 getrlimit/setrlimit，不分配內存也不獲取鎖
+            unsafe {
+                command.pre_exec(coredump::enable_core_dumps);
+            }
+        }
+        
+        // 限制整組內存時讓子進程在 exec 前進入自己的 cgroup，沒有可用的 cgroup 時按進程樹計量
+        let memory_group = process.memory_limit.map(|_| MemoryGroup::for_process(name));
+        #[cfg(target_os = "linux")]
+        if let Some(procs) = memory_group.as_ref().and_then(MemoryGroup::cgroup).and_then(super::memory::cgroup::procs_path) {
+            // SAFETY: 閉包只調用 open/write/close，不分配內存也不獲取鎖
+            unsafe {
+                command.pre_exec(move || super::memory::cgroup::join(&procs));
+            }
+        }
+        
+        #[cfg(not(target_os = "linux"))]
+        if process.cpu_affinity.is_some() || process.sched.is_some() {
+            log_handler.log(name, LogType::System, "cpu_affinity and sched are only supported on Linux, ignoring");
+        }
+        #[cfg(not(target_os = "linux"))]
+        if process.pipe_size.is_some() {
+            log_handler.log(name, LogType::System, "pipe_size is only supported on Linux, ignoring");
+        }
+        
+        // 子進程或容器的日誌寫入的文件，未設置時使用管道
+        let mut outputs = None;
+        
+        // 合併輸出：兩個流寫入同一個管道，保持進程寫入的順序
+        let merged_output = if process.merge_output {
+            match merged_output_pipe() {
+                Ok((receiver, stdout, stderr)) => {
+                    outputs = Some((stdout, stderr));
+                    Some(receiver)
+                }
+                Err(e) => {
+                    let error_msg = format!("Failed to create output pipe: {}", e);
+                    log_handler.log(name, LogType::System, &error_msg);
+                    process.status = ProcessStatus::Failed;
+                    self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                    return Err(JanusError::Process(error_msg));
+                }
+            }
+        } else {
+            None
+        };
+        
+        // 按照 env_allow / env_deny 限制繼承的環境變量
+        if process.env_allow.is_some() || process.env_deny.is_some() {
+            command.env_clear();
+            command.envs(environment::filter_inherited(
+                std::env::vars(),
+                process.env_allow.as_deref(),
+                process.env_deny.as_deref(),
+            ));
+        }
+        
+        // 掛載目錄中的變量在 env 之前設置，同名時以 env 為準；文件變化後重新讀取，輪換後的密鑰在重啟後生效
+        for dir in &process.env_dirs {
+            match process.env_cache.read_env_dir(dir) {
+                Ok(vars) => {
+                    command.envs(vars);
+                }
+                Err(e) => {
+                    let error_msg = format!("Failed to read env_dirs entry {}: {}", dir.display(), e);
+                    log_handler.log(name, LogType::System, &error_msg);
+                    process.status = ProcessStatus::Failed;
+                    self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                    return Err(JanusError::Process(error_msg));
+                }
+            }
+        }
+        
         // 設置環境變量
         for (key, value) in &env {
             command.env(key, value);
         }
         
+        if let Some(port) = port {
+            command.env(&process.port_env, port.to_string());
+        }
+        
+        // 渲染模板，變量與進程看到的環境變量相同
+        if !process.render.is_empty() {
+            let mut vars: HashMap<String, String> = if process.env_allow.is_some() || process.env_deny.is_some() {
+                HashMap::new()
+            } else {
+                std::env::vars().collect()
+            };
+            for (key, value) in command.as_std().get_envs() {
+                let key = key.to_string_lossy().into_owned();
+                match value {
+                    Some(value) => vars.insert(key, value.to_string_lossy().into_owned()),
+                    None => vars.remove(&key),
+                };
+            }
+            if let Err(error_msg) = process.render.iter().try_for_each(|config| render::render_file(config, &vars)) {
+                log_handler.log(name, LogType::System, &error_msg);
+                process.status = ProcessStatus::Failed;
+                self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                return Err(JanusError::Process(error_msg));
+            }
+        }
+        
+        // 告訴進程心跳 socket 的路徑和超時，socket 只在首次啟動時創建
+        if process.watchdog_socket && process.watchdog_listener.is_none() {
+            match WatchdogSocket::bind(name, process.heartbeat.clone()) {
+                Ok(listener) => process.watchdog_listener = Some(listener),
+                Err(e) => log_handler.log(name, LogType::System, &format!("Failed to create watchdog socket: {}", e)),
+            }
+        }
+        if let Some(listener) = &process.watchdog_listener {
+            command.env(watchdog::SOCKET_ENV, listener.path());
+        }
+        if let Some(timeout) = process.watchdog_timeout {
+            command.env(watchdog::TIMEOUT_ENV, timeout.to_string());
+        }
+        
+        // 兼容 sd_notify 的通知 socket，進程需要在每次啟動後重新報告就緒
+        if process.notify {
+            process.notify_state.reset();
+            if process.notify_listener.is_none() {
+                match NotifySocket::bind(name, process.notify_state.clone(), process.heartbeat.clone(), clock.clone(), log_handler.clone()) {
+                    Ok(listener) => process.notify_listener = Some(listener),
+                    Err(e) => log_handler.log(name, LogType::System, &format!("Failed to create notify socket: {}", e)),
+                }
+            }
+        }
+        if let Some(listener) = &process.notify_listener {
+            command.env(notify::SOCKET_ENV, listener.path());
+            if let Some(timeout) = process.watchdog_timeout {
+                command.env(notify::WATCHDOG_USEC_ENV, (timeout * 1_000_000).to_string());
+            }
+        } else {
+            // 不能繼承 systemd 給 janus 的 NOTIFY_SOCKET，否則進程的 READY=1 會被當成 janus 的
+            command.env_remove(notify::SOCKET_ENV);
+        }
+        
         // 設置工作目錄
         if let Some(dir) = &working_dir {
             command.current_dir(dir);
         }
         
-        // 啟動進程
-        match command.spawn() {
+        // 本次運行的輸出寫入的各個目標，每次運行重新收集最近的輸出
+        let capture = match process.capture_to.as_deref().map(|template| OutputCapture::create(template, name, process.capture_only)) {
+            Some(Ok(capture)) => Some(capture),
+            Some(Err(e)) => {
+                let error_msg = format!("Failed to set up capture_to: {}", e);
+                log_handler.log(name, LogType::System, &error_msg);
+                process.status = ProcessStatus::Failed;
+                self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                return Err(JanusError::Process(error_msg));
+            }
+            None => None,
+        };
+        let sinks = OutputSinks::new(name, process, &log_handler, capture);
+        sinks.recent_output.clear();
+        
+        // 原始模式下子進程直接寫入 janus 的 stdout/stderr 或 capture_to 文件，不創建管道也不逐行處理
+        if process.log_mode == LogMode::Raw {
+            match sinks.capture.as_ref().map(OutputCapture::files) {
+                Some(Ok(files)) => {
+                    outputs = Some(files);
+                }
+                Some(Err(e)) => {
+                    let error_msg = format!("Failed to set up capture_to: {}", e);
+                    log_handler.log(name, LogType::System, &error_msg);
+                    process.status = ProcessStatus::Failed;
+                    self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                    return Err(JanusError::Process(error_msg));
+                }
+                None if container.is_some() => match container::inherited_outputs() {
+                    Ok(files) => outputs = Some(files),
+                    Err(e) => {
+                        let error_msg = format!("Failed to attach container output: {}", e);
+                        log_handler.log(name, LogType::System, &error_msg);
+                        process.status = ProcessStatus::Failed;
+                        self.events.emit(ProcessEvent::new(name, EventKind::StartFailed).with_detail(error_msg.clone()));
+                        return Err(JanusError::Process(error_msg));
+                    }
+                },
+                None => {
+                    command.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+                }
+            }
+        }
+        
+        // 啟動進程；容器的 env 和端口只傳入容器，省略 command 時運行鏡像自身的命令
+        let is_container = container.is_some();
+        let spawned = match container {
+            Some((api, container_name, image)) => {
+                let mut env: Vec<(String, String)> = env.clone().into_iter().collect();
+                if let Some(port) = port {
+                    env.push((process.port_env.clone(), port.to_string()));
+                }
+                env.sort();
+                let spec = ContainerSpec {
+                    name: container_name,
+                    image,
+                    cmd: std::iter::once(command_str.clone()).filter(|command| !command.is_empty()).chain(args.clone()).collect(),
+                    env,
+                    ports: process.container_ports.clone(),
+                    volumes: process.container_volumes.clone(),
+                    host_config: process.container_host_config.clone(),
+                };
+                api.run(&spec, outputs).await.map(|handle| Box::new(handle) as Box<dyn ProcessHandle>)
+            }
+            None => {
+                if let Some((stdout, stderr)) = outputs {
+                    command.stdout(stdout).stderr(stderr);
+                }
+                spawner.spawn(name, &mut command)
+            }
+        };
+        match spawned {
             Ok(mut child) => {
+                let mut pipe_monitors = Vec::new();
+                
+                // 合併輸出時 stdout 和 stderr 共用一個管道，只需讀取一次
+                if let Some(merged) = merged_output {
+                    pipe_monitors.extend(watch_pipe(&merged, LogType::Stdout, process.pipe_size, &sinks));
+                    spawn_output_reader(merged, LogType::Stdout, sinks.clone());
+                }
+                
                 // 處理標準輸出
-                if let Some(stdout) = child.stdout.take() {
-                    let log_handler_clone = log_handler.clone();
-                    let process_name_clone = process_name.clone();
-                    
-                    tokio::spawn(async move {
-                        let mut reader = BufReader::new(stdout);
-                        let mut line = String::new();
-                        
-                        loop {
-                            line.clear();
-                            match reader.read_line(&mut line).await {
-                                Ok(0) => break, // EOF
-                                Ok(_) => {
-                                    if !line.is_empty() {
-                                        log_handler_clone.log(&process_name_clone, LogType::Stdout, line.trim());
-                                    }
-                                }
-                                Err(e) => {
-                                    log_handler_clone.log(
-                                        &process_name_clone,
-                                        LogType::System,
-                                        &format!("Error reading stdout: {}", e),
-                                    );
-                                    break;
-                                }
-                            }
-                        }
-                    });
+                if let Some(stdout) = child.take_stdout() {
+                    pipe_monitors.extend(watch_pipe(&stdout, LogType::Stdout, process.pipe_size, &sinks));
+                    spawn_output_reader(stdout, LogType::Stdout, sinks.clone());
                 }
                 
                 // 處理標準錯誤
-                if let Some(stderr) = child.stderr.take() {
-                    let log_handler_clone = log_handler.clone();
-                    let process_name_clone = process_name.clone();
-                    
-                    tokio::spawn(async move {
-                        let mut reader = BufReader::new(stderr);
-                        let mut line = String::new();
-                        
-                        loop {
-                            line.clear();
-                            match reader.read_line(&mut line).await {
-                                Ok(0) => break, // EOF
-                                Ok(_) => {
-                                    if !line.is_empty() {
-                                        log_handler_clone.log(&process_name_clone, LogType::Stderr, line.trim());
-                                    }
-                                }
-                                Err(e) => {
-                                    log_handler_clone.log(
-                                        &process_name_clone,
-                                        LogType::System,
-                                        &format!("Error reading stderr: {}", e),
-                                    );
-                                    break;
-                                }
-                            }
-                        }
-                    });
+                if let Some(stderr) = child.take_stderr() {
+                    pipe_monitors.extend(watch_pipe(&stderr, LogType::Stderr, process.pipe_size, &sinks));
+                    spawn_output_reader(stderr, LogType::Stderr, sinks.clone());
                 }
+                process.pipe_monitors = pipe_monitors;
                 
                 // 保存進程狀態
+                // 在回收之前打開 pidfd，此時 pid 不可能已被重用；容器的信號經由 API 發送
+                let pid = child.id();
+                process.pidfd = pid.filter(|_| !is_container).and_then(PidFd::open);
                 process.process = Some(child);
+                process.memory_group = memory_group;
+                process.oom_kills = process.oom_cgroup().and_then(|cgroup| cgroup::oom_kills(&cgroup));
+                process.fd_monitor = FdMonitor::default();
+                process.heartbeat.beat();
                 process.status = ProcessStatus::Running;
-                process.start_time = Some(Instant::now());
+                process.start_time = Some(clock.now());
+                process.exit_code = None;
+                process.termination = None;
+                process.restart_at = None;
+                process.current_output = sinks.capture.map(|capture| capture.path);
                 
-                // 創建進程監控
-                let process_name_clone = process_name.clone();
-                let log_handler_clone = log_handler.clone();
+                // 啟動耗時在進程就緒時記錄，見 check_started
+                process.awaiting_ready = Some((start_requested_at, clock.now()));
+                Self::note_started(&process_name, process, clock.now(), &log_handler);
+                process.pending_binary_fingerprint = None;
                 
-                // 監控進程退出
-                tokio::spawn(async move {
-                    // 因為架構限制，這裡只能記錄監控開始
-                    // 實際的進程監控和重啟邏輯需要更複雜的設計
-                    log_handler_clone.log(
-                        &process_name_clone,
-                        LogType::System,
-                        "Process monitoring started",
-                    );
-                });
+                // 記錄可執行文件指紋，供更新偵測使用
+                if process.restart_on_binary_change {
+                    process.binary_fingerprint = executable::resolve(&command_str, &env, working_dir.as_deref())
+                        .and_then(|path| executable::fingerprint(&path));
+                }
+                
+                // 進程退出由 check_processes 在監控循環中回收
                 
                 log_handler.log(
                     &process_name,
                     LogType::System,
                     "Process started",
                 );
+                self.events.emit(ProcessEvent::new(&process_name, EventKind::Started).with_pid(pid));
                 
                 Ok(())
             }
@@ -307,9 +2939,185 @@ impl ProcessManager {
                 
                 let process = self.get_process_mut(&process_name).unwrap();
                 process.status = ProcessStatus::Failed;
+                self.events.emit(ProcessEvent::new(&process_name, EventKind::StartFailed).with_detail(error_msg.clone()));
                 
                 Err(JanusError::Process(error_msg))
             }
         }
     }
 }
+
+// 讀取任務寫入的目標，每次運行創建一次
+#[derive(Clone)]
+struct OutputSinks {
+    process_name: String,
+    log_handler: LogHandler,
+    capture: Option<OutputCapture>,
+    recent_output: RecentOutput,
+    heartbeat: Option<LineHeartbeat>,
+    // 這個進程的讀取任務，回收時據此等待最後的輸出
+    readers: ReaderBarrier,
+    // 輸出按此編碼解碼，無法解碼的字節替換為 U+FFFD
+    encoding: &'static Encoding,
+    read_buffer_size: usize,
+    sampling: Option<LoggingConfig>,
+}
+
+impl OutputSinks {
+    fn new(name: &str, process: &ManagedProcess, log_handler: &LogHandler, capture: Option<OutputCapture>) -> Self {
+        Self {
+            process_name: name.to_string(),
+            log_handler: log_handler.clone(),
+            capture,
+            recent_output: process.recent_output.clone(),
+            // 匹配 watchdog_pattern 的輸出行記為心跳
+            heartbeat: process.watchdog_pattern.clone().map(|pattern| LineHeartbeat {
+                pattern,
+                heartbeat: process.heartbeat.clone(),
+            }),
+            readers: process.output_readers.clone(),
+            encoding: process.output_encoding,
+            read_buffer_size: process.read_buffer_size,
+            sampling: process.logging.clone(),
+        }
+    }
+}
+
+// 升級時交給新 janus 的輸出管道讀取端
+#[cfg(target_os = "linux")]
+fn handed_pipes(process: &ManagedProcess) -> Vec<(i32, HandedStream)> {
+    process
+        .pipe_monitors
+        .iter()
+        .map(|monitor| (monitor.raw_fd(), HandedStream::from_log_type(&monitor.stream)))
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn handed_pipes(_process: &ManagedProcess) -> Vec<(i32, HandedStream)> {
+    Vec::new()
+}
+
+// 繼續讀取上一個 janus 交接的輸出管道，返回新的寫滿偵測
+#[cfg(target_os = "linux")]
+fn resume_pipes(pipes: Vec<(i32, HandedStream)>, sinks: &OutputSinks) -> Vec<PipeMonitor> {
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    let mut monitors = Vec::new();
+    for (fd, stream) in pipes {
+        // SAFETY: fd 由上一個 janus 交接，只在這裡取得所有權
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        match tokio::net::unix::pipe::Receiver::from_owned_fd(fd) {
+            Ok(receiver) => {
+                monitors.extend(watch_pipe(&receiver, stream.log_type(), None, sinks));
+                spawn_output_reader(receiver, stream.log_type(), sinks.clone());
+            }
+            Err(e) => sinks.log_handler.log(
+                &sinks.process_name,
+                LogType::System,
+                &format!("Failed to resume reading {}: {}", stream.log_type().as_str(), e),
+            ),
+        }
+    }
+    monitors
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resume_pipes(_pipes: Vec<(i32, HandedStream)>, _sinks: &OutputSinks) -> Vec<PipeMonitor> {
+    Vec::new()
+}
+
+// 按 pipe_size 調整管道容量，並創建寫滿偵測
+#[cfg(target_os = "linux")]
+fn watch_pipe(pipe: &impl std::os::fd::AsFd, stream: LogType, pipe_size: Option<usize>, sinks: &OutputSinks) -> Option<PipeMonitor> {
+    if let Some(size) = pipe_size {
+        if let Err(e) = super::pipe::set_size(pipe.as_fd(), size) {
+            sinks.log_handler.log(
+                &sinks.process_name,
+                LogType::System,
+                &format!("Failed to set {} pipe size to {} bytes: {}", stream.as_str(), size, e),
+            );
+        }
+    }
+    PipeMonitor::new(pipe.as_fd(), stream)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn watch_pipe<P>(_pipe: &P, _stream: LogType, _pipe_size: Option<usize>, _sinks: &OutputSinks) -> Option<PipeMonitor> {
+    None
+}
+
+// 逐行讀取進程輸出並寫入日誌，讀到 EOF 之前持有全局和進程自己的讀取屏障
+fn spawn_output_reader<R>(output: R, log_type: LogType, sinks: OutputSinks)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let OutputSinks {
+        process_name,
+        log_handler,
+        capture,
+        recent_output,
+        heartbeat,
+        readers,
+        encoding,
+        read_buffer_size,
+        sampling,
+    } = sinks;
+    let mut sampler = sampling.as_ref().and_then(|config| LogSampler::new(config, &log_type));
+    let guards = (log_handler.reader_guard(), readers.enter());
+    
+    tokio::spawn(async move {
+        let _guards = guards;
+        let mut reader = BufReader::with_capacity(read_buffer_size, output);
+        let mut bytes = Vec::new();
+        
+        loop {
+            bytes.clear();
+            match reader.read_until(b'\n', &mut bytes).await {
+                Ok(0) => break, // EOF
+                Ok(_) => {
+                    let (line, _) = encoding.decode_without_bom_handling(&bytes);
+                    recent_output.push(line.trim_end());
+                    if let Some(heartbeat) = &heartbeat {
+                        heartbeat.check(line.trim_end());
+                    }
+                    if let Some(capture) = &capture {
+                        capture.write(&line);
+                        if capture.exclusive {
+                            continue;
+                        }
+                    }
+                    if sampler.as_mut().is_some_and(|sampler| !sampler.keep(line.trim())) {
+                        continue;
+                    }
+                    if !line.is_empty() {
+                        log_handler.log(&process_name, log_type.clone(), line.trim());
+                    }
+                }
+                Err(e) => {
+                    log_handler.log(
+                        &process_name,
+                        LogType::System,
+                        &format!("Error reading {}: {}", log_type.as_str(), e),
+                    );
+                    break;
+                }
+            }
+        }
+    });
+}
+
+// 創建 stdout 和 stderr 共用的管道，返回讀取端和子進程的兩個寫入端
+#[cfg(unix)]
+fn merged_output_pipe() -> Result<(tokio::net::unix::pipe::Receiver, std::fs::File, std::fs::File)> {
+    let (reader, writer) = std::io::pipe()?;
+    let receiver = tokio::net::unix::pipe::Receiver::from_owned_fd(reader.into())?;
+    let stderr = writer.try_clone()?;
+    
+    Ok((receiver, std::os::fd::OwnedFd::from(writer).into(), std::os::fd::OwnedFd::from(stderr).into()))
+}
+
+#[cfg(not(unix))]
+fn merged_output_pipe() -> Result<(tokio::process::ChildStdout, std::fs::File, std::fs::File)> {
+    Err(JanusError::Process("merge_output is only supported on Unix".to_string()))
+}
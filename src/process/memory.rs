@@ -0,0 +1,254 @@
+// 限制受管進程及其全部子孫進程的總內存：有可寫的 cgroup v2 時每個進程放入自己的 cgroup，
+// 通過 memory.current 計量並用 cgroup.kill 一次殺死整組；否則掃描 /proc 對進程樹的 RSS 求和
+use std::path::{Path, PathBuf};
+
+use super::tree::ProcessTable;
+
+// 解析 "512M"、"1G"、"64K" 或字節數，單位按 1024 進制
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier).filter(|size| *size > 0)
+}
+
+// 進程組的內存計量方式
+#[derive(Debug, Clone)]
+pub enum MemoryGroup {
+    Cgroup(PathBuf),
+    Tree,
+}
+
+impl MemoryGroup {
+    // 為進程準備 cgroup；沒有 cgroup v2 或沒有權限時退回進程樹
+    pub fn for_process(name: &str) -> Self {
+        match cgroup::create(name) {
+            Some(path) => Self::Cgroup(path),
+            None => Self::Tree,
+        }
+    }
+
+    pub fn cgroup(&self) -> Option<&Path> {
+        match self {
+            Self::Cgroup(path) => Some(path),
+            Self::Tree => None,
+        }
+    }
+
+    // 整組當前的內存用量；進程樹模式下 table 只在需要時掃描一次，供同一輪檢查的其他進程複用
+    pub fn usage(&self, pid: u32, table: &mut Option<ProcessTable>) -> Option<u64> {
+        match self {
+            Self::Cgroup(path) => std::fs::read_to_string(path.join("memory.current"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok(),
+            Self::Tree => {
+                if table.is_none() {
+                    *table = ProcessTable::scan();
+                }
+                table.as_ref()?.tree(pid).map(|root| root.total_rss())
+            }
+        }
+    }
+
+    // 殺死整組進程，而不只是直接子進程
+    pub fn kill(&self, pid: u32) {
+        match self {
+            Self::Cgroup(path) => cgroup::kill(path),
+            Self::Tree => {
+                let Some(root) = ProcessTable::scan().and_then(|table| table.tree(pid)) else {
+                    return;
+                };
+                // 先殺父進程再殺子進程，避免父進程繼續 fork
+                let mut pending = vec![root];
+                while let Some(node) = pending.pop() {
+                    kill_pid(node.pid);
+                    pending.extend(node.children);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn kill_pid(pid: u32) {
+    // SAFETY: kill 只接受整數參數；進程已經退出時返回 ESRCH，可以忽略
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+}
+
+#[cfg(not(unix))]
+fn kill_pid(_pid: u32) {}
+
+#[cfg(target_os = "linux")]
+pub mod cgroup {
+    use std::ffi::CString;
+    use std::fs;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+
+    const ROOT: &str = "/sys/fs/cgroup";
+
+    // cgroup v2 不允許有子 cgroup 的非根 cgroup 中直接包含進程，janus 自身移到這個葉子 cgroup
+    const SUPERVISOR: &str = "supervisor";
+
+    static BASE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    // janus 所在的 cgroup 目錄，並已為其子 cgroup 啟用 memory 控制器；首次調用時完成設置
+    fn base() -> Option<&'static Path> {
+        BASE.get_or_init(|| {
+            // 只支持統一的 cgroup v2 層級
+            let root = Path::new(ROOT);
+            if !root.join("cgroup.controllers").exists() {
+                return None;
+            }
+            let own = fs::read_to_string("/proc/self/cgroup").ok()?;
+            let relative = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+            let base = root.join(relative.trim_start_matches('/'));
+
+            // upgrade --exec 之後 janus 已經在之前移入的 supervisor 葉子 cgroup 中，沿用原來的目錄
+            if let Some(parent) = base.parent().filter(|_| base.ends_with(SUPERVISOR)) {
+                let controllers = fs::read_to_string(parent.join("cgroup.subtree_control")).unwrap_or_default();
+                if controllers.split_whitespace().any(|controller| controller == "memory") {
+                    return Some(parent.to_path_buf());
+                }
+            }
+
+            // 根 cgroup 沒有 cgroup.type，可以直接包含進程；其他 cgroup 需要先把其中的進程移出
+            if base.join("cgroup.type").exists() {
+                let supervisor = base.join(SUPERVISOR);
+                create_dir(&supervisor).ok()?;
+                let procs = fs::read_to_string(base.join("cgroup.procs")).ok()?;
+                // 期間退出的進程會移動失敗，由下面啟用控制器的結果決定是否可用
+                for pid in procs.lines() {
+                    let _ = fs::write(supervisor.join("cgroup.procs"), pid);
+                }
+            }
+            fs::write(base.join("cgroup.subtree_control"), "+memory").ok()?;
+            Some(base)
+        })
+        .as_deref()
+    }
+
+    fn create_dir(path: &Path) -> io::Result<()> {
+        match fs::create_dir(path) {
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    // 每個受管進程一個 cgroup，項目前綴中的 '/' 替換為 ':'；重啟時沿用同一個 cgroup
+    pub fn create(name: &str) -> Option<PathBuf> {
+        let path = base()?.join(format!("process-{}", name.replace('/', ":")));
+        create_dir(&path).ok()?;
+        Some(path)
+    }
+
+    // 在 fork 之後、exec 之前把子進程移入 cgroup，此後它派生的所有進程都留在其中
+    pub fn procs_path(path: &Path) -> Option<CString> {
+        CString::new(path.join("cgroup.procs").as_os_str().as_bytes()).ok()
+    }
+
+    // 在 pre_exec 中調用：只使用系統調用，不分配內存也不獲取鎖
+    pub fn join(procs: &CString) -> io::Result<()> {
+        // SAFETY: procs 是有效的 C 字符串，寫入 "0" 表示移動調用者自身
+        unsafe {
+            let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+            let error = io::Error::last_os_error();
+            libc::close(fd);
+            if written != 1 {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    // janus 所在的 cgroup，即容器的 cgroup；janus 已移入 supervisor 葉子 cgroup 時取其父目錄
+    pub fn container() -> Option<PathBuf> {
+        let own = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let relative = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+        let path = Path::new(ROOT).join(relative.trim_start_matches('/'));
+        let path = match path.parent() {
+            // 不調用 base()，避免只為讀取計數而移動 janus 自身
+            Some(parent) if path.ends_with(SUPERVISOR) && BASE.get().and_then(Option::as_deref) == Some(parent) => {
+                parent.to_path_buf()
+            }
+            _ => path,
+        };
+        path.join("memory.events").exists().then_some(path)
+    }
+
+    // memory.events 中 OOM killer 在該 cgroup 及其子 cgroup 中殺死進程的累計次數
+    pub fn oom_kills(path: &Path) -> Option<u64> {
+        let events = fs::read_to_string(path.join("memory.events")).ok()?;
+        events
+            .lines()
+            .find_map(|line| line.strip_prefix("oom_kill "))
+            .and_then(|count| count.trim().parse().ok())
+    }
+
+    // memory.peak 需要 Linux 5.19
+    pub fn peak(path: &Path) -> Option<u64> {
+        fs::read_to_string(path.join("memory.peak")).ok()?.trim().parse().ok()
+    }
+
+    // 對該 cgroup 生效的內核上限：自身及各級父 cgroup 的 memory.max 中最小的一個，未設置時為 "max"
+    pub fn max(path: &Path) -> Option<u64> {
+        path.ancestors()
+            .take_while(|ancestor| ancestor.starts_with(ROOT))
+            .filter_map(|ancestor| fs::read_to_string(ancestor.join("memory.max")).ok()?.trim().parse().ok())
+            .min()
+    }
+
+    // cgroup.kill 需要 Linux 5.14；更早的內核上逐個殺死 cgroup.procs 中的進程
+    pub fn kill(path: &Path) {
+        if fs::write(path.join("cgroup.kill"), "1").is_ok() {
+            return;
+        }
+        if let Ok(procs) = fs::read_to_string(path.join("cgroup.procs")) {
+            for pid in procs.lines().filter_map(|pid| pid.parse().ok()) {
+                super::kill_pid(pid);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub mod cgroup {
+    use std::path::{Path, PathBuf};
+
+    pub fn create(_name: &str) -> Option<PathBuf> {
+        None
+    }
+
+    pub fn container() -> Option<PathBuf> {
+        None
+    }
+
+    pub fn oom_kills(_path: &Path) -> Option<u64> {
+        None
+    }
+
+    pub fn peak(_path: &Path) -> Option<u64> {
+        None
+    }
+
+    pub fn max(_path: &Path) -> Option<u64> {
+        None
+    }
+
+    pub fn kill(_path: &Path) {}
+}
@@ -1,29 +1,547 @@
+pub mod accounting;
+pub mod adopt;
+pub mod capture;
+pub mod clock;
+pub mod container;
+pub mod coredump;
+pub mod dependency;
+pub mod environment;
+pub mod events;
+pub mod executable;
+pub mod fds;
+pub mod handover;
+pub mod http;
+pub mod kubernetes;
 pub mod manager;
-use std::collections::HashMap;
-use std::time::Instant;
-use tokio::process::Child;
+pub mod memory;
+pub mod notify;
+pub mod pidfd;
+pub mod pipe;
+pub mod plugin;
+pub mod ports;
+pub mod queue;
+pub mod recent_output;
+pub mod registry;
+pub mod render;
+pub mod restart_script;
+pub mod schedule;
+pub mod scheduling;
+pub mod secrets;
+pub mod shell;
+pub mod snapshot;
+pub mod spawner;
+pub mod start_window;
+pub mod timeline;
+pub mod tree;
+pub mod watchdog;
+use chrono::{DateTime, Local, Utc};
+use encoding_rs::{Encoding, UTF_8};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config::{ChecksumMismatch, LogMode, LoggingConfig, MissedRunPolicy, OverlapPolicy, PortConfig, ProcessConfig, RenderConfig, SchedPolicy, ShellKind};
+use crate::logging::barrier::ReaderBarrier;
+use crate::logging::search::parse_duration;
+use crate::signal::names::signal_name;
+use accounting::ResourceUsage;
+use executable::BinaryFingerprint;
+use notify::{NotifySocket, NotifyState};
+use pipe::PipeMonitor;
+use recent_output::RecentOutput;
+use restart_script::RestartScript;
+use schedule::CronSchedule;
+use spawner::ProcessHandle;
+use start_window::StartWindow;
+use watchdog::{Heartbeat, WatchdogSocket};
+
+// 保留的最近運行記錄數量
+pub const RUN_HISTORY_LIMIT: usize = 10;
+
+// 默認保留的崩潰上下文行數
+pub const DEFAULT_CRASH_CONTEXT_LINES: usize = 20;
+
+// 讀取進程輸出的默認緩衝區大小，與 BufReader 的默認值相同
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
+
+// 一次已結束運行的記錄，包括退出碼和 capture_to 輸出文件
+#[derive(Clone, Debug)]
+pub struct RunRecord {
+    pub finished_at: DateTime<Local>,
+    pub duration: Duration,
+    pub exit_code: Option<i32>,
+    pub termination: Option<Termination>,
+    pub output_path: Option<PathBuf>,
+    pub core_path: Option<PathBuf>,
+    // 異常退出前的最後幾行輸出
+    pub crash_context: Vec<String>,
+    // 本次運行累計的 CPU 時間和 I/O，無法讀取時為 None
+    pub usage: Option<ResourceUsage>,
+    // 停止時超過 stop_timeout 仍未退出，最後被 SIGKILL 殺死
+    pub required_kill: bool,
+}
+
+// 進程結束的方式：正常退出及其退出碼，或終止它的信號
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Termination {
+    Exited(i32),
+    Signaled { signal: i32, core_dumped: bool },
+}
+
+impl Termination {
+    pub fn from_status(status: &std::process::ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            if let Some(signal) = status.signal() {
+                return Termination::Signaled {
+                    signal,
+                    core_dumped: status.core_dumped(),
+                };
+            }
+        }
+        Termination::Exited(status.code().unwrap_or(-1))
+    }
+
+    // 與 shell 一致的退出碼：被信號終止時為 128 + 信號編號，例如 SIGTERM 為 143
+    pub fn shell_code(&self) -> i32 {
+        match self {
+            Termination::Exited(code) => *code,
+            Termination::Signaled { signal, .. } => 128 + signal,
+        }
+    }
+
+    // 簡短描述，例如 "3" 或 "SIGSEGV (core dumped)"
+    pub fn short_label(&self) -> String {
+        match self {
+            Termination::Exited(code) => code.to_string(),
+            Termination::Signaled { signal, core_dumped: true } => format!("{} (core dumped)", signal_name(*signal)),
+            Termination::Signaled { signal, core_dumped: false } => signal_name(*signal),
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Exited(code) => write!(f, "exited with code: {}", code),
+            Termination::Signaled { .. } => write!(f, "killed by {}", self.short_label()),
+        }
+    }
+}
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum ProcessStatus {
     Stopped,
     Running,
+    // 已發送 SIGTERM，等待進程在 stop_timeout 內退出
+    Stopping,
     Failed,
+    Completed,
+    GaveUp,
 }
 
-// ManagedProcess 不能自動派生 Clone，因為 tokio::process::Child 不實現 Clone
+// ManagedProcess 不能自動派生 Clone，因為進程句柄不實現 Clone
 pub struct ManagedProcess {
     pub name: String,
+    pub display_name: Option<String>,
+    pub label: Option<String>,
+    pub group: Option<String>,
     pub command: String,
     pub args: Vec<String>,
+    pub exec_prefix: Vec<String>,
+    pub shell: Option<ShellKind>,
+    // 設置時以該鏡像的容器運行
+    pub image: Option<String>,
+    pub container_ports: Vec<String>,
+    pub container_volumes: Vec<String>,
+    pub container_host_config: HashMap<String, serde_json::Value>,
     pub working_dir: Option<String>,
     pub env: HashMap<String, String>,
+    pub env_dirs: Vec<PathBuf>,
+    // 上次啟動時讀取的 env_dirs
+    pub env_cache: environment::EnvCache,
+    pub env_allow: Option<Vec<String>>,
+    pub env_deny: Option<Vec<String>>,
     pub auto_restart: bool,
     pub restart_count: u32,
     pub restart_limit: Option<u32>,
     pub restart_delay: u64,
+    pub restart_budget: Option<u32>,
+    pub restart_budget_window: u64,
+    // 連續運行這麼久後清零 restart_count 和重啟預算
+    pub stable_after: Option<Duration>,
+    pub restart_script: Option<Arc<RestartScript>>,
+    pub restart_on_binary_change: bool,
+    pub expected_sha256: Option<String>,
+    pub checksum_mismatch: ChecksumMismatch,
+    pub oneshot_keep_status: bool,
+    pub ignore_exit_codes: Vec<i32>,
+    pub wait_for: Vec<String>,
+    pub wait_for_path: Vec<PathBuf>,
+    pub cleanup_paths: Vec<PathBuf>,
+    // 未配置時為 None，等待時由 ProcessManager::wait_timeout 決定
+    pub wait_timeout: Option<u64>,
+    pub requires: Vec<String>,
+    pub critical: bool,
+    pub slow_start_threshold: Option<u64>,
+    pub expected_start_duration: Option<Duration>,
+    pub slow_stop_threshold: Option<u64>,
+    // 設置時停止先發送 SIGTERM，超時後才殺死；stopping_since 為進入 Stopping 狀態的時間
+    pub stop_timeout: Option<Duration>,
+    pub stopping_since: Option<Instant>,
+    pub merge_output: bool,
+    pub output_encoding: &'static Encoding,
+    pub log_mode: LogMode,
+    pub cpu_affinity: Option<Vec<usize>>,
+    pub sched: Option<SchedPolicy>,
+    pub sched_priority: u8,
+    pub capture_to: Option<String>,
+    pub capture_only: bool,
+    pub core_dump_dir: Option<String>,
+    pub adopt_pid_file: Option<PathBuf>,
+    // 接管的外部進程的 pid；此時 process 為 None
+    pub adopted_pid: Option<u32>,
+    // 當前進程的 pidfd，內核不支持時為 None 並回退到基於 pid 的方式
+    pub pidfd: Option<pidfd::PidFd>,
+    // 進程及其子孫進程的總內存上限，單位為字節
+    pub memory_limit: Option<u64>,
+    // 當前運行的內存計量方式，啟動時根據是否有可用的 cgroup 決定
+    pub memory_group: Option<memory::MemoryGroup>,
+    // 啟動時 OOM killer 的累計次數，之後增加說明組內有進程因內存不足被殺死
+    pub oom_kills: Option<u64>,
+    // 打開的文件描述符達到此數量時警告，未配置時按 RLIMIT_NOFILE 的軟限制計算
+    pub max_open_fds: Option<u64>,
+    pub fd_monitor: fds::FdMonitor,
+    // 進程退出後、回收之前讀取的資源用量，記錄本次運行時取走
+    pub exit_usage: Option<ResourceUsage>,
+    // 超過這麼多秒沒有心跳時重啟進程
+    pub watchdog_timeout: Option<u64>,
+    pub watchdog_file: Option<PathBuf>,
+    pub watchdog_socket: bool,
+    pub watchdog_pattern: Option<regex::Regex>,
+    // 最近一次通過 socket 或日誌行收到心跳的時間，每次啟動時重置
+    pub heartbeat: Heartbeat,
+    // 首次啟動時創建，重啟時沿用同一個 socket 路徑
+    pub watchdog_listener: Option<WatchdogSocket>,
+    pub notify: bool,
+    // 進程通過 NOTIFY_SOCKET 報告的就緒狀態和狀態文本
+    pub notify_state: NotifyState,
+    pub notify_listener: Option<NotifySocket>,
+    // 這個進程仍在運行的輸出讀取任務
+    pub output_readers: ReaderBarrier,
+    pub pipe_size: Option<usize>,
+    pub read_buffer_size: usize,
+    // 當前運行的輸出管道，監控循環據此偵測管道寫滿
+    pub pipe_monitors: Vec<PipeMonitor>,
+    pub recent_output: RecentOutput,
+    pub every: Option<Duration>,
+    pub run_on_start: bool,
+    pub schedule: Option<CronSchedule>,
+    pub missed: MissedRunPolicy,
+    pub overlap: OverlapPolicy,
+    pub jitter: Option<Duration>,
+    pub scheduled_restart: Option<CronSchedule>,
+    // janus start 和自動重啟只在窗口內啟動進程；waiting_for_window 表示啟動被推遲到窗口打開
+    pub start_window: Option<StartWindow>,
+    pub waiting_for_window: bool,
+    pub next_restart_at: Option<DateTime<Utc>>,
+    // 計劃重啟中：已發送 SIGTERM，進程退出後立即重新啟動；recycle_kill_at 後仍未退出則殺死
+    pub recycling: bool,
+    pub recycle_kill_at: Option<Instant>,
+    // 寫入日誌的輸出按此抽樣
+    pub logging: Option<LoggingConfig>,
+    // 每次啟動前渲染的模板
+    pub render: Vec<RenderConfig>,
+    pub port_setting: Option<PortConfig>,
+    pub port_env: String,
+    // 最近一次啟動時傳給進程的端口
+    pub port: Option<u16>,
+    pub sidecar_of: Option<String>,
+    // 因主進程沒有運行而被 janus 停止或推遲啟動，主進程再次運行時啟動
+    pub waiting_for_main: bool,
     pub status: ProcessStatus,
-    pub process: Option<Child>,
+    pub process: Option<Box<dyn ProcessHandle>>,
     pub start_time: Option<Instant>,
+    pub exit_code: Option<i32>,
+    pub termination: Option<Termination>,
+    pub start_requested_at: Option<Instant>,
+    // 已創建但尚未就緒的進程：(啟動請求時刻, 進程創建時刻)，就緒時計算啟動耗時
+    pub awaiting_ready: Option<(Instant, Instant)>,
+    pub last_start_duration: Option<Duration>,
+    pub last_stop_duration: Option<Duration>,
+    pub restart_at: Option<Instant>,
+    // 待執行的重啟是否計入 restart_count
+    pub restart_counted: bool,
+    pub recent_restarts: VecDeque<Instant>,
+    pub binary_fingerprint: Option<BinaryFingerprint>,
+    pub pending_binary_fingerprint: Option<BinaryFingerprint>,
+    pub current_output: Option<PathBuf>,
+    pub run_history: VecDeque<RunRecord>,
+    pub next_run_at: Option<Instant>,
+    pub next_scheduled_at: Option<DateTime<Utc>>,
+    pub scheduled_run_at: Option<Instant>,
+    pub overlap_queued: bool,
+    // 維護模式的結束時間，期間不自動重啟，也不執行健康檢查觸發的動作
+    pub maintenance_until: Option<Instant>,
+    // 用戶手動停止後不再自動啟動，直到顯式啟動或到達 resume_at
+    pub manual_stop: bool,
+    pub resume_at: Option<Instant>,
+}
+
+impl ManagedProcess {
+    pub fn from_config(config: &ProcessConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            display_name: config.display_name.clone(),
+            label: config.label.clone(),
+            group: config.group.clone(),
+            command: config.command.clone(),
+            args: config.args.clone().unwrap_or_default(),
+            exec_prefix: config.exec_prefix.clone().unwrap_or_default(),
+            shell: config.shell,
+            image: config.image.clone(),
+            container_ports: config.container_ports.clone().unwrap_or_default(),
+            container_volumes: config.container_volumes.clone().unwrap_or_default(),
+            container_host_config: config.container_host_config.clone().unwrap_or_default(),
+            env: config.env.clone().unwrap_or_default(),
+            env_dirs: config.env_dirs.iter().flatten().map(PathBuf::from).collect(),
+            env_cache: environment::EnvCache::default(),
+            env_allow: config.env_allow.clone(),
+            env_deny: config.env_deny.clone(),
+            working_dir: config.working_dir.clone(),
+            auto_restart: config.auto_restart.unwrap_or(false),
+            restart_count: 0,
+            restart_limit: config.restart_limit,
+            restart_delay: config.restart_delay.unwrap_or(1),
+            restart_budget: config.restart_budget,
+            restart_budget_window: config.restart_budget_window.unwrap_or(60),
+            stable_after: config.stable_after.as_deref().and_then(|stable_after| parse_duration(stable_after).ok()),
+            // 腳本在配置校驗時已經編譯過一次
+            restart_script: config
+                .restart_script
+                .as_deref()
+                .and_then(|source| RestartScript::compile(source).ok())
+                .map(Arc::new),
+            restart_on_binary_change: config.restart_on_binary_change.unwrap_or(false),
+            expected_sha256: config.expected_sha256.as_deref().map(str::to_ascii_lowercase),
+            checksum_mismatch: config.checksum_mismatch.unwrap_or_default(),
+            oneshot_keep_status: config.oneshot_keep_status.unwrap_or(false),
+            ignore_exit_codes: config.ignore_exit_codes.clone().unwrap_or_default(),
+            wait_for: config.wait_for.clone().unwrap_or_default(),
+            wait_for_path: config.wait_for_path.iter().flatten().map(PathBuf::from).collect(),
+            cleanup_paths: config.cleanup_paths.iter().flatten().map(PathBuf::from).collect(),
+            wait_timeout: config.wait_timeout,
+            requires: config.requires.clone().unwrap_or_default(),
+            critical: config.critical.unwrap_or(config.every.is_none() && config.schedule.is_none()),
+            slow_start_threshold: config.slow_start_threshold,
+            expected_start_duration: config
+                .expected_start_duration
+                .as_deref()
+                .and_then(|expected| parse_duration(expected).ok()),
+            slow_stop_threshold: config.slow_stop_threshold,
+            stop_timeout: config.stop_timeout.map(Duration::from_secs),
+            stopping_since: None,
+            merge_output: config.merge_output.unwrap_or(false),
+            output_encoding: config
+                .output_encoding
+                .as_deref()
+                .and_then(|label| Encoding::for_label(label.as_bytes()))
+                .unwrap_or(UTF_8),
+            log_mode: config.log_mode.unwrap_or_default(),
+            cpu_affinity: config.cpu_affinity.clone(),
+            sched: config.sched,
+            sched_priority: config.sched_priority.unwrap_or(0),
+            capture_to: config.capture_to.clone(),
+            capture_only: config.capture_only.unwrap_or(false),
+            core_dump_dir: config.core_dump_dir.clone(),
+            adopt_pid_file: config.adopt_pid_file.as_ref().map(PathBuf::from),
+            adopted_pid: None,
+            pidfd: None,
+            memory_limit: config.memory_limit.as_deref().and_then(memory::parse_size),
+            memory_group: None,
+            oom_kills: None,
+            max_open_fds: config.max_open_fds,
+            fd_monitor: fds::FdMonitor::default(),
+            exit_usage: None,
+            watchdog_timeout: config.watchdog_timeout,
+            watchdog_file: config.watchdog_file.as_ref().map(PathBuf::from),
+            watchdog_socket: config.watchdog_socket.unwrap_or(false),
+            watchdog_pattern: config
+                .watchdog_pattern
+                .as_deref()
+                .and_then(|pattern| regex::Regex::new(pattern).ok()),
+            heartbeat: Heartbeat::new(),
+            watchdog_listener: None,
+            notify: config.notify.unwrap_or(false),
+            notify_state: NotifyState::new(),
+            notify_listener: None,
+            output_readers: ReaderBarrier::new(),
+            pipe_size: config.pipe_size,
+            read_buffer_size: config.read_buffer_size.unwrap_or(DEFAULT_READ_BUFFER_SIZE),
+            pipe_monitors: Vec::new(),
+            recent_output: RecentOutput::new(config.crash_context_lines.unwrap_or(DEFAULT_CRASH_CONTEXT_LINES)),
+            every: config.every.as_deref().and_then(|every| parse_duration(every).ok()),
+            run_on_start: config.run_on_start.unwrap_or(true),
+            schedule: config
+                .schedule
+                .as_deref()
+                .and_then(|schedule| CronSchedule::parse(schedule, config.timezone.as_deref()).ok()),
+            missed: config.missed.unwrap_or(MissedRunPolicy::Skip),
+            overlap: config.overlap.unwrap_or(OverlapPolicy::Skip),
+            jitter: config.jitter.as_deref().and_then(|jitter| parse_duration(jitter).ok()),
+            scheduled_restart: config
+                .scheduled_restart
+                .as_deref()
+                .and_then(|schedule| CronSchedule::parse(schedule, config.timezone.as_deref()).ok()),
+            start_window: StartWindow::parse(
+                config.not_before.as_deref(),
+                config.not_after.as_deref(),
+                config.timezone.as_deref(),
+            )
+            .ok()
+            .flatten(),
+            waiting_for_window: false,
+            next_restart_at: None,
+            recycling: false,
+            recycle_kill_at: None,
+            logging: config.logging.clone(),
+            render: config.render.clone().unwrap_or_default(),
+            port_setting: config.port,
+            port_env: config.port_env.clone().unwrap_or_else(|| ports::DEFAULT_ENV.to_string()),
+            port: match config.port {
+                Some(PortConfig::Fixed(port)) => Some(port),
+                _ => None,
+            },
+            sidecar_of: config.sidecar_of.clone(),
+            waiting_for_main: false,
+            status: ProcessStatus::Stopped,
+            process: None,
+            start_time: None,
+            exit_code: None,
+            termination: None,
+            start_requested_at: None,
+            awaiting_ready: None,
+            last_start_duration: None,
+            last_stop_duration: None,
+            restart_at: None,
+            restart_counted: true,
+            recent_restarts: VecDeque::new(),
+            binary_fingerprint: None,
+            pending_binary_fingerprint: None,
+            current_output: None,
+            run_history: VecDeque::new(),
+            next_run_at: None,
+            next_scheduled_at: None,
+            scheduled_run_at: None,
+            overlap_queued: false,
+            maintenance_until: None,
+            manual_stop: false,
+            resume_at: None,
+        }
+    }
+
+    // 由 janus 啟動的子進程或接管的外部進程的 pid
+    pub fn pid(&self) -> Option<u32> {
+        self.process.as_ref().and_then(|child| child.id()).or(self.adopted_pid)
+    }
+
+    // 日誌前綴和狀態中顯示的名稱，例如 "🌐 Web Frontend"
+    pub fn display_label(&self) -> String {
+        let name = self.display_name.as_deref().unwrap_or(&self.name);
+        match &self.label {
+            Some(label) => format!("{} {}", label, name),
+            None => name.to_string(),
+        }
+    }
+
+    // 進程退出時記錄本次運行，只保留最近 RUN_HISTORY_LIMIT 次
+    pub fn record_run(&mut self) {
+        self.run_history.push_back(RunRecord {
+            finished_at: Local::now(),
+            duration: self.start_time.map(|start_time| start_time.elapsed()).unwrap_or_default(),
+            exit_code: self.exit_code,
+            termination: self.termination,
+            output_path: self.current_output.take(),
+            core_path: None,
+            crash_context: Vec::new(),
+            usage: self.exit_usage.take(),
+            required_kill: false,
+        });
+        while self.run_history.len() > RUN_HISTORY_LIMIT {
+            self.run_history.pop_front();
+        }
+    }
+
+    // 子進程已經退出時在回收之前讀取它的資源用量；之後的 try_wait 會回收它，/proc 中的數據隨之消失
+    pub fn capture_usage(&mut self) {
+        if self.exit_usage.is_none() {
+            self.exit_usage = self.pid().filter(|pid| accounting::exited(*pid)).and_then(accounting::read);
+        }
+    }
+
+    // OOM 計數所在的 cgroup：限制了內存的進程有自己的 cgroup，其他進程共用容器的 cgroup
+    pub fn oom_cgroup(&self) -> Option<PathBuf> {
+        match self.memory_group.as_ref().and_then(memory::MemoryGroup::cgroup) {
+            Some(cgroup) => Some(cgroup.to_path_buf()),
+            None => memory::cgroup::container(),
+        }
+    }
+
+    // now 來自管理器的時鐘；status 顯示時按系統時鐘
+    pub fn in_maintenance(&self, now: Instant) -> bool {
+        self.maintenance_until.is_some_and(|until| until > now)
+    }
+
+    // 啟動階段已持續的時間：notify 進程在發送 READY=1 之前，其他進程在運行時間達到 expected_start_duration 之前
+    pub fn starting_for(&self) -> Option<Duration> {
+        if self.status != ProcessStatus::Running || self.pid().is_none() {
+            return None;
+        }
+        let uptime = self.start_time?.elapsed();
+        let starting = if self.notify {
+            !self.notify_state.is_ready()
+        } else {
+            self.expected_start_duration.is_some_and(|expected| uptime < expected)
+        };
+        starting.then_some(uptime)
+    }
+
+    // 進程就緒的時刻：notify 進程為收到 READY=1 時，設置 expected_start_duration 的進程為運行滿該時長時，
+    // 其他進程創建即就緒。notify 進程尚未報告時返回 None
+    pub fn ready_at(&self, spawned_at: Instant) -> Option<Instant> {
+        if self.notify {
+            self.notify_state.get().ready_at
+        } else {
+            Some(spawned_at + self.expected_start_duration.unwrap_or_default())
+        }
+    }
+
+    // 定時任務在 run_on_start = false 時不隨 start 啟動，等待第一個週期；cron 任務只在排程時間運行
+    pub fn starts_with_supervisor(&self) -> bool {
+        self.schedule.is_none() && (self.every.is_none() || self.run_on_start)
+    }
+
+    // 熔斷：在 restart_budget_window 秒內已重啟 restart_budget 次時不再重啟
+    pub fn restart_budget_exhausted(&mut self, now: Instant) -> bool {
+        let Some(budget) = self.restart_budget else {
+            return false;
+        };
+        let window = Duration::from_secs(self.restart_budget_window);
+
+        while self
+            .recent_restarts
+            .front()
+            .is_some_and(|restarted_at| now.saturating_duration_since(*restarted_at) > window)
+        {
+            self.recent_restarts.pop_front();
+        }
+
+        self.recent_restarts.len() >= budget as usize
+    }
 }
 
 // 手動實現 Clone，避免克隆 tokio::process::Child
@@ -31,17 +549,121 @@ impl Clone for ManagedProcess {
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
+            display_name: self.display_name.clone(),
+            label: self.label.clone(),
+            group: self.group.clone(),
             command: self.command.clone(),
             args: self.args.clone(),
+            exec_prefix: self.exec_prefix.clone(),
+            shell: self.shell,
+            image: self.image.clone(),
+            container_ports: self.container_ports.clone(),
+            container_volumes: self.container_volumes.clone(),
+            container_host_config: self.container_host_config.clone(),
             working_dir: self.working_dir.clone(),
             env: self.env.clone(),
+            env_dirs: self.env_dirs.clone(),
+            env_cache: environment::EnvCache::default(),
+            env_allow: self.env_allow.clone(),
+            env_deny: self.env_deny.clone(),
             auto_restart: self.auto_restart,
             restart_count: self.restart_count,
             restart_limit: self.restart_limit,
             restart_delay: self.restart_delay,
+            restart_budget: self.restart_budget,
+            restart_budget_window: self.restart_budget_window,
+            stable_after: self.stable_after,
+            restart_script: self.restart_script.clone(),
+            restart_on_binary_change: self.restart_on_binary_change,
+            expected_sha256: self.expected_sha256.clone(),
+            checksum_mismatch: self.checksum_mismatch,
+            oneshot_keep_status: self.oneshot_keep_status,
+            ignore_exit_codes: self.ignore_exit_codes.clone(),
+            wait_for: self.wait_for.clone(),
+            wait_for_path: self.wait_for_path.clone(),
+            cleanup_paths: self.cleanup_paths.clone(),
+            wait_timeout: self.wait_timeout,
+            requires: self.requires.clone(),
+            critical: self.critical,
+            slow_start_threshold: self.slow_start_threshold,
+            expected_start_duration: self.expected_start_duration,
+            slow_stop_threshold: self.slow_stop_threshold,
+            stop_timeout: self.stop_timeout,
+            stopping_since: self.stopping_since,
+            merge_output: self.merge_output,
+            output_encoding: self.output_encoding,
+            log_mode: self.log_mode,
+            cpu_affinity: self.cpu_affinity.clone(),
+            sched: self.sched,
+            sched_priority: self.sched_priority,
+            capture_to: self.capture_to.clone(),
+            capture_only: self.capture_only,
+            core_dump_dir: self.core_dump_dir.clone(),
+            adopt_pid_file: self.adopt_pid_file.clone(),
+            adopted_pid: None,
+            pidfd: None,
+            memory_limit: self.memory_limit,
+            memory_group: None,
+            oom_kills: None,
+            max_open_fds: self.max_open_fds,
+            fd_monitor: fds::FdMonitor::default(),
+            exit_usage: None,
+            watchdog_timeout: self.watchdog_timeout,
+            watchdog_file: self.watchdog_file.clone(),
+            watchdog_socket: self.watchdog_socket,
+            watchdog_pattern: self.watchdog_pattern.clone(),
+            heartbeat: Heartbeat::new(),
+            watchdog_listener: None,
+            notify: self.notify,
+            notify_state: NotifyState::new(),
+            notify_listener: None,
+            output_readers: ReaderBarrier::new(),
+            pipe_size: self.pipe_size,
+            read_buffer_size: self.read_buffer_size,
+            pipe_monitors: Vec::new(),
+            recent_output: self.recent_output.clone(),
+            every: self.every,
+            run_on_start: self.run_on_start,
+            schedule: self.schedule.clone(),
+            missed: self.missed,
+            overlap: self.overlap,
+            jitter: self.jitter,
+            scheduled_restart: self.scheduled_restart.clone(),
+            start_window: self.start_window.clone(),
+            waiting_for_window: self.waiting_for_window,
+            next_restart_at: self.next_restart_at,
+            recycling: self.recycling,
+            recycle_kill_at: self.recycle_kill_at,
+            logging: self.logging.clone(),
+            render: self.render.clone(),
+            port_setting: self.port_setting,
+            port_env: self.port_env.clone(),
+            port: self.port,
+            sidecar_of: self.sidecar_of.clone(),
+            waiting_for_main: self.waiting_for_main,
             status: self.status.clone(),
             process: None, // 不克隆進程句柄
             start_time: self.start_time, // Instant 已實現 Copy，無需克隆
+            exit_code: self.exit_code,
+            termination: self.termination,
+            start_requested_at: self.start_requested_at,
+            awaiting_ready: self.awaiting_ready,
+            last_start_duration: self.last_start_duration,
+            last_stop_duration: self.last_stop_duration,
+            restart_at: self.restart_at,
+            restart_counted: self.restart_counted,
+            recent_restarts: self.recent_restarts.clone(),
+            binary_fingerprint: self.binary_fingerprint.clone(),
+            pending_binary_fingerprint: self.pending_binary_fingerprint.clone(),
+            current_output: self.current_output.clone(),
+            run_history: self.run_history.clone(),
+            next_run_at: self.next_run_at,
+            next_scheduled_at: self.next_scheduled_at,
+            scheduled_run_at: self.scheduled_run_at,
+            overlap_queued: self.overlap_queued,
+            maintenance_until: self.maintenance_until,
+            manual_stop: self.manual_stop,
+            resume_at: self.resume_at,
         }
     }
 }
@@ -0,0 +1,165 @@
+// 兼容 systemd sd_notify 的通知 socket：進程從 NOTIFY_SOCKET 得到路徑，發送換行分隔的 KEY=VALUE 消息。
+// READY=1 表示進程已就緒，STATUS= 設置狀態文本，WATCHDOG=1 記為一次看門狗心跳，其他消息被忽略
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::clock::Clock;
+
+use crate::logging::handler::LogHandler;
+use crate::logging::LogType;
+
+use super::watchdog::Heartbeat;
+
+pub const SOCKET_ENV: &str = "NOTIFY_SOCKET";
+
+// 啟用看門狗時傳給進程的超時，單位為微秒，與 systemd 一致
+pub const WATCHDOG_USEC_ENV: &str = "WATCHDOG_USEC";
+
+// sd_notify 消息不會超過一個數據報
+const MAX_MESSAGE_BYTES: usize = 4096;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NotifyStatus {
+    pub ready: bool,
+    // 收到 READY=1 的時刻，用於計算啟動耗時
+    pub ready_at: Option<Instant>,
+    pub status: Option<String>,
+}
+
+// 進程通過通知 socket 報告的狀態，在 socket 任務和監控循環之間共享
+#[derive(Clone, Debug, Default)]
+pub struct NotifyState(Arc<Mutex<NotifyStatus>>);
+
+impl NotifyState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> NotifyStatus {
+        self.0.lock().map(|status| status.clone()).unwrap_or_default()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.get().ready
+    }
+
+    // 每次啟動時重置，進程需要重新報告 READY=1
+    pub fn reset(&self) {
+        if let Ok(mut status) = self.0.lock() {
+            *status = NotifyStatus::default();
+        }
+    }
+
+    // now 來自管理器的時鐘
+    pub fn set_ready(&self, now: Instant) {
+        if let Ok(mut status) = self.0.lock() {
+            status.ready = true;
+            status.ready_at.get_or_insert(now);
+        }
+    }
+
+    // 處理一條消息，返回進程是否因此變為就緒
+    pub fn apply(&self, message: &str, heartbeat: &Heartbeat, now: Instant) -> bool {
+        let Ok(mut status) = self.0.lock() else {
+            return false;
+        };
+        let mut became_ready = false;
+        for (key, value) in message.lines().filter_map(|line| line.split_once('=')) {
+            match key {
+                "READY" if value == "1" => {
+                    became_ready = !status.ready;
+                    status.ready = true;
+                    status.ready_at.get_or_insert(now);
+                }
+                "STATUS" => status.status = Some(value.to_string()).filter(|text| !text.is_empty()),
+                "WATCHDOG" if value == "1" => heartbeat.beat(),
+                _ => {}
+            }
+        }
+        became_ready
+    }
+}
+
+// janus 自己作為 systemd 服務 (Type=notify) 運行時向 systemd 報告狀態；沒有 NOTIFY_SOCKET 時什麼也不做。
+// 以 @ 開頭的地址是 Linux 的抽象命名空間 socket
+#[cfg(unix)]
+pub fn notify_systemd(message: &str) -> io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(address) = std::env::var_os(SOCKET_ENV) else {
+        return Ok(());
+    };
+    let socket = UnixDatagram::unbound()?;
+    #[cfg(target_os = "linux")]
+    if let Some(name) = address.to_string_lossy().strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        let address = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+        socket.send_to_addr(message.as_bytes(), &address)?;
+        return Ok(());
+    }
+    socket.send_to(message.as_bytes(), Path::new(&address))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn notify_systemd(_message: &str) -> io::Result<()> {
+    Ok(())
+}
+
+// janus 為進程創建的通知 socket；釋放時刪除 socket 文件
+pub struct NotifySocket {
+    path: PathBuf,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl NotifySocket {
+    #[cfg(unix)]
+    pub fn bind(
+        process_name: &str,
+        state: NotifyState,
+        heartbeat: Heartbeat,
+        clock: Arc<dyn Clock>,
+        log_handler: LogHandler,
+    ) -> io::Result<Self> {
+        let file_name = format!("janus-{}-{}.notify", std::process::id(), process_name.replace('/', ":"));
+        let path = std::env::temp_dir().join(file_name);
+        let _ = std::fs::remove_file(&path);
+        let socket = tokio::net::UnixDatagram::bind(&path)?;
+
+        let process_name = process_name.to_string();
+        let task = tokio::spawn(async move {
+            let mut buffer = vec![0u8; MAX_MESSAGE_BYTES];
+            while let Ok(len) = socket.recv(&mut buffer).await {
+                let message = String::from_utf8_lossy(&buffer[..len]);
+                if state.apply(&message, &heartbeat, clock.now()) {
+                    log_handler.log(&process_name, LogType::System, "Process ready");
+                }
+            }
+        });
+        Ok(Self { path, task })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(
+        _process_name: &str,
+        _state: NotifyState,
+        _heartbeat: Heartbeat,
+        _clock: Arc<dyn Clock>,
+        _log_handler: LogHandler,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "notify sockets are only supported on unix"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for NotifySocket {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
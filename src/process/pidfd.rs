@@ -0,0 +1,203 @@
+// Linux 的進程文件描述符：pidfd 綁定到具體的進程，即使 pid 被重用也不會把信號發給別的進程；
+// macOS 上用 kqueue 的 EVFILT_PROC 得到進程退出的通知。內核不支持 (Linux 5.3 之前) 或其他平台上
+// open 返回 None，調用方回退到基於 pid 的方式
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::io;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::ExitStatus;
+
+#[cfg(target_os = "linux")]
+pub struct PidFd(std::os::fd::OwnedFd);
+
+#[cfg(target_os = "linux")]
+impl PidFd {
+    pub fn open(pid: u32) -> Option<Self> {
+        use std::os::fd::{FromRawFd, OwnedFd};
+
+        // SAFETY: pidfd_open 只接受整數參數，成功時返回新的文件描述符
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+        if fd < 0 {
+            return None;
+        }
+        // SAFETY: fd 剛由內核創建，所有權交給 OwnedFd
+        Some(Self(unsafe { OwnedFd::from_raw_fd(fd as libc::c_int) }))
+    }
+
+    pub fn send_signal(&self, signal: libc::c_int) -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: fd 在 self 存活期間有效，siginfo 為空時與 kill 的語義相同
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                self.0.as_raw_fd(),
+                signal,
+                std::ptr::null::<libc::siginfo_t>(),
+                0,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    // 進程退出後 pidfd 變為可讀，不需要 pid 也不會被重用的 pid 混淆
+    pub fn exited(&self) -> bool {
+        use std::os::fd::AsRawFd;
+
+        let mut pollfd = libc::pollfd {
+            fd: self.0.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: 超時為 0，不會阻塞；pollfd 指向有效的結構
+        let ready = unsafe { libc::poll(&mut pollfd, 1, 0) };
+        ready > 0
+    }
+
+    // 進程是 janus 的子進程時回收它並返回退出狀態；P_PIDFD 只會回收這一個進程
+    pub fn reap(&self) -> Option<ExitStatus> {
+        use std::os::fd::AsRawFd;
+        use std::os::unix::process::ExitStatusExt;
+
+        // SAFETY: siginfo_t 是普通的 C 結構，全零是有效的初始值
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        // SAFETY: WNOHANG 不會阻塞，info 指向有效的結構
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PIDFD,
+                self.0.as_raw_fd() as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOHANG,
+            )
+        };
+        // 不是子進程時返回 ECHILD
+        // SAFETY: waitid 成功後 si_pid 和 si_status 已由內核填充
+        if result != 0 || unsafe { info.si_pid() } == 0 {
+            return None;
+        }
+
+        let status = unsafe { info.si_status() };
+        let raw = match info.si_code {
+            libc::CLD_EXITED => (status & 0xff) << 8,
+            libc::CLD_KILLED => status,
+            libc::CLD_DUMPED => status | 0x80,
+            _ => return None,
+        };
+        Some(ExitStatus::from_raw(raw))
+    }
+}
+
+// kqueue 中的 NOTE_EXIT 在註冊時綁定到這個進程，pid 之後被重用也只會報告原進程的退出
+#[cfg(target_os = "macos")]
+pub struct PidFd {
+    kqueue: std::os::fd::OwnedFd,
+    pid: libc::pid_t,
+    // 退出事件只能取出一次
+    exited: std::sync::atomic::AtomicBool,
+}
+
+#[cfg(target_os = "macos")]
+impl PidFd {
+    pub fn open(pid: u32) -> Option<Self> {
+        use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+        // SAFETY: kqueue 不接受參數，成功時返回新的文件描述符
+        let fd = unsafe { libc::kqueue() };
+        if fd < 0 {
+            return None;
+        }
+        // SAFETY: fd 剛由內核創建，所有權交給 OwnedFd
+        let kqueue = unsafe { OwnedFd::from_raw_fd(fd) };
+        let change = libc::kevent {
+            ident: pid as libc::uintptr_t,
+            filter: libc::EVFILT_PROC,
+            flags: libc::EV_ADD,
+            fflags: libc::NOTE_EXIT,
+            data: 0,
+            udata: std::ptr::null_mut(),
+        };
+        // 不取回事件，只註冊後立即返回；進程已經不存在時返回 ESRCH
+        // SAFETY: change 指向有效的結構，事件列表為空
+        let result = unsafe {
+            libc::kevent(kqueue.as_raw_fd(), &change, 1, std::ptr::null_mut(), 0, std::ptr::null())
+        };
+        (result == 0).then(|| Self {
+            kqueue,
+            pid: pid as libc::pid_t,
+            exited: std::sync::atomic::AtomicBool::new(false),
+        })
+    }
+
+    // macOS 沒有綁定進程的 kill，退出後不再發送信號，避免送給重用了 pid 的進程
+    pub fn send_signal(&self, signal: libc::c_int) -> io::Result<()> {
+        if self.exited() {
+            return Err(io::Error::from_raw_os_error(libc::ESRCH));
+        }
+        // SAFETY: kill 只接受整數參數
+        if unsafe { libc::kill(self.pid, signal) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn exited(&self) -> bool {
+        use std::os::fd::AsRawFd;
+        use std::sync::atomic::Ordering;
+
+        if self.exited.load(Ordering::Relaxed) {
+            return true;
+        }
+        // SAFETY: kevent 是普通的 C 結構，全零是有效的初始值
+        let mut event: libc::kevent = unsafe { std::mem::zeroed() };
+        let timeout = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        // SAFETY: 超時為 0，不會阻塞；event 指向有效的結構
+        let ready = unsafe {
+            libc::kevent(self.kqueue.as_raw_fd(), std::ptr::null(), 0, &mut event, 1, &timeout)
+        };
+        let exited = ready > 0 && event.fflags & libc::NOTE_EXIT != 0;
+        if exited {
+            self.exited.store(true, Ordering::Relaxed);
+        }
+        exited
+    }
+
+    // 退出的進程是 janus 的子進程時回收它並返回退出狀態
+    pub fn reap(&self) -> Option<ExitStatus> {
+        use std::os::unix::process::ExitStatusExt;
+
+        if !self.exited() {
+            return None;
+        }
+        let mut status = 0;
+        // SAFETY: WNOHANG 不會阻塞，status 指向有效的整數；不是子進程時返回 ECHILD
+        let reaped = unsafe { libc::waitpid(self.pid, &mut status, libc::WNOHANG) };
+        (reaped == self.pid).then(|| ExitStatus::from_raw(status))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub struct PidFd;
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+impl PidFd {
+    pub fn open(_pid: u32) -> Option<Self> {
+        None
+    }
+
+    #[cfg(unix)]
+    pub fn send_signal(&self, _signal: libc::c_int) -> std::io::Result<()> {
+        Err(std::io::Error::from(std::io::ErrorKind::Unsupported))
+    }
+
+    pub fn exited(&self) -> bool {
+        false
+    }
+
+    pub fn reap(&self) -> Option<std::process::ExitStatus> {
+        None
+    }
+}
@@ -0,0 +1,79 @@
+// 子進程 stdio 管道的容量調整與寫滿偵測。讀取任務忙不過來時管道會被填滿，
+// 進程隨後在寫輸出時阻塞；監控循環定期檢查管道中的待讀數據並記錄警告。僅支持 Linux
+use crate::logging::LogType;
+
+#[cfg(target_os = "linux")]
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::fd::{AsRawFd, BorrowedFd, OwnedFd};
+
+// 調整管道容量，返回內核實際設置的大小（向上取整到頁大小的 2 的冪）
+#[cfg(target_os = "linux")]
+pub fn set_size(pipe: BorrowedFd<'_>, size: usize) -> io::Result<usize> {
+    let size = libc::c_int::try_from(size).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    // SAFETY: fd 在調用期間有效，F_SETPIPE_SZ 只接受整數參數
+    let result = unsafe { libc::fcntl(pipe.as_raw_fd(), libc::F_SETPIPE_SZ, size) };
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result as usize)
+    }
+}
+
+// 持有讀取端的副本，不影響讀取任務，也不會推遲 EOF（EOF 只取決於寫入端）
+#[cfg(target_os = "linux")]
+pub struct PipeMonitor {
+    fd: OwnedFd,
+    pub stream: LogType,
+    stalled: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl PipeMonitor {
+    pub fn new(pipe: BorrowedFd<'_>, stream: LogType) -> Option<Self> {
+        Some(Self {
+            fd: pipe.try_clone_to_owned().ok()?,
+            stream,
+            stalled: false,
+        })
+    }
+
+    // 升級時交給新 janus 的讀取端
+    pub fn raw_fd(&self) -> std::os::fd::RawFd {
+        self.fd.as_raw_fd()
+    }
+
+    // 管道變為已滿時返回 Some(true)，恢復時返回 Some(false)，狀態不變時返回 None
+    pub fn check(&mut self) -> Option<bool> {
+        let fd = self.fd.as_raw_fd();
+        // SAFETY: fd 在 self 存活期間有效
+        let capacity = unsafe { libc::fcntl(fd, libc::F_GETPIPE_SZ) };
+        let mut available: libc::c_int = 0;
+        // SAFETY: FIONREAD 把待讀字節數寫入 available
+        let result = unsafe { libc::ioctl(fd, libc::FIONREAD, &mut available) };
+        if capacity <= 0 || result < 0 {
+            return None;
+        }
+
+        // 小塊寫入不一定能填滿每一頁，剩餘空間不足一頁時就視為已滿
+        let page = 4096;
+        let stalled = available >= capacity - page.min(capacity / 2);
+        if stalled == self.stalled {
+            return None;
+        }
+        self.stalled = stalled;
+        Some(stalled)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub struct PipeMonitor {
+    pub stream: LogType,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl PipeMonitor {
+    pub fn check(&mut self) -> Option<bool> {
+        None
+    }
+}
@@ -0,0 +1,163 @@
+// 插件：配置的外部可執行文件在 stdin 上收到一行 JSON 格式的進程事件，
+// 可以在 stdout 上返回一行 JSON 動作，例如取消重啟或修改重啟延遲
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use crate::config::PluginConfig;
+use crate::error::{JanusError, Result};
+
+use super::events::{EventKind, ProcessEvent};
+
+// 插件未設置 timeout 時等待響應的秒數
+pub const DEFAULT_PLUGIN_TIMEOUT: u64 = 5;
+
+// 插件的響應最多讀取的字節數
+const MAX_RESPONSE_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct PluginEvent {
+    // started、start_failed、exited、crashed、stopped、gave_up、oom_killed、restart_paused 或 fds_high
+    pub event: &'static str,
+    pub process: String,
+    pub timestamp: String,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub detail: String,
+    pub restart_count: u32,
+    // 已安排重啟時距離重啟的秒數
+    pub restart_in: Option<u64>,
+    // oom_killed 事件的內存上限和峰值，單位為字節
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_peak: Option<u64>,
+}
+
+impl PluginEvent {
+    // 異常退出 (進程狀態為 Failed) 的 Exited 事件報告為 crashed
+    pub fn new(event: &ProcessEvent, crashed: bool, restart_count: u32, restart_in: Option<u64>) -> Self {
+        let name = match event.kind {
+            EventKind::Exited if crashed => "crashed",
+            kind => kind.as_str(),
+        };
+        Self {
+            event: name,
+            process: event.process.clone(),
+            timestamp: event.timestamp.to_rfc3339(),
+            pid: event.pid,
+            exit_code: event.exit_code,
+            detail: event.detail.clone(),
+            restart_count,
+            restart_in,
+            memory_limit: event.memory_limit,
+            memory_peak: event.memory_peak,
+        }
+    }
+}
+
+// 插件可以返回的動作，只對已安排重啟的 exited 和 crashed 事件生效；沒有輸出表示不做任何修改
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginAction {
+    pub suppress_restart: bool,
+    // 以秒為單位的新重啟延遲
+    pub restart_delay: Option<u64>,
+}
+
+pub async fn notify(plugin: &PluginConfig, event: &PluginEvent) -> Result<PluginAction> {
+    let mut input = serde_json::to_string(event)
+        .map_err(|e| JanusError::Process(format!("Failed to encode plugin event: {}", e)))?;
+    input.push('\n');
+
+    let mut child = Command::new(&plugin.command)
+        .args(plugin.args.iter().flatten())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| JanusError::Process(format!("Failed to run plugin {}: {}", plugin.command, e)))?;
+
+    let mut stdin = child.stdin.take().expect("plugin stdin is piped");
+    let mut stdout = child.stdout.take().expect("plugin stdout is piped");
+    let timeout = Duration::from_secs(plugin.timeout.unwrap_or(DEFAULT_PLUGIN_TIMEOUT));
+
+    let exchange = async {
+        // 插件不讀取 stdin 就退出時寫入會失敗，這不影響讀取它的響應
+        let _ = stdin.write_all(input.as_bytes()).await;
+        drop(stdin);
+
+        let mut output = String::new();
+        (&mut stdout).take(MAX_RESPONSE_BYTES).read_to_string(&mut output).await?;
+        let status = child.wait().await?;
+        Ok::<_, std::io::Error>((status, output))
+    };
+
+    let (status, output) = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| JanusError::Process(format!("Plugin {} timed out after {}s", plugin.command, timeout.as_secs())))?
+        .map_err(|e| JanusError::Process(format!("Plugin {} failed: {}", plugin.command, e)))?;
+
+    if !status.success() {
+        return Err(JanusError::Process(format!("Plugin {} exited with {}", plugin.command, status)));
+    }
+
+    match output.lines().map(str::trim).find(|line| !line.is_empty()) {
+        Some(line) => serde_json::from_str(line).map_err(|e| {
+            JanusError::Process(format!("Invalid response from plugin {}: {}", plugin.command, e))
+        }),
+        None => Ok(PluginAction::default()),
+    }
+}
+
+// 交給插件的一個事件；event 是原始事件，插件的動作按它應用
+pub struct PluginJob {
+    pub plugins: Vec<PluginConfig>,
+    pub event: ProcessEvent,
+    pub plugin_event: PluginEvent,
+}
+
+// 一個事件的所有插件運行結束後發回的結果，按插件的配置順序排列
+pub struct PluginReply {
+    pub event: ProcessEvent,
+    pub actions: Vec<(String, Result<PluginAction>)>,
+}
+
+// 在獨立任務中按事件發生的順序運行插件，監控循環提交事件後不等待插件響應，
+// 之後每輪取回已經完成的結果
+pub struct PluginRunner {
+    jobs: mpsc::UnboundedSender<PluginJob>,
+    replies: mpsc::UnboundedReceiver<PluginReply>,
+}
+
+impl PluginRunner {
+    pub fn start() -> Self {
+        let (jobs, mut pending) = mpsc::unbounded_channel::<PluginJob>();
+        let (done, replies) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(job) = pending.recv().await {
+                let mut actions = Vec::new();
+                for plugin in &job.plugins {
+                    actions.push((plugin.command.clone(), notify(plugin, &job.plugin_event).await));
+                }
+                if done.send(PluginReply { event: job.event, actions }).is_err() {
+                    return;
+                }
+            }
+        });
+        Self { jobs, replies }
+    }
+
+    pub fn submit(&self, job: PluginJob) {
+        // 任務只在接收端被丟棄時結束，這時管理器也已不存在
+        let _ = self.jobs.send(job);
+    }
+
+    pub fn try_recv(&mut self) -> Option<PluginReply> {
+        self.replies.try_recv().ok()
+    }
+}
@@ -0,0 +1,78 @@
+// 端口池：port = "auto" 的進程每次啟動時從 global.port_range 中取得一個端口，通過環境變量傳給進程。
+// 同一個進程重啟時盡量沿用原來的端口；分配時跳過其他進程已分配或固定使用的端口，以及已被其他程序佔用的端口
+use std::collections::{HashMap, HashSet};
+use std::net::TcpListener;
+use std::ops::RangeInclusive;
+
+pub const DEFAULT_RANGE: RangeInclusive<u16> = 20000..=29999;
+
+// 默認傳給進程的環境變量
+pub const DEFAULT_ENV: &str = "PORT";
+
+// 解析 "20000-29999" 這樣的範圍
+pub fn parse_range(range: &str) -> Option<RangeInclusive<u16>> {
+    let (start, end) = range.split_once('-')?;
+    let start: u16 = start.trim().parse().ok()?;
+    let end: u16 = end.trim().parse().ok()?;
+    (start > 0 && start <= end).then_some(start..=end)
+}
+
+#[derive(Debug)]
+pub struct PortPool {
+    range: RangeInclusive<u16>,
+    assigned: HashMap<String, u16>,
+}
+
+impl Default for PortPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_RANGE)
+    }
+}
+
+impl PortPool {
+    pub fn new(range: RangeInclusive<u16>) -> Self {
+        Self { range, assigned: HashMap::new() }
+    }
+
+    // 範圍改變後，已分配但不在新範圍內的端口在下次啟動時重新分配
+    pub fn set_range(&mut self, range: RangeInclusive<u16>) {
+        self.range = range;
+    }
+
+    pub fn assigned(&self, name: &str) -> Option<u16> {
+        self.assigned.get(name).copied()
+    }
+
+    // reserved 是其他進程固定使用的端口；沒有可用端口時返回 None
+    pub fn allocate(&mut self, name: &str, reserved: &HashSet<u16>) -> Option<u16> {
+        let taken: HashSet<u16> = self
+            .assigned
+            .iter()
+            .filter(|(owner, _)| owner.as_str() != name)
+            .map(|(_, port)| *port)
+            .chain(reserved.iter().copied())
+            .collect();
+        let usable = |port: u16| self.range.contains(&port) && !taken.contains(&port) && available(port);
+
+        let port = match self.assigned(name).filter(|port| usable(*port)) {
+            Some(port) => port,
+            None => self.range.clone().find(|port| usable(*port))?,
+        };
+        self.assigned.insert(name.to_string(), port);
+        Some(port)
+    }
+
+    // 記錄已經在使用的端口，例如升級前分配的端口
+    pub fn claim(&mut self, name: &str, port: u16) {
+        self.assigned.insert(name.to_string(), port);
+    }
+
+    pub fn release(&mut self, name: &str) {
+        self.assigned.remove(name);
+    }
+}
+
+// 端口當前是否可以監聽；std 在 Unix 上設置 SO_REUSEADDR，剛退出的進程留下的 TIME_WAIT 連接不影響判斷
+fn available(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
@@ -0,0 +1,133 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::Result;
+
+// 隊列文件被其他 janus 進程鎖住時重試的間隔
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+// 持久化的任務隊列：每行一個進程名，由 janus enqueue 追加，由前台監控循環按順序取出
+pub struct TaskQueue {
+    path: PathBuf,
+}
+
+impl TaskQueue {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    // 追加到隊尾，返回在隊列中的位置（從 1 開始）
+    pub async fn push(&self, name: &str) -> Result<usize> {
+        self.update(|queue| {
+            queue.push(name.to_string());
+            queue.len()
+        })
+        .await
+    }
+
+    pub async fn pending(&self) -> Result<Vec<String>> {
+        self.update(|queue| queue.clone()).await
+    }
+
+    // 從隊首取出最多 max 個任務；隊首任務不能啟動時停止，保持先進先出
+    pub async fn take<F>(&self, max: usize, mut can_start: F) -> Result<Vec<String>>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.update(|queue| {
+            let mut taken = Vec::new();
+            while taken.len() < max && queue.first().is_some_and(|name| can_start(name)) {
+                taken.push(queue.remove(0));
+            }
+            taken
+        })
+        .await
+    }
+
+    // 在文件鎖內讀取、修改並寫回隊列，避免與其他 janus 進程交錯；
+    // 鎖被佔用時異步等待後重試，不阻塞監控循環所在的 runtime
+    async fn update<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Vec<String>) -> R,
+    {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        let _lock = loop {
+            if let Some(lock) = FileLock::try_acquire(&file)? {
+                break lock;
+            }
+            tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+        };
+
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        let mut queue: Vec<String> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        let before = queue.clone();
+
+        let result = f(&mut queue);
+
+        if queue != before {
+            let mut content = queue.join("\n");
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(content.as_bytes())?;
+            file.set_len(content.len() as u64)?;
+            file.sync_all()?;
+        }
+
+        Ok(result)
+    }
+}
+
+// flock 排他鎖，離開作用域時釋放
+struct FileLock {
+    #[cfg(unix)]
+    fd: std::os::unix::io::RawFd,
+}
+
+impl FileLock {
+    // 不等待鎖：被其他進程持有時返回 None
+    #[cfg(unix)]
+    fn try_acquire(file: &File) -> Result<Option<Self>> {
+        use std::os::unix::io::AsRawFd;
+
+        let fd = file.as_raw_fd();
+        // SAFETY: fd 在 file 的生命週期內有效，鎖在 file 關閉前釋放
+        if unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let error = std::io::Error::last_os_error();
+            if error.kind() == std::io::ErrorKind::WouldBlock {
+                return Ok(None);
+            }
+            return Err(error.into());
+        }
+        Ok(Some(Self { fd }))
+    }
+
+    #[cfg(not(unix))]
+    fn try_acquire(_file: &File) -> Result<Option<Self>> {
+        Ok(Some(Self {}))
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // SAFETY: 鎖在 file 之後聲明，因此先於 file 被釋放，fd 仍然有效
+        #[cfg(unix)]
+        unsafe {
+            libc::flock(self.fd, libc::LOCK_UN);
+        }
+    }
+}
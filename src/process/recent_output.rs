@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// 進程最近的輸出行，進程異常退出時作為崩潰上下文記錄下來
+#[derive(Clone, Debug, Default)]
+pub struct RecentOutput {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl RecentOutput {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, line: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut lines) = self.lines.lock() {
+            lines.clear();
+        }
+    }
+}
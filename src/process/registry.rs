@@ -0,0 +1,87 @@
+// 進程註冊表：每個進程當前的狀態、pid 和端口，寫入 global.registry_file 供容器中的其他程序讀取，
+// 例如反向代理據此找到 port = "auto" 的實例。只在內容變化時重寫，先寫臨時文件再改名，讀取方不會看到寫了一半的文件
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+use crate::logging::handler::LogHandler;
+use crate::logging::LogType;
+
+use super::snapshot::StatusSnapshot;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Registry {
+    // 寫入時間，不參與是否變化的比較
+    pub updated_at: String,
+    // 寫入註冊表的 janus 的 pid
+    pub supervisor_pid: u32,
+    pub processes: BTreeMap<String, RegistryEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub status: String,
+    pub pid: Option<u32>,
+    pub port: Option<u16>,
+}
+
+impl Registry {
+    pub fn from_snapshot(snapshot: &StatusSnapshot) -> Self {
+        Self {
+            updated_at: snapshot.timestamp.clone(),
+            supervisor_pid: std::process::id(),
+            processes: snapshot
+                .processes
+                .iter()
+                .map(|process| {
+                    let entry = RegistryEntry {
+                        status: process.status.clone(),
+                        pid: process.pid,
+                        port: process.port,
+                    };
+                    (process.name.clone(), entry)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, json + "\n")?;
+        fs::rename(&temp, path)
+    }
+}
+
+// 跟隨發布的狀態快照更新註冊表，直到 janus 退出；寫入失敗時只記錄一次，直到錯誤改變或恢復
+pub async fn watch(path: PathBuf, mut status: watch::Receiver<Arc<StatusSnapshot>>, log_handler: LogHandler) {
+    let mut written: Option<BTreeMap<String, RegistryEntry>> = None;
+    let mut last_error: Option<String> = None;
+    loop {
+        let registry = Registry::from_snapshot(&status.borrow_and_update());
+        if written.as_ref() != Some(&registry.processes) {
+            match registry.write(&path) {
+                Ok(()) => {
+                    written = Some(registry.processes);
+                    last_error = None;
+                }
+                Err(e) => {
+                    let error = format!("Failed to write registry {}: {}", path.display(), e);
+                    if last_error.as_ref() != Some(&error) {
+                        log_handler.log("janus", LogType::System, &error);
+                        last_error = Some(error);
+                    }
+                }
+            }
+        }
+        if status.changed().await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// 啟動進程前按模板生成配置文件，例如從環境變量生成 nginx.conf，替代入口腳本中的 envsubst。
+// {{ NAME }} 替換為變量的值，{{ NAME:-默認值 }} 在變量不存在時使用默認值；沒有默認值的未知變量使啟動失敗
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::RenderConfig;
+
+pub fn render(template: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| format!("unterminated placeholder at line {}", line_of(template, rest, start)))?;
+        let placeholder = after[..end].trim();
+        let (name, default) = match placeholder.split_once(":-") {
+            Some((name, default)) => (name.trim(), Some(default.trim())),
+            None => (placeholder, None),
+        };
+        if !is_name(name) {
+            return Err(format!(
+                "invalid placeholder {{{{ {} }}}} at line {}",
+                placeholder,
+                line_of(template, rest, start)
+            ));
+        }
+        match vars.get(name).map(String::as_str).or(default) {
+            Some(value) => output.push_str(value),
+            None => return Err(format!("variable {} is not set", name)),
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+// 先寫入同目錄下的臨時文件再改名，進程不會讀到寫了一半的文件
+pub fn render_file(config: &RenderConfig, vars: &HashMap<String, String>) -> Result<(), String> {
+    let template = fs::read_to_string(&config.template)
+        .map_err(|e| format!("Failed to read template {}: {}", config.template, e))?;
+    let mut vars = vars.clone();
+    vars.extend(config.vars.clone().unwrap_or_default());
+    let content = render(&template, &vars).map_err(|e| format!("Failed to render {}: {}", config.template, e))?;
+
+    let target = Path::new(&config.target);
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", config.target, e);
+    if let Some(parent) = target.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(write_error)?;
+    }
+    let temp = target.with_file_name(format!(
+        ".{}.janus-tmp",
+        target.file_name().and_then(|name| name.to_str()).unwrap_or("render")
+    ));
+    fs::write(&temp, content).map_err(write_error)?;
+    #[cfg(unix)]
+    if let Some(mode) = config.mode.as_deref().and_then(parse_mode) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&temp, fs::Permissions::from_mode(mode)).map_err(write_error)?;
+    }
+    fs::rename(&temp, target).map_err(write_error)
+}
+
+// 八進制的文件權限，如 "0600"
+pub fn parse_mode(mode: &str) -> Option<u32> {
+    u32::from_str_radix(mode, 8).ok().filter(|mode| *mode <= 0o7777)
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// rest 是 template 的後綴，offset 是佔位符在 rest 中的位置
+fn line_of(template: &str, rest: &str, offset: usize) -> usize {
+    let position = template.len() - rest.len() + offset;
+    template[..position].matches('\n').count() + 1
+}
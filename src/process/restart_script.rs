@@ -0,0 +1,97 @@
+// 每個進程可選的 Rhai 腳本，在安排重啟之前根據退出情況決定是否重啟以及延遲多久
+use chrono::{Datelike, Local, Timelike};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+use super::{ManagedProcess, RunRecord, Termination};
+use crate::signal::names::signal_name;
+
+// 單次執行最多的操作數，防止腳本中的死循環卡住監控循環
+const MAX_OPERATIONS: u64 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestartDecision {
+    // 按 restart_delay 照常重啟
+    Default,
+    Skip,
+    Delay(u64),
+}
+
+pub struct RestartScript {
+    engine: Engine,
+    ast: AST,
+}
+
+impl RestartScript {
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        Ok(Self { engine, ast })
+    }
+
+    // 腳本的返回值：false 表示不重啟，整數表示延遲的秒數，true 或沒有返回值表示照常重啟
+    pub fn decide(&self, process: &ManagedProcess) -> Result<RestartDecision, String> {
+        let mut scope = Self::scope(process);
+        let result: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| e.to_string())?;
+
+        if result.is_unit() {
+            return Ok(RestartDecision::Default);
+        }
+        if let Ok(restart) = result.as_bool() {
+            return Ok(if restart { RestartDecision::Default } else { RestartDecision::Skip });
+        }
+        match result.as_int() {
+            Ok(delay) if delay >= 0 => Ok(RestartDecision::Delay(delay as u64)),
+            Ok(delay) => Err(format!("restart delay cannot be negative: {}", delay)),
+            Err(kind) => Err(format!("expected a bool or an integer, got {}", kind)),
+        }
+    }
+
+    // 剛結束的運行是 runs 的最後一項
+    fn scope(process: &ManagedProcess) -> Scope<'static> {
+        let now = Local::now();
+        let last = process.run_history.back();
+        let runs: Array = process.run_history.iter().map(|run| Dynamic::from_map(Self::run(run))).collect();
+
+        let mut scope = Scope::new();
+        scope.push("exit_code", Self::exit_code(last));
+        scope.push("signal", Self::signal(last));
+        scope.push("restart_count", process.restart_count as i64);
+        scope.push("uptime", last.map_or(0.0, |run| run.duration.as_secs_f64()));
+        scope.push("runs", runs);
+        scope.push("hour", now.hour() as i64);
+        scope.push("minute", now.minute() as i64);
+        // 與 cron 一致，0 表示星期日
+        scope.push("weekday", now.weekday().num_days_from_sunday() as i64);
+        scope
+    }
+
+    fn run(run: &RunRecord) -> Map {
+        let mut map = Map::new();
+        map.insert("exit_code".into(), Self::exit_code(Some(run)));
+        map.insert("signal".into(), Self::signal(Some(run)));
+        map.insert("duration".into(), Dynamic::from_float(run.duration.as_secs_f64()));
+        map.insert(
+            "success".into(),
+            Dynamic::from_bool(run.termination == Some(Termination::Exited(0))),
+        );
+        map
+    }
+
+    fn exit_code(run: Option<&RunRecord>) -> Dynamic {
+        match run.and_then(|run| run.exit_code) {
+            Some(code) => Dynamic::from_int(code.into()),
+            None => Dynamic::UNIT,
+        }
+    }
+
+    fn signal(run: Option<&RunRecord>) -> Dynamic {
+        match run.and_then(|run| run.termination) {
+            Some(Termination::Signaled { signal, .. }) => Dynamic::from(signal_name(signal)),
+            _ => Dynamic::UNIT,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{JanusError, Result};
+
+// 排程時間過去超過此時長才被視為錯過，而不是監控循環的正常延遲
+pub const MISSED_RUN_GRACE: Duration = Duration::from_secs(30);
+
+// cron 表達式及其時區，未指定時區時按本地時間計算
+#[derive(Clone, Debug)]
+pub struct CronSchedule {
+    cron: Cron,
+    expression: String,
+    timezone: Option<Tz>,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str, timezone: Option<&str>) -> std::result::Result<Self, String> {
+        let cron = Cron::new(expression)
+            .parse()
+            .map_err(|e| format!("invalid cron expression '{}': {}", expression, e))?;
+        let timezone = timezone
+            .map(|name| name.parse::<Tz>().map_err(|_| format!("unknown time zone '{}'", name)))
+            .transpose()?;
+
+        Ok(Self {
+            cron,
+            expression: expression.to_string(),
+            timezone,
+        })
+    }
+
+    // time 之後（不含）的下一次排程時間
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.timezone {
+            Some(tz) => self
+                .cron
+                .find_next_occurrence(&time.with_timezone(&tz), false)
+                .ok()
+                .map(|next| next.with_timezone(&Utc)),
+            None => self
+                .cron
+                .find_next_occurrence(&time.with_timezone(&Local), false)
+                .ok()
+                .map(|next| next.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.timezone {
+            Some(tz) => write!(f, "{} ({})", self.expression, tz),
+            None => write!(f, "{}", self.expression),
+        }
+    }
+}
+
+// 0 到 max 之間的隨機時長；RandomState 每次創建時使用新的隨機種子，相同配置的多個實例也會得到不同的延遲
+pub fn random_delay(max: Duration) -> Duration {
+    let max_millis = max.as_millis() as u64;
+    if max_millis == 0 {
+        return Duration::ZERO;
+    }
+
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % (max_millis + 1))
+}
+
+// 記錄每個進程最近一次已處理的排程時間，janus 重啟後據此判斷期間是否錯過了運行
+pub struct ScheduleState {
+    path: PathBuf,
+}
+
+impl ScheduleState {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn last_run(&self, name: &str) -> Option<DateTime<Utc>> {
+        self.load().remove(name)
+    }
+
+    pub fn record(&self, name: &str, scheduled_at: DateTime<Utc>) -> Result<()> {
+        let mut state = self.load();
+        state.insert(name.to_string(), scheduled_at);
+
+        let content: HashMap<&String, String> = state
+            .iter()
+            .map(|(name, time)| (name, time.to_rfc3339()))
+            .collect();
+        let content = serde_json::to_string_pretty(&content)
+            .map_err(|e| JanusError::Process(format!("Failed to serialize schedule state: {}", e)))?;
+
+        // 先寫臨時文件再重命名，避免中途退出留下不完整的文件
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+
+    // 文件不存在或無法解析時視為沒有記錄
+    fn load(&self) -> HashMap<String, DateTime<Utc>> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return HashMap::new();
+        };
+        let state: HashMap<String, String> = serde_json::from_str(&content).unwrap_or_default();
+
+        state
+            .into_iter()
+            .filter_map(|(name, time)| {
+                DateTime::parse_from_rfc3339(&time)
+                    .ok()
+                    .map(|time| (name, time.with_timezone(&Utc)))
+            })
+            .collect()
+    }
+}
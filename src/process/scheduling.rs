@@ -0,0 +1,67 @@
+// 子進程的 CPU 調度設置，在 fork 之後、exec 之前於子進程中應用
+#[cfg(target_os = "linux")]
+use std::io;
+
+use crate::config::SchedPolicy;
+
+// 實時調度策略的優先級範圍
+pub const MIN_RT_PRIORITY: u8 = 1;
+pub const MAX_RT_PRIORITY: u8 = 99;
+
+// cpu_set_t 能表示的 CPU 數量（CPU_SETSIZE）
+pub const MAX_CPUS: usize = 1024;
+
+// 在 fork 之前構建 CPU 集合，pre_exec 中只做系統調用，不分配內存
+#[cfg(target_os = "linux")]
+pub fn cpu_set(cpus: &[usize]) -> libc::cpu_set_t {
+    // SAFETY: cpu_set_t 是純位圖，全零表示空集合
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus.iter().filter(|cpu| **cpu < MAX_CPUS) {
+        // SAFETY: cpu 小於 CPU_SETSIZE，不會越界
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    set
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_cpu_affinity(set: &libc::cpu_set_t) -> io::Result<()> {
+    // SAFETY: pid 0 表示調用線程，set 指向有效的 cpu_set_t
+    let result = unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), set) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// 檢查調度策略和優先級的組合是否有效
+pub fn validate_policy(policy: Option<SchedPolicy>, priority: Option<u8>) -> Result<(), String> {
+    match (policy, priority) {
+        (Some(SchedPolicy::Fifo | SchedPolicy::Rr), Some(priority))
+            if (MIN_RT_PRIORITY..=MAX_RT_PRIORITY).contains(&priority) => Ok(()),
+        (Some(SchedPolicy::Fifo | SchedPolicy::Rr), _) => Err(format!(
+            "sched_priority must be between {} and {} for real-time policies",
+            MIN_RT_PRIORITY, MAX_RT_PRIORITY
+        )),
+        (_, None | Some(0)) => Ok(()),
+        (_, Some(_)) => Err("sched_priority is only valid with the fifo and rr policies".to_string()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_policy(policy: SchedPolicy, priority: u8) -> io::Result<()> {
+    let policy = match policy {
+        SchedPolicy::Other => libc::SCHED_OTHER,
+        SchedPolicy::Batch => libc::SCHED_BATCH,
+        SchedPolicy::Idle => libc::SCHED_IDLE,
+        SchedPolicy::Fifo => libc::SCHED_FIFO,
+        SchedPolicy::Rr => libc::SCHED_RR,
+    };
+    let param = libc::sched_param { sched_priority: priority.into() };
+
+    // SAFETY: pid 0 表示調用進程，param 指向有效的 sched_param
+    let result = unsafe { libc::sched_setscheduler(0, policy, &param) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
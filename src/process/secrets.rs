@@ -0,0 +1,244 @@
+// 環境變量中的密鑰引用，例如 env.DB_PASSWORD = "vault:secret/data/app#password"：
+// 每次啟動進程時向對應的提供者讀取，輪換後的密鑰在重啟後生效。讀取失敗時使用上一次成功讀取的值
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::timeout;
+
+use crate::logging::handler::LogHandler;
+use crate::logging::LogType;
+
+use super::http::{self, Endpoint};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// 錯誤直接寫入進程的日誌和啟動失敗事件
+pub type Result<T> = std::result::Result<T, String>;
+
+// 一個路徑下的字段名和值
+pub type Fields = HashMap<String, String>;
+
+// 密鑰來源，例如 Vault；一次讀取一個路徑下的全部字段
+pub trait SecretProvider: Send + Sync {
+    // 引用的前綴，不含冒號
+    fn scheme(&self) -> &'static str;
+    fn fetch<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Fields>>;
+}
+
+// 引用的格式為 "<scheme>:<path>#<field>"
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecretRef {
+    pub scheme: String,
+    pub path: String,
+    pub field: String,
+}
+
+impl SecretRef {
+    // 以已知前綴開頭的值才是引用；前綴正確但格式錯誤時返回錯誤
+    pub fn parse(value: &str) -> Option<Result<Self>> {
+        let (scheme, rest) = value.split_once(':')?;
+        if !SCHEMES.contains(&scheme) {
+            return None;
+        }
+        Some(match rest.rsplit_once('#') {
+            Some((path, field)) if !path.trim_matches('/').is_empty() && !field.is_empty() => Ok(Self {
+                scheme: scheme.to_string(),
+                path: path.trim_matches('/').to_string(),
+                field: field.to_string(),
+            }),
+            _ => Err(format!("expected {}:<path>#<field>, got '{}'", scheme, value)),
+        })
+    }
+}
+
+// 內置提供者的前綴
+pub const SCHEMES: &[&str] = &[VaultProvider::SCHEME];
+
+#[derive(Clone)]
+pub struct SecretResolver {
+    providers: Arc<Vec<Box<dyn SecretProvider>>>,
+    // 每個路徑上一次成功讀取的字段
+    cache: Arc<Mutex<HashMap<(String, String), Fields>>>,
+}
+
+impl Default for SecretResolver {
+    // Vault 的地址和令牌按 Vault CLI 的慣例從 VAULT_ADDR、VAULT_TOKEN 等環境變量讀取
+    fn default() -> Self {
+        Self::new(vec![Box::new(VaultProvider::from_env())])
+    }
+}
+
+impl SecretResolver {
+    pub fn new(providers: Vec<Box<dyn SecretProvider>>) -> Self {
+        Self {
+            providers: Arc::new(providers),
+            cache: Arc::default(),
+        }
+    }
+
+    // 替換 env 中的全部引用；同一路徑在一次啟動中只讀取一次
+    pub async fn resolve(
+        &self,
+        name: &str,
+        env: &HashMap<String, String>,
+        log_handler: &LogHandler,
+    ) -> Result<HashMap<String, String>> {
+        let mut resolved = env.clone();
+        let mut fetched: HashMap<(String, String), Fields> = HashMap::new();
+        for (key, value) in env {
+            let Some(reference) = SecretRef::parse(value) else {
+                continue;
+            };
+            let reference = reference.map_err(|e| format!("env {}: {}", key, e))?;
+            let location = (reference.scheme.clone(), reference.path.clone());
+            if !fetched.contains_key(&location) {
+                let fields = self.fetch(name, &reference, log_handler).await?;
+                fetched.insert(location.clone(), fields);
+            }
+            let secret = fetched[&location].get(&reference.field).ok_or_else(|| {
+                format!(
+                    "env {}: field {} not found in {}:{}",
+                    key, reference.field, reference.scheme, reference.path
+                )
+            })?;
+            resolved.insert(key.clone(), secret.clone());
+        }
+        Ok(resolved)
+    }
+
+    async fn fetch(
+        &self,
+        name: &str,
+        reference: &SecretRef,
+        log_handler: &LogHandler,
+    ) -> Result<Fields> {
+        let provider = self
+            .providers
+            .iter()
+            .find(|provider| provider.scheme() == reference.scheme)
+            .ok_or_else(|| format!("No secret provider for {}:", reference.scheme))?;
+        let location = (reference.scheme.clone(), reference.path.clone());
+        match provider.fetch(&reference.path).await {
+            Ok(fields) => {
+                self.cache.lock().unwrap().insert(location, fields.clone());
+                Ok(fields)
+            }
+            Err(e) => {
+                let cached = self.cache.lock().unwrap().get(&location).cloned();
+                let error = format!("Failed to read {}:{}: {}", reference.scheme, reference.path, e);
+                match cached {
+                    Some(fields) => {
+                        log_handler.log(name, LogType::System, &format!("{}, using the cached value", error));
+                        Ok(fields)
+                    }
+                    None => Err(error),
+                }
+            }
+        }
+    }
+}
+
+// 讀取一次 Vault 的最長時間，超時視為讀取失敗
+const VAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// HashiCorp Vault 的 KV 引擎：v2 的路徑包含 data/，例如 secret/data/app；也支持 v1 的路徑
+pub struct VaultProvider {
+    address: String,
+    token: Option<String>,
+    namespace: Option<String>,
+    // PEM 格式的 CA 證書，https 時用於驗證 Vault 的證書
+    ca_cert: Option<String>,
+}
+
+impl VaultProvider {
+    pub const SCHEME: &'static str = "vault";
+
+    pub fn new(address: &str, token: Option<&str>) -> Self {
+        Self {
+            address: address.trim_end_matches('/').to_string(),
+            token: token.map(str::to_string),
+            namespace: None,
+            ca_cert: None,
+        }
+    }
+
+    // 令牌也可以放在 VAULT_TOKEN_FILE 指向的文件中；沒有 VAULT_CACERT 時使用系統的 CA 證書
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let token = var("VAULT_TOKEN").or_else(|| {
+            var("VAULT_TOKEN_FILE")
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .map(|token| token.trim().to_string())
+        });
+        Self {
+            namespace: var("VAULT_NAMESPACE"),
+            ca_cert: var("VAULT_CACERT"),
+            ..Self::new(&var("VAULT_ADDR").unwrap_or_else(|| "http://127.0.0.1:8200".to_string()), token.as_deref())
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<Fields> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or_else(|| "VAULT_TOKEN is not set".to_string())?;
+        let endpoint = Endpoint::parse(&self.address).ok_or_else(|| format!("Unsupported VAULT_ADDR {}", self.address))?;
+
+        let mut request = format!("GET /v1/{} HTTP/1.0\r\nHost: {}\r\nX-Vault-Token: {}\r\n", path, endpoint.authority, token);
+        if let Some(namespace) = &self.namespace {
+            request.push_str(&format!("X-Vault-Namespace: {}\r\n", namespace));
+        }
+        request.push_str("\r\n");
+
+        let response = endpoint.send(&request, self.ca_cert.as_deref()).await?;
+        parse_response(&response)
+    }
+}
+
+impl SecretProvider for VaultProvider {
+    fn scheme(&self) -> &'static str {
+        Self::SCHEME
+    }
+
+    fn fetch<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Fields>> {
+        Box::pin(async move {
+            timeout(VAULT_TIMEOUT, self.get(path))
+                .await
+                .map_err(|_| "Vault did not answer in time".to_string())?
+        })
+    }
+}
+
+// KV v2 的字段在 data.data 中，v1 直接在 data 中；非字符串的值按 JSON 文本傳給進程
+fn parse_response(response: &str) -> Result<Fields> {
+    let (status, body) = http::split_response(response);
+    let json: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+    if status != Some(200) {
+        let errors = json["errors"]
+            .as_array()
+            .map(|errors| errors.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("; "))
+            .filter(|errors| !errors.is_empty())
+            .unwrap_or_else(|| "no details".to_string());
+        let status = status.map_or_else(|| "invalid response".to_string(), |code| format!("HTTP {}", code));
+        return Err(format!("Vault returned {}: {}", status, errors));
+    }
+
+    let data = &json["data"];
+    let fields = if data["metadata"].is_object() { &data["data"] } else { data };
+    let fields = fields
+        .as_object()
+        .ok_or_else(|| "Vault response has no data".to_string())?;
+    Ok(fields
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect())
+}
@@ -0,0 +1,85 @@
+// 通過 shell 運行進程：command 原樣作為 shell 的命令行，args 按該 shell 的引號規則轉義後附加在後面，
+// 例如 Windows 上在 cmd 與 PowerShell 之間選擇，不必自己處理兩者不同的引號規則
+use tokio::process::Command;
+
+use crate::config::ShellKind;
+
+pub fn program(shell: ShellKind) -> &'static str {
+    match shell {
+        ShellKind::Sh => "sh",
+        ShellKind::Cmd => "cmd",
+        ShellKind::Powershell => "powershell",
+        ShellKind::Pwsh => "pwsh",
+    }
+}
+
+pub fn command_line(shell: ShellKind, command: &str, args: &[String]) -> String {
+    let mut line = command.to_string();
+    for arg in args {
+        line.push(' ');
+        line.push_str(&quote(shell, arg));
+    }
+    line
+}
+
+// 在 command 上加上運行 line 所需的參數，command 的程序應為 program(shell)
+pub fn add_args(command: &mut Command, shell: ShellKind, line: &str) {
+    match shell {
+        ShellKind::Sh => {
+            command.arg("-c").arg(line);
+        }
+        // /S 讓 cmd 只去掉整個命令行首尾的引號；cmd 自行解析命令行，不能按通常的規則再加一層引號
+        ShellKind::Cmd => {
+            #[cfg(windows)]
+            command.raw_arg("/D /S /C").raw_arg(format!("\"{}\"", line));
+            #[cfg(not(windows))]
+            command.args(["/D", "/S", "/C"]).arg(line);
+        }
+        ShellKind::Powershell | ShellKind::Pwsh => {
+            command.args(["-NoProfile", "-NonInteractive", "-Command"]).arg(line);
+        }
+    }
+}
+
+pub fn quote(shell: ShellKind, arg: &str) -> String {
+    match shell {
+        ShellKind::Sh if is_plain(arg, "_@%+=:,./-") => arg.to_string(),
+        ShellKind::Sh => format!("'{}'", arg.replace('\'', "'\\''")),
+        ShellKind::Cmd => quote_cmd(arg),
+        ShellKind::Powershell | ShellKind::Pwsh if is_plain(arg, "_+=:,./-\\") => arg.to_string(),
+        // 單引號字符串中只有單引號需要轉義 (寫兩次)，$ 和反引號都不會展開
+        ShellKind::Powershell | ShellKind::Pwsh => format!("'{}'", arg.replace('\'', "''")),
+    }
+}
+
+fn is_plain(arg: &str, allowed: &str) -> bool {
+    !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || allowed.contains(c))
+}
+
+// 按 Windows 程序解析命令行的規則加引號：引號寫兩次，緊接引號或結尾的反斜杠加倍；
+// 放在引號中的 & | < > ^ 不會被 cmd 解釋，但 %VAR% 仍會展開，cmd 沒有轉義它的方法
+fn quote_cmd(arg: &str) -> String {
+    if is_plain(arg, "_+=:,./-\\") {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2));
+                quoted.push_str("\"\"");
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
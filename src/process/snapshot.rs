@@ -0,0 +1,230 @@
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::config::manager::ConfigManager;
+use crate::error::{JanusError, Result};
+use crate::logging::writer::WriteStats;
+
+use super::accounting::ResourceUsage;
+use super::ManagedProcess;
+
+// 某一時刻的完整診斷快照，用於事後分析
+#[derive(Debug, Default, Serialize)]
+pub struct StatusSnapshot {
+    pub timestamp: String,
+    pub config_path: Option<String>,
+    pub config_sha256: Option<String>,
+    pub config_version: u64,
+    // 已完成的 upgrade --exec 次數
+    pub generation: u64,
+    pub config_reload_pending: bool,
+    // janus 寫入終端和日誌文件時的錯誤
+    pub log_output: WriteStats,
+    pub processes: Vec<ProcessSnapshot>,
+    // 發布快照時生效的配置，讀取快照的信號處理程序從中查找動作；不寫入快照文件
+    #[serde(skip)]
+    pub config: Option<Arc<ConfigManager>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSnapshot {
+    pub name: String,
+    // label 和 display_name 組成的顯示名稱，與 name 相同時為空
+    pub display_label: Option<String>,
+    pub status: String,
+    pub pid: Option<u32>,
+    pub uptime_secs: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub termination: Option<String>,
+    pub restart_count: u32,
+    pub restart_limit: Option<u32>,
+    pub last_start_duration_ms: Option<u128>,
+    pub last_stop_duration_ms: Option<u128>,
+    pub command: String,
+    pub args: Vec<String>,
+    pub working_dir: Option<String>,
+    pub env_count: usize,
+    pub auto_restart: bool,
+    pub restart_delay: u64,
+    pub restart_budget: Option<u32>,
+    pub restart_budget_window: u64,
+    pub every_secs: Option<u64>,
+    pub schedule: Option<String>,
+    // 以容器運行時的鏡像
+    pub image: Option<String>,
+    pub rss_kb: Option<u64>,
+    // 啟動時 env_dirs 命中和未命中緩存的次數
+    pub env_cache_hits: u64,
+    pub env_cache_misses: u64,
+    // 最近一次採樣時打開的文件描述符數量和 RLIMIT_NOFILE 的軟限制
+    pub open_fds: Option<u64>,
+    pub fd_limit: Option<u64>,
+    // janus ready 是否等待這個進程
+    pub critical: bool,
+    // 只對 notify 進程有值
+    pub ready: Option<bool>,
+    pub notify_status: Option<String>,
+    pub group: Option<String>,
+    // 維護模式剩餘的秒數
+    pub maintenance_secs: Option<u64>,
+    pub manual_stop: bool,
+    // stop-one --for 恢復前剩餘的秒數
+    pub resume_secs: Option<u64>,
+    // Stopping 狀態已持續的秒數，以及超過多少秒後改用 SIGKILL
+    pub stopping_secs: Option<u64>,
+    pub stop_timeout_secs: Option<u64>,
+    // 仍在啟動階段時已持續的秒數，見 ManagedProcess::starting_for
+    pub starting_secs: Option<u64>,
+    pub expected_start_secs: Option<u64>,
+    // 啟動推遲到 start_window 打開時為窗口的描述
+    pub waiting_for_window: Option<String>,
+    // 最近一次啟動時傳給進程的端口
+    pub port: Option<u16>,
+    pub recent_runs: Vec<RunSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSnapshot {
+    pub finished_at: String,
+    pub duration_ms: u128,
+    pub exit_code: Option<i32>,
+    pub termination: Option<String>,
+    pub output_path: Option<String>,
+    pub core_path: Option<String>,
+    pub crash_context: Vec<String>,
+    pub usage: Option<UsageSnapshot>,
+    pub required_kill: bool,
+}
+
+// 一次運行累計的資源用量
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSnapshot {
+    pub user_cpu_ms: u128,
+    pub system_cpu_ms: u128,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub voluntary_switches: u64,
+    pub involuntary_switches: u64,
+}
+
+impl From<&ResourceUsage> for UsageSnapshot {
+    fn from(usage: &ResourceUsage) -> Self {
+        Self {
+            user_cpu_ms: usage.user_time.as_millis(),
+            system_cpu_ms: usage.system_time.as_millis(),
+            read_bytes: usage.read_bytes,
+            write_bytes: usage.write_bytes,
+            voluntary_switches: usage.voluntary_switches,
+            involuntary_switches: usage.involuntary_switches,
+        }
+    }
+}
+
+impl ProcessSnapshot {
+    pub fn from_process(process: &ManagedProcess) -> Self {
+        let pid = process.pid();
+
+        Self {
+            name: process.name.clone(),
+            display_label: Some(process.display_label()).filter(|label| *label != process.name),
+            status: format!("{:?}", process.status),
+            pid,
+            uptime_secs: pid
+                .and(process.start_time)
+                .map(|start_time| start_time.elapsed().as_secs()),
+            exit_code: process.exit_code,
+            termination: process.termination.map(|termination| termination.to_string()),
+            restart_count: process.restart_count,
+            restart_limit: process.restart_limit,
+            last_start_duration_ms: process.last_start_duration.map(|d| d.as_millis()),
+            last_stop_duration_ms: process.last_stop_duration.map(|d| d.as_millis()),
+            command: process.command.clone(),
+            image: process.image.clone(),
+            args: process.args.clone(),
+            working_dir: process.working_dir.clone(),
+            env_count: process.env.len(),
+            auto_restart: process.auto_restart,
+            restart_delay: process.restart_delay,
+            restart_budget: process.restart_budget,
+            restart_budget_window: process.restart_budget_window,
+            every_secs: process.every.map(|every| every.as_secs()),
+            schedule: process.schedule.as_ref().map(|schedule| schedule.to_string()),
+            rss_kb: pid.and_then(read_rss_kb),
+            env_cache_hits: process.env_cache.hits,
+            env_cache_misses: process.env_cache.misses,
+            open_fds: process.fd_monitor.usage.filter(|_| pid.is_some()).map(|usage| usage.open),
+            fd_limit: process.fd_monitor.usage.filter(|_| pid.is_some()).and_then(|usage| usage.limit),
+            critical: process.critical,
+            ready: process.notify.then(|| process.notify_state.is_ready()),
+            notify_status: process.notify.then(|| process.notify_state.get().status).flatten(),
+            group: process.group.clone(),
+            maintenance_secs: process
+                .maintenance_until
+                .filter(|_| process.in_maintenance(Instant::now()))
+                .map(|until| until.saturating_duration_since(Instant::now()).as_secs()),
+            manual_stop: process.manual_stop,
+            resume_secs: process
+                .resume_at
+                .map(|resume_at| resume_at.saturating_duration_since(Instant::now()).as_secs()),
+            stopping_secs: process.stopping_since.map(|since| since.elapsed().as_secs()),
+            stop_timeout_secs: process.stop_timeout.map(|timeout| timeout.as_secs()),
+            starting_secs: process.starting_for().map(|uptime| uptime.as_secs()),
+            expected_start_secs: process.expected_start_duration.map(|expected| expected.as_secs()),
+            waiting_for_window: process
+                .start_window
+                .as_ref()
+                .filter(|_| process.waiting_for_window)
+                .map(|window| window.to_string()),
+            port: process.port,
+            recent_runs: process
+                .run_history
+                .iter()
+                .map(|run| RunSnapshot {
+                    finished_at: run.finished_at.to_rfc3339(),
+                    duration_ms: run.duration.as_millis(),
+                    exit_code: run.exit_code,
+                    termination: run.termination.map(|termination| termination.to_string()),
+                    output_path: run.output_path.as_ref().map(|path| path.display().to_string()),
+                    core_path: run.core_path.as_ref().map(|path| path.display().to_string()),
+                    crash_context: run.crash_context.clone(),
+                    usage: run.usage.as_ref().map(UsageSnapshot::from),
+                    required_kill: run.required_kill,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl StatusSnapshot {
+    // 寫入帶時間戳的 JSON 文件，返回文件路徑
+    pub fn write_to_dir(&self, dir: &Path) -> Result<PathBuf> {
+        let file_name = format!("janus-status-{}.json", Local::now().format("%Y%m%dT%H%M%S%.3f"));
+        let path = dir.join(file_name);
+
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            JanusError::Process(format!("Failed to serialize status snapshot: {}", e))
+        })?;
+        fs::write(&path, content)?;
+
+        Ok(path)
+    }
+}
+
+// 從 /proc 讀取常駐內存大小（僅 Linux）
+#[cfg(target_os = "linux")]
+fn read_rss_kb(pid: u32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_rss_kb(_pid: u32) -> Option<u64> {
+    None
+}
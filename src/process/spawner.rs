@@ -0,0 +1,78 @@
+// 子進程的啟動方式：ProcessManager 通過注入的 ProcessSpawner 啟動進程，之後通過返回的 ProcessHandle
+// 查詢、等待和終止它。默認的 TokioSpawner 直接運行命令；測試可以注入不運行任何程序的實現
+// (test-util 特性的 FakeSpawner)，以後也可以接入遠程執行器等其他後端
+use std::fmt::Debug;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::process::ExitStatus;
+use tokio::process::{Child, ChildStderr, ChildStdout, Command};
+
+pub type WaitFuture<'a> = Pin<Box<dyn Future<Output = io::Result<ExitStatus>> + Send + 'a>>;
+
+pub trait ProcessSpawner: Debug + Send + Sync {
+    // command 已設置好參數、環境變量、工作目錄和標準輸出輸入
+    fn spawn(&self, name: &str, command: &mut Command) -> io::Result<Box<dyn ProcessHandle>>;
+}
+
+// 已啟動的進程，接口與 tokio::process::Child 相同
+pub trait ProcessHandle: Debug + Send + Sync {
+    // 進程已被回收後返回 None
+    fn id(&self) -> Option<u32>;
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>>;
+    fn wait(&mut self) -> WaitFuture<'_>;
+    // 發送 SIGKILL (Windows 上終止進程)，不等待它退出
+    fn start_kill(&mut self) -> io::Result<()>;
+    // 沒有 pidfd 時用來發送信號
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> io::Result<()>;
+    fn take_stdout(&mut self) -> Option<ChildStdout>;
+    fn take_stderr(&mut self) -> Option<ChildStderr>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioSpawner;
+
+impl ProcessSpawner for TokioSpawner {
+    fn spawn(&self, _name: &str, command: &mut Command) -> io::Result<Box<dyn ProcessHandle>> {
+        Ok(Box::new(command.spawn()?))
+    }
+}
+
+impl ProcessHandle for Child {
+    fn id(&self) -> Option<u32> {
+        Child::id(self)
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        Child::try_wait(self)
+    }
+
+    fn wait(&mut self) -> WaitFuture<'_> {
+        Box::pin(Child::wait(self))
+    }
+
+    fn start_kill(&mut self) -> io::Result<()> {
+        Child::start_kill(self)
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> io::Result<()> {
+        let Some(pid) = Child::id(self) else {
+            return Ok(());
+        };
+        // SAFETY: kill 只接受整數參數，pid 屬於尚未回收的子進程
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.stdout.take()
+    }
+
+    fn take_stderr(&mut self) -> Option<ChildStderr> {
+        self.stderr.take()
+    }
+}
@@ -0,0 +1,139 @@
+// 自動啟動的時間窗口：not_before 和 not_after 可以是日期時間 (例如 2026-11-01 或 2026-11-01 06:00)，
+// 也可以是每天的時刻 (例如 18:00)；兩者都是時刻且 not_before 晚於 not_after 時窗口跨越午夜，
+// 例如 18:00 到 08:00 只在夜間啟動。janus start 和自動重啟在窗口外推遲到窗口打開，顯式的 start-one 不受限制
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bound {
+    At(NaiveDateTime),
+    Daily(NaiveTime),
+}
+
+impl Bound {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        for format in ["%H:%M", "%H:%M:%S"] {
+            if let Ok(time) = NaiveTime::parse_from_str(value, format) {
+                return Some(Bound::Daily(time));
+            }
+        }
+        for format in ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+            if let Ok(at) = NaiveDateTime::parse_from_str(value, format) {
+                return Some(Bound::At(at));
+            }
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(|date| Bound::At(date.and_time(NaiveTime::MIN)))
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = match self {
+            Bound::At(at) => at.time(),
+            Bound::Daily(time) => *time,
+        };
+        let time_format = if time.second() == 0 { "%H:%M" } else { "%H:%M:%S" };
+        match self {
+            Bound::At(at) => write!(f, "{} {}", at.format("%Y-%m-%d"), at.format(time_format)),
+            Bound::Daily(time) => write!(f, "{}", time.format(time_format)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StartWindow {
+    not_before: Option<Bound>,
+    not_after: Option<Bound>,
+    timezone: Option<Tz>,
+}
+
+impl StartWindow {
+    // 兩者都未設置時返回 None
+    pub fn parse(
+        not_before: Option<&str>,
+        not_after: Option<&str>,
+        timezone: Option<&str>,
+    ) -> std::result::Result<Option<Self>, String> {
+        if not_before.is_none() && not_after.is_none() {
+            return Ok(None);
+        }
+        let bound = |field: &str, value: Option<&str>| {
+            value
+                .map(|value| {
+                    Bound::parse(value).ok_or_else(|| {
+                        format!("invalid {} '{}', expected HH:MM, YYYY-MM-DD or YYYY-MM-DD HH:MM", field, value)
+                    })
+                })
+                .transpose()
+        };
+        let window = Self {
+            not_before: bound("not_before", not_before)?,
+            not_after: bound("not_after", not_after)?,
+            timezone: timezone
+                .map(|name| name.parse::<Tz>().map_err(|_| format!("unknown time zone '{}'", name)))
+                .transpose()?,
+        };
+        if let (Some(Bound::At(before)), Some(Bound::At(after))) = (window.not_before, window.not_after) {
+            if before >= after {
+                return Err(format!("not_before {} is not earlier than not_after {}", before, after));
+            }
+        }
+        Ok(Some(window))
+    }
+
+    // 按 timezone (未指定時為本地時間) 判斷 now 是否在窗口內
+    pub fn allows(&self, now: DateTime<Utc>) -> bool {
+        let local = self.local(now);
+        if let (Some(Bound::Daily(before)), Some(Bound::Daily(after))) = (self.not_before, self.not_after) {
+            let time = local.time();
+            return if before <= after {
+                time >= before && time < after
+            } else {
+                time >= before || time < after
+            };
+        }
+
+        let after_start = match self.not_before {
+            Some(Bound::At(before)) => local >= before,
+            Some(Bound::Daily(before)) => local.time() >= before,
+            None => true,
+        };
+        let before_end = match self.not_after {
+            Some(Bound::At(after)) => local < after,
+            Some(Bound::Daily(after)) => local.time() < after,
+            None => true,
+        };
+        after_start && before_end
+    }
+
+    // not_after 是已經過去的日期時間時，窗口不會再打開
+    pub fn has_ended(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.not_after, Some(Bound::At(after)) if self.local(now) >= after)
+    }
+
+    fn local(&self, now: DateTime<Utc>) -> NaiveDateTime {
+        match self.timezone {
+            Some(tz) => now.with_timezone(&tz).naive_local(),
+            None => now.with_timezone(&Local).naive_local(),
+        }
+    }
+}
+
+impl fmt::Display for StartWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.not_before, self.not_after) {
+            (Some(before), Some(after)) => write!(f, "{} to {}", before, after)?,
+            (Some(before), None) => write!(f, "from {}", before)?,
+            (None, Some(after)) => write!(f, "until {}", after)?,
+            (None, None) => write!(f, "always")?,
+        }
+        if let Some(tz) = self.timezone {
+            write!(f, " ({})", tz)?;
+        }
+        Ok(())
+    }
+}
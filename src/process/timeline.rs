@@ -0,0 +1,136 @@
+// 進程狀態變化的時間線：每個進程保留最近 TIMELINE_LIMIT 個生命週期事件，供儀表板和 janus timeline
+// 查看進程在過去幾小時內何時、多頻繁地崩潰和重啟。設置 global.timeline_file 時同時以 JSON Lines 追加到文件，
+// janus 重新啟動後從中恢復；追加的行數超過保留的條目數時重寫文件，文件大小保持有界
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use super::events::ProcessEvent;
+
+// 每個進程保留的事件數量
+pub const TIMELINE_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    // RFC 3339 格式
+    pub timestamp: String,
+    pub process: String,
+    // 與插件收到的事件名稱相同，例如 started、exited、gave_up
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+impl TimelineEntry {
+    pub fn from_event(event: &ProcessEvent) -> Self {
+        Self {
+            timestamp: event.timestamp.to_rfc3339(),
+            process: event.process.clone(),
+            event: event.kind.as_str().to_string(),
+            pid: event.pid,
+            exit_code: event.exit_code,
+            detail: event.detail.clone(),
+        }
+    }
+
+    pub fn time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Timeline {
+    entries: HashMap<String, VecDeque<TimelineEntry>>,
+    file: Option<PathBuf>,
+    // 上次重寫之後追加到文件的行數
+    appended: usize,
+}
+
+impl Timeline {
+    // 從文件恢復，文件不存在時從空的時間線開始；無法解析的行 (例如寫了一半的最後一行) 被跳過
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut timeline = Self {
+            file: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(timeline),
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(file).lines() {
+            if let Ok(entry) = serde_json::from_str::<TimelineEntry>(&line?) {
+                timeline.push(entry);
+            }
+        }
+        Ok(timeline)
+    }
+
+    pub fn set_file(&mut self, path: Option<PathBuf>) {
+        if self.file != path {
+            self.file = path;
+            self.appended = 0;
+        }
+    }
+
+    // 記錄一個事件；寫入文件失敗時內存中的時間線仍然更新
+    pub fn record(&mut self, entry: TimelineEntry) -> io::Result<()> {
+        self.push(entry.clone());
+        let Some(path) = self.file.clone() else {
+            return Ok(());
+        };
+
+        if self.appended >= self.len() {
+            self.appended = 0;
+            return self.rewrite(&path);
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry).map_err(io::Error::other)?)?;
+        self.appended += 1;
+        Ok(())
+    }
+
+    // 某個進程的事件，從舊到新
+    pub fn entries(&self, process: &str) -> Vec<TimelineEntry> {
+        self.entries
+            .get(process)
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push(&mut self, entry: TimelineEntry) {
+        let entries = self.entries.entry(entry.process.clone()).or_default();
+        if entries.len() >= TIMELINE_LIMIT {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    fn len(&self) -> usize {
+        self.entries.values().map(VecDeque::len).sum()
+    }
+
+    // 只寫入保留的條目，先寫臨時文件再改名
+    fn rewrite(&self, path: &Path) -> io::Result<()> {
+        let mut entries: Vec<&TimelineEntry> = self.entries.values().flatten().collect();
+        entries.sort_by_key(|entry| entry.time());
+        let mut contents = String::new();
+        for entry in entries {
+            contents.push_str(&serde_json::to_string(entry).map_err(io::Error::other)?);
+            contents.push('\n');
+        }
+
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, contents)?;
+        fs::rename(&temp, path)
+    }
+}
@@ -0,0 +1,121 @@
+// 通過掃描 /proc 中每個進程的 ppid 重建進程樹，用於查看受管進程派生的子孫進程
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct ProcessNode {
+    pub pid: u32,
+    pub command: String,
+    // 常駐內存，單位為字節
+    pub rss: u64,
+    pub children: Vec<ProcessNode>,
+}
+
+impl ProcessNode {
+    // 包括自身在內的進程數
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(ProcessNode::count).sum::<usize>()
+    }
+
+    pub fn total_rss(&self) -> u64 {
+        self.rss + self.children.iter().map(ProcessNode::total_rss).sum::<u64>()
+    }
+}
+
+struct Entry {
+    ppid: u32,
+    command: String,
+    rss: u64,
+}
+
+// 某一時刻所有進程的快照；掃描一次後可以為多個受管進程構建子樹
+pub struct ProcessTable {
+    entries: HashMap<u32, Entry>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTable {
+    // 沒有 /proc 的平台上返回 None
+    #[cfg(target_os = "linux")]
+    pub fn scan() -> Option<Self> {
+        // SAFETY: sysconf 只讀取系統配置
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(0) as u64;
+        let mut entries = HashMap::new();
+
+        // 掃描期間退出的進程直接跳過
+        for dir in std::fs::read_dir("/proc").ok()?.flatten() {
+            let Some(pid) = dir.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+                continue;
+            };
+            let Ok(stat) = std::fs::read_to_string(dir.path().join("stat")) else {
+                continue;
+            };
+            let Some((comm, ppid, rss_pages)) = parse_stat(&stat) else {
+                continue;
+            };
+
+            // 內核線程和僵屍進程沒有命令行，顯示為 [comm]
+            let command = std::fs::read(dir.path().join("cmdline"))
+                .ok()
+                .map(|cmdline| {
+                    cmdline
+                        .split(|&byte| byte == 0)
+                        .filter(|arg| !arg.is_empty())
+                        .map(String::from_utf8_lossy)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .filter(|command| !command.is_empty())
+                .unwrap_or_else(|| format!("[{}]", comm));
+
+            entries.insert(pid, Entry { ppid, command, rss: rss_pages * page_size });
+        }
+
+        Some(Self::from_entries(entries))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn scan() -> Option<Self> {
+        None
+    }
+
+    fn from_entries(entries: HashMap<u32, Entry>) -> Self {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (&pid, entry) in &entries {
+            children.entry(entry.ppid).or_default().push(pid);
+        }
+        for pids in children.values_mut() {
+            pids.sort_unstable();
+        }
+        Self { entries, children }
+    }
+
+    // 以 pid 為根的進程樹；進程已不存在時返回 None
+    pub fn tree(&self, pid: u32) -> Option<ProcessNode> {
+        let entry = self.entries.get(&pid)?;
+        let children = self
+            .children
+            .get(&pid)
+            .map(|pids| pids.iter().filter_map(|&child| self.tree(child)).collect())
+            .unwrap_or_default();
+
+        Some(ProcessNode {
+            pid,
+            command: entry.command.clone(),
+            rss: entry.rss,
+            children,
+        })
+    }
+}
+
+// /proc/<pid>/stat 的格式為 "pid (comm) state ppid ..."，comm 本身可能包含空格和括號，
+// 因此以最後一個 ')' 分隔；rss 是第 24 個字段，單位為頁
+#[cfg(target_os = "linux")]
+fn parse_stat(stat: &str) -> Option<(&str, u32, u64)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let comm = stat.get(open + 1..close)?;
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+    let ppid = fields.get(1)?.parse().ok()?;
+    let rss_pages = fields.get(21)?.parse::<i64>().ok()?.max(0) as u64;
+    Some((comm, ppid, rss_pages))
+}
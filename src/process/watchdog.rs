@@ -0,0 +1,98 @@
+// 看門狗：進程需要定期觸碰文件、向 janus 提供的 socket 發送數據報或輸出特定的日誌行，
+// 心跳停止超過 watchdog_timeout 時認為進程卡死並重啟
+use regex::Regex;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+// 傳給進程的環境變量：數據報 socket 的路徑和超時秒數
+pub const SOCKET_ENV: &str = "JANUS_WATCHDOG_SOCKET";
+pub const TIMEOUT_ENV: &str = "JANUS_WATCHDOG_TIMEOUT";
+
+// 最近一次心跳的時間，在讀取輸出和 socket 的任務之間共享
+#[derive(Clone, Debug)]
+pub struct Heartbeat(Arc<Mutex<Instant>>);
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    pub fn beat(&self) {
+        if let Ok(mut last) = self.0.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.0.lock().map(|last| last.elapsed()).unwrap_or_default()
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 輸出中匹配 watchdog_pattern 的行記為一次心跳
+#[derive(Clone, Debug)]
+pub struct LineHeartbeat {
+    pub pattern: Regex,
+    pub heartbeat: Heartbeat,
+}
+
+impl LineHeartbeat {
+    pub fn check(&self, line: &str) {
+        if self.pattern.is_match(line) {
+            self.heartbeat.beat();
+        }
+    }
+}
+
+// 心跳文件最近一次修改距今的時間；文件不存在時返回 None
+pub fn file_age(path: &Path) -> Option<Duration> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(SystemTime::now().duration_since(modified).unwrap_or_default())
+}
+
+// janus 為進程創建的數據報 socket，收到任何數據報都記為一次心跳；釋放時刪除 socket 文件
+pub struct WatchdogSocket {
+    path: PathBuf,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl WatchdogSocket {
+    #[cfg(unix)]
+    pub fn bind(process_name: &str, heartbeat: Heartbeat) -> io::Result<Self> {
+        let file_name = format!("janus-{}-{}.sock", std::process::id(), process_name.replace('/', ":"));
+        let path = std::env::temp_dir().join(file_name);
+        let _ = std::fs::remove_file(&path);
+        let socket = tokio::net::UnixDatagram::bind(&path)?;
+
+        let task = tokio::spawn(async move {
+            let mut buffer = [0u8; 64];
+            while socket.recv(&mut buffer).await.is_ok() {
+                heartbeat.beat();
+            }
+        });
+        Ok(Self { path, task })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(_process_name: &str, _heartbeat: Heartbeat) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "watchdog sockets are only supported on unix"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for WatchdogSocket {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
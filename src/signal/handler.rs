@@ -1,10 +1,28 @@
 #[cfg(unix)]
 use tokio::signal::unix::{signal, SignalKind};
+#[cfg(unix)]
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+#[cfg(unix)]
+use crate::cli::status_reporter::StatusReporter;
+#[cfg(unix)]
+use crate::config::{GlobalConfig, SighupAction, SignalAction};
 use crate::error::Result;
+use crate::msg;
 use crate::process::manager::ProcessManager;
+use crate::process::notify;
+
+// 從全局配置中取出某個用戶信號對應的動作
+#[cfg(unix)]
+type SignalActionSelector = fn(&GlobalConfig) -> Option<SignalAction>;
+
+// 按依賴順序停止所有進程的默認總秒數
+pub const DEFAULT_SHUTDOWN_GRACE: u64 = 10;
+// 整個關閉流程的默認期限
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
 
 pub struct SignalHandler {
     manager: Arc<Mutex<ProcessManager>>,
@@ -26,21 +44,20 @@ impl SignalHandler {
             tokio::spawn(async move {
                 tokio::select! {
                     _ = sigint.recv() => {
-                        println!("Received SIGINT, shutting down...");
+                        println!("{}", msg!("signal.shutting_down", signal = "SIGINT"));
                     }
                     _ = sigterm.recv() => {
-                        println!("Received SIGTERM, shutting down...");
+                        println!("{}", msg!("signal.shutting_down", signal = "SIGTERM"));
                     }
                 }
                 
-                // tokio::sync::Mutex 可以安全地在異步上下文中使用
-                let mut manager_guard = manager.lock().await;
-                if let Err(e) = manager_guard.stop_all().await {
-                    println!("Error during shutdown: {}", e);
-                }
-                
-                std::process::exit(0);
+                shutdown(manager).await;
             });
+            
+            self.register_sighup()?;
+            self.register_user_signals()?;
+            #[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
+            self.register_siginfo()?;
         }
         
         // Windows 平台簡化處理
@@ -51,20 +68,166 @@ impl SignalHandler {
             // 在 Windows 上使用 tokio 的 ctrl_c 處理程序
             tokio::spawn(async move {
                 let _ = tokio::signal::ctrl_c().await;
-                println!("Received Ctrl+C, shutting down...");
-                
-                // tokio::sync::Mutex 可以安全地在異步上下文中使用
+                println!("{}", msg!("signal.shutting_down", signal = "Ctrl+C"));
+                shutdown(manager).await;
+            });
+            
+            println!("{}", msg!("signal.ctrl_c_registered"));
+        }
+        
+        Ok(())
+    }
+    
+    // SIGHUP：按照配置重新加載配置文件，或轉發給所有子進程
+    #[cfg(unix)]
+    fn register_sighup(&self) -> Result<()> {
+        let mut sighup = signal(SignalKind::hangup())?;
+        let manager = self.manager.clone();
+        
+        tokio::spawn(async move {
+            while sighup.recv().await.is_some() {
                 let mut manager_guard = manager.lock().await;
-                if let Err(e) = manager_guard.stop_all().await {
-                    println!("Error during shutdown: {}", e);
-                }
+                let action = manager_guard
+                    .get_config_manager()
+                    .and_then(|config_manager| config_manager.get_global_config().sighup_action)
+                    .unwrap_or_default();
                 
-                std::process::exit(0);
-            });
+                match action {
+                    SighupAction::Reload => {
+                        println!("{}", msg!("signal.reloading"));
+                        if let Err(e) = manager_guard.reload().await {
+                            println!("{}", msg!("signal.reload_failed", error = e));
+                        }
+                    }
+                    SighupAction::Forward => {
+                        println!("{}", msg!("signal.forwarding"));
+                        manager_guard.signal_all(libc::SIGHUP);
+                    }
+                }
+            }
+        });
+        
+        Ok(())
+    }
+    
+    // SIGINFO (macOS 和 BSD 終端中的 Ctrl+T)：在終端打印所有進程的狀態
+    #[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly"))]
+    fn register_siginfo(&self) -> Result<()> {
+        let mut siginfo = signal(SignalKind::info())?;
+        let manager = self.manager.clone();
+        
+        tokio::spawn(async move {
+            // 從監控循環發布的快照輸出狀態，耗時的操作持有管理器的鎖時也能立即響應
+            let status = manager.lock().await.subscribe_status();
+            while siginfo.recv().await.is_some() {
+                let snapshot = status.borrow().clone();
+                println!("{}", msg!("signal.dumping_status", signal = "SIGINFO"));
+                if let Err(e) = StatusReporter::report_snapshot(&snapshot) {
+                    println!("{}", msg!("signal.dump_failed", error = e));
+                }
+            }
+        });
+        
+        Ok(())
+    }
+    
+    // SIGUSR1/SIGUSR2：執行配置中指定的動作，未配置時忽略
+    #[cfg(unix)]
+    fn register_user_signals(&self) -> Result<()> {
+        let user_signals: [(SignalKind, &str, SignalActionSelector); 2] = [
+            (SignalKind::user_defined1(), "SIGUSR1", |global| global.sigusr1_action.clone()),
+            (SignalKind::user_defined2(), "SIGUSR2", |global| global.sigusr2_action.clone()),
+        ];
+        
+        for (kind, signal_name, get_action) in user_signals {
+            let mut stream = signal(kind)?;
+            let manager = self.manager.clone();
             
-            println!("Ctrl+C handler registered");
+            tokio::spawn(async move {
+                // 轉儲狀態只讀取最近發布的快照，不等待管理器的鎖；只有重啟進程時才獲取鎖
+                let status = manager.lock().await.subscribe_status();
+                while stream.recv().await.is_some() {
+                    let snapshot = status.borrow().clone();
+                    let action = snapshot
+                        .config
+                        .as_ref()
+                        .and_then(|config_manager| get_action(config_manager.get_global_config()));
+                    
+                    match action {
+                        Some(SignalAction::DumpStatus) => {
+                            println!("{}", msg!("signal.dumping_status", signal = signal_name));
+                            if let Err(e) = StatusReporter::report_snapshot(&snapshot) {
+                                println!("{}", msg!("signal.dump_failed", error = e));
+                            }
+                        }
+                        Some(SignalAction::DumpFile) => {
+                            let dump_dir = snapshot
+                                .config
+                                .as_ref()
+                                .and_then(|config_manager| config_manager.get_global_config().dump_dir.clone())
+                                .map(PathBuf::from)
+                                .unwrap_or_else(std::env::temp_dir);
+                            
+                            match snapshot.write_to_dir(&dump_dir) {
+                                Ok(path) => println!("{}", msg!("signal.snapshot_written", signal = signal_name, path = path.display())),
+                                Err(e) => println!("{}", msg!("signal.snapshot_failed", error = e)),
+                            }
+                        }
+                        Some(SignalAction::Restart(names)) => {
+                            println!("{}", msg!("signal.restarting", signal = signal_name, names = names.join(", ")));
+                            let mut manager_guard = manager.lock().await;
+                            for name in &names {
+                                if let Err(e) = manager_guard.restart_process(name).await {
+                                    println!("{}", msg!("signal.restart_failed", name = name, error = e));
+                                }
+                            }
+                        }
+                        None => {
+                            println!("{}", msg!("signal.no_action", signal = signal_name));
+                        }
+                    }
+                }
+            });
         }
         
         Ok(())
     }
 }
+
+// 關閉流程一直持有管理器的鎖，監控循環和遠程命令都會停在等待鎖上，不會再啟動或重啟進程；
+// 超過 shutdown_timeout 時殺死剩餘的進程並直接退出
+async fn shutdown(manager: Arc<Mutex<ProcessManager>>) {
+    // tokio::sync::Mutex 可以安全地在異步上下文中使用
+    let mut manager_guard = manager.lock().await;
+    let (grace, timeout, sd_notify) = manager_guard
+        .get_config_manager()
+        .map(|config_manager| {
+            let global = config_manager.get_global_config();
+            (global.shutdown_grace, global.shutdown_timeout, global.sd_notify == Some(true))
+        })
+        .unwrap_or_default();
+    if sd_notify {
+        if let Err(e) = notify::notify_systemd("STOPPING=1") {
+            println!("{}", msg!("systemd.notify_failed", error = e));
+        }
+    }
+    let grace = Duration::from_secs(grace.unwrap_or(DEFAULT_SHUTDOWN_GRACE));
+    let timeout = Duration::from_secs(timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT));
+    
+    let exit_code = match tokio::time::timeout(timeout, manager_guard.shutdown(grace)).await {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            println!("{}", msg!("signal.shutdown_failed", error = e));
+            1
+        }
+        Err(_) => {
+            println!("{}", msg!("signal.shutdown_timed_out", seconds = timeout.as_secs()));
+            #[cfg(unix)]
+            manager_guard.signal_all(libc::SIGKILL);
+            manager_guard.get_log_handler().flush();
+            1
+        }
+    };
+    
+    std::process::exit(exit_code);
+}
@@ -0,0 +1,51 @@
+// 常見信號的名稱，用於日誌和狀態中描述進程被哪個信號終止
+#[cfg(unix)]
+const SIGNAL_NAMES: &[(libc::c_int, &str)] = &[
+    (libc::SIGHUP, "SIGHUP"),
+    (libc::SIGINT, "SIGINT"),
+    (libc::SIGQUIT, "SIGQUIT"),
+    (libc::SIGILL, "SIGILL"),
+    (libc::SIGTRAP, "SIGTRAP"),
+    (libc::SIGABRT, "SIGABRT"),
+    (libc::SIGBUS, "SIGBUS"),
+    (libc::SIGFPE, "SIGFPE"),
+    (libc::SIGKILL, "SIGKILL"),
+    (libc::SIGUSR1, "SIGUSR1"),
+    (libc::SIGSEGV, "SIGSEGV"),
+    (libc::SIGUSR2, "SIGUSR2"),
+    (libc::SIGPIPE, "SIGPIPE"),
+    (libc::SIGALRM, "SIGALRM"),
+    (libc::SIGTERM, "SIGTERM"),
+    (libc::SIGXCPU, "SIGXCPU"),
+    (libc::SIGXFSZ, "SIGXFSZ"),
+    (libc::SIGSYS, "SIGSYS"),
+];
+
+// 未知的信號顯示為 "signal N"
+pub fn signal_name(signal: i32) -> String {
+    #[cfg(unix)]
+    if let Some((_, name)) = SIGNAL_NAMES.iter().find(|(number, _)| *number == signal) {
+        return name.to_string();
+    }
+    format!("signal {}", signal)
+}
+
+// 按名稱查找信號，接受 KILL、SIGKILL (不區分大小寫) 或信號編號；Windows 上只有 KILL 可用
+pub fn signal_number(name: &str) -> Option<i32> {
+    let upper = name.trim().to_ascii_uppercase();
+    let upper = upper.strip_prefix("SIG").unwrap_or(&upper);
+    #[cfg(unix)]
+    {
+        if let Ok(number) = upper.parse::<i32>() {
+            return (number > 0).then_some(number);
+        }
+        SIGNAL_NAMES
+            .iter()
+            .find(|(_, signal)| signal[3..] == *upper)
+            .map(|(number, _)| *number)
+    }
+    #[cfg(not(unix))]
+    {
+        matches!(upper, "KILL" | "9").then_some(9)
+    }
+}
@@ -0,0 +1,333 @@
+// 確定性測試的輔助工具，只在啟用 test-util 特性時編譯：MockClock 代替系統時鐘，
+// FakeSpawner 代替真實的子進程，EventCapture 收集管理器發出的生命週期事件。重啟延遲、重啟預算和
+// stop_timeout 等邏輯可以通過推進時鐘、讓假進程退出並調用 check_processes 逐步驗證，不必等待真實的時間
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::process::{ChildStderr, ChildStdout, Command};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tokio::sync::watch;
+
+use crate::process::clock::Clock;
+use crate::process::events::{EventKind, ProcessEvent};
+use crate::process::manager::ProcessManager;
+use crate::process::spawner::{ProcessHandle, ProcessSpawner, WaitFuture};
+
+// 假進程的 pid 從這裡開始，遠大於內核允許的最大 pid，發給它們的信號不會送達真實的進程
+const FAKE_PID_BASE: u32 = 1_000_000_000;
+
+// 只在調用 advance 時前進的時鐘；克隆共享同一個時間
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    start_utc: DateTime<Utc>,
+    offset: Arc<Mutex<Duration>>,
+    // jump 累計的牆上時間跳變
+    wall_offset: Arc<Mutex<TimeDelta>>,
+}
+
+impl MockClock {
+    // 從當前的系統時間開始
+    pub fn new() -> Self {
+        Self::starting_at(Utc::now())
+    }
+
+    // 牆上時間從 start_utc 開始，用於測試 cron 排程和啟動時間窗口
+    pub fn starting_at(start_utc: DateTime<Utc>) -> Self {
+        Self {
+            start: Instant::now(),
+            start_utc,
+            offset: Arc::default(),
+            wall_offset: Arc::default(),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.offset.lock().unwrap() += duration;
+    }
+
+    // 只移動牆上時間，單調時間不變，如同系統時間被調整；正值也相當於主機休眠後恢復
+    pub fn jump(&self, delta: TimeDelta) {
+        *self.wall_offset.lock().unwrap() += delta;
+    }
+
+    fn offset(&self) -> Duration {
+        *self.offset.lock().unwrap()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.offset()
+    }
+
+    fn utc(&self) -> DateTime<Utc> {
+        self.start_utc + self.offset() + *self.wall_offset.lock().unwrap()
+    }
+}
+
+// 從創建時起收集管理器發出的事件
+pub struct EventCapture {
+    receiver: broadcast::Receiver<ProcessEvent>,
+    events: Vec<ProcessEvent>,
+}
+
+impl EventCapture {
+    pub fn new(manager: &ProcessManager) -> Self {
+        Self {
+            receiver: manager.subscribe_events(),
+            events: Vec::new(),
+        }
+    }
+
+    // 到目前為止收到的所有事件，從舊到新
+    pub fn events(&mut self) -> &[ProcessEvent] {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => self.events.push(event),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        &self.events
+    }
+
+    // 某個進程的事件類型，從舊到新
+    pub fn kinds(&mut self, process: &str) -> Vec<EventKind> {
+        self.events()
+            .iter()
+            .filter(|event| event.process == process)
+            .map(|event| event.kind)
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.events();
+        self.events.clear();
+    }
+}
+
+// 啟動過的命令
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnedCommand {
+    pub name: String,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+// 不運行任何程序的進程後端：記錄每次啟動的命令，假進程一直運行，直到測試調用 exit、
+// 收到 SIGTERM、SIGINT、SIGQUIT 或被殺死；克隆共享同一個狀態
+#[derive(Debug, Clone, Default)]
+pub struct FakeSpawner {
+    state: Arc<Mutex<FakeState>>,
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    spawned: Vec<SpawnedCommand>,
+    // 每個進程最近一次啟動的假進程
+    latest: HashMap<String, Arc<FakeProcess>>,
+    fail_spawn: HashSet<String>,
+    ignore_sigterm: HashSet<String>,
+}
+
+impl FakeSpawner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawned(&self) -> Vec<SpawnedCommand> {
+        self.state.lock().unwrap().spawned.clone()
+    }
+
+    // 某個進程被啟動的次數
+    pub fn spawn_count(&self, name: &str) -> usize {
+        self.state.lock().unwrap().spawned.iter().filter(|command| command.name == name).count()
+    }
+
+    // 讓進程最近一次啟動的假進程以 code 退出
+    pub fn exit(&self, name: &str, code: i32) {
+        if let Some(process) = self.state.lock().unwrap().latest.get(name) {
+            process.finish(exit_status(code));
+        }
+    }
+
+    // 之後啟動該進程都失敗，如同可執行文件不存在
+    pub fn fail_spawn(&self, name: &str, fail: bool) {
+        let mut state = self.state.lock().unwrap();
+        if fail {
+            state.fail_spawn.insert(name.to_string());
+        } else {
+            state.fail_spawn.remove(name);
+        }
+    }
+
+    // 之後啟動的假進程不理會 SIGTERM，只能被殺死
+    pub fn ignore_sigterm(&self, name: &str) {
+        self.state.lock().unwrap().ignore_sigterm.insert(name.to_string());
+    }
+
+    // 進程最近一次啟動的假進程收到的信號
+    pub fn signals(&self, name: &str) -> Vec<i32> {
+        self.state
+            .lock()
+            .unwrap()
+            .latest
+            .get(name)
+            .map(|process| process.signals.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    // 最近一次啟動的假進程是否仍在運行
+    pub fn is_running(&self, name: &str) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .latest
+            .get(name)
+            .is_some_and(|process| process.status.borrow().is_none())
+    }
+}
+
+impl ProcessSpawner for FakeSpawner {
+    fn spawn(&self, name: &str, command: &mut Command) -> io::Result<Box<dyn ProcessHandle>> {
+        let mut state = self.state.lock().unwrap();
+        if state.fail_spawn.contains(name) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory (fake)"));
+        }
+
+        let std_command = command.as_std();
+        state.spawned.push(SpawnedCommand {
+            name: name.to_string(),
+            program: std_command.get_program().to_string_lossy().into_owned(),
+            args: std_command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+        });
+        let process = Arc::new(FakeProcess {
+            pid: FAKE_PID_BASE + state.spawned.len() as u32,
+            status: watch::channel(None).0,
+            signals: Mutex::default(),
+            ignore_sigterm: state.ignore_sigterm.contains(name),
+        });
+        state.latest.insert(name.to_string(), process.clone());
+        Ok(Box::new(FakeHandle { process, reaped: false }))
+    }
+}
+
+#[derive(Debug)]
+struct FakeProcess {
+    pid: u32,
+    // 退出之前為 None
+    status: watch::Sender<Option<ExitStatus>>,
+    signals: Mutex<Vec<i32>>,
+    ignore_sigterm: bool,
+}
+
+impl FakeProcess {
+    // 已經退出時保持第一次的退出狀態
+    fn finish(&self, status: ExitStatus) {
+        self.status.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(status);
+            true
+        });
+    }
+}
+
+#[derive(Debug)]
+struct FakeHandle {
+    process: Arc<FakeProcess>,
+    reaped: bool,
+}
+
+impl ProcessHandle for FakeHandle {
+    fn id(&self) -> Option<u32> {
+        (!self.reaped).then_some(self.process.pid)
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = *self.process.status.borrow();
+        self.reaped |= status.is_some();
+        Ok(status)
+    }
+
+    fn wait(&mut self) -> WaitFuture<'_> {
+        Box::pin(async move {
+            let mut receiver = self.process.status.subscribe();
+            let status = receiver
+                .wait_for(Option::is_some)
+                .await
+                .map(|status| status.expect("waited for an exit status"))
+                .map_err(io::Error::other)?;
+            self.reaped = true;
+            Ok(status)
+        })
+    }
+
+    fn start_kill(&mut self) -> io::Result<()> {
+        self.process.signals.lock().unwrap().push(KILL_SIGNAL);
+        self.process.finish(signal_status(KILL_SIGNAL));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> io::Result<()> {
+        self.process.signals.lock().unwrap().push(signal);
+        let terminates = match signal {
+            libc::SIGTERM => !self.process.ignore_sigterm,
+            libc::SIGINT | libc::SIGQUIT | libc::SIGKILL => true,
+            _ => false,
+        };
+        if terminates {
+            self.process.finish(signal_status(signal));
+        }
+        Ok(())
+    }
+
+    fn take_stdout(&mut self) -> Option<ChildStdout> {
+        None
+    }
+
+    fn take_stderr(&mut self) -> Option<ChildStderr> {
+        None
+    }
+}
+
+#[cfg(unix)]
+const KILL_SIGNAL: i32 = libc::SIGKILL;
+#[cfg(not(unix))]
+const KILL_SIGNAL: i32 = 9;
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(unix)]
+fn signal_status(signal: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(signal)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+// Windows 上被終止的進程退出碼為 1
+#[cfg(windows)]
+fn signal_status(_signal: i32) -> ExitStatus {
+    exit_status(1)
+}
@@ -1,8 +1,7 @@
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::fs;
-    use std::io::Write;
-    use std::path::Path;
     use std::process::Command;
     use tempfile::TempDir;
     
@@ -66,56 +65,6593 @@ mod tests {
         assert!(result.is_err());
     }
     
+    #[test]
+    fn test_cpu_affinity_validation() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("affinity_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "pinned"
+        command = "true"
+        cpu_affinity = [0, 4096]
+        "#).unwrap();
+        
+        let result = janus::config::manager::ConfigManager::new(
+            config_path.to_str().unwrap(),
+        );
+        
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_memory_limit_parsing() {
+        use janus::process::memory::parse_size;
+        
+        assert_eq!(parse_size("512M"), Some(512 << 20));
+        assert_eq!(parse_size("2GiB"), Some(2 << 30));
+        assert_eq!(parse_size("64 kb"), Some(64 << 10));
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("0M"), None);
+        assert_eq!(parse_size("lots"), None);
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("memory_config.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "hungry"
+        command = "true"
+        memory_limit = "1T"
+        "#).unwrap();
+        assert!(janus::config::manager::ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_oom_kill_event() {
+        use janus::process::events::{EventKind, ProcessEvent};
+        use janus::process::memory::cgroup;
+        use janus::process::plugin::PluginEvent;
+        
+        // memory.events 中的 oom_kill 計數和 memory.peak
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("memory.events"), "low 0\nhigh 0\nmax 12\noom 3\noom_kill 2\noom_group_kill 0\n").unwrap();
+        fs::write(temp_dir.path().join("memory.peak"), "67108864\n").unwrap();
+        assert_eq!(cgroup::oom_kills(temp_dir.path()), Some(2));
+        assert_eq!(cgroup::peak(temp_dir.path()), Some(64 << 20));
+        assert_eq!(cgroup::oom_kills(&temp_dir.path().join("missing")), None);
+        
+        let event = ProcessEvent::new("web", EventKind::OomKilled).with_memory(Some(128 << 20), Some(64 << 20));
+        let json = serde_json::to_value(PluginEvent::new(&event, true, 1, None)).unwrap();
+        assert_eq!(json["event"], "oom_killed");
+        assert_eq!(json["memory_limit"], 128 << 20);
+        assert_eq!(json["memory_peak"], 64 << 20);
+        
+        // 其他事件不帶內存字段
+        let event = ProcessEvent::new("web", EventKind::Exited);
+        let json = serde_json::to_value(PluginEvent::new(&event, true, 1, None)).unwrap();
+        assert!(json.get("memory_limit").is_none());
+        assert!(json.get("memory_peak").is_none());
+    }
+    
+    #[test]
+    fn test_sched_policy_validation() {
+        use janus::config::SchedPolicy;
+        use janus::process::scheduling::validate_policy;
+        
+        assert!(validate_policy(Some(SchedPolicy::Idle), None).is_ok());
+        assert!(validate_policy(Some(SchedPolicy::Fifo), Some(10)).is_ok());
+        // 實時策略需要 1-99 的優先級，其他策略不接受優先級
+        assert!(validate_policy(Some(SchedPolicy::Rr), None).is_err());
+        assert!(validate_policy(Some(SchedPolicy::Fifo), Some(100)).is_err());
+        assert!(validate_policy(Some(SchedPolicy::Batch), Some(5)).is_err());
+    }
+    
+    #[test]
+    fn test_executable_fingerprint_changes_on_replace() {
+        use janus::process::executable::{fingerprint, resolve};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let binary = temp_dir.path().join("app");
+        fs::write(&binary, "v1").unwrap();
+        
+        let resolved = resolve(binary.to_str().unwrap(), &HashMap::new(), None).unwrap();
+        let before = fingerprint(&resolved);
+        assert!(before.is_some());
+        
+        // 模擬部署：新版本寫入臨時文件後原子替換
+        let replacement = temp_dir.path().join("app.new");
+        fs::write(&replacement, "version 2").unwrap();
+        fs::rename(&replacement, &binary).unwrap();
+        
+        assert_ne!(before, fingerprint(&resolved));
+    }
+    
+    #[test]
+    fn test_config_json_schema() {
+        let schema = janus::config::manager::ConfigManager::json_schema().unwrap();
+        
+        assert!(schema.contains("\"ProcessConfig\""));
+        assert!(schema.contains("\"restart_limit\""));
+    }
+    
+    #[tokio::test]
+    async fn test_reload_adds_and_removes_processes() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("reload_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "old"
+        command = "true"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        assert!(!manager.is_config_reload_pending());
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "new"
+        command = "true"
+        "#).unwrap();
+        
+        // 修改後未重新加載時應標記為待重新加載
+        assert!(manager.is_config_reload_pending());
+        
+        manager.reload().await.unwrap();
+        assert!(!manager.is_config_reload_pending());
+        
+        let processes = manager.get_all_processes();
+        assert!(processes.contains_key("new"));
+        assert!(!processes.contains_key("old"));
+    }
+    
+    #[tokio::test]
+    async fn test_reload_starts_added_processes_like_start() {
+        use chrono::Local;
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("reload_config.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["30"]
+        "#).unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        let not_before = (Local::now() + chrono::Duration::hours(1)).format("%Y-%m-%d %H:%M:%S");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["30"]
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        args = ["30"]
+        
+        [[process]]
+        name = "timer"
+        command = "sleep"
+        args = ["30"]
+        every = "1m"
+        run_on_start = false
+        
+        [[process]]
+        name = "nightly"
+        command = "sleep"
+        args = ["30"]
+        schedule = "0 3 * * *"
+        
+        [[process]]
+        name = "importer"
+        command = "sleep"
+        args = ["30"]
+        not_before = "{}"
+        "#, not_before)).unwrap();
+        manager.reload().await.unwrap();
+        
+        // 和 start 一樣只啟動隨 janus 啟動的進程，窗口外的進程等待窗口打開
+        let pid = |name: &str| manager.get_process(name).unwrap().pid();
+        assert!(pid("api").is_some());
+        assert!(pid("timer").is_none());
+        assert!(pid("nightly").is_none());
+        assert!(pid("importer").is_none());
+        assert!(manager.get_process("importer").unwrap().waiting_for_window);
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_reload_swaps_config_snapshot() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("snapshot_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "first"
+        command = "true"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        let before = manager.config_snapshot().unwrap();
+        assert_eq!(manager.config_version(), 1);
+        
+        // 無效的配置不會替換當前版本
+        fs::write(&config_path, "[[process]]\nname = \"broken\"\ncommand = \"\"\n").unwrap();
+        assert!(manager.reload().await.is_err());
+        assert_eq!(manager.config_version(), 1);
+        assert_eq!(manager.get_config_manager().unwrap().get_process_configs()[0].name, "first");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "second"
+        command = "true"
+        "#).unwrap();
+        manager.reload().await.unwrap();
+        
+        // 重新加載前取得的快照仍是舊版本
+        assert_eq!(manager.config_version(), 2);
+        assert_eq!(before.get_process_configs()[0].name, "first");
+        assert_eq!(manager.config_snapshot().unwrap().get_process_configs()[0].name, "second");
+        assert_eq!(manager.snapshot().config_version, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_config_reload_pending_flag() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("pending.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "true"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        assert!(!manager.is_config_reload_pending());
+        assert!(!manager.snapshot().config_reload_pending);
+        
+        // 修改配置文件後快照報告需要重新加載
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "true"
+        args = ["--changed"]
+        "#).unwrap();
+        assert!(manager.is_config_reload_pending());
+        assert!(manager.snapshot().config_reload_pending);
+        
+        // 重新加載後標記清除
+        manager.reload().await.unwrap();
+        assert!(!manager.is_config_reload_pending());
+        let snapshot = manager.snapshot();
+        assert!(!snapshot.config_reload_pending);
+        assert_eq!(snapshot.config_version, 2);
+    }
+    
+    #[test]
+    fn test_display_name_and_label() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::logging::{LogEntry, LogType};
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("display_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "svc-7f3a9c-web"
+        display_name = "web"
+        label = "🌐"
+        command = "true"
+        
+        [[process]]
+        name = "worker"
+        command = "true"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info");
+        let manager = ProcessManager::new(config_manager, log_handler.clone());
+        
+        assert_eq!(manager.get_process("svc-7f3a9c-web").unwrap().display_label(), "🌐 web");
+        assert_eq!(manager.get_process("worker").unwrap().display_label(), "worker");
+        
+        // 日誌前綴使用顯示名稱
+        let entry = LogEntry {
+            timestamp: chrono::Local::now(),
+            process_name: "svc-7f3a9c-web".to_string(),
+            log_type: LogType::Stdout,
+            content: "listening".to_string(),
+        };
+        let formatted = log_handler.format_log_entry(&entry);
+        assert!(formatted.contains("🌐 web"));
+        assert!(!formatted.contains("svc-7f3a9c-web"));
+    }
+    
+    #[tokio::test]
+    async fn test_merge_output_keeps_write_order() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("merge_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "interleaved"
+        command = "sh"
+        args = ["-c", "for i in 1 2 3 4 5; do echo out$i; echo err$i >&2; done"]
+        merge_output = true
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler);
+        manager.start_process("interleaved").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        
+        // 合併輸出時日誌順序與進程寫入順序一致
+        let content = fs::read_to_string(temp_dir.path().join("interleaved.log")).unwrap();
+        let lines: Vec<&str> = content
+            .lines()
+            .filter(|line| line.contains(" stdout "))
+            .map(|line| line.rsplit(' ').next().unwrap())
+            .collect();
+        assert_eq!(lines, ["out1", "err1", "out2", "err2", "out3", "err3", "out4", "err4", "out5", "err5"]);
+    }
+    
+    #[tokio::test]
+    async fn test_capture_to_records_output_and_exit_code() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::events::EventKind;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use janus::test_util::EventCapture;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("capture_config.toml");
+        let capture_template = temp_dir.path().join("results/{name}.out");
+        
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "report"
+        command = "sh"
+        args = ["-c", "echo line1; echo line2 >&2; exit 3"]
+        capture_to = "{}"
+        capture_only = true
+        merge_output = true
+        "#, capture_template.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("report").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        manager.check_processes().await.unwrap();
+        
+        // 輸出寫入文件，運行記錄中包含退出碼和文件路徑
+        let output_path = temp_dir.path().join("results/report.out");
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), "line1\nline2\n");
+        
+        let run = manager.get_process("report").unwrap().run_history.back().unwrap().clone();
+        assert_eq!(run.exit_code, Some(3));
+        assert_eq!(run.output_path, Some(output_path));
+        
+        // 無法創建輸出文件時與其他啟動失敗一樣標記為失敗並發出 StartFailed
+        fs::write(temp_dir.path().join("blocked"), "").unwrap();
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "report"
+        command = "true"
+        capture_to = "{}"
+        "#, temp_dir.path().join("blocked/{name}.out").display())).unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        let mut events = EventCapture::new(&manager);
+        assert!(manager.start_process("report").await.is_err());
+        assert_eq!(manager.get_process("report").unwrap().status, ProcessStatus::Failed);
+        assert_eq!(events.kinds("report"), vec![EventKind::StartFailed]);
+    }
+    
+    #[tokio::test]
+    async fn test_timer_skips_overlapping_runs() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("timer_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "refresh"
+        command = "sleep"
+        args = ["2"]
+        every = "1s"
+        run_on_start = false
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        assert!(!manager.get_process("refresh").unwrap().starts_with_supervisor());
+        
+        // run_on_start = false 時等待第一個週期
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("refresh").unwrap().status, ProcessStatus::Stopped);
+        
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("refresh").unwrap();
+        assert_eq!(process.status, ProcessStatus::Running);
+        let pid = process.process.as_ref().and_then(|child| child.id());
+        
+        // 上一次運行未結束，下一個週期跳過而不是再啟動一個
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("refresh").unwrap();
+        assert_eq!(process.process.as_ref().and_then(|child| child.id()), pid);
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_timer_overlap_policies() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::schedule::random_delay;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("overlap_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "queued"
+        command = "sleep"
+        args = ["1.5"]
+        every = "1s"
+        overlap = "queue"
+        
+        [[process]]
+        name = "killer"
+        command = "sleep"
+        args = ["10"]
+        every = "1s"
+        overlap = "kill-previous"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("queued").await.unwrap();
+        manager.start_process("killer").await.unwrap();
+        let pid = |manager: &ProcessManager, name: &str| {
+            manager.get_process(name).unwrap().process.as_ref().and_then(|child| child.id())
+        };
+        let queued_pid = pid(&manager, "queued");
+        let killer_pid = pid(&manager, "killer");
+        
+        manager.check_processes().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        manager.check_processes().await.unwrap();
+        
+        // kill-previous 終止上一次運行並立即重新啟動；queue 等待上一次運行結束
+        assert_ne!(pid(&manager, "killer"), killer_pid);
+        assert_eq!(pid(&manager, "queued"), queued_pid);
+        assert!(manager.get_process("queued").unwrap().overlap_queued);
+        
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        manager.check_processes().await.unwrap();
+        assert!(pid(&manager, "queued").is_some());
+        assert_ne!(pid(&manager, "queued"), queued_pid);
+        
+        manager.stop_all().await.unwrap();
+        
+        let max = Duration::from_secs(30);
+        assert!((0..100).all(|_| random_delay(max) <= max));
+        assert_eq!(random_delay(Duration::ZERO), Duration::ZERO);
+    }
+    
+    #[test]
+    fn test_config_rejects_invalid_every() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("every_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "refresh"
+        command = "true"
+        every = "0s"
+        "#).unwrap();
+        
+        assert!(janus::config::manager::ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        
+        // 溢出的間隔同樣被拒絕
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "refresh"
+        command = "true"
+        every = "300000000000000000d"
+        "#).unwrap();
+        
+        assert!(janus::config::manager::ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "refresh"
+        command = "true"
+        every = "30s"
+        auto_restart = true
+        "#).unwrap();
+        
+        assert!(janus::config::manager::ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        
+        // jitter 和 overlap 只對定時或 cron 任務有效
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "refresh"
+        command = "true"
+        jitter = "30s"
+        "#).unwrap();
+        
+        assert!(janus::config::manager::ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[test]
+    fn test_cron_schedule_time_zone() {
+        use chrono::{TimeZone, Utc};
+        use janus::process::schedule::CronSchedule;
+        
+        // 東京時間每天 09:00 即 UTC 00:00
+        let schedule = CronSchedule::parse("0 9 * * *", Some("Asia/Tokyo")).unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(schedule.next_after(after), Some(Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap()));
+        assert_eq!(schedule.to_string(), "0 9 * * * (Asia/Tokyo)");
+        
+        assert!(CronSchedule::parse("0 9 * * *", Some("Mars/Olympus")).is_err());
+        assert!(CronSchedule::parse("61 * * * *", None).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_schedule_missed_run_policy() {
+        use chrono::Utc;
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::schedule::ScheduleState;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("schedule_config.toml");
+        let state_path = temp_dir.path().join("schedule.json");
+        
+        fs::write(&config_path, format!(r#"
+        [global]
+        schedule_state_file = "{}"
+        
+        [[process]]
+        name = "catch-up"
+        command = "sleep"
+        args = ["5"]
+        schedule = "0 * * * *"
+        missed = "run-once"
+        
+        [[process]]
+        name = "skipper"
+        command = "sleep"
+        args = ["5"]
+        schedule = "0 * * * *"
+        timezone = "UTC"
+        "#, state_path.display())).unwrap();
+        
+        // 模擬 janus 停止了三小時，期間錯過了排程
+        let state = ScheduleState::new(&state_path);
+        let three_hours_ago = Utc::now() - chrono::Duration::hours(3);
+        state.record("catch-up", three_hours_ago).unwrap();
+        state.record("skipper", three_hours_ago).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        assert!(!manager.get_process("catch-up").unwrap().starts_with_supervisor());
+        manager.check_processes().await.unwrap();
+        
+        assert_eq!(manager.get_process("catch-up").unwrap().status, ProcessStatus::Running);
+        assert_eq!(manager.get_process("skipper").unwrap().status, ProcessStatus::Stopped);
+        
+        // 已處理的時間被記錄下來，下一次排程在未來
+        assert!(state.last_run("skipper").unwrap() > three_hours_ago);
+        assert!(manager.get_process("skipper").unwrap().next_scheduled_at.unwrap() > Utc::now());
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_ignore_exit_codes_not_counted() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("exit_codes_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "graceful"
+        command = "sh"
+        args = ["-c", "kill -TERM $$"]
+        auto_restart = true
+        restart_limit = 1
+        restart_delay = 0
+        ignore_exit_codes = [143]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("graceful").await.unwrap();
+        
+        // 被 SIGTERM 終止按退出碼 143 處理，多次重啟也不計入 restart_limit
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            manager.check_processes().await.unwrap();
+        }
+        
+        let process = manager.get_process("graceful").unwrap();
+        assert_eq!(process.restart_count, 0);
+        assert_ne!(process.status, ProcessStatus::Failed);
+        assert!(process.run_history.len() >= 2);
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_restart_budget_gives_up() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("budget_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "flapping"
+        command = "false"
+        auto_restart = true
+        restart_delay = 0
+        restart_budget = 2
+        restart_budget_window = 600
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler);
+        manager.start_process("flapping").await.unwrap();
+        
+        // 窗口內重啟兩次後不再重啟，標記為 GaveUp
+        for _ in 0..30 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            manager.check_processes().await.unwrap();
+            if manager.get_process("flapping").unwrap().status == ProcessStatus::GaveUp {
+                break;
+            }
+        }
+        
+        let process = manager.get_process("flapping").unwrap();
+        assert_eq!(process.status, ProcessStatus::GaveUp);
+        assert_eq!(process.restart_count, 2);
+        assert_eq!(process.run_history.len(), 3);
+        
+        // 放棄後不會再安排重啟
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("flapping").unwrap();
+        assert_eq!(process.status, ProcessStatus::GaveUp);
+        assert_eq!(process.restart_count, 2);
+        
+        let content = fs::read_to_string(temp_dir.path().join("flapping.log")).unwrap();
+        assert!(content.contains("Restart budget exhausted (2 restarts within 600 seconds), giving up"), "{}", content);
+    }
+    
+    #[tokio::test]
+    async fn test_start_stop_timing_and_slow_warnings() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::time::{Duration, Instant};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("timing_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "slow"
+        command = "sh"
+        args = ["-c", "trap 'sleep 1; exit 0' TERM; while :; do sleep 0.1; done"]
+        stop_timeout = 5
+        slow_start_threshold = 1
+        slow_stop_threshold = 1
+        
+        [[process]]
+        name = "fast"
+        command = "sleep"
+        args = ["30"]
+        slow_start_threshold = 1
+        slow_stop_threshold = 1
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        
+        // 啟動耗時從啟動請求開始計算，模擬等待依賴用了兩秒
+        manager.get_process_mut("slow").unwrap().start_requested_at = Some(Instant::now() - Duration::from_secs(2));
+        manager.start_process("slow").await.unwrap();
+        manager.start_process("fast").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        
+        manager.stop_process("slow").await.unwrap();
+        manager.stop_process("fast").await.unwrap();
+        
+        let slow = manager.get_process("slow").unwrap();
+        assert!(slow.last_start_duration.unwrap() >= Duration::from_secs(2));
+        assert!(slow.last_stop_duration.unwrap() >= Duration::from_secs(1));
+        let fast = manager.get_process("fast").unwrap();
+        assert!(fast.last_start_duration.unwrap() < Duration::from_secs(1));
+        assert!(fast.last_stop_duration.unwrap() < Duration::from_secs(1));
+        
+        // 快照中包含兩個耗時
+        let snapshot = manager.snapshot();
+        let slow = snapshot.processes.iter().find(|process| process.name == "slow").unwrap();
+        assert!(slow.last_start_duration_ms.unwrap() >= 2000);
+        assert!(slow.last_stop_duration_ms.unwrap() >= 1000);
+        
+        // 只有超過閾值的進程記錄警告
+        log_handler.flush();
+        let content = fs::read_to_string(temp_dir.path().join("slow.log")).unwrap();
+        assert!(content.contains("Slow start: process took 2."), "{}", content);
+        assert!(content.contains("Slow stop: process took 1."), "{}", content);
+        let content = fs::read_to_string(temp_dir.path().join("fast.log")).unwrap();
+        assert!(!content.contains("Slow start") && !content.contains("Slow stop"), "{}", content);
+    }
+    
+    #[tokio::test]
+    async fn test_termination_by_signal() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::{ProcessStatus, Termination};
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("signal_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "crasher"
+        command = "sh"
+        args = ["-c", "ulimit -c 0; kill -SEGV $$"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("crasher").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        manager.check_processes().await.unwrap();
+        
+        // 被信號終止時沒有退出碼，記錄為終止它的信號
+        let process = manager.get_process("crasher").unwrap();
+        let termination = process.termination.unwrap();
+        assert_eq!(process.status, ProcessStatus::Failed);
+        assert_eq!(process.exit_code, None);
+        assert_eq!(termination, Termination::Signaled { signal: 11, core_dumped: false });
+        assert_eq!(termination.shell_code(), 139);
+        assert_eq!(termination.to_string(), "killed by SIGSEGV");
+        assert_eq!(process.run_history.back().unwrap().termination, Some(termination));
+        
+        assert_eq!(
+            Termination::Signaled { signal: 6, core_dumped: true }.to_string(),
+            "killed by SIGABRT (core dumped)"
+        );
+        assert_eq!(Termination::Exited(3).to_string(), "exited with code: 3");
+    }
+    
+    #[test]
+    fn test_core_pattern_resolution_and_preserve() {
+        use janus::process::coredump::{preserve, resolve_pattern, CoreLocation};
+        use std::path::Path;
+        
+        let working_dir = Path::new("/srv/app");
+        assert_eq!(
+            resolve_pattern("/var/crash/core.%e.%p.%t", false, 42, working_dir),
+            CoreLocation::File("/var/crash/core.*.42.*".to_string())
+        );
+        // core_uses_pid 時內核追加 .pid，相對路徑基於工作目錄
+        assert_eq!(
+            resolve_pattern("core", true, 42, working_dir),
+            CoreLocation::File("/srv/app/core.42".to_string())
+        );
+        assert_eq!(
+            resolve_pattern("|/usr/lib/systemd/systemd-coredump %P", false, 42, working_dir),
+            CoreLocation::Piped("/usr/lib/systemd/systemd-coredump %P".to_string())
+        );
+        
+        // 匹配到的 core 文件被移動到目標目錄，文件名包含進程名
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("core.42"), b"core").unwrap();
+        let location = resolve_pattern("core.%p", false, 42, temp_dir.path());
+        let saved = preserve(&location, &temp_dir.path().join("cores"), "crasher").unwrap().unwrap();
+        
+        assert!(saved.file_name().unwrap().to_str().unwrap().starts_with("crasher-"));
+        assert_eq!(fs::read(&saved).unwrap(), b"core");
+        assert!(!temp_dir.path().join("core.42").exists());
+        assert_eq!(preserve(&location, &temp_dir.path().join("cores"), "crasher").unwrap(), None);
+    }
+    
+    #[tokio::test]
+    async fn test_crash_context_lines() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("crash_context_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "failing"
+        command = "sh"
+        args = ["-c", "for i in 1 2 3 4 5; do echo line$i; done; echo fatal >&2; exit 2"]
+        crash_context_lines = 3
+        merge_output = true
+        
+        [[process]]
+        name = "clean"
+        command = "sh"
+        args = ["-c", "echo done"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("failing").await.unwrap();
+        manager.start_process("clean").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        manager.check_processes().await.unwrap();
+        
+        // 只保留異常退出前的最後幾行，正常退出不記錄
+        let run = manager.get_process("failing").unwrap().run_history.back().unwrap().clone();
+        assert_eq!(run.crash_context, vec!["line4", "line5", "fatal"]);
+        let run = manager.get_process("clean").unwrap().run_history.back().unwrap().clone();
+        assert!(run.crash_context.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_adopt_pid_file() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("legacy.pid");
+        let config_path = temp_dir.path().join("adopt.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "legacy"
+        command = "sleep"
+        args = ["30"]
+        adopt_pid_file = "{}"
+        "#, pid_file.display())).unwrap();
+        
+        // 在 janus 之外啟動的實例；它退出後由 janus 回收，因此這裡不調用 wait
+        #[allow(clippy::zombie_processes)]
+        let mut external = Command::new("sleep").arg("30").spawn().unwrap();
+        fs::write(&pid_file, format!("{}\n", external.id())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("legacy").await.unwrap();
+        let process = manager.get_process("legacy").unwrap();
+        assert_eq!(process.status, ProcessStatus::Running);
+        assert!(process.process.is_none());
+        assert_eq!(process.pid(), Some(external.id()));
+        
+        // 接管的進程退出後被發現
+        external.kill().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("legacy").unwrap();
+        assert_eq!(process.status, ProcessStatus::Failed);
+        assert_eq!(process.pid(), None);
+        
+        // PID 文件中的進程已不存在時正常啟動新進程
+        manager.start_process("legacy").await.unwrap();
+        let process = manager.get_process("legacy").unwrap();
+        assert!(process.process.is_some());
+        assert_ne!(process.pid(), Some(external.id()));
+        manager.stop_process("legacy").await.unwrap();
+    }
+    
+    // macOS 上的 PidFd 基於 kqueue 的 EVFILT_PROC
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_pidfd_signal_and_exit() {
+        use janus::process::pidfd::PidFd;
+        use std::os::unix::process::ExitStatusExt;
+        
+        #[allow(clippy::zombie_processes)]
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        // 內核早於 5.3 時沒有 pidfd，跳過
+        let Some(pidfd) = PidFd::open(child.id()) else {
+            return;
+        };
+        assert!(!pidfd.exited());
+        assert!(pidfd.reap().is_none());
+        
+        // 通過 pidfd 發送信號，並通過 pidfd 回收子進程
+        pidfd.send_signal(libc::SIGTERM).unwrap();
+        let started = std::time::Instant::now();
+        while !pidfd.exited() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let status = pidfd.reap().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+        assert!(pidfd.reap().is_none());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_tree_includes_grandchildren() {
+        use janus::process::tree::ProcessTable;
+        
+        // 後台的 sleep 在 exec 之後成為 root 的子進程
+        let mut root = Command::new("sh").arg("-c").arg("sleep 30 & exec sleep 31").spawn().unwrap();
+        let pid = root.id();
+        
+        let started = std::time::Instant::now();
+        let tree = loop {
+            let tree = ProcessTable::scan().unwrap().tree(pid).unwrap();
+            if tree.children.len() == 1 && tree.command == "sleep 31" {
+                break tree;
+            }
+            assert!(started.elapsed() < std::time::Duration::from_secs(5), "unexpected tree: {:?}", tree);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        };
+        
+        let child = &tree.children[0];
+        assert_eq!(child.command, "sleep 30");
+        assert_eq!(tree.count(), 2);
+        assert!(tree.rss > 0);
+        assert_eq!(tree.total_rss(), tree.rss + child.rss);
+        
+        unsafe { libc::kill(child.pid as libc::pid_t, libc::SIGKILL) };
+        root.kill().unwrap();
+        root.wait().unwrap();
+        assert!(ProcessTable::scan().unwrap().tree(pid).is_none());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_run_resource_usage() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("usage.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "busy"
+        command = "sh"
+        args = ["-c", "i=0; while [ $i -lt 200000 ]; do i=$((i+1)); done"]
+        
+        [[process]]
+        name = "idle"
+        command = "sleep"
+        args = ["30"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("busy").await.unwrap();
+        manager.start_process("idle").await.unwrap();
+        
+        // 退出的進程在回收之前讀取本次運行的 CPU 時間和上下文切換次數
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+        while manager.get_process("busy").unwrap().status == ProcessStatus::Running {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            manager.check_processes().await.unwrap();
+        }
+        let usage = manager.get_process("busy").unwrap().run_history.back().unwrap().usage.unwrap();
+        assert!(usage.cpu_time() > std::time::Duration::ZERO);
+        assert!(usage.voluntary_switches + usage.involuntary_switches > 0);
+        
+        // 被停止的進程同樣有記錄
+        manager.stop_process("idle").await.unwrap();
+        assert!(manager.get_process("idle").unwrap().run_history.back().unwrap().usage.is_some());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_memory_limit_kills_whole_group() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::tree::ProcessTable;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("memory.toml");
+        // 上限低於兩個 sleep 的 RSS 之和，第一次檢查就會超出
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "forker"
+        command = "sh"
+        args = ["-c", "sleep 30 & exec sleep 31"]
+        memory_limit = "64K"
+        restart_delay = 0
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("forker").await.unwrap();
+        let pid = manager.get_process("forker").unwrap().pid().unwrap();
+        
+        let started = std::time::Instant::now();
+        let grandchild = loop {
+            let tree = ProcessTable::scan().unwrap().tree(pid).unwrap();
+            if let Some(child) = tree.children.first() {
+                break child.pid;
+            }
+            assert!(started.elapsed() < std::time::Duration::from_secs(5));
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        };
+        
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("forker").unwrap();
+        assert_eq!(process.status, ProcessStatus::Running);
+        assert_ne!(process.pid(), Some(pid));
+        
+        // 孫進程也被殺死：不再存在，或者是等待 init 回收的僵屍進程
+        let stat = fs::read_to_string(format!("/proc/{}/stat", grandchild)).unwrap_or_default();
+        let state = stat.rsplit(')').next().unwrap_or_default().split_whitespace().next();
+        assert!(matches!(state, None | Some("Z") | Some("X")), "grandchild still running: {}", stat);
+        
+        manager.stop_process("forker").await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_plugin_suppresses_restart() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let events_path = temp_dir.path().join("events.jsonl");
+        let plugin_path = temp_dir.path().join("plugin.sh");
+        // 記錄收到的事件，過一會兒再要求取消崩潰後的重啟
+        fs::write(&plugin_path, format!(
+            "cat >> {}\nsleep 1\necho '{{\"suppress_restart\": true}}'\n",
+            events_path.display()
+        )).unwrap();
+        
+        let config_path = temp_dir.path().join("plugins.toml");
+        fs::write(&config_path, format!(r#"
+        [global]
+        plugins = [{{ command = "sh", args = ["{}"] }}]
+        
+        [[process]]
+        name = "crasher"
+        command = "sh"
+        args = ["-c", "exit 3"]
+        auto_restart = true
+        "#, plugin_path.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("crasher").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        
+        // 監控循環不等待插件響應；響應之前到期的重啟也暫緩，插件仍能取消它
+        let started = std::time::Instant::now();
+        manager.check_processes().await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_millis(500));
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while manager.get_process("crasher").unwrap().restart_at.is_some() {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            manager.check_processes().await.unwrap();
+        }
+        
+        let process = manager.get_process("crasher").unwrap();
+        assert_eq!(process.status, ProcessStatus::Failed);
+        assert_eq!(process.restart_count, 0);
+        
+        let events = fs::read_to_string(&events_path).unwrap();
+        let kinds: Vec<String> = events
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["event"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(kinds, ["started", "crashed"]);
+        assert!(events.contains("\"exit_code\":3"));
+    }
+    
+    #[tokio::test]
+    async fn test_restart_script_decides_restart() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("script.toml");
+        
+        // 語法錯誤的腳本在加載配置時被拒絕
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "broken"
+        command = "true"
+        restart_script = "if exit_code == {"
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        
+        // 連續三次不到一秒的崩潰後不再重啟，在此之前立即重啟
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "crasher"
+        command = "sh"
+        args = ["-c", "exit 3"]
+        auto_restart = true
+        restart_delay = 30
+        restart_script = '''
+            let recent = runs.extract(-3);
+            if hour >= 0 && recent.len() == 3 && recent.all(|run| !run.success && run.duration < 1.0) {
+                return false;
+            }
+            if exit_code == 3 && signal == () { 0 } else { true }
+        '''
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("crasher").await.unwrap();
+        for _ in 0..3 {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            manager.check_processes().await.unwrap();
+        }
+        
+        let process = manager.get_process("crasher").unwrap();
+        assert_eq!(process.status, ProcessStatus::Failed);
+        assert_eq!(process.restart_count, 2);
+        assert!(process.restart_at.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_watchdog_restarts_silent_process() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("watchdog.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "alive"
+        command = "sh"
+        args = ["-c", "while true; do echo heartbeat; sleep 0.2; done"]
+        watchdog_timeout = 1
+        watchdog_pattern = "^heartbeat$"
+        
+        [[process]]
+        name = "stuck"
+        command = "sh"
+        args = ["-c", "echo heartbeat; exec sleep 30"]
+        watchdog_timeout = 1
+        watchdog_pattern = "^heartbeat$"
+        restart_delay = 0
+        restart_budget = 1
+        restart_budget_window = 600
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("alive").await.unwrap();
+        manager.start_process("stuck").await.unwrap();
+        let alive_pid = manager.get_process("alive").unwrap().pid();
+        let stuck_pid = manager.get_process("stuck").unwrap().pid();
+        
+        // 只有持續輸出心跳的進程不會被重啟
+        tokio::time::sleep(std::time::Duration::from_millis(1600)).await;
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("alive").unwrap().pid(), alive_pid);
+        let stuck = manager.get_process("stuck").unwrap();
+        assert_eq!(stuck.status, ProcessStatus::Running);
+        assert_ne!(stuck.pid(), stuck_pid);
+        assert_eq!(stuck.restart_count, 1);
+        
+        // watchdog 觸發的重啟和崩潰一樣計入重啟預算，用完後不再重啟
+        tokio::time::sleep(std::time::Duration::from_millis(1600)).await;
+        manager.check_processes().await.unwrap();
+        let stuck = manager.get_process("stuck").unwrap();
+        assert_eq!(stuck.status, ProcessStatus::GaveUp);
+        assert!(stuck.pid().is_none());
+        
+        manager.stop_process("alive").await.unwrap();
+        manager.stop_process("stuck").await.unwrap();
+        
+        // 沒有心跳來源的 watchdog_timeout 是配置錯誤
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "bad"
+        command = "true"
+        watchdog_timeout = 5
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_notify_socket_reports_readiness() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::os::unix::net::UnixDatagram;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("notify.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "daemon"
+        command = "sleep"
+        args = ["30"]
+        notify = true
+        watchdog_timeout = 1
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("daemon").await.unwrap();
+        let process = manager.get_process("daemon").unwrap();
+        let pid = process.pid().unwrap();
+        let socket_path = process.notify_listener.as_ref().unwrap().path().to_path_buf();
+        assert!(!process.notify_state.is_ready());
+        
+        // 進程收到與 systemd 相同的環境變量
+        let environ = fs::read(format!("/proc/{}/environ", pid)).unwrap();
+        let environ = String::from_utf8_lossy(&environ);
+        assert!(environ.contains(&format!("NOTIFY_SOCKET={}", socket_path.display())));
+        assert!(environ.contains("WATCHDOG_USEC=1000000"));
+        
+        // 代替進程按 sd_notify 協議發送消息，WATCHDOG=1 讓看門狗不會重啟它
+        let client = UnixDatagram::unbound().unwrap();
+        client.send_to(b"READY=1\nSTATUS=Serving 3 clients", &socket_path).unwrap();
+        for _ in 0..6 {
+            tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+            client.send_to(b"WATCHDOG=1", &socket_path).unwrap();
+        }
+        manager.check_processes().await.unwrap();
+        
+        let process = manager.get_process("daemon").unwrap();
+        assert_eq!(process.pid(), Some(pid));
+        let status = process.notify_state.get();
+        assert!(status.ready);
+        assert_eq!(status.status.as_deref(), Some("Serving 3 clients"));
+        
+        manager.stop_all().await.unwrap();
+        assert!(!socket_path.exists());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_start_duration_measured_until_ready() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::os::unix::net::UnixDatagram;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("ready_timing.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "daemon"
+        command = "sleep"
+        args = ["30"]
+        notify = true
+        slow_start_threshold = 1
+        
+        [[process]]
+        name = "warming"
+        command = "sleep"
+        args = ["30"]
+        expected_start_duration = "1s"
+        slow_start_threshold = 1
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("daemon").await.unwrap();
+        manager.start_process("warming").await.unwrap();
+        
+        // 進程創建後尚未就緒，不記錄啟動耗時
+        manager.check_processes().await.unwrap();
+        assert!(manager.get_process("daemon").unwrap().last_start_duration.is_none());
+        assert!(manager.get_process("warming").unwrap().last_start_duration.is_none());
+        
+        let socket_path = manager.get_process("daemon").unwrap().notify_listener.as_ref().unwrap().path().to_path_buf();
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        UnixDatagram::unbound().unwrap().send_to(b"READY=1", &socket_path).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        manager.check_processes().await.unwrap();
+        
+        // notify 進程按 READY=1 計算，其他進程按 expected_start_duration 計算
+        let daemon = manager.get_process("daemon").unwrap().last_start_duration.unwrap();
+        assert!(daemon >= Duration::from_millis(1200) && daemon < Duration::from_millis(1400), "{:?}", daemon);
+        let warming = manager.get_process("warming").unwrap().last_start_duration.unwrap();
+        assert!(warming >= Duration::from_secs(1) && warming < Duration::from_millis(1200), "{:?}", warming);
+        
+        manager.stop_all().await.unwrap();
+        log_handler.flush();
+        for name in ["daemon", "warming"] {
+            let content = fs::read_to_string(temp_dir.path().join(format!("{}.log", name))).unwrap();
+            assert!(content.contains("Slow start: process took 1."), "{}", content);
+        }
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_waits_for_graceful_exit() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("shutdown.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "graceful"
+        command = "sh"
+        args = ["-c", "trap 'echo cleaned up; exit 0' TERM; while true; do sleep 0.1; done"]
+        auto_restart = true
+        
+        [[process]]
+        name = "stubborn"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler);
+        manager.start_process("graceful").await.unwrap();
+        manager.start_process("stubborn").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        
+        let started = std::time::Instant::now();
+        manager.shutdown(std::time::Duration::from_secs(1)).await.unwrap();
+        // 忽略 SIGTERM 的進程在寬限期結束後才被殺死
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        
+        // 自行退出的進程保留退出碼，且不會被重啟
+        let graceful = manager.get_process("graceful").unwrap();
+        assert_eq!(graceful.status, ProcessStatus::Stopped);
+        assert_eq!(graceful.exit_code, Some(0));
+        assert!(graceful.pid().is_none());
+        assert_eq!(manager.get_process("stubborn").unwrap().status, ProcessStatus::Stopped);
+        
+        // 退出前的最後一行輸出已經寫入日誌
+        let log = fs::read_to_string(temp_dir.path().join("graceful.log")).unwrap();
+        assert!(log.contains("cleaned up"), "{}", log);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_follows_dependency_order_and_stop_timeout() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("api-stopped");
+        let config_path = temp_dir.path().join("shutdown.toml");
+        // api 依賴 db：db 收到 SIGTERM 時 api 應已退出；stubborn 不理會 SIGTERM，只等待自己的 stop_timeout
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "db"
+        command = "sh"
+        args = ["-c", "trap 'if [ -f {marker} ]; then echo after api; else echo before api; fi; exit 0' TERM; while true; do sleep 0.1; done"]
+        
+        [[process]]
+        name = "api"
+        command = "sh"
+        args = ["-c", "trap 'sleep 0.3; touch {marker}; exit 0' TERM; while true; do sleep 0.1; done"]
+        requires = ["db"]
+        
+        [[process]]
+        name = "stubborn"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        stop_timeout = 1
+        "#, marker = marker.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler);
+        for name in ["db", "api", "stubborn"] {
+            manager.start_process(name).await.unwrap();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        
+        // 總期限遠長於 stubborn 的 stop_timeout，關閉在 stop_timeout 之後就結束
+        let started = std::time::Instant::now();
+        manager.shutdown(std::time::Duration::from_secs(10)).await.unwrap();
+        let elapsed = started.elapsed();
+        assert!(elapsed >= std::time::Duration::from_secs(1), "{:?}", elapsed);
+        assert!(elapsed < std::time::Duration::from_secs(5), "{:?}", elapsed);
+        for name in ["db", "api", "stubborn"] {
+            assert_eq!(manager.get_process(name).unwrap().status, ProcessStatus::Stopped);
+        }
+        
+        let log = fs::read_to_string(temp_dir.path().join("db.log")).unwrap();
+        assert!(log.contains("after api"), "{}", log);
+        let log = fs::read_to_string(temp_dir.path().join("stubborn.log")).unwrap();
+        assert!(log.contains("Did not stop within 1s, required SIGKILL"), "{}", log);
+        
+        // stop_timeout 超過總期限時，到期即殺死
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "stubborn"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        stop_timeout = 30
+        "#).unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler);
+        manager.start_process("stubborn").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        
+        let started = std::time::Instant::now();
+        manager.shutdown(std::time::Duration::from_secs(1)).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(manager.get_process("stubborn").unwrap().status, ProcessStatus::Stopped);
+        let log = fs::read_to_string(temp_dir.path().join("stubborn.log")).unwrap();
+        assert!(log.contains("Still running after shutdown_grace of 1s, killed"), "{}", log);
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_final_output_is_read_before_reaping() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("crash.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "crasher"
+        command = "sh"
+        args = ["-c", "i=0; while [ $i -lt 2000 ]; do echo line $i; i=$((i+1)); done; echo final words; exit 3"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("crasher").await.unwrap();
+        let pid = manager.get_process("crasher").unwrap().pid().unwrap();
+        
+        // 阻塞等待進程變為僵屍，期間讀取任務沒有機會運行，輸出全部留在管道中
+        let started = std::time::Instant::now();
+        loop {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+            if stat.rsplit(')').next().unwrap().split_whitespace().next() == Some("Z") {
+                break;
+            }
+            assert!(started.elapsed() < std::time::Duration::from_secs(5));
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("crasher").unwrap();
+        assert_eq!(process.exit_code, Some(3));
+        let run = process.run_history.back().unwrap();
+        assert_eq!(run.crash_context.last().map(String::as_str), Some("final words"));
+        
+        // 最後一行輸出寫在退出日誌之前
+        log_handler.drain().await;
+        let log = fs::read_to_string(temp_dir.path().join("crasher.log")).unwrap();
+        let final_line = log.find("final words").unwrap();
+        let exit_line = log.find("Process exited with code: 3").unwrap();
+        assert!(final_line < exit_line);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_expected_sha256_verification() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::executable::sha256;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::os::unix::fs::PermissionsExt;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("app.sh");
+        fs::write(&script, "#!/bin/sh\nsleep 5\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let digest = sha256(&script).unwrap();
+        let wrong = "0".repeat(64);
+        
+        let config_path = temp_dir.path().join("checksum.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "verified"
+        command = "{script}"
+        expected_sha256 = "{upper}"
+        
+        [[process]]
+        name = "tampered"
+        command = "{script}"
+        expected_sha256 = "{wrong}"
+        
+        [[process]]
+        name = "tolerated"
+        command = "{script}"
+        expected_sha256 = "{wrong}"
+        checksum_mismatch = "warn"
+        "#, script = script.display(), upper = digest.to_uppercase(), wrong = wrong)).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        // 大小寫不影響比較
+        manager.start_process("verified").await.unwrap();
+        assert_eq!(manager.get_process("verified").unwrap().status, ProcessStatus::Running);
+        
+        // 不匹配時默認拒絕啟動
+        assert!(manager.start_process("tampered").await.is_err());
+        assert_eq!(manager.get_process("tampered").unwrap().status, ProcessStatus::Failed);
+        
+        // checksum_mismatch = "warn" 只記錄警告
+        manager.start_process("tolerated").await.unwrap();
+        assert_eq!(manager.get_process("tolerated").unwrap().status, ProcessStatus::Running);
+        manager.stop_all().await.unwrap();
+        
+        // 格式錯誤的摘要在加載配置時被拒絕
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "bad"
+        command = "{}"
+        expected_sha256 = "abc123"
+        "#, script.display())).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_output_encoding_decoding() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("encoding.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "latin"
+        command = "printf"
+        args = ["caf\\351\\n"]
+        output_encoding = "latin1"
+        
+        [[process]]
+        name = "sjis"
+        command = "printf"
+        args = ["\\223\\372\\226\\173\\n"]
+        output_encoding = "shift_jis"
+        
+        [[process]]
+        name = "legacy"
+        command = "printf"
+        args = ["bad \\377 byte\\nnext line\\n"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        for name in ["latin", "sjis", "legacy"] {
+            manager.start_process(name).await.unwrap();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        
+        let read_log = |name: &str| fs::read_to_string(temp_dir.path().join(format!("{}.log", name))).unwrap();
+        assert!(read_log("latin").contains("café"));
+        assert!(read_log("sjis").contains("日本"));
+        
+        // 默認 UTF-8 下無效的字節被替換，後面的輸出不會丟失
+        let legacy = read_log("legacy");
+        assert!(legacy.contains("bad \u{FFFD} byte"), "{}", legacy);
+        assert!(legacy.contains("next line"));
+        
+        // 不能按行分割的編碼在加載配置時被拒絕
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "wide"
+        command = "true"
+        output_encoding = "utf-16le"
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_log_sampling() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("sampling.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "chatty"
+        command = "sh"
+        args = ["-c", "for i in $(seq 1 20); do echo \"DEBUG tick $i\"; echo \"INFO step $i\"; echo \"err $i\" >&2; done"]
+        
+        [process.logging]
+        stdout_sample = 5
+        level_sample = { debug = 10 }
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("chatty").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        log_handler.flush();
+        
+        let log = fs::read_to_string(temp_dir.path().join("chatty.log")).unwrap();
+        let count = |needle: &str| log.lines().filter(|line| line.contains(needle)).count();
+        // DEBUG 行按級別每 10 行記錄一行，其他 stdout 行每 5 行記錄一行，stderr 全部記錄
+        assert_eq!(count("DEBUG tick"), 2, "{}", log);
+        // 每組的第一行總是保留
+        assert!(log.lines().any(|line| line.ends_with("DEBUG tick 1")), "{}", log);
+        assert!(log.lines().any(|line| line.ends_with("DEBUG tick 11")), "{}", log);
+        assert_eq!(count("INFO step"), 4, "{}", log);
+        assert_eq!(count("err "), 20, "{}", log);
+        
+        // 未知級別和 0 抽樣率在加載配置時被拒絕
+        for logging in ["level_sample = { verbose = 10 }", "stderr_sample = 0"] {
+            fs::write(&config_path, format!(r#"
+            [[process]]
+            name = "bad"
+            command = "true"
+            
+            [process.logging]
+            {}
+            "#, logging)).unwrap();
+            assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err(), "{}", logging);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_exec_prefix_wraps_command() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("exec_prefix.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "wrapped"
+        command = "sh"
+        args = ["-c", "echo wrapper set $WRAPPED"]
+        exec_prefix = ["env", "WRAPPED=yes"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("wrapped").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        
+        // 包裝程序在實際命令之前運行
+        let log = fs::read_to_string(temp_dir.path().join("wrapped.log")).unwrap();
+        assert!(log.contains("wrapper set yes"), "{}", log);
+        
+        // 空的 exec_prefix 在加載配置時被拒絕
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "empty"
+        command = "true"
+        exec_prefix = []
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_raw_log_mode_passthrough() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let capture = temp_dir.path().join("raw-output.txt");
+        let config_path = temp_dir.path().join("raw.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "structured"
+        command = "sh"
+        args = ["-c", "echo '{{\"level\":\"info\"}}'; echo oops >&2"]
+        log_mode = "raw"
+        capture_to = "{}"
+        "#, capture.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("structured").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        log_handler.flush();
+        
+        // 輸出原樣寫入文件，沒有時間戳和前綴
+        assert_eq!(fs::read_to_string(&capture).unwrap(), "{\"level\":\"info\"}\noops\n");
+        // 日誌文件中只有系統消息
+        let log = fs::read_to_string(temp_dir.path().join("structured.log")).unwrap();
+        assert!(!log.contains("oops"), "{}", log);
+        
+        // 依賴逐行處理的選項與原始模式衝突
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "conflict"
+        command = "true"
+        log_mode = "raw"
+        merge_output = true
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_full_output_pipe_is_reported() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("pipe.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "bursty"
+        command = "sh"
+        args = ["-c", "head -c 1000000 /dev/zero | tr '\\0' 'x'; sleep 5"]
+        pipe_size = 131072
+        read_buffer_size = 65536
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("bursty").await.unwrap();
+        
+        // 阻塞運行時，讀取任務無法運行，進程很快寫滿管道
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        manager.check_processes().await.unwrap();
+        
+        // 讀取任務追上之後報告恢復
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        manager.check_processes().await.unwrap();
+        manager.stop_all().await.unwrap();
+        log_handler.flush();
+        
+        let log = fs::read_to_string(temp_dir.path().join("bursty.log")).unwrap();
+        assert!(log.contains("Output pipe full: process is blocked writing stdout"), "{}", log);
+        assert!(log.contains("Output pipe drained"), "{}", log);
+    }
+    
+    #[test]
+    fn test_env_allow_and_deny_filtering() {
+        use janus::process::environment::filter_inherited;
+        
+        let vars = vec![
+            ("AWS_REGION".to_string(), "eu-west-1".to_string()),
+            ("AWS_SECRET_ACCESS_KEY".to_string(), "secret".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        
+        let allow = vec!["AWS_*".to_string()];
+        let deny = vec!["*SECRET*".to_string()];
+        let filtered = filter_inherited(vars.clone(), Some(&allow), Some(&deny));
+        assert_eq!(filtered, vec![("AWS_REGION".to_string(), "eu-west-1".to_string())]);
+        
+        // 只設置 env_deny 時其餘變量全部繼承
+        let filtered = filter_inherited(vars, None, Some(&deny));
+        assert_eq!(filtered.len(), 2);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_env_dirs_from_mounted_files() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::environment::read_env_dir;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        // 模擬 Kubernetes 的 Secret 卷：可見文件是指向 ..data 目錄的符號鏈接
+        let secrets = temp_dir.path().join("secrets");
+        fs::create_dir_all(secrets.join("..data")).unwrap();
+        fs::write(secrets.join("..data/API_KEY"), "s3cr3t\n").unwrap();
+        fs::write(secrets.join("..data/DB_HOST"), "db.internal").unwrap();
+        std::os::unix::fs::symlink("..data/API_KEY", secrets.join("API_KEY")).unwrap();
+        std::os::unix::fs::symlink("..data/DB_HOST", secrets.join("DB_HOST")).unwrap();
+        fs::write(secrets.join(".hidden"), "ignored").unwrap();
+        
+        let vars = read_env_dir(&secrets).unwrap();
+        assert_eq!(vars, vec![
+            ("API_KEY".to_string(), "s3cr3t".to_string()),
+            ("DB_HOST".to_string(), "db.internal".to_string()),
+        ]);
+        
+        // env 中的同名變量優先
+        let config_path = temp_dir.path().join("env_dirs.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "app"
+        command = "sh"
+        args = ["-c", "echo \"$API_KEY $DB_HOST\""]
+        env_dirs = ["{}"]
+        env = {{ DB_HOST = "override" }}
+        
+        [[process]]
+        name = "unmounted"
+        command = "true"
+        env_dirs = ["{}/missing"]
+        "#, secrets.display(), temp_dir.path().display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("app").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        let log = fs::read_to_string(temp_dir.path().join("app.log")).unwrap();
+        assert!(log.contains("s3cr3t override"), "{}", log);
+        
+        // 目錄不存在時啟動失敗
+        assert!(manager.start_process("unmounted").await.is_err());
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_env_cache_reuses_unchanged_sources() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::environment::EnvCache;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let secrets = temp_dir.path().join("secrets");
+        fs::create_dir_all(&secrets).unwrap();
+        fs::write(secrets.join("API_KEY"), "first").unwrap();
+        
+        // 文件沒有變化時使用上次讀到的值，內容變化後重新讀取
+        let mut cache = EnvCache::default();
+        let first = vec![("API_KEY".to_string(), "first".to_string())];
+        assert_eq!(cache.read_env_dir(&secrets).unwrap(), first);
+        assert_eq!(cache.read_env_dir(&secrets).unwrap(), first);
+        assert_eq!((cache.hits, cache.misses), (1, 1));
+        fs::write(secrets.join("API_KEY"), "rotated").unwrap();
+        assert_eq!(cache.read_env_dir(&secrets).unwrap(), vec![("API_KEY".to_string(), "rotated".to_string())]);
+        assert_eq!((cache.hits, cache.misses), (1, 2));
+        
+        // 替換為大小相同、修改時間被改回原值的文件時，inode 和 ctime 不同，同樣重新讀取
+        let modified = fs::metadata(secrets.join("API_KEY")).unwrap().modified().unwrap();
+        let replacement = temp_dir.path().join("API_KEY");
+        fs::write(&replacement, "renewed").unwrap();
+        fs::File::options().write(true).open(&replacement).unwrap().set_modified(modified).unwrap();
+        fs::rename(&replacement, secrets.join("API_KEY")).unwrap();
+        assert_eq!(cache.read_env_dir(&secrets).unwrap(), vec![("API_KEY".to_string(), "renewed".to_string())]);
+        assert_eq!((cache.hits, cache.misses), (1, 3));
+        
+        // 重啟時命中緩存，次數出現在狀態快照中
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "app"
+        command = "sleep"
+        args = ["30"]
+        env_dirs = ["{}"]
+        "#, secrets.display())).unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("app").await.unwrap();
+        manager.restart_process("app").await.unwrap();
+        let snapshot = manager.snapshot();
+        let app = snapshot.processes.iter().find(|process| process.name == "app").unwrap();
+        assert_eq!((app.env_cache_hits, app.env_cache_misses), (1, 1));
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[test]
+    fn test_wait_for_endpoint_parsing() {
+        use janus::process::dependency::Endpoint;
+        
+        assert_eq!(
+            Endpoint::parse("tcp://db:5432").unwrap(),
+            Endpoint::Tcp { address: "db:5432".to_string() }
+        );
+        assert_eq!(
+            Endpoint::parse("http://minio/health").unwrap(),
+            Endpoint::Http {
+                endpoint: janus::process::http::Endpoint::parse("http://minio").unwrap(),
+                path: "/health".to_string(),
+            }
+        );
+        assert_eq!(
+            Endpoint::parse("http://minio:9000").unwrap(),
+            Endpoint::Http {
+                endpoint: janus::process::http::Endpoint::parse("http://minio:9000").unwrap(),
+                path: "/".to_string(),
+            }
+        );
+        assert!(Endpoint::parse("https://minio:9000").is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_wait_for_http_and_response_limit() {
+        use janus::logging::handler::LogHandler;
+        use janus::process::dependency::wait_for_endpoints;
+        use janus::process::http::Endpoint;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        // 讀完請求頭後返回給定的響應並關閉連接
+        let serve = |response: Vec<u8>| async move {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let response = response.clone();
+                    tokio::spawn(async move {
+                        let mut request = Vec::new();
+                        let mut buffer = [0u8; 1024];
+                        while !request.ends_with(b"\r\n\r\n") {
+                            let read = stream.read(&mut buffer).await.unwrap();
+                            if read == 0 {
+                                break;
+                            }
+                            request.extend_from_slice(&buffer[..read]);
+                        }
+                        let _ = stream.write_all(&response).await;
+                    });
+                }
+            });
+            format!("http://{}", address)
+        };
+        
+        // wait_for 的 http 端點經由共用的 HTTP 客戶端探測
+        let healthy = serve(b"HTTP/1.0 204 No Content\r\n\r\n".to_vec()).await;
+        let log_handler = LogHandler::new("info");
+        let endpoints = vec![format!("{}/health", healthy)];
+        wait_for_endpoints("app", &endpoints, std::time::Duration::from_secs(5), &log_handler).await.unwrap();
+        
+        // 超過上限的響應返回錯誤，不會全部讀入內存
+        let mut huge = b"HTTP/1.0 200 OK\r\n\r\n".to_vec();
+        huge.resize(4 * 1024 * 1024, b'x');
+        let endpoint = Endpoint::parse(&serve(huge).await).unwrap();
+        let error = endpoint.send("GET / HTTP/1.0\r\n\r\n", None).await.unwrap_err();
+        assert!(error.contains("larger than"), "{}", error);
+    }
+    
+    #[tokio::test]
+    async fn test_wait_for_path() {
+        use janus::logging::handler::LogHandler;
+        use janus::process::dependency::{resolve_path, wait_for_paths};
+        use std::path::Path;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let token = temp_dir.path().join("secrets/token");
+        let log_handler = LogHandler::new("info");
+        
+        // 相對路徑按工作目錄解析
+        assert_eq!(resolve_path(Path::new("data/db"), Some("/srv")), Path::new("/srv/data/db"));
+        assert_eq!(resolve_path(Path::new("/data/db"), Some("/srv")), Path::new("/data/db"));
+        
+        // 模擬稍後才掛載的密鑰
+        let mounted = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            fs::create_dir_all(mounted.parent().unwrap()).unwrap();
+            fs::write(&mounted, "abc").unwrap();
+        });
+        wait_for_paths("app", &[token], std::time::Duration::from_secs(5), &log_handler).await.unwrap();
+        
+        // 超時後返回錯誤
+        let missing = temp_dir.path().join("never");
+        let result = wait_for_paths("app", &[missing], std::time::Duration::from_secs(1), &log_handler).await;
+        assert!(result.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_start_waits_for_dependencies_without_lock() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let token = temp_dir.path().join("token");
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "app"
+        command = "sleep"
+        args = ["30"]
+        wait_for_path = ["{}"]
+        wait_timeout = 10
+        "#, token.display())).unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let pid = |manager: &ProcessManager| manager.get_process("app").unwrap().pid();
+        
+        // 監控循環內部的啟動不等待依賴，依賴就緒後在之後的一輪中啟動
+        manager.lock().await.start_process("app").await.unwrap();
+        manager.lock().await.check_processes().await.unwrap();
+        assert!(pid(&*manager.lock().await).is_none());
+        fs::write(&token, "abc").unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while pid(&*manager.lock().await).is_none() {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            manager.lock().await.check_processes().await.unwrap();
+        }
+        manager.lock().await.stop_process_manually("app", None).await.unwrap();
+        fs::remove_file(&token).unwrap();
+        
+        // gRPC 和 CLI 的啟動等待依賴時不持有鎖，依賴就緒後才返回
+        let start = tokio::spawn({
+            let manager = manager.clone();
+            async move { ProcessManager::start_when_ready(&manager, "app").await }
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!start.is_finished());
+        assert!(manager.try_lock().is_ok());
+        fs::write(&token, "abc").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), start).await.unwrap().unwrap().unwrap();
+        assert!(pid(&*manager.lock().await).is_some());
+        
+        manager.lock().await.stop_all().await.unwrap();
+    }
+    
+    #[test]
+    fn test_config_lint_levels() {
+        use janus::config::lint::{lint, Severity};
+        use janus::config::manager::ConfigManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("lint_config.toml");
+        
+        fs::write(&config_path, format!(r#"
+        [global]
+        log_dir = "{}"
+        
+        [global.env]
+        BASE = "/srv"
+        
+        [[process]]
+        name = "web"
+        command = "nginx"
+        auto_restart = true
+        restart_delay = 0
+        
+        [process.env]
+        ROOT = "${{BASE}}/www"
+        URL = "http://$JANUS_LINT_UNDEFINED:80"
+        
+        [[process]]
+        name = "api"
+        command = "api"
+        auto_restart = true
+        restart_limit = 3
+        "#, temp_dir.path().display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let diagnostics = lint(&config_manager);
+        let messages = |severity| -> Vec<String> {
+            diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == severity)
+                .map(|diagnostic| format!("{}: {}", diagnostic.process.as_deref().unwrap_or("-"), diagnostic.message))
+                .collect()
+        };
+        
+        // api 的配置沒有問題，BASE 在全局 env 中已定義
+        assert_eq!(messages(Severity::Warning), vec![
+            "web: restart_delay is 0, a crashing process will be restarted in a tight loop",
+            "web: auto_restart without restart_limit or restart_budget restarts forever",
+            "web: env URL references undefined variable JANUS_LINT_UNDEFINED",
+        ]);
+        assert!(messages(Severity::Info).is_empty());
+    }
+    
+    #[test]
+    fn test_dependency_graph() {
+        use janus::cli::graph::DependencyGraph;
+        use janus::config::manager::ConfigManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("graph.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        requires = ["api", "cache"]
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        requires = ["db"]
+        wait_for = ["tcp://db.internal:5432"]
+        
+        [[process]]
+        name = "cache"
+        command = "sleep"
+        requires = ["db"]
+        
+        [[process]]
+        name = "db"
+        command = "sleep"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let graph = DependencyGraph::new(config_manager.get_process_configs());
+        assert!(graph.problems().is_empty());
+        let tree = graph.render_tree();
+        assert!(tree.starts_with("web\n├── api\n│   ├── db\n│   └── tcp://db.internal:5432 (external)\n└── cache\n    └── db\n"));
+        assert!(tree.contains("Start order: db, api, cache, web"));
+        let dot = graph.render_dot();
+        assert!(dot.contains("\"web\" -> \"api\";"));
+        assert!(dot.contains("\"api\" -> \"tcp://db.internal:5432\" [style=dashed];"));
+        
+        // 有環和未知進程的配置無法加載，graph 仍然能顯示並指出問題
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "a"
+        command = "sleep"
+        requires = ["b"]
+        
+        [[process]]
+        name = "b"
+        command = "sleep"
+        requires = ["a", "ghost"]
+        
+        [[process]]
+        name = "c"
+        command = "sleep"
+        requires = ["b"]
+        
+        [[process]]
+        name = "d"
+        command = "sleep"
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        let config_manager = ConfigManager::load(config_path.to_str().unwrap()).unwrap();
+        let graph = DependencyGraph::new(config_manager.get_process_configs());
+        assert_eq!(graph.problems(), vec![
+            "circular requires: a -> b -> a".to_string(),
+            "b requires unknown process ghost".to_string(),
+            "cannot be started: a, b, c".to_string(),
+        ]);
+        assert!(graph.render_tree().contains("    │   └── b (cycle)\n    └── ghost (unknown process)\n"));
+        assert!(graph.render_dot().contains("\"b\" -> \"a\" [color=red];"));
+        
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "graph"])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("circular requires"));
+    }
+    
+    #[tokio::test]
+    async fn test_vault_secret_references() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::secrets::{SecretResolver, VaultProvider};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::{Arc, Mutex};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        
+        // 模擬 Vault 的 KV v2 接口，記錄收到的請求；available 為 false 時返回 503
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let available = Arc::new(AtomicBool::new(true));
+        {
+            let requests = requests.clone();
+            let available = available.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let read = stream.read(&mut buffer).await.unwrap();
+                        if read == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+                    requests.lock().unwrap().push(String::from_utf8_lossy(&request).into_owned());
+                    let response = if available.load(Ordering::SeqCst) {
+                        let body = r#"{"data":{"data":{"password":"hunter2","user":"admin"},"metadata":{"version":3}}}"#;
+                        format!("HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}", body)
+                    } else {
+                        "HTTP/1.0 503 Service Unavailable\r\n\r\n{\"errors\":[\"Vault is sealed\"]}".to_string()
+                    };
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                }
+            });
+        }
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("vault.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "app"
+        command = "sh"
+        args = ["-c", "echo \"$DB_USER:$DB_PASSWORD\""]
+        env = { DB_PASSWORD = "vault:secret/data/app#password", DB_USER = "vault:secret/data/app#user" }
+        "#).unwrap();
+        let resolver = || SecretResolver::new(vec![Box::new(VaultProvider::new(&address, Some("s.token")))]);
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone()).with_secret_resolver(resolver());
+        manager.start_process("app").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        log_handler.flush();
+        let log = fs::read_to_string(temp_dir.path().join("app.log")).unwrap();
+        assert!(log.contains("admin:hunter2"), "{}", log);
+        
+        // 同一路徑的兩個字段只讀取一次
+        {
+            let requests = requests.lock().unwrap();
+            assert_eq!(requests.len(), 1);
+            assert!(requests[0].starts_with("GET /v1/secret/data/app "), "{}", requests[0]);
+            assert!(requests[0].contains("X-Vault-Token: s.token\r\n"));
+        }
+        
+        // 重啟時重新讀取，Vault 不可用時使用緩存的值
+        available.store(false, Ordering::SeqCst);
+        manager.check_processes().await.unwrap();
+        manager.start_process("app").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        log_handler.flush();
+        let log = fs::read_to_string(temp_dir.path().join("app.log")).unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        assert!(log.contains("Vault is sealed, using the cached value"), "{}", log);
+        assert_eq!(log.matches("admin:hunter2").count(), 2, "{}", log);
+        
+        // 沒有緩存時啟動失敗
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone()).with_secret_resolver(resolver());
+        assert!(manager.start_process("app").await.is_err());
+        
+        // 格式錯誤的引用在加載配置時被拒絕
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "app"
+        command = "true"
+        env = { DB_PASSWORD = "vault:secret/data/app" }
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[test]
+    fn test_encrypted_config_values() {
+        use age::secrecy::ExposeSecret;
+        use janus::config::secret::{self, DataKey};
+        use std::io::Write;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let identity = age::x25519::Identity::generate();
+        let identity_path = temp_dir.path().join("keys.txt");
+        fs::write(&identity_path, identity.to_string().expose_secret()).unwrap();
+        let identities: Vec<Box<dyn age::Identity>> = vec![Box::new(identity.clone())];
+        
+        // 不讀取開發機上 sops 默認位置的身份
+        let janus = |args: &[&str], identity_file: Option<&std::path::Path>| {
+            let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_janus"));
+            command
+                .args(args)
+                .env_remove(secret::AGE_KEY_ENV)
+                .env_remove(secret::AGE_KEY_FILE_ENV)
+                .env("XDG_CONFIG_HOME", temp_dir.path());
+            if let Some(identity_file) = identity_file {
+                command.env(secret::AGE_KEY_FILE_ENV, identity_file);
+            }
+            command.output().unwrap()
+        };
+        
+        // 為 age 接收者生成 [sops] 表，再用配置文件中的數據密鑰加密值
+        let recipient = identity.to_public().to_string();
+        let output = janus(&["secret", "init", "--age", &recipient], None);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let metadata = String::from_utf8(output.stdout).unwrap();
+        assert!(metadata.contains(&recipient) && metadata.contains("BEGIN AGE ENCRYPTED FILE"), "{}", metadata);
+        let config_path = temp_dir.path().join("secret.toml");
+        fs::write(&config_path, &metadata).unwrap();
+        let config_arg = config_path.to_str().unwrap();
+        let encrypt = |path: &str| {
+            let output = janus(&["--config", config_arg, "secret", "encrypt", "--path", path, "hunter2"], Some(&identity_path));
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        let encrypted = encrypt("process.env.TOKEN");
+        assert!(encrypted.starts_with("ENC[AES256_GCM,data:") && encrypted.ends_with(",type:str]"), "{}", encrypted);
+        assert!(!encrypted.contains("hunter2"));
+        // 必須指定值所在的鍵路徑，並且需要能解密數據密鑰的身份
+        assert!(!janus(&["--config", config_arg, "secret", "encrypt", "hunter2"], Some(&identity_path)).status.success());
+        assert!(!janus(&["--config", config_arg, "secret", "encrypt", "--path", "process.env.TOKEN", "x"], None).status.success());
+        
+        let metadata_value: toml::Value = toml::from_str(&metadata).unwrap();
+        let key = DataKey::from_metadata(&metadata_value[secret::METADATA_KEY], &identities).unwrap();
+        assert_eq!(key.decrypt(&encrypted, "process:env:TOKEN:").unwrap().as_str(), Some("hunter2"));
+        // 鍵路徑作為附加數據參與認證，密文不能挪到其他鍵下
+        assert!(key.decrypt(&encrypted, "process:env:OTHER:").is_err());
+        let other: Vec<Box<dyn age::Identity>> = vec![Box::new(age::x25519::Identity::generate())];
+        assert!(DataKey::from_metadata(&metadata_value[secret::METADATA_KEY], &other).is_err());
+        
+        // 加密值可以出現在任何字符串位置，加載時解密；鍵路徑不含數組下標
+        let encrypted_arg = encrypt("process.args");
+        let config = format!(r#"
+        [[process]]
+        name = "api"
+        command = "sleep"
+        args = ["{encrypted_arg}"]
+        env = {{ TOKEN = "{encrypted}" }}
+        
+        {metadata}"#);
+        let mut value: toml::Value = toml::from_str(&config).unwrap();
+        value.as_table_mut().unwrap().remove(secret::METADATA_KEY);
+        secret::decrypt_all(&mut value, &key).unwrap();
+        assert_eq!(value["process"][0]["env"]["TOKEN"].as_str(), Some("hunter2"));
+        assert_eq!(value["process"][0]["args"][0].as_str(), Some("hunter2"));
+        
+        // 交換兩個密文後拒絕加載
+        let swapped = config.replace(&encrypted, "SWAP").replace(&encrypted_arg, &encrypted).replace("SWAP", &encrypted_arg);
+        let mut value: toml::Value = toml::from_str(&swapped).unwrap();
+        value.as_table_mut().unwrap().remove(secret::METADATA_KEY);
+        assert!(secret::decrypt_all(&mut value, &key).is_err());
+        
+        fs::write(&config_path, &config).unwrap();
+        let validate = ["--config", config_arg, "validate"];
+        let output = janus(&validate, Some(&identity_path));
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+        
+        // 沒有身份或身份不匹配時拒絕加載
+        let output = janus(&validate, None);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains(secret::AGE_KEY_FILE_ENV));
+        let wrong_path = temp_dir.path().join("wrong.txt");
+        fs::write(&wrong_path, age::x25519::Identity::generate().to_string().expose_secret()).unwrap();
+        let output = janus(&validate, Some(&wrong_path));
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("None of the age identities"));
+        
+        // sops 生成的值：32 字節 IV 的 AES-256-GCM，鍵路徑以 : 連接，並按 type 還原類型。
+        // 數據密鑰與 sops 一樣用 age 加密為 ASCII armor
+        let mut enc = Vec::new();
+        let encryptor = age::Encryptor::with_recipients(std::iter::once(&identity.to_public() as &dyn age::Recipient)).unwrap();
+        let armored = age::armor::ArmoredWriter::wrap_output(&mut enc, age::armor::Format::AsciiArmor).unwrap();
+        let mut writer = encryptor.wrap_output(armored).unwrap();
+        writer.write_all(&(0u8..32).collect::<Vec<u8>>()).unwrap();
+        writer.finish().unwrap().finish().unwrap();
+        let sops_config = format!(r#"
+        [[process]]
+        name = "api"
+        command = "sleep"
+        env = {{ TOKEN = "ENC[AES256_GCM,data:f7SfPOh95A==,iv:ICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj8=,tag:03cqdS+lTKy04/AH7Ij3/g==,type:str]" }}
+        restart_limit = "ENC[AES256_GCM,data:UQ==,iv:QEFCQ0RFRkdISUpLTE1OT1BRUlNUVVZXWFlaW1xdXl8=,tag:9VqyY+XX86K7nJoKYUqqig==,type:int]"
+        auto_restart = "ENC[AES256_GCM,data:Pwv3GA==,iv:YGFiY2RlZmdoaWprbG1ub3BxcnN0dXZ3eHl6e3x9fn8=,tag:Wi9+UmKmt/qCuU3PRoKsXA==,type:bool]"
+        
+        [sops]
+        version = "3.9.0"
+        
+        [[sops.age]]
+        recipient = "{recipient}"
+        enc = """
+        {}"""
+        "#, String::from_utf8(enc).unwrap());
+        let mut value: toml::Value = toml::from_str(&sops_config).unwrap();
+        let metadata = value.as_table_mut().unwrap().remove(secret::METADATA_KEY).unwrap();
+        let key = DataKey::from_metadata(&metadata, &identities).unwrap();
+        secret::decrypt_all(&mut value, &key).unwrap();
+        assert_eq!(value["process"][0]["env"]["TOKEN"].as_str(), Some("hunter2"));
+        assert_eq!(value["process"][0]["restart_limit"].as_integer(), Some(5));
+        assert_eq!(value["process"][0]["auto_restart"].as_bool(), Some(true));
+        fs::write(&config_path, &sops_config).unwrap();
+        let output = janus(&validate, Some(&identity_path));
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+        
+        // 有加密值但沒有 [sops] 表時拒絕加載
+        fs::write(&config_path, sops_config.split("[sops]").next().unwrap()).unwrap();
+        let output = janus(&validate, Some(&identity_path));
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("no [sops] metadata"));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_render_templates_before_start() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::render::render;
+        use std::os::unix::fs::PermissionsExt;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let template = temp_dir.path().join("nginx.conf.tmpl");
+        let target = temp_dir.path().join("conf/nginx.conf");
+        fs::write(&template, "listen {{ PORT }};\nworker_processes {{WORKERS:-4}};\nserver_name {{ SERVER_NAME }};\nproxy_set_header Host $host;\n").unwrap();
+        
+        let config_path = temp_dir.path().join("render.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "web"
+        command = "cat"
+        args = ["{target}"]
+        env = {{ PORT = "8080", SERVER_NAME = "from-env" }}
+        
+        [[process.render]]
+        template = "{template}"
+        target = "{target}"
+        vars = {{ SERVER_NAME = "example.com" }}
+        mode = "0600"
+        
+        [[process]]
+        name = "broken"
+        command = "true"
+        
+        [[process.render]]
+        template = "{template}"
+        target = "{broken}"
+        "#, template = template.display(), target = target.display(), broken = temp_dir.path().join("broken.conf").display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("web").await.unwrap();
+        
+        // 進程啟動時文件已經生成，vars 優先於 env，未設置的變量使用默認值，其他 $ 原樣保留
+        let rendered = fs::read_to_string(&target).unwrap();
+        assert_eq!(rendered, "listen 8080;\nworker_processes 4;\nserver_name example.com;\nproxy_set_header Host $host;\n");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        log_handler.flush();
+        let log = fs::read_to_string(temp_dir.path().join("web.log")).unwrap();
+        assert!(log.contains("server_name example.com;"), "{}", log);
+        
+        // 缺少變量時拒絕啟動，不留下目標文件
+        assert!(manager.start_process("broken").await.is_err());
+        assert!(!temp_dir.path().join("broken.conf").exists());
+        log_handler.flush();
+        let log = fs::read_to_string(temp_dir.path().join("broken.log")).unwrap();
+        assert!(log.contains("variable PORT is not set"), "{}", log);
+        
+        assert!(render("a {{ B", &Default::default()).unwrap_err().contains("unterminated"));
+        assert!(render("{{ not a name }}", &Default::default()).unwrap_err().contains("invalid placeholder"));
+        
+        // 無效的權限在加載配置時被拒絕
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "true"
+        
+        [[process.render]]
+        template = "a"
+        target = "b"
+        mode = "rw-r--r--"
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_auto_port_allocation() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("ports.toml");
+        fs::write(&config_path, r#"
+        [global]
+        port_range = "39100-39120"
+        
+        [[process]]
+        name = "a"
+        command = "sh"
+        args = ["-c", "echo port=$PORT"]
+        port = "auto"
+        
+        [[process]]
+        name = "b"
+        command = "sh"
+        args = ["-c", "echo port=$HTTP_PORT"]
+        port = "auto"
+        port_env = "HTTP_PORT"
+        
+        [[process]]
+        name = "fixed"
+        command = "sh"
+        args = ["-c", "echo port=$PORT"]
+        port = 39100
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        for name in ["a", "b", "fixed"] {
+            manager.start_process(name).await.unwrap();
+        }
+        let port = |manager: &ProcessManager, name: &str| manager.get_process(name).unwrap().port.unwrap();
+        let (a, b) = (port(&manager, "a"), port(&manager, "b"));
+        
+        // 分配的端口在範圍內、互不相同，並且避開其他進程的固定端口
+        assert_eq!(port(&manager, "fixed"), 39100);
+        assert!((39101..=39120).contains(&a) && (39101..=39120).contains(&b), "{} {}", a, b);
+        assert_ne!(a, b);
+        let snapshot = manager.snapshot();
+        assert!(snapshot.processes.iter().any(|process| process.name == "b" && process.port == Some(b)));
+        
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        log_handler.flush();
+        for (name, expected) in [("a", a), ("b", b), ("fixed", 39100)] {
+            let log = fs::read_to_string(temp_dir.path().join(format!("{}.log", name))).unwrap();
+            assert!(log.contains(&format!("port={}", expected)), "{}", log);
+        }
+        
+        // 重啟時沿用原來的端口；端口被其他程序佔用時換一個
+        manager.check_processes().await.unwrap();
+        manager.start_process("a").await.unwrap();
+        assert_eq!(port(&manager, "a"), a);
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        manager.check_processes().await.unwrap();
+        let _occupied = std::net::TcpListener::bind(("0.0.0.0", a)).unwrap();
+        manager.start_process("a").await.unwrap();
+        let moved = port(&manager, "a");
+        assert!(moved != a && moved != b, "{}", moved);
+        
+        // 重複的固定端口、與 env 衝突的變量和無效的範圍在加載配置時被拒絕
+        for config in [
+            "[[process]]\nname = \"x\"\ncommand = \"true\"\nport = 8080\n[[process]]\nname = \"y\"\ncommand = \"true\"\nport = 8080",
+            "[[process]]\nname = \"x\"\ncommand = \"true\"\nport = \"auto\"\nenv = { PORT = \"1\" }",
+            "[global]\nport_range = \"30000-20000\"",
+            "[[process]]\nname = \"x\"\ncommand = \"true\"\nport = \"any\"",
+        ] {
+            fs::write(&config_path, config).unwrap();
+            assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err(), "{}", config);
+        }
+    }
+    
+    #[test]
+    fn test_requires_order_and_dependents() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("requires_config.toml");
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        requires = ["api"]
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        requires = ["db"]
+        
+        [[process]]
+        name = "db"
+        command = "sleep"
+        
+        [[process]]
+        name = "worker"
+        command = "sleep"
+        requires = ["db"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        // 被依賴的進程先啟動，依賴者先停止
+        let selected: Vec<String> = ["web", "worker", "db", "api"].iter().map(|name| name.to_string()).collect();
+        assert_eq!(manager.start_order(&selected), vec!["db", "api", "web", "worker"]);
+        assert_eq!(manager.dependents("db"), vec!["web", "api", "worker"]);
+        assert!(manager.dependents("web").is_empty());
+        
+        // 循環依賴和未知進程在加載配置時報錯
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "a"
+        command = "sleep"
+        requires = ["b"]
+        
+        [[process]]
+        name = "b"
+        command = "sleep"
+        requires = ["a"]
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "a"
+        command = "sleep"
+        requires = ["missing"]
+        "#).unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_control_listener_requires_client_cert() {
+        use janus::config::manager::ConfigManager;
+        use janus::control::{ControlListener, Response};
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+        use std::sync::Arc;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::TcpStream;
+        use tokio::sync::Mutex;
+        use tokio_rustls::rustls::crypto::ring;
+        use tokio_rustls::rustls::pki_types::pem::PemObject;
+        use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+        use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+        use tokio_rustls::TlsConnector;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        
+        // 生成 CA，並用它簽發服務端和客戶端證書
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        let server_key = KeyPair::generate().unwrap();
+        let server = CertificateParams::new(vec!["localhost".to_string()])
+            .unwrap()
+            .signed_by(&server_key, &ca, &ca_key)
+            .unwrap();
+        let client_key = KeyPair::generate().unwrap();
+        let client = CertificateParams::new(vec!["central".to_string()])
+            .unwrap()
+            .signed_by(&client_key, &ca, &ca_key)
+            .unwrap();
+        
+        fs::write(dir.join("ca.pem"), ca.pem()).unwrap();
+        fs::write(dir.join("server.pem"), server.pem()).unwrap();
+        fs::write(dir.join("server.key"), server_key.serialize_pem()).unwrap();
+        
+        let config_path = dir.join("control.toml");
+        fs::write(&config_path, format!(r#"
+        [global.control]
+        listen = "127.0.0.1:0"
+        cert = "{dir}/server.pem"
+        key = "{dir}/server.key"
+        client_ca = "{dir}/ca.pem"
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        "#, dir = dir.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let control = config_manager.get_global_config().control.clone().unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let listener = ControlListener::bind(&control).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(listener.run(manager.clone()));
+        
+        let mut roots = RootCertStore::empty();
+        roots.add(CertificateDer::from_pem_slice(ca.pem().as_bytes()).unwrap()).unwrap();
+        let builder = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots);
+        let server_name = ServerName::try_from("localhost").unwrap();
+        
+        // 帶客戶端證書時可以查詢狀態和重啟進程
+        let client_config = builder
+            .clone()
+            .with_client_auth_cert(
+                vec![CertificateDer::from_pem_slice(client.pem().as_bytes()).unwrap()],
+                PrivateKeyDer::from_pem_slice(client_key.serialize_pem().as_bytes()).unwrap(),
+            )
+            .unwrap();
+        let stream = TcpStream::connect(address).await.unwrap();
+        let stream = TlsConnector::from(Arc::new(client_config))
+            .connect(server_name.clone(), stream)
+            .await
+            .unwrap();
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+        
+        writer.write_all(b"{\"command\":\"restart\",\"name\":\"web\"}\n").await.unwrap();
+        let response: Response = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(response.ok);
+        
+        writer.write_all(b"{\"command\":\"status\"}\n").await.unwrap();
+        let response: Response = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        let processes = &response.result.unwrap()["processes"];
+        assert_eq!(processes[0]["name"], "web");
+        assert_eq!(processes[0]["status"], "Running");
+        
+        writer.write_all(b"{\"command\":\"restart\",\"name\":\"missing\"}\n").await.unwrap();
+        let response: Response = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(!response.ok);
+        assert!(response.error.unwrap().contains("missing"));
+        
+        // 沒有客戶端證書時服務端拒絕連接
+        let client_config = builder.with_no_client_auth();
+        let stream = TcpStream::connect(address).await.unwrap();
+        let result = match TlsConnector::from(Arc::new(client_config)).connect(server_name, stream).await {
+            Ok(stream) => {
+                let (reader, mut writer) = tokio::io::split(stream);
+                let _ = writer.write_all(b"{\"command\":\"status\"}\n").await;
+                BufReader::new(reader).lines().next_line().await
+            }
+            Err(e) => Err(e),
+        };
+        assert!(!matches!(result, Ok(Some(_))));
+        
+        manager.lock().await.stop_process("web").await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_status_does_not_wait_for_busy_manager() {
+        use janus::config::manager::ConfigManager;
+        use janus::control::{handle_connection, Response};
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("busy.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        manager.lock().await.start_process("web").await.unwrap();
+        let status = manager.lock().await.subscribe_status();
+        
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(handle_connection(server, manager.clone(), status));
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+        
+        // 模擬耗時的停止或重啟操作一直持有鎖，status 仍返回最近發布的狀態
+        let busy = manager.lock().await;
+        writer.write_all(b"{\"command\":\"status\"}\n").await.unwrap();
+        let line = tokio::time::timeout(std::time::Duration::from_secs(1), lines.next_line())
+            .await
+            .expect("status waited for the manager lock")
+            .unwrap()
+            .unwrap();
+        let response: Response = serde_json::from_str(&line).unwrap();
+        assert_eq!(response.result.unwrap()["processes"][0]["status"], "Running");
+        drop(busy);
+        
+        // 停止之後發布的快照反映新狀態
+        manager.lock().await.stop_process("web").await.unwrap();
+        writer.write_all(b"{\"command\":\"status\"}\n").await.unwrap();
+        let response: Response = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response.result.unwrap()["processes"][0]["status"], "Stopped");
+    }
+    
+    #[tokio::test]
+    async fn test_process_registry_file() {
+        use janus::config::manager::ConfigManager;
+        use janus::control::{handle_connection, Response};
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::registry::{self, Registry};
+        use std::sync::Arc;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("registry.toml");
+        let registry_path = temp_dir.path().join("registry.json");
+        fs::write(&config_path, format!(r#"
+        [global]
+        port_range = "39200-39220"
+        registry_file = "{}"
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        args = ["10"]
+        port = "auto"
+        
+        [[process]]
+        name = "worker"
+        command = "sleep"
+        args = ["10"]
+        "#, registry_path.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info");
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, log_handler.clone())));
+        let status = manager.lock().await.subscribe_status();
+        tokio::spawn(registry::watch(registry_path.clone(), status.clone(), log_handler));
+        manager.lock().await.start_process("api").await.unwrap();
+        manager.lock().await.start_process("worker").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        
+        let read = || -> Registry { serde_json::from_str(&fs::read_to_string(&registry_path).unwrap()).unwrap() };
+        let registry = read();
+        assert_eq!(registry.supervisor_pid, std::process::id());
+        let api = &registry.processes["api"];
+        let (api_pid, api_port) = {
+            let manager = manager.lock().await;
+            let process = manager.get_process("api").unwrap();
+            (process.pid(), process.port)
+        };
+        assert_eq!((api.status.as_str(), api.pid, api.port), ("Running", api_pid, api_port));
+        assert!(api.port.is_some());
+        assert_eq!(registry.processes["worker"].port, None);
+        
+        // 停止後更新註冊表
+        manager.lock().await.stop_process("worker").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let worker = &read().processes["worker"];
+        assert_eq!((worker.status.as_str(), worker.pid), ("Stopped", None));
+        
+        // 控制協議返回同樣的內容
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(handle_connection(server, manager.clone(), status));
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"{\"command\":\"registry\"}\n").await.unwrap();
+        let response: Response = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        let registry: Registry = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(registry.processes, read().processes);
+        
+        manager.lock().await.stop_process("api").await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_grpc_api_round_trip() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::proto::{EventKind, ListProcessesRequest, ProcessRequest, StreamEventsRequest, StreamLogsRequest};
+        use janus::grpc::{GrpcServer, JanusClient};
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("grpc.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "greeter"
+        command = "sh"
+        args = ["-c", "echo hello; sleep 10"]
+        
+        [[process]]
+        name = "idle"
+        command = "sleep"
+        args = ["10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.run(manager.clone()));
+        
+        let mut client = JanusClient::connect(format!("http://{}", address)).await.unwrap();
+        let response = client.list_processes(ListProcessesRequest {}).await.unwrap().into_inner();
+        assert_eq!(response.config_version, 1);
+        assert!(!response.config_reload_pending);
+        let mut names: Vec<String> = response.processes.into_iter().map(|process| process.name).collect();
+        names.sort();
+        assert_eq!(names, vec!["greeter", "idle"]);
+        
+        // 配置文件被修改後報告需要重新加載
+        fs::write(&config_path, fs::read_to_string(&config_path).unwrap() + "\n# edited\n").unwrap();
+        manager.lock().await.publish_status();
+        let response = client.list_processes(ListProcessesRequest {}).await.unwrap().into_inner();
+        assert!(response.config_reload_pending);
+        
+        // 只訂閱 greeter 的日誌和事件
+        let selection = vec!["greeter".to_string()];
+        let mut logs = client
+            .stream_logs(StreamLogsRequest { names: selection.clone(), ..Default::default() })
+            .await
+            .unwrap()
+            .into_inner();
+        let mut events = client
+            .stream_events(StreamEventsRequest { names: selection })
+            .await
+            .unwrap()
+            .into_inner();
+        
+        client.start(ProcessRequest { name: "idle".to_string(), resume_after_secs: None }).await.unwrap();
+        let response = client.start(ProcessRequest { name: "greeter".to_string(), resume_after_secs: None }).await.unwrap().into_inner();
+        let process = response.process.unwrap();
+        assert_eq!(process.status, "Running");
+        assert!(process.pid.is_some());
+        
+        let event = events.message().await.unwrap().unwrap();
+        assert_eq!(event.process, "greeter");
+        assert_eq!(event.kind(), EventKind::Started);
+        assert_eq!(event.pid, process.pid);
+        
+        let mut contents = Vec::new();
+        while !contents.iter().any(|content| content == "hello") {
+            let line = logs.message().await.unwrap().unwrap();
+            assert_eq!(line.process, "greeter");
+            contents.push(line.content);
+        }
+        
+        client.stop(ProcessRequest { name: "greeter".to_string(), resume_after_secs: None }).await.unwrap();
+        let event = events.message().await.unwrap().unwrap();
+        assert_eq!(event.kind(), EventKind::Stopped);
+        
+        // 未知進程返回 NotFound
+        let status = client.stop(ProcessRequest { name: "missing".to_string(), resume_after_secs: None }).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        
+        manager.lock().await.stop_process("idle").await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_grpc_stream_logs_filters_in_supervisor() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::proto::StreamLogsRequest;
+        use janus::grpc::{GrpcServer, JanusClient};
+        use janus::logging::handler::LogHandler;
+        use janus::logging::LogType;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("grpc_logs.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "api"
+        command = "sh"
+        args = ["-c", "echo 'GET /a'; echo 'ERROR boom'; echo 'GET /b'; sleep 10"]
+        
+        [[process]]
+        name = "worker"
+        command = "sh"
+        args = ["-c", "echo 'ERROR worker'; sleep 10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, log_handler.clone())));
+        manager.lock().await.start_process("api").await.unwrap();
+        manager.lock().await.start_process("worker").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.run(manager.clone()));
+        let mut client = JanusClient::connect(format!("http://{}", address)).await.unwrap();
+        
+        // 選擇和過濾在 janus 中完成，先發送日誌文件中最後一行符合條件的歷史
+        let request = StreamLogsRequest {
+            names: Vec::new(),
+            include: vec!["*".to_string()],
+            exclude: vec!["worker".to_string()],
+            r#match: "ERROR|GET /b".to_string(),
+            lines: Some(1),
+        };
+        let mut logs = client.stream_logs(request).await.unwrap().into_inner();
+        let line = logs.message().await.unwrap().unwrap();
+        assert_eq!((line.process.as_str(), line.content.as_str()), ("api", "GET /b"));
+        
+        // 之後只推送符合條件的新行
+        log_handler.log("worker", LogType::Stdout, "ERROR excluded");
+        log_handler.log("api", LogType::Stdout, "INFO unmatched");
+        log_handler.log("api", LogType::Stdout, "ERROR live");
+        let line = logs.message().await.unwrap().unwrap();
+        assert_eq!((line.process.as_str(), line.content.as_str()), ("api", "ERROR live"));
+        
+        let request = StreamLogsRequest { r#match: "(".to_string(), ..Default::default() };
+        let status = client.stream_logs(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        
+        // 沒有 log_dir 時明確要求歷史行會被拒絕，未指定時只跟隨新行
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager_without_files = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.run(manager_without_files));
+        let mut client = JanusClient::connect(format!("http://{}", address)).await.unwrap();
+        let status = client.stream_logs(StreamLogsRequest { lines: Some(3), ..Default::default() }).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert!(client.stream_logs(StreamLogsRequest::default()).await.is_ok());
+        
+        manager.lock().await.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_remote_cli_with_token() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("remote.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let server = GrpcServer::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .with_token(Some("secret".to_string()));
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        // 在不同的工作目錄中運行，確保不讀取本地配置文件
+        let janus = |args: &[&str], token: &str| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host])
+                .args(args)
+                .env("JANUS_TOKEN", token)
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        let output = janus(&["start-one", "web"], "secret").await.unwrap();
+        assert!(output.status.success());
+        assert!(manager.lock().await.get_process("web").unwrap().process.is_some());
+        
+        let output = janus(&["ps"], "secret").await.unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.lines().any(|line| line.starts_with("web") && line.contains("Running")));
+        
+        // 令牌錯誤或命令不支持遠程執行時失敗
+        let output = janus(&["ps"], "wrong").await.unwrap();
+        assert!(!output.status.success());
+        let output = janus(&["ps"], "secre").await.unwrap();
+        assert!(!output.status.success());
+        let output = janus(&["logs", "--purge"], "secret").await.unwrap();
+        assert!(!output.status.success());
+        
+        let output = janus(&["stop-one", "web"], "secret").await.unwrap();
+        assert!(output.status.success());
+        assert!(manager.lock().await.get_process("web").unwrap().process.is_none());
+        
+        // 管理接口監聽本機以外的地址時必須設置令牌
+        let process = "[[process]]\nname = \"web\"\ncommand = \"sleep\"\n";
+        for (global, valid) in [
+            ("grpc_listen = \"0.0.0.0:7777\"", false),
+            ("grpc_listen = \"[::]:7777\"", false),
+            ("grpc_listen = \"0.0.0.0:7777\"\ngrpc_token = \"secret\"", true),
+            ("grpc_listen = \"127.0.0.1:7777\"", true),
+            ("grpc_listen = \"[::1]:7777\"", true),
+        ] {
+            fs::write(&config_path, format!("[global]\n{}\n\n{}", global, process)).unwrap();
+            assert_eq!(ConfigManager::new(config_path.to_str().unwrap()).is_ok(), valid, "{}", global);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_ready_waits_for_critical_processes() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("ready.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        
+        [[process]]
+        name = "report"
+        command = "true"
+        every = "1h"
+        
+        [[process]]
+        name = "debug-shell"
+        command = "sleep"
+        args = ["10"]
+        critical = false
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        let janus = |args: &[&str]| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host, "ready"])
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        // web 未運行時超時失敗，並列出未就緒的進程
+        let output = janus(&["--timeout", "1s"]).await.unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("web (stopped)"));
+        assert!(!stderr.contains("report") && !stderr.contains("debug-shell"));
+        
+        // 只等待關鍵進程：定時任務和 critical = false 的進程不影響結果
+        manager.lock().await.start_process("web").await.unwrap();
+        let output = janus(&["--timeout", "5s"]).await.unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Ready: web"));
+        assert!(stdout.contains("All 1 critical process(es) are ready"));
+        
+        // 指定名稱時只等待這些進程
+        let output = janus(&["--timeout", "1s", "debug-shell"]).await.unwrap();
+        assert!(!output.status.success());
+        let output = janus(&["--timeout", "1s", "missing"]).await.unwrap();
+        assert!(!output.status.success());
+        
+        manager.lock().await.stop_all().await.unwrap();
+        
+        // 沒有 --host 也沒有 grpc_listen 時直接報錯
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "ready"])
+            .output()
+            .await
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("grpc_listen"));
+    }
+    
+    #[tokio::test]
+    async fn test_remote_restart_by_state() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("selectors.toml");
+        let marker = temp_dir.path().join("ran");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "worker"
+        command = "sh"
+        args = ["-c", "if [ -e {marker} ]; then sleep 10; else touch {marker}; exit 1; fi"]
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        
+        [[process]]
+        name = "batch"
+        command = "sleep"
+        args = ["10"]
+        "#, marker = marker.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        {
+            let mut manager = manager.lock().await;
+            manager.start_process("worker").await.unwrap();
+            manager.start_process("web").await.unwrap();
+        }
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while manager.lock().await.get_process("worker").unwrap().status != ProcessStatus::Failed {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            manager.lock().await.check_processes().await.unwrap();
+        }
+        let web_pid = manager.lock().await.get_process("web").unwrap().pid();
+        
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        // 只重啟失敗的進程，停止的進程保持停止，運行中的進程不受影響
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &host, "restart", "--failed"])
+            .current_dir(temp_dir.path())
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("worker") && !stdout.contains("web") && !stdout.contains("batch"), "{}", stdout);
+        
+        let manager = manager.lock().await;
+        assert_eq!(manager.get_process("worker").unwrap().status, ProcessStatus::Running);
+        assert_eq!(manager.get_process("batch").unwrap().status, ProcessStatus::Stopped);
+        assert_eq!(manager.get_process("web").unwrap().pid(), web_pid);
+    }
+    
+    #[tokio::test]
+    async fn test_maintenance_mode() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("maintenance.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sh"
+        args = ["-c", "sleep 0.2; exit 1"]
+        group = "backend"
+        auto_restart = true
+        restart_delay = 0
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        args = ["10"]
+        group = "backend"
+        
+        [[process]]
+        name = "proxy"
+        command = "sleep"
+        args = ["10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        let janus = |args: &[&str]| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host])
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        // 按組開啟維護模式，不影響組外的進程
+        let output = janus(&["maintenance", "on", "--group", "backend", "--for", "10m"]).await.unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("web") && stdout.contains("api") && !stdout.contains("proxy"));
+        assert!(manager.lock().await.get_process("web").unwrap().in_maintenance(std::time::Instant::now()));
+        assert!(!manager.lock().await.get_process("proxy").unwrap().in_maintenance(std::time::Instant::now()));
+        
+        let output = janus(&["status"]).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).matches("Maintenance mode:").count(), 2);
+        
+        // 維護期間崩潰的進程不會被重啟
+        manager.lock().await.start_process("web").await.unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while manager.lock().await.get_process("web").unwrap().status == ProcessStatus::Running {
+            assert!(std::time::Instant::now() < deadline);
+            manager.lock().await.check_processes().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        manager.lock().await.check_processes().await.unwrap();
+        let process = manager.lock().await.get_process("web").unwrap().clone();
+        assert_eq!(process.status, ProcessStatus::Failed);
+        assert!(process.restart_at.is_none());
+        assert_eq!(process.restart_count, 0);
+        
+        let output = janus(&["maintenance", "off", "web"]).await.unwrap();
+        assert!(output.status.success());
+        assert!(!manager.lock().await.get_process("web").unwrap().in_maintenance(std::time::Instant::now()));
+        let output = janus(&["maintenance", "on", "--group", "frontend"]).await.unwrap();
+        assert!(!output.status.success());
+        
+        // 到期後自動恢復
+        let mut manager = manager.lock().await;
+        manager.set_maintenance("api", Some(std::time::Duration::from_millis(50))).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        manager.check_processes().await.unwrap();
+        assert!(manager.get_process("api").unwrap().maintenance_until.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_manual_stop_is_not_restarted() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("manual_stop.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        auto_restart = true
+        restart_delay = 0
+        
+        [[process]]
+        name = "crasher"
+        command = "sh"
+        args = ["-c", "exit 1"]
+        auto_restart = true
+        restart_delay = 60
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        
+        // 手動停止後監控循環不會重新啟動進程
+        {
+            let mut manager = manager.lock().await;
+            manager.start_process("web").await.unwrap();
+            manager.stop_process_manually("web", None).await.unwrap();
+            for _ in 0..3 {
+                manager.check_processes().await.unwrap();
+            }
+            let process = manager.get_process("web").unwrap();
+            assert_eq!(process.status, ProcessStatus::Stopped);
+            assert!(process.manual_stop);
+            
+            // 已安排的重啟也被取消
+            manager.start_process("crasher").await.unwrap();
+            while manager.get_process("crasher").unwrap().restart_at.is_none() {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                manager.check_processes().await.unwrap();
+            }
+            manager.stop_process_manually("crasher", None).await.unwrap();
+            assert!(manager.get_process("crasher").unwrap().restart_at.is_none());
+            
+            // 顯式啟動後恢復正常管理
+            manager.start_process("web").await.unwrap();
+            assert!(!manager.get_process("web").unwrap().manual_stop);
+        }
+        
+        // stop-one --for 到期後由監控進程重新啟動
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &host, "stop-one", "web", "--for", "1s"])
+            .current_dir(temp_dir.path())
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("resumes in 1s"));
+        assert!(manager.lock().await.get_process("web").unwrap().resume_at.is_some());
+        
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while manager.lock().await.get_process("web").unwrap().status != ProcessStatus::Running {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            manager.lock().await.check_processes().await.unwrap();
+        }
+        assert!(!manager.lock().await.get_process("web").unwrap().manual_stop);
+        manager.lock().await.stop_all().await.unwrap();
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_upgrade_exec_keeps_processes_running() {
+        use janus::grpc::RemoteClient;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let host = format!("tcp://{}", address);
+        let config_path = temp_dir.path().join("upgrade.toml");
+        // 等到 go 文件出現後再輸出，驗證升級後仍在讀取進程原來的輸出管道
+        fs::write(&config_path, format!(r#"
+        [global]
+        log_dir = "{}"
+        grpc_listen = "{}"
+        grpc_token = "secret"
+        
+        [[process]]
+        name = "worker"
+        command = "sh"
+        args = ["-c", "echo before; while [ ! -f go ]; do sleep 0.1; done; echo after; exec sleep 30"]
+        working_dir = "{}"
+        "#, temp_dir.path().join("logs").display(), address, temp_dir.path().display())).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let pid = loop {
+            if let Ok(mut client) = RemoteClient::connect(&host, Some("secret")).await {
+                if let Some(pid) = client.list().await.unwrap()[0].pid {
+                    break pid;
+                }
+            }
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        };
+        
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &host, "--token", "secret", "upgrade", "--exec"])
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("1 process(es) kept running"));
+        
+        // 進程沒有重啟，janus 的 pid 也沒有變化
+        let mut client = RemoteClient::connect(&host, Some("secret")).await.unwrap();
+        assert_eq!(client.generation().await.unwrap(), 1);
+        let worker = client.list().await.unwrap().remove(0);
+        assert_eq!(worker.pid, Some(pid));
+        assert_eq!(worker.restart_count, 0);
+        assert!(supervisor.try_wait().unwrap().is_none());
+        
+        fs::write(temp_dir.path().join("go"), "").unwrap();
+        let log_path = temp_dir.path().join("logs/worker.log");
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !fs::read_to_string(&log_path).unwrap_or_default().contains("after") {
+            assert!(std::time::Instant::now() < deadline, "{}", fs::read_to_string(&log_path).unwrap_or_default());
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        let log = fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("before"));
+        assert!(log.contains(&format!("Resumed after supervisor upgrade (pid {})", pid)));
+        
+        // 升級後的 janus 照常關閉進程
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        supervisor.wait().await.unwrap();
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+        let state = stat.rsplit(')').next().unwrap_or_default().split_whitespace().next();
+        assert!(matches!(state, None | Some("Z") | Some("X")), "worker still running: {}", stat);
+    }
+    
+    #[tokio::test]
+    async fn test_record_process_output() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("record.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sh"
+        args = ["-c", "echo hello; sleep 0.5; echo world; exec sleep 10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        // --restart 在開始錄製之後重啟進程，錄下完整的啟動過程
+        let cast_path = temp_dir.path().join("session.cast");
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &host, "record", "web", "--to", cast_path.to_str().unwrap(), "--restart", "--for", "2s"])
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("asciinema play"));
+        
+        // asciicast v2：頭部之後每行一個 [秒數, "o", 內容] 事件
+        let cast = fs::read_to_string(&cast_path).unwrap();
+        let mut lines = cast.lines().map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap());
+        let header = lines.next().unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["title"], "janus record web");
+        let events: Vec<(f64, String)> = lines
+            .map(|event| {
+                assert_eq!(event[1], "o");
+                (event[0].as_f64().unwrap(), event[2].as_str().unwrap().to_string())
+            })
+            .collect();
+        assert!(events.iter().any(|(_, text)| text.contains("[janus] Process started")));
+        let time = |expected: &str| events.iter().find(|(_, text)| text == expected).unwrap().0;
+        assert!(time("world\r\n") - time("hello\r\n") >= 0.4);
+        assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        
+        manager.lock().await.stop_all().await.unwrap();
+    }
+    
+    #[test]
+    fn test_projects_namespace_processes() {
+        use janus::config::manager::ConfigManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir(dir.join("api")).unwrap();
+        fs::write(dir.join("api/janus.toml"), r#"
+        [global]
+        working_dir = "/srv/api"
+        env = { STAGE = "prod", PORT = "80" }
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        env = { PORT = "8080" }
+        requires = ["db"]
+        
+        [[process]]
+        name = "db"
+        command = "sleep"
+        "#).unwrap();
+        fs::write(dir.join("batch.toml"), r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        "#).unwrap();
+        
+        let config_path = dir.join("janus.toml");
+        fs::write(&config_path, r#"
+        [[project]]
+        name = "api"
+        config = "api/janus.toml"
+        
+        [[project]]
+        name = "batch"
+        config = "batch.toml"
+        "#).unwrap();
+        
+        // 兩個項目中同名的進程互不衝突，requires 和全局設置在項目內生效
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut names: Vec<&str> = config_manager.get_process_configs().iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["api/db", "api/web", "batch/web"]);
+        let web = config_manager.get_process_configs().iter().find(|p| p.name == "api/web").unwrap();
+        assert_eq!(web.requires.as_deref(), Some(&["api/db".to_string()][..]));
+        assert_eq!(web.working_dir.as_deref(), Some("/srv/api"));
+        let env = web.env.as_ref().unwrap();
+        assert_eq!(env["STAGE"], "prod");
+        assert_eq!(env["PORT"], "8080");
+        
+        // 修改項目配置文件也視為配置變化
+        assert!(!config_manager.is_changed_on_disk());
+        fs::write(dir.join("batch.toml"), "[[process]]\nname = \"web\"\ncommand = \"true\"\n").unwrap();
+        assert!(config_manager.is_changed_on_disk());
+        
+        // --project 限定命令作用的進程
+        let output = Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "--project", "api", "ps"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("api/web"));
+        assert!(stdout.contains("api/db"));
+        assert!(!stdout.contains("batch/web"));
+        
+        fs::write(&config_path, r#"
+        [[project]]
+        name = "api"
+        config = "api/janus.toml"
+        
+        [[project]]
+        name = "api"
+        config = "batch.toml"
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("Duplicate project name: api"));
+    }
+    
+    #[test]
+    fn test_wasm_log_plugin() {
+        use janus::config::LogPluginConfig;
+        use janus::logging::handler::LogHandler;
+        use janus::logging::wasm::WasmLogPlugin;
+        use janus::logging::LogType;
+        
+        // 以 s 開頭的內容替換為 [REDACTED]，以 d 開頭的丟棄，系統日誌只輸出到終端
+        let redact = wat::parse_str(r#"
+            (module
+              (import "janus" "route" (func $route (param i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 0) "[REDACTED]")
+              (global $next (mut i32) (i32.const 1024))
+              (func (export "janus_abi_version") (result i32) (i32.const 1))
+              (func (export "janus_alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $next))
+                (global.set $next (i32.add (global.get $next) (local.get $len)))
+                (local.get $ptr))
+              (func (export "janus_process")
+                (param $stream i32) (param $name i32) (param $name_len i32) (param $content i32) (param $len i32)
+                (result i64)
+                (local $first i32)
+                (global.set $next (i32.const 1024))
+                (if (i32.eq (local.get $stream) (i32.const 2))
+                  (then (call $route (i32.const 1)) (return (i64.const 0))))
+                (if (i32.eqz (local.get $len)) (then (return (i64.const 0))))
+                (local.set $first (i32.load8_u (local.get $content)))
+                (if (i32.eq (local.get $first) (i32.const 115))
+                  (then (return (i64.const 10))))
+                (if (i32.eq (local.get $first) (i32.const 100))
+                  (then (return (i64.const -1))))
+                (i64.const 0)))
+        "#).unwrap();
+        let config = |path: &str| LogPluginConfig { path: path.to_string(), fuel: Some(10_000), max_memory: None };
+        let plugin = WasmLogPlugin::from_bytes(&config("redact.wasm"), &redact).unwrap();
+        
+        // 死循環在燃料耗盡後中止，日誌保持原樣
+        let spin = wat::parse_str(r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "janus_abi_version") (result i32) (i32.const 1))
+              (func (export "janus_alloc") (param i32) (result i32) (i32.const 0))
+              (func (export "janus_process") (param i32 i32 i32 i32 i32) (result i64)
+                (loop $forever (br $forever))
+                (i64.const -1)))
+        "#).unwrap();
+        let spinner = WasmLogPlugin::from_bytes(&config("spin.wasm"), &spin).unwrap();
+        
+        // 不支持的 ABI 版本在加載時被拒絕
+        let future = wat::parse_str(r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "janus_abi_version") (result i32) (i32.const 2)))
+        "#).unwrap();
+        assert!(WasmLogPlugin::from_bytes(&config("future.wasm"), &future).is_err());
+        
+        let temp_dir = TempDir::new().unwrap();
+        let handler = LogHandler::new("info")
+            .with_log_dir(Some(temp_dir.path().to_path_buf()))
+            .with_plugins(vec![spinner, plugin]);
+        handler.log("api", LogType::Stdout, "secret token=abc");
+        handler.log("api", LogType::Stdout, "debug noise");
+        handler.log("api", LogType::Stderr, "request failed");
+        handler.log("api", LogType::System, "Process started");
+        handler.flush();
+        
+        let log = fs::read_to_string(temp_dir.path().join("api.log")).unwrap();
+        let contents: Vec<&str> = log.lines().map(|line| line.splitn(4, ' ').nth(3).unwrap()).collect();
+        assert_eq!(contents, ["[REDACTED]", "request failed"]);
+    }
+    
+    #[test]
+    fn test_log_writer_batches_in_order() {
+        use janus::logging::handler::LogHandler;
+        use janus::logging::LogType;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        
+        // 多個克隆交錯寫入兩個進程的日誌，每個文件內保持寫入順序
+        let other = handler.clone();
+        for i in 0..5000 {
+            handler.log("api", LogType::Stdout, &format!("api {}", i));
+            other.log("worker", LogType::Stderr, &format!("worker {}", i));
+        }
+        handler.flush();
+        
+        for name in ["api", "worker"] {
+            let log = fs::read_to_string(temp_dir.path().join(format!("{}.log", name))).unwrap();
+            let lines: Vec<String> = log.lines().map(|line| line.splitn(4, ' ').nth(3).unwrap().to_string()).collect();
+            let expected: Vec<String> = (0..5000).map(|i| format!("{} {}", name, i)).collect();
+            assert_eq!(lines, expected);
+        }
+    }
+    
+    #[test]
+    fn test_log_file_line_and_filter() {
+        use janus::logging::tail::LogFilter;
+        use janus::logging::{LogEntry, LogType};
+        
+        // 日誌文件行可以解析回原來的條目
+        let line = "2024-05-01 12:00:00.123 stderr connection refused: db:5432";
+        let entry = LogEntry::from_file_line("web", line).unwrap();
+        assert_eq!(entry.log_type, LogType::Stderr);
+        assert_eq!(entry.content, "connection refused: db:5432");
+        assert_eq!(entry.to_file_line(), format!("{}\n", line));
+        assert!(LogEntry::from_file_line("web", "not a log line").is_none());
+        
+        let filter = LogFilter::new(
+            Vec::new(),
+            &["web-*".to_string()],
+            &["web-debug".to_string()],
+            Some("refused|timeout"),
+        )
+        .unwrap();
+        assert!(filter.matches_process("web-api"));
+        assert!(!filter.matches_process("web-debug"));
+        assert!(!filter.matches_process("worker"));
+        assert!(filter.matches_content(&entry.content));
+        assert!(!filter.matches_content("ok"));
+        
+        assert!(LogFilter::new(Vec::new(), &[], &[], Some("(")).is_err());
+    }
+    
+    #[test]
+    fn test_log_search_since() {
+        use janus::logging::search::{parse_duration, search};
+        use janus::logging::tail::LogFilter;
+        use std::time::Duration;
+        
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("15m").unwrap(), Duration::from_secs(900));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(172800));
+        assert!(parse_duration("1w").is_err());
+        // 溢出時返回錯誤而不是 panic 或回繞
+        assert!(parse_duration("18446744073709551615d").is_err());
+        assert_eq!(parse_duration("18446744073709551615").unwrap(), Duration::from_secs(u64::MAX));
+        
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("api.log"),
+            "2020-01-01 00:00:00.000 stdout old timeout\n2099-01-01 00:00:00.000 stderr new timeout\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("worker.log"), "2099-01-01 00:00:00.000 stdout timeout\n").unwrap();
+        
+        // 按進程名和時間範圍過濾
+        let filter = LogFilter::new(vec!["api".to_string()], &[], &[], Some("timeout")).unwrap();
+        let since = chrono::Local::now() - chrono::Duration::hours(1);
+        let entries = search(temp_dir.path(), &filter, Some(since)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "new timeout");
+    }
+    
+    #[test]
+    fn test_log_retention_and_purge() {
+        use janus::logging::retention::{apply, purge, RetentionPolicy};
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let api_log = temp_dir.path().join("api.log");
+        fs::write(
+            &api_log,
+            "2020-01-01 00:00:00.000 stdout old\n2099-01-01 00:00:00.000 stdout new\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("worker.log"), "2020-01-01 00:00:00.000 stdout old\n").unwrap();
+        
+        // 超過 max_age 的行從文件開頭刪除
+        let policy = RetentionPolicy { max_age: Some(Duration::from_secs(3600)), max_total_size: None };
+        apply(temp_dir.path(), &policy).unwrap();
+        assert_eq!(fs::read_to_string(&api_log).unwrap(), "2099-01-01 00:00:00.000 stdout new\n");
+        assert_eq!(fs::read_to_string(temp_dir.path().join("worker.log")).unwrap(), "");
+        
+        // 總大小超限時保留最新的行
+        fs::write(
+            &api_log,
+            "2099-01-01 00:00:00.000 stdout first\n2099-01-01 00:00:01.000 stdout second\n",
+        )
+        .unwrap();
+        let policy = RetentionPolicy { max_age: None, max_total_size: Some(40) };
+        apply(temp_dir.path(), &policy).unwrap();
+        assert_eq!(fs::read_to_string(&api_log).unwrap(), "2099-01-01 00:00:01.000 stdout second\n");
+        
+        assert_eq!(purge(temp_dir.path(), Some("api")).unwrap(), vec!["api".to_string()]);
+        assert_eq!(fs::metadata(&api_log).unwrap().len(), 0);
+    }
+    
+    #[test]
+    fn test_message_catalog() {
+        use janus::messages::catalog::{Catalog, DEFAULT_MESSAGES};
+        
+        let english = Catalog::english();
+        assert_eq!(
+            english.format("process.started", &[("name", "web".to_string())]),
+            "Process started: web"
+        );
+        
+        // 語言文件只需覆蓋部分消息
+        let temp_dir = TempDir::new().unwrap();
+        let locale_path = temp_dir.path().join("zh-TW.toml");
+        fs::write(&locale_path, "\"process.started\" = \"進程已啟動：{name}\"\n").unwrap();
+        let catalog = Catalog::with_locale_file(locale_path.to_str().unwrap()).unwrap();
+        assert_eq!(catalog.format("process.started", &[("name", "web".to_string())]), "進程已啟動：web");
+        assert_eq!(catalog.format("process.stopped", &[("name", "web".to_string())]), "Process stopped: web");
+        
+        fs::write(&locale_path, "\"no.such.message\" = \"x\"\n").unwrap();
+        assert!(Catalog::with_locale_file(locale_path.to_str().unwrap()).is_err());
+        
+        // 源碼中使用的每個消息 ID 都必須有英文默認文本
+        let src_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut dirs = vec![src_dir];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let source = fs::read_to_string(&path).unwrap();
+                for usage in source.split("msg!(\"").skip(1) {
+                    let id = &usage[..usage.find('"').unwrap()];
+                    assert!(
+                        DEFAULT_MESSAGES.iter().any(|(known, _)| *known == id),
+                        "{} uses unknown message id {}",
+                        path.display(),
+                        id
+                    );
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn test_systemd_unit() {
+        use janus::cli::systemd::{ReloadMethod, SystemdUnit};
+        use std::path::Path;
+        
+        let unit = SystemdUnit {
+            exec_path: Path::new("/usr/local/bin/janus"),
+            config_path: Path::new("/etc/janus/my app.toml"),
+            user: Some("janus"),
+            notify: false,
+            reload: ReloadMethod::Sighup,
+        }
+        .render();
+        
+        assert!(unit.contains("Type=simple\n"));
+        assert!(unit.contains("ExecStart=/usr/local/bin/janus --config \"/etc/janus/my app.toml\" start\n"));
+        assert!(unit.contains("ExecReload=/bin/kill -HUP $MAINPID\n"));
+        assert!(unit.contains("WorkingDirectory=/etc/janus\n"));
+        assert!(unit.contains("User=janus\n"));
+        assert!(unit.contains("KillMode=mixed\n"));
+        
+        // sd_notify 時等待 janus 報告就緒，有 gRPC 時通過 janus reload 重新加載
+        let unit = SystemdUnit {
+            exec_path: Path::new("/usr/local/bin/janus"),
+            config_path: Path::new("/etc/janus/janus.toml"),
+            user: None,
+            notify: true,
+            reload: ReloadMethod::Command,
+        }
+        .render();
+        
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains("ExecReload=/usr/local/bin/janus --config /etc/janus/janus.toml reload\n"));
+        assert!(!unit.contains("kill -HUP"));
+    }
+    
+    #[test]
+    fn test_generate_systemd_unit_reload_method() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        let generate = |global: &str| {
+            fs::write(&config_path, format!("[global]\n{}\n\n[[process]]\nname = \"web\"\ncommand = \"sleep\"\n", global)).unwrap();
+            std::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--config", config_path.to_str().unwrap(), "generate", "systemd-unit"])
+                .output()
+                .unwrap()
+        };
+        
+        // 默認 sighup_action = "reload"，SIGHUP 重新加載配置
+        let output = generate("");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let unit = String::from_utf8_lossy(&output.stdout);
+        assert!(unit.contains("Type=simple\n"), "{}", unit);
+        assert!(unit.contains("ExecReload=/bin/kill -HUP $MAINPID\n"), "{}", unit);
+        
+        // forward 時 SIGHUP 會發給進程，systemctl reload 無法重新加載 janus，拒絕生成
+        let output = generate("sighup_action = \"forward\"");
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("sighup_action = \"forward\""));
+        
+        // 有 gRPC 時改用 janus reload
+        let output = generate("sighup_action = \"forward\"\nsd_notify = true\ngrpc_listen = \"127.0.0.1:7777\"");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let unit = String::from_utf8_lossy(&output.stdout);
+        assert!(unit.contains("Type=notify\n"), "{}", unit);
+        assert!(unit.contains(&format!("--config {} reload\n", fs::canonicalize(&config_path).unwrap().display())), "{}", unit);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sd_notify_and_reload_command() {
+        use janus::grpc::RemoteClient;
+        use tokio::net::UnixDatagram;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("notify.sock");
+        let systemd = UnixDatagram::bind(&socket_path).unwrap();
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let marker = temp_dir.path().join("inherited");
+        let config_path = temp_dir.path().join("janus.toml");
+        let config = format!(r#"
+        [global]
+        sd_notify = true
+        sighup_action = "forward"
+        grpc_listen = "{}"
+        
+        [[process]]
+        name = "web"
+        command = "sh"
+        args = ["-c", "echo ${{NOTIFY_SOCKET:-none}} > {}; exec sleep 30"]
+        "#, address, marker.display());
+        fs::write(&config_path, &config).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .env("NOTIFY_SOCKET", &socket_path)
+            .stdout(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let receive = async || {
+            let mut buffer = vec![0u8; 4096];
+            let read = systemd.recv(&mut buffer);
+            let len = tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap().unwrap();
+            String::from_utf8_lossy(&buffer[..len]).to_string()
+        };
+        
+        // 進程啟動後 janus 向 systemd 報告就緒，進程本身沒有繼承 NOTIFY_SOCKET
+        let message = receive().await;
+        assert!(message.contains("READY=1"), "{}", message);
+        assert!(message.contains(&format!("MAINPID={}", supervisor.id().unwrap())), "{}", message);
+        // 進程被創建後才執行 echo，READY=1 可能先於標記文件寫完到達
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while !fs::read_to_string(&marker).unwrap_or_default().ends_with('\n') {
+            assert!(std::time::Instant::now() < deadline, "{} was not written", marker.display());
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(fs::read_to_string(&marker).unwrap().trim(), "none");
+        
+        // janus reload 在 sighup_action = "forward" 時也重新加載 janus 的配置
+        fs::write(&config_path, format!("{}\n[[process]]\nname = \"worker\"\ncommand = \"sleep\"\nargs = [\"30\"]\n", config)).unwrap();
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "reload"])
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Reloaded the configuration (version 2)"), "{}", String::from_utf8_lossy(&output.stdout));
+        let mut client = RemoteClient::connect(&format!("tcp://{}", address), None).await.unwrap();
+        assert!(client.list().await.unwrap().iter().any(|process| process.name == "worker"));
+        
+        // 新配置無效時保留當前配置，命令失敗
+        fs::write(&config_path, "[[process]]\nname = ").unwrap();
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &format!("tcp://{}", address), "reload"])
+            .output()
+            .await
+            .unwrap();
+        assert!(!output.status.success());
+        assert_eq!(client.list().await.unwrap().len(), 2);
+        
+        // 關閉時報告 STOPPING=1
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        let message = receive().await;
+        assert!(message.contains("STOPPING=1"), "{}", message);
+        supervisor.wait().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_task_queue_order() {
+        use janus::process::queue::TaskQueue;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let queue_path = temp_dir.path().join("queue");
+        let queue = TaskQueue::new(&queue_path);
+        
+        assert_eq!(queue.push("backup").await.unwrap(), 1);
+        assert_eq!(queue.push("vacuum").await.unwrap(), 2);
+        assert_eq!(queue.push("backup").await.unwrap(), 3);
+        
+        // 重新打開同一文件，隊列內容保持不變
+        let queue = TaskQueue::new(&queue_path);
+        assert_eq!(queue.pending().await.unwrap(), vec!["backup", "vacuum", "backup"]);
+        
+        // 隊首任務不能啟動時不跳過，保持先進先出
+        assert!(queue.take(2, |name| name != "backup").await.unwrap().is_empty());
+        
+        let taken = queue.take(2, |_| true).await.unwrap();
+        assert_eq!(taken, vec!["backup", "vacuum"]);
+        assert_eq!(queue.pending().await.unwrap(), vec!["backup"]);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_task_queue_waits_for_lock_without_blocking() {
+        use janus::process::queue::TaskQueue;
+        use std::os::unix::io::AsRawFd;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let queue_path = temp_dir.path().join("queue");
+        fs::write(&queue_path, "backup\n").unwrap();
+        
+        // 模擬另一個 janus 進程持有隊列文件鎖
+        let holder = fs::File::open(&queue_path).unwrap();
+        assert_eq!(unsafe { libc::flock(holder.as_raw_fd(), libc::LOCK_EX) }, 0);
+        
+        let queue = TaskQueue::new(&queue_path);
+        let push = tokio::spawn(async move { queue.push("vacuum").await.unwrap() });
+        
+        // 等待鎖時 runtime 上的其他任務照常運行
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!push.is_finished());
+        assert_eq!(fs::read_to_string(&queue_path).unwrap(), "backup\n");
+        
+        drop(holder);
+        assert_eq!(tokio::time::timeout(Duration::from_secs(5), push).await.unwrap().unwrap(), 2);
+        assert_eq!(fs::read_to_string(&queue_path).unwrap(), "backup\nvacuum\n");
+    }
+    
     // 注意：以下測試需要實際運行進程，可能需要在 CI 環境中特別處理
+    #[cfg(unix)]
     #[test]
     #[ignore]
     fn test_process_lifecycle() {
         // 創建臨時配置文件
         let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("lifecycle_config.toml");
+        let config_path = temp_dir.path().join("lifecycle_config.toml");
+        let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let host = format!("tcp://{}", address);
+        
+        // 使用 sleep 命令作為測試進程；start 留在前台監控，通過 gRPC 查詢狀態
+        let config_content = format!(r#"
+        [global]
+        grpc_listen = "{}"
+        
+        [[process]]
+        name = "sleep-process"
+        command = "sleep"
+        args = ["10"]
+        "#, address);
+        
+        fs::write(&config_path, config_content).unwrap();
+        
+        // 啟動進程
+        let mut supervisor = Command::new("cargo")
+            .args(["run", "--", "--config", config_path.to_str().unwrap(), "start"])
+            .spawn()
+            .unwrap();
+        
+        // 檢查進程狀態；cargo run 可能需要先編譯
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(120);
+        loop {
+            let output = Command::new("cargo")
+                .args(["run", "--", "--host", &host, "status"])
+                .output()
+                .unwrap();
+            if String::from_utf8_lossy(&output.stdout).contains("Status: Running") {
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "{}", String::from_utf8_lossy(&output.stderr));
+            assert!(supervisor.try_wait().unwrap().is_none());
+            std::thread::sleep(std::time::Duration::from_millis(500));
+        }
+        
+        // 停止進程
+        unsafe { libc::kill(supervisor.id() as i32, libc::SIGTERM) };
+        let status = supervisor.wait().unwrap();
+        
+        assert!(status.success());
+        
+        // 再次檢查狀態
+        let output = Command::new("cargo")
+            .args(["run", "--", "--config", config_path.to_str().unwrap(), "status"])
+            .output()
+            .unwrap();
+        
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        assert!(output_str.contains("Status: Stopped"));
+    }
+    
+    #[tokio::test]
+    async fn test_startup_banner() {
+        use janus::config::manager::ConfigManager;
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("banner.toml");
+        let registry_path = temp_dir.path().join("registry.json");
+        // 同時佔用兩個端口，確保兩個地址不同
+        let listeners = [(); 2].map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap());
+        let [grpc, grpc_readonly] = listeners.each_ref().map(|listener| listener.local_addr().unwrap());
+        drop(listeners);
+        fs::write(&config_path, format!(r#"
+        [global]
+        startup_banner = true
+        registry_file = "{}"
+        grpc_listen = "{}"
+        grpc_readonly_listen = "{}"
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["30"]
+        
+        [[process]]
+        name = "nightly"
+        command = "true"
+        schedule = "0 3 * * *"
+        "#, registry_path.display(), grpc, grpc_readonly)).unwrap();
+        let hash = ConfigManager::new(config_path.to_str().unwrap()).unwrap().get_config_hash().to_string();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let mut lines = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        let mut banner = Vec::new();
+        let read = async {
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let done = line.contains("Registry:");
+                banner.push(line);
+                if done {
+                    break;
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap();
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        supervisor.wait().await.unwrap();
+        
+        let banner = banner.join("\n");
+        assert!(banner.contains(&format!("janus {} (pid ", env!("CARGO_PKG_VERSION"))), "{}", banner);
+        assert!(banner.contains(&fs::canonicalize(&config_path).unwrap().display().to_string()), "{}", banner);
+        assert!(banner.contains(&format!("SHA-256:    {}", hash)), "{}", banner);
+        assert!(banner.contains("Processes:  2 configured, 1 started"), "{}", banner);
+        assert!(banner.contains(&format!("Registry:   {}", registry_path.display())), "{}", banner);
+        assert!(banner.contains(&format!("gRPC:       {}", grpc)), "{}", banner);
+        assert!(banner.contains(&format!("gRPC (ro):  {}", grpc_readonly)), "{}", banner);
+        assert!(!banner.contains("Control:"));
+    }
+    
+    #[tokio::test]
+    async fn test_sidecar_follows_main_process() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("sidecar.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "main"
+        command = "sleep"
+        args = ["30"]
+        auto_restart = true
+        restart_delay = 0
+        
+        [[process]]
+        name = "proxy"
+        command = "sleep"
+        args = ["30"]
+        sidecar_of = "main"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        let pid = |manager: &ProcessManager, name: &str| manager.get_process(name).unwrap().pid();
+        
+        // 邊車排在主進程之後，主進程沒有運行時等待它啟動
+        let names = vec!["proxy".to_string(), "main".to_string()];
+        assert_eq!(manager.start_order(&names), vec!["main".to_string(), "proxy".to_string()]);
+        manager.start_process("proxy").await.unwrap();
+        assert_eq!(pid(&manager, "proxy"), None);
+        manager.start_process("main").await.unwrap();
+        let proxy_pid = pid(&manager, "proxy").expect("sidecar starts with its main process");
+        
+        // 主進程重啟時邊車也重啟
+        manager.restart_process("main").await.unwrap();
+        let restarted_pid = pid(&manager, "proxy").unwrap();
+        assert_ne!(restarted_pid, proxy_pid);
+        
+        // 主進程崩潰時邊車隨之停止，主進程被重啟後邊車再次啟動
+        unsafe { libc::kill(pid(&manager, "main").unwrap() as i32, libc::SIGKILL) };
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        manager.check_processes().await.unwrap();
+        manager.check_processes().await.unwrap();
+        assert!(pid(&manager, "main").is_some());
+        let proxy = pid(&manager, "proxy").expect("sidecar restarted with its main process");
+        assert_ne!(proxy, restarted_pid);
+        
+        // 用戶停止主進程時邊車一起停止
+        manager.stop_process_manually("main", None).await.unwrap();
+        assert_eq!(pid(&manager, "proxy"), None);
+        
+        // 引用未知進程或邊車的邊車時拒絕加載配置
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "proxy"
+        command = "sleep"
+        sidecar_of = "main"
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("sidecar_of names unknown process main"), "{}", error);
+    }
+    
+    #[tokio::test]
+    async fn test_scheduled_restart() {
+        use chrono::Utc;
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("recycle.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "service"
+        command = "sleep"
+        args = ["30"]
+        scheduled_restart = "0 3 * * *"
+        timezone = "Europe/Berlin"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("service").await.unwrap();
+        let first_pid = manager.get_process("service").unwrap().pid().unwrap();
+        
+        // 首次檢查只計算下一次重啟時間
+        manager.check_processes().await.unwrap();
+        let next = manager.get_process("service").unwrap().next_restart_at.unwrap();
+        assert!(next > Utc::now());
+        assert_eq!(manager.get_process("service").unwrap().pid(), Some(first_pid));
+        
+        // 到期時先發送 SIGTERM，退出後立即重新啟動，不計入重啟次數；沒有 auto_restart 也會重啟
+        manager.get_process_mut("service").unwrap().next_restart_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        manager.check_processes().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        manager.check_processes().await.unwrap();
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("service").unwrap();
+        let second_pid = process.pid().expect("restarted after the scheduled restart");
+        assert_ne!(second_pid, first_pid);
+        assert_eq!(process.restart_count, 0);
+        assert!(process.next_restart_at.unwrap() > Utc::now());
+        
+        // 維護模式中跳過計劃重啟
+        manager.set_maintenance("service", Some(std::time::Duration::from_secs(60))).unwrap();
+        manager.get_process_mut("service").unwrap().next_restart_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        manager.check_processes().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("service").unwrap().pid(), Some(second_pid));
+        
+        // 不能與 schedule 一起使用
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "job"
+        command = "true"
+        schedule = "0 * * * *"
+        scheduled_restart = "0 3 * * *"
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("cannot combine scheduled_restart"), "{}", error);
+    }
+    
+    #[tokio::test]
+    async fn test_shell_command_quoting() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("shell.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "echo"
+        command = "printf '[%s]\n'"
+        args = ["it's", "a b", "$HOME", "x;y", ""]
+        shell = "sh"
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("echo").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        manager.check_processes().await.unwrap();
+        
+        // command 交給 shell 解析，args 原樣傳到命令中，不會被 shell 展開或拆分
+        log_handler.flush();
+        let log = fs::read_to_string(temp_dir.path().join("echo.log")).unwrap();
+        for expected in ["[it's]", "[a b]", "[$HOME]", "[x;y]", "[]"] {
+            assert!(log.contains(expected), "missing {} in {}", expected, log);
+        }
+        
+        // 命令行無法校驗可執行文件
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "echo"
+        command = "echo hi"
+        shell = "sh"
+        restart_on_binary_change = true
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("cannot be used with shell"), "{}", error);
+    }
+    
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn test_windows_shells() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("shells.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "cmd"
+        command = "echo"
+        args = ["a & b", "say \"hi\""]
+        shell = "cmd"
+        
+        [[process]]
+        name = "powershell"
+        command = "Write-Output"
+        args = ["it's $HOME", "C:\\Program Files\\"]
+        shell = "powershell"
+        
+        [[process]]
+        name = "server"
+        command = "ping"
+        args = ["-t", "127.0.0.1"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let log_handler = LogHandler::new("info").with_log_dir(Some(temp_dir.path().to_path_buf()));
+        let mut manager = ProcessManager::new(config_manager, log_handler.clone());
+        manager.start_process("cmd").await.unwrap();
+        manager.start_process("powershell").await.unwrap();
+        manager.start_process("server").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        manager.check_processes().await.unwrap();
+        
+        // 引號中的 & 不會被 cmd 當作命令分隔符；PowerShell 的單引號字符串不展開變量
+        log_handler.flush();
+        let cmd = fs::read_to_string(temp_dir.path().join("cmd.log")).unwrap();
+        assert!(cmd.contains(r#""a & b" "say ""hi""""#), "{}", cmd);
+        let powershell = fs::read_to_string(temp_dir.path().join("powershell.log")).unwrap();
+        assert!(powershell.contains("it's $HOME"), "{}", powershell);
+        assert!(powershell.contains(r"C:\Program Files\"), "{}", powershell);
+        
+        // SIGTERM 對應 CTRL_BREAK：ping 收到後自行退出
+        manager.terminate_process("server");
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("server").unwrap().pid(), None);
+    }
+    
+    #[cfg(target_os = "macos")]
+    #[tokio::test]
+    async fn test_siginfo_prints_status() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("siginfo.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "worker"
+        command = "sleep"
+        args = ["30"]
+        "#).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let mut lines = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        
+        // 進程啟動後按 Ctrl+T 時終端發送 SIGINFO，janus 打印所有進程的狀態
+        let read = async {
+            let mut output = Vec::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                if line.starts_with("All processes started") {
+                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                    unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGINFO) };
+                }
+                let done = output.iter().any(|line: &String| line.contains("Received SIGINFO")) && line.contains("worker");
+                output.push(line);
+                if done {
+                    break;
+                }
+            }
+            output.join("\n")
+        };
+        let output = tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap();
+        assert!(output.contains("Received SIGINFO, dumping status..."), "{}", output);
+        
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        supervisor.wait().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_start_progress_output() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("progress.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["30"]
+        
+        [[process]]
+        name = "broken"
+        command = "/nonexistent/janus-test-binary"
+        requires = ["web"]
+        "#).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let mut stdout = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        let mut stderr = BufReader::new(supervisor.stderr.take().unwrap()).lines();
+        let mut output = Vec::new();
+        let read = async {
+            while let Some(line) = stdout.next_line().await.unwrap() {
+                let done = line.contains("failed");
+                output.push(line);
+                if done && output.last().unwrap().contains("started") {
+                    break;
+                }
+            }
+            stderr.next_line().await.unwrap().unwrap()
+        };
+        let error = tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap();
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        supervisor.wait().await.unwrap();
+        
+        // 每個進程完成時打印一行結果，失敗寫入 stderr，最後是彙總表
+        let output = output.join("\n");
+        assert!(output.contains("[1/2] Started web ("), "{}", output);
+        assert!(error.starts_with("[2/2] Failed to start broken: "), "{}", error);
+        let table = output.lines().skip_while(|line| !line.starts_with("NAME")).collect::<Vec<_>>();
+        assert!(table[1].starts_with("web     started"), "{}", output);
+        assert!(table[2].starts_with("broken  failed"), "{}", output);
+        assert_eq!(table[3], "1 started, 1 failed");
+    }
+    
+    #[tokio::test]
+    async fn test_global_restart_rate_limit() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::events::EventKind;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("rate.toml");
+        fs::write(&config_path, r#"
+        [global]
+        restart_rate_limit = 2
+        
+        [[process]]
+        name = "worker-a"
+        command = "sh"
+        args = ["-c", "exit 1"]
+        auto_restart = true
+        restart_delay = 0
+        critical = false
+        
+        [[process]]
+        name = "worker-b"
+        command = "sh"
+        args = ["-c", "exit 1"]
+        auto_restart = true
+        restart_delay = 0
+        critical = false
+        
+        [[process]]
+        name = "api"
+        command = "sh"
+        args = ["-c", "sleep 0.1; exit 1"]
+        auto_restart = true
+        restart_delay = 0
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        let mut events = manager.subscribe_events();
+        for name in ["worker-a", "worker-b", "api"] {
+            manager.start_process(name).await.unwrap();
+        }
+        
+        // 窗口內所有進程合計最多重啟 2 次，之後非關鍵進程的重啟被暫緩，關鍵進程照常重啟
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while manager.get_process("api").unwrap().restart_count < 4 {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            manager.check_processes().await.unwrap();
+        }
+        let workers = ["worker-a", "worker-b"].map(|name| manager.get_process(name).unwrap().clone());
+        assert!(workers.iter().map(|process| process.restart_count).sum::<u32>() <= 2);
+        assert!(workers.iter().all(|process| process.pid().is_none() && process.restart_at.is_some()));
+        
+        // 每個被暫緩的進程發出一次 RestartPaused 事件
+        let mut paused = Vec::new();
+        loop {
+            match events.try_recv() {
+                Ok(event) if event.kind == EventKind::RestartPaused => paused.push(event.process),
+                Ok(_) | Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+        paused.sort();
+        assert_eq!(paused, ["worker-a", "worker-b"]);
+        
+        // restart_rate_limit 必須為正數
+        fs::write(&config_path, r#"
+        [global]
+        restart_rate_limit = 0
+        
+        [[process]]
+        name = "web"
+        command = "true"
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("restart_rate_limit"), "{}", error);
+    }
+    
+    #[tokio::test]
+    async fn test_cleanup_paths_removed_before_start_and_after_exit() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let pid_path = temp_dir.path().join("app.pid");
+        let socket_path = temp_dir.path().join("app.sock");
+        let config_path = temp_dir.path().join("cleanup.toml");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "app"
+        command = "sh"
+        args = ["-c", "[ ! -e app.sock ] && [ ! -e {pid} ] && touch app.sock {pid} && sleep 0.2"]
+        working_dir = "{dir}"
+        cleanup_paths = ["app.sock", "{pid}"]
+        "#, pid = pid_path.display(), dir = temp_dir.path().display())).unwrap();
+        
+        // 上次崩潰遺留的文件在啟動前被刪除，相對路徑按 working_dir 解析
+        fs::write(&pid_path, "12345").unwrap();
+        fs::write(&socket_path, "").unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("app").await.unwrap();
+        
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !socket_path.exists() {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        
+        // 進程退出後再次刪除
+        while manager.get_process("app").unwrap().pid().is_some() {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            manager.check_processes().await.unwrap();
+        }
+        assert_eq!(manager.get_process("app").unwrap().exit_code, Some(0));
+        assert!(!socket_path.exists());
+        assert!(!pid_path.exists());
+    }
+    
+    #[tokio::test]
+    async fn test_process_timeline() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let timeline_path = temp_dir.path().join("timeline.jsonl");
+        let config_path = temp_dir.path().join("timeline.toml");
+        fs::write(&config_path, format!(r#"
+        [global]
+        timeline_file = "{}"
+        
+        [[process]]
+        name = "flaky"
+        command = "sh"
+        args = ["-c", "exit 3"]
+        auto_restart = true
+        restart_delay = 0
+        restart_limit = 1
+        "#, timeline_path.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("flaky").await.unwrap();
+        
+        // 崩潰、重啟、再次崩潰後不再重啟
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            manager.check_processes().await.unwrap();
+            let events: Vec<String> = manager.timeline().lock().unwrap().entries("flaky").into_iter().map(|entry| entry.event).collect();
+            if events.len() == 4 {
+                assert_eq!(events, ["started", "exited", "started", "exited"]);
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "{:?}", events);
+        }
+        let entries = manager.timeline().lock().unwrap().entries("flaky");
+        assert_eq!(entries[1].exit_code, Some(3));
+        
+        // 時間線寫入 timeline_file，janus 重新啟動後恢復
+        assert_eq!(fs::read_to_string(&timeline_path).unwrap().lines().count(), 4);
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        assert_eq!(manager.timeline().lock().unwrap().entries("flaky"), entries);
+        
+        // janus timeline 通過 gRPC 接口讀取
+        let manager = Arc::new(Mutex::new(manager));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let janus = |args: &[&str]| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host, "timeline"])
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        let output = janus(&["flaky"]).await.unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert!(lines[0].starts_with("TIME"), "{}", stdout);
+        assert!(lines[2].contains("exited") && lines[2].contains(" 3  "), "{}", stdout);
+        assert_eq!(lines.last().unwrap(), &"4 event(s): 2 start(s), 2 failed start(s) or unclean exit(s)");
+        
+        // --since 只顯示最近的事件
+        let output = janus(&["flaky", "--since", "1h"]).await.unwrap();
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("TIME"));
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        let output = janus(&["flaky", "--since", "1s"]).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "No events recorded for flaky");
+        
+        let output = janus(&["missing"]).await.unwrap();
+        assert!(!output.status.success());
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_process() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("kill.toml");
+        // 忽略 SIGTERM 的進程
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "hung"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        auto_restart = true
+        restart_delay = 0
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("hung").await.unwrap();
+        let manager = Arc::new(Mutex::new(manager));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let janus = |args: &[&str]| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host, "kill"])
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        let output = janus(&["hung", "--signal", "BOGUS"]).await.unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown signal: BOGUS"));
+        
+        // 進程忽略 SIGTERM，仍在運行
+        let output = janus(&["hung", "--signal", "TERM"]).await.unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        manager.lock().await.check_processes().await.unwrap();
+        assert_eq!(manager.lock().await.get_process("hung").unwrap().status, ProcessStatus::Running);
+        
+        let output = janus(&["hung"]).await.unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Killing process with KILL: hung"));
+        
+        // 與 stop 一樣不會被自動重啟，stopped 事件記錄是被 kill 的
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let mut manager = manager.lock().await;
+        manager.check_processes().await.unwrap();
+        let process = manager.get_process("hung").unwrap();
+        assert_eq!(process.status, ProcessStatus::Stopped);
+        assert!(process.manual_stop);
+        assert!(process.pid().is_none());
+        let entries = manager.timeline().lock().unwrap().entries("hung");
+        let last = entries.last().unwrap();
+        assert_eq!((last.event.as_str(), last.detail.as_str()), ("stopped", "Killed by user with SIGKILL"));
+        drop(manager);
+        
+        let output = janus(&["hung"]).await.unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Process is not running: hung"));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_timeout_escalates_to_sigkill() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("stop_timeout.toml");
+        // stubborn 和 hung 忽略 SIGTERM，polite 收到 SIGTERM 後退出
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "stubborn"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        stop_timeout = 1
+        
+        [[process]]
+        name = "polite"
+        command = "sh"
+        args = ["-c", "trap 'exit 0' TERM; while true; do sleep 0.1; done"]
+        stop_timeout = 10
+        
+        [[process]]
+        name = "hung"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        stop_timeout = 60
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        for name in ["stubborn", "polite", "hung"] {
+            manager.start_process(name).await.unwrap();
+        }
+        
+        // 超過 stop_timeout 後改用 SIGKILL，並記錄在運行記錄和 stopped 事件中
+        manager.stop_process_manually("stubborn", None).await.unwrap();
+        let process = manager.get_process("stubborn").unwrap();
+        assert_eq!(process.status, ProcessStatus::Stopped);
+        assert!(process.last_stop_duration.unwrap() >= std::time::Duration::from_secs(1));
+        assert!(process.run_history.back().unwrap().required_kill);
+        manager.check_processes().await.unwrap();
+        let entries = manager.timeline().lock().unwrap().entries("stubborn");
+        assert_eq!(entries.last().unwrap().detail, "Did not stop within 1s, required SIGKILL");
+        
+        // 在超時前自行退出的進程不需要 SIGKILL
+        manager.stop_process_manually("polite", None).await.unwrap();
+        let process = manager.get_process("polite").unwrap();
+        assert_eq!(process.status, ProcessStatus::Stopped);
+        assert!(!process.run_history.back().unwrap().required_kill);
+        
+        // 通過接口停止時等待期間顯示 Stopping，kill 可以立即結束它
+        let manager = Arc::new(Mutex::new(manager));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let janus = |args: &[&str]| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host])
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        let stop = tokio::spawn(janus(&["stop-one", "hung"]));
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while manager.lock().await.get_process("hung").unwrap().status != ProcessStatus::Stopping {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        let output = janus(&["status"]).await.unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Status: Stopping"), "{}", stdout);
+        assert!(stdout.contains("SIGKILL after 1m 0s"), "{}", stdout);
+        
+        let output = janus(&["kill", "hung"]).await.unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let output = stop.await.unwrap().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let manager = manager.lock().await;
+        let process = manager.get_process("hung").unwrap();
+        assert_eq!(process.status, ProcessStatus::Stopped);
+        assert!(process.last_stop_duration.unwrap() < std::time::Duration::from_secs(60));
+    }
+    
+    #[tokio::test]
+    async fn test_start_window_defers_autostart() {
+        use chrono::{Local, TimeZone, Utc};
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::start_window::StartWindow;
+        
+        // 18:00 到 08:00 跨越午夜
+        let night = StartWindow::parse(Some("18:00"), Some("08:00"), Some("UTC")).unwrap().unwrap();
+        assert!(night.allows(Utc.with_ymd_and_hms(2026, 3, 2, 23, 0, 0).unwrap()));
+        assert!(night.allows(Utc.with_ymd_and_hms(2026, 3, 2, 7, 59, 0).unwrap()));
+        assert!(!night.allows(Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap()));
+        assert_eq!(night.to_string(), "18:00 to 08:00 (UTC)");
+        
+        let until = StartWindow::parse(None, Some("2026-03-01"), Some("UTC")).unwrap().unwrap();
+        assert!(until.allows(Utc.with_ymd_and_hms(2026, 2, 28, 12, 0, 0).unwrap()));
+        assert!(until.has_ended(Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()));
+        assert!(StartWindow::parse(Some("25:00"), None, None).is_err());
+        assert!(StartWindow::parse(Some("2026-03-02"), Some("2026-03-01"), None).is_err());
+        assert!(StartWindow::parse(None, None, None).unwrap().is_none());
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("window.toml");
+        let not_before = (Local::now() + chrono::Duration::seconds(2)).format("%Y-%m-%d %H:%M:%S");
+        fs::write(&config_path, format!(r#"
+        [[process]]
+        name = "importer"
+        command = "sleep"
+        args = ["30"]
+        not_before = "{}"
+        "#, not_before)).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        // 窗口打開前推遲啟動，打開後由監控循環啟動
+        assert!(manager.defer_start("importer"));
+        manager.check_processes().await.unwrap();
+        assert!(manager.get_process("importer").unwrap().pid().is_none());
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while manager.get_process("importer").unwrap().pid().is_none() {
+            assert!(std::time::Instant::now() < deadline);
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            manager.check_processes().await.unwrap();
+        }
+        assert!(!manager.get_process("importer").unwrap().waiting_for_window);
+        assert!(!manager.defer_start("importer"));
+        manager.stop_all().await.unwrap();
+        
+        // 格式錯誤的窗口在加載配置時報錯
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "importer"
+        command = "sleep"
+        not_after = "tomorrow"
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).err().unwrap().to_string();
+        assert!(error.contains("invalid not_after 'tomorrow'"), "{}", error);
+    }
+    
+    #[tokio::test]
+    async fn test_expected_start_duration() {
+        use janus::cli::status_reporter::StatusReporter;
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("expected.toml");
+        fs::write(&config_path, r#"
+        [global]
+        start_order = "slowest-first"
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        args = ["30"]
+        requires = ["jvm"]
+        
+        [[process]]
+        name = "cache"
+        command = "sleep"
+        args = ["30"]
+        
+        [[process]]
+        name = "jvm"
+        command = "sleep"
+        args = ["30"]
+        expected_start_duration = "90s"
+        
+        [[process]]
+        name = "worker"
+        command = "sleep"
+        args = ["30"]
+        requires = ["jvm"]
+        wait_timeout = 5
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        // 預計啟動最慢的進程先啟動，依賴關係仍然優先
+        let names: Vec<String> = ["api", "cache", "jvm", "worker"].iter().map(|name| name.to_string()).collect();
+        assert_eq!(manager.start_order(&names), ["jvm", "api", "cache", "worker"]);
+        
+        // 依賴慢啟動進程的等待時間默認延長到它的兩倍，顯式配置的 wait_timeout 不變
+        assert_eq!(manager.wait_timeout("api"), std::time::Duration::from_secs(180));
+        assert_eq!(manager.wait_timeout("worker"), std::time::Duration::from_secs(5));
+        assert_eq!(manager.wait_timeout("cache"), std::time::Duration::from_secs(60));
+        
+        assert_eq!(StatusReporter::starting_label(45, Some(90)), "starting (45s / expected 90s)");
+        manager.start_process("jvm").await.unwrap();
+        manager.start_process("cache").await.unwrap();
+        assert!(manager.get_process("jvm").unwrap().starting_for().is_some());
+        assert!(manager.get_process("cache").unwrap().starting_for().is_none());
+        
+        let manager = Arc::new(Mutex::new(manager));
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &host, "ps"])
+            .output()
+            .await
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("starting (0s / expected 90s)"), "{}", stdout);
+        assert!(stdout.lines().any(|line| line.starts_with("cache") && line.contains("Running")), "{}", stdout);
+        
+        manager.lock().await.stop_all().await.unwrap();
+    }
+    
+    #[test]
+    fn test_env_presets() {
+        use janus::config::manager::ConfigManager;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [global]
+        env = { LOG_LEVEL = "info", REGION = "eu" }
+        
+        [env_presets.db_creds]
+        DB_HOST = "db.internal"
+        DB_USER = "app"
+        LOG_LEVEL = "warn"
+        
+        [env_presets.tracing]
+        OTEL_ENDPOINT = "http://collector:4317"
+        DB_HOST = "db-traced.internal"
+        
+        [[process]]
+        name = "api"
+        command = "sleep"
+        env_presets = ["db_creds", "tracing"]
+        env = { DB_USER = "api" }
+        
+        [[process]]
+        name = "worker"
+        command = "sleep"
+        "#).unwrap();
+        
+        // 後者覆蓋前者：global.env、按順序的預設、進程自身的 env
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let api = &config_manager.get_process_configs()[0];
+        let env = api.env.clone().unwrap();
+        assert_eq!(env.len(), 5);
+        assert_eq!(env["LOG_LEVEL"], "warn");
+        assert_eq!(env["REGION"], "eu");
+        assert_eq!(env["DB_HOST"], "db-traced.internal");
+        assert_eq!(env["DB_USER"], "api");
+        assert_eq!(env["OTEL_ENDPOINT"], "http://collector:4317");
+        assert_eq!(config_manager.get_process_configs()[1].env.clone().unwrap().len(), 2);
+        
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "env", "api"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "DB_HOST=db-traced.internal\nDB_USER=api\nLOG_LEVEL=warn\nOTEL_ENDPOINT=http://collector:4317\nREGION=eu\n"
+        );
+        
+        // 引用未定義的預設時拒絕加載配置
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "api"
+        command = "sleep"
+        env_presets = ["missing"]
+        "#).unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("Process api: unknown env preset 'missing'"));
+    }
+    
+    #[tokio::test]
+    async fn test_mock_clock_drives_restart_delay_and_budget() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::events::EventKind;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use janus::test_util::{EventCapture, MockClock};
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "crasher"
+        command = "false"
+        auto_restart = true
+        restart_delay = 30
+        restart_budget = 2
+        restart_budget_window = 600
+        "#).unwrap();
         
-        // 使用 sleep 命令作為測試進程
-        let config_content = r#"
+        let clock = MockClock::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info")).with_clock(Arc::new(clock.clone()));
+        let mut events = EventCapture::new(&manager);
+        
+        // 進程本身仍是真實的，等待它退出並被回收
+        async fn wait_for_exit(manager: &mut ProcessManager, events: &mut EventCapture, exits: usize) {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while events.kinds("crasher").iter().filter(|kind| **kind == EventKind::Exited).count() < exits {
+                assert!(Instant::now() < deadline, "crasher did not exit");
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                manager.check_processes().await.unwrap();
+            }
+        }
+        
+        manager.start_process("crasher").await.unwrap();
+        wait_for_exit(&mut manager, &mut events, 1).await;
+        
+        // 時鐘不前進時重啟一直等待，推進到 restart_delay 才重啟
+        clock.advance(Duration::from_secs(29));
+        manager.check_processes().await.unwrap();
+        assert_eq!(events.kinds("crasher"), vec![EventKind::Started, EventKind::Exited]);
+        clock.advance(Duration::from_secs(1));
+        manager.check_processes().await.unwrap();
+        assert_eq!(events.kinds("crasher"), vec![EventKind::Started, EventKind::Exited, EventKind::Started]);
+        assert_eq!(manager.get_process("crasher").unwrap().restart_count, 1);
+        
+        wait_for_exit(&mut manager, &mut events, 2).await;
+        clock.advance(Duration::from_secs(30));
+        manager.check_processes().await.unwrap();
+        wait_for_exit(&mut manager, &mut events, 3).await;
+        
+        // 600 秒內已重啟兩次，預算用完
+        assert_eq!(manager.get_process("crasher").unwrap().status, ProcessStatus::GaveUp);
+        assert_eq!(events.kinds("crasher").last(), Some(&EventKind::GaveUp));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_start_duration_follows_manager_clock() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::test_util::{FakeSpawner, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
         [[process]]
-        name = "sleep-process"
+        name = "warming"
+        command = "/opt/app/bin/server"
+        expected_start_duration = "30s"
+        "#).unwrap();
+        
+        let clock = MockClock::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()))
+            .with_spawner(Arc::new(FakeSpawner::new()));
+        
+        // 就緒和啟動耗時按管理器的時鐘計算，不受真實時間影響
+        manager.start_process("warming").await.unwrap();
+        clock.advance(Duration::from_secs(29));
+        manager.check_processes().await.unwrap();
+        assert!(manager.get_process("warming").unwrap().last_start_duration.is_none());
+        clock.advance(Duration::from_secs(1));
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("warming").unwrap().last_start_duration, Some(Duration::from_secs(30)));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_fake_spawner_drives_start_stop_and_restart() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::events::EventKind;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use janus::test_util::{EventCapture, FakeSpawner, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "api"
+        command = "/opt/api/bin/server"
+        args = ["--port", "8080"]
+        auto_restart = true
+        restart_delay = 5
+        stop_timeout = 10
+        
+        [[process]]
+        name = "missing"
+        command = "/opt/missing"
+        "#).unwrap();
+        
+        let clock = MockClock::new();
+        let spawner = FakeSpawner::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()))
+            .with_spawner(Arc::new(spawner.clone()));
+        let mut events = EventCapture::new(&manager);
+        
+        // 不運行任何程序，只記錄命令
+        manager.start_process("api").await.unwrap();
+        let spawned = spawner.spawned();
+        assert_eq!(spawned.len(), 1);
+        assert_eq!(spawned[0].program, "/opt/api/bin/server");
+        assert_eq!(spawned[0].args, ["--port", "8080"]);
+        assert_eq!(manager.get_process("api").unwrap().status, ProcessStatus::Running);
+        
+        // 崩潰後按 restart_delay 重啟
+        spawner.exit("api", 1);
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("api").unwrap().exit_code, Some(1));
+        clock.advance(Duration::from_secs(4));
+        manager.check_processes().await.unwrap();
+        assert_eq!(spawner.spawn_count("api"), 1);
+        clock.advance(Duration::from_secs(1));
+        manager.check_processes().await.unwrap();
+        assert_eq!(spawner.spawn_count("api"), 2);
+        assert_eq!(events.kinds("api"), vec![EventKind::Started, EventKind::Exited, EventKind::Started]);
+        
+        // 不理會 SIGTERM 的進程在 stop_timeout 之後被殺死
+        spawner.ignore_sigterm("api");
+        manager.restart_process("api").await.unwrap();
+        assert_eq!(spawner.spawn_count("api"), 3);
+        events.clear();
+        assert!(manager.begin_stop("api"));
+        assert_eq!(spawner.signals("api"), vec![libc::SIGTERM]);
+        assert!(manager.poll_stop("api").await.is_none());
+        assert_eq!(manager.get_process("api").unwrap().status, ProcessStatus::Stopping);
+        clock.advance(Duration::from_secs(10));
+        manager.poll_stop("api").await.unwrap().unwrap();
+        assert_eq!(spawner.signals("api"), vec![libc::SIGTERM, libc::SIGKILL]);
+        assert!(!spawner.is_running("api"));
+        assert_eq!(manager.get_process("api").unwrap().status, ProcessStatus::Stopped);
+        assert_eq!(events.kinds("api"), vec![EventKind::Stopped]);
+        
+        // 啟動失敗如同可執行文件不存在
+        spawner.fail_spawn("missing", true);
+        assert!(manager.start_process("missing").await.is_err());
+        assert_eq!(manager.get_process("missing").unwrap().status, ProcessStatus::Failed);
+        assert_eq!(events.kinds("missing"), vec![EventKind::StartFailed]);
+    }
+    
+    // 模擬 Docker Engine API：記錄請求行和正文；同名容器帶 label 中的實例標籤 (為空時不存在)，
+    // 第一次創建時鏡像不存在，日誌流和 wait 在 exited 變為 true 時結束
+    #[cfg(unix)]
+    fn spawn_fake_container_api(
+        socket: &std::path::Path,
+        label: std::sync::Arc<std::sync::Mutex<String>>,
+        exited: tokio::sync::watch::Receiver<bool>,
+    ) -> tokio::sync::mpsc::UnboundedReceiver<(String, String)> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::UnixListener;
+        
+        let listener = UnixListener::bind(socket).unwrap();
+        let (sender, requests) = tokio::sync::mpsc::unbounded_channel();
+        let pulled = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let (sender, label, pulled, mut exited) = (sender.clone(), label.clone(), pulled.clone(), exited.clone());
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 4096];
+                    let (head, body) = loop {
+                        let read = stream.read(&mut buffer).await.unwrap();
+                        request.extend_from_slice(&buffer[..read]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                            let length = head
+                                .lines()
+                                .find_map(|line| line.strip_prefix("Content-Length: "))
+                                .and_then(|length| length.parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if body.len() >= length || read == 0 {
+                                break (head.to_string(), body.to_string());
+                            }
+                        }
+                    };
+                    let line = head.lines().next().unwrap().trim_end_matches(" HTTP/1.0").to_string();
+                    sender.send((line.clone(), body)).unwrap();
+                    
+                    let respond = |status: &str, body: &str| format!("HTTP/1.0 {}\r\nContent-Type: application/json\r\n\r\n{}", status, body);
+                    let response = match line.split_once(' ').unwrap() {
+                        ("POST", path) if path.starts_with("/containers/create") && !pulled.load(std::sync::atomic::Ordering::SeqCst) => {
+                            respond("404 Not Found", r#"{"message":"No such image: redis:7"}"#)
+                        }
+                        ("POST", path) if path.starts_with("/containers/create") => respond("201 Created", r#"{"Id":"c1"}"#),
+                        ("POST", path) if path.starts_with("/images/create") => {
+                            pulled.store(true, std::sync::atomic::Ordering::SeqCst);
+                            respond("200 OK", "{\"status\":\"Pulling from library/redis\"}\n{\"status\":\"Downloaded newer image for redis:7\"}\n")
+                        }
+                        ("GET", "/containers/c1/json") => respond("200 OK", r#"{"Id":"c1","State":{"Pid":4242}}"#),
+                        ("GET", path) if path.starts_with("/containers/c1/logs") => {
+                            let mut frames = b"HTTP/1.0 200 OK\r\nContent-Type: application/vnd.docker.multiplexed-stream\r\n\r\n".to_vec();
+                            for (stream, text) in [(1u8, "ready\n"), (2u8, "warn\n")] {
+                                frames.extend([stream, 0, 0, 0]);
+                                frames.extend((text.len() as u32).to_be_bytes());
+                                frames.extend(text.as_bytes());
+                            }
+                            stream.write_all(&frames).await.unwrap();
+                            let _ = exited.wait_for(|exited| *exited).await;
+                            return;
+                        }
+                        ("POST", "/containers/c1/wait") => {
+                            let _ = exited.wait_for(|exited| *exited).await;
+                            respond("200 OK", r#"{"StatusCode":137,"Error":null}"#)
+                        }
+                        ("GET", path) if path.ends_with("/json") => match label.lock().unwrap().as_str() {
+                            "" => respond("404 Not Found", r#"{"message":"No such container"}"#),
+                            instance => respond("200 OK", &format!(r#"{{"Config":{{"Labels":{{"io.janus.instance":"{}"}}}}}}"#, instance)),
+                        },
+                        ("POST", _) | ("DELETE", _) => respond("204 No Content", ""),
+                        _ => respond("404 Not Found", r#"{"message":"page not found"}"#),
+                    };
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        requests
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_container_image_process() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("api.sock");
+        let (exit, exited) = tokio::sync::watch::channel(false);
+        let mut requests = spawn_fake_container_api(&socket, Arc::new(Mutex::new(String::new())), exited);
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, format!(r#"
+        [global]
+        container_socket = "unix://{}"
+        
+        [[process]]
+        name = "cache"
+        image = "redis:7"
+        args = ["--appendonly", "yes"]
+        container_ports = ["127.0.0.1:6379:6379"]
+        container_volumes = ["/data/redis:/data"]
+        container_host_config = {{ Memory = 536870912 }}
+        env = {{ REDIS_PASSWORD = "secret", LOG_FORMAT = "json" }}
+        auto_restart = true
+        "#, socket.display())).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        
+        // 鏡像不存在時先拉取再創建；容器名和標籤包含由配置文件路徑得到的實例標識
+        manager.start_process("cache").await.unwrap();
+        let instance = janus::process::container::instance_id(config_path.to_str().unwrap());
+        assert_eq!(instance.len(), 12);
+        let mut received = Vec::new();
+        while let Ok((line, body)) = requests.try_recv() {
+            received.push((line, body));
+        }
+        let lines: Vec<&str> = received.iter().map(|(line, _)| line.as_str()).collect();
+        let create = format!("POST /containers/create?name=janus-{}-cache", instance);
+        assert_eq!(lines, [
+            format!("GET /containers/janus-{}-cache/json", instance).as_str(),
+            &create,
+            "POST /images/create?fromImage=redis%3A7",
+            &create,
+            "POST /containers/c1/start",
+            "GET /containers/c1/json",
+            "GET /containers/c1/logs?follow=1&stdout=1&stderr=1",
+        ]);
+        let body: serde_json::Value = serde_json::from_str(&received[3].1).unwrap();
+        assert_eq!(body["Image"], "redis:7");
+        assert_eq!(body["Cmd"], serde_json::json!(["--appendonly", "yes"]));
+        assert_eq!(body["Env"], serde_json::json!(["LOG_FORMAT=json", "REDIS_PASSWORD=secret"]));
+        assert_eq!(body["Labels"]["io.janus.instance"], instance.as_str());
+        assert_eq!(body["ExposedPorts"], serde_json::json!({ "6379/tcp": {} }));
+        assert_eq!(body["HostConfig"]["PortBindings"]["6379/tcp"], serde_json::json!([{ "HostIp": "127.0.0.1", "HostPort": "6379" }]));
+        assert_eq!(body["HostConfig"]["Binds"], serde_json::json!(["/data/redis:/data"]));
+        assert_eq!(body["HostConfig"]["Memory"], 536870912);
+        
+        // pid 是容器中主進程的 pid，日誌從 API 的日誌流中拆分為 stdout 和 stderr
+        let process = manager.get_process("cache").unwrap();
+        assert_eq!(process.status, ProcessStatus::Running);
+        assert_eq!(process.pid(), Some(4242));
+        assert_eq!(manager.snapshot().processes[0].image.as_deref(), Some("redis:7"));
+        let recent_output = process.recent_output.clone();
+        for _ in 0..100 {
+            if recent_output.lines().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let mut lines = recent_output.lines();
+        lines.sort();
+        assert_eq!(lines.len(), 2, "{:?}", lines);
+        assert!(lines[0].contains("ready") && lines[1].contains("warn"), "{:?}", lines);
+        
+        // 信號經由 API 發送；等待退出的請求在後台發出
+        manager.signal_process("cache", libc::SIGTERM);
+        let mut lines = Vec::new();
+        while !lines.iter().any(|line| line == "POST /containers/c1/kill?signal=15") {
+            let (line, _) = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+            lines.push(line);
+        }
+        
+        // 容器退出後被刪除，退出碼按普通進程處理
+        exit.send(true).unwrap();
+        for _ in 0..100 {
+            manager.check_processes().await.unwrap();
+            if manager.get_process("cache").unwrap().exit_code.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let process = manager.get_process("cache").unwrap();
+        assert_eq!(process.exit_code, Some(137));
+        assert!(process.restart_at.is_some());
+        while let Ok((line, _)) = requests.try_recv() {
+            lines.push(line);
+        }
+        lines.sort();
+        assert_eq!(lines, [
+            "DELETE /containers/c1?force=1&v=1",
+            "POST /containers/c1/kill?signal=15",
+            "POST /containers/c1/wait",
+        ]);
+        
+        // 依賴可執行文件本身或進程的選項不能與 image 一起使用
+        for (option, message) in [
+            ("shell = \"sh\"", "Process cache: shell cannot be used with image"),
+            ("memory_limit = \"512M\"", "Process cache: memory_limit cannot be used with image"),
+            ("container_ports = [\"6379:redis\"]", "Process cache: invalid container port 6379:redis"),
+        ] {
+            fs::write(&config_path, format!("[[process]]\nname = \"cache\"\nimage = \"redis:7\"\n{}\n", option)).unwrap();
+            let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+            assert!(error.to_string().contains(message), "{}", error);
+        }
+        fs::write(&config_path, "[[process]]\nname = \"cache\"\ncommand = \"redis-server\"\ncontainer_ports = [\"6379:6379\"]\n").unwrap();
+        let error = ConfigManager::new(config_path.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("container_ports requires image"));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_container_remove_stale_only_own_instance() {
+        use janus::process::container::{Api, ContainerName};
+        use std::sync::{Arc, Mutex};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let socket = temp_dir.path().join("api.sock");
+        let label = Arc::new(Mutex::new(String::new()));
+        let (_exit, exited) = tokio::sync::watch::channel(false);
+        let mut requests = spawn_fake_container_api(&socket, label.clone(), exited);
+        let api = Api::new(&socket);
+        let container = ContainerName::new("abc123", "cache");
+        assert_eq!(container.name, "janus-abc123-cache");
+        let mut received = || {
+            let mut lines = Vec::new();
+            while let Ok((line, _)) = requests.try_recv() {
+                lines.push(line);
+            }
+            lines
+        };
+        
+        // 同名容器不存在或屬於另一個 janus 實例時不刪除
+        api.remove_stale(&container).await;
+        assert_eq!(received(), ["GET /containers/janus-abc123-cache/json"]);
+        *label.lock().unwrap() = "other".to_string();
+        api.remove_stale(&container).await;
+        assert_eq!(received(), ["GET /containers/janus-abc123-cache/json"]);
+        
+        // 本實例遺留的容器被刪除
+        *label.lock().unwrap() = "abc123".to_string();
+        api.remove_stale(&container).await;
+        assert_eq!(received(), [
+            "GET /containers/janus-abc123-cache/json",
+            "DELETE /containers/janus-abc123-cache?force=1&v=1",
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn test_kubernetes_pod_status() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+        
+        // 模擬 Kubernetes API，收集 PATCH 請求並返回 200
+        let api = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = api.local_addr().unwrap();
+        let (sender, mut requests) = tokio::sync::mpsc::unbounded_channel::<String>();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = api.accept().await.unwrap();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 4096];
+                    loop {
+                        let read = stream.read(&mut buffer).await.unwrap();
+                        if read == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&buffer[..read]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                            let length = head
+                                .lines()
+                                .find_map(|line| line.strip_prefix("Content-Length: "))
+                                .and_then(|length| length.parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if body.len() >= length {
+                                break;
+                            }
+                        }
+                    }
+                    sender.send(String::from_utf8_lossy(&request).to_string()).unwrap();
+                    stream.write_all(b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}").await.unwrap();
+                });
+            }
+        });
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("kubernetes.toml");
+        fs::write(&config_path, format!(r#"
+        [global.kubernetes_status]
+        condition = "janus.io/healthy"
+        annotation = "janus.io/status"
+        api_server = "http://{}"
+        
+        [[process]]
+        name = "web"
         command = "sleep"
-        args = ["10"]
-        "#;
+        args = ["30"]
         
-        fs::write(&config_path, config_content).unwrap();
+        [[process]]
+        name = "broken"
+        command = "sh"
+        args = ["-c", "sleep 1; exit 3"]
+        "#, address)).unwrap();
         
-        // 啟動進程
-        let status = Command::new("cargo")
-            .args(&["run", "--", "--config", config_path.to_str().unwrap(), "start"])
-            .status()
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .env("POD_NAME", "web-0")
+            .env("POD_NAMESPACE", "prod")
+            .stdout(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
             .unwrap();
         
-        assert!(status.success());
+        // 先報告健康，broken 退出後報告不健康
+        let mut received = Vec::new();
+        let collect = async {
+            while let Some(request) = requests.recv().await {
+                received.push(request);
+                let unhealthy = received.iter().filter(|request| request.contains("broken (Failed)")).count();
+                if unhealthy == 2 {
+                    break;
+                }
+            }
+        };
+        let finished = tokio::time::timeout(std::time::Duration::from_secs(15), collect).await;
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        supervisor.wait().await.unwrap();
+        assert!(finished.is_ok(), "{:#?}", received);
         
-        // 檢查進程狀態
-        let output = Command::new("cargo")
-            .args(&["run", "--", "--config", config_path.to_str().unwrap(), "status"])
+        let condition: Vec<&String> = received
+            .iter()
+            .filter(|request| request.starts_with("PATCH /api/v1/namespaces/prod/pods/web-0/status HTTP/1.0"))
+            .collect();
+        assert!(condition.iter().all(|request| request.contains("application/strategic-merge-patch+json")));
+        assert!(condition.first().unwrap().contains(r#""status":"True""#), "{:#?}", condition);
+        assert!(condition.first().unwrap().contains(r#""type":"janus.io/healthy""#));
+        let failed = condition.last().unwrap();
+        assert!(failed.contains(r#""status":"False""#), "{}", failed);
+        assert!(failed.contains(r#""reason":"ProcessesUnhealthy""#), "{}", failed);
+        assert!(failed.contains("lastTransitionTime"), "{}", failed);
+        
+        let annotation: Vec<&String> = received
+            .iter()
+            .filter(|request| request.starts_with("PATCH /api/v1/namespaces/prod/pods/web-0 HTTP/1.0"))
+            .collect();
+        assert!(annotation.first().unwrap().contains(r#"{"metadata":{"annotations":{"janus.io/status":"healthy"}}}"#), "{:#?}", annotation);
+        assert!(annotation.last().unwrap().contains(r#""janus.io/status":"unhealthy: broken (Failed)""#));
+    }
+    
+    #[test]
+    fn test_processes_from_environment() {
+        use janus::config::env::merge_processes;
+        use janus::config::ProcessConfig;
+        
+        let file: Vec<ProcessConfig> = toml::from_str::<janus::config::Config>(r#"
+        [[process]]
+        name = "web"
+        command = "node"
+        args = ["server.js"]
+        restart_delay = 5
+        env = { NODE_ENV = "production", PORT = "3000" }
+        "#).unwrap().process;
+        let vars = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+        };
+        
+        // 與配置文件中同名的進程合併，環境變量優先；env 按鍵合併
+        let mut processes = file.clone();
+        merge_processes(&mut processes, vars(&[
+            ("PATH", "/usr/bin"),
+            ("JANUS_PROCESS_WEB_ARGS", "server.js --inspect"),
+            ("JANUS_PROCESS_WEB_AUTO_RESTART", "true"),
+            ("JANUS_PROCESS_WEB_ENV_PORT", "8080"),
+            ("JANUS_PROCESS_MY_JOB_COMMAND", "sh"),
+            ("JANUS_PROCESS_MY_JOB_ARGS", r#"["-c", "echo hello world"]"#),
+            ("JANUS_PROCESS_MY_JOB_RESTART_LIMIT", "3"),
+            ("JANUS_PROCESS_MY_JOB_ENV_NODE_ENV", "test"),
+        ])).unwrap();
+        assert_eq!(processes.len(), 2);
+        let web = &processes[0];
+        assert_eq!(web.command, "node");
+        assert_eq!(web.args, Some(vec!["server.js".to_string(), "--inspect".to_string()]));
+        assert_eq!(web.auto_restart, Some(true));
+        assert_eq!(web.restart_delay, Some(5));
+        let env = web.env.clone().unwrap();
+        assert_eq!(env["NODE_ENV"], "production");
+        assert_eq!(env["PORT"], "8080");
+        
+        // 進程名可以包含下劃線
+        let job = &processes[1];
+        assert_eq!(job.name, "my_job");
+        assert_eq!(job.args, Some(vec!["-c".to_string(), "echo hello world".to_string()]));
+        assert_eq!(job.restart_limit, Some(3));
+        assert_eq!(job.env.clone().unwrap()["NODE_ENV"], "test");
+        
+        // 無法識別的配置項和類型錯誤的值
+        let mut processes = file.clone();
+        let error = merge_processes(&mut processes, vars(&[("JANUS_PROCESS_WEB_COMAND", "node")])).unwrap_err();
+        assert!(error.to_string().contains("Unrecognized variable JANUS_PROCESS_WEB_COMAND"), "{}", error);
+        let error = merge_processes(&mut processes, vars(&[("JANUS_PROCESS_WEB_RESTART_DELAY", "soon")])).unwrap_err();
+        assert!(error.to_string().contains("Process web defined by JANUS_PROCESS_WEB_* variables"), "{}", error);
+        
+        // 沒有配置文件時只使用環境變量中的進程
+        let temp_dir = TempDir::new().unwrap();
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["env", "api"])
+            .current_dir(temp_dir.path())
+            .env("JANUS_PROCESS_API_COMMAND", "sleep")
+            .env("JANUS_PROCESS_API_ENV_GREETING", "hello")
             .output()
             .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "GREETING=hello\n");
+    }
+    
+    #[test]
+    fn test_guidance_without_config_or_processes() {
+        let temp_dir = TempDir::new().unwrap();
+        let janus = |args: &[&str]| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(args)
+                .current_dir(temp_dir.path())
+                .output()
+                .unwrap()
+        };
         
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        assert!(output_str.contains("RUNNING"));
+        // 沒有配置文件時說明如何開始，錯誤不以 Debug 格式打印
+        let output = janus(&["status"]);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.starts_with("Error: Config error: No config file at janus.toml."), "{}", stderr);
+        assert!(stderr.contains("JANUS_PROCESS_<NAME>_COMMAND"), "{}", stderr);
         
-        // 停止進程
-        let status = Command::new("cargo")
-            .args(&["run", "--", "--config", config_path.to_str().unwrap(), "stop"])
-            .status()
+        // 子命令的幫助不需要配置文件
+        let output = janus(&["start", "--help"]);
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Usage: janus start"));
+        let output = janus(&["help", "status"]);
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Usage: janus status"));
+        
+        // 配置中沒有進程
+        fs::write(temp_dir.path().join("janus.toml"), "[global]\ngrpc_listen = \"127.0.0.1:1\"\n").unwrap();
+        let output = janus(&["status"]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("No processes configured\nAdd [[process]] sections"), "{}", stdout);
+        
+        // janus start 沒有運行
+        let output = janus(&["maintenance", "on", "web"]);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Failed to connect to 127.0.0.1:1"), "{}", stderr);
+        assert!(stderr.contains("Is janus start running and serving its gRPC API (global.grpc_listen) on 127.0.0.1:1?"), "{}", stderr);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reload_preserves_uptime_and_history() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use janus::test_util::{FakeSpawner, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        let config = |worker_args: &str| format!(r#"
+        [[process]]
+        name = "api"
+        command = "/opt/api"
+        auto_restart = true
+        restart_delay = 1
+        
+        [[process]]
+        name = "worker"
+        command = "/opt/worker"
+        args = [{}]
+        auto_restart = true
+        restart_delay = 1
+        "#, worker_args);
+        fs::write(&config_path, config(r#""--queue", "default""#)).unwrap();
+        
+        let clock = MockClock::new();
+        let spawner = FakeSpawner::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()))
+            .with_spawner(Arc::new(spawner.clone()));
+        
+        // 兩個進程各崩潰並重啟一次
+        for name in ["api", "worker"] {
+            manager.start_process(name).await.unwrap();
+            spawner.exit(name, 1);
+        }
+        manager.check_processes().await.unwrap();
+        clock.advance(Duration::from_secs(1));
+        manager.check_processes().await.unwrap();
+        let api = manager.get_process("api").unwrap();
+        assert_eq!(api.status, ProcessStatus::Running);
+        assert_eq!(api.restart_count, 1);
+        assert_eq!(api.run_history.len(), 1);
+        let (api_pid, api_started) = (api.pid(), api.start_time);
+        assert_eq!(manager.get_process("worker").unwrap().run_history.len(), 1);
+        
+        // 不涉及 api 的重新加載：同一個進程繼續運行，運行時間、重啟計數和歷史不變
+        std::thread::sleep(Duration::from_millis(1100));
+        fs::write(&config_path, config(r#""--queue", "urgent""#)).unwrap();
+        manager.reload().await.unwrap();
+        let api = manager.get_process("api").unwrap();
+        assert_eq!(api.status, ProcessStatus::Running);
+        assert_eq!(api.pid(), api_pid);
+        assert_eq!(api.start_time, api_started);
+        assert_eq!(api.restart_count, 1);
+        assert_eq!(api.run_history.len(), 1);
+        assert_eq!(spawner.spawn_count("api"), 2);
+        let snapshot = manager.snapshot();
+        let uptime = snapshot.processes.iter().find(|process| process.name == "api").unwrap().uptime_secs;
+        assert!(uptime >= Some(1), "{:?}", uptime);
+        
+        // 變更的 worker 按新配置重新啟動，保留運行歷史，重啟計數重新開始
+        let worker = manager.get_process("worker").unwrap();
+        assert_eq!(worker.status, ProcessStatus::Running);
+        assert_eq!(worker.args, ["--queue", "urgent"]);
+        assert_eq!(worker.restart_count, 0);
+        assert_eq!(worker.run_history.len(), 2);
+        assert_eq!(spawner.spawn_count("worker"), 3);
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_console_failover_after_broken_pipe() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        let failover_path = temp_dir.path().join("console.log");
+        fs::write(&config_path, format!(r#"
+        [global]
+        log_failover_file = "{}"
+        sigusr1_action = "dump-status"
+        
+        [[process]]
+        name = "talker"
+        command = "sh"
+        args = ["-c", "while true; do echo tick; sleep 0.05; done"]
+        "#, failover_path.display())).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
             .unwrap();
         
-        assert!(status.success());
+        // 讀到進程的輸出後關閉管道的讀取端，如同日誌驅動退出
+        let mut lines = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        let read = async {
+            while let Some(line) = lines.next_line().await.unwrap() {
+                if line.contains("tick") {
+                    break;
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap();
+        drop(lines);
         
-        // 再次檢查狀態
-        let output = Command::new("cargo")
-            .args(&["run", "--", "--config", config_path.to_str().unwrap(), "status"])
+        let wait_for = |text: &'static str| {
+            let failover_path = failover_path.clone();
+            async move {
+                let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+                loop {
+                    let content = fs::read_to_string(&failover_path).unwrap_or_default();
+                    if content.contains(text) {
+                        return content;
+                    }
+                    assert!(std::time::Instant::now() < deadline, "{:?} not in failover file: {}", text, content);
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+            }
+        };
+        
+        // 之後的輸出寫入 failover 文件，janus 繼續運行並在狀態報告中計數
+        wait_for("[talker] tick").await;
+        // 狀態報告來自監控循環發布的快照，等下一輪檢查發布切換後的計數
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGUSR1) };
+        let content = wait_for("console output redirected to log_failover_file").await;
+        assert!(content.contains("Log output: "), "{}", content);
+        assert!(supervisor.try_wait().unwrap().is_none());
+        
+        unsafe { libc::kill(supervisor.id().unwrap() as i32, libc::SIGTERM) };
+        let status = supervisor.wait().await.unwrap();
+        assert!(status.success(), "{:?}", status);
+    }
+    
+    #[tokio::test]
+    async fn test_clock_jump_rearms_timers() {
+        use chrono::{TimeDelta, TimeZone, Utc};
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::test_util::{FakeSpawner, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "report"
+        command = "/opt/report"
+        every = "10m"
+        
+        [[process]]
+        name = "nightly"
+        command = "/opt/nightly"
+        schedule = "0 3 * * *"
+        timezone = "UTC"
+        "#).unwrap();
+        
+        let start = Utc.with_ymd_and_hms(2026, 3, 10, 2, 0, 0).unwrap();
+        let clock = MockClock::starting_at(start);
+        let spawner = FakeSpawner::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()))
+            .with_spawner(Arc::new(spawner.clone()));
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("nightly").unwrap().next_scheduled_at, Some(start + TimeDelta::hours(1)));
+        
+        // 小幅的校正不算跳變
+        clock.advance(Duration::from_secs(1));
+        clock.jump(TimeDelta::seconds(10));
+        manager.check_processes().await.unwrap();
+        assert_eq!(spawner.spawn_count("report"), 0);
+        
+        // 時間被撥回一天：cron 排程從新的時間重新計算，不必等待 25 小時
+        clock.advance(Duration::from_secs(1));
+        clock.jump(TimeDelta::days(-1));
+        manager.check_processes().await.unwrap();
+        let now = start + TimeDelta::seconds(12) - TimeDelta::days(1);
+        assert_eq!(
+            manager.get_process("nightly").unwrap().next_scheduled_at,
+            Some(Utc.with_ymd_and_hms(2026, 3, 9, 3, 0, 0).unwrap())
+        );
+        assert!(manager.get_process("nightly").unwrap().next_scheduled_at.unwrap() > now);
+        assert_eq!(spawner.spawn_count("nightly"), 0);
+        
+        // 休眠一小時後恢復 (單調時鐘沒有前進)：every 計時器到期，本輪運行一次
+        clock.advance(Duration::from_secs(1));
+        clock.jump(TimeDelta::hours(1));
+        manager.check_processes().await.unwrap();
+        assert_eq!(spawner.spawn_count("report"), 1);
+        spawner.exit("report", 0);
+        manager.check_processes().await.unwrap();
+        assert_eq!(spawner.spawn_count("report"), 1);
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_open_fds_warning() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::events::EventKind;
+        use janus::process::fds::FdUsage;
+        use janus::process::manager::ProcessManager;
+        use janus::test_util::{EventCapture, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        // 沒有配置 max_open_fds 時按軟限制的 90% 警告，限制為 unlimited 時不警告
+        let usage = FdUsage { open: 10, limit: Some(1000) };
+        assert_eq!(usage.threshold(None), Some(900));
+        assert_eq!(usage.threshold(Some(64)), Some(64));
+        assert_eq!(FdUsage { open: 10, limit: None }.threshold(None), None);
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "leaky"
+        command = "sh"
+        args = ["-c", "exec 3</dev/null; sleep 1; exec 3<&-; sleep 30"]
+        max_open_fds = 4
+        
+        [[process]]
+        name = "quiet"
+        command = "sleep"
+        args = ["30"]
+        "#).unwrap();
+        
+        let clock = MockClock::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()));
+        let mut events = EventCapture::new(&manager);
+        manager.start_process("leaky").await.unwrap();
+        manager.start_process("quiet").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        
+        // stdin、stdout、stderr 和打開的第 4 個描述符達到 max_open_fds
+        manager.check_processes().await.unwrap();
+        assert_eq!(events.kinds("leaky"), vec![EventKind::Started, EventKind::FdsHigh]);
+        assert_eq!(events.kinds("quiet"), vec![EventKind::Started]);
+        let snapshot = manager.snapshot();
+        let leaky = snapshot.processes.iter().find(|process| process.name == "leaky").unwrap();
+        assert_eq!(leaky.open_fds, Some(4));
+        assert!(leaky.fd_limit.is_some());
+        
+        // 採樣間隔內不再讀取，也不重複警告；關閉描述符後下一次採樣回落到閾值以下
+        manager.check_processes().await.unwrap();
+        for _ in 0..50 {
+            clock.advance(Duration::from_secs(10));
+            manager.check_processes().await.unwrap();
+            let snapshot = manager.snapshot();
+            if snapshot.processes.iter().any(|process| process.name == "leaky" && process.open_fds == Some(3)) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let snapshot = manager.snapshot();
+        let leaky = snapshot.processes.iter().find(|process| process.name == "leaky").unwrap();
+        assert_eq!(leaky.open_fds, Some(3));
+        assert_eq!(events.kinds("leaky"), vec![EventKind::Started, EventKind::FdsHigh]);
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_mock_clock_drives_maintenance() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use janus::test_util::{FakeSpawner, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "api"
+        command = "/opt/api"
+        auto_restart = true
+        restart_delay = 1
+        "#).unwrap();
+        
+        let clock = MockClock::new();
+        let spawner = FakeSpawner::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()))
+            .with_spawner(Arc::new(spawner.clone()));
+        manager.start_process("api").await.unwrap();
+        
+        // 維護期間退出的進程不重啟
+        manager.set_maintenance("api", Some(Duration::from_secs(1800))).unwrap();
+        spawner.exit("api", 1);
+        manager.check_processes().await.unwrap();
+        clock.advance(Duration::from_secs(1799));
+        manager.check_processes().await.unwrap();
+        assert!(manager.get_process("api").unwrap().maintenance_until.is_some());
+        assert_eq!(spawner.spawn_count("api"), 1);
+        
+        // 維護期限按管理器的時鐘計算，時鐘走過 30 分鐘後結束
+        clock.advance(Duration::from_secs(1));
+        manager.check_processes().await.unwrap();
+        assert!(manager.get_process("api").unwrap().maintenance_until.is_none());
+        assert_ne!(manager.get_process("api").unwrap().status, ProcessStatus::Running);
+    }
+    
+    #[tokio::test]
+    async fn test_stable_after_resets_restart_count() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        use janus::test_util::{FakeSpawner, MockClock};
+        use std::sync::Arc;
+        use std::time::Duration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        let config = |name: &str, stable_after: &str| format!(r#"
+        [[process]]
+        name = "{}"
+        command = "/opt/api"
+        auto_restart = true
+        restart_delay = 1
+        restart_limit = 2
+        {}
+        "#, name, stable_after);
+        fs::write(&config_path, [config("api", r#"stable_after = "10m""#), config("legacy", "")].concat()).unwrap();
+        
+        let clock = MockClock::new();
+        let spawner = FakeSpawner::new();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"))
+            .with_clock(Arc::new(clock.clone()))
+            .with_spawner(Arc::new(spawner.clone()));
+        // 啟動時間按管理器的時鐘記錄，時鐘在啟動前已經走過的時間不算作穩定運行
+        clock.advance(Duration::from_secs(3600));
+        
+        // 兩個進程各連續崩潰兩次，用完 restart_limit
+        for name in ["api", "legacy"] {
+            manager.start_process(name).await.unwrap();
+        }
+        for _ in 0..2 {
+            for name in ["api", "legacy"] {
+                spawner.exit(name, 1);
+            }
+            manager.check_processes().await.unwrap();
+            clock.advance(Duration::from_secs(1));
+            manager.check_processes().await.unwrap();
+        }
+        assert_eq!(manager.get_process("api").unwrap().restart_count, 2);
+        assert_eq!(manager.get_process("legacy").unwrap().restart_count, 2);
+        
+        // 穩定運行不足 stable_after 時不清零
+        clock.advance(Duration::from_secs(300));
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("api").unwrap().restart_count, 2);
+        
+        // 穩定運行 10 分鐘後 api 的重啟次數清零，legacy 不變
+        clock.advance(Duration::from_secs(300));
+        manager.check_processes().await.unwrap();
+        assert_eq!(manager.get_process("api").unwrap().restart_count, 0);
+        assert!(manager.get_process("api").unwrap().recent_restarts.is_empty());
+        assert_eq!(manager.get_process("legacy").unwrap().restart_count, 2);
+        
+        // 今天再崩潰一次：api 照常重啟，legacy 已達 restart_limit
+        for name in ["api", "legacy"] {
+            spawner.exit(name, 1);
+        }
+        manager.check_processes().await.unwrap();
+        clock.advance(Duration::from_secs(1));
+        manager.check_processes().await.unwrap();
+        let api = manager.get_process("api").unwrap();
+        assert_eq!(api.status, ProcessStatus::Running);
+        assert_eq!(api.restart_count, 1);
+        assert_eq!(spawner.spawn_count("api"), 4);
+        assert_ne!(manager.get_process("legacy").unwrap().status, ProcessStatus::Running);
+        assert_eq!(spawner.spawn_count("legacy"), 3);
+        
+        manager.stop_all().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_readonly_grpc_endpoint() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        manager.lock().await.start_process("web").await.unwrap();
+        let server = GrpcServer::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .with_token(Some("viewer".to_string()))
+            .read_only();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        let janus = |args: &[&str]| {
+            tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+                .args(["--host", &host])
+                .args(args)
+                .env("JANUS_TOKEN", "viewer")
+                .current_dir(temp_dir.path())
+                .output()
+        };
+        
+        // 可以查看狀態和時間線
+        let output = janus(&["ps"]).await.unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.lines().any(|line| line.starts_with("web") && line.contains("Running")));
+        let output = janus(&["timeline", "web"]).await.unwrap();
+        assert!(output.status.success());
+        
+        // 改變進程的請求被拒絕，進程繼續運行
+        for args in [&["stop-one", "web"][..], &["kill", "web"], &["maintenance", "off", "web"]] {
+            let output = janus(args).await.unwrap();
+            assert!(!output.status.success(), "{:?} should be refused", args);
+            assert!(String::from_utf8_lossy(&output.stderr).contains("read-only"));
+        }
+        assert!(manager.lock().await.get_process("web").unwrap().process.is_some());
+        
+        manager.lock().await.stop_process("web").await.unwrap();
+        
+        // 只讀令牌需要只讀地址，只讀地址不能與管理接口相同
+        for global in [
+            r#"grpc_readonly_token = "viewer""#,
+            "grpc_listen = \"127.0.0.1:7777\"\ngrpc_readonly_listen = \"127.0.0.1:7777\"",
+        ] {
+            fs::write(&config_path, format!("[global]\n{}\n\n[[process]]\nname = \"web\"\ncommand = \"sleep\"\n", global)).unwrap();
+            assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_upgrade_requires_token_and_allowed_path() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::{GrpcServer, RemoteClient};
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("janus.toml");
+        fs::write(&config_path, r#"
+        [global]
+        upgrade_executables = ["/opt/janus/janus-1.4"]
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        "#).unwrap();
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        
+        // 沒有配置令牌時拒絕升級
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let mut client = RemoteClient::connect(&host, None).await.unwrap();
+        let error = client.upgrade(None).await.unwrap_err().to_string();
+        assert!(error.contains("grpc_token"), "{}", error);
+        
+        // 有令牌時只接受當前二進制文件和 upgrade_executables 中的路徑
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap().with_token(Some("secret".to_string()));
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        let mut client = RemoteClient::connect(&host, Some("secret")).await.unwrap();
+        let error = client.upgrade(Some("/bin/sh".to_string())).await.unwrap_err().to_string();
+        assert!(error.contains("upgrade_executables"), "{}", error);
+        // 列出的路徑通過檢查，但文件不存在
+        let error = client.upgrade(Some("/opt/janus/janus-1.4".to_string())).await.unwrap_err().to_string();
+        assert!(error.contains("Not an executable file"), "{}", error);
+        assert!(manager.lock().await.take_upgrade().is_none());
+        
+        // 列表中只能是絕對路徑
+        fs::write(&config_path, "[global]\nupgrade_executables = [\"janus\"]\n").unwrap();
+        assert!(ConfigManager::new(config_path.to_str().unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_oneshot_keep_status_completed_and_failed() {
+        use janus::config::manager::ConfigManager;
+        use janus::logging::handler::LogHandler;
+        use janus::process::kubernetes::Health;
+        use janus::process::manager::ProcessManager;
+        use janus::process::ProcessStatus;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("oneshot.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "migrate"
+        command = "true"
+        auto_restart = true
+        oneshot_keep_status = true
+        
+        [[process]]
+        name = "broken"
+        command = "sh"
+        args = ["-c", "exit 3"]
+        oneshot_keep_status = true
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let mut manager = ProcessManager::new(config_manager, LogHandler::new("info"));
+        manager.start_process("migrate").await.unwrap();
+        manager.start_process("broken").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        manager.check_processes().await.unwrap();
+        
+        // 退出碼 0：標記為 Completed，保留退出碼，即使 auto_restart 也不重啟
+        let migrate = manager.get_process("migrate").unwrap();
+        assert_eq!(migrate.status, ProcessStatus::Completed);
+        assert_eq!(migrate.exit_code, Some(0));
+        manager.check_processes().await.unwrap();
+        let migrate = manager.get_process("migrate").unwrap();
+        assert_eq!((migrate.status.clone(), migrate.pid(), migrate.restart_count), (ProcessStatus::Completed, None, 0));
+        
+        // 非零退出碼：仍然是 Failed
+        let broken = manager.get_process("broken").unwrap();
+        assert_eq!(broken.status, ProcessStatus::Failed);
+        assert_eq!(broken.exit_code, Some(3));
+        
+        // 已完成的進程不參與整體健康狀態的匯總，失敗的進程使整體不健康
+        let snapshot = manager.snapshot();
+        let completed = snapshot.processes.iter().find(|process| process.name == "migrate").unwrap();
+        assert_eq!((completed.status.as_str(), completed.exit_code), ("Completed", Some(0)));
+        let health = Health::from_snapshot(&snapshot);
+        assert_eq!(health.unhealthy, vec![("broken".to_string(), "Failed".to_string())]);
+    }
+    
+    #[test]
+    fn test_ps_compact_listing() {
+        use std::process::Command;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("ps.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "worker"
+        command = "sh"
+        args = ["-c", "while true; do echo processing the nightly batch; sleep 1; done"]
+        
+        [[process]]
+        name = "api"
+        command = "./server"
+        args = ["--port", "8080"]
+        "#).unwrap();
+        
+        let output = Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "ps"])
             .output()
             .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout.lines().collect();
         
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        assert!(output_str.contains("STOPPED"));
+        // 表頭之後每個進程一行，按名稱排序；沒有 pid 和退出碼時顯示 -
+        assert_eq!(lines.len(), 3, "{}", stdout);
+        assert_eq!(lines[0].split_whitespace().collect::<Vec<_>>(), ["NAME", "PID", "STATE", "EXIT", "COMMAND"]);
+        assert_eq!(lines[1].split_whitespace().collect::<Vec<_>>(), ["api", "-", "Stopped", "-", "./server", "--port", "8080"]);
+        assert!(lines[2].starts_with("worker "));
+        
+        // 過長的命令被截斷到固定寬度，各列對齊
+        let command = &lines[2][lines[0].find("COMMAND").unwrap()..];
+        assert!(command.starts_with("sh -c while true;"), "{}", command);
+        assert!(command.ends_with("..."), "{}", command);
+        assert_eq!(command.chars().count(), 40);
+        assert_eq!(lines[1].find("./server"), lines[0].find("COMMAND"));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sigusr_actions() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("sigusr.toml");
+        fs::write(&config_path, r#"
+        [global]
+        sigusr1_action = "dump-status"
+        sigusr2_action = { restart = ["web"] }
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["30"]
+        "#).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let pid = supervisor.id().unwrap() as i32;
+        let mut lines = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        let mut output: Vec<String> = Vec::new();
+        // 信號處理的提示和狀態報告直接打印，日誌行由寫入線程批量輸出，兩者之間沒有先後順序，
+        // 每一行分別等待，直到收到至少 count 行包含 text 的輸出
+        let mut read_until = async |text: &str, count: usize| {
+            let read = async {
+                while output.iter().filter(|line| line.contains(text)).count() < count {
+                    let line = lines.next_line().await.unwrap().expect("janus closed its output");
+                    output.push(line);
+                }
+            };
+            if tokio::time::timeout(std::time::Duration::from_secs(30), read).await.is_err() {
+                panic!("Timed out waiting for {:?}:\n{}", text, output.join("\n"));
+            }
+        };
+        read_until("All processes started", 1).await;
+        read_until("Process started", 1).await;
+        
+        // SIGUSR2 重啟配置中列出的進程
+        unsafe { libc::kill(pid, libc::SIGUSR2) };
+        read_until("Received SIGUSR2, restarting web...", 1).await;
+        read_until("Process stopped for restart", 1).await;
+        read_until("Process started", 2).await;
+        
+        // SIGUSR1 打印狀態報告
+        unsafe { libc::kill(pid, libc::SIGUSR1) };
+        read_until("Received SIGUSR1, dumping status...", 1).await;
+        read_until("Process Status Report:", 1).await;
+        read_until("Process: web", 1).await;
+        
+        unsafe { libc::kill(pid, libc::SIGTERM) };
+        assert!(supervisor.wait().await.unwrap().success());
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sigusr_dump_status_while_manager_busy() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("busy.toml");
+        fs::write(&config_path, r#"
+        [global]
+        sigusr1_action = "dump-status"
+        sigusr2_action = { restart = ["stubborn"] }
+        
+        [[process]]
+        name = "stubborn"
+        command = "sh"
+        args = ["-c", "trap '' TERM; while true; do sleep 0.1; done"]
+        stop_timeout = 5
+        "#).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let pid = supervisor.id().unwrap() as i32;
+        let mut lines = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        let mut output = Vec::new();
+        let mut read_until = async |text: &str| {
+            let read = async {
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    let found = line.contains(text);
+                    output.push(line);
+                    if found {
+                        return;
+                    }
+                }
+            };
+            tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap();
+            output.join("\n")
+        };
+        read_until("All processes started").await;
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        
+        // 重啟忽略 SIGTERM 的進程時管理器的鎖要持有到 stop_timeout，狀態報告不必等它
+        unsafe { libc::kill(pid, libc::SIGUSR2) };
+        read_until("Received SIGUSR2, restarting stubborn...").await;
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let started = std::time::Instant::now();
+        unsafe { libc::kill(pid, libc::SIGUSR1) };
+        read_until("Received SIGUSR1, dumping status...").await;
+        let output = read_until("Process: stubborn").await;
+        assert!(started.elapsed() < std::time::Duration::from_secs(3), "{}", output);
+        assert!(output.contains("Process Status Report:"), "{}", output);
+        assert_eq!(output.matches("Process started").count(), 1, "{}", output);
+        
+        unsafe { libc::kill(pid, libc::SIGTERM) };
+        assert!(supervisor.wait().await.unwrap().success());
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_signal_dump_file() {
+        use tokio::io::{AsyncBufReadExt, BufReader};
+        
+        let temp_dir = TempDir::new().unwrap();
+        let dump_dir = temp_dir.path().join("dumps");
+        fs::create_dir(&dump_dir).unwrap();
+        let config_path = temp_dir.path().join("dump.toml");
+        fs::write(&config_path, format!(r#"
+        [global]
+        sigusr1_action = "dump-file"
+        sigusr2_action = {{ restart = ["web"] }}
+        dump_dir = "{}"
+        
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["30"]
+        "#, dump_dir.display())).unwrap();
+        
+        let mut supervisor = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--config", config_path.to_str().unwrap(), "start"])
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let pid = supervisor.id().unwrap() as i32;
+        let mut lines = BufReader::new(supervisor.stdout.take().unwrap()).lines();
+        let mut read_until = async |text: &str| {
+            let read = async {
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    if line.contains(text) {
+                        return line;
+                    }
+                }
+                panic!("janus exited before printing '{}'", text);
+            };
+            tokio::time::timeout(std::time::Duration::from_secs(10), read).await.unwrap()
+        };
+        read_until("All processes started").await;
+        
+        // 先重啟一次，讓快照裡有運行歷史；快照在重啟完成後才發布
+        unsafe { libc::kill(pid, libc::SIGUSR2) };
+        read_until("Process stopped for restart").await;
+        read_until("Process started").await;
+        
+        unsafe { libc::kill(pid, libc::SIGUSR1) };
+        let line = read_until("status written to").await;
+        let path = std::path::PathBuf::from(line.split("status written to ").nth(1).unwrap().trim());
+        assert_eq!(path.parent().unwrap(), dump_dir);
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        assert!(file_name.starts_with("janus-status-") && file_name.ends_with(".json"), "{}", file_name);
+        
+        let snapshot: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(snapshot["config_path"], config_path.to_str().unwrap());
+        assert_eq!(snapshot["config_sha256"].as_str().unwrap().len(), 64);
+        let process = &snapshot["processes"][0];
+        assert_eq!(process["name"], "web");
+        assert_eq!(process["status"], "Running");
+        assert!(process["pid"].is_u64());
+        let runs = process["recent_runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0]["finished_at"].is_string());
+        
+        unsafe { libc::kill(pid, libc::SIGTERM) };
+        assert!(supervisor.wait().await.unwrap().success());
+    }
+    
+    #[test]
+    fn test_console_completion_and_shorthands() {
+        use janus::cli::console::{command_args, complete, split_words};
+        
+        let names = vec!["api".to_string(), "api-worker".to_string(), "web".to_string()];
+        // 第一個詞補全命令，之後補全進程名；唯一匹配時加上空格，多個匹配時補全到共同前綴
+        assert_eq!(complete("sta", &names), ("".to_string(), vec!["start".to_string(), "status".to_string()]));
+        assert_eq!(complete("time", &names).0, "line ");
+        assert_eq!(complete("start w", &names).0, "eb ");
+        assert_eq!(complete("logs a", &names).0, "pi");
+        assert_eq!(complete("logs x", &names), (String::new(), Vec::new()));
+        
+        assert_eq!(split_words(r#"logs api --match "connection refused""#), ["logs", "api", "--match", "connection refused"]);
+        assert_eq!(split_words("  stop  ''  "), ["stop", ""]);
+        assert_eq!(command_args(&split_words("start api")), ["start-one", "api"]);
+        assert_eq!(command_args(&split_words("restart --failed")), ["restart", "--failed"]);
+        assert_eq!(command_args(&split_words("logs")), ["tail", "--all"]);
+        assert_eq!(command_args(&split_words("logs api web")), ["tail", "api", "web"]);
+        assert_eq!(command_args(&split_words("timeline api")), ["timeline", "api"]);
+    }
+    
+    #[tokio::test]
+    async fn test_console_runs_commands_from_stdin() {
+        use janus::config::manager::ConfigManager;
+        use janus::grpc::GrpcServer;
+        use janus::logging::handler::LogHandler;
+        use janus::process::manager::ProcessManager;
+        use std::sync::Arc;
+        use tokio::io::AsyncWriteExt;
+        use tokio::sync::Mutex;
+        
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("console.toml");
+        fs::write(&config_path, r#"
+        [[process]]
+        name = "web"
+        command = "sleep"
+        args = ["10"]
+        "#).unwrap();
+        
+        let config_manager = ConfigManager::new(config_path.to_str().unwrap()).unwrap();
+        let manager = Arc::new(Mutex::new(ProcessManager::new(config_manager, LogHandler::new("info"))));
+        manager.lock().await.start_process("web").await.unwrap();
+        let server = GrpcServer::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("tcp://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run(manager.clone()));
+        
+        // 標準輸入不是終端時逐行執行命令，出錯的命令不會結束控制台，exit 之後的輸入被忽略
+        let mut console = tokio::process::Command::new(env!("CARGO_BIN_EXE_janus"))
+            .args(["--host", &host, "console"])
+            .current_dir(temp_dir.path())
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = console.stdin.take().unwrap();
+        stdin.write_all(b"stop web\n\nbogus\nps\nstatus --tree\nstart web\nexit\nstop web\n").await.unwrap();
+        drop(stdin);
+        let output = tokio::time::timeout(std::time::Duration::from_secs(10), console.wait_with_output())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stdout.contains("Process stopped: web"), "{}", stdout);
+        assert!(stdout.lines().any(|line| line.starts_with("web") && line.contains("Stopped")), "{}", stdout);
+        assert!(stdout.contains("Process started: web"), "{}", stdout);
+        assert!(stderr.contains("bogus"), "{}", stderr);
+        assert!(stderr.contains("Command error: status --tree is not available with --host"), "{}", stderr);
+        assert!(manager.lock().await.get_process("web").unwrap().process.is_some());
     }
 }